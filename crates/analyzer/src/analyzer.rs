@@ -1,98 +1,219 @@
 use crate::analyzer::resource_table::PathId;
 use crate::analyzer_error::AnalyzerError;
+use crate::attribute::{AllowItem, Attribute, MemoryItem};
 use crate::attribute_table;
+use crate::call_graph;
+use crate::evaluator::{Evaluated, Evaluator};
 use crate::handlers::*;
 use crate::msb_table;
 use crate::namespace::Namespace;
 use crate::namespace_table;
 use crate::symbol::{
-    Direction, DocComment, Symbol, SymbolId, SymbolKind, TypeKind, VariableAffiliation,
+    Direction, DocComment, DocTag, Symbol, SymbolId, SymbolKind, TypeKind, VariableAffiliation,
 };
 use crate::symbol_table;
 use crate::type_dag;
 use crate::var_ref::{
-    AssignPosition, AssignPositionTree, AssignPositionType, ExpressionTargetType,
-    VarRefAffiliation, VarRefPath, VarRefType,
+    Assign, AssignPositionTree, AssignPositionType, ExpressionTargetType, VarRefAffiliation,
+    VarRefPath, VarRefType,
 };
 use itertools::Itertools;
 use std::path::Path;
-use veryl_metadata::{Build, Lint, Metadata};
-use veryl_parser::resource_table;
+use veryl_metadata::{Build, CancellationToken, Lint, Metadata};
+use veryl_parser::resource_table::{self, StrId};
 use veryl_parser::veryl_grammar_trait::*;
 use veryl_parser::veryl_token::{Token, TokenSource};
 use veryl_parser::veryl_walker::{Handler, VerylWalker};
 
 pub struct AnalyzerPass1<'a> {
     handlers: Pass1Handlers<'a>,
+    cancellation: &'a CancellationToken,
 }
 
 impl<'a> AnalyzerPass1<'a> {
-    pub fn new(text: &'a str, build_opt: &'a Build, lint_opt: &'a Lint) -> Self {
+    pub fn new(
+        text: &'a str,
+        build_opt: &'a Build,
+        lint_opt: &'a Lint,
+        cancellation: &'a CancellationToken,
+    ) -> Self {
         AnalyzerPass1 {
             handlers: Pass1Handlers::new(text, build_opt, lint_opt),
+            cancellation,
         }
     }
 }
 
 impl<'a> VerylWalker for AnalyzerPass1<'a> {
     fn get_handlers(&mut self) -> Option<Vec<&mut dyn Handler>> {
-        Some(self.handlers.get_handlers())
+        // Skip all handlers for the rest of walk after cancellation
+        if self.cancellation.is_cancelled() {
+            None
+        } else {
+            Some(self.handlers.get_handlers())
+        }
     }
 }
 
 pub struct AnalyzerPass2<'a> {
     handlers: Pass2Handlers<'a>,
+    cancellation: &'a CancellationToken,
 }
 
 impl<'a> AnalyzerPass2<'a> {
-    pub fn new(text: &'a str, build_opt: &'a Build, lint_opt: &'a Lint) -> Self {
+    pub fn new(
+        text: &'a str,
+        build_opt: &'a Build,
+        lint_opt: &'a Lint,
+        cancellation: &'a CancellationToken,
+    ) -> Self {
         AnalyzerPass2 {
             handlers: Pass2Handlers::new(text, build_opt, lint_opt),
+            cancellation,
         }
     }
 }
 
 impl<'a> VerylWalker for AnalyzerPass2<'a> {
     fn get_handlers(&mut self) -> Option<Vec<&mut dyn Handler>> {
-        Some(self.handlers.get_handlers())
+        // Skip all handlers for the rest of walk after cancellation
+        if self.cancellation.is_cancelled() {
+            None
+        } else {
+            Some(self.handlers.get_handlers())
+        }
     }
 }
 pub struct AnalyzerPass3<'a> {
     path: PathId,
     text: &'a str,
-    symbols: Vec<Symbol>,
 }
 
 impl<'a> AnalyzerPass3<'a> {
     pub fn new(path: &'a Path, text: &'a str) -> Self {
-        let symbols = symbol_table::get_all();
         let path = resource_table::get_path_id(path.to_path_buf()).unwrap();
-        AnalyzerPass3 {
-            path,
-            text,
-            symbols,
-        }
+        AnalyzerPass3 { path, text }
     }
 
     pub fn check_variables(&self) -> Vec<AnalyzerError> {
         let mut ret = Vec::new();
 
-        for symbol in &self.symbols {
-            if symbol.token.source == self.path {
-                if let SymbolKind::Variable(_) = symbol.kind {
-                    if symbol.references.is_empty() && !symbol.allow_unused {
-                        let name = symbol.token.to_string();
-                        if !name.starts_with('_') {
-                            ret.push(AnalyzerError::unused_variable(
-                                &symbol.token.to_string(),
+        symbol_table::for_each_in(self.path, |symbol| {
+            if let SymbolKind::Variable(_) = symbol.kind {
+                if symbol.references.is_empty() && !symbol.allow_unused {
+                    let name = symbol.token.to_string();
+                    if !name.starts_with('_') {
+                        ret.push(AnalyzerError::unused_variable(
+                            &symbol.token.to_string(),
+                            self.text,
+                            &symbol.token.into(),
+                        ));
+                    }
+                }
+            }
+        });
+
+        ret
+    }
+
+    pub fn check_functions(&self) -> Vec<AnalyzerError> {
+        let mut ret = Vec::new();
+
+        symbol_table::for_each_in(self.path, |symbol| {
+            if let SymbolKind::Function(ref x) = symbol.kind {
+                let name = symbol.token.to_string();
+                if call_graph::is_recursive(symbol.id) {
+                    ret.push(AnalyzerError::recursive_function(
+                        &name,
+                        self.text,
+                        &symbol.token.into(),
+                    ));
+                }
+
+                // functions in interface and package may be used by other projects
+                if symbol.references.is_empty()
+                    && !symbol.allow_unused
+                    && !name.starts_with('_')
+                    && is_module_item(symbol)
+                {
+                    ret.push(AnalyzerError::unused_function(
+                        &name,
+                        self.text,
+                        &symbol.token.into(),
+                    ));
+                }
+
+                for port in &x.ports {
+                    if let Some(port_symbol) = symbol_table::get(port.symbol) {
+                        let unused_input = matches!(
+                            port_symbol.kind,
+                            SymbolKind::Port(ref x) if x.direction == Direction::Input
+                        ) && port_symbol.references.is_empty();
+                        let port_name = port_symbol.token.to_string();
+                        if unused_input && !port_name.starts_with('_') {
+                            ret.push(AnalyzerError::unused_function_input(
+                                &port_name,
+                                &name,
                                 self.text,
-                                &symbol.token.into(),
+                                &port_symbol.token.into(),
                             ));
                         }
                     }
                 }
             }
-        }
+        });
+
+        ret
+    }
+
+    pub fn check_doc_comments(&self) -> Vec<AnalyzerError> {
+        let mut ret = Vec::new();
+
+        symbol_table::for_each_in(self.path, |symbol| {
+            if symbol.doc_comment.is_empty() {
+                return;
+            }
+
+            let (params, ports): (Vec<_>, Vec<_>) = match &symbol.kind {
+                SymbolKind::Module(x) => (
+                    generic_names(&x.generic_parameters)
+                        .chain(x.parameters.iter().map(|x| x.name))
+                        .collect(),
+                    x.ports.iter().map(|x| x.name).collect(),
+                ),
+                SymbolKind::ProtoModule(x) => (
+                    x.parameters.iter().map(|x| x.name).collect(),
+                    x.ports.iter().map(|x| x.name).collect(),
+                ),
+                SymbolKind::Interface(x) => (
+                    generic_names(&x.generic_parameters)
+                        .chain(x.parameters.iter().map(|x| x.name))
+                        .collect(),
+                    vec![],
+                ),
+                SymbolKind::Function(x) => (
+                    generic_names(&x.generic_parameters).collect(),
+                    x.ports.iter().map(|x| x.name).collect(),
+                ),
+                _ => return,
+            };
+
+            for tag in symbol.doc_comment.tags() {
+                let (kind, name, defined) = match &tag {
+                    DocTag::Param { name, .. } => ("parameter", name, &params),
+                    DocTag::Port { name, .. } => ("port", name, &ports),
+                    DocTag::Example(_) => continue,
+                };
+                if !defined.iter().any(|x| x.to_string() == *name) {
+                    ret.push(AnalyzerError::unknown_doc_target(
+                        kind,
+                        name,
+                        self.text,
+                        &symbol.token.into(),
+                    ));
+                }
+            }
+        });
 
         ret
     }
@@ -103,19 +224,23 @@ impl<'a> AnalyzerPass3<'a> {
         let assign_list = symbol_table::get_assign_list();
         let mut assignable_list = Vec::new();
 
-        for symbol in &self.symbols {
-            if symbol.token.source == self.path {
-                assignable_list.append(&mut traverse_assignable_symbol(
-                    symbol.id,
-                    &VarRefPath::new((&symbol.id).into()),
-                ));
-            }
-        }
+        symbol_table::for_each_in(self.path, |symbol| {
+            assignable_list.append(&mut traverse_assignable_symbol(
+                symbol.id,
+                &VarRefPath::new((&symbol.id).into()),
+            ));
+        });
         let mut assignable_list: Vec<_> = assignable_list.iter().map(|x| (x, vec![])).collect();
+        let mut missing_resets = Vec::new();
         for assign in &assign_list {
             for assignable in &mut assignable_list {
                 if assignable.0.included(&assign.path) {
-                    assignable.1.push((assign.position.clone(), assign.partial));
+                    let mut assign = assign.clone();
+                    // the selected range is meaningful only if it selects the assignable itself
+                    if assignable.0.full_path().len() != assign.path.full_path().len() {
+                        assign.range = None;
+                    }
+                    assignable.1.push(assign);
                 }
             }
         }
@@ -124,7 +249,10 @@ impl<'a> AnalyzerPass3<'a> {
             if positions.is_empty() {
                 let full_path = path.full_path();
                 let symbol = symbol_table::get(*full_path.first().unwrap()).unwrap();
-                if must_be_assigned(&symbol.kind) {
+                let synchronized = attribute_table::get(&symbol.token)
+                    .iter()
+                    .any(|x| matches!(x, Attribute::Sync(..)));
+                if must_be_assigned(&symbol.kind) && !synchronized {
                     let path: Vec<_> = full_path
                         .iter()
                         .map(|x| symbol_table::get(*x).unwrap().token.to_string())
@@ -140,6 +268,21 @@ impl<'a> AnalyzerPass3<'a> {
             let full_path = path.full_path();
             let symbol = symbol_table::get(*full_path.first().unwrap()).unwrap();
 
+            // variables with these attributes are assigned by the inserted registers
+            let inserted = attribute_table::get(&symbol.token)
+                .iter()
+                .any(|x| matches!(x, Attribute::Pipeline(_) | Attribute::Sync(..)));
+            if must_be_assigned(&symbol.kind) && !inserted {
+                if let Some(bits) = unassigned_bits(path, positions) {
+                    ret.push(AnalyzerError::unassign_bits(
+                        &path.to_string(),
+                        &bits,
+                        self.text,
+                        &symbol.token.into(),
+                    ));
+                }
+            }
+
             if positions.len() > 1 {
                 for comb in positions.iter().combinations(2) {
                     ret.append(&mut check_multiple_assignment(
@@ -157,32 +300,70 @@ impl<'a> AnalyzerPass3<'a> {
             };
             if non_state_variable {
                 ret.append(&mut check_assign_position_tree(
-                    &symbol, self.text, positions,
+                    &symbol,
+                    self.text,
+                    positions,
+                    path,
+                    &mut missing_resets,
                 ));
             }
         }
 
+        // signals which are not reset are reported at once for each if_reset statement
+        for (reset, mut names) in missing_resets {
+            names.sort_by_key(|(token, _)| (token.line, token.column));
+            let token = names[0].0;
+            let names: Vec<_> = names.into_iter().map(|(_, name)| name).collect();
+            ret.push(AnalyzerError::missing_reset_statement(
+                &names.join(", "),
+                self.text,
+                &token.into(),
+                &reset.into(),
+            ));
+        }
+
         ret
     }
 
-    pub fn check_unassigned(&self) -> Vec<AnalyzerError> {
+    pub fn check_read_before_write(&self) -> Vec<AnalyzerError> {
         let mut ret = Vec::new();
 
         for (key, list) in symbol_table::get_var_ref_list() {
-            if matches!(key, VarRefAffiliation::AlwaysComb { .. }) {
+            if let VarRefAffiliation::AlwaysComb { token } = key {
+                if token.source != self.path
+                    || attribute_table::contains(
+                        &token,
+                        Attribute::Allow(AllowItem::ReadBeforeWrite),
+                    )
+                {
+                    continue;
+                }
+
                 let list: Vec<_> = list.iter().enumerate().collect();
                 let assign_list: Vec<_> = list.iter().filter(|(_i, x)| x.is_assign()).collect();
                 for (i, var_ref) in &list {
                     if let VarRefType::ExpressionTarget { r#type } = var_ref.r#type {
-                        if matches!(
+                        if !matches!(
                             r#type,
                             ExpressionTargetType::Variable | ExpressionTargetType::OutputPort
-                        ) && assign_list.iter().any(|(index, assign)| {
-                            index > i && assign.path.may_fully_included(&var_ref.path)
-                        }) {
+                        ) {
+                            continue;
+                        }
+
+                        // the value assigned by the preceding statement is read
+                        let assigned_before = assign_list.iter().any(|(index, assign)| {
+                            index < i && var_ref.path.may_fully_included(&assign.path)
+                        });
+                        let assigned_after = assign_list.iter().any(|(index, assign)| {
+                            index > i
+                                && (assign.path.may_fully_included(&var_ref.path)
+                                    || var_ref.path.may_fully_included(&assign.path))
+                        });
+
+                        if !assigned_before && assigned_after {
                             let full_path = var_ref.path.full_path();
                             let symbol = symbol_table::get(*full_path.first().unwrap()).unwrap();
-                            ret.push(AnalyzerError::unassign_variable(
+                            ret.push(AnalyzerError::read_before_write(
                                 &var_ref.path.to_string(),
                                 self.text,
                                 &symbol.token.into(),
@@ -195,11 +376,77 @@ impl<'a> AnalyzerPass3<'a> {
 
         ret
     }
+
+    pub fn check_memory(&self) -> Vec<AnalyzerError> {
+        let mut ret = Vec::new();
+        let var_ref_list = symbol_table::get_var_ref_list();
+
+        symbol_table::for_each_in(self.path, |symbol| {
+            let SymbolKind::Variable(ref x) = symbol.kind else {
+                return;
+            };
+            let Some((kind, latency)) =
+                attribute_table::get(&symbol.token)
+                    .into_iter()
+                    .find_map(|x| match x {
+                        Attribute::Memory(x, y) => Some((x, y)),
+                        _ => None,
+                    })
+            else {
+                return;
+            };
+
+            let mut reasons = Vec::new();
+            if x.r#type.array.is_empty() {
+                reasons.push("memory should be declared as unpacked array".to_string());
+            }
+            if latency > 2 {
+                reasons.push("read latency should be 0, 1 or 2".to_string());
+            } else if latency == 0 && kind == MemoryItem::TrueDualPort {
+                reasons.push("true_dual_port memory can't be read asynchronously".to_string());
+            }
+
+            let mut write_ports = 0;
+            let mut written_outside = false;
+            for (key, list) in &var_ref_list {
+                let written = list
+                    .iter()
+                    .any(|x| x.is_assign() && x.path.full_path().first() == Some(&symbol.id));
+                if written {
+                    match key {
+                        VarRefAffiliation::AlwaysFF { .. } => write_ports += 1,
+                        _ => written_outside = true,
+                    }
+                }
+            }
+            if written_outside {
+                reasons.push("memory should be written in always_ff".to_string());
+            }
+            if write_ports > kind.write_ports() {
+                reasons.push(format!(
+                    "{kind} memory can be written by {} always_ff, but it is written by {write_ports}",
+                    kind.write_ports()
+                ));
+            }
+
+            for reason in reasons {
+                ret.push(AnalyzerError::invalid_memory(
+                    &symbol.token.to_string(),
+                    &reason,
+                    self.text,
+                    &symbol.token.into(),
+                ));
+            }
+        });
+
+        ret
+    }
 }
 
 pub struct Analyzer {
     build_opt: Build,
     lint_opt: Lint,
+    cancellation: CancellationToken,
 }
 
 fn new_namespace(name: &str) -> (Token, Symbol) {
@@ -237,6 +484,7 @@ impl Analyzer {
         Analyzer {
             build_opt: metadata.build.clone(),
             lint_opt: metadata.lint.clone(),
+            cancellation: metadata.cancellation.clone(),
         }
     }
 
@@ -250,8 +498,12 @@ impl Analyzer {
         let mut ret = Vec::new();
 
         namespace_table::set_default(&[project_name.into()]);
-        let mut pass1 = AnalyzerPass1::new(text, &self.build_opt, &self.lint_opt);
+        let mut pass1 =
+            AnalyzerPass1::new(text, &self.build_opt, &self.lint_opt, &self.cancellation);
         pass1.veryl(input);
+        if self.cancellation.is_cancelled() {
+            return vec![AnalyzerError::Cancelled];
+        }
         ret.append(&mut pass1.handlers.get_errors());
 
         ret
@@ -271,8 +523,12 @@ impl Analyzer {
         let mut ret = Vec::new();
 
         namespace_table::set_default(&[project_name.into()]);
-        let mut pass2 = AnalyzerPass2::new(text, &self.build_opt, &self.lint_opt);
+        let mut pass2 =
+            AnalyzerPass2::new(text, &self.build_opt, &self.lint_opt, &self.cancellation);
         pass2.veryl(input);
+        if self.cancellation.is_cancelled() {
+            return vec![AnalyzerError::Cancelled];
+        }
         ret.append(&mut pass2.handlers.get_errors());
 
         ret
@@ -289,15 +545,27 @@ impl Analyzer {
 
         namespace_table::set_default(&[project_name.into()]);
         let pass3 = AnalyzerPass3::new(path.as_ref(), text);
-        ret.append(&mut pass3.check_variables());
-        ret.append(&mut pass3.check_assignment());
-        ret.append(&mut pass3.check_unassigned());
+        let checks = [
+            AnalyzerPass3::check_variables,
+            AnalyzerPass3::check_functions,
+            AnalyzerPass3::check_doc_comments,
+            AnalyzerPass3::check_assignment,
+            AnalyzerPass3::check_read_before_write,
+            AnalyzerPass3::check_memory,
+        ];
+        for check in checks {
+            if self.cancellation.is_cancelled() {
+                return vec![AnalyzerError::Cancelled];
+            }
+            ret.append(&mut check(&pass3));
+        }
 
         ret
     }
 
     pub fn clear(&self) {
         attribute_table::clear();
+        call_graph::clear();
         msb_table::clear();
         namespace_table::clear();
         symbol_table::clear();
@@ -305,6 +573,21 @@ impl Analyzer {
     }
 }
 
+fn generic_names(ids: &[SymbolId]) -> impl Iterator<Item = StrId> + '_ {
+    ids.iter()
+        .filter_map(|x| symbol_table::get(*x).map(|x| x.token.text))
+}
+
+fn is_module_item(symbol: &Symbol) -> bool {
+    let mut namespace = symbol.namespace.clone();
+    if let Some(parent) = namespace.paths.pop() {
+        if let Ok(parent) = symbol_table::resolve((&vec![parent], &namespace)) {
+            return matches!(parent.found.kind, SymbolKind::Module(_));
+        }
+    }
+    false
+}
+
 fn is_assignable(direction: &Direction) -> bool {
     matches!(
         direction,
@@ -457,16 +740,81 @@ fn traverse_assignable_symbol(id: SymbolId, path: &VarRefPath) -> Vec<VarRefPath
     vec![]
 }
 
+fn assignable_width(id: SymbolId) -> Option<isize> {
+    let symbol = symbol_table::get(id)?;
+    let r#type = match symbol.kind {
+        SymbolKind::Port(x) => x.r#type?,
+        SymbolKind::Variable(x) => x.r#type,
+        SymbolKind::StructMember(x) => x.r#type,
+        _ => return None,
+    };
+
+    let mut evaluator = Evaluator::new();
+    match r#type.array.as_slice() {
+        [] => evaluator.type_width(r#type).map(|x| x as isize),
+        [x] => match evaluator.expression(x) {
+            Evaluated::Fixed { value, .. } => Some(value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn unassigned_bits(path: &VarRefPath, assigns: &[Assign]) -> Option<String> {
+    if assigns.is_empty() || assigns.iter().any(|x| !x.partial) {
+        return None;
+    }
+
+    let width = assignable_width(*path.full_path().last()?)?;
+    let mut covered = vec![false; width.max(0) as usize];
+    for assign in assigns {
+        let range = assign.range.clone()?;
+        for i in range {
+            if let Some(x) = covered.get_mut(i as usize) {
+                *x = true;
+            }
+        }
+    }
+
+    let mut ranges = Vec::new();
+    let mut i = covered.len();
+    while i > 0 {
+        i -= 1;
+        if !covered[i] {
+            let msb = i;
+            while i > 0 && !covered[i - 1] {
+                i -= 1;
+            }
+            if msb == i {
+                ranges.push(format!("[{}]", msb));
+            } else {
+                ranges.push(format!("[{}:{}]", msb, i));
+            }
+        }
+    }
+
+    if ranges.is_empty() {
+        None
+    } else {
+        Some(ranges.join(", "))
+    }
+}
+
 fn check_multiple_assignment(
     symbol: &Symbol,
     text: &str,
-    x: &(AssignPosition, bool),
-    y: &(AssignPosition, bool),
+    x: &Assign,
+    y: &Assign,
 ) -> Vec<AnalyzerError> {
-    let x_pos = &x.0;
-    let y_pos = &y.0;
-    let x_partial = &x.1;
-    let y_partial = &y.1;
+    let x_pos = &x.position;
+    let y_pos = &y.position;
+    // partial assignments conflict only if their ranges overlap
+    let overlapped = match (&x.range, &y.range) {
+        (Some(x), Some(y)) => x.start() <= y.end() && y.start() <= x.end(),
+        _ => false,
+    };
+    let x_partial = x.partial && !overlapped;
+    let y_partial = y.partial && !overlapped;
     let mut ret = Vec::new();
     let len = x_pos.0.len().min(y_pos.0.len());
 
@@ -504,33 +852,42 @@ fn check_multiple_assignment(
 fn check_assign_position_tree(
     symbol: &Symbol,
     text: &str,
-    positions: &[(AssignPosition, bool)],
+    positions: &[Assign],
+    path: &VarRefPath,
+    missing_resets: &mut Vec<(Token, Vec<(Token, String)>)>,
 ) -> Vec<AnalyzerError> {
     let mut ret = Vec::new();
 
     let mut tree = AssignPositionTree::default();
     for x in positions {
-        let pos = &x.0;
-
-        tree.add(pos.clone());
+        tree.add(x.position.clone());
     }
 
-    if let Some(token) = tree.check_always_comb_uncovered() {
+    if let Some(uncovered) = tree.check_always_comb_uncovered() {
+        let assigned: Vec<_> = uncovered.assigned.iter().map(|x| (*x).into()).collect();
+        let missing: Vec<_> = uncovered.missing.iter().map(|x| (*x).into()).collect();
         ret.push(AnalyzerError::uncovered_branch(
             &symbol.token.to_string(),
             text,
             &symbol.token.into(),
-            &token.into(),
+            &uncovered.token.into(),
+            &assigned,
+            &missing,
         ));
     }
 
     if let Some(token) = tree.check_always_ff_missing_reset() {
-        ret.push(AnalyzerError::missing_reset_statement(
-            &symbol.token.to_string(),
-            text,
-            &symbol.token.into(),
-            &token.into(),
-        ));
+        let index = if let Some(index) = missing_resets.iter().position(|x| x.0 == token) {
+            index
+        } else {
+            missing_resets.push((token, Vec::new()));
+            missing_resets.len() - 1
+        };
+        let names = &mut missing_resets[index].1;
+        let name = path.to_string();
+        if !names.iter().any(|(_, x)| *x == name) {
+            names.push((symbol.token, name));
+        }
     }
 
     ret
@@ -1,5 +1,7 @@
-use miette::{self, Diagnostic, NamedSource, SourceSpan};
+use miette::{self, Diagnostic, LabeledSpan, NamedSource, Severity, SourceCode, SourceSpan};
+use std::fmt;
 use thiserror::Error;
+use veryl_metadata::{Lint, LintSeverity};
 use veryl_parser::veryl_token::TokenRange;
 
 #[derive(Error, Diagnostic, Debug)]
@@ -75,7 +77,9 @@ pub enum AnalyzerError {
     #[diagnostic(
         severity(Error),
         code(invalid_allow),
-        help(""),
+        help(
+            "available items are missing_port, missing_reset_statement, unused_variable, unused_function, side_effect, read_before_write and hierarchical_reference"
+        ),
         url("https://doc.veryl-lang.org/book/07_appendix/02_semantic_error.html#invalid_allow")
     )]
     #[error("{identifier} can't be allowed")]
@@ -279,6 +283,21 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Error),
+        code(invalid_modport_item),
+        help("declare it in the interface")
+    )]
+    #[error("#{identifier} is not declared in interface \"{interface}\"")]
+    InvalidModportItem {
+        identifier: String,
+        interface: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Error),
         code(invalid_modport_function_item),
@@ -323,6 +342,20 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Error),
+        code(invalid_param_non_elaborative),
+        help("use constant expression")
+    )]
+    #[error("Override value of param \"{param}\" cannot be used because it is not evaluable at elaboration time")]
+    InvalidParamNonElaborative {
+        param: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Error),
         code(invalid_case_condition_non_elaborative),
@@ -496,10 +529,24 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Warning),
+        code(missing_return),
+        help("add return statement to all paths")
+    )]
+    #[error("function {identifier} doesn't return value on some paths")]
+    MissingReturn {
+        identifier: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Warning),
         code(missing_port),
-        help("add \"{port}\" port"),
+        help("connect \"{port}\" port, or connect it to \"_\" to leave it open"),
         url("https://doc.veryl-lang.org/book/07_appendix/02_semantic_error.html#missing_port")
     )]
     #[error("module \"{name}\" has \"{port}\", but it is not connected")]
@@ -512,6 +559,20 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Error),
+        code(invalid_sim_only_instance),
+        help("add #[sim_only] to the instance or the enclosing module")
+    )]
+    #[error("module \"{name}\" is simulation only, but it is instantiated in synthesizable code")]
+    InvalidSimOnlyInstance {
+        name: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Error),
         code(missing_clock_signal),
@@ -546,7 +607,7 @@ pub enum AnalyzerError {
         help("add reset statement"),
         url("https://doc.veryl-lang.org/book/07_appendix/02_semantic_error.html#missing_reset_statement")
     )]
-    #[error("{name} is not reset in if_reset statement")]
+    #[error("if_reset statement doesn't reset {name}")]
     MissingResetStatement {
         name: String,
         #[source_code]
@@ -624,6 +685,86 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(severity(Error), code(invalid_memory_kind), help(""), url(""))]
+    #[error("{identifier} is not valid memory kind")]
+    InvalidMemoryKind {
+        identifier: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(severity(Error), code(invalid_memory), help(""))]
+    #[error("memory {identifier} is invalid: {reason}")]
+    InvalidMemory {
+        identifier: String,
+        reason: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(severity(Error), code(invalid_pipeline), help(""))]
+    #[error("pipeline {identifier} is invalid: {reason}")]
+    InvalidPipeline {
+        identifier: String,
+        reason: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(severity(Error), code(invalid_parity), help(""))]
+    #[error("parity register {identifier} is invalid: {reason}")]
+    InvalidParity {
+        identifier: String,
+        reason: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(severity(Error), code(invalid_flatten), help(""))]
+    #[error("flattened port {identifier} is invalid: {reason}")]
+    InvalidFlatten {
+        identifier: String,
+        reason: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(
+        severity(Error),
+        code(invalid_auto_connect),
+        help("connect the port explicitly")
+    )]
+    #[error("port {identifier} can't be auto-connected: {reason}")]
+    InvalidAutoConnect {
+        identifier: String,
+        reason: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(severity(Error), code(invalid_protocol), help(""))]
+    #[error("protocol checker {identifier} is invalid: {reason}")]
+    InvalidProtocol {
+        identifier: String,
+        reason: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Error),
         code(too_large_enum_variant),
@@ -667,6 +808,22 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Error),
+        code(duplicated_enum_variant_value),
+        help("change the value or remove it to be assigned by encoding")
+    )]
+    #[error("the value {value} of enum variant {identifier} is the same as {other}")]
+    DuplicatedEnumVariantValue {
+        identifier: String,
+        value: usize,
+        other: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Error),
         code(too_large_number),
@@ -801,6 +958,40 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Error),
+        code(missing_modport),
+        help("connect an instance of the interface which has the modport")
+    )]
+    #[error("\"{interface}\" doesn't have modport \"{modport}\" required by port \"{port}\"")]
+    MissingModport {
+        interface: String,
+        modport: String,
+        port: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(
+        severity(Error),
+        code(mismatch_modport_direction),
+        help("connect a modport which can drive the member")
+    )]
+    #[error(
+        "#{member} is driven through port \"{port}\" but it is input in modport \"{modport}\""
+    )]
+    MismatchModportDirection {
+        member: String,
+        port: String,
+        modport: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Error),
         code(unknown_member),
@@ -817,6 +1008,22 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Error),
+        code(out_of_range_index),
+        help("index should be less than the array size")
+    )]
+    #[error("index {index} is out of range of \"{name}\" which has {size} elements")]
+    OutOfRangeIndex {
+        name: String,
+        index: isize,
+        size: isize,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Error),
         code(unknown_unsafe),
@@ -893,6 +1100,35 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Error),
+        code(recursive_function),
+        help("replace recursive call with loop because recursive function is not synthesizable")
+    )]
+    #[error("function {identifier} is called recursively")]
+    RecursiveFunction {
+        identifier: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(
+        severity(Error),
+        code(function_side_effect),
+        help("pass the signal through output or ref port, or add #[allow(side_effect)] to the function")
+    )]
+    #[error("function {function} assigns {identifier} declared outside of it")]
+    FunctionSideEffect {
+        identifier: String,
+        function: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Warning),
         code(unused_variable),
@@ -908,6 +1144,35 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Warning),
+        code(unused_function),
+        help("add prefix `_` to unused function name")
+    )]
+    #[error("function {identifier} is unused")]
+    UnusedFunction {
+        identifier: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(
+        severity(Warning),
+        code(unused_function_input),
+        help("add prefix `_` to unused input name")
+    )]
+    #[error("input {identifier} of function {function} is never read")]
+    UnusedFunctionInput {
+        identifier: String,
+        function: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Warning),
         code(unused_return),
@@ -923,6 +1188,92 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Warning),
+        code(unknown_doc_target),
+        help("fix the name or remove the tag")
+    )]
+    #[error("{kind} {identifier} documented by doc comment is not found")]
+    UnknownDocTarget {
+        kind: String,
+        identifier: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(
+        severity(Warning),
+        code(read_before_write),
+        help("move the assignment before the reference, or add #[allow(read_before_write)] to always_comb")
+    )]
+    #[error("{identifier} is read before it is assigned in always_comb")]
+    ReadBeforeWrite {
+        identifier: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(
+        severity(Warning),
+        code(dft_generated_clock),
+        help("drive the clock from a port, or bypass it by a test clock in scan mode")
+    )]
+    #[error("clock {identifier} is generated by logic in the module, which is not controllable in scan mode")]
+    DftGeneratedClock {
+        identifier: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(
+        severity(Warning),
+        code(dft_generated_reset),
+        help("drive the reset from a port, or bypass it by a test reset in scan mode")
+    )]
+    #[error("reset {identifier} is derived from logic in the module, which is not controllable in scan mode")]
+    DftGeneratedReset {
+        identifier: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(
+        severity(Error),
+        code(unsafe_fsm_encoding),
+        help("add #[enum_encoding(onehot)] or #[enum_encoding(gray)]")
+    )]
+    #[error("FSM {identifier} should be encoded by one-hot or gray")]
+    UnsafeFsmEncoding {
+        identifier: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
+    #[diagnostic(
+        severity(Error),
+        code(hierarchical_reference),
+        help("connect the signal through ports, or add #[allow(hierarchical_reference)] to testbench")
+    )]
+    #[error("{identifier} is a hierarchical reference into instance \"{instance}\" which may not be synthesizable")]
+    HierarchicalReference {
+        identifier: String,
+        instance: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Warning),
         code(unassign_variable),
@@ -940,6 +1291,21 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
     },
 
+    #[diagnostic(
+        severity(Warning),
+        code(unassign_bits),
+        help("assign the remaining bits")
+    )]
+    #[error("{bits} of {identifier} is unassigned")]
+    UnassignBits {
+        identifier: String,
+        bits: String,
+        #[source_code]
+        input: NamedSource<String>,
+        #[label("Error location")]
+        error_location: SourceSpan,
+    },
+
     #[diagnostic(
         severity(Warning),
         code(uncovered_branch),
@@ -955,6 +1321,8 @@ pub enum AnalyzerError {
         error_location: SourceSpan,
         #[label("Uncovered")]
         uncovered: SourceSpan,
+        #[label(collection)]
+        branches: Vec<LabeledSpan>,
     },
 
     #[diagnostic(
@@ -989,6 +1357,10 @@ pub enum AnalyzerError {
         #[label("Error location")]
         error_location: SourceSpan,
     },
+
+    #[diagnostic(severity(Error), code(cancelled), help(""), url(""))]
+    #[error("analysis is cancelled")]
+    Cancelled,
 }
 
 impl AnalyzerError {
@@ -1181,6 +1553,14 @@ impl AnalyzerError {
         }
     }
 
+    pub fn invalid_param_non_elaborative(param: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::InvalidParamNonElaborative {
+            param: param.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn invalid_case_condition_non_elaborative(source: &str, token: &TokenRange) -> Self {
         AnalyzerError::InvalidCaseConditionNonElaborative {
             input: AnalyzerError::named_source(source, token),
@@ -1217,6 +1597,20 @@ impl AnalyzerError {
         }
     }
 
+    pub fn invalid_modport_item(
+        identifier: &str,
+        interface: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::InvalidModportItem {
+            identifier: identifier.into(),
+            interface: interface.into(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn invalid_modport_function_item(
         identifier: &str,
         source: &str,
@@ -1380,6 +1774,14 @@ impl AnalyzerError {
         }
     }
 
+    pub fn missing_return(identifier: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::MissingReturn {
+            identifier: identifier.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn missing_port(name: &str, port: &str, source: &str, token: &TokenRange) -> Self {
         AnalyzerError::MissingPort {
             name: name.to_string(),
@@ -1389,6 +1791,14 @@ impl AnalyzerError {
         }
     }
 
+    pub fn invalid_sim_only_instance(name: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::InvalidSimOnlyInstance {
+            name: name.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn sv_keyword_usage(identifier: &str, source: &str, token: &TokenRange) -> Self {
         AnalyzerError::SvKeywordUsage {
             identifier: identifier.to_string(),
@@ -1412,6 +1822,98 @@ impl AnalyzerError {
         }
     }
 
+    pub fn invalid_memory_kind(identifier: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::InvalidMemoryKind {
+            identifier: identifier.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn invalid_memory(
+        identifier: &str,
+        reason: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::InvalidMemory {
+            identifier: identifier.to_string(),
+            reason: reason.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn invalid_pipeline(
+        identifier: &str,
+        reason: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::InvalidPipeline {
+            identifier: identifier.to_string(),
+            reason: reason.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn invalid_parity(
+        identifier: &str,
+        reason: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::InvalidParity {
+            identifier: identifier.to_string(),
+            reason: reason.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn invalid_flatten(
+        identifier: &str,
+        reason: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::InvalidFlatten {
+            identifier: identifier.to_string(),
+            reason: reason.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn invalid_auto_connect(
+        identifier: &str,
+        reason: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::InvalidAutoConnect {
+            identifier: identifier.to_string(),
+            reason: reason.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn invalid_protocol(
+        identifier: &str,
+        reason: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::InvalidProtocol {
+            identifier: identifier.to_string(),
+            reason: reason.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn too_large_enum_variant(
         identifier: &str,
         value: isize,
@@ -1458,6 +1960,22 @@ impl AnalyzerError {
         }
     }
 
+    pub fn duplicated_enum_variant_value(
+        identifier: &str,
+        value: usize,
+        other: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::DuplicatedEnumVariantValue {
+            identifier: identifier.to_string(),
+            value,
+            other: other.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn too_much_enum_variant(
         identifier: &str,
         number: usize,
@@ -1528,6 +2046,38 @@ impl AnalyzerError {
         }
     }
 
+    pub fn missing_modport(
+        interface: &str,
+        modport: &str,
+        port: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::MissingModport {
+            interface: interface.into(),
+            modport: modport.into(),
+            port: port.into(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn mismatch_modport_direction(
+        member: &str,
+        port: &str,
+        modport: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::MismatchModportDirection {
+            member: member.into(),
+            port: port.into(),
+            modport: modport.into(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn unknown_member(name: &str, member: &str, source: &str, token: &TokenRange) -> Self {
         AnalyzerError::UnknownMember {
             name: name.to_string(),
@@ -1537,6 +2087,22 @@ impl AnalyzerError {
         }
     }
 
+    pub fn out_of_range_index(
+        name: &str,
+        index: isize,
+        size: isize,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::OutOfRangeIndex {
+            name: name.to_string(),
+            index,
+            size,
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn unknown_unsafe(name: &str, source: &str, token: &TokenRange) -> Self {
         AnalyzerError::UnknownUnsafe {
             name: name.to_string(),
@@ -1578,6 +2144,28 @@ impl AnalyzerError {
         }
     }
 
+    pub fn recursive_function(identifier: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::RecursiveFunction {
+            identifier: identifier.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn function_side_effect(
+        identifier: &str,
+        function: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::FunctionSideEffect {
+            identifier: identifier.to_string(),
+            function: function.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn unused_variable(identifier: &str, source: &str, token: &TokenRange) -> Self {
         AnalyzerError::UnusedVariable {
             identifier: identifier.to_string(),
@@ -1586,6 +2174,28 @@ impl AnalyzerError {
         }
     }
 
+    pub fn unused_function(identifier: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::UnusedFunction {
+            identifier: identifier.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn unused_function_input(
+        identifier: &str,
+        function: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::UnusedFunctionInput {
+            identifier: identifier.to_string(),
+            function: function.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn unused_return(identifier: &str, source: &str, token: &TokenRange) -> Self {
         AnalyzerError::UnusedReturn {
             identifier: identifier.to_string(),
@@ -1594,6 +2204,75 @@ impl AnalyzerError {
         }
     }
 
+    pub fn unknown_doc_target(
+        kind: &str,
+        identifier: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::UnknownDocTarget {
+            kind: kind.to_string(),
+            identifier: identifier.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn read_before_write(identifier: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::ReadBeforeWrite {
+            identifier: identifier.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn dft_generated_clock(identifier: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::DftGeneratedClock {
+            identifier: identifier.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn dft_generated_reset(identifier: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::DftGeneratedReset {
+            identifier: identifier.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn unsafe_fsm_encoding(identifier: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::UnsafeFsmEncoding {
+            identifier: identifier.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn hierarchical_reference(
+        identifier: &str,
+        instance: &str,
+        source: &str,
+        token: &TokenRange,
+    ) -> Self {
+        AnalyzerError::HierarchicalReference {
+            identifier: identifier.to_string(),
+            instance: instance.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
+    pub fn unassign_bits(identifier: &str, bits: &str, source: &str, token: &TokenRange) -> Self {
+        AnalyzerError::UnassignBits {
+            identifier: identifier.to_string(),
+            bits: bits.to_string(),
+            input: AnalyzerError::named_source(source, token),
+            error_location: token.into(),
+        }
+    }
+
     pub fn unassign_variable(identifier: &str, source: &str, token: &TokenRange) -> Self {
         AnalyzerError::UnassignVariable {
             identifier: identifier.to_string(),
@@ -1607,12 +2286,21 @@ impl AnalyzerError {
         source: &str,
         token: &TokenRange,
         uncovered: &TokenRange,
+        assigned: &[TokenRange],
+        missing: &[TokenRange],
     ) -> Self {
+        let assigned = assigned
+            .iter()
+            .map(|x| LabeledSpan::new_with_span(Some("Assigned".to_string()), x));
+        let missing = missing
+            .iter()
+            .map(|x| LabeledSpan::new_with_span(Some("Not assigned".to_string()), x));
         AnalyzerError::UncoveredBranch {
             identifier: identifier.to_string(),
             input: AnalyzerError::named_source(source, token),
             error_location: token.into(),
             uncovered: uncovered.into(),
+            branches: assigned.chain(missing).collect(),
         }
     }
 
@@ -1633,3 +2321,179 @@ impl AnalyzerError {
         }
     }
 }
+
+/// Codes of all diagnostics which can be configured by `[lint.severity]`
+pub const CODES: &[&str] = &[
+    "call_non_function",
+    "cyclice_type_dependency",
+    "duplicated_identifier",
+    "multiple_assignment",
+    "invalid_allow",
+    "invalid_assignment",
+    "invalid_assignment_to_const",
+    "invalid_direction",
+    "invalid_factor",
+    "invalid_identifier",
+    "invalid_import",
+    "invalid_lsb",
+    "invalid_msb",
+    "invalid_number_character",
+    "invalid_statement",
+    "invalid_clock",
+    "invalid_modport_variable_item",
+    "invalid_modport_item",
+    "invalid_modport_function_item",
+    "invalid_reset",
+    "invalid_reset_non_elaborative",
+    "invalid_param_non_elaborative",
+    "invalid_case_condition_non_elaborative",
+    "invalid_cast",
+    "invalid_test",
+    "incompat_proto",
+    "missing_default_argument",
+    "mismatch_function_arity",
+    "mismatch_generics_arity",
+    "mismatch_attribute_args",
+    "mismatch_type",
+    "mismatch_clock_domain",
+    "missing_if_reset",
+    "missing_return",
+    "missing_port",
+    "invalid_sim_only_instance",
+    "missing_clock_signal",
+    "missing_reset_signal",
+    "missing_reset_statement",
+    "missing_tri",
+    "missing_clock_domain",
+    "sv_keyword_usage",
+    "sv_with_implicit_reset",
+    "invalid_enum_encoding",
+    "invalid_memory_kind",
+    "invalid_memory",
+    "invalid_pipeline",
+    "invalid_parity",
+    "invalid_flatten",
+    "invalid_auto_connect",
+    "invalid_protocol",
+    "too_large_enum_variant",
+    "unevaluatable_enum_variant_value",
+    "invalid_enum_variant_value",
+    "duplicated_enum_variant_value",
+    "too_large_number",
+    "too_much_enum_variant",
+    "undefined_identifier",
+    "unresolvable_generic_argument",
+    "unknown_attribute",
+    "unknown_embed_lang",
+    "unknown_embed_way",
+    "unknown_include_way",
+    "missing_modport",
+    "mismatch_modport_direction",
+    "unknown_member",
+    "out_of_range_index",
+    "unknown_unsafe",
+    "private_member",
+    "unknown_msb",
+    "unknown_port",
+    "unknown_param",
+    "recursive_function",
+    "function_side_effect",
+    "unused_variable",
+    "unused_function",
+    "unused_function_input",
+    "unused_return",
+    "unknown_doc_target",
+    "read_before_write",
+    "dft_generated_clock",
+    "dft_generated_reset",
+    "unsafe_fsm_encoding",
+    "hierarchical_reference",
+    "unassign_variable",
+    "unassign_bits",
+    "uncovered_branch",
+    "reserved_identifier",
+    "include_failure",
+];
+
+impl AnalyzerError {
+    /// Returns the severity overridden by `[lint.severity]` or `[lint.dft]` if it is configured
+    pub fn severity_with(&self, lint: &Lint) -> Severity {
+        let configured = self.code().and_then(|x| {
+            let code = x.to_string();
+            lint.severity.get(&code).copied().or_else(|| {
+                if !lint.dft.enable {
+                    return None;
+                }
+                match code.as_str() {
+                    "dft_generated_clock" => Some(lint.dft.generated_clock),
+                    "dft_generated_reset" => Some(lint.dft.generated_reset),
+                    "uncovered_branch" => Some(lint.dft.latch),
+                    _ => None,
+                }
+            })
+        });
+        match configured {
+            Some(LintSeverity::Error) => Severity::Error,
+            Some(LintSeverity::Warning) => Severity::Warning,
+            Some(LintSeverity::Info) => Severity::Advice,
+            None => self.severity().unwrap_or(Severity::Error),
+        }
+    }
+
+    /// Returns the keys of `[lint.severity]` which don't match any diagnostic code
+    pub fn unknown_severity_codes(lint: &Lint) -> Vec<&str> {
+        lint.severity
+            .keys()
+            .map(|x| x.as_str())
+            .filter(|x| !CODES.contains(x))
+            .collect()
+    }
+
+    pub fn with_lint(self, lint: &Lint) -> LintedError {
+        LintedError {
+            severity: self.severity_with(lint),
+            error: self,
+        }
+    }
+}
+
+/// AnalyzerError with the severity configured by `[lint.severity]`
+#[derive(Debug)]
+pub struct LintedError {
+    pub error: AnalyzerError,
+    pub severity: Severity,
+}
+
+impl fmt::Display for LintedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for LintedError {}
+
+impl Diagnostic for LintedError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.code()
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(self.severity)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.error.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.error.labels()
+    }
+}
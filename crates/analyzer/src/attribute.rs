@@ -8,6 +8,17 @@ pub enum Attribute {
     Ifdef(StrId),
     Ifndef(StrId),
     Sv(StrId),
+    SimOnly,
+    ClockEn(Token),
+    Memory(MemoryItem, usize),
+    Pipeline(Option<Token>),
+    Sync(Token, Token, usize),
+    Protocol(Token),
+    PowerDomain(StrId),
+    Fsm,
+    Parity,
+    Flatten,
+    AutoConnect,
     Allow(AllowItem),
     EnumEncoding(EnumEncodingItem),
     EnumMemberPrefix(StrId),
@@ -20,6 +31,20 @@ impl fmt::Display for Attribute {
             Attribute::Ifdef(x) => format!("ifdef({})", x),
             Attribute::Ifndef(x) => format!("ifndef({})", x),
             Attribute::Sv(x) => format!("sv(\"{}\")", x),
+            Attribute::SimOnly => "sim_only".to_string(),
+            Attribute::ClockEn(x) => format!("clock_en({})", x.text),
+            Attribute::Memory(x, y) => format!("memory({}, \"{}\")", x, y),
+            Attribute::Pipeline(x) => match x {
+                Some(x) => format!("pipeline({})", x.text),
+                None => "pipeline".to_string(),
+            },
+            Attribute::Sync(x, y, z) => format!("sync({}, {}, \"{}\")", x.text, y.text, z),
+            Attribute::Protocol(x) => format!("protocol({})", x.text),
+            Attribute::PowerDomain(x) => format!("power_domain(\"{}\")", x),
+            Attribute::Fsm => "fsm".to_string(),
+            Attribute::Parity => "parity".to_string(),
+            Attribute::Flatten => "flatten".to_string(),
+            Attribute::AutoConnect => "auto_connect".to_string(),
             Attribute::Allow(x) => format!("allow({})", x),
             Attribute::EnumEncoding(x) => format!("enum_encoding({})", x),
             Attribute::EnumMemberPrefix(x) => format!("enum_member_prefix({})", x),
@@ -35,6 +60,7 @@ pub enum AttributeError {
     MismatchArgs(&'static str),
     InvalidAllow(StrId),
     InvalidEnumEncoding(StrId),
+    InvalidMemory(StrId),
 }
 
 fn get_arg_ident(
@@ -81,11 +107,30 @@ struct Pattern {
     pub ifdef: StrId,
     pub ifndef: StrId,
     pub sv: StrId,
+    pub sim_only: StrId,
+    pub clock_en: StrId,
+    pub memory: StrId,
+    pub single_port: StrId,
+    pub simple_dual_port: StrId,
+    pub true_dual_port: StrId,
+    pub pipeline: StrId,
+    pub sync: StrId,
+    pub protocol: StrId,
+    pub power_domain: StrId,
+    pub fsm: StrId,
+    pub parity: StrId,
+    pub flatten: StrId,
+    pub auto_connect: StrId,
     pub allow: StrId,
     pub missing_port: StrId,
     pub missing_reset_statement: StrId,
     pub unused_variable: StrId,
+    pub unused_function: StrId,
+    pub side_effect: StrId,
+    pub read_before_write: StrId,
+    pub hierarchical_reference: StrId,
     pub enum_encoding: StrId,
+    pub encoding: StrId,
     pub sequential: StrId,
     pub onehot: StrId,
     pub gray: StrId,
@@ -99,11 +144,30 @@ impl Pattern {
             ifdef: resource_table::insert_str("ifdef"),
             ifndef: resource_table::insert_str("ifndef"),
             sv: resource_table::insert_str("sv"),
+            sim_only: resource_table::insert_str("sim_only"),
+            clock_en: resource_table::insert_str("clock_en"),
+            memory: resource_table::insert_str("memory"),
+            single_port: resource_table::insert_str("single_port"),
+            simple_dual_port: resource_table::insert_str("simple_dual_port"),
+            true_dual_port: resource_table::insert_str("true_dual_port"),
+            pipeline: resource_table::insert_str("pipeline"),
+            sync: resource_table::insert_str("sync"),
+            protocol: resource_table::insert_str("protocol"),
+            power_domain: resource_table::insert_str("power_domain"),
+            fsm: resource_table::insert_str("fsm"),
+            parity: resource_table::insert_str("parity"),
+            flatten: resource_table::insert_str("flatten"),
+            auto_connect: resource_table::insert_str("auto_connect"),
             allow: resource_table::insert_str("allow"),
             missing_port: resource_table::insert_str("missing_port"),
             missing_reset_statement: resource_table::insert_str("missing_reset_statement"),
             unused_variable: resource_table::insert_str("unused_variable"),
+            unused_function: resource_table::insert_str("unused_function"),
+            side_effect: resource_table::insert_str("side_effect"),
+            read_before_write: resource_table::insert_str("read_before_write"),
+            hierarchical_reference: resource_table::insert_str("hierarchical_reference"),
             enum_encoding: resource_table::insert_str("enum_encoding"),
+            encoding: resource_table::insert_str("encoding"),
             sequential: resource_table::insert_str("sequential"),
             onehot: resource_table::insert_str("onehot"),
             gray: resource_table::insert_str("gray"),
@@ -142,6 +206,112 @@ impl TryFrom<&veryl_parser::veryl_grammar_trait::Attribute> for Attribute {
                     Err(AttributeError::MismatchArgs("single string"))
                 }
             }
+            x if x == pat.sim_only => {
+                if value.attribute_opt.is_none() {
+                    Ok(Attribute::SimOnly)
+                } else {
+                    Err(AttributeError::MismatchArgs("no argument"))
+                }
+            }
+            x if x == pat.clock_en => {
+                let arg = get_arg_ident(&value.attribute_opt, 0);
+
+                if let Some(arg) = arg {
+                    Ok(Attribute::ClockEn(arg))
+                } else {
+                    Err(AttributeError::MismatchArgs("single identifier"))
+                }
+            }
+            x if x == pat.memory => {
+                let arg = get_arg_ident(&value.attribute_opt, 0);
+                let latency = get_arg_string(&value.attribute_opt, 1);
+                let expected = "memory kind and optional read latency";
+
+                let Some(arg) = arg else {
+                    return Err(AttributeError::MismatchArgs(expected));
+                };
+                let item = match arg.text {
+                    x if x == pat.single_port => MemoryItem::SinglePort,
+                    x if x == pat.simple_dual_port => MemoryItem::SimpleDualPort,
+                    x if x == pat.true_dual_port => MemoryItem::TrueDualPort,
+                    _ => return Err(AttributeError::InvalidMemory(arg.text)),
+                };
+                // Read latency is given as string like "1" because attribute can't take number
+                let latency = match latency {
+                    Some(x) => {
+                        let text = x.to_string();
+                        match text.trim_matches('"').parse() {
+                            Ok(x) => x,
+                            Err(_) => return Err(AttributeError::MismatchArgs(expected)),
+                        }
+                    }
+                    None => 1,
+                };
+                Ok(Attribute::Memory(item, latency))
+            }
+            x if x == pat.pipeline => {
+                if value.attribute_opt.is_none() {
+                    return Ok(Attribute::Pipeline(None));
+                }
+                let arg = get_arg_ident(&value.attribute_opt, 0);
+
+                if let Some(arg) = arg {
+                    Ok(Attribute::Pipeline(Some(arg)))
+                } else {
+                    Err(AttributeError::MismatchArgs("valid signal or no argument"))
+                }
+            }
+            x if x == pat.sync => {
+                let source = get_arg_ident(&value.attribute_opt, 0);
+                let clock = get_arg_ident(&value.attribute_opt, 1);
+                let stages = get_arg_string(&value.attribute_opt, 2);
+                let expected = "source signal, destination clock and optional stages";
+
+                let (Some(source), Some(clock)) = (source, clock) else {
+                    return Err(AttributeError::MismatchArgs(expected));
+                };
+                let stages = match stages {
+                    Some(x) => match x.to_string().trim_matches('"').parse() {
+                        Ok(x) if x >= 2 => x,
+                        _ => return Err(AttributeError::MismatchArgs(expected)),
+                    },
+                    None => 2,
+                };
+                Ok(Attribute::Sync(source, clock, stages))
+            }
+            x if x == pat.protocol => {
+                let arg = get_arg_ident(&value.attribute_opt, 0);
+
+                if let Some(arg) = arg {
+                    Ok(Attribute::Protocol(arg))
+                } else {
+                    Err(AttributeError::MismatchArgs("single identifier"))
+                }
+            }
+            x if x == pat.power_domain => {
+                let arg = get_arg_string(&value.attribute_opt, 0);
+
+                if let Some(arg) = arg {
+                    let text = arg.to_string();
+                    let text = text.trim_matches('"');
+                    Ok(Attribute::PowerDomain(resource_table::insert_str(text)))
+                } else {
+                    Err(AttributeError::MismatchArgs("single string"))
+                }
+            }
+            x if x == pat.fsm || x == pat.parity || x == pat.flatten || x == pat.auto_connect => {
+                if value.attribute_opt.is_some() {
+                    Err(AttributeError::MismatchArgs("no argument"))
+                } else if x == pat.fsm {
+                    Ok(Attribute::Fsm)
+                } else if x == pat.parity {
+                    Ok(Attribute::Parity)
+                } else if x == pat.flatten {
+                    Ok(Attribute::Flatten)
+                } else {
+                    Ok(Attribute::AutoConnect)
+                }
+            }
             x if x == pat.allow => {
                 let arg = get_arg_ident(&value.attribute_opt, 0);
 
@@ -154,13 +324,24 @@ impl TryFrom<&veryl_parser::veryl_grammar_trait::Attribute> for Attribute {
                         x if x == pat.unused_variable => {
                             Ok(Attribute::Allow(AllowItem::UnusedVariable))
                         }
+                        x if x == pat.unused_function => {
+                            Ok(Attribute::Allow(AllowItem::UnusedFunction))
+                        }
+                        x if x == pat.side_effect => Ok(Attribute::Allow(AllowItem::SideEffect)),
+                        x if x == pat.read_before_write => {
+                            Ok(Attribute::Allow(AllowItem::ReadBeforeWrite))
+                        }
+                        x if x == pat.hierarchical_reference => {
+                            Ok(Attribute::Allow(AllowItem::HierarchicalReference))
+                        }
                         _ => Err(AttributeError::InvalidAllow(arg.text)),
                     }
                 } else {
                     Err(AttributeError::MismatchArgs("allowable rule"))
                 }
             }
-            x if x == pat.enum_encoding => {
+            // `encoding` is the short form of `enum_encoding`
+            x if x == pat.enum_encoding || x == pat.encoding => {
                 let arg = get_arg_ident(&value.attribute_opt, 0);
 
                 if let Some(arg) = arg {
@@ -202,11 +383,23 @@ impl TryFrom<&veryl_parser::veryl_grammar_trait::Attribute> for Attribute {
     }
 }
 
+/// Items of `#[allow(...)]` suppressing the check of the same name
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum AllowItem {
+    /// Unconnected ports of the instance
     MissingPort,
+    /// Variables without reset statement in the `always_ff`
     MissingResetStatement,
+    /// Unused variables declared in the item
     UnusedVariable,
+    /// Unused functions declared in the item
+    UnusedFunction,
+    /// Assignments to outside of the function
+    SideEffect,
+    /// References before assignments in the `always_comb`
+    ReadBeforeWrite,
+    /// References into module instances like `u.a`
+    HierarchicalReference,
 }
 
 impl fmt::Display for AllowItem {
@@ -215,6 +408,10 @@ impl fmt::Display for AllowItem {
             AllowItem::MissingPort => "missing_port",
             AllowItem::MissingResetStatement => "missing_reset_statement",
             AllowItem::UnusedVariable => "unused_variable",
+            AllowItem::UnusedFunction => "unused_function",
+            AllowItem::SideEffect => "side_effect",
+            AllowItem::ReadBeforeWrite => "read_before_write",
+            AllowItem::HierarchicalReference => "hierarchical_reference",
         };
         text.fmt(f)
     }
@@ -238,3 +435,30 @@ impl fmt::Display for EnumEncodingItem {
         text.fmt(f)
     }
 }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MemoryItem {
+    SinglePort,
+    SimpleDualPort,
+    TrueDualPort,
+}
+
+impl MemoryItem {
+    pub fn write_ports(&self) -> usize {
+        match self {
+            MemoryItem::SinglePort | MemoryItem::SimpleDualPort => 1,
+            MemoryItem::TrueDualPort => 2,
+        }
+    }
+}
+
+impl fmt::Display for MemoryItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            MemoryItem::SinglePort => "single_port",
+            MemoryItem::SimpleDualPort => "simple_dual_port",
+            MemoryItem::TrueDualPort => "true_dual_port",
+        };
+        text.fmt(f)
+    }
+}
@@ -36,3 +36,7 @@ pub fn get_all() -> Vec<(TokenRange, Attribute)> {
 pub fn clear() {
     ATTRIBUTE_TABLE.with(|f| f.borrow_mut().clear())
 }
+
+pub(crate) fn swap(table: &mut RangeTable<Attribute>) {
+    ATTRIBUTE_TABLE.with(|f| std::mem::swap(&mut *f.borrow_mut(), table))
+}
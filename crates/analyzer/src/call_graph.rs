@@ -0,0 +1,124 @@
+use crate::symbol::SymbolId;
+use crate::symbol_table;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use veryl_parser::resource_table::PathId;
+use veryl_parser::veryl_token::Token;
+
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub caller: SymbolId,
+    pub callee: SymbolId,
+    pub token: Token,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct CallGraph {
+    calls: Vec<Call>,
+}
+
+impl CallGraph {
+    pub fn insert(&mut self, caller: SymbolId, callee: SymbolId, token: &Token) {
+        self.calls.push(Call {
+            caller,
+            callee,
+            token: *token,
+        });
+    }
+
+    pub fn get_callers(&self, callee: SymbolId) -> Vec<Call> {
+        self.calls
+            .iter()
+            .filter(|x| x.callee == callee)
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_callees(&self, caller: SymbolId) -> Vec<Call> {
+        self.calls
+            .iter()
+            .filter(|x| x.caller == caller)
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_recursive(&self, id: SymbolId) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(caller) = stack.pop() {
+            for call in self.calls.iter().filter(|x| x.caller == caller) {
+                if call.callee == id {
+                    return true;
+                }
+                if visited.insert(call.callee) {
+                    stack.push(call.callee);
+                }
+            }
+        }
+        false
+    }
+
+    pub fn drop(&mut self, file_path: PathId) {
+        self.calls.retain(|x| x.token.source != file_path);
+    }
+
+    pub fn clear(&mut self) {
+        self.calls.clear()
+    }
+}
+
+impl fmt::Display for CallGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "CallGraph [")?;
+        for call in &self.calls {
+            let caller = symbol_table::get(call.caller).map(|x| x.token.to_string());
+            let callee = symbol_table::get(call.callee).map(|x| x.token.to_string());
+            writeln!(
+                f,
+                "    {} -> {} @ {}:{}:{}",
+                caller.unwrap_or_default(),
+                callee.unwrap_or_default(),
+                call.token.source,
+                call.token.line,
+                call.token.column,
+            )?;
+        }
+        writeln!(f, "]")?;
+        Ok(())
+    }
+}
+
+thread_local!(static CALL_GRAPH: RefCell<CallGraph> = RefCell::new(CallGraph::default()));
+
+pub fn insert(caller: SymbolId, callee: SymbolId, token: &Token) {
+    CALL_GRAPH.with(|f| f.borrow_mut().insert(caller, callee, token))
+}
+
+pub fn get_callers(callee: SymbolId) -> Vec<Call> {
+    CALL_GRAPH.with(|f| f.borrow().get_callers(callee))
+}
+
+pub fn get_callees(caller: SymbolId) -> Vec<Call> {
+    CALL_GRAPH.with(|f| f.borrow().get_callees(caller))
+}
+
+pub fn is_recursive(id: SymbolId) -> bool {
+    CALL_GRAPH.with(|f| f.borrow().is_recursive(id))
+}
+
+pub fn dump() -> String {
+    CALL_GRAPH.with(|f| f.borrow().to_string())
+}
+
+pub fn drop(file_path: PathId) {
+    CALL_GRAPH.with(|f| f.borrow_mut().drop(file_path))
+}
+
+pub fn clear() {
+    CALL_GRAPH.with(|f| f.borrow_mut().clear())
+}
+
+pub(crate) fn swap(table: &mut CallGraph) {
+    CALL_GRAPH.with(|f| std::mem::swap(&mut *f.borrow_mut(), table))
+}
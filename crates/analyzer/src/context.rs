@@ -0,0 +1,75 @@
+use crate::attribute::Attribute;
+use crate::call_graph::CallGraph;
+use crate::msb_table::MsbTable;
+use crate::namespace_table::NamespaceTable;
+use crate::r#unsafe::Unsafe;
+use crate::range_table::RangeTable;
+use crate::symbol_table::SymbolTable;
+use crate::type_dag::TypeDag;
+use crate::{attribute_table, call_graph, msb_table, namespace_table};
+use crate::{symbol_table, type_dag, unsafe_table};
+
+/// Tables of analysis results owned by each project.
+///
+/// Table functions like `symbol_table::resolve` refer the tables of the current thread,
+/// so a context should be entered before analysis to keep projects separated.
+/// This only switches the thread-local tables of the current thread,
+/// so analysis on other threads doesn't see the tables of the entered context.
+pub struct AnalyzerContext {
+    attribute_table: RangeTable<Attribute>,
+    call_graph: CallGraph,
+    msb_table: MsbTable,
+    namespace_table: NamespaceTable,
+    symbol_table: SymbolTable,
+    type_dag: TypeDag,
+    unsafe_table: RangeTable<Unsafe>,
+}
+
+impl Default for AnalyzerContext {
+    fn default() -> Self {
+        Self {
+            attribute_table: RangeTable::default(),
+            call_graph: CallGraph::default(),
+            msb_table: MsbTable::default(),
+            namespace_table: NamespaceTable::default(),
+            symbol_table: SymbolTable::new(),
+            type_dag: TypeDag::new(),
+            unsafe_table: RangeTable::default(),
+        }
+    }
+}
+
+impl AnalyzerContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exchanges the tables of this context and the current thread.
+    /// The exchanged tables are not restored automatically, so `enter` should be used
+    /// unless the context is switched until the next `swap`.
+    pub fn swap(&mut self) {
+        attribute_table::swap(&mut self.attribute_table);
+        call_graph::swap(&mut self.call_graph);
+        msb_table::swap(&mut self.msb_table);
+        namespace_table::swap(&mut self.namespace_table);
+        symbol_table::swap(&mut self.symbol_table);
+        type_dag::swap(&mut self.type_dag);
+        unsafe_table::swap(&mut self.unsafe_table);
+    }
+
+    /// Runs `f` with the tables of this context, and restores the previous tables after it
+    /// even if `f` panics
+    pub fn enter<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        struct Guard<'a>(&'a mut AnalyzerContext);
+
+        impl Drop for Guard<'_> {
+            fn drop(&mut self) {
+                self.0.swap();
+            }
+        }
+
+        self.swap();
+        let _guard = Guard(self);
+        f()
+    }
+}
@@ -1,6 +1,7 @@
-use crate::symbol::{Type, TypeKind};
+use crate::symbol::{ParameterKind, SymbolKind, Type, TypeKind};
 use crate::symbol_table::{self, ResolveError, ResolveResult};
 use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_walker::VerylWalker;
 
 #[derive(Clone, Copy, Debug)]
 pub enum Evaluated {
@@ -327,8 +328,8 @@ impl Evaluator {
             ">>" => left.unsigned_shr(right),
             "<=" => left.le(right),
             ">=" => left.ge(right),
-            "<" => left.lt(right),
-            ">" => left.gt(right),
+            "<" | "<:" => left.lt(right),
+            ">" | ">:" => left.gt(right),
             "===" => left.eq(right),
             "==?" => left.eq(right),
             "!==" => left.ne(right),
@@ -383,11 +384,7 @@ impl Evaluator {
                 if x.width.len() == 1 {
                     let width = self.expression(&x.width[0]);
                     if let Evaluated::Fixed { value, .. } = width {
-                        if let Ok(width) = value.try_into() {
-                            Some(width)
-                        } else {
-                            None
-                        }
+                        value.try_into().ok()
                     } else {
                         None
                     }
@@ -838,3 +835,21 @@ impl Evaluator {
         Evaluated::Unknown
     }
 }
+
+/// Finds references to overridable parameters in expression
+#[derive(Default)]
+pub struct OverridableCollector {
+    pub found: bool,
+}
+
+impl VerylWalker for OverridableCollector {
+    fn scoped_identifier(&mut self, arg: &ScopedIdentifier) {
+        if let Ok(symbol) = symbol_table::resolve(arg) {
+            if let SymbolKind::Parameter(x) = symbol.found.kind {
+                if matches!(x.kind, ParameterKind::Param) {
+                    self.found = true;
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,21 @@
 pub mod check_attribute;
 pub mod check_clock_domain;
 pub mod check_clock_reset;
+pub mod check_dft;
 pub mod check_direction;
 pub mod check_embed_include;
 pub mod check_enum;
 pub mod check_expression;
 pub mod check_function;
+pub mod check_hierarchy;
 pub mod check_identifier;
 pub mod check_modport;
 pub mod check_msb_lsb;
 pub mod check_number;
+pub mod check_pipeline;
 pub mod check_proto;
+pub mod check_protocol;
+pub mod check_safety;
 pub mod check_statement;
 pub mod check_type;
 pub mod check_unsafe;
@@ -21,16 +26,21 @@ pub mod create_type_dag;
 use check_attribute::*;
 use check_clock_domain::*;
 use check_clock_reset::*;
+use check_dft::*;
 use check_direction::*;
 use check_embed_include::*;
 use check_enum::*;
 use check_expression::*;
 use check_function::*;
+use check_hierarchy::*;
 use check_identifier::*;
 use check_modport::*;
 use check_msb_lsb::*;
 use check_number::*;
+use check_pipeline::*;
 use check_proto::*;
+use check_protocol::*;
+use check_safety::*;
 use check_statement::*;
 use check_type::*;
 use check_unsafe::*;
@@ -109,10 +119,15 @@ pub struct Pass2Handlers<'a> {
     check_clock_domain: CheckClockDomain<'a>,
     check_proto: CheckProto<'a>,
     check_type: CheckType<'a>,
+    check_hierarchy: CheckHierarchy<'a>,
+    check_dft: CheckDft<'a>,
+    check_pipeline: CheckPipeline<'a>,
+    check_protocol: CheckProtocol<'a>,
+    check_safety: CheckSafety<'a>,
 }
 
 impl<'a> Pass2Handlers<'a> {
-    pub fn new(text: &'a str, _build_opt: &'a Build, _lint_opt: &'a Lint) -> Self {
+    pub fn new(text: &'a str, _build_opt: &'a Build, lint_opt: &'a Lint) -> Self {
         Self {
             check_enum: CheckEnum::new(text),
             check_modport: CheckModport::new(text),
@@ -126,6 +141,11 @@ impl<'a> Pass2Handlers<'a> {
             check_clock_domain: CheckClockDomain::new(text),
             check_proto: CheckProto::new(text),
             check_type: CheckType::new(text),
+            check_hierarchy: CheckHierarchy::new(text, lint_opt),
+            check_dft: CheckDft::new(text, lint_opt),
+            check_pipeline: CheckPipeline::new(text),
+            check_protocol: CheckProtocol::new(text),
+            check_safety: CheckSafety::new(text),
         }
     }
 
@@ -143,6 +163,11 @@ impl<'a> Pass2Handlers<'a> {
             &mut self.check_clock_domain as &mut dyn Handler,
             &mut self.check_proto as &mut dyn Handler,
             &mut self.check_type as &mut dyn Handler,
+            &mut self.check_hierarchy as &mut dyn Handler,
+            &mut self.check_dft as &mut dyn Handler,
+            &mut self.check_pipeline as &mut dyn Handler,
+            &mut self.check_protocol as &mut dyn Handler,
+            &mut self.check_safety as &mut dyn Handler,
         ]
     }
 
@@ -160,6 +185,11 @@ impl<'a> Pass2Handlers<'a> {
         ret.append(&mut self.check_clock_domain.errors);
         ret.append(&mut self.check_proto.errors);
         ret.append(&mut self.check_type.errors);
+        ret.append(&mut self.check_hierarchy.errors);
+        ret.append(&mut self.check_dft.errors);
+        ret.append(&mut self.check_pipeline.errors);
+        ret.append(&mut self.check_protocol.errors);
+        ret.append(&mut self.check_safety.errors);
         ret
     }
 }
@@ -69,6 +69,13 @@ impl<'a> VerylGrammarTrait for CheckAttribute<'a> {
                                 &arg.identifier.as_ref().into(),
                             ));
                         }
+                        crate::attribute::AttributeError::InvalidMemory(x) => {
+                            self.errors.push(AnalyzerError::invalid_memory_kind(
+                                &x.to_string(),
+                                self.text,
+                                &arg.identifier.as_ref().into(),
+                            ));
+                        }
                     }
                 }
             }
@@ -1,6 +1,10 @@
 use crate::analyzer_error::AnalyzerError;
+use crate::attribute::Attribute as Attr;
+use crate::attribute_table;
+use crate::namespace_table;
 use crate::r#unsafe::Unsafe;
-use crate::symbol::{ClockDomain, SymbolId, SymbolKind};
+use crate::symbol::{ClockDomain, SymbolId, SymbolKind, TypeKind};
+use crate::symbol_path::SymbolPath;
 use crate::symbol_table;
 use crate::unsafe_table;
 use std::collections::HashMap;
@@ -60,6 +64,100 @@ impl<'a> CheckClockDomain<'a> {
         }
         prev.map(|(x, _)| x).unwrap_or(ClockDomain::None)
     }
+
+    fn check_clock_enable(&mut self, enable: &Token) {
+        let range: TokenRange = enable.into();
+        let Some(namespace) = namespace_table::get(enable.id) else {
+            return;
+        };
+        let path = SymbolPath::new(&[enable.text]);
+        match symbol_table::resolve((&path, &namespace)) {
+            Ok(symbol) => {
+                symbol_table::add_reference(symbol.found.id, enable);
+                let domain = match symbol.found.kind {
+                    SymbolKind::Variable(x) => x.clock_domain,
+                    SymbolKind::Port(x) => x.clock_domain,
+                    _ => return,
+                };
+                if let Some(always_ff) = self.always_ff_clock_domain {
+                    if !domain.compatible(&always_ff.0)
+                        && !unsafe_table::contains(enable, Unsafe::Cdc)
+                    {
+                        self.errors.push(AnalyzerError::mismatch_clock_domain(
+                            &domain.to_string(),
+                            &always_ff.0.to_string(),
+                            self.text,
+                            &range,
+                            &always_ff.1,
+                        ));
+                    }
+                }
+            }
+            Err(_) => {
+                self.errors.push(AnalyzerError::undefined_identifier(
+                    &enable.to_string(),
+                    self.text,
+                    &range,
+                ));
+            }
+        }
+    }
+
+    /// Checks the destination of `#[sync]` belongs to the clock domain of the destination clock.
+    /// The crossing from the source signal is safe because synchronizer is inserted.
+    fn check_sync(&mut self, target: &VarDeclaration, source: &Token, clock: &Token) {
+        let range: TokenRange = target.identifier.as_ref().into();
+        let Ok(target) = symbol_table::resolve(target.identifier.as_ref()) else {
+            return;
+        };
+        let SymbolKind::Variable(ref target) = target.found.kind else {
+            return;
+        };
+
+        let mut resolved = Vec::new();
+        for token in [source, clock] {
+            let symbol = namespace_table::get(token.id).and_then(|namespace| {
+                symbol_table::resolve((&SymbolPath::new(&[token.text]), &namespace)).ok()
+            });
+            if let Some(symbol) = symbol {
+                symbol_table::add_reference(symbol.found.id, token);
+                resolved.push(symbol.found);
+            } else {
+                self.errors.push(AnalyzerError::undefined_identifier(
+                    &token.to_string(),
+                    self.text,
+                    &token.into(),
+                ));
+                return;
+            }
+        }
+
+        let (clock_type, clock_domain) = match &resolved[1].kind {
+            SymbolKind::Port(x) => (x.r#type.as_ref().map(|x| x.kind.clone()), x.clock_domain),
+            SymbolKind::Variable(x) => (Some(x.r#type.kind.clone()), x.clock_domain),
+            _ => (None, ClockDomain::None),
+        };
+        if !matches!(
+            clock_type,
+            Some(TypeKind::Clock | TypeKind::ClockPosedge | TypeKind::ClockNegedge)
+        ) {
+            self.errors.push(AnalyzerError::invalid_clock(
+                &clock.to_string(),
+                self.text,
+                &clock.into(),
+            ));
+            return;
+        }
+        if !target.clock_domain.compatible(&clock_domain) {
+            self.errors.push(AnalyzerError::mismatch_clock_domain(
+                &target.clock_domain.to_string(),
+                &clock_domain.to_string(),
+                self.text,
+                &range,
+                &clock.into(),
+            ));
+        }
+    }
 }
 
 impl<'a> Handler for CheckClockDomain<'a> {
@@ -159,12 +257,30 @@ impl<'a> VerylGrammarTrait for CheckClockDomain<'a> {
                         }
                     }
                 }
+
+                // Clock enable should belong to the clock domain of always_ff
+                for attr in attribute_table::get(&arg.always_ff.always_ff_token.token) {
+                    if let Attr::ClockEn(enable) = attr {
+                        self.check_clock_enable(&enable);
+                    }
+                }
             }
             HandlerPoint::After => self.always_ff_clock_domain = None,
         }
         Ok(())
     }
 
+    fn var_declaration(&mut self, arg: &VarDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            for attr in attribute_table::get(&arg.identifier.identifier_token.token) {
+                if let Attr::Sync(source, clock, _) = attr {
+                    self.check_sync(arg, &source, &clock);
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn assign_declaration(&mut self, arg: &AssignDeclaration) -> Result<(), ParolError> {
         match self.point {
             HandlerPoint::Before => self.expr_clock_domains.clear(),
@@ -282,29 +282,29 @@ impl<'a> VerylGrammarTrait for CheckClockReset<'a> {
                 match x.casting_type.as_ref() {
                     CastingType::Clock(_)
                     | CastingType::ClockPosedge(_)
-                    | CastingType::ClockNegedge(_) => {
-                        if !src.is_clock() {
-                            self.errors.push(AnalyzerError::invalid_cast(
-                                "non-clock type",
-                                "clock type",
-                                self.text,
-                                &arg.into(),
-                            ));
-                        }
+                    | CastingType::ClockNegedge(_)
+                        if !src.is_clock() =>
+                    {
+                        self.errors.push(AnalyzerError::invalid_cast(
+                            "non-clock type",
+                            "clock type",
+                            self.text,
+                            &arg.into(),
+                        ));
                     }
                     CastingType::Reset(_)
                     | CastingType::ResetAsyncHigh(_)
                     | CastingType::ResetAsyncLow(_)
                     | CastingType::ResetSyncHigh(_)
-                    | CastingType::ResetSyncLow(_) => {
-                        if !src.is_reset() {
-                            self.errors.push(AnalyzerError::invalid_cast(
-                                "non-reset type",
-                                "reset type",
-                                self.text,
-                                &arg.into(),
-                            ));
-                        }
+                    | CastingType::ResetSyncLow(_)
+                        if !src.is_reset() =>
+                    {
+                        self.errors.push(AnalyzerError::invalid_cast(
+                            "non-reset type",
+                            "reset type",
+                            self.text,
+                            &arg.into(),
+                        ));
                     }
                     _ => (),
                 }
@@ -0,0 +1,133 @@
+use crate::analyzer_error::AnalyzerError;
+use crate::symbol::{SymbolId, SymbolKind};
+use crate::symbol_table;
+use veryl_metadata::Lint;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::TokenRange;
+use veryl_parser::veryl_walker::{Handler, HandlerPoint};
+use veryl_parser::ParolError;
+
+pub struct CheckDft<'a> {
+    pub errors: Vec<AnalyzerError>,
+    text: &'a str,
+    lint_opt: &'a Lint,
+    point: HandlerPoint,
+    default_clock: Option<SymbolId>,
+    default_reset: Option<SymbolId>,
+}
+
+impl<'a> CheckDft<'a> {
+    pub fn new(text: &'a str, lint_opt: &'a Lint) -> Self {
+        Self {
+            errors: Vec::new(),
+            text,
+            lint_opt,
+            point: HandlerPoint::Before,
+            default_clock: None,
+            default_reset: None,
+        }
+    }
+
+    /// Clocks and resets declared as variable are driven by logic in the module
+    fn check_clock(&mut self, id: SymbolId, range: &TokenRange) {
+        if let Some(symbol) = symbol_table::get(id) {
+            if matches!(symbol.kind, SymbolKind::Variable(_)) {
+                self.errors.push(AnalyzerError::dft_generated_clock(
+                    &symbol.token.to_string(),
+                    self.text,
+                    range,
+                ));
+            }
+        }
+    }
+
+    fn check_reset(&mut self, id: SymbolId, range: &TokenRange) {
+        if let Some(symbol) = symbol_table::get(id) {
+            if matches!(symbol.kind, SymbolKind::Variable(_)) {
+                self.errors.push(AnalyzerError::dft_generated_reset(
+                    &symbol.token.to_string(),
+                    self.text,
+                    range,
+                ));
+            }
+        }
+    }
+}
+
+impl<'a> Handler for CheckDft<'a> {
+    fn set_point(&mut self, p: HandlerPoint) {
+        self.point = p;
+    }
+}
+
+impl<'a> VerylGrammarTrait for CheckDft<'a> {
+    fn module_declaration(&mut self, arg: &ModuleDeclaration) -> Result<(), ParolError> {
+        match self.point {
+            HandlerPoint::Before => {
+                if let Ok(found) = symbol_table::resolve(arg.identifier.as_ref()) {
+                    if let SymbolKind::Module(x) = found.found.kind {
+                        self.default_clock = x.default_clock;
+                        self.default_reset = x.default_reset;
+                    }
+                }
+            }
+            HandlerPoint::After => {
+                self.default_clock = None;
+                self.default_reset = None;
+            }
+        }
+        Ok(())
+    }
+
+    fn always_ff_declaration(&mut self, arg: &AlwaysFfDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            if !self.lint_opt.dft.enable || arg.always_ff_declaration_opt.is_some() {
+                return Ok(());
+            }
+
+            let range: TokenRange = arg.into();
+            if let Some(clock) = self.default_clock {
+                self.check_clock(clock, &range);
+            }
+
+            let if_reset_exists = arg
+                .statement_block
+                .statement_block_list
+                .first()
+                .is_some_and(|x| match &*x.statement_block_item {
+                    StatementBlockItem::Statement(x) => {
+                        matches!(*x.statement, Statement::IfResetStatement(_))
+                    }
+                    _ => false,
+                });
+            if let (true, Some(reset)) = (if_reset_exists, self.default_reset) {
+                self.check_reset(reset, &range);
+            }
+        }
+        Ok(())
+    }
+
+    fn always_ff_clock(&mut self, arg: &AlwaysFfClock) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            if !self.lint_opt.dft.enable {
+                return Ok(());
+            }
+            if let Ok(found) = symbol_table::resolve(arg.hierarchical_identifier.as_ref()) {
+                self.check_clock(found.found.id, &arg.hierarchical_identifier.as_ref().into());
+            }
+        }
+        Ok(())
+    }
+
+    fn always_ff_reset(&mut self, arg: &AlwaysFfReset) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            if !self.lint_opt.dft.enable {
+                return Ok(());
+            }
+            if let Ok(found) = symbol_table::resolve(arg.hierarchical_identifier.as_ref()) {
+                self.check_reset(found.found.id, &arg.hierarchical_identifier.as_ref().into());
+            }
+        }
+        Ok(())
+    }
+}
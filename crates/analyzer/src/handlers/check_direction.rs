@@ -58,32 +58,26 @@ impl<'a> VerylGrammarTrait for CheckDirection<'a> {
     fn direction(&mut self, arg: &Direction) -> Result<(), ParolError> {
         if let HandlerPoint::Before = self.point {
             match arg {
-                Direction::Ref(x) => {
-                    if !self.in_function {
-                        self.errors.push(AnalyzerError::invalid_direction(
-                            "ref",
-                            self.text,
-                            &x.r#ref.ref_token.token.into(),
-                        ));
-                    }
+                Direction::Ref(x) if !self.in_function => {
+                    self.errors.push(AnalyzerError::invalid_direction(
+                        "ref",
+                        self.text,
+                        &x.r#ref.ref_token.token.into(),
+                    ));
                 }
-                Direction::Modport(x) => {
-                    if !self.in_module || self.in_function {
-                        self.errors.push(AnalyzerError::invalid_direction(
-                            "modport",
-                            self.text,
-                            &x.modport.modport_token.token.into(),
-                        ));
-                    }
+                Direction::Modport(x) if (!self.in_module || self.in_function) => {
+                    self.errors.push(AnalyzerError::invalid_direction(
+                        "modport",
+                        self.text,
+                        &x.modport.modport_token.token.into(),
+                    ));
                 }
-                Direction::Import(x) => {
-                    if !self.in_modport {
-                        self.errors.push(AnalyzerError::invalid_direction(
-                            "import",
-                            self.text,
-                            &x.import.import_token.token.into(),
-                        ));
-                    }
+                Direction::Import(x) if !self.in_modport => {
+                    self.errors.push(AnalyzerError::invalid_direction(
+                        "import",
+                        self.text,
+                        &x.import.import_token.token.into(),
+                    ));
                 }
                 _ => (),
             }
@@ -2,6 +2,7 @@ use crate::analyzer_error::AnalyzerError;
 use crate::evaluator::Evaluator;
 use crate::symbol::SymbolKind;
 use crate::symbol_table;
+use std::collections::HashMap;
 use veryl_parser::veryl_grammar_trait::*;
 use veryl_parser::veryl_walker::{Handler, HandlerPoint};
 use veryl_parser::ParolError;
@@ -37,6 +38,29 @@ impl<'a> VerylGrammarTrait for CheckEnum<'a> {
         if let HandlerPoint::Before = self.point {
             let enum_symbol = symbol_table::resolve(arg.identifier.as_ref()).unwrap();
             if let SymbolKind::Enum(r#enum) = enum_symbol.found.kind {
+                // Explicit values may conflict with other values including values assigned by encoding
+                let mut values: HashMap<usize, String> = HashMap::new();
+                for id in &r#enum.members {
+                    let member_symbol = symbol_table::get(*id).unwrap();
+                    if let SymbolKind::EnumMember(member) = member_symbol.kind {
+                        let Some(value) = member.value.value() else {
+                            continue;
+                        };
+                        if let Some(other) = values.get(&value) {
+                            self.errors
+                                .push(AnalyzerError::duplicated_enum_variant_value(
+                                    &member_symbol.token.to_string(),
+                                    value,
+                                    other,
+                                    self.text,
+                                    &member_symbol.token.into(),
+                                ));
+                        } else {
+                            values.insert(value, member_symbol.token.to_string());
+                        }
+                    }
+                }
+
                 if let Some(r#type) = r#enum.r#type {
                     if let Some(width) = Evaluator::new().type_width(r#type) {
                         let variants = r#enum.members.len();
@@ -1,8 +1,14 @@
 use crate::analyzer_error::AnalyzerError;
-use crate::symbol::SymbolKind;
+use crate::attribute::AllowItem;
+use crate::attribute::Attribute as Attr;
+use crate::attribute_table;
+use crate::call_graph;
+use crate::namespace::Namespace;
+use crate::symbol::{Symbol, SymbolId, SymbolKind};
 use crate::symbol_path::SymbolPath;
 use crate::symbol_table;
 use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::Token;
 use veryl_parser::veryl_walker::{Handler, HandlerPoint};
 use veryl_parser::ParolError;
 
@@ -10,6 +16,8 @@ pub struct CheckFunction<'a> {
     pub errors: Vec<AnalyzerError>,
     text: &'a str,
     point: HandlerPoint,
+    callers: Vec<SymbolId>,
+    functions: Vec<Option<(String, Namespace)>>,
 }
 
 impl<'a> CheckFunction<'a> {
@@ -18,10 +26,113 @@ impl<'a> CheckFunction<'a> {
             errors: Vec::new(),
             text,
             point: HandlerPoint::Before,
+            callers: Vec::new(),
+            functions: Vec::new(),
+        }
+    }
+
+    fn push_caller(&mut self, arg: &Identifier) {
+        if let Ok(symbol) = symbol_table::resolve(arg) {
+            self.callers.push(symbol.found.id);
+        }
+    }
+
+    fn check_side_effect(&mut self, arg: &ExpressionIdentifier) {
+        let token = arg.identifier().token;
+        if attribute_table::contains(&token, Attr::Allow(AllowItem::SideEffect)) {
+            return;
+        }
+
+        if let Some(Some((function, namespace))) = self.functions.last() {
+            if let Ok(symbol) = symbol_table::resolve(arg) {
+                // the first element of path is the assigned variable even if a member is selected
+                let symbol = symbol.full_path.first().and_then(|x| symbol_table::get(*x));
+                if let Some(symbol) = symbol {
+                    if !symbol.namespace.included(namespace) {
+                        self.errors.push(AnalyzerError::function_side_effect(
+                            &symbol.token.to_string(),
+                            function,
+                            self.text,
+                            &arg.into(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_call(&mut self, symbol: &Symbol, token: &Token) {
+        if let Some(caller) = self.callers.last() {
+            call_graph::insert(*caller, symbol.id, token);
         }
     }
 }
 
+fn statement_returns(arg: &Statement) -> bool {
+    match arg {
+        Statement::ReturnStatement(_) => true,
+        Statement::IfStatement(x) => {
+            let x = &x.if_statement;
+            if let Some(ref y) = x.if_statement_opt {
+                block_returns(&x.statement_block)
+                    && x.if_statement_list
+                        .iter()
+                        .all(|x| block_returns(&x.statement_block))
+                    && block_returns(&y.statement_block)
+            } else {
+                false
+            }
+        }
+        Statement::IfResetStatement(x) => {
+            let x = &x.if_reset_statement;
+            if let Some(ref y) = x.if_reset_statement_opt {
+                block_returns(&x.statement_block)
+                    && x.if_reset_statement_list
+                        .iter()
+                        .all(|x| block_returns(&x.statement_block))
+                    && block_returns(&y.statement_block)
+            } else {
+                false
+            }
+        }
+        Statement::CaseStatement(x) => {
+            let items = &x.case_statement.case_statement_list;
+            items
+                .iter()
+                .any(|x| matches!(*x.case_item.case_item_group, CaseItemGroup::Defaul(_)))
+                && items
+                    .iter()
+                    .all(|x| match x.case_item.case_item_group0.as_ref() {
+                        CaseItemGroup0::Statement(x) => statement_returns(&x.statement),
+                        CaseItemGroup0::StatementBlock(x) => block_returns(&x.statement_block),
+                    })
+        }
+        Statement::SwitchStatement(x) => {
+            let items = &x.switch_statement.switch_statement_list;
+            items
+                .iter()
+                .any(|x| matches!(*x.switch_item.switch_item_group, SwitchItemGroup::Defaul(_)))
+                && items
+                    .iter()
+                    .all(|x| match x.switch_item.switch_item_group0.as_ref() {
+                        SwitchItemGroup0::Statement(x) => statement_returns(&x.statement),
+                        SwitchItemGroup0::StatementBlock(x) => block_returns(&x.statement_block),
+                    })
+        }
+        _ => false,
+    }
+}
+
+fn block_returns(arg: &StatementBlock) -> bool {
+    arg.statement_block_list.iter().any(|x| {
+        if let StatementBlockItem::Statement(x) = x.statement_block_item.as_ref() {
+            statement_returns(&x.statement)
+        } else {
+            false
+        }
+    })
+}
+
 impl<'a> Handler for CheckFunction<'a> {
     fn set_point(&mut self, p: HandlerPoint) {
         self.point = p;
@@ -29,8 +140,67 @@ impl<'a> Handler for CheckFunction<'a> {
 }
 
 impl<'a> VerylGrammarTrait for CheckFunction<'a> {
+    fn module_declaration(&mut self, arg: &ModuleDeclaration) -> Result<(), ParolError> {
+        match self.point {
+            HandlerPoint::Before => self.push_caller(&arg.identifier),
+            HandlerPoint::After => {
+                self.callers.pop();
+            }
+        }
+        Ok(())
+    }
+
+    fn interface_declaration(&mut self, arg: &InterfaceDeclaration) -> Result<(), ParolError> {
+        match self.point {
+            HandlerPoint::Before => self.push_caller(&arg.identifier),
+            HandlerPoint::After => {
+                self.callers.pop();
+            }
+        }
+        Ok(())
+    }
+
+    fn package_declaration(&mut self, arg: &PackageDeclaration) -> Result<(), ParolError> {
+        match self.point {
+            HandlerPoint::Before => self.push_caller(&arg.identifier),
+            HandlerPoint::After => {
+                self.callers.pop();
+            }
+        }
+        Ok(())
+    }
+
+    fn function_declaration(&mut self, arg: &FunctionDeclaration) -> Result<(), ParolError> {
+        match self.point {
+            HandlerPoint::Before => {
+                self.push_caller(&arg.identifier);
+
+                let function = symbol_table::resolve(arg.identifier.as_ref())
+                    .ok()
+                    .map(|x| (x.found.token.to_string(), x.found.inner_namespace()));
+                self.functions.push(function);
+
+                if arg.function_declaration_opt1.is_some() && !block_returns(&arg.statement_block) {
+                    self.errors.push(AnalyzerError::missing_return(
+                        &arg.identifier.identifier_token.to_string(),
+                        self.text,
+                        &arg.identifier.as_ref().into(),
+                    ));
+                }
+            }
+            HandlerPoint::After => {
+                self.callers.pop();
+                self.functions.pop();
+            }
+        }
+        Ok(())
+    }
+
     fn identifier_statement(&mut self, arg: &IdentifierStatement) -> Result<(), ParolError> {
         if let HandlerPoint::Before = self.point {
+            if let IdentifierStatementGroup::Assignment(_) = &*arg.identifier_statement_group {
+                self.check_side_effect(&arg.expression_identifier);
+            }
             if let IdentifierStatementGroup::FunctionCall(_) = &*arg.identifier_statement_group {
                 // skip system function
                 if matches!(
@@ -51,6 +221,10 @@ impl<'a> VerylGrammarTrait for CheckFunction<'a> {
                         }
                         _ => return Ok(()),
                     };
+                    self.add_call(
+                        &function_symbol,
+                        &arg.expression_identifier.identifier().token,
+                    );
                     if let SymbolKind::Function(x) = function_symbol.kind {
                         if x.ret.is_some() {
                             let name = format!(
@@ -93,19 +267,24 @@ impl<'a> VerylGrammarTrait for CheckFunction<'a> {
                 }
 
                 if let Ok(symbol) = symbol_table::resolve(x.expression_identifier.as_ref()) {
-                    let arity = match symbol.found.kind {
-                        SymbolKind::Function(x) => Some(x.ports.len()),
-                        SymbolKind::ModportFunctionMember(x) => {
-                            if let SymbolKind::Function(x) =
-                                symbol_table::get(x.function).unwrap().kind
-                            {
-                                Some(x.ports.len())
-                            } else {
-                                unreachable!();
-                            }
-                        }
+                    let function_symbol = match symbol.found.kind {
+                        SymbolKind::Function(_) => Some(symbol.found),
+                        SymbolKind::ModportFunctionMember(x) => symbol_table::get(x.function),
                         _ => None,
                     };
+                    let arity = if let Some(function_symbol) = function_symbol {
+                        self.add_call(
+                            &function_symbol,
+                            &x.expression_identifier.identifier().token,
+                        );
+                        if let SymbolKind::Function(x) = function_symbol.kind {
+                            Some(x.ports.len())
+                        } else {
+                            unreachable!();
+                        }
+                    } else {
+                        None
+                    };
 
                     let mut args = 0;
                     if let Some(ref x) = x.factor_opt {
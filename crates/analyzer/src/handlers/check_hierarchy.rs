@@ -0,0 +1,96 @@
+use crate::analyzer_error::AnalyzerError;
+use crate::attribute::AllowItem;
+use crate::attribute::Attribute as Attr;
+use crate::attribute_table;
+use crate::symbol::SymbolKind;
+use crate::symbol_table::{self, ResolveResult};
+use veryl_metadata::Lint;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, TokenRange};
+use veryl_parser::veryl_walker::{Handler, HandlerPoint};
+use veryl_parser::ParolError;
+
+pub struct CheckHierarchy<'a> {
+    pub errors: Vec<AnalyzerError>,
+    text: &'a str,
+    lint_opt: &'a Lint,
+    point: HandlerPoint,
+}
+
+impl<'a> CheckHierarchy<'a> {
+    pub fn new(text: &'a str, lint_opt: &'a Lint) -> Self {
+        Self {
+            errors: Vec::new(),
+            text,
+            lint_opt,
+            point: HandlerPoint::Before,
+        }
+    }
+
+    fn check(&mut self, symbol: &ResolveResult, token: &Token, range: &TokenRange) {
+        if self.lint_opt.allow_hierarchical_reference
+            || attribute_table::contains(token, Attr::Allow(AllowItem::HierarchicalReference))
+        {
+            return;
+        }
+
+        // Access through module instance is emitted as cross-module reference
+        let Some((_, path)) = symbol.full_path.split_last() else {
+            return;
+        };
+        for id in path {
+            let Some(instance) = symbol_table::get(*id) else {
+                continue;
+            };
+            let SymbolKind::Instance(ref x) = instance.kind else {
+                continue;
+            };
+            let Ok(r#type) = symbol_table::resolve((&x.type_name, &instance.namespace)) else {
+                continue;
+            };
+            let is_module = match r#type.found.kind {
+                SymbolKind::Module(_) => true,
+                SymbolKind::GenericInstance(ref x) => symbol_table::get(x.base)
+                    .is_some_and(|x| matches!(x.kind, SymbolKind::Module(_))),
+                _ => false,
+            };
+            if is_module {
+                self.errors.push(AnalyzerError::hierarchical_reference(
+                    &symbol.found.token.to_string(),
+                    &instance.token.to_string(),
+                    self.text,
+                    range,
+                ));
+                return;
+            }
+        }
+    }
+}
+
+impl<'a> Handler for CheckHierarchy<'a> {
+    fn set_point(&mut self, p: HandlerPoint) {
+        self.point = p;
+    }
+}
+
+impl<'a> VerylGrammarTrait for CheckHierarchy<'a> {
+    fn hierarchical_identifier(&mut self, arg: &HierarchicalIdentifier) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            if let Ok(symbol) = symbol_table::resolve(arg) {
+                let token = arg.identifier.identifier_token.token;
+                self.check(&symbol, &token, &arg.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn expression_identifier(&mut self, arg: &ExpressionIdentifier) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            if let Ok(symbol) = symbol_table::resolve(arg) {
+                let token = arg.identifier().token;
+                self.check(&symbol, &token, &arg.into());
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,12 @@
 use crate::analyzer_error::AnalyzerError;
-use crate::symbol::SymbolKind;
+use crate::attribute::Attribute as Attr;
+use crate::attribute_table;
+use crate::namespace::Namespace;
+use crate::symbol::{Direction as SymDirection, Port, Symbol, SymbolKind, TypeKind};
 use crate::symbol_table;
+use veryl_parser::resource_table;
 use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::TokenRange;
 use veryl_parser::veryl_walker::{Handler, HandlerPoint};
 use veryl_parser::ParolError;
 
@@ -9,6 +14,7 @@ pub struct CheckModport<'a> {
     pub errors: Vec<AnalyzerError>,
     text: &'a str,
     point: HandlerPoint,
+    interface_namespace: Option<Namespace>,
 }
 
 impl<'a> CheckModport<'a> {
@@ -17,8 +23,147 @@ impl<'a> CheckModport<'a> {
             errors: Vec::new(),
             text,
             point: HandlerPoint::Before,
+            interface_namespace: None,
         }
     }
+
+    fn check_connection(&mut self, port: &Port, connected: &Symbol, range: &TokenRange) {
+        let Some(modport) = port_modport(port) else {
+            return;
+        };
+        let SymbolKind::Modport(ref property) = modport.kind else {
+            return;
+        };
+        let port_name = resource_table::get_str_value(port.name).unwrap();
+        let required = modport_name(&modport);
+
+        let (interface, connected_modport) = match &connected.kind {
+            SymbolKind::Port(x) if x.direction == SymDirection::Modport => {
+                // Flattened port doesn't exist as interface in SystemVerilog
+                let flattened = |x: &Symbol| attribute_table::contains(&x.token, Attr::Flatten);
+                if flattened(connected)
+                    && !symbol_table::get(port.symbol).is_some_and(|x| flattened(&x))
+                {
+                    self.errors.push(AnalyzerError::invalid_flatten(
+                        &connected.token.to_string(),
+                        &format!("it can't be connected to {port_name} which is not flattened"),
+                        self.text,
+                        range,
+                    ));
+                    return;
+                }
+                let Some(connected_modport) = port_modport(&Port {
+                    name: connected.token.text,
+                    symbol: connected.id,
+                }) else {
+                    return;
+                };
+                let interface = connected_modport
+                    .namespace
+                    .paths
+                    .last()
+                    .map(|x| x.to_string())
+                    .unwrap_or_default();
+                (interface, Some(connected_modport))
+            }
+            SymbolKind::Instance(x) => {
+                let Ok(interface) = symbol_table::resolve((&x.type_name, &connected.namespace))
+                else {
+                    return;
+                };
+                let interface = match interface.found.kind {
+                    SymbolKind::Interface(_) => interface.found,
+                    SymbolKind::GenericInstance(ref x) => match symbol_table::get(x.base) {
+                        Some(x) if matches!(x.kind, SymbolKind::Interface(_)) => x,
+                        _ => return,
+                    },
+                    _ => return,
+                };
+                // Interface instance has the modport if the modport is declared in the interface
+                let found =
+                    (interface.inner_namespace() == modport.namespace).then(|| modport.clone());
+                (interface.token.to_string(), found)
+            }
+            _ => return,
+        };
+
+        let Some(connected_modport) = connected_modport else {
+            self.errors.push(AnalyzerError::missing_modport(
+                &interface, &required, &port_name, self.text, range,
+            ));
+            return;
+        };
+        if connected_modport.namespace != modport.namespace
+            || !matches!(connected_modport.kind, SymbolKind::Modport(_))
+        {
+            self.errors.push(AnalyzerError::missing_modport(
+                &interface, &required, &port_name, self.text, range,
+            ));
+            return;
+        }
+
+        // Interface instance can be connected to any modport,
+        // but modport port can't drive members which it receives
+        if !matches!(connected.kind, SymbolKind::Port(_)) || connected_modport.id == modport.id {
+            return;
+        }
+        let SymbolKind::Modport(ref connected_property) = connected_modport.kind else {
+            return;
+        };
+        for member in &property.members {
+            let Some(member) = symbol_table::get(*member) else {
+                continue;
+            };
+            let SymbolKind::ModportVariableMember(ref x) = member.kind else {
+                continue;
+            };
+            if !matches!(x.direction, SymDirection::Output | SymDirection::Inout) {
+                continue;
+            }
+            let received = connected_property.members.iter().any(|id| {
+                symbol_table::get(*id).is_some_and(|y| {
+                    y.token.text == member.token.text
+                        && matches!(
+                            y.kind,
+                            SymbolKind::ModportVariableMember(ref y)
+                                if y.direction == SymDirection::Input
+                        )
+                })
+            });
+            if received {
+                self.errors.push(AnalyzerError::mismatch_modport_direction(
+                    &member.token.to_string(),
+                    &port_name,
+                    &modport_name(&connected_modport),
+                    self.text,
+                    range,
+                ));
+            }
+        }
+    }
+}
+
+/// Returns the modport symbol of modport port
+fn port_modport(port: &Port) -> Option<Symbol> {
+    let symbol = symbol_table::get(port.symbol)?;
+    let SymbolKind::Port(ref x) = symbol.kind else {
+        return None;
+    };
+    if x.direction != SymDirection::Modport {
+        return None;
+    }
+    let TypeKind::UserDefined(ref path) = x.r#type.as_ref()?.kind else {
+        return None;
+    };
+    let modport = symbol_table::resolve((path, &symbol.namespace)).ok()?;
+    Some(modport.found)
+}
+
+fn modport_name(modport: &Symbol) -> String {
+    match modport.namespace.paths.last() {
+        Some(x) => format!("{}::{}", x, modport.token),
+        None => modport.token.to_string(),
+    }
 }
 
 impl<'a> Handler for CheckModport<'a> {
@@ -28,9 +173,39 @@ impl<'a> Handler for CheckModport<'a> {
 }
 
 impl<'a> VerylGrammarTrait for CheckModport<'a> {
+    fn modport_declaration(&mut self, arg: &ModportDeclaration) -> Result<(), ParolError> {
+        match self.point {
+            HandlerPoint::Before => {
+                self.interface_namespace = symbol_table::resolve(arg.identifier.as_ref())
+                    .ok()
+                    .map(|x| x.found.namespace);
+            }
+            HandlerPoint::After => self.interface_namespace = None,
+        }
+        Ok(())
+    }
+
     fn modport_item(&mut self, arg: &ModportItem) -> Result<(), ParolError> {
         if let HandlerPoint::Before = self.point {
             if let Ok(symbol) = symbol_table::resolve(arg.identifier.as_ref()) {
+                // Members imported from packages can't be a part of modport
+                if let Some(ref namespace) = self.interface_namespace {
+                    if &symbol.found.namespace != namespace {
+                        let interface = namespace
+                            .paths
+                            .last()
+                            .map(|x| x.to_string())
+                            .unwrap_or_default();
+                        self.errors.push(AnalyzerError::invalid_modport_item(
+                            &arg.identifier.identifier_token.token.to_string(),
+                            &interface,
+                            self.text,
+                            &arg.identifier.as_ref().into(),
+                        ));
+                        return Ok(());
+                    }
+                }
+
                 match &*arg.direction {
                     Direction::Ref(_) | Direction::Modport(_) => {}
                     Direction::Import(_) => {
@@ -58,4 +233,69 @@ impl<'a> VerylGrammarTrait for CheckModport<'a> {
         }
         Ok(())
     }
+
+    fn port_declaration_item(&mut self, arg: &PortDeclarationItem) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let token = &arg.identifier.identifier_token.token;
+            if !attribute_table::contains(token, Attr::Flatten) {
+                return Ok(());
+            }
+            let Ok(symbol) = symbol_table::resolve(arg.identifier.as_ref()) else {
+                return Ok(());
+            };
+            if let Err(reason) = symbol.found.flattened_members() {
+                self.errors.push(AnalyzerError::invalid_flatten(
+                    &token.to_string(),
+                    &reason,
+                    self.text,
+                    &arg.identifier.as_ref().into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn inst_declaration(&mut self, arg: &InstDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let ports = match symbol_table::resolve(arg.scoped_identifier.as_ref()) {
+                Ok(x) => match x.found.kind {
+                    SymbolKind::Module(x) => x.ports,
+                    SymbolKind::GenericInstance(x) => match symbol_table::get(x.base) {
+                        Some(Symbol {
+                            kind: SymbolKind::Module(x),
+                            ..
+                        }) => x.ports,
+                        _ => return Ok(()),
+                    },
+                    _ => return Ok(()),
+                },
+                Err(_) => return Ok(()),
+            };
+            let Ok(instance) = symbol_table::resolve(arg.identifier.as_ref()) else {
+                return Ok(());
+            };
+            let SymbolKind::Instance(ref property) = instance.found.kind else {
+                return Ok(());
+            };
+
+            for (token, targets) in &property.connects {
+                let Some(port) = ports.iter().find(|x| x.name == token.text) else {
+                    continue;
+                };
+                // Only direct connection like `a: b` is checked
+                let [target] = targets.as_slice() else {
+                    continue;
+                };
+                if target.path.len() != 1 {
+                    continue;
+                }
+                if let Ok(connected) =
+                    symbol_table::resolve((&target.path(), &instance.found.namespace))
+                {
+                    self.check_connection(port, &connected.found, &token.into());
+                }
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,200 @@
+use crate::analyzer_error::AnalyzerError;
+use crate::attribute::Attribute as Attr;
+use crate::attribute_table;
+use crate::evaluator::{Evaluated, Evaluator};
+use crate::namespace_table;
+use crate::symbol::{Symbol, SymbolKind};
+use crate::symbol_path::SymbolPath;
+use crate::symbol_table;
+use crate::var_ref::{VarRefPath, VarRefPathItem};
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, TokenRange};
+use veryl_parser::veryl_walker::{Handler, HandlerPoint};
+use veryl_parser::ParolError;
+
+#[derive(Default)]
+pub struct CheckPipeline<'a> {
+    pub errors: Vec<AnalyzerError>,
+    text: &'a str,
+    point: HandlerPoint,
+    default_clock_exists: bool,
+    in_assignment_target: bool,
+}
+
+impl<'a> CheckPipeline<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            ..Default::default()
+        }
+    }
+
+    fn push_error(&mut self, identifier: &Token, reason: &str, range: &TokenRange) {
+        self.errors.push(AnalyzerError::invalid_pipeline(
+            &identifier.to_string(),
+            reason,
+            self.text,
+            range,
+        ));
+    }
+
+    /// Checks that the pipeline is assigned at the first stage and read at the final stage
+    fn check_stage(&mut self, path: &VarRefPath, is_assign: bool, range: &TokenRange) {
+        let Some(VarRefPathItem::Identifier { symbol_id }) = path.0.first() else {
+            return;
+        };
+        let Some(symbol) = symbol_table::get(*symbol_id) else {
+            return;
+        };
+        if pipeline_attribute(&symbol).is_none() {
+            return;
+        }
+
+        let index = match path.0.get(1) {
+            Some(VarRefPathItem::SelectSingle {
+                index: Evaluated::Fixed { value, .. },
+            }) => Some(*value),
+            _ => None,
+        };
+        if is_assign {
+            if index != Some(0) {
+                self.push_error(&symbol.token, "only the first stage can be assigned", range);
+            }
+        } else if let Some(stages) = pipeline_stages(&symbol) {
+            if index != Some(stages - 1) {
+                self.push_error(&symbol.token, "only the final stage can be read", range);
+            }
+        }
+    }
+}
+
+/// Returns the valid signal of `#[pipeline]` if the symbol is pipeline
+fn pipeline_attribute(symbol: &Symbol) -> Option<Option<Token>> {
+    attribute_table::get(&symbol.token)
+        .into_iter()
+        .find_map(|x| match x {
+            Attr::Pipeline(x) => Some(x),
+            _ => None,
+        })
+}
+
+fn pipeline_stages(symbol: &Symbol) -> Option<isize> {
+    let SymbolKind::Variable(ref x) = symbol.kind else {
+        return None;
+    };
+    match x.r#type.array.as_slice() {
+        [x] => match Evaluator::new().expression(x) {
+            Evaluated::Fixed { value, .. } => Some(value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl<'a> Handler for CheckPipeline<'a> {
+    fn set_point(&mut self, p: HandlerPoint) {
+        self.point = p;
+    }
+}
+
+impl<'a> VerylGrammarTrait for CheckPipeline<'a> {
+    fn module_declaration(&mut self, arg: &ModuleDeclaration) -> Result<(), ParolError> {
+        match self.point {
+            HandlerPoint::Before => {
+                if let Ok(found) = symbol_table::resolve(arg.identifier.as_ref()) {
+                    if let SymbolKind::Module(x) = found.found.kind {
+                        self.default_clock_exists = x.default_clock.is_some();
+                    }
+                }
+            }
+            HandlerPoint::After => self.default_clock_exists = false,
+        }
+        Ok(())
+    }
+
+    fn var_declaration(&mut self, arg: &VarDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let Ok(symbol) = symbol_table::resolve(arg.identifier.as_ref()) else {
+                return Ok(());
+            };
+            let Some(valid) = pipeline_attribute(&symbol.found) else {
+                return Ok(());
+            };
+            let token = symbol.found.token;
+            let range: TokenRange = arg.identifier.as_ref().into();
+
+            if pipeline_stages(&symbol.found).is_none() {
+                self.push_error(&token, "stages should be given as array size", &range);
+            }
+            if !self.default_clock_exists {
+                self.push_error(&token, "pipeline requires the default clock", &range);
+            }
+
+            // Valid signal should be pipeline which has the same stages
+            if let Some(valid) = valid {
+                let valid_symbol = namespace_table::get(valid.id).and_then(|namespace| {
+                    symbol_table::resolve((&SymbolPath::new(&[valid.text]), &namespace)).ok()
+                });
+                let is_valid = valid_symbol.is_some_and(|x| {
+                    matches!(pipeline_attribute(&x.found), Some(None))
+                        && pipeline_stages(&x.found) == pipeline_stages(&symbol.found)
+                });
+                if !is_valid {
+                    let reason = format!(
+                        "valid signal {} should be pipeline without valid signal and with the same stages",
+                        valid
+                    );
+                    self.push_error(&token, &reason, &range);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn identifier_statement(&mut self, _arg: &IdentifierStatement) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            self.in_assignment_target = true;
+        }
+        Ok(())
+    }
+
+    fn assignment(&mut self, _arg: &Assignment) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            self.in_assignment_target = false;
+        }
+        Ok(())
+    }
+
+    fn function_call(&mut self, _arg: &FunctionCall) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            self.in_assignment_target = false;
+        }
+        Ok(())
+    }
+
+    fn assign_declaration(&mut self, arg: &AssignDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let is_pipeline = symbol_table::resolve(arg.hierarchical_identifier.as_ref())
+                .is_ok_and(|x| pipeline_attribute(&x.found).is_some());
+            if is_pipeline {
+                if let Ok(path) = VarRefPath::try_from(arg.hierarchical_identifier.as_ref()) {
+                    self.check_stage(&path, true, &arg.hierarchical_identifier.as_ref().into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn expression_identifier(&mut self, arg: &ExpressionIdentifier) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let is_pipeline =
+                symbol_table::resolve(arg).is_ok_and(|x| pipeline_attribute(&x.found).is_some());
+            if is_pipeline {
+                if let Ok(path) = VarRefPath::try_from(arg) {
+                    self.check_stage(&path, self.in_assignment_target, &arg.into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use crate::analyzer_error::AnalyzerError;
+use crate::attribute::Attribute as Attr;
+use crate::attribute_table;
+use crate::namespace_table;
+use crate::symbol::{Direction as SymDirection, SymbolKind};
+use crate::symbol_path::SymbolPath;
+use crate::symbol_table;
+use veryl_parser::resource_table;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, TokenRange};
+use veryl_parser::veryl_walker::{Handler, HandlerPoint};
+use veryl_parser::ParolError;
+
+pub struct CheckProtocol<'a> {
+    pub errors: Vec<AnalyzerError>,
+    text: &'a str,
+    point: HandlerPoint,
+}
+
+impl<'a> CheckProtocol<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            errors: Vec::new(),
+            text,
+            point: HandlerPoint::Before,
+        }
+    }
+
+    fn push_error(&mut self, checker: &Token, reason: &str, range: &TokenRange) {
+        self.errors.push(AnalyzerError::invalid_protocol(
+            &checker.to_string(),
+            reason,
+            self.text,
+            range,
+        ));
+    }
+}
+
+impl<'a> Handler for CheckProtocol<'a> {
+    fn set_point(&mut self, p: HandlerPoint) {
+        self.point = p;
+    }
+}
+
+impl<'a> VerylGrammarTrait for CheckProtocol<'a> {
+    fn interface_declaration(&mut self, arg: &InterfaceDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let Ok(interface) = symbol_table::resolve(arg.identifier.as_ref()) else {
+                return Ok(());
+            };
+            let interface_namespace = interface.found.inner_namespace();
+            let range: TokenRange = arg.identifier.as_ref().into();
+
+            for attr in attribute_table::get(&arg.identifier.identifier_token.token) {
+                let Attr::Protocol(checker) = attr else {
+                    continue;
+                };
+                let Some(namespace) = namespace_table::get(checker.id) else {
+                    continue;
+                };
+                let ports =
+                    match symbol_table::resolve((&SymbolPath::new(&[checker.text]), &namespace)) {
+                        Ok(x) => match x.found.kind {
+                            SymbolKind::Module(ref y) => {
+                                symbol_table::add_reference(x.found.id, &checker);
+                                y.ports.clone()
+                            }
+                            _ => {
+                                self.push_error(&checker, "checker should be a module", &range);
+                                continue;
+                            }
+                        },
+                        Err(_) => {
+                            self.push_error(&checker, "checker is not found", &range);
+                            continue;
+                        }
+                    };
+
+                // Checker ports are connected to the interface members by name through `bind`
+                for port in &ports {
+                    let name = resource_table::get_str_value(port.name).unwrap();
+                    let is_input = symbol_table::get(port.symbol).is_some_and(|x| {
+                        matches!(x.kind, SymbolKind::Port(ref x) if x.direction == SymDirection::Input)
+                    });
+                    if !is_input {
+                        let reason = format!("port {name} should be input");
+                        self.push_error(&checker, &reason, &range);
+                        continue;
+                    }
+
+                    let member = symbol_table::resolve((
+                        &SymbolPath::new(&[port.name]),
+                        &interface_namespace,
+                    ));
+                    let member = member.ok().filter(|x| {
+                        x.found.namespace == interface_namespace
+                            && matches!(
+                                x.found.kind,
+                                SymbolKind::Variable(_) | SymbolKind::Parameter(_)
+                            )
+                    });
+                    if let Some(member) = member {
+                        symbol_table::add_reference(member.found.id, &checker);
+                    } else {
+                        let reason = format!(
+                            "port {name} doesn't have the corresponding member in {}",
+                            interface.found.token
+                        );
+                        self.push_error(&checker, &reason, &range);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use crate::analyzer_error::AnalyzerError;
+use crate::attribute::{Attribute as Attr, EnumEncodingItem};
+use crate::attribute_table;
+use crate::symbol::{Symbol, SymbolKind};
+use crate::symbol_table;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, TokenRange};
+use veryl_parser::veryl_walker::{Handler, HandlerPoint};
+use veryl_parser::ParolError;
+
+#[derive(Default)]
+pub struct CheckSafety<'a> {
+    pub errors: Vec<AnalyzerError>,
+    text: &'a str,
+    point: HandlerPoint,
+    in_always_ff: bool,
+}
+
+impl<'a> CheckSafety<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            ..Default::default()
+        }
+    }
+
+    fn push_error(&mut self, identifier: &Token, reason: &str, range: &TokenRange) {
+        self.errors.push(AnalyzerError::invalid_parity(
+            &identifier.to_string(),
+            reason,
+            self.text,
+            range,
+        ));
+    }
+}
+
+fn is_parity(symbol: &Symbol) -> bool {
+    attribute_table::get(&symbol.token)
+        .iter()
+        .any(|x| matches!(x, Attr::Parity))
+}
+
+impl<'a> Handler for CheckSafety<'a> {
+    fn set_point(&mut self, p: HandlerPoint) {
+        self.point = p;
+    }
+}
+
+impl<'a> VerylGrammarTrait for CheckSafety<'a> {
+    fn enum_declaration(&mut self, arg: &EnumDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let attrs = attribute_table::get(&arg.identifier.identifier_token.token);
+            let is_fsm = attrs.iter().any(|x| matches!(x, Attr::Fsm));
+            let is_safe = attrs.iter().any(|x| {
+                matches!(
+                    x,
+                    Attr::EnumEncoding(EnumEncodingItem::OneHot | EnumEncodingItem::Gray)
+                )
+            });
+            if is_fsm && !is_safe {
+                self.errors.push(AnalyzerError::unsafe_fsm_encoding(
+                    &arg.identifier.identifier_token.to_string(),
+                    self.text,
+                    &arg.identifier.as_ref().into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn var_declaration(&mut self, arg: &VarDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let Ok(symbol) = symbol_table::resolve(arg.identifier.as_ref()) else {
+                return Ok(());
+            };
+            if !is_parity(&symbol.found) {
+                return Ok(());
+            }
+            if let SymbolKind::Variable(ref x) = symbol.found.kind {
+                if !x.r#type.array.is_empty() {
+                    self.push_error(
+                        &symbol.found.token,
+                        "array can't be protected by parity",
+                        &arg.identifier.as_ref().into(),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn always_ff_declaration(&mut self, _arg: &AlwaysFfDeclaration) -> Result<(), ParolError> {
+        match self.point {
+            HandlerPoint::Before => self.in_always_ff = true,
+            HandlerPoint::After => self.in_always_ff = false,
+        }
+        Ok(())
+    }
+
+    fn assign_declaration(&mut self, arg: &AssignDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            if let Ok(symbol) = symbol_table::resolve(arg.hierarchical_identifier.as_ref()) {
+                if is_parity(&symbol.found) {
+                    self.push_error(
+                        &symbol.found.token,
+                        "it should be assigned in always_ff",
+                        &arg.hierarchical_identifier.as_ref().into(),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn identifier_statement(&mut self, arg: &IdentifierStatement) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let IdentifierStatementGroup::Assignment(_) = &*arg.identifier_statement_group else {
+                return Ok(());
+            };
+            let ident = arg.expression_identifier.as_ref();
+            let Ok(symbol) = symbol_table::resolve(ident) else {
+                return Ok(());
+            };
+            if !is_parity(&symbol.found) {
+                return Ok(());
+            }
+
+            let range: TokenRange = ident.into();
+            if !self.in_always_ff {
+                self.push_error(
+                    &symbol.found.token,
+                    "it should be assigned in always_ff",
+                    &range,
+                );
+            }
+            // Parity is calculated from the whole value, so partial assignment can't update it
+            if !ident.expression_identifier_list.is_empty()
+                || !ident.expression_identifier_list0.is_empty()
+            {
+                self.push_error(
+                    &symbol.found.token,
+                    "only the whole value can be assigned",
+                    &range,
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -5,9 +5,10 @@ use crate::attribute_table;
 use crate::namespace_table;
 use crate::symbol::{GenericBoundKind, Symbol, SymbolKind, TypeKind};
 use crate::symbol_path::GenericSymbolPath;
-use crate::symbol_table;
+use crate::symbol_table::{self, ResolveResult};
 use veryl_parser::resource_table;
 use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, TokenRange};
 use veryl_parser::veryl_walker::{Handler, HandlerPoint, VerylWalker};
 use veryl_parser::{ParolError, Stringifier};
 
@@ -21,6 +22,7 @@ pub struct CheckType<'a> {
     in_casting_type: Vec<()>,
     in_generic_argument: Vec<()>,
     in_modport: bool,
+    in_inst_parameter: Option<Token>,
 }
 
 impl<'a> CheckType<'a> {
@@ -32,6 +34,29 @@ impl<'a> CheckType<'a> {
     }
 }
 
+impl<'a> CheckType<'a> {
+    fn check_param_elaborative(&mut self, symbol: &ResolveResult, range: &TokenRange) {
+        let Some(ref param) = self.in_inst_parameter else {
+            return;
+        };
+        let non_elaborative = symbol.full_path.iter().any(|x| {
+            symbol_table::get(*x).is_some_and(|x| match x.kind {
+                SymbolKind::Variable(ref x) => !x.loop_variable,
+                SymbolKind::Port(_) => true,
+                _ => false,
+            })
+        });
+        if non_elaborative {
+            self.errors
+                .push(AnalyzerError::invalid_param_non_elaborative(
+                    &param.to_string(),
+                    self.text,
+                    range,
+                ));
+        }
+    }
+}
+
 impl<'a> Handler for CheckType<'a> {
     fn set_point(&mut self, p: HandlerPoint) {
         self.point = p;
@@ -246,6 +271,33 @@ impl<'a> VerylGrammarTrait for CheckType<'a> {
         Ok(())
     }
 
+    fn inst_parameter_item(&mut self, arg: &InstParameterItem) -> Result<(), ParolError> {
+        match self.point {
+            HandlerPoint::Before => {
+                self.in_inst_parameter = Some(arg.identifier.identifier_token.token);
+                // `A` is a shorthand of `A: A`
+                if arg.inst_parameter_item_opt.is_none() {
+                    if let Ok(symbol) = symbol_table::resolve(arg.identifier.as_ref()) {
+                        self.check_param_elaborative(&symbol, &arg.identifier.as_ref().into());
+                    }
+                }
+            }
+            HandlerPoint::After => self.in_inst_parameter = None,
+        }
+        Ok(())
+    }
+
+    fn expression_identifier(&mut self, arg: &ExpressionIdentifier) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            if self.in_inst_parameter.is_some() {
+                if let Ok(symbol) = symbol_table::resolve(arg) {
+                    self.check_param_elaborative(&symbol, &arg.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn inst_declaration(&mut self, arg: &InstDeclaration) -> Result<(), ParolError> {
         if let HandlerPoint::Before = self.point {
             let mut connected_params = Vec::new();
@@ -253,7 +305,10 @@ impl<'a> VerylGrammarTrait for CheckType<'a> {
                 if let Some(ref x) = x.inst_parameter.inst_parameter_opt {
                     let items: Vec<InstParameterItem> = x.inst_parameter_list.as_ref().into();
                     for item in items {
-                        connected_params.push(item.identifier.identifier_token.token.text);
+                        connected_params.push((
+                            item.identifier.identifier_token.token.text,
+                            TokenRange::from(item.identifier.as_ref()),
+                        ));
                     }
                 }
             }
@@ -342,14 +397,53 @@ impl<'a> VerylGrammarTrait for CheckType<'a> {
                     ));
                 }
 
+                // Simulation only module can be instantiated in simulation only code
+                let module = match symbol.found.kind {
+                    SymbolKind::Module(_) => Some(symbol.found.clone()),
+                    SymbolKind::GenericInstance(ref x) => symbol_table::get(x.base)
+                        .filter(|x| matches!(x.kind, SymbolKind::Module(_))),
+                    _ => None,
+                };
+                if let Some(module) = module {
+                    if attribute_table::contains(&module.token, Attr::SimOnly)
+                        && !attribute_table::contains(&arg.inst.inst_token.token, Attr::SimOnly)
+                    {
+                        self.errors.push(AnalyzerError::invalid_sim_only_instance(
+                            name,
+                            self.text,
+                            &arg.identifier.as_ref().into(),
+                        ));
+                    }
+                }
+
                 if check_port_connection {
+                    let auto_connect =
+                        attribute_table::contains(&arg.inst.inst_token.token, Attr::AutoConnect);
+                    let namespace =
+                        namespace_table::get(arg.identifier.identifier_token.token.id).unwrap();
                     for port in &ports {
-                        if !connected_ports.contains(&port.name)
-                            && !attribute_table::contains(
-                                &arg.inst.inst_token.token,
-                                Attr::Allow(AllowItem::MissingPort),
-                            )
-                        {
+                        if connected_ports.contains(&port.name) {
+                            continue;
+                        }
+                        if auto_connect {
+                            match port.auto_connect_target(&namespace) {
+                                Some(Ok(_)) => continue,
+                                Some(Err(reason)) => {
+                                    self.errors.push(AnalyzerError::invalid_auto_connect(
+                                        &port.name.to_string(),
+                                        &reason,
+                                        self.text,
+                                        &arg.identifier.as_ref().into(),
+                                    ));
+                                    continue;
+                                }
+                                None => (),
+                            }
+                        }
+                        if !attribute_table::contains(
+                            &arg.inst.inst_token.token,
+                            Attr::Allow(AllowItem::MissingPort),
+                        ) {
                             let port = resource_table::get_str_value(port.name).unwrap();
                             self.errors.push(AnalyzerError::missing_port(
                                 name,
@@ -359,15 +453,11 @@ impl<'a> VerylGrammarTrait for CheckType<'a> {
                             ));
                         }
                     }
-                    for param in &connected_params {
+                    for (param, range) in &connected_params {
                         if !params.iter().any(|x| &x.name == param) {
                             let param = resource_table::get_str_value(*param).unwrap();
-                            self.errors.push(AnalyzerError::unknown_param(
-                                name,
-                                &param,
-                                self.text,
-                                &arg.identifier.as_ref().into(),
-                            ));
+                            self.errors
+                                .push(AnalyzerError::unknown_param(name, &param, self.text, range));
                         }
                     }
                     for port in &connected_ports {
@@ -2,7 +2,8 @@ use crate::analyzer_error::AnalyzerError;
 use crate::attribute::AllowItem;
 use crate::attribute::Attribute as Attr;
 use crate::attribute_table;
-use crate::symbol::{Direction, SymbolId, SymbolKind, TypeKind};
+use crate::symbol::{auto_connections, Direction, SymbolId, SymbolKind, TypeKind};
+use crate::symbol_path::GenericSymbolPath;
 use crate::symbol_table;
 use crate::var_ref::{
     AssignDeclarationType, AssignPosition, AssignPositionType, AssignStatementBranchItemType,
@@ -247,12 +248,22 @@ impl<'a> VerylGrammarTrait for CheckVarRef<'a> {
         match self.point {
             HandlerPoint::Before => {
                 self.branch_index = 0;
-                let branches = 1 + arg.if_statement_list.len() + arg.if_statement_opt.iter().len();
+                let mut items = vec![arg.r#if.if_token.token];
+                items.extend(
+                    arg.if_statement_list
+                        .iter()
+                        .map(|x| x.r#else.else_token.token),
+                );
+                items.extend(
+                    arg.if_statement_opt
+                        .iter()
+                        .map(|x| x.r#else.else_token.token),
+                );
                 let has_default = arg.if_statement_opt.is_some();
                 self.assign_position
                     .push(AssignPositionType::StatementBranch {
                         token: arg.r#if.if_token.token,
-                        branches,
+                        items,
                         has_default,
                         allow_missing_reset_statement: false,
                         r#type: AssignStatementBranchType::If,
@@ -277,8 +288,17 @@ impl<'a> VerylGrammarTrait for CheckVarRef<'a> {
         match self.point {
             HandlerPoint::Before => {
                 self.branch_index = 0;
-                let branches =
-                    1 + arg.if_reset_statement_list.len() + arg.if_reset_statement_opt.iter().len();
+                let mut items = vec![arg.if_reset.if_reset_token.token];
+                items.extend(
+                    arg.if_reset_statement_list
+                        .iter()
+                        .map(|x| x.r#else.else_token.token),
+                );
+                items.extend(
+                    arg.if_reset_statement_opt
+                        .iter()
+                        .map(|x| x.r#else.else_token.token),
+                );
                 let has_default = arg.if_reset_statement_opt.is_some();
                 let allow_missing_reset_statement = attribute_table::contains(
                     &arg.if_reset.if_reset_token.token,
@@ -287,7 +307,7 @@ impl<'a> VerylGrammarTrait for CheckVarRef<'a> {
                 self.assign_position
                     .push(AssignPositionType::StatementBranch {
                         token: arg.if_reset.if_reset_token.token,
-                        branches,
+                        items,
                         has_default,
                         allow_missing_reset_statement,
                         r#type: AssignStatementBranchType::IfReset,
@@ -325,7 +345,11 @@ impl<'a> VerylGrammarTrait for CheckVarRef<'a> {
         match self.point {
             HandlerPoint::Before => {
                 self.branch_index = 0;
-                let branches = arg.case_statement_list.len();
+                let items = arg
+                    .case_statement_list
+                    .iter()
+                    .map(|x| x.case_item.colon.colon_token.token)
+                    .collect();
                 let has_default = arg.case_statement_list.iter().any(|x| {
                     matches!(
                         x.case_item.case_item_group.as_ref(),
@@ -335,7 +359,7 @@ impl<'a> VerylGrammarTrait for CheckVarRef<'a> {
                 self.assign_position
                     .push(AssignPositionType::StatementBranch {
                         token: arg.case.case_token.token,
-                        branches,
+                        items,
                         has_default,
                         allow_missing_reset_statement: false,
                         r#type: AssignStatementBranchType::Case,
@@ -453,7 +477,10 @@ impl<'a> VerylGrammarTrait for CheckVarRef<'a> {
                     let mut port_unknown = false;
                     let mut sv_instance = false;
 
-                    if let Ok(x) = symbol_table::resolve((&x.type_name, &symbol.found.namespace)) {
+                    // Generic instance is not created yet, so ports are taken from the generic module
+                    let type_path: GenericSymbolPath = arg.scoped_identifier.as_ref().into();
+                    let type_path = type_path.generic_path();
+                    if let Ok(x) = symbol_table::resolve((&type_path, &symbol.found.namespace)) {
                         match x.found.kind {
                             SymbolKind::Module(ref x) => {
                                 for port in &x.ports {
@@ -556,6 +583,20 @@ impl<'a> VerylGrammarTrait for CheckVarRef<'a> {
                             }
                         }
                     }
+
+                    // Check assignment from output port connected by `#[auto_connect]`
+                    for (port, target) in auto_connections(arg) {
+                        if matches!(
+                            port.property().direction,
+                            Direction::Inout | Direction::Output
+                        ) {
+                            self.assign_position.push(AssignPositionType::Connect {
+                                token: arg.identifier.identifier_token.token,
+                                maybe: false,
+                            });
+                            self.add_assign(&VarRefPath::new((&target.id).into()));
+                        }
+                    }
                 }
             }
         }
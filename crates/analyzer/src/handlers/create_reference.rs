@@ -1,12 +1,13 @@
 use crate::analyzer_error::AnalyzerError;
+use crate::evaluator::{Evaluated, Evaluator, OverridableCollector};
 use crate::namespace::Namespace;
 use crate::namespace_table;
-use crate::symbol::{GenericMap, SymbolKind};
+use crate::symbol::{auto_connections, GenericMap, Symbol, SymbolKind};
 use crate::symbol_path::{GenericSymbolPath, SymbolPath};
 use crate::symbol_table::{self, ResolveError, ResolveErrorCause};
 use veryl_parser::veryl_grammar_trait::*;
 use veryl_parser::veryl_token::{Token, TokenRange};
-use veryl_parser::veryl_walker::{Handler, HandlerPoint};
+use veryl_parser::veryl_walker::{Handler, HandlerPoint, VerylWalker};
 use veryl_parser::ParolError;
 
 #[derive(Default)]
@@ -63,6 +64,80 @@ impl<'a> CreateReference<'a> {
         }
     }
 
+    /// Checks that constant indices of array are within the array size
+    fn check_array_index(&mut self, symbol: &Symbol, selects: &[&Select], token: &TokenRange) {
+        let array = match &symbol.kind {
+            SymbolKind::Variable(x) => &x.r#type.array,
+            SymbolKind::Parameter(x) => &x.r#type.array,
+            SymbolKind::StructMember(x) => &x.r#type.array,
+            SymbolKind::UnionMember(x) => &x.r#type.array,
+            SymbolKind::Port(x) => match &x.r#type {
+                Some(x) => &x.array,
+                None => return,
+            },
+            _ => return,
+        };
+
+        for (size, select) in array.iter().zip(selects) {
+            // Range select is not checked because it may be reversed
+            if select.select_opt.is_some() {
+                continue;
+            }
+            // Size depending on overridable parameter is unknown until instantiation
+            let mut collector = OverridableCollector::default();
+            collector.expression(size);
+            if collector.found {
+                continue;
+            }
+
+            let size = Evaluator::new().expression(size);
+            let index = Evaluator::new().expression(&select.expression);
+            if let (Evaluated::Fixed { value: size, .. }, Evaluated::Fixed { value: index, .. }) =
+                (size, index)
+            {
+                if index < 0 || index >= size {
+                    self.errors.push(AnalyzerError::out_of_range_index(
+                        &symbol.token.to_string(),
+                        index,
+                        size,
+                        self.text,
+                        token,
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_hierarchical_index(&mut self, arg: &HierarchicalIdentifier) {
+        let ident = arg.identifier.identifier_token.token;
+        let Some(namespace) = namespace_table::get(ident.id) else {
+            return;
+        };
+        let token: TokenRange = arg.into();
+        let mut path = SymbolPath::new(&[ident.text]);
+
+        if let Ok(symbol) = symbol_table::resolve((&path, &namespace)) {
+            let selects: Vec<_> = arg
+                .hierarchical_identifier_list
+                .iter()
+                .map(|x| x.select.as_ref())
+                .collect();
+            self.check_array_index(&symbol.found, &selects, &token);
+        }
+
+        for x in &arg.hierarchical_identifier_list0 {
+            path.push(x.identifier.identifier_token.token.text);
+            if let Ok(symbol) = symbol_table::resolve((&path, &namespace)) {
+                let selects: Vec<_> = x
+                    .hierarchical_identifier_list0_list
+                    .iter()
+                    .map(|x| x.select.as_ref())
+                    .collect();
+                self.check_array_index(&symbol.found, &selects, &token);
+            }
+        }
+    }
+
     fn generic_symbol_path(
         &mut self,
         path: &GenericSymbolPath,
@@ -159,6 +234,7 @@ impl<'a> VerylGrammarTrait for CreateReference<'a> {
                     for id in symbol.full_path {
                         symbol_table::add_reference(id, &arg.identifier.identifier_token.token);
                     }
+                    self.check_hierarchical_index(arg);
                 }
                 Err(err) => {
                     let is_single_identifier = SymbolPath::from(arg).as_slice().len() == 1;
@@ -192,6 +268,16 @@ impl<'a> VerylGrammarTrait for CreateReference<'a> {
             let ident = arg.identifier().token;
             let mut path: SymbolPath = arg.scoped_identifier.as_ref().into();
             let namespace = namespace_table::get(ident.id).unwrap();
+            let token: TokenRange = arg.into();
+
+            if let Ok(symbol) = symbol_table::resolve((&path, &namespace)) {
+                let selects: Vec<_> = arg
+                    .expression_identifier_list
+                    .iter()
+                    .map(|x| x.select.as_ref())
+                    .collect();
+                self.check_array_index(&symbol.found, &selects, &token);
+            }
 
             for x in &arg.expression_identifier_list0 {
                 path.push(x.identifier.identifier_token.token.text);
@@ -199,9 +285,17 @@ impl<'a> VerylGrammarTrait for CreateReference<'a> {
                 match symbol_table::resolve((&path, &namespace)) {
                     Ok(symbol) => {
                         symbol_table::add_reference(symbol.found.id, &ident);
+                        let selects: Vec<_> = x
+                            .expression_identifier_list0_list
+                            .iter()
+                            .map(|x| x.select.as_ref())
+                            .collect();
+                        self.check_array_index(&symbol.found, &selects, &token);
                     }
                     Err(err) => {
-                        self.push_resolve_error(err, &arg.into(), None);
+                        // The following members can't be resolved after the first error
+                        self.push_resolve_error(err, &token, None);
+                        break;
                     }
                 }
             }
@@ -244,6 +338,16 @@ impl<'a> VerylGrammarTrait for CreateReference<'a> {
         Ok(())
     }
 
+    fn inst_declaration(&mut self, arg: &InstDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            // implicit port connection by `#[auto_connect]`
+            for (_, target) in auto_connections(arg) {
+                symbol_table::add_reference(target.id, &arg.identifier.identifier_token.token);
+            }
+        }
+        Ok(())
+    }
+
     fn import_declaration(&mut self, arg: &ImportDeclaration) -> Result<(), ParolError> {
         if let HandlerPoint::Before = self.point {
             let is_wildcard = arg.import_declaration_opt.is_some();
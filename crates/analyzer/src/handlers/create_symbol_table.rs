@@ -126,8 +126,15 @@ impl<'a> CreateSymbolTable<'a> {
         };
         let mut symbol = Symbol::new(token, kind, &self.namespace, public, doc_comment);
 
-        if attribute_table::contains(token, Attr::Allow(AllowItem::UnusedVariable)) {
-            symbol.allow_unused = true;
+        let allow_item = match symbol.kind {
+            SymbolKind::Function(_) => Some(AllowItem::UnusedFunction),
+            SymbolKind::Variable(_) => Some(AllowItem::UnusedVariable),
+            _ => None,
+        };
+        if let Some(x) = allow_item {
+            if attribute_table::contains(token, Attr::Allow(x)) {
+                symbol.allow_unused = true;
+            }
         }
 
         symbol.r#type = r#type;
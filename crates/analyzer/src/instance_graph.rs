@@ -0,0 +1,102 @@
+use crate::namespace::Namespace;
+use crate::symbol::{SymbolId, SymbolKind};
+use crate::symbol_table;
+use std::fmt;
+use veryl_parser::veryl_token::Token;
+
+#[derive(Clone, Debug)]
+pub struct Instance {
+    /// Module or interface including the instance
+    pub parent: SymbolId,
+    /// Instantiated module or interface
+    pub child: SymbolId,
+    /// Instance name
+    pub token: Token,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct InstanceGraph {
+    instances: Vec<Instance>,
+}
+
+impl InstanceGraph {
+    /// Builds instance graph from instance symbols in the symbol table.
+    /// Because instance symbols are created at pass1, all files analyzed by pass1 are included.
+    pub fn new() -> Self {
+        let mut instances = Vec::new();
+        for symbol in symbol_table::get_all() {
+            if let SymbolKind::Instance(ref x) = symbol.kind {
+                if symbol.namespace.paths.len() < 2 {
+                    continue;
+                }
+
+                let owner = vec![symbol.namespace.paths[1]];
+                let mut namespace = Namespace::new();
+                namespace.push(symbol.namespace.paths[0]);
+                let parent = symbol_table::resolve((&owner, &namespace)).ok();
+                let child = symbol_table::resolve((&x.type_name, &symbol.namespace)).ok();
+
+                if let (Some(parent), Some(child)) = (parent, child) {
+                    let child = match child.found.kind {
+                        SymbolKind::GenericInstance(ref x) => symbol_table::get(x.base),
+                        _ => Some(child.found),
+                    };
+                    if let Some(child) = child {
+                        if matches!(child.kind, SymbolKind::Module(_) | SymbolKind::Interface(_)) {
+                            instances.push(Instance {
+                                parent: parent.found.id,
+                                child: child.id,
+                                token: symbol.token,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Self { instances }
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// Returns instances of the given module or interface
+    pub fn get_parents(&self, child: SymbolId) -> Vec<Instance> {
+        self.instances
+            .iter()
+            .filter(|x| x.child == child)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns instances in the given module or interface
+    pub fn get_children(&self, parent: SymbolId) -> Vec<Instance> {
+        self.instances
+            .iter()
+            .filter(|x| x.parent == parent)
+            .cloned()
+            .collect()
+    }
+}
+
+impl fmt::Display for InstanceGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "InstanceGraph [")?;
+        for instance in &self.instances {
+            let parent = symbol_table::get(instance.parent).map(|x| x.token.to_string());
+            let child = symbol_table::get(instance.child).map(|x| x.token.to_string());
+            writeln!(
+                f,
+                "    {} -> {} ({}) @ {}:{}:{}",
+                parent.unwrap_or_default(),
+                child.unwrap_or_default(),
+                instance.token,
+                instance.token.source,
+                instance.token.line,
+                instance.token.column,
+            )?;
+        }
+        writeln!(f, "]")?;
+        Ok(())
+    }
+}
@@ -2,12 +2,16 @@ pub mod analyzer;
 pub mod analyzer_error;
 pub mod attribute;
 pub mod attribute_table;
+pub mod call_graph;
+pub mod context;
 pub mod evaluator;
 pub mod handlers;
+pub mod instance_graph;
 pub mod msb_table;
 pub mod namespace;
 pub mod namespace_table;
 pub mod range_table;
+pub mod structural_hash;
 pub mod symbol;
 pub mod symbol_path;
 pub mod symbol_table;
@@ -16,6 +20,7 @@ pub mod r#unsafe;
 pub mod unsafe_table;
 pub mod var_ref;
 pub use analyzer::Analyzer;
-pub use analyzer_error::AnalyzerError;
+pub use analyzer_error::{AnalyzerError, LintedError};
+pub use context::AnalyzerContext;
 #[cfg(test)]
 mod tests;
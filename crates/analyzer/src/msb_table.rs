@@ -35,3 +35,7 @@ pub fn get(id: TokenId) -> Option<Expression> {
 pub fn clear() {
     MSB_TABLE.with(|f| f.borrow_mut().clear())
 }
+
+pub(crate) fn swap(table: &mut MsbTable) {
+    MSB_TABLE.with(|f| std::mem::swap(&mut *f.borrow_mut(), table))
+}
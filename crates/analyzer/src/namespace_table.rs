@@ -109,3 +109,7 @@ pub fn get_default() -> Namespace {
 pub fn clear() {
     NAMESPACE_TABLE.with(|f| f.borrow_mut().clear())
 }
+
+pub(crate) fn swap(table: &mut NamespaceTable) {
+    NAMESPACE_TABLE.with(|f| std::mem::swap(&mut *f.borrow_mut(), table))
+}
@@ -0,0 +1,116 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use veryl_parser::resource_table::StrId;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, VerylToken};
+use veryl_parser::veryl_walker::VerylWalker;
+
+/// Hash of the structure of a module ignoring its name, comments and formatting
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StructuralHash {
+    /// Hash with identifiers renamed in order of appearance
+    pub exact: u64,
+    /// Hash with literals ignored additionally, which matches modules differing only in constants
+    pub near: u64,
+    /// Number of tokens in the module
+    pub size: usize,
+}
+
+#[derive(Hash)]
+enum Item {
+    Token(StrId),
+    Identifier(usize),
+    Literal(StrId),
+}
+
+#[derive(Default)]
+struct Collector {
+    items: Vec<Item>,
+    identifiers: HashMap<StrId, usize>,
+}
+
+impl Collector {
+    fn identifier_token(&mut self, token: &Token) {
+        let len = self.identifiers.len();
+        let index = *self.identifiers.entry(token.text).or_insert(len);
+        self.items.push(Item::Identifier(index));
+    }
+
+    fn literal_token(&mut self, token: &Token) {
+        self.items.push(Item::Literal(token.text));
+    }
+}
+
+impl VerylWalker for Collector {
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        self.items.push(Item::Token(arg.token.text));
+    }
+
+    fn identifier(&mut self, arg: &Identifier) {
+        self.identifier_token(&arg.identifier_token.token);
+    }
+
+    fn based(&mut self, arg: &Based) {
+        self.literal_token(&arg.based_token.token);
+    }
+
+    fn base_less(&mut self, arg: &BaseLess) {
+        self.literal_token(&arg.base_less_token.token);
+    }
+
+    fn all_bit(&mut self, arg: &AllBit) {
+        self.literal_token(&arg.all_bit_token.token);
+    }
+
+    fn fixed_point(&mut self, arg: &FixedPoint) {
+        self.literal_token(&arg.fixed_point_token.token);
+    }
+
+    fn exponent(&mut self, arg: &Exponent) {
+        self.literal_token(&arg.exponent_token.token);
+    }
+}
+
+impl From<&ModuleDeclaration> for StructuralHash {
+    fn from(value: &ModuleDeclaration) -> Self {
+        let mut collector = Collector::default();
+        collector.module_declaration(value);
+
+        // `pub` is not a part of the structure, and the module name is always the first identifier
+        if value.module_declaration_opt.is_some() {
+            collector.items.remove(0);
+        }
+
+        let mut exact = DefaultHasher::new();
+        let mut near = DefaultHasher::new();
+        for item in &collector.items {
+            item.hash(&mut exact);
+            match item {
+                Item::Literal(_) => std::mem::discriminant(item).hash(&mut near),
+                _ => item.hash(&mut near),
+            }
+        }
+
+        Self {
+            exact: exact.finish(),
+            near: near.finish(),
+            size: collector.items.len(),
+        }
+    }
+}
+
+/// Structural hashes of modules in `veryl` with the name token of each module
+pub fn module_hashes(veryl: &Veryl) -> Vec<(Token, StructuralHash)> {
+    let mut ret = Vec::new();
+    for x in &veryl.veryl_list {
+        let items: Vec<DescriptionItem> = x.description_group.as_ref().into();
+        for item in items {
+            if let DescriptionItem::ModuleDeclaration(x) = item {
+                let x = &x.module_declaration;
+                ret.push((x.identifier.identifier_token.token, x.as_ref().into()));
+            }
+        }
+    }
+    ret
+}
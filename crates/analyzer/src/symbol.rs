@@ -1,10 +1,12 @@
-use crate::attribute::EnumEncodingItem;
-use crate::evaluator::{Evaluated, Evaluator};
+use crate::attribute::{Attribute as Attr, EnumEncodingItem};
+use crate::attribute_table;
+use crate::evaluator::{Evaluated, Evaluator, OverridableCollector};
 use crate::namespace::Namespace;
+use crate::namespace_table;
 use crate::symbol_path::{GenericSymbolPath, SymbolPath};
 use crate::symbol_table;
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use veryl_parser::resource_table::{PathId, StrId};
 use veryl_parser::veryl_grammar_trait as syntax_tree;
@@ -28,13 +30,50 @@ pub fn new_symbol_id() -> SymbolId {
 #[derive(Debug, Default, Clone)]
 pub struct DocComment(pub Vec<StrId>);
 
+/// Structured tag of doc comment.
+/// Lines following a tag are continuation of it until the next tag.
+///
+/// ```text
+/// /// @param WIDTH Data width
+/// /// @port clk Clock input
+/// /// @example
+/// /// inst u: Module (clk);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DocTag {
+    Param { name: String, description: String },
+    Port { name: String, description: String },
+    Example(String),
+}
+
+fn tag_line(line: &str) -> bool {
+    let line = line.trim_start();
+    ["@param", "@port", "@example"].iter().any(|x| {
+        line.strip_prefix(x)
+            .map(|x| x.is_empty() || x.starts_with(char::is_whitespace))
+            .unwrap_or(false)
+    })
+}
+
 impl DocComment {
+    fn lines(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|x| {
+                let x = format!("{}", x);
+                x.trim_start_matches("///").to_string()
+            })
+            .collect()
+    }
+
+    /// Format doc comment except structured tags
     pub fn format(&self, single_line: bool) -> String {
         let mut ret = String::new();
-        for t in &self.0 {
-            let t = format!("{}", t);
-            let t = t.trim_start_matches("///");
-            ret.push_str(t);
+        for t in self.lines() {
+            if tag_line(&t) {
+                break;
+            }
+            ret.push_str(&t);
             if single_line {
                 break;
             }
@@ -42,11 +81,105 @@ impl DocComment {
         ret
     }
 
+    pub fn tags(&self) -> Vec<DocTag> {
+        let mut ret = Vec::new();
+        for line in self.lines() {
+            let line = line.trim_end_matches(['\r', '\n']);
+            let trimmed = line.trim();
+            if tag_line(line) {
+                let (tag, rest) = trimmed
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((trimmed, ""));
+                let (name, description) = rest
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((rest.trim(), ""));
+                let name = name.to_string();
+                let description = description.trim().to_string();
+                match tag {
+                    "@param" => ret.push(DocTag::Param { name, description }),
+                    "@port" => ret.push(DocTag::Port { name, description }),
+                    _ => ret.push(DocTag::Example(String::new())),
+                }
+                continue;
+            }
+            match ret.last_mut() {
+                Some(DocTag::Param { description, .. })
+                | Some(DocTag::Port { description, .. })
+                    if !trimmed.is_empty() =>
+                {
+                    if !description.is_empty() {
+                        description.push(' ');
+                    }
+                    description.push_str(trimmed);
+                }
+                Some(DocTag::Example(x)) => {
+                    x.push_str(line.strip_prefix(' ').unwrap_or(line));
+                    x.push('\n');
+                }
+                _ => (),
+            }
+        }
+        for tag in &mut ret {
+            if let DocTag::Example(x) = tag {
+                *x = x.trim_matches('\n').to_string();
+            }
+        }
+        ret
+    }
+
+    /// Veryl code blocks in doc comment which should be compilable.
+    /// Examples without fence are treated as Veryl code.
+    pub fn code_blocks(&self) -> Vec<String> {
+        let mut ret = code_blocks(&self.format(false), false);
+        for tag in self.tags() {
+            if let DocTag::Example(x) = tag {
+                if x.contains("```") {
+                    ret.append(&mut code_blocks(&x, true));
+                } else if !x.is_empty() {
+                    ret.push(x);
+                }
+            }
+        }
+        ret
+    }
+
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
 }
 
+/// Extract fenced code blocks marked as `veryl` except `ignore`
+fn code_blocks(text: &str, default_veryl: bool) -> Vec<String> {
+    let mut ret = Vec::new();
+    let mut block: Option<(bool, String)> = None;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            if let Some((target, code)) = block.take() {
+                if target {
+                    ret.push(code);
+                }
+            } else {
+                let attrs: Vec<_> = info
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|x| !x.is_empty())
+                    .collect();
+                let veryl = attrs
+                    .first()
+                    .map(|x| *x == "veryl")
+                    .unwrap_or(default_veryl);
+                let target = veryl && !attrs.contains(&"ignore");
+                block = Some((target, String::new()));
+            }
+        } else if let Some((_, ref mut code)) = block {
+            code.push_str(line);
+            code.push('\n');
+        }
+    }
+    ret
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct GenericMap {
     pub name: String,
@@ -276,6 +409,91 @@ impl Symbol {
             _ => Vec::new(),
         }
     }
+
+    /// Members of modport port which are emitted as individual ports by `#[flatten]`
+    pub fn flattened_members(&self) -> Result<Vec<FlattenedMember>, String> {
+        let SymbolKind::Port(ref port) = self.kind else {
+            return Err("it is not a port".to_string());
+        };
+        let r#type = port.r#type.as_ref();
+        let path = match r#type.map(|x| &x.kind) {
+            Some(TypeKind::UserDefined(x)) if port.direction == Direction::Modport => x,
+            _ => return Err("only modport port can be flattened".to_string()),
+        };
+        if r#type.is_some_and(|x| !x.array.is_empty()) {
+            return Err("array of modport can't be flattened".to_string());
+        }
+        let modport = symbol_table::resolve((path, &self.namespace))
+            .map_err(|_| "modport is not found".to_string())?;
+        let SymbolKind::Modport(ref x) = modport.found.kind else {
+            return Err("only modport port can be flattened".to_string());
+        };
+
+        let mut ret = Vec::new();
+        for member in &x.members {
+            let member = symbol_table::get(*member).unwrap();
+            let name = member.token.to_string();
+            let SymbolKind::ModportVariableMember(ref x) = member.kind else {
+                return Err(format!("function {name} can't be flattened"));
+            };
+            let variable = symbol_table::resolve((&member.token, &modport.found.namespace))
+                .map_err(|_| format!("{name} is not found"))?;
+            let SymbolKind::Variable(ref variable) = variable.found.kind else {
+                return Err(format!("{name} is not a variable"));
+            };
+            let r#type = &variable.r#type;
+            if !r#type.array.is_empty() {
+                return Err(format!("array {name} can't be flattened"));
+            }
+            if !matches!(
+                r#type.kind,
+                TypeKind::Bit
+                    | TypeKind::Logic
+                    | TypeKind::Clock
+                    | TypeKind::ClockPosedge
+                    | TypeKind::ClockNegedge
+                    | TypeKind::Reset
+                    | TypeKind::ResetAsyncHigh
+                    | TypeKind::ResetAsyncLow
+                    | TypeKind::ResetSyncHigh
+                    | TypeKind::ResetSyncLow
+            ) {
+                return Err(format!(
+                    "type of {name} should be bit, logic, clock or reset"
+                ));
+            }
+            let mut width = Vec::new();
+            for x in &r#type.width {
+                let mut collector = OverridableCollector::default();
+                collector.expression(x);
+                if collector.found {
+                    return Err(format!("width of {name} depends on overridable parameter"));
+                }
+                match Evaluator::new().expression(x) {
+                    Evaluated::Fixed { value, .. } if value > 0 => width.push(value as usize),
+                    _ => return Err(format!("width of {name} should be constant")),
+                }
+            }
+            ret.push(FlattenedMember {
+                name: member.token.text,
+                direction: x.direction,
+                kind: r#type.kind.clone(),
+                signed: r#type.modifier.contains(&TypeModifier::Signed),
+                width,
+            });
+        }
+        Ok(ret)
+    }
+}
+
+/// Member of modport port flattened by `#[flatten]`
+#[derive(Debug, Clone)]
+pub struct FlattenedMember {
+    pub name: StrId,
+    pub direction: Direction,
+    pub kind: TypeKind,
+    pub signed: bool,
+    pub width: Vec<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -1005,6 +1223,166 @@ impl Port {
             unreachable!()
         }
     }
+
+    /// Signal connected to the port by `#[auto_connect]`.
+    /// `None` means that there is no signal with the same name in `namespace`.
+    pub fn auto_connect_target(&self, namespace: &Namespace) -> Option<Result<Symbol, String>> {
+        let path = SymbolPath::new(&[self.name]);
+        let target = symbol_table::resolve((&path, namespace)).ok()?.found;
+        Some(self.check_auto_connect(&target).map(|_| target))
+    }
+
+    fn check_auto_connect(&self, target: &Symbol) -> Result<(), String> {
+        let name = self.name;
+        let port = self.property();
+        if !matches!(
+            port.direction,
+            Direction::Input | Direction::Output | Direction::Inout
+        ) {
+            return Err(format!("{} port can't be auto-connected", port.direction));
+        }
+        let Some(port_type) = port.r#type else {
+            return Err("type of port is unknown".to_string());
+        };
+
+        let target_type = match &target.kind {
+            SymbolKind::Variable(x) => x.r#type.clone(),
+            SymbolKind::Port(x) => {
+                if !matches!(
+                    x.direction,
+                    Direction::Input | Direction::Output | Direction::Inout
+                ) {
+                    return Err(format!("{name} is {} port", x.direction));
+                }
+                if x.direction == Direction::Input && port.direction != Direction::Input {
+                    return Err(format!(
+                        "{name} is input port, but it is driven by {} port",
+                        port.direction
+                    ));
+                }
+                let Some(ref x) = x.r#type else {
+                    return Err(format!("type of {name} is unknown"));
+                };
+                x.clone()
+            }
+            _ => return Err(format!("{name} is not a variable or port")),
+        };
+
+        if port_type.kind.is_clock() != target_type.kind.is_clock()
+            || port_type.kind.is_reset() != target_type.kind.is_reset()
+        {
+            return Err(format!("type of {name} is mismatched"));
+        }
+
+        let port_shape = connection_shape(&port_type)
+            .ok_or_else(|| "width of port should be constant".to_string())?;
+        let target_shape = connection_shape(&target_type)
+            .ok_or_else(|| format!("width of {name} should be constant"))?;
+        if port_shape != target_shape {
+            return Err(format!("width of {name} is mismatched"));
+        }
+        Ok(())
+    }
+}
+
+/// Module instantiated by the instance, and namespace of the instance
+fn instantiated_module(arg: &syntax_tree::InstDeclaration) -> Option<(ModuleProperty, Namespace)> {
+    let namespace = namespace_table::get(arg.identifier.identifier_token.token.id)?;
+    let path: GenericSymbolPath = arg.scoped_identifier.as_ref().into();
+    let module = symbol_table::resolve((&path.generic_path(), &namespace)).ok()?;
+    if let SymbolKind::Module(x) = module.found.kind {
+        Some((x, namespace))
+    } else {
+        None
+    }
+}
+
+/// Ports connected explicitly including ones left open by `_`
+fn connected_ports(arg: &syntax_tree::InstDeclaration) -> HashSet<StrId> {
+    let mut ret = HashSet::new();
+    if let Some(ref x) = arg.inst_declaration_opt1 {
+        if let Some(ref x) = x.inst_declaration_opt2 {
+            let items: Vec<syntax_tree::InstPortItem> = x.inst_port_list.as_ref().into();
+            for item in items {
+                ret.insert(item.identifier.identifier_token.token.text);
+            }
+        }
+    }
+    ret
+}
+
+/// Ports of the instance which are not connected explicitly, and signals connected to them by
+/// `#[auto_connect]`
+pub fn auto_connections(arg: &syntax_tree::InstDeclaration) -> Vec<(Port, Symbol)> {
+    let mut ret = Vec::new();
+    if !attribute_table::contains(&arg.inst.inst_token.token, Attr::AutoConnect) {
+        return ret;
+    }
+    let Some((module, namespace)) = instantiated_module(arg) else {
+        return ret;
+    };
+
+    let connected = connected_ports(arg);
+    for port in &module.ports {
+        if !connected.contains(&port.name) {
+            if let Some(Ok(target)) = port.auto_connect_target(&namespace) {
+                ret.push((port.clone(), target));
+            }
+        }
+    }
+    ret
+}
+
+/// Ports of the instance which are connected neither explicitly nor by `#[auto_connect]`
+pub fn unconnected_ports(arg: &syntax_tree::InstDeclaration) -> Vec<Port> {
+    let Some((module, _)) = instantiated_module(arg) else {
+        return Vec::new();
+    };
+
+    let mut connected = connected_ports(arg);
+    for (port, _) in auto_connections(arg) {
+        connected.insert(port.name);
+    }
+    module
+        .ports
+        .into_iter()
+        .filter(|x| !connected.contains(&x.name))
+        .collect()
+}
+
+/// Width and array dimensions of a connected signal.
+/// `None` means that they can't be determined statically.
+fn connection_shape(x: &Type) -> Option<(Vec<usize>, Vec<usize>)> {
+    let dims = |exprs: &[syntax_tree::Expression]| -> Option<Vec<usize>> {
+        let mut ret = Vec::new();
+        for x in exprs {
+            let mut collector = OverridableCollector::default();
+            collector.expression(x);
+            if collector.found {
+                return None;
+            }
+            match Evaluator::new().expression(x) {
+                Evaluated::Fixed { value, .. } if value > 0 => ret.push(value as usize),
+                _ => return None,
+            }
+        }
+        Some(ret)
+    };
+
+    let width = if matches!(x.kind, TypeKind::Bit | TypeKind::Logic)
+        || x.kind.is_clock()
+        || x.kind.is_reset()
+    {
+        let width = dims(&x.width)?;
+        if width.is_empty() {
+            vec![1]
+        } else {
+            width
+        }
+    } else {
+        vec![Evaluator::new().type_width(x.clone())?]
+    };
+    Some((width, dims(&x.array)?))
 }
 
 #[derive(Debug, Clone)]
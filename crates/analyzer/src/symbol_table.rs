@@ -17,7 +17,7 @@ pub struct ResolveResult {
 
 #[derive(Clone, Debug)]
 pub struct ResolveError {
-    pub last_found: Option<Symbol>,
+    pub last_found: Option<Box<Symbol>>,
     pub cause: ResolveErrorCause,
 }
 
@@ -30,7 +30,7 @@ pub enum ResolveErrorCause {
 impl ResolveError {
     pub fn new(last_found: Option<&Symbol>, cause: ResolveErrorCause) -> Self {
         Self {
-            last_found: last_found.cloned(),
+            last_found: last_found.map(|x| Box::new(x.clone())),
             cause,
         }
     }
@@ -46,7 +46,9 @@ pub struct Import {
 #[derive(Clone, Default, Debug)]
 pub struct SymbolTable {
     name_table: HashMap<StrId, Vec<SymbolId>>,
-    symbol_table: HashMap<SymbolId, Symbol>,
+    /// Arena of all symbols which are looked up through `symbol_index`
+    symbols: Vec<Symbol>,
+    symbol_index: HashMap<SymbolId, usize>,
     project_local_table: HashMap<StrId, HashMap<StrId, StrId>>,
     var_ref_list: HashMap<VarRefAffiliation, Vec<VarRef>>,
     import_list: Vec<Import>,
@@ -71,7 +73,10 @@ impl SymbolTable {
             let _ = ret.insert(&token, symbol);
         }
 
-        for func in DEFINED_SYSTEM_FUNCTIONS {
+        for func in DEFINED_SYSTEM_FUNCTIONS
+            .iter()
+            .chain(LOG_SYSTEM_FUNCTIONS.iter())
+        {
             let token = Token::new(func, 0, 0, 0, 0, TokenSource::Builtin);
             let symbol = Symbol::new(
                 &token,
@@ -89,24 +94,36 @@ impl SymbolTable {
     pub fn insert(&mut self, token: &Token, symbol: Symbol) -> Option<SymbolId> {
         let entry = self.name_table.entry(token.text).or_default();
         for id in entry.iter() {
-            let item = self.symbol_table.get(id).unwrap();
+            let item = &self.symbols[self.symbol_index[id]];
             if symbol.namespace == item.namespace {
                 return None;
             }
         }
         let id = symbol.id;
         entry.push(id);
-        self.symbol_table.insert(id, symbol);
+        self.update(symbol);
         Some(id)
     }
 
     pub fn get(&self, id: SymbolId) -> Option<Symbol> {
-        self.symbol_table.get(&id).cloned()
+        self.get_ref(id).cloned()
+    }
+
+    fn get_ref(&self, id: SymbolId) -> Option<&Symbol> {
+        self.symbol_index.get(&id).map(|x| &self.symbols[*x])
+    }
+
+    fn get_mut(&mut self, id: SymbolId) -> Option<&mut Symbol> {
+        self.symbol_index.get(&id).map(|x| &mut self.symbols[*x])
     }
 
     pub fn update(&mut self, symbol: Symbol) {
-        let id = symbol.id;
-        self.symbol_table.insert(id, symbol);
+        if let Some(x) = self.symbol_index.get(&symbol.id) {
+            self.symbols[*x] = symbol;
+        } else {
+            self.symbol_index.insert(symbol.id, self.symbols.len());
+            self.symbols.push(symbol);
+        }
     }
 
     fn trace_user_defined<'a>(
@@ -132,6 +149,10 @@ impl SymbolTable {
             }
             context.namespace = symbol.found.inner_namespace();
             context.inner = true;
+        } else if let Some(last_found) = context.last_found {
+            // Builtin type doesn't have member, so siblings of the symbol should not be resolved
+            context.namespace = last_found.inner_namespace();
+            context.inner = true;
         }
         Ok(context)
     }
@@ -153,7 +174,8 @@ impl SymbolTable {
             }
         }
 
-        for name in path.as_slice() {
+        for (i, name) in path.as_slice().iter().enumerate() {
+            let is_last = i + 1 == path.as_slice().len();
             let mut max_depth = 0;
             context.found = None;
 
@@ -174,7 +196,7 @@ impl SymbolTable {
 
             if let Some(ids) = self.name_table.get(name) {
                 for id in ids {
-                    let symbol = self.symbol_table.get(id).unwrap();
+                    let symbol = self.get_ref(*id).unwrap();
                     let included = if context.inner {
                         context.namespace.matched(&symbol.namespace)
                     } else {
@@ -240,11 +262,13 @@ impl SymbolTable {
                             context.namespace = found.inner_namespace();
                             context.inner = true;
                         }
-                        SymbolKind::Instance(ref x) => {
+                        // Generic instance may not be created yet while analyzing the declaration,
+                        // so the type is traced only if its member is referred
+                        SymbolKind::Instance(ref x) if !is_last => {
                             let path = SymbolPath::new(&x.type_name);
                             let symbol = self.resolve(&path, &context.namespace)?;
                             if let SymbolKind::GenericInstance(x) = &symbol.found.kind {
-                                let symbol = self.symbol_table.get(&x.base).unwrap();
+                                let symbol = self.get_ref(x.base).unwrap();
                                 context.namespace = symbol.inner_namespace();
                                 context.inner = true;
                             } else {
@@ -256,7 +280,7 @@ impl SymbolTable {
                             }
                         }
                         SymbolKind::GenericInstance(ref x) => {
-                            let symbol = self.symbol_table.get(&x.base).unwrap();
+                            let symbol = self.get_ref(x.base).unwrap();
                             context.namespace = symbol.inner_namespace();
                             context.inner = true;
                             context
@@ -268,7 +292,8 @@ impl SymbolTable {
                             context.inner = true;
                         }
                         // don't trace inner item
-                        SymbolKind::Function(_)
+                        SymbolKind::Instance(_)
+                        | SymbolKind::Function(_)
                         | SymbolKind::ProtoModule(_)
                         | SymbolKind::Struct(_)
                         | SymbolKind::Union(_)
@@ -315,15 +340,24 @@ impl SymbolTable {
 
     pub fn get_all(&self) -> Vec<Symbol> {
         let mut ret = Vec::new();
-        for symbol in self.symbol_table.values() {
+        for symbol in &self.symbols {
             symbol.evaluate();
             ret.push(symbol.clone());
         }
         ret
     }
 
+    pub fn for_each_in<F: FnMut(&Symbol)>(&self, file_path: PathId, mut f: F) {
+        for symbol in &self.symbols {
+            if symbol.token.source == file_path {
+                symbol.evaluate();
+                f(symbol);
+            }
+        }
+    }
+
     pub fn dump(&self) -> String {
-        for symbol in self.symbol_table.values() {
+        for symbol in &self.symbols {
             symbol.evaluate();
         }
         format!("{self}")
@@ -361,45 +395,45 @@ impl SymbolTable {
 
     pub fn drop(&mut self, file_path: PathId) {
         let drop_list: Vec<_> = self
-            .symbol_table
+            .symbols
             .iter()
-            .filter(|x| x.1.token.source == file_path)
-            .map(|x| *x.0)
+            .filter(|x| x.token.source == file_path)
+            .map(|x| x.id)
             .collect();
 
-        for id in &drop_list {
-            self.symbol_table.remove(id);
-        }
+        self.symbols.retain(|x| x.token.source != file_path);
+        self.symbol_index = self
+            .symbols
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.id, i))
+            .collect();
 
         for (_, symbols) in self.name_table.iter_mut() {
             symbols.retain(|x| !drop_list.contains(x));
         }
 
-        for (_, symbol) in self.symbol_table.iter_mut() {
+        for symbol in self.symbols.iter_mut() {
             symbol.references.retain(|x| x.source != file_path);
         }
     }
 
     pub fn add_reference(&mut self, target: SymbolId, token: &Token) {
-        for (_, symbol) in self.symbol_table.iter_mut() {
-            if symbol.id == target {
-                symbol.references.push(token.to_owned());
-                break;
-            }
+        if let Some(symbol) = self.get_mut(target) {
+            symbol.references.push(token.to_owned());
         }
     }
 
     pub fn add_generic_instance(&mut self, target: SymbolId, instance: SymbolId) {
-        for (_, symbol) in self.symbol_table.iter_mut() {
-            if symbol.id == target && !symbol.generic_instances.contains(&instance) {
+        if let Some(symbol) = self.get_mut(target) {
+            if !symbol.generic_instances.contains(&instance) {
                 symbol.generic_instances.push(instance);
-                break;
             }
         }
     }
 
     fn add_imported_item(&mut self, target: TokenId, namespace: &Namespace) {
-        for (_, symbol) in self.symbol_table.iter_mut() {
+        for symbol in self.symbols.iter_mut() {
             if symbol.token.id == target {
                 symbol.imported.push(namespace.to_owned());
             }
@@ -407,7 +441,7 @@ impl SymbolTable {
     }
 
     fn add_imported_package(&mut self, target: &Namespace, namespace: &Namespace) {
-        for (_, symbol) in self.symbol_table.iter_mut() {
+        for symbol in self.symbols.iter_mut() {
             if symbol.namespace.matched(target) {
                 symbol.imported.push(namespace.to_owned());
             }
@@ -490,7 +524,7 @@ impl fmt::Display for SymbolTable {
         for (k, v) in &vec {
             symbol_width = symbol_width.max(format!("{k}").len());
             for id in *v {
-                let symbol = self.symbol_table.get(id).unwrap();
+                let symbol = self.get_ref(*id).unwrap();
                 namespace_width = namespace_width.max(format!("{}", symbol.namespace).len());
                 reference_width = reference_width.max(format!("{}", symbol.references.len()).len());
                 import_width = import_width.max(format!("{}", symbol.imported.len()).len());
@@ -498,7 +532,7 @@ impl fmt::Display for SymbolTable {
         }
         for (k, v) in &vec {
             for id in *v {
-                let symbol = self.symbol_table.get(id).unwrap();
+                let symbol = self.get_ref(*id).unwrap();
                 let evaluated = if let Some(evaluated) = symbol.evaluated.get() {
                     match evaluated {
                         Evaluated::Unknown => "".to_string(),
@@ -814,6 +848,9 @@ pub fn is_sv_keyword(s: &str) -> bool {
     SYSTEMVERILOG_KEYWORDS.binary_search(&s).is_ok()
 }
 
+// Logging functions lowered according to `log_style` of `[build]`
+const LOG_SYSTEM_FUNCTIONS: [&str; 4] = ["$log_error", "$log_fatal", "$log_info", "$log_warning"];
+
 // Refer IEEE Std 1800-2012  Clause 20 and 21
 const DEFINED_SYSTEM_FUNCTIONS: [&str; 196] = [
     "$acos",
@@ -1037,6 +1074,12 @@ pub fn get_all() -> Vec<Symbol> {
     SYMBOL_TABLE.with(|f| f.borrow().get_all())
 }
 
+/// Calls `f` with each symbol defined in `file_path` without cloning it.
+/// The symbol table must not be modified in `f`.
+pub fn for_each_in<F: FnMut(&Symbol)>(file_path: PathId, f: F) {
+    SYMBOL_TABLE.with(|x| x.borrow().for_each_in(file_path, f))
+}
+
 pub fn dump() -> String {
     SYMBOL_TABLE.with(|f| f.borrow().dump())
 }
@@ -1089,6 +1132,10 @@ pub fn clear() {
     SYMBOL_TABLE.with(|f| f.borrow_mut().clear())
 }
 
+pub(crate) fn swap(table: &mut SymbolTable) {
+    SYMBOL_TABLE.with(|f| std::mem::swap(&mut *f.borrow_mut(), table))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::namespace::Namespace;
@@ -1165,9 +1212,9 @@ mod tests {
     fn parse() {
         let metadata: Metadata =
             toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
-        let parser = Parser::parse(&CODE, &"").unwrap();
+        let parser = Parser::parse(CODE, &"").unwrap();
         let analyzer = Analyzer::new(&metadata);
-        analyzer.analyze_pass1(&"prj", &CODE, &"", &parser.veryl);
+        analyzer.analyze_pass1("prj", CODE, "", &parser.veryl);
     }
 
     #[track_caller]
@@ -1539,4 +1586,34 @@ mod tests {
         let symbol = resolve(&["instA", "memberB", "memberB", "memberA"], &["ModuleA"]);
         check_found(symbol, "prj::PackageA::StructB");
     }
+
+    #[test]
+    fn drop_file() {
+        parse();
+
+        let code = "module ModuleB { var memberA: logic; }";
+        let metadata: Metadata =
+            toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+        let parser = Parser::parse(code, &"b.veryl").unwrap();
+        let analyzer = Analyzer::new(&metadata);
+        analyzer.analyze_pass1("prj", code, "b.veryl", &parser.veryl);
+
+        let path_a = resource_table::insert_path(std::path::Path::new(""));
+        let path_b = resource_table::insert_path(std::path::Path::new("b.veryl"));
+        let mut names = Vec::new();
+        symbol_table::for_each_in(path_b, |x| names.push(x.token.to_string()));
+        names.sort();
+        assert_eq!(names, ["ModuleB", "memberA"]);
+
+        symbol_table::drop(path_a);
+
+        check_not_found(resolve(&["ModuleA"], &[]));
+        let symbol = resolve(&["memberA"], &["ModuleB"]).unwrap().found;
+        check_found(resolve(&["memberA"], &["ModuleB"]), "prj::ModuleB");
+        assert_eq!(
+            symbol_table::get(symbol.id).unwrap().token.to_string(),
+            "memberA"
+        );
+        symbol_table::for_each_in(path_a, |_| unreachable!());
+    }
 }
@@ -1,21 +1,24 @@
-use crate::{symbol_table, Analyzer, AnalyzerError};
-use veryl_metadata::Metadata;
+use crate::instance_graph::InstanceGraph;
+use crate::{attribute_table, symbol_table, Analyzer, AnalyzerError};
+use miette::Severity;
+use veryl_metadata::{Lint, LintSeverity, Metadata};
 use veryl_parser::Parser;
 
 #[track_caller]
 fn analyze(code: &str) -> Vec<AnalyzerError> {
     symbol_table::clear();
+    attribute_table::clear();
 
     let metadata: Metadata =
         toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
-    let parser = Parser::parse(&code, &"").unwrap();
+    let parser = Parser::parse(code, &"").unwrap();
     let analyzer = Analyzer::new(&metadata);
 
     let mut errors = vec![];
-    errors.append(&mut analyzer.analyze_pass1(&"prj", &code, &"", &parser.veryl));
+    errors.append(&mut analyzer.analyze_pass1("prj", code, "", &parser.veryl));
     Analyzer::analyze_post_pass1();
-    errors.append(&mut analyzer.analyze_pass2(&"prj", &code, &"", &parser.veryl));
-    errors.append(&mut analyzer.analyze_pass3(&"prj", &code, &"", &parser.veryl));
+    errors.append(&mut analyzer.analyze_pass2("prj", code, "", &parser.veryl));
+    errors.append(&mut analyzer.analyze_pass3("prj", code, "", &parser.veryl));
     dbg!(&errors);
     errors
 }
@@ -482,6 +485,37 @@ fn multiple_assignment() {
         errors[0],
         AnalyzerError::MultipleAssignment { .. }
     ));
+
+    let code = r#"
+    module ModuleB {
+        var a: logic<4>;
+
+        assign a[2:0] = 1;
+        always_comb {
+            a[3:2] = 1;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::MultipleAssignment { .. }
+    ));
+
+    let code = r#"
+    module ModuleC {
+        var a: logic<4>;
+
+        assign a[1:0] = 1;
+        always_comb {
+            a[3:2] = 1;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
 }
 
 #[test]
@@ -727,6 +761,29 @@ fn invalid_modport_item() {
         errors[0],
         AnalyzerError::InvalidModportFunctionItem { .. }
     ));
+
+    let code = r#"
+    package PackageD {
+        function f -> logic {
+            return 1;
+        }
+    }
+    interface InterfaceD {
+        import PackageD::*;
+        var a: logic;
+
+        modport mp {
+            a: input ,
+            f: import,
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidModportItem { .. }
+    ));
 }
 
 #[test]
@@ -735,7 +792,9 @@ fn mismatch_function_arity() {
     module ModuleA {
         function FuncA (
             a: input logic,
-        ) -> logic {}
+        ) -> logic {
+            return 0;
+        }
 
         let _a: logic = FuncA(1, 2);
     }
@@ -751,7 +810,9 @@ fn mismatch_function_arity() {
     interface InterfaceB {
         function FuncB (
             a: input logic,
-        ) -> logic {}
+        ) -> logic {
+            return 0;
+        }
     }
 
     module ModuleB {
@@ -770,7 +831,9 @@ fn mismatch_function_arity() {
     interface InterfaceC {
         function FuncC (
             a: input logic,
-        ) -> logic {}
+        ) -> logic {
+            return 0;
+        }
 
         modport mp {
             FuncC: import,
@@ -795,22 +858,34 @@ fn mismatch_function_arity() {
 fn missing_default_generic_argument() {
     let code = r#"
     module ModuleA {
-        function FuncA::<A: const> () -> logic<A> {}
+        function FuncA::<A: const> () -> logic<A> {
+            return 0;
+        }
         let _a: logic = FuncA::<1>();
 
-        function FuncB::<A: const, B: const, C: const> () -> logic<A + B + C> {}
+        function FuncB::<A: const, B: const, C: const> () -> logic<A + B + C> {
+            return 0;
+        }
         let _b: logic = FuncB::<1, 2, 3>();
 
-        function FuncC::<A: const = 1> () -> logic<A> {}
+        function FuncC::<A: const = 1> () -> logic<A> {
+            return 0;
+        }
         let _c: logic = FuncC::<>();
 
-        function FuncD::<A: const = 1, B: const = 2, C: const = 3> () -> logic<A + B + C> {}
+        function FuncD::<A: const = 1, B: const = 2, C: const = 3> () -> logic<A + B + C> {
+            return 0;
+        }
         let _d: logic = FuncD::<>();
 
-        function FuncE::<A: const, B: const = 2, C: const = 3> () -> logic<A + B + C> {}
+        function FuncE::<A: const, B: const = 2, C: const = 3> () -> logic<A + B + C> {
+            return 0;
+        }
         let _e: logic = FuncE::<1>();
 
-        function FuncF::<A: const, B: const, C: const = 3> () -> logic<A + B + C> {}
+        function FuncF::<A: const, B: const, C: const = 3> () -> logic<A + B + C> {
+            return 0;
+        }
         let _f: logic = FuncF::<1, 2>();
     }
     "#;
@@ -820,7 +895,9 @@ fn missing_default_generic_argument() {
 
     let code = r#"
         module ModuleB {
-            function FuncA::<A: const = 1, B: const, C: const = 3> () -> logic<A + B + C> {}
+            function FuncA::<A: const = 1, B: const, C: const = 3> () -> logic<A + B + C> {
+                return 0;
+            }
             let _a: logic = FuncA::<1, 2, 3> ();
         }
     "#;
@@ -833,7 +910,9 @@ fn missing_default_generic_argument() {
 
     let code = r#"
         module ModuleC {
-            function FuncA::<A: const = 1, B: const = 2, C: const> () -> logic<A + B + C> {}
+            function FuncA::<A: const = 1, B: const = 2, C: const> () -> logic<A + B + C> {
+                return 0;
+            }
             let _a: logic = FuncA::<1, 2, 3>();
         }
     "#;
@@ -851,7 +930,9 @@ fn mismatch_generics_arity() {
     module ModuleA {
         function FuncA::<T: const> (
             a: input logic<T>,
-        ) -> logic<T> {}
+        ) -> logic<T> {
+            return 0;
+        }
 
         let _a: logic = FuncA::<1, 2>(1);
     }
@@ -867,7 +948,9 @@ fn mismatch_generics_arity() {
     module ModuleB {
         function FuncA::<T: const, U: const> (
             a: input logic<T>,
-        ) -> logic<T> {}
+        ) -> logic<T> {
+            return 0;
+        }
 
         let _a: logic = FuncA::<1>(1);
     }
@@ -927,6 +1010,17 @@ fn mismatch_attribute_args() {
         errors[0],
         AnalyzerError::MismatchAttributeArgs { .. }
     ));
+
+    let code = r#"
+    #[power_domain(pd_core)]
+    module ModuleB {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::MismatchAttributeArgs { .. }
+    ));
 }
 
 #[test]
@@ -1169,6 +1263,123 @@ fn mismatch_type() {
     assert!(matches!(errors[0], AnalyzerError::MismatchType { .. }));
 }
 
+#[test]
+fn missing_modport() {
+    let code = r#"
+    interface InterfaceA {
+        var a: logic;
+        modport mp {
+            a: input,
+        }
+    }
+    interface InterfaceB {
+        var a: logic;
+        modport mp {
+            a: input,
+        }
+    }
+    module ModuleA {
+        inst a: InterfaceA;
+        inst u: ModuleB (
+            p: a,
+        );
+    }
+    module ModuleB (
+        p: modport InterfaceA::mp,
+    ) {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    interface InterfaceA {
+        var a: logic;
+        modport mp {
+            a: input,
+        }
+    }
+    interface InterfaceB {
+        var a: logic;
+        modport mp {
+            a: input,
+        }
+    }
+    module ModuleA {
+        inst b: InterfaceB;
+        inst u: ModuleB (
+            p: b,
+        );
+    }
+    module ModuleB (
+        p: modport InterfaceA::mp,
+    ) {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::MissingModport { .. }));
+}
+
+#[test]
+fn mismatch_modport_direction() {
+    let code = r#"
+    interface InterfaceA {
+        var a: logic;
+        modport master {
+            a: output,
+        }
+        modport slave {
+            a: input,
+        }
+    }
+    module ModuleA (
+        m: modport InterfaceA::master,
+        s: modport InterfaceA::slave ,
+    ) {
+        inst u0: ModuleB (
+            p: m,
+        );
+        inst u1: ModuleB (
+            p: s,
+        );
+    }
+    module ModuleB (
+        p: modport InterfaceA::slave,
+    ) {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    interface InterfaceA {
+        var a: logic;
+        modport master {
+            a: output,
+        }
+        modport slave {
+            a: input,
+        }
+    }
+    module ModuleA (
+        s: modport InterfaceA::slave,
+    ) {
+        inst u: ModuleB (
+            p: s,
+        );
+    }
+    module ModuleB (
+        p: modport InterfaceA::master,
+    ) {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::MismatchModportDirection { .. }
+    ));
+}
+
 #[test]
 fn missing_if_reset() {
     let code = r#"
@@ -1201,6 +1412,46 @@ fn missing_port() {
     assert!(matches!(errors[0], AnalyzerError::MissingPort { .. }));
 }
 
+#[test]
+fn invalid_sim_only_instance() {
+    let code = r#"
+    module ModuleA {
+        inst u: ModuleB;
+    }
+
+    #[sim_only]
+    module ModuleB {
+        initial {
+            $display("hello");
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidSimOnlyInstance { .. }
+    ));
+
+    let code = r#"
+    module ModuleA {
+        #[sim_only]
+        inst u: ModuleB;
+    }
+
+    #[sim_only]
+    module ModuleB {}
+
+    #[sim_only]
+    module ModuleC {
+        inst u: ModuleB;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+}
+
 #[test]
 fn missing_clock_signal() {
     let code = r#"
@@ -1419,6 +1670,35 @@ fn missing_reset_statement() {
         errors[0],
         AnalyzerError::MissingResetStatement { .. }
     ));
+
+    let code = r#"
+    module ModuleB (
+        clk: input clock,
+        rst: input reset,
+    ) {
+        var a: logic;
+        var b: logic;
+        var c: logic;
+
+        always_ff(clk, rst) {
+            if_reset {
+                b = 0;
+            } else {
+                a = 1;
+                b = 1;
+                c = 1;
+            }
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert_eq!(errors.len(), 1);
+    if let AnalyzerError::MissingResetStatement { name, .. } = &errors[0] {
+        assert_eq!(name, "a, c");
+    } else {
+        unreachable!();
+    }
 }
 
 #[test]
@@ -1644,32 +1924,81 @@ fn invalid_enum_variant() {
 }
 
 #[test]
-fn undefined_identifier() {
+fn duplicated_enum_variant_value() {
     let code = r#"
     module ModuleA {
-        assign a = 1;
+        #[encoding(gray)]
+        enum EnumA {
+            A,
+            B,
+            C = 3,
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleB {
+        enum EnumA {
+            A,
+            B,
+            C = 1,
+        }
     }
     "#;
 
     let errors = analyze(code);
     assert!(matches!(
         errors[0],
-        AnalyzerError::UndefinedIdentifier { .. }
+        AnalyzerError::DuplicatedEnumVariantValue { .. }
     ));
-}
 
-#[test]
-fn unknown_attribute() {
     let code = r#"
-    module ModuleA {
-        #[dummy_name]
-        const a: u32 = 1;
+    module ModuleC {
+        #[encoding(onehot)]
+        enum EnumA {
+            A = 1,
+            B = 1,
+        }
     }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::UnknownAttribute { .. }));
-}
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::DuplicatedEnumVariantValue { .. }
+    ));
+}
+
+#[test]
+fn undefined_identifier() {
+    let code = r#"
+    module ModuleA {
+        assign a = 1;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::UndefinedIdentifier { .. }
+    ));
+}
+
+#[test]
+fn unknown_attribute() {
+    let code = r#"
+    module ModuleA {
+        #[dummy_name]
+        const a: u32 = 1;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::UnknownAttribute { .. }));
+}
 
 #[test]
 fn unknown_embed_lang() {
@@ -1707,6 +2036,87 @@ fn unknown_member() {
 
     let errors = analyze(code);
     assert!(matches!(errors[0], AnalyzerError::UnknownMember { .. }));
+
+    let code = r#"
+    module ModuleB {
+        struct StructA {
+            memberA: logic,
+            memberB: logic,
+        }
+        struct StructB {
+            memberA: StructA,
+        }
+        union UnionA {
+            memberA: logic<2>,
+            memberB: StructA,
+        }
+        var a: StructB [2];
+        var b: UnionA;
+        assign a[0].memberA.memberB = 1;
+        assign b.memberB.memberA    = 1;
+        assign a[1].memberA.memberA.memberB = 1;
+    }
+    "#;
+
+    let errors = analyze(code);
+    let errors: Vec<_> = errors
+        .iter()
+        .filter(|x| matches!(x, AnalyzerError::UnknownMember { .. }))
+        .collect();
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn out_of_range_index() {
+    let code = r#"
+    module ModuleA #(
+        param N: u32 = 2,
+    ) {
+        struct StructA {
+            memberA: logic,
+        }
+        const M: u32 = 2;
+        var a: StructA [2];
+        var b: logic [M, 3];
+        var c: logic [N];
+        var d: logic<3>;
+        assign a    = 0;
+        assign b    = 0;
+        assign c    = 0;
+        assign d[0] = a[1].memberA;
+        assign d[1] = b[1][2];
+        assign d[2] = c[2];
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleB {
+        struct StructA {
+            memberA: logic,
+        }
+        var a: StructA [2];
+        var b: logic;
+        assign a[0].memberA = 1;
+        assign a[1].memberA = 1;
+        assign b = a[2].memberA;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::OutOfRangeIndex { .. }));
+
+    let code = r#"
+    module ModuleC {
+        var a: logic [2, 3];
+        assign a[1][3] = 1;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::OutOfRangeIndex { .. }));
 }
 
 #[test]
@@ -1836,431 +2246,1515 @@ fn unused_return() {
 }
 
 #[test]
-fn break_outside_loop() {
-    let code = r#"
-    module ModuleA {
-        always_comb {
-            break;
-        }
+fn unknown_doc_target() {
+    let code = r#"
+    /// Module A
+    /// @param WIDTH Data width
+    /// @port i_a Input
+    /// @port o_b Output
+    ///   which continues
+    /// @example
+    /// inst u: ModuleA (i_a, o_b);
+    module ModuleA #(
+        param WIDTH: u32 = 1,
+    ) (
+        i_a: input logic,
+        o_b: output logic,
+    ) {
+        assign o_b = i_a;
     }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::InvalidStatement { .. }));
+    assert!(errors.is_empty());
 
     let code = r#"
-    module ModuleA {
-        always_comb {
-            if 1 {
-                break;
-            }
-        }
-    }
+    /// @port i_b Input
+    module ModuleB (
+        i_a: input logic,
+    ) {}
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::InvalidStatement { .. }));
+    assert!(matches!(errors[0], AnalyzerError::UnknownDocTarget { .. }));
+
+    let code = r#"
+    /// @param WIDTH Data width
+    interface InterfaceC {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::UnknownDocTarget { .. }));
 }
 
 #[test]
-fn unassign_variable() {
+fn unused_function() {
     let code = r#"
     module ModuleA {
-        var _a: logic;
+        function FuncA () -> logic {
+            return 1;
+        }
     }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::UnassignVariable { .. }));
+    assert!(matches!(errors[0], AnalyzerError::UnusedFunction { .. }));
 
     let code = r#"
-    module ModuleA {
-        var a: logic;
-        var b: logic;
-        always_comb {
-            b = a;
-            a = 1;
+    module ModuleB {
+        function _FuncB () -> logic {
+            return 1;
         }
     }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::UnassignVariable { .. }));
+    assert!(errors.is_empty());
 
     let code = r#"
-    module ModuleA {
-        var a: logic;
-        always_comb {
-            a = a;
-            a = 1;
+    package PackageC {
+        function FuncC () -> logic {
+            return 1;
         }
     }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::UnassignVariable { .. }));
+    assert!(errors.is_empty());
 
     let code = r#"
-    module ModuleA {
-        var a: logic;
-        var b: logic;
-        always_comb {
-            if 1 {
-                let c: logic = 1;
-                a = c;
-            } else {
-                a = 0;
-            }
-            if 1 {
-                var c: logic;
-                b = c;
-            } else {
-                b = 0;
-            }
+    module ModuleD {
+        #[allow(unused_function)]
+        function FuncD () -> logic {
+            return 1;
         }
     }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::UnassignVariable { .. }));
+    assert!(errors.is_empty());
 
     let code = r#"
-    module ModuleA {
-        var a: logic;
-        always_comb {
-            let b: logic = 1;
-            a = b;
+    module ModuleE {
+        #[allow(unused_variable)]
+        function FuncE () -> logic {
+            return 1;
         }
     }
     "#;
 
     let errors = analyze(code);
-    assert!(errors.is_empty());
+    assert!(matches!(errors[0], AnalyzerError::UnusedFunction { .. }));
 
     let code = r#"
-    module ModuleA {
-        var a: logic;
-        always_comb {
-            for i: u32 in 0..1 {
-                a = i;
-            }
-        }
+    module ModuleF {
+        #[allow(unused_function)]
+        let a: logic = 1;
     }
     "#;
 
     let errors = analyze(code);
-    assert!(errors.is_empty());
+    assert!(matches!(errors[0], AnalyzerError::UnusedVariable { .. }));
+}
 
+#[test]
+fn recursive_function() {
     let code = r#"
     module ModuleA {
-        var a: logic<2>;
-        always_comb {
-            a[0] = 0;
+        function FuncA (a: input logic) -> logic {
+            return FuncA(a);
         }
+        let _a: logic = FuncA(1);
+    }
+    "#;
 
-        always_comb {
-            a[1] = a[0];
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::RecursiveFunction { .. }));
+
+    let code = r#"
+    module ModuleB {
+        function FuncA (a: input logic) -> logic {
+            return FuncB(a);
         }
+        function FuncB (a: input logic) -> logic {
+            return FuncA(a);
+        }
+        let _a: logic = FuncA(1);
     }
     "#;
 
     let errors = analyze(code);
-    assert!(errors.is_empty());
+    assert!(matches!(errors[0], AnalyzerError::RecursiveFunction { .. }));
 }
 
 #[test]
-fn uncovered_branch() {
+fn missing_return() {
     let code = r#"
     module ModuleA {
-        var a: logic;
-        var b: logic;
-        let x: logic = 1;
-
-      always_comb {
-        if x {
-            let y: logic = 1;
-            a = y;
-        } else {
-            a = 0;
-        }
-      }
-
-      always_comb {
-        var z: logic;
-        if x {
-            z = 1;
-            b = z;
-        } else {
-            b = 0;
+        function FuncA (a: input logic) -> logic {
+            if a {
+                return 1;
+            }
         }
-      }
+        let _a: logic = FuncA(1);
     }
     "#;
 
     let errors = analyze(code);
-    assert!(errors.is_empty());
+    assert!(matches!(errors[0], AnalyzerError::MissingReturn { .. }));
 
     let code = r#"
     module ModuleB {
-        var a: logic;
-        let x: logic = 1;
-
-        always_comb {
-            if x {
-                a = 1;
+        function FuncA (a: input logic) -> logic {
+            if a {
+                return 1;
+            } else {
+                return 0;
+            }
+        }
+        function FuncB (a: input logic) -> logic {
+            case a {
+                0      : return 1;
+                default: return 0;
             }
         }
+        let _a: logic = FuncA(1);
+        let _b: logic = FuncB(1);
     }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::UncoveredBranch { .. }));
+    assert!(errors.is_empty());
 }
 
 #[test]
-fn reserved_identifier() {
+fn function_side_effect() {
     let code = r#"
-    module __ModuleA {
+    module ModuleA {
+        var a: logic;
+        function FuncA (b: input logic) -> logic {
+            a = b;
+            return b;
+        }
+        let _b: logic = FuncA(1);
     }
     "#;
 
     let errors = analyze(code);
     assert!(matches!(
         errors[0],
-        AnalyzerError::ReservedIdentifier { .. }
+        AnalyzerError::FunctionSideEffect { .. }
     ));
-}
 
-#[test]
-fn reset_value_non_elaborative() {
     let code = r#"
-    module ModuleA (
-        i_clk: input clock,
-        i_rst: input reset,
-    ) {
+    module ModuleB {
         var a: logic;
-        var b: logic;
+        #[allow(side_effect)]
+        function FuncA (b: input logic) -> logic {
+            a = b;
+            return b;
+        }
+        let _b: logic = FuncA(1);
+    }
+    "#;
 
-        always_ff {
-            if_reset {
-                a = b;
-            } else {
-                a = 1'b0;
-            }
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleC {
+        function FuncA (b: input logic) -> logic {
+            var c: logic;
+            c = b;
+            return c;
         }
-    }"#;
+        let _b: logic = FuncA(1);
+    }
+    "#;
 
     let errors = analyze(code);
-    assert!(matches!(
-        errors[0],
-        AnalyzerError::InvalidResetNonElaborative { .. }
-    ));
+    assert!(errors.is_empty());
 }
 
 #[test]
-fn invalid_factor_kind() {
+fn unused_function_input() {
     let code = r#"
     module ModuleA {
-        function f (
-            a: input logic,
-        ) -> logic {
+        function FuncA (a: input logic, b: input logic) -> logic {
             return a;
         }
+        let _a: logic = FuncA(1, 1);
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::UnusedFunctionInput { .. }
+    ));
+
+    let code = r#"
+    module ModuleB {
+        function FuncA (a: input logic, _b: input logic) -> logic {
+            return a;
+        }
+        let _a: logic = FuncA(1, 1);
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn lint_severity() {
+    let code = r#"
+    module ModuleA {
+        let a: logic = 1;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::UnusedVariable { .. }));
+
+    let mut lint = Lint::default();
+    assert_eq!(errors[0].severity_with(&lint), Severity::Warning);
+
+    lint.severity
+        .insert("unused_variable".to_string(), LintSeverity::Error);
+    assert_eq!(errors[0].severity_with(&lint), Severity::Error);
+
+    lint.severity
+        .insert("unused_variable".to_string(), LintSeverity::Info);
+    assert_eq!(errors[0].severity_with(&lint), Severity::Advice);
+
+    assert!(AnalyzerError::unknown_severity_codes(&lint).is_empty());
+    lint.severity
+        .insert("unused_varaible".to_string(), LintSeverity::Error);
+    assert_eq!(
+        AnalyzerError::unknown_severity_codes(&lint),
+        vec!["unused_varaible"]
+    );
+}
+
+#[test]
+fn lint_severity_codes() {
+    // All codes of AnalyzerError should be listed in CODES
+    let source = include_str!("analyzer_error.rs");
+    let codes: Vec<_> = source
+        .lines()
+        .filter(|x| x.trim().starts_with("code(") || x.contains("#[diagnostic("))
+        .filter_map(|x| x.split_once("code(").map(|(_, x)| x))
+        .filter_map(|x| x.split_once(')').map(|(x, _)| x))
+        .filter(|x| *x != "cancelled")
+        .collect();
+    assert!(!codes.is_empty());
+    assert_eq!(codes, crate::analyzer_error::CODES);
+}
+
+#[test]
+fn dft() {
+    symbol_table::clear();
+    attribute_table::clear();
+
+    let code = r#"
+    module ModuleA (
+        i_clk: input `a clock,
+        i_rst: input `a reset,
+        i_en : input `a logic,
+    ) {
+        var gclk: `a clock;
+        var grst: `a reset;
+        var r0  : `a logic;
+        var r1  : `a logic;
+        assign gclk = i_clk & i_en;
+        assign grst = i_rst | i_en;
+
+        always_ff (gclk, grst) {
+            if_reset {
+                r0 = 0;
+            } else {
+                r0 = ~r0;
+            }
+        }
+
+        always_ff (i_clk, i_rst) {
+            if_reset {
+                r1 = 0;
+            } else {
+                r1 = r0;
+            }
+        }
+    }
+    "#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+    metadata.lint.dft.enable = true;
+    metadata.lint.dft.generated_reset = LintSeverity::Error;
+    let parser = Parser::parse(code, &"").unwrap();
+    let analyzer = Analyzer::new(&metadata);
+
+    let mut errors = analyzer.analyze_pass1("prj", code, "", &parser.veryl);
+    Analyzer::analyze_post_pass1();
+    errors.append(&mut analyzer.analyze_pass2("prj", code, "", &parser.veryl));
+    errors.append(&mut analyzer.analyze_pass3("prj", code, "", &parser.veryl));
+
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(errors[0], AnalyzerError::DftGeneratedClock { .. }));
+    assert!(matches!(errors[1], AnalyzerError::DftGeneratedReset { .. }));
+    assert_eq!(errors[0].severity_with(&metadata.lint), Severity::Warning);
+    assert_eq!(errors[1].severity_with(&metadata.lint), Severity::Error);
+
+    metadata.lint.dft.enable = false;
+    let analyzer = Analyzer::new(&metadata);
+    let errors = analyzer.analyze_pass2("prj", code, "", &parser.veryl);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn break_outside_loop() {
+    let code = r#"
+    module ModuleA {
+        always_comb {
+            break;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidStatement { .. }));
+
+    let code = r#"
+    module ModuleA {
+        always_comb {
+            if 1 {
+                break;
+            }
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidStatement { .. }));
+}
+
+#[test]
+fn unassign_variable() {
+    let code = r#"
+    module ModuleA {
+        var _a: logic;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::UnassignVariable { .. }));
+
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        var b: logic;
+        always_comb {
+            if 1 {
+                let c: logic = 1;
+                a = c;
+            } else {
+                a = 0;
+            }
+            if 1 {
+                var c: logic;
+                b = c;
+            } else {
+                b = 0;
+            }
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::UnassignVariable { .. }));
+
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        always_comb {
+            let b: logic = 1;
+            a = b;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        always_comb {
+            for i: u32 in 0..1 {
+                a = i;
+            }
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleA {
+        var a: logic<2>;
+        always_comb {
+            a[0] = 0;
+        }
+
+        always_comb {
+            a[1] = a[0];
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleA::<T: type> (
+        i_d: input  T,
+        o_d: output T,
+    ) {
+        assign o_d = i_d;
+    }
+    module ModuleB (
+        i_d: input  logic<8>,
+        o_d: output logic<8>,
+    ) {
+        inst u: ModuleA::<PackageA::B> (i_d, o_d);
+    }
+    package PackageA {
+        type B = logic<8>;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn read_before_write() {
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        var b: logic;
+        always_comb {
+            b = a;
+            a = 1;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::ReadBeforeWrite { .. }));
+
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        always_comb {
+            a = a;
+            a = 1;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::ReadBeforeWrite { .. }));
+
+    let code = r#"
+    module ModuleA {
+        var a: logic<2>;
+        var b: logic;
+        always_comb {
+            b = a[0];
+            a = 1;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::ReadBeforeWrite { .. }));
+
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        var b: logic;
+        always_comb {
+            a = 1;
+            b = a;
+            a = 0;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        var b: logic;
+        #[allow(read_before_write)]
+        always_comb {
+            b = a;
+            a = 1;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn hierarchical_reference() {
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        inst u: ModuleB;
+        assign a = u.b;
+    }
+
+    module ModuleB {
+        var b: logic;
+        assign b = 1;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::HierarchicalReference { .. }
+    ));
+
+    let code = r#"
+    interface InterfaceA {
+        var b: logic;
+    }
+
+    module ModuleA {
+        var a: logic;
+        inst u: InterfaceA;
+        assign u.b = 1;
+        assign a   = u.b;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    #[allow(hierarchical_reference)]
+    module ModuleA {
+        var a: logic;
+        inst u: ModuleB;
+        assign a = u.b;
+    }
+
+    module ModuleB {
+        var b: logic;
+        assign b = 1;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn unassign_bits() {
+    let code = r#"
+    module ModuleA {
+        var a: logic<8>;
+
+        assign a[7:6] = 1;
+        assign a[3] = 1;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::UnassignBits { .. }));
+    if let AnalyzerError::UnassignBits { bits, .. } = &errors[0] {
+        assert_eq!(bits, "[5:4], [2:0]");
+    }
+
+    let code = r#"
+    module ModuleB {
+        var a: logic<4>;
+        var b: logic [2];
+
+        assign a[3:1] = 1;
+        assign a[0]   = 1;
+        assign b[0]   = 1;
+        assign b[1]   = 1;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn uncovered_branch() {
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        var b: logic;
+        let x: logic = 1;
+
+      always_comb {
+        if x {
+            let y: logic = 1;
+            a = y;
+        } else {
+            a = 0;
+        }
+      }
+
+      always_comb {
+        var z: logic;
+        if x {
+            z = 1;
+            b = z;
+        } else {
+            b = 0;
+        }
+      }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleB {
+        var a: logic;
+        let x: logic = 1;
+
+        always_comb {
+            if x {
+                a = 1;
+            }
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::UncoveredBranch { .. }));
+
+    let code = r#"
+    module ModuleC {
+        var a: logic;
+        var x: logic;
+        assign x = 1;
+
+        always_comb {
+            if x {
+                a = 1;
+            } else if x {
+                a = 0;
+            } else {
+            }
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    if let AnalyzerError::UncoveredBranch { branches, .. } = &errors[0] {
+        let labels: Vec<_> = branches.iter().map(|x| x.label().unwrap()).collect();
+        assert_eq!(labels, ["Assigned", "Assigned", "Not assigned"]);
+    } else {
+        unreachable!();
+    }
+}
+
+#[test]
+fn reserved_identifier() {
+    let code = r#"
+    module __ModuleA {
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::ReservedIdentifier { .. }
+    ));
+}
+
+#[test]
+fn param_non_elaborative() {
+    let code = r#"
+    module ModuleA #(
+        param W: u32 = 8,
+    ) {
+        const X: u32 = W * 2;
+        for i in 0..2 :g {
+            inst u: ModuleB #(
+                A: X + i,
+                W   ,
+            );
+        }
+    }
+
+    module ModuleB #(
+        param A: u32 = 1,
+        param W: u32 = 1,
+    ) {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleA (
+        i_a: input logic<32>,
+    ) {
+        inst u: ModuleB #(
+            A: i_a,
+        );
+    }
+
+    module ModuleB #(
+        param A: u32 = 1,
+    ) {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidParamNonElaborative { .. }
+    ));
+
+    let code = r#"
+    module ModuleA {
+        var A: logic<32>;
+        assign A = 0;
+        inst u: ModuleB #(
+            A,
+        );
+    }
+
+    module ModuleB #(
+        param A: u32 = 1,
+    ) {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidParamNonElaborative { .. }
+    ));
+}
+
+#[test]
+fn reset_value_non_elaborative() {
+    let code = r#"
+    module ModuleA (
+        i_clk: input clock,
+        i_rst: input reset,
+    ) {
+        var a: logic;
+        var b: logic;
+
+        always_ff {
+            if_reset {
+                a = b;
+            } else {
+                a = 1'b0;
+            }
+        }
+    }"#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidResetNonElaborative { .. }
+    ));
+}
+
+#[test]
+fn invalid_factor_kind() {
+    let code = r#"
+    module ModuleA {
+        function f (
+            a: input logic,
+        ) -> logic {
+            return a;
+        }
+
+        var a: logic;
+
+        assign a = f + 1;
+    }"#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidFactor { .. }));
+}
+
+#[test]
+fn call_non_function() {
+    let code = r#"
+    module ModuleA {
+        function f (
+            a: input logic,
+        ) -> logic {
+            return a;
+        }
+
+        var a: logic;
+        var b: logic;
+
+        assign a = b() + 1;
+    }"#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::CallNonFunction { .. }));
+}
+
+#[test]
+fn invalid_assignment_to_const() {
+    let code = r#"
+    module ModuleA (
+        i_clk: input clock,
+        i_rst: input reset,
+    ) {
+        always_comb {
+            let y: logic = 1;
+            y = 0;
+        }
+    }"#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidAssignmentToConst { .. }
+    ));
+}
+
+// TODO disable until adding expression type check
+//#[test]
+//fn test_factors() {
+//    let code = r#"
+//    interface InterfaceA {
+//        var a: logic;
+//        modport master {
+//            a: input,
+//        }
+//    }
+//
+//    module ModuleA #(
+//        param K: i32 = 32,
+//    ) (
+//        i_clk: input   clock             ,
+//        i_rst: input   reset             ,
+//        mst  : modport InterfaceA::master,
+//    ) {
+//
+//        enum State: logic<3> {
+//            Idle = 3'bxx1,
+//            Run0 = 3'b000,
+//            Run1 = 3'b010,
+//            Run2 = 3'b100,
+//            Done = 3'b110,
+//        }
+//
+//        struct S {
+//            v: logic,
+//        }
+//
+//        union U {
+//            v: logic,
+//            w: logic,
+//        }
+//
+//        let state: State = State::Run1;
+//        var u    : U    ;
+//        var s    : S    ;
+//        const J    : i32   = 32;
+//
+//        for i in 0..1 :g_display {
+//            always_ff {
+//                $display("%d", i);
+//            }
+//        }
+//
+//        function foo () -> logic {
+//            return 1'b1;
+//        }
+//
+//        function bar (
+//            l: input logic,
+//        ) -> logic {
+//            return foo();
+//        }
+//
+//        assign u.v = 1'b1;
+//        assign s.v = 1'b1;
+//        initial {
+//            $display("%d", u);
+//            $display("%d", s);
+//            $display("%d", state);
+//            $display("%d", mst.a);
+//            $display("%d", i_clk);
+//            $display("%d", K);
+//            $display("%d", J);
+//            $display("%d", foo());
+//            // Using $bits as a placeholder SystemFunciton.
+//            $display("%d", $bits(S));
+//            $display("%d", $bits(U));
+//            $display("%d", $bits(foo(), State));
+//            $display("%d", bar($bits(State)));
+//            $display("%d", $bits(State));
+//            // The following 4 cases should be error.
+//            $display("%d", bar(S));
+//            $display("%d", bar(U));
+//            $display("%d", bar(State));
+//            $display("%d", $bits(bar(State)));
+//        }
+//    }"#;
+//
+//    let errors = analyze(code);
+//    assert!(errors.len() == 4);
+//    for error in errors {
+//        assert!(matches!(error, AnalyzerError::InvalidFactor { .. }));
+//    }
+//}
+
+#[test]
+fn enum_non_const_exception() {
+    let code = r#"
+    module ModuleA (
+        i_clk: input clock,
+        i_rst: input reset,
+    ) {
+
+        enum State: logic<3> {
+            Idle = 3'bxx1,
+            Run0 = 3'b000,
+            Run1 = 3'b010,
+            Run2 = 3'b100,
+            Done = 3'b110,
+        }
+        var state: State;
+
+        always_ff {
+            if_reset {
+                state = State::Idle;
+            }
+        }
+
+    }"#;
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn invalid_case_condition_expression() {
+    let code = r#"
+    module ModuleA (
+        i_sel: input  logic<3>,
+        i_a  : input  logic<4>,
+        o_b  : output logic,
+        o_c  : output logic,
+    ) {
+        const ONE: bit <3> = 3'd1;
+
+        always_comb {
+          case i_sel {
+            3'd0   : o_b = i_a[0];
+            ONE    : o_b = i_a[1];
+            2..=3  : o_b = i_a[2];
+            3'b1xx : o_b = i_a[3];
+            default: o_b = i_a[3];
+          }
+        }
+
+        assign o_c = case i_sel {
+            3'd0   : i_a[0],
+            ONE    : i_a[1],
+            2..=3  : i_a[2],
+            3'b1xx : i_a[3],
+            default: i_a[3],
+        };
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleB (
+        i_sel: input  logic<2>,
+        i_a  : input  logic<3>,
+        o_b  : output logic,
+    ) {
+        let c: logic<2> = 2'd0;
+
+        always_comb {
+          case i_sel {
+            c      : o_b = i_a[0];
+            default: o_b = i_a[1];
+          }
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidCaseConditionNonElaborative { .. }
+    ));
+
+    let code = r#"
+    module ModuleC (
+        i_sel: input  logic<2>,
+        i_a  : input  logic<3>,
+        o_b  : output logic,
+    ) {
+        let c: logic<2> = 2'd1;
+
+        always_comb {
+          case i_sel {
+            0..=c  : o_b = i_a[0];
+            default: o_b = i_a[1];
+          }
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidCaseConditionNonElaborative { .. }
+    ));
+
+    let code = r#"
+    module ModuleD (
+        i_sel: input  logic<2>,
+        i_a  : input  logic<3>,
+        o_b  : output logic,
+    ) {
+        let c: logic<2> = 2'd0;
+
+        assign o_b = case i_sel {
+            c      : i_a[0],
+            default: i_a[1],
+        };
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidCaseConditionNonElaborative { .. }
+    ));
+
+    let code = r#"
+    module ModuleE (
+        i_sel: input  logic<2>,
+        i_a  : input  logic<3>,
+        o_b  : output logic,
+    ) {
+        let c: logic<2> = 2'd1;
+
+        assign o_b = case i_sel {
+            0..=c  : i_a[0],
+            default: i_a[1],
+        };
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::InvalidCaseConditionNonElaborative { .. }
+    ));
+}
+
+#[test]
+fn invalid_cast() {
+    let code = r#"
+    module ModuleA {
+        let a: clock = 1;
+        let _b: reset = a as reset;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidCast { .. }));
+}
+
+#[test]
+fn invalid_test() {
+    let code = r#"
+    module ModuleA {}
+
+    #[test(TestA)]
+    embed (cocotb) py {{{
+    }}}
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidTest { .. }));
+}
+
+#[test]
+fn invalid_memory() {
+    let code = r#"
+    module ModuleA (
+        i_clk: input  clock,
+        i_we : input  logic,
+        i_adr: input  logic<4>,
+        i_dat: input  logic<8>,
+        o_dat: output logic<8>,
+    ) {
+        #[memory(simple_dual_port)]
+        var mem: logic<8> [16];
+
+        always_ff {
+            if i_we {
+                mem[i_adr] = i_dat;
+            }
+            o_dat = mem[i_adr];
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleB (
+        i_clk: input  clock,
+        i_dat: input  logic<8>,
+        o_dat: output logic<8>,
+    ) {
+        #[memory(single_port)]
+        var mem: logic<8>;
+
+        always_ff {
+            mem = i_dat;
+        }
+        assign o_dat = mem;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidMemory { .. }));
+
+    let code = r#"
+    module ModuleC (
+        i_clk: input  clock,
+        i_adr: input  logic<4>,
+        o_dat: output logic<8>,
+    ) {
+        #[memory(true_dual_port, "0")]
+        var mem: logic<8> [16];
+
+        always_ff {
+            mem[i_adr] = 0;
+        }
+        assign o_dat = mem[i_adr];
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidMemory { .. }));
+
+    let code = r#"
+    module ModuleD (
+        i_clk: input  clock,
+        i_adr: input  logic<4>,
+        o_dat: output logic<8>,
+    ) {
+        #[memory(single_port)]
+        var mem: logic<8> [16];
+
+        always_ff {
+            mem[i_adr] = 0;
+        }
+        always_ff {
+            mem[i_adr + 1] = 1;
+        }
+        assign o_dat = mem[i_adr];
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidMemory { .. }));
+
+    let code = r#"
+    module ModuleE {
+        #[memory(quad_port)]
+        var mem: logic<8> [16];
+
+        assign mem = '{default: 0};
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidMemoryKind { .. }));
+}
+
+#[test]
+fn invalid_pipeline() {
+    let code = r#"
+    module ModuleA (
+        i_clk  : input  clock   ,
+        i_rst  : input  reset   ,
+        i_valid: input  logic   ,
+        i_dat  : input  logic<8>,
+        o_valid: output logic   ,
+        o_dat  : output logic<8>,
+    ) {
+        #[pipeline]
+        var valid: logic [3];
+        #[pipeline(valid)]
+        var dat: logic<8> [3];
+
+        assign valid[0] = i_valid;
+        assign dat[0]   = i_dat;
+        assign o_valid  = valid[2];
+        assign o_dat    = dat[2];
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleB (
+        i_clk: input  clock   ,
+        i_dat: input  logic<8>,
+        o_dat: output logic<8>,
+    ) {
+        #[pipeline]
+        var dat: logic<8> [3];
+
+        assign dat[0] = i_dat;
+        assign o_dat  = dat[1];
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidPipeline { .. }));
+
+    let code = r#"
+    module ModuleC (
+        i_clk: input  clock   ,
+        i_dat: input  logic<8>,
+        o_dat: output logic<8>,
+    ) {
+        #[pipeline]
+        var dat: logic<8> [3];
+
+        assign dat[1] = i_dat;
+        assign o_dat  = dat[2];
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidPipeline { .. }));
+
+    let code = r#"
+    module ModuleD (
+        i_dat: input  logic<8>,
+        o_dat: output logic<8>,
+    ) {
+        #[pipeline]
+        var dat: logic<8> [3];
+
+        assign dat[0] = i_dat;
+        assign o_dat  = dat[2];
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidPipeline { .. }));
+}
+
+#[test]
+fn invalid_protocol() {
+    let code = r#"
+    module CheckerA (
+        clk  : input clock,
+        valid: input logic,
+        ready: input logic,
+    ) {
+        always_ff (clk) {
+            if valid && !ready {
+                $display("stall");
+            }
+        }
+    }
+
+    #[protocol(CheckerA)]
+    interface InterfaceA {
+        var clk  : clock;
+        var valid: logic;
+        var ready: logic;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    package PackageB {}
+
+    #[protocol(PackageB)]
+    interface InterfaceB {
+        var valid: logic;
+    }
+    "#;
 
-        var a: logic;
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidProtocol { .. }));
 
-        assign a = f + 1;
-    }"#;
+    let code = r#"
+    module CheckerC (
+        valid: output logic,
+    ) {
+        assign valid = 0;
+    }
+
+    #[protocol(CheckerC)]
+    interface InterfaceC {
+        var valid: logic;
+    }
+    "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::InvalidFactor { .. }));
-}
+    assert!(matches!(errors[0], AnalyzerError::InvalidProtocol { .. }));
 
-#[test]
-fn call_non_function() {
     let code = r#"
-    module ModuleA {
-        function f (
-            a: input logic,
-        ) -> logic {
-            return a;
-        }
-
-        var a: logic;
-        var b: logic;
+    module CheckerD (
+        valid: input logic,
+        ready: input logic,
+    ) {}
 
-        assign a = b() + 1;
-    }"#;
+    #[protocol(CheckerD)]
+    interface InterfaceD {
+        var valid: logic;
+    }
+    "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::CallNonFunction { .. }));
+    assert!(matches!(errors[0], AnalyzerError::InvalidProtocol { .. }));
 }
 
 #[test]
-fn invalid_assignment_to_const() {
+fn invalid_parity() {
     let code = r#"
     module ModuleA (
-        i_clk: input clock,
-        i_rst: input reset,
+        i_clk: input  clock   ,
+        i_rst: input  reset   ,
+        i_dat: input  logic<8>,
+        o_dat: output logic<8>,
     ) {
-        always_comb {
-            let y: logic = 1;
-            y = 0;
+        #[parity]
+        var dat: logic<8>;
+
+        always_ff {
+            if_reset {
+                dat = 0;
+            } else {
+                dat += i_dat;
+            }
         }
-    }"#;
+        assign o_dat = dat;
+    }
+    "#;
 
     let errors = analyze(code);
-    assert!(matches!(
-        errors[0],
-        AnalyzerError::InvalidAssignmentToConst { .. }
-    ));
-}
+    assert!(errors.is_empty());
 
-// TODO disable until adding expression type check
-//#[test]
-//fn test_factors() {
-//    let code = r#"
-//    interface InterfaceA {
-//        var a: logic;
-//        modport master {
-//            a: input,
-//        }
-//    }
-//
-//    module ModuleA #(
-//        param K: i32 = 32,
-//    ) (
-//        i_clk: input   clock             ,
-//        i_rst: input   reset             ,
-//        mst  : modport InterfaceA::master,
-//    ) {
-//
-//        enum State: logic<3> {
-//            Idle = 3'bxx1,
-//            Run0 = 3'b000,
-//            Run1 = 3'b010,
-//            Run2 = 3'b100,
-//            Done = 3'b110,
-//        }
-//
-//        struct S {
-//            v: logic,
-//        }
-//
-//        union U {
-//            v: logic,
-//            w: logic,
-//        }
-//
-//        let state: State = State::Run1;
-//        var u    : U    ;
-//        var s    : S    ;
-//        const J    : i32   = 32;
-//
-//        for i in 0..1 :g_display {
-//            always_ff {
-//                $display("%d", i);
-//            }
-//        }
-//
-//        function foo () -> logic {
-//            return 1'b1;
-//        }
-//
-//        function bar (
-//            l: input logic,
-//        ) -> logic {
-//            return foo();
-//        }
-//
-//        assign u.v = 1'b1;
-//        assign s.v = 1'b1;
-//        initial {
-//            $display("%d", u);
-//            $display("%d", s);
-//            $display("%d", state);
-//            $display("%d", mst.a);
-//            $display("%d", i_clk);
-//            $display("%d", K);
-//            $display("%d", J);
-//            $display("%d", foo());
-//            // Using $bits as a placeholder SystemFunciton.
-//            $display("%d", $bits(S));
-//            $display("%d", $bits(U));
-//            $display("%d", $bits(foo(), State));
-//            $display("%d", bar($bits(State)));
-//            $display("%d", $bits(State));
-//            // The following 4 cases should be error.
-//            $display("%d", bar(S));
-//            $display("%d", bar(U));
-//            $display("%d", bar(State));
-//            $display("%d", $bits(bar(State)));
-//        }
-//    }"#;
-//
-//    let errors = analyze(code);
-//    assert!(errors.len() == 4);
-//    for error in errors {
-//        assert!(matches!(error, AnalyzerError::InvalidFactor { .. }));
-//    }
-//}
+    let code = r#"
+    module ModuleB (
+        i_dat: input  logic<8>,
+        o_dat: output logic<8>,
+    ) {
+        #[parity]
+        var dat: logic<8>;
+
+        assign dat   = i_dat;
+        assign o_dat = dat;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidParity { .. }));
 
-#[test]
-fn enum_non_const_exception() {
     let code = r#"
-    module ModuleA (
-        i_clk: input clock,
-        i_rst: input reset,
+    module ModuleC (
+        i_clk: input  clock   ,
+        i_dat: input  logic<8>,
+        o_dat: output logic<8>,
     ) {
+        #[parity]
+        var dat: logic<8>;
 
-        enum State: logic<3> {
-            Idle = 3'bxx1,
-            Run0 = 3'b000,
-            Run1 = 3'b010,
-            Run2 = 3'b100,
-            Done = 3'b110,
+        always_ff {
+            dat[3:0] = i_dat[3:0];
+            dat[7:4] = i_dat[7:4];
         }
-        var state: State;
+        assign o_dat = dat;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidParity { .. }));
+
+    let code = r#"
+    module ModuleD (
+        i_clk: input  clock   ,
+        i_dat: input  logic<8>,
+        o_dat: output logic<8>,
+    ) {
+        #[parity]
+        var dat: logic<8> [2];
 
         always_ff {
-            if_reset {
-                state = State::Idle;
-            }
+            dat[0] = i_dat;
+            dat[1] = dat[0];
         }
+        assign o_dat = dat[1];
+    }
+    "#;
 
-    }"#;
     let errors = analyze(code);
-    assert!(errors.is_empty());
+    assert!(matches!(errors[0], AnalyzerError::InvalidParity { .. }));
 }
 
 #[test]
-fn invalid_case_condition_expression() {
+fn invalid_flatten() {
     let code = r#"
+    interface InterfaceA {
+        var a: logic<8>;
+        var b: logic   ;
+        modport master {
+            a: output,
+            b: input ,
+        }
+    }
     module ModuleA (
-        i_sel: input  logic<3>,
-        i_a  : input  logic<4>,
-        o_b  : output logic,
-        o_c  : output logic,
+        #[flatten]
+        bus: modport InterfaceA::master,
     ) {
-        const ONE: bit <3> = 3'd1;
-
-        always_comb {
-          case i_sel {
-            3'd0   : o_b = i_a[0];
-            ONE    : o_b = i_a[1];
-            2..=3  : o_b = i_a[2];
-            3'b1xx : o_b = i_a[3];
-            default: o_b = i_a[3];
-          }
-        }
-
-        assign o_c = case i_sel {
-            3'd0   : i_a[0],
-            ONE    : i_a[1],
-            2..=3  : i_a[2],
-            3'b1xx : i_a[3],
-            default: i_a[3],
-        };
+        assign bus.a = 0;
+    }
+    module ModuleB {
+        inst bus_if: InterfaceA;
+        inst u: ModuleA (bus: bus_if);
+        assign bus_if.b = 0;
     }
     "#;
 
@@ -2268,119 +3762,183 @@ fn invalid_case_condition_expression() {
     assert!(errors.is_empty());
 
     let code = r#"
-    module ModuleB (
-        i_sel: input  logic<2>,
-        i_a  : input  logic<3>,
-        o_b  : output logic,
+    module ModuleA (
+        #[flatten]
+        i_a: input logic,
+    ) {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidFlatten { .. }));
+
+    let code = r#"
+    interface InterfaceA #(
+        param W: u32 = 8,
     ) {
-        let c: logic<2> = 2'd0;
+        var a: logic<W>;
+        modport master {
+            a: output,
+        }
+    }
+    module ModuleA (
+        #[flatten]
+        bus: modport InterfaceA::master,
+    ) {
+        assign bus.a = 0;
+    }
+    "#;
 
-        always_comb {
-          case i_sel {
-            c      : o_b = i_a[0];
-            default: o_b = i_a[1];
-          }
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidFlatten { .. }));
+
+    let code = r#"
+    interface InterfaceA {
+        var a: logic;
+        modport master {
+            a: output,
         }
     }
+    module ModuleA (
+        #[flatten]
+        bus: modport InterfaceA::master,
+    ) {
+        inst u: ModuleB (bus);
+    }
+    module ModuleB (
+        bus: modport InterfaceA::master,
+    ) {
+        assign bus.a = 0;
+    }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(
-        errors[0],
-        AnalyzerError::InvalidCaseConditionNonElaborative { .. }
-    ));
+    assert!(matches!(errors[0], AnalyzerError::InvalidFlatten { .. }));
+}
 
+#[test]
+fn invalid_auto_connect() {
     let code = r#"
-    module ModuleC (
-        i_sel: input  logic<2>,
-        i_a  : input  logic<3>,
-        o_b  : output logic,
+    module ModuleA (
+        i_clk: input  clock   ,
+        a    : input  logic<8>,
+        o_b  : output logic<8>,
+    ) {
+        assign o_b = a;
+    }
+    module ModuleB (
+        i_clk: input  clock   ,
+        o_b  : output logic<8>,
     ) {
-        let c: logic<2> = 2'd1;
+        var a: logic<8>;
+        assign a = 1;
+        #[auto_connect]
+        inst u: ModuleA;
+    }
+    "#;
 
-        always_comb {
-          case i_sel {
-            0..=c  : o_b = i_a[0];
-            default: o_b = i_a[1];
-          }
-        }
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleA (
+        i_a: input logic<8>,
+    ) {}
+    module ModuleB {
+        var i_a: logic<4>;
+        assign i_a = 1;
+        #[auto_connect]
+        inst u: ModuleA;
     }
     "#;
 
     let errors = analyze(code);
     assert!(matches!(
         errors[0],
-        AnalyzerError::InvalidCaseConditionNonElaborative { .. }
+        AnalyzerError::InvalidAutoConnect { .. }
     ));
 
     let code = r#"
-    module ModuleD (
-        i_sel: input  logic<2>,
-        i_a  : input  logic<3>,
-        o_b  : output logic,
+    module ModuleA (
+        o_a: output logic,
     ) {
-        let c: logic<2> = 2'd0;
-
-        assign o_b = case i_sel {
-            c      : i_a[0],
-            default: i_a[1],
-        };
+        assign o_a = 1;
+    }
+    module ModuleB (
+        o_a: input logic,
+    ) {
+        #[auto_connect]
+        inst u: ModuleA;
     }
     "#;
 
     let errors = analyze(code);
     assert!(matches!(
         errors[0],
-        AnalyzerError::InvalidCaseConditionNonElaborative { .. }
+        AnalyzerError::InvalidAutoConnect { .. }
     ));
 
     let code = r#"
-    module ModuleE (
-        i_sel: input  logic<2>,
-        i_a  : input  logic<3>,
-        o_b  : output logic,
-    ) {
-        let c: logic<2> = 2'd1;
-
-        assign o_b = case i_sel {
-            0..=c  : i_a[0],
-            default: i_a[1],
-        };
+    module ModuleA (
+        i_a: input logic,
+    ) {}
+    module ModuleB {
+        #[auto_connect]
+        inst u: ModuleA;
     }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(
-        errors[0],
-        AnalyzerError::InvalidCaseConditionNonElaborative { .. }
-    ));
+    assert!(matches!(errors[0], AnalyzerError::MissingPort { .. }));
 }
 
 #[test]
-fn invalid_cast() {
+fn unsafe_fsm_encoding() {
     let code = r#"
     module ModuleA {
-        let a: clock = 1;
-        let _b: reset = a as reset;
+        #[fsm]
+        #[enum_encoding(onehot)]
+        enum StateA {
+            Idle,
+            Busy,
+        }
+        #[fsm]
+        #[enum_encoding(gray)]
+        enum StateB {
+            Idle,
+            Busy,
+        }
     }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::InvalidCast { .. }));
-}
+    assert!(errors.is_empty());
 
-#[test]
-fn invalid_test() {
     let code = r#"
-    module ModuleA {}
+    module ModuleB {
+        #[fsm]
+        enum State {
+            Idle,
+            Busy,
+        }
+    }
+    "#;
 
-    #[test(TestA)]
-    embed (cocotb) py {{{
-    }}}
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::UnsafeFsmEncoding { .. }));
+
+    let code = r#"
+    module ModuleC {
+        #[fsm]
+        #[enum_encoding(sequential)]
+        enum State {
+            Idle,
+            Busy,
+        }
+    }
     "#;
 
     let errors = analyze(code);
-    assert!(matches!(errors[0], AnalyzerError::InvalidTest { .. }));
+    assert!(matches!(errors[0], AnalyzerError::UnsafeFsmEncoding { .. }));
 }
 
 #[test]
@@ -2531,6 +4089,123 @@ fn clock_domain() {
     ));
 }
 
+#[test]
+fn clock_enable() {
+    let code = r#"
+    module ModuleA (
+        i_clk: input  clock,
+        i_rst: input  reset,
+        i_en : input  logic,
+        o_dat: output logic,
+    ) {
+        #[clock_en(i_en)]
+        always_ff {
+            if_reset {
+                o_dat = 0;
+            } else {
+                o_dat = 1;
+            }
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleB (
+        i_clk: input  `a clock,
+        i_en : input  `b logic,
+        o_dat: output `a logic,
+    ) {
+        #[clock_en(i_en)]
+        always_ff {
+            o_dat = 1;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::MismatchClockDomain { .. }
+    ));
+
+    let code = r#"
+    module ModuleC (
+        i_clk: input  clock,
+        o_dat: output logic,
+    ) {
+        #[clock_en(i_en)]
+        always_ff {
+            o_dat = 1;
+        }
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::UndefinedIdentifier { .. }
+    ));
+}
+
+#[test]
+fn sync() {
+    let code = r#"
+    module ModuleA (
+        i_clk_a: input  `a clock,
+        i_dat_a: input  `a logic,
+        i_clk_b: input  `b clock,
+        o_dat_b: output `b logic,
+    ) {
+        #[sync(i_dat_a, i_clk_b)]
+        var dat_b: `b logic;
+
+        assign o_dat_b = dat_b;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let code = r#"
+    module ModuleB (
+        i_clk_a: input  `a clock,
+        i_dat_a: input  `a logic,
+        i_clk_b: input  `b clock,
+        o_dat_a: output `a logic,
+    ) {
+        #[sync(i_dat_a, i_clk_b, "3")]
+        var dat_a: `a logic;
+
+        assign o_dat_a = dat_a;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::MismatchClockDomain { .. }
+    ));
+
+    let code = r#"
+    module ModuleC (
+        i_clk  : input  clock,
+        i_dat_a: input  logic,
+        o_dat_b: output logic,
+    ) {
+        #[sync(i_dat_a, i_dat_a)]
+        var dat_b: logic;
+
+        assign o_dat_b = dat_b;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(errors[0], AnalyzerError::InvalidClock { .. }));
+}
+
 #[test]
 fn r#unsafe() {
     let code = r#"
@@ -2649,3 +4324,224 @@ fn unresolvable_generic_argument() {
         AnalyzerError::UnresolvableGenericArgument { .. }
     ));
 }
+
+#[test]
+fn type_dag_dependencies() {
+    let code = r#"
+    package PackageA {
+        type word = logic<32>;
+    }
+    module ModuleA {
+        inst u0: ModuleB;
+    }
+    module ModuleB {
+        var a: PackageA::word;
+        assign a = 0;
+    }
+    module ModuleC {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let module_a = symbol_table::get_all()
+        .into_iter()
+        .find(|x| x.token.to_string() == "ModuleA")
+        .unwrap();
+    let names: Vec<_> = crate::type_dag::dependencies(module_a.id)
+        .iter()
+        .map(|x| x.token.to_string())
+        .collect();
+    assert!(names.contains(&"ModuleA".to_string()));
+    assert!(names.contains(&"ModuleB".to_string()));
+    assert!(names.contains(&"PackageA".to_string()));
+    assert!(!names.contains(&"ModuleC".to_string()));
+}
+
+#[test]
+fn type_dag_dependency_order() {
+    let code = r#"
+    module ModuleA {
+        inst u0: ModuleC;
+        inst u1: ModuleB;
+    }
+    module ModuleB {
+        var a: PackageA::word;
+        assign a = 0;
+    }
+    module ModuleC {}
+    package PackageA {
+        type word = logic<32>;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let names: Vec<_> = crate::type_dag::dependency_order()
+        .iter()
+        .map(|x| x.symbol.token.to_string())
+        .collect();
+    assert_eq!(names, vec!["ModuleC", "PackageA", "ModuleB", "ModuleA"]);
+}
+
+#[test]
+fn structural_hash() {
+    let code = r#"
+    module ModuleA (
+        i_a: input  logic<8>,
+        o_b: output logic<8>,
+    ) {
+        assign o_b = i_a + 1;
+    }
+    pub module ModuleB (
+        i_x: input  logic<8>,
+        o_y: output logic<8>,
+    ) {
+        // renamed copy
+        assign o_y = i_x + 1;
+    }
+    module ModuleC (
+        i_a: input  logic<16>,
+        o_b: output logic<16>,
+    ) {
+        assign o_b = i_a + 2;
+    }
+    module ModuleD (
+        i_a: input  logic<8>,
+        o_b: output logic<8>,
+    ) {
+        assign o_b = i_a - 1;
+    }
+    "#;
+
+    let parser = Parser::parse(code, &"").unwrap();
+    let hashes: Vec<_> = crate::structural_hash::module_hashes(&parser.veryl)
+        .into_iter()
+        .map(|(_, x)| x)
+        .collect();
+    assert_eq!(hashes.len(), 4);
+
+    assert_eq!(hashes[0], hashes[1]);
+    assert_ne!(hashes[0].exact, hashes[2].exact);
+    assert_eq!(hashes[0].near, hashes[2].near);
+    assert_ne!(hashes[0].near, hashes[3].near);
+}
+
+#[test]
+fn instance_graph() {
+    let code = r#"
+    module ModuleA {
+        inst u0: ModuleB;
+        inst u1: ModuleB;
+    }
+    module ModuleB {
+        :g {
+            inst u2: ModuleC;
+        }
+    }
+    module ModuleC {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let graph = InstanceGraph::new();
+    let name = |x| symbol_table::get(x).unwrap().token.to_string();
+    let instances: Vec<_> = graph
+        .instances()
+        .iter()
+        .map(|x| (name(x.parent), name(x.child), x.token.to_string()))
+        .collect();
+    assert_eq!(instances.len(), 3);
+    assert!(instances.contains(&(
+        "ModuleA".to_string(),
+        "ModuleB".to_string(),
+        "u1".to_string()
+    )));
+    assert!(instances.contains(&(
+        "ModuleB".to_string(),
+        "ModuleC".to_string(),
+        "u2".to_string()
+    )));
+
+    let module_b = graph
+        .instances()
+        .iter()
+        .find(|x| name(x.child) == "ModuleB")
+        .unwrap()
+        .child;
+    assert_eq!(graph.get_parents(module_b).len(), 2);
+    assert_eq!(graph.get_children(module_b).len(), 1);
+}
+
+#[test]
+fn cancellation() {
+    symbol_table::clear();
+
+    let code = r#"
+    module ModuleA {
+        var a: logic;
+        assign a = 1;
+    }
+    "#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+    let parser = Parser::parse(code, &"").unwrap();
+    let analyzer = Analyzer::new(&metadata);
+
+    metadata.cancellation.cancel();
+    let errors = analyzer.analyze_pass1("prj", code, "", &parser.veryl);
+    assert!(matches!(errors[..], [AnalyzerError::Cancelled]));
+    let symbols = symbol_table::get_all();
+    assert!(!symbols.iter().any(|x| x.token.to_string() == "ModuleA"));
+
+    metadata.cancellation.reset();
+    let mut errors = analyzer.analyze_pass1("prj", code, "", &parser.veryl);
+    Analyzer::analyze_post_pass1();
+    errors.append(&mut analyzer.analyze_pass2("prj", code, "", &parser.veryl));
+    errors.append(&mut analyzer.analyze_pass3("prj", code, "", &parser.veryl));
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn context() {
+    use crate::namespace::Namespace;
+    use crate::symbol_path::SymbolPath;
+    use crate::AnalyzerContext;
+    use veryl_parser::resource_table;
+
+    symbol_table::clear();
+
+    let resolve = |name: &str| {
+        let mut namespace = Namespace::new();
+        namespace.push(resource_table::insert_str("prj"));
+        let path = SymbolPath::new(&[resource_table::insert_str(name)]);
+        symbol_table::resolve((&path, &namespace)).is_ok()
+    };
+
+    let mut context_a = AnalyzerContext::new();
+    let mut context_b = AnalyzerContext::new();
+
+    let errors = context_a.enter(|| analyze("module ModuleA {}"));
+    assert!(errors.is_empty());
+
+    // The same name can be defined in another context without conflict
+    let errors = context_b.enter(|| analyze("module ModuleA {} module ModuleB {}"));
+    assert!(errors.is_empty());
+
+    assert!(context_a.enter(|| resolve("ModuleA") && !resolve("ModuleB")));
+    assert!(context_b.enter(|| resolve("ModuleA") && resolve("ModuleB")));
+
+    // The tables of the current thread are restored
+    assert!(!resolve("ModuleA"));
+
+    // The tables are restored even if panic occurs in the context
+    let ret = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        context_a.enter(|| panic!("panic in context"))
+    }));
+    assert!(ret.is_err());
+    assert!(!resolve("ModuleA"));
+    assert!(context_a.enter(|| resolve("ModuleA")));
+}
@@ -1,12 +1,13 @@
-use crate::symbol::{Symbol, SymbolId};
+use crate::symbol::{Symbol, SymbolId, SymbolKind};
 use crate::symbol_path::SymbolPathNamespace;
 use crate::symbol_table;
 use bimap::BiMap;
 use daggy::petgraph::unionfind::UnionFind;
 use daggy::petgraph::visit::{EdgeRef, NodeIndexable};
 use daggy::{petgraph::algo, Dag, Walker};
-use std::{cell::RefCell, collections::HashMap, collections::HashSet};
-use veryl_parser::veryl_token::Token;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::{cell::RefCell, path::PathBuf};
+use veryl_parser::veryl_token::{Token, TokenSource};
 
 #[derive(Clone, Default)]
 pub struct TypeDag {
@@ -42,15 +43,22 @@ pub enum Context {
     GenericInstance,
 }
 
+/// Module, interface or package with the file defining it
+#[derive(Clone, Debug)]
+pub struct DesignUnit {
+    pub symbol: Symbol,
+    /// `None` if it is not defined in any file like builtin packages
+    pub path: Option<PathBuf>,
+}
+
 #[derive(Debug, Clone)]
 pub enum DagError {
-    Cyclic(Symbol, Symbol),
+    Cyclic(Box<Symbol>, Box<Symbol>),
     UnableToResolve(Box<TypeResolveInfo>),
 }
 
 impl TypeDag {
-    #[allow(dead_code)]
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         let mut dag = Dag::<(), Context, u32>::new();
         let source = dag.add_node(()).index() as u32;
         Self {
@@ -113,7 +121,7 @@ impl TypeDag {
                 } else {
                     let ssym = self.get_symbol(start);
                     let esym = self.get_symbol(end);
-                    Err(DagError::Cyclic(ssym, esym))
+                    Err(DagError::Cyclic(Box::new(ssym), Box::new(esym)))
                 }
             }
         }
@@ -142,6 +150,83 @@ impl TypeDag {
         ret
     }
 
+    fn dependency_order(&self) -> Vec<DesignUnit> {
+        let graph = self.dag.graph();
+        let key = |node: u32| {
+            if let Some(symbol) = self.symbols.get(&node) {
+                let source = symbol.token.source.to_string();
+                let name = format!("{}::{}", symbol.namespace, symbol.token);
+                (source, name, node)
+            } else {
+                (String::new(), String::new(), node)
+            }
+        };
+
+        // Kahn's algorithm which takes the smallest key among the ready nodes,
+        // so the order doesn't depend on the order of insertion
+        let mut in_degree = HashMap::new();
+        for edge in graph.edge_references() {
+            *in_degree.entry(edge.target().index() as u32).or_insert(0) += 1;
+        }
+        let mut ready: BTreeSet<_> = graph
+            .node_indices()
+            .map(|x| x.index() as u32)
+            .filter(|x| !in_degree.contains_key(x))
+            .map(key)
+            .collect();
+
+        let mut ret = Vec::new();
+        while let Some((_, _, node)) = ready.pop_first() {
+            if let Some(symbol) = self.symbols.get(&node) {
+                if matches!(
+                    symbol.kind,
+                    SymbolKind::Module(_) | SymbolKind::Interface(_) | SymbolKind::Package(_)
+                ) {
+                    let path = if let TokenSource::File(x) = symbol.token.source {
+                        Some(PathBuf::from(x.to_string()))
+                    } else {
+                        None
+                    };
+                    ret.push(DesignUnit {
+                        symbol: symbol.clone(),
+                        path,
+                    });
+                }
+            }
+            for child in self.dag.children(node.into()).iter(&self.dag) {
+                let child = child.1.index() as u32;
+                let degree = in_degree.get_mut(&child).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.insert(key(child));
+                }
+            }
+        }
+        ret
+    }
+
+    fn dependencies(&self, id: SymbolId) -> Vec<Symbol> {
+        let Some(node) = self.nodes.get_by_left(&id) else {
+            return Vec::new();
+        };
+
+        // Edges are directed from dependency to user
+        let mut visited = HashSet::new();
+        let mut stack = vec![*node];
+        while let Some(node) = stack.pop() {
+            if visited.insert(node) {
+                for parent in self.dag.parents(node.into()).iter(&self.dag) {
+                    let parent = parent.1.index() as u32;
+                    if self.paths.contains_key(&parent) {
+                        stack.push(parent);
+                    }
+                }
+            }
+        }
+
+        visited.into_iter().map(|x| self.get_symbol(x)).collect()
+    }
+
     fn connected_components(&self) -> Vec<Vec<Symbol>> {
         let graph = self.dag.graph();
         let mut vertex_sets = UnionFind::new(graph.node_bound());
@@ -250,6 +335,17 @@ pub fn toposort() -> Vec<Symbol> {
     TYPE_DAG.with(|f| f.borrow().toposort())
 }
 
+/// Modules, interfaces and packages sorted so that dependencies precede their users.
+/// Independent ones are sorted by file path and name, so the order is deterministic.
+pub fn dependency_order() -> Vec<DesignUnit> {
+    TYPE_DAG.with(|f| f.borrow().dependency_order())
+}
+
+/// Symbols used from `id` directly or indirectly, including itself
+pub fn dependencies(id: SymbolId) -> Vec<Symbol> {
+    TYPE_DAG.with(|f| f.borrow().dependencies(id))
+}
+
 pub fn connected_components() -> Vec<Vec<Symbol>> {
     TYPE_DAG.with(|f| f.borrow().connected_components())
 }
@@ -261,3 +357,7 @@ pub fn dump() -> String {
 pub fn clear() {
     TYPE_DAG.with(|f| f.borrow_mut().clear())
 }
+
+pub(crate) fn swap(table: &mut TypeDag) {
+    TYPE_DAG.with(|f| std::mem::swap(&mut *f.borrow_mut(), table))
+}
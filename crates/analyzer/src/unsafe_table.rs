@@ -36,3 +36,7 @@ pub fn get_all() -> Vec<(TokenRange, Unsafe)> {
 pub fn clear() {
     UNSAFE_TABLE.with(|f| f.borrow_mut().clear())
 }
+
+pub(crate) fn swap(table: &mut RangeTable<Unsafe>) {
+    UNSAFE_TABLE.with(|f| std::mem::swap(&mut *f.borrow_mut(), table))
+}
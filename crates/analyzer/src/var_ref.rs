@@ -33,6 +33,7 @@ pub struct Assign {
     pub path: VarRefPath,
     pub position: AssignPosition,
     pub partial: bool,
+    pub range: Option<RangeInclusive<isize>>,
 }
 
 impl Assign {
@@ -42,6 +43,7 @@ impl Assign {
                 path: var_ref.path.clone(),
                 position: position.clone(),
                 partial: var_ref.path.is_partial(),
+                range: var_ref.path.partial_range(),
             },
             _ => unreachable!(),
         }
@@ -320,6 +322,18 @@ impl VarRefPath {
             .iter()
             .any(|x| !matches!(x, VarRefPathItem::Identifier { .. }))
     }
+
+    /// Returns the selected range of the last identifier if it is statically known
+    pub fn partial_range(&self) -> Option<RangeInclusive<isize>> {
+        let last = self
+            .0
+            .iter()
+            .rposition(|x| matches!(x, VarRefPathItem::Identifier { .. }))?;
+        match &self.0[last + 1..] {
+            [x] => x.select_range(x),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for VarRefPath {
@@ -491,7 +505,7 @@ pub enum AssignPositionType {
     },
     StatementBranch {
         token: Token,
-        branches: usize,
+        items: Vec<Token>,
         has_default: bool,
         allow_missing_reset_statement: bool,
         r#type: AssignStatementBranchType,
@@ -557,6 +571,16 @@ pub enum AssignStatementBranchItemType {
     Case,
 }
 
+/// Branch statement which doesn't assign a variable in all branches
+#[derive(Clone, Debug)]
+pub struct UncoveredBranch {
+    pub token: Token,
+    /// Branch items assigning the variable
+    pub assigned: Vec<Token>,
+    /// Branch items not assigning the variable
+    pub missing: Vec<Token>,
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct AssignPositionTree {
     r#type: Option<AssignPositionType>,
@@ -586,7 +610,7 @@ impl AssignPositionTree {
         self.children.push(node);
     }
 
-    pub fn check_always_comb_uncovered(&self) -> Option<Token> {
+    pub fn check_always_comb_uncovered(&self) -> Option<UncoveredBranch> {
         if let Some(AssignPositionType::Declaration { ref r#type, .. }) = self.r#type {
             if *r#type == AssignDeclarationType::AlwaysComb {
                 let children: Vec<_> = self
@@ -612,16 +636,32 @@ impl AssignPositionTree {
         None
     }
 
-    fn impl_always_comb_uncovered(&self) -> Option<Token> {
+    fn impl_always_comb_uncovered(&self) -> Option<UncoveredBranch> {
         match self.r#type {
             Some(AssignPositionType::StatementBranch {
                 token,
-                branches,
+                ref items,
                 has_default,
                 ..
             }) => {
-                if !has_default || self.children.len() != branches {
-                    Some(token)
+                let assigned: Vec<_> = self
+                    .children
+                    .iter()
+                    .filter_map(|x| match x.r#type {
+                        Some(AssignPositionType::StatementBranchItem { index, .. }) => Some(index),
+                        _ => None,
+                    })
+                    .collect();
+                if !has_default || assigned.len() != items.len() {
+                    let (assigned, missing) = items
+                        .iter()
+                        .enumerate()
+                        .partition::<Vec<_>, _>(|(i, _)| assigned.contains(i));
+                    Some(UncoveredBranch {
+                        token,
+                        assigned: assigned.into_iter().map(|(_, x)| *x).collect(),
+                        missing: missing.into_iter().map(|(_, x)| *x).collect(),
+                    })
                 } else {
                     self.children
                         .iter()
@@ -0,0 +1,43 @@
+/* C API of Veryl checker provided by veryl-capi */
+
+#ifndef VERYL_H
+#define VERYL_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum {
+    VERYL_SEVERITY_ERROR   = 0,
+    VERYL_SEVERITY_WARNING = 1,
+    VERYL_SEVERITY_ADVICE  = 2,
+} VerylSeverity;
+
+typedef struct VerylCheck VerylCheck;
+
+/* Parses and analyzes `len` bytes of UTF-8 source.
+ * The result must be released by `veryl_check_free`.
+ * Null `source` and internal errors are reported as error diagnostic. */
+VerylCheck *veryl_check(const char *source, size_t len);
+void veryl_check_free(VerylCheck *check);
+
+/* Diagnostics are accessed by index in [0, veryl_check_len(check)).
+ * Strings are owned by `check` and valid until `veryl_check_free`.
+ * Null `check` is treated as a result without diagnostics. */
+size_t veryl_check_len(const VerylCheck *check);
+bool veryl_check_has_error(const VerylCheck *check);
+VerylSeverity veryl_check_severity(const VerylCheck *check, size_t index);
+const char *veryl_check_code(const VerylCheck *check, size_t index);
+const char *veryl_check_message(const VerylCheck *check, size_t index);
+/* 1-origin, or 0 if the diagnostic has no location */
+size_t veryl_check_line(const VerylCheck *check, size_t index);
+size_t veryl_check_column(const VerylCheck *check, size_t index);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,256 @@
+//! C API to embed Veryl checking into EDA tools without subprocess.
+//!
+//! See `include/veryl.h` for the declarations.
+
+#[cfg(test)]
+mod tests;
+
+use miette::{Diagnostic, Severity};
+use std::ffi::{c_char, CString};
+use std::ptr;
+use veryl_analyzer::Analyzer;
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+
+/// Name of the project which the checked buffer belongs to
+const PROJECT_NAME: &str = "project";
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerylSeverity {
+    Error = 0,
+    Warning = 1,
+    Advice = 2,
+}
+
+struct VerylDiagnostic {
+    severity: VerylSeverity,
+    code: CString,
+    message: CString,
+    /// 1-origin, or 0 if the diagnostic has no location
+    line: usize,
+    column: usize,
+}
+
+impl VerylDiagnostic {
+    fn new(source: &str, diag: &dyn Diagnostic, severity: Severity) -> Self {
+        let severity = match severity {
+            Severity::Error => VerylSeverity::Error,
+            Severity::Warning => VerylSeverity::Warning,
+            Severity::Advice => VerylSeverity::Advice,
+        };
+        let code = diag.code().map(|x| x.to_string()).unwrap_or_default();
+        let (line, column) = diag
+            .labels()
+            .and_then(|mut x| x.next())
+            .map(|x| line_column(source, x.offset()))
+            .unwrap_or((0, 0));
+        Self {
+            severity,
+            code: to_cstring(code),
+            message: to_cstring(diag.to_string()),
+            line,
+            column,
+        }
+    }
+
+    fn error(message: String) -> Self {
+        Self {
+            severity: VerylSeverity::Error,
+            code: to_cstring(String::new()),
+            message: to_cstring(message),
+            line: 0,
+            column: 0,
+        }
+    }
+}
+
+/// Result of `veryl_check`
+pub struct VerylCheck {
+    diagnostics: Vec<VerylDiagnostic>,
+}
+
+impl VerylCheck {
+    fn error(message: String) -> Self {
+        Self {
+            diagnostics: vec![VerylDiagnostic::error(message)],
+        }
+    }
+}
+
+/// Returns `index`-th diagnostic, or `None` if `check` is null or `index` is out of range
+unsafe fn get<'a>(check: *const VerylCheck, index: usize) -> Option<&'a VerylDiagnostic> {
+    check.as_ref().and_then(|x| x.diagnostics.get(index))
+}
+
+fn to_cstring(x: String) -> CString {
+    CString::new(x.replace('\0', "")).unwrap()
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let head = &source.as_bytes()[..offset];
+    let line = head.iter().filter(|x| **x == b'\n').count() + 1;
+    let column = head.iter().rev().take_while(|x| **x != b'\n').count() + 1;
+    (line, column)
+}
+
+fn check(source: &str) -> VerylCheck {
+    let metadata: Metadata = Metadata::create_default_toml(PROJECT_NAME)
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    let mut diagnostics = Vec::new();
+    match Parser::parse(source, &"") {
+        Ok(parser) => {
+            let analyzer = Analyzer::new(&metadata);
+            analyzer.clear();
+
+            let mut errors = vec![];
+            errors.append(&mut analyzer.analyze_pass1(PROJECT_NAME, source, "", &parser.veryl));
+            Analyzer::analyze_post_pass1();
+            errors.append(&mut analyzer.analyze_pass2(PROJECT_NAME, source, "", &parser.veryl));
+            errors.append(&mut analyzer.analyze_pass3(PROJECT_NAME, source, "", &parser.veryl));
+
+            for error in &errors {
+                let severity = error.severity_with(&metadata.lint);
+                diagnostics.push(VerylDiagnostic::new(source, error, severity));
+            }
+        }
+        Err(error) => {
+            diagnostics.push(VerylDiagnostic::new(source, &error, Severity::Error));
+        }
+    }
+    VerylCheck { diagnostics }
+}
+
+/// Parses and analyzes `len` bytes of UTF-8 source.
+/// The result must be released by `veryl_check_free`.
+/// Null `source` and internal panic are reported as error diagnostic.
+///
+/// # Safety
+///
+/// `source` must be null or point `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn veryl_check(source: *const c_char, len: usize) -> *mut VerylCheck {
+    let ret = if source.is_null() {
+        VerylCheck::error("source is null".to_string())
+    } else {
+        let bytes = std::slice::from_raw_parts(source as *const u8, len);
+        match std::str::from_utf8(bytes) {
+            // Panic must not unwind across the FFI boundary
+            Ok(x) => std::panic::catch_unwind(|| check(x)).unwrap_or_else(|x| {
+                let message = x
+                    .downcast_ref::<&str>()
+                    .map(|x| x.to_string())
+                    .or_else(|| x.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                VerylCheck::error(format!("internal error: {message}"))
+            }),
+            Err(x) => VerylCheck::error(x.to_string()),
+        }
+    };
+    Box::into_raw(Box::new(ret))
+}
+
+/// # Safety
+///
+/// `check` must be null or returned by `veryl_check` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn veryl_check_free(check: *mut VerylCheck) {
+    if !check.is_null() {
+        drop(Box::from_raw(check));
+    }
+}
+
+/// Returns the number of diagnostics
+///
+/// # Safety
+///
+/// `check` must be null or returned by `veryl_check` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn veryl_check_len(check: *const VerylCheck) -> usize {
+    check.as_ref().map(|x| x.diagnostics.len()).unwrap_or(0)
+}
+
+/// Returns whether any diagnostic is error
+///
+/// # Safety
+///
+/// `check` must be null or returned by `veryl_check` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn veryl_check_has_error(check: *const VerylCheck) -> bool {
+    check
+        .as_ref()
+        .map(|x| {
+            x.diagnostics
+                .iter()
+                .any(|x| x.severity == VerylSeverity::Error)
+        })
+        .unwrap_or(false)
+}
+
+/// Returns the severity of `index`-th diagnostic, or error for out of range
+///
+/// # Safety
+///
+/// `check` must be null or returned by `veryl_check` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn veryl_check_severity(
+    check: *const VerylCheck,
+    index: usize,
+) -> VerylSeverity {
+    get(check, index)
+        .map(|x| x.severity)
+        .unwrap_or(VerylSeverity::Error)
+}
+
+/// Returns the code like `undefined_identifier` of `index`-th diagnostic.
+/// The string is owned by `check`, and null is returned for out of range.
+///
+/// # Safety
+///
+/// `check` must be null or returned by `veryl_check` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn veryl_check_code(check: *const VerylCheck, index: usize) -> *const c_char {
+    get(check, index)
+        .map(|x| x.code.as_ptr())
+        .unwrap_or(ptr::null())
+}
+
+/// Returns the message of `index`-th diagnostic.
+/// The string is owned by `check`, and null is returned for out of range.
+///
+/// # Safety
+///
+/// `check` must be null or returned by `veryl_check` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn veryl_check_message(
+    check: *const VerylCheck,
+    index: usize,
+) -> *const c_char {
+    get(check, index)
+        .map(|x| x.message.as_ptr())
+        .unwrap_or(ptr::null())
+}
+
+/// Returns the 1-origin line of `index`-th diagnostic, or 0 if unknown
+///
+/// # Safety
+///
+/// `check` must be null or returned by `veryl_check` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn veryl_check_line(check: *const VerylCheck, index: usize) -> usize {
+    get(check, index).map(|x| x.line).unwrap_or(0)
+}
+
+/// Returns the 1-origin column of `index`-th diagnostic, or 0 if unknown
+///
+/// # Safety
+///
+/// `check` must be null or returned by `veryl_check` and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn veryl_check_column(check: *const VerylCheck, index: usize) -> usize {
+    get(check, index).map(|x| x.column).unwrap_or(0)
+}
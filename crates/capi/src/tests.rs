@@ -0,0 +1,71 @@
+use crate::*;
+use std::ffi::CStr;
+
+fn diagnostics(source: &str) -> Vec<(VerylSeverity, String, usize, usize)> {
+    let mut ret = Vec::new();
+    unsafe {
+        let check = veryl_check(source.as_ptr() as *const c_char, source.len());
+        for i in 0..veryl_check_len(check) {
+            let code = CStr::from_ptr(veryl_check_code(check, i));
+            ret.push((
+                veryl_check_severity(check, i),
+                code.to_string_lossy().to_string(),
+                veryl_check_line(check, i),
+                veryl_check_column(check, i),
+            ));
+        }
+        assert!(veryl_check_message(check, ret.len()).is_null());
+        veryl_check_free(check);
+    }
+    ret
+}
+
+#[test]
+fn check_buffer() {
+    let code = "module ModuleA {\n    assign a = 1;\n}\n";
+    let ret = diagnostics(code);
+    assert_eq!(
+        ret,
+        vec![(
+            VerylSeverity::Error,
+            "undefined_identifier".to_string(),
+            2,
+            12
+        )]
+    );
+
+    let code = "module ModuleA {\n    let a: logic = 1;\n}\n";
+    let ret = diagnostics(code);
+    assert_eq!(ret.len(), 1);
+    assert_eq!(ret[0].0, VerylSeverity::Warning);
+    assert_eq!(ret[0].1, "unused_variable");
+
+    let code = "module ModuleA {\n";
+    let ret = diagnostics(code);
+    assert_eq!(ret.len(), 1);
+    assert_eq!(ret[0].0, VerylSeverity::Error);
+
+    let code = "module ModuleA {}\n";
+    assert!(diagnostics(code).is_empty());
+}
+
+#[test]
+fn null_pointer() {
+    unsafe {
+        let check = veryl_check(ptr::null(), 0);
+        assert_eq!(veryl_check_len(check), 1);
+        assert!(veryl_check_has_error(check));
+        let message = CStr::from_ptr(veryl_check_message(check, 0));
+        assert_eq!(message.to_string_lossy(), "source is null");
+        veryl_check_free(check);
+
+        let check = ptr::null();
+        assert_eq!(veryl_check_len(check), 0);
+        assert!(!veryl_check_has_error(check));
+        assert_eq!(veryl_check_severity(check, 0), VerylSeverity::Error);
+        assert!(veryl_check_code(check, 0).is_null());
+        assert!(veryl_check_message(check, 0).is_null());
+        assert_eq!(veryl_check_line(check, 0), 0);
+        assert_eq!(veryl_check_column(check, 0), 0);
+    }
+}
@@ -1,9 +1,12 @@
-use crate::emitter::{identifier_with_prefix_suffix, symbol_string, SymbolContext};
-use std::collections::HashMap;
-use veryl_analyzer::symbol::{GenericMap, SymbolKind};
+use crate::emitter::{
+    flattened_inst_ports, log_level, param_overrides, renamed_inst_ports, signal_token,
+    symbol_string, SymbolContext,
+};
+use std::collections::{HashMap, HashSet};
+use veryl_analyzer::symbol::GenericMap;
 use veryl_analyzer::symbol_table;
 use veryl_metadata::{Build, BuiltinType, Metadata};
-use veryl_parser::resource_table::StrId;
+use veryl_parser::resource_table::{StrId, TokenId};
 use veryl_parser::veryl_grammar_trait::*;
 use veryl_parser::veryl_token::{Token, VerylToken};
 use veryl_parser::veryl_walker::VerylWalker;
@@ -137,6 +140,9 @@ pub struct Aligner {
     project_name: Option<StrId>,
     build_opt: Build,
     generic_map: Vec<GenericMap>,
+    param_overrides: HashMap<TokenId, String>,
+    flattened_inst_ports: HashSet<StrId>,
+    renamed_inst_ports: HashMap<StrId, String>,
 }
 
 impl Aligner {
@@ -150,6 +156,7 @@ impl Aligner {
     }
 
     pub fn align(&mut self, input: &Veryl) {
+        self.param_overrides = param_overrides(&self.build_opt, input);
         self.veryl(input);
         self.finish_group();
         for align in &self.aligns {
@@ -295,36 +302,24 @@ impl VerylWalker for Aligner {
 
     /// Semantic action for non-terminal 'Identifier'
     fn identifier(&mut self, arg: &Identifier) {
-        let (prefix, suffix) = if let Ok(found) = symbol_table::resolve(arg) {
-            match &found.found.kind {
-                SymbolKind::Port(x) => (x.prefix.clone(), x.suffix.clone()),
-                SymbolKind::Variable(x) => (x.prefix.clone(), x.suffix.clone()),
-                _ => (None, None),
-            }
-        } else {
-            (None, None)
-        };
-        self.veryl_token(&identifier_with_prefix_suffix(arg, &prefix, &suffix));
+        let symbol = symbol_table::resolve(arg).ok().map(|x| x.found);
+        self.veryl_token(&signal_token(arg, symbol.as_ref(), &self.build_opt));
     }
 
     /// Semantic action for non-terminal 'HierarchicalIdentifier'
     fn hierarchical_identifier(&mut self, arg: &HierarchicalIdentifier) {
         let list_len = &arg.hierarchical_identifier_list0.len();
-        let (prefix, suffix) = if let Ok(found) = symbol_table::resolve(arg) {
-            match &found.found.kind {
-                SymbolKind::Port(x) => (x.prefix.clone(), x.suffix.clone()),
-                SymbolKind::Variable(x) => (x.prefix.clone(), x.suffix.clone()),
-                _ => (None, None),
-            }
+        let symbol = if let Ok(found) = symbol_table::resolve(arg) {
+            found.found
         } else {
             unreachable!()
         };
 
         if *list_len == 0 {
-            self.veryl_token(&identifier_with_prefix_suffix(
+            self.veryl_token(&signal_token(
                 &arg.identifier,
-                &prefix,
-                &suffix,
+                Some(&symbol),
+                &self.build_opt,
             ));
         } else {
             self.identifier(&arg.identifier);
@@ -337,11 +332,7 @@ impl VerylWalker for Aligner {
         for (i, x) in arg.hierarchical_identifier_list0.iter().enumerate() {
             self.dot(&x.dot);
             if (i + 1) == *list_len {
-                self.veryl_token(&identifier_with_prefix_suffix(
-                    &x.identifier,
-                    &prefix,
-                    &suffix,
-                ));
+                self.veryl_token(&signal_token(&x.identifier, Some(&symbol), &self.build_opt));
             } else {
                 self.identifier(&x.identifier);
             }
@@ -632,9 +623,14 @@ impl VerylWalker for Aligner {
 
     /// Semantic action for non-terminal 'IdentifierStatement'
     fn identifier_statement(&mut self, arg: &IdentifierStatement) {
-        self.aligns[align_kind::IDENTIFIER].start_item();
-        self.expression_identifier(&arg.expression_identifier);
-        self.aligns[align_kind::IDENTIFIER].finish_item();
+        // Logging function is not aligned because it is lowered to another call
+        if log_level(&arg.expression_identifier).is_some() {
+            self.expression_identifier(&arg.expression_identifier);
+        } else {
+            self.aligns[align_kind::IDENTIFIER].start_item();
+            self.expression_identifier(&arg.expression_identifier);
+            self.aligns[align_kind::IDENTIFIER].finish_item();
+        }
         match &*arg.identifier_statement_group {
             IdentifierStatementGroup::FunctionCall(x) => {
                 self.function_call(&x.function_call);
@@ -823,6 +819,8 @@ impl VerylWalker for Aligner {
         if single_line {
             return;
         }
+        self.flattened_inst_ports = flattened_inst_ports(arg).into_keys().collect();
+        self.renamed_inst_ports = renamed_inst_ports(arg, &self.build_opt);
         if let Some(ref x) = arg.inst_declaration_opt {
             self.array(&x.array);
         }
@@ -860,8 +858,25 @@ impl VerylWalker for Aligner {
 
     /// Semantic action for non-terminal 'InstPortItem'
     fn inst_port_item(&mut self, arg: &InstPortItem) {
+        // flattened port is expanded to multiple lines, so it breaks alignment groups
+        if self
+            .flattened_inst_ports
+            .contains(&arg.identifier.identifier_token.token.text)
+        {
+            self.aligns[align_kind::IDENTIFIER].finish_group();
+            self.aligns[align_kind::EXPRESSION].finish_group();
+            return;
+        }
         self.aligns[align_kind::IDENTIFIER].start_item();
-        self.identifier(&arg.identifier);
+        if let Some(x) = self
+            .renamed_inst_ports
+            .get(&arg.identifier.identifier_token.token.text)
+            .cloned()
+        {
+            self.veryl_token(&arg.identifier.identifier_token.replace(&x));
+        } else {
+            self.identifier(&arg.identifier);
+        }
         self.aligns[align_kind::IDENTIFIER].finish_item();
         if let Some(ref x) = arg.inst_port_item_opt {
             self.colon(&x.colon);
@@ -870,17 +885,12 @@ impl VerylWalker for Aligner {
             self.expression(&x.expression);
             self.aligns[align_kind::EXPRESSION].finish_item();
         } else {
-            let (prefix, suffix) = if let Ok(found) = symbol_table::resolve(arg.identifier.as_ref())
-            {
-                match &found.found.kind {
-                    SymbolKind::Port(x) => (x.prefix.clone(), x.suffix.clone()),
-                    SymbolKind::Variable(x) => (x.prefix.clone(), x.suffix.clone()),
-                    _ => (None, None),
-                }
+            let symbol = if let Ok(found) = symbol_table::resolve(arg.identifier.as_ref()) {
+                found.found
             } else {
                 unreachable!()
             };
-            let token = identifier_with_prefix_suffix(&arg.identifier, &prefix, &suffix);
+            let token = signal_token(&arg.identifier, Some(&symbol), &self.build_opt);
             self.aligns[align_kind::EXPRESSION].start_item();
             self.aligns[align_kind::EXPRESSION].duplicated_token(&token, 0);
             self.aligns[align_kind::EXPRESSION].finish_item();
@@ -924,7 +934,13 @@ impl VerylWalker for Aligner {
         }
         self.equ(&arg.equ);
         self.aligns[align_kind::EXPRESSION].start_item();
-        self.expression(&arg.expression);
+        let token = arg.identifier.identifier_token.token.id;
+        if let Some(value) = self.param_overrides.get(&token) {
+            let token = arg.equ.equ_token.replace(value);
+            self.aligns[align_kind::EXPRESSION].duplicated_token(&token, 0);
+        } else {
+            self.expression(&arg.expression);
+        }
         self.aligns[align_kind::EXPRESSION].finish_item();
     }
 
@@ -1,18 +1,26 @@
 use crate::aligner::{Aligner, Location};
+use crate::provenance::Provenance;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use veryl_analyzer::attribute::Attribute as Attr;
 use veryl_analyzer::attribute::EnumEncodingItem;
 use veryl_analyzer::evaluator::{Evaluated, Evaluator};
 use veryl_analyzer::namespace::Namespace;
 use veryl_analyzer::symbol::TypeModifier as SymTypeModifier;
 use veryl_analyzer::symbol::{
-    GenericMap, Symbol, SymbolId, SymbolKind, TypeKind, VariableAffiliation,
+    auto_connections, unconnected_ports, Direction as SymDirection, FlattenedMember,
+    GenericBoundKind, GenericMap, Port, Symbol, SymbolId, SymbolKind, TypeKind,
+    VariableAffiliation,
 };
 use veryl_analyzer::symbol_path::{GenericSymbolPath, SymbolPath};
 use veryl_analyzer::symbol_table;
-use veryl_analyzer::{msb_table, namespace_table};
-use veryl_metadata::{Build, BuiltinType, ClockType, Format, Metadata, ResetType, SourceMapTarget};
-use veryl_parser::resource_table::{self, StrId};
+use veryl_analyzer::{attribute_table, msb_table, namespace_table};
+use veryl_metadata::{
+    Build, BuiltinType, ClockGatingCell, ClockType, Format, LogStyle, MemoryStyle, Metadata,
+    ProtocolCheck, ResetType, SourceMapTarget, TypeGenericStyle,
+};
+use veryl_parser::resource_table::{self, StrId, TokenId};
 use veryl_parser::veryl_grammar_trait::*;
 use veryl_parser::veryl_token::{Token, TokenSource, VerylToken};
 use veryl_parser::veryl_walker::VerylWalker;
@@ -30,6 +38,35 @@ pub enum AttributeType {
     Test,
 }
 
+/// Reset synchronizer inserted by `[build.reset_synchronizer]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResetSynchronizerReport {
+    pub module: String,
+    pub clock: String,
+    pub reset: String,
+    /// Name of the synchronized reset
+    pub name: String,
+}
+
+/// Port connected by `#[auto_connect]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutoConnectReport {
+    pub module: String,
+    pub instance: String,
+    pub port: String,
+    /// Name of the connected signal
+    pub target: String,
+}
+
+/// Module or port renamed by `[build.rename]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenameReport {
+    /// Module which has the renamed port, or `None` if the module itself is renamed
+    pub module: Option<String>,
+    pub original: String,
+    pub renamed: String,
+}
+
 pub struct Emitter {
     project_name: Option<StrId>,
     build_opt: Build,
@@ -52,6 +89,15 @@ pub struct Emitter {
     default_clock: Option<SymbolId>,
     default_reset: Option<SymbolId>,
     reset_signal: Option<String>,
+    clock_enable: Option<String>,
+    gated_clock: Option<String>,
+    gated_clocks: HashSet<String>,
+    synced_reset: Option<String>,
+    synced_resets: HashSet<String>,
+    reset_synchronizers: Vec<ResetSynchronizerReport>,
+    auto_connects: Vec<AutoConnectReport>,
+    renames: Vec<RenameReport>,
+    module_name: String,
     default_block: Option<String>,
     enum_width: usize,
     emit_enum_implicit_valiant: bool,
@@ -59,7 +105,14 @@ pub struct Emitter {
     attribute: Vec<AttributeType>,
     assignment_lefthand_side: Option<ExpressionIdentifier>,
     generic_map: Vec<GenericMap>,
+    in_parameterized_generic: bool,
+    flattened_inst_ports: HashMap<StrId, Vec<FlattenedMember>>,
+    renamed_inst_ports: HashMap<StrId, String>,
     source_map: Option<SourceMap>,
+    provenance: Option<Provenance>,
+    param_overrides: HashMap<TokenId, String>,
+    protected: bool,
+    project_path: PathBuf,
 }
 
 impl Default for Emitter {
@@ -86,6 +139,15 @@ impl Default for Emitter {
             default_clock: None,
             default_reset: None,
             reset_signal: None,
+            clock_enable: None,
+            gated_clock: None,
+            gated_clocks: HashSet::new(),
+            synced_reset: None,
+            synced_resets: HashSet::new(),
+            reset_synchronizers: Vec::new(),
+            auto_connects: Vec::new(),
+            renames: Vec::new(),
+            module_name: String::new(),
             default_block: None,
             enum_width: 0,
             emit_enum_implicit_valiant: false,
@@ -93,7 +155,14 @@ impl Default for Emitter {
             attribute: Vec::new(),
             assignment_lefthand_side: None,
             generic_map: Vec::new(),
+            in_parameterized_generic: false,
+            flattened_inst_ports: HashMap::new(),
+            renamed_inst_ports: HashMap::new(),
             source_map: None,
+            provenance: None,
+            param_overrides: HashMap::new(),
+            protected: false,
+            project_path: PathBuf::new(),
         }
     }
 }
@@ -111,16 +180,41 @@ impl Emitter {
             format_opt: metadata.format.clone(),
             aligner,
             source_map: Some(source_map),
+            project_path: metadata
+                .metadata_path
+                .parent()
+                .map(|x| x.to_path_buf())
+                .unwrap_or_default(),
             ..Default::default()
         }
     }
 
     pub fn emit(&mut self, project_name: &str, input: &Veryl) {
         namespace_table::set_default(&[project_name.into()]);
+        self.param_overrides = param_overrides(&self.build_opt, input);
         self.aligner.align(input);
         self.veryl(input);
     }
 
+    /// Emits only the specified modules, interfaces and packages with file scope imports.
+    /// Returns `false` if none of them is found in `input`.
+    pub fn emit_only(&mut self, project_name: &str, input: &Veryl, names: &[&str]) -> bool {
+        let mut input = input.clone();
+        let mut found = false;
+        input.veryl_list.retain(|x| {
+            let target = description_names(&x.description_group)
+                .iter()
+                .any(|x| names.contains(&x.as_str()));
+            found |= target;
+            target || is_import(&x.description_group)
+        });
+
+        if found {
+            self.emit(project_name, &input);
+        }
+        found
+    }
+
     pub fn as_str(&self) -> &str {
         &self.string
     }
@@ -129,6 +223,27 @@ impl Emitter {
         self.source_map.as_mut().unwrap()
     }
 
+    /// Set provenance written in the header if `[build.header]` is enabled
+    pub fn set_provenance(&mut self, provenance: Provenance) {
+        self.provenance = Some(provenance);
+    }
+
+    pub fn protected(&self) -> bool {
+        self.protected
+    }
+
+    pub fn reset_synchronizers(&self) -> &[ResetSynchronizerReport] {
+        &self.reset_synchronizers
+    }
+
+    pub fn auto_connects(&self) -> &[AutoConnectReport] {
+        &self.auto_connects
+    }
+
+    pub fn renames(&self) -> &[RenameReport] {
+        &self.renames
+    }
+
     fn str(&mut self, x: &str) {
         self.string.push_str(x);
 
@@ -218,6 +333,104 @@ impl Emitter {
         }
     }
 
+    fn header(&mut self) {
+        let mut lines = vec![format!(
+            "Generated by Veryl {}. DO NOT EDIT.",
+            env!("CARGO_PKG_VERSION")
+        )];
+        if let Some(ref x) = self.provenance {
+            let items = [
+                ("Source", &x.source),
+                ("Commit", &x.commit),
+                ("Timestamp", &x.timestamp),
+            ];
+            for (name, value) in items {
+                if let Some(value) = value {
+                    lines.push(format!("{:<9} : {}", name, value));
+                }
+            }
+        }
+        for x in &self.build_opt.header.text {
+            lines.extend(x.lines().map(|x| x.to_string()));
+        }
+
+        for line in lines {
+            self.str(format!("// {line}").trim_end());
+            self.str(NEWLINE);
+        }
+        self.str(NEWLINE);
+    }
+
+    /// Comments of resolved parameter values, port widths and source locations of the module
+    fn annotation(&mut self, symbol: &Symbol) {
+        let SymbolKind::Module(ref property) = symbol.kind else {
+            return;
+        };
+
+        let mut lines = vec![(symbol.token.to_string(), self.location(&symbol.token))];
+        for x in &property.parameters {
+            let Some(param) = symbol_table::get(x.symbol) else {
+                continue;
+            };
+            let SymbolKind::Parameter(ref y) = param.kind else {
+                continue;
+            };
+            let value = if let Some(value) = self.param_overrides.get(&param.token.id) {
+                format!("{value} (overridden)")
+            } else if let Evaluated::Fixed { value, .. } = param.evaluate() {
+                value.to_string()
+            } else {
+                let mut stringifier = Stringifier::new();
+                stringifier.expression(&y.value);
+                stringifier.as_str().to_string()
+            };
+            lines.push((
+                format!("  param {}: {} = {}", param.token, y.r#type, value),
+                self.location(&param.token),
+            ));
+        }
+        for x in &property.ports {
+            let Some(port) = symbol_table::get(x.symbol) else {
+                continue;
+            };
+            let SymbolKind::Port(ref y) = port.kind else {
+                continue;
+            };
+            let mut text = format!("  port  {}: {}", port.token, y.direction);
+            if let Some(ref r#type) = y.r#type {
+                text.push_str(&format!(" {type}"));
+                let width = if r#type.array.is_empty() {
+                    Evaluator::new().type_width(r#type.clone())
+                } else {
+                    None
+                };
+                if let Some(width) = width {
+                    text.push_str(&format!(" ({width} bits)"));
+                }
+            }
+            lines.push((text, self.location(&port.token)));
+        }
+
+        let width = lines.iter().map(|x| x.0.len()).max().unwrap_or(0);
+        for (text, location) in lines {
+            self.str(&format!("// {text:<width$}  {location}"));
+            self.str(NEWLINE);
+        }
+    }
+
+    /// Source location of `token` relative to the project
+    fn location(&self, token: &Token) -> String {
+        let source = token.source.to_string();
+        let path = Path::new(&source);
+        let path = path.strip_prefix(&self.project_path).unwrap_or(path);
+        format!(
+            "{}:{}:{}",
+            path.to_string_lossy().replace('\\', "/"),
+            token.line,
+            token.column
+        )
+    }
+
     fn space(&mut self, repeat: usize) {
         self.str(&" ".repeat(repeat));
     }
@@ -478,7 +691,12 @@ impl Emitter {
     }
 
     fn always_ff_implicit_clock_event(&mut self) {
-        let symbol = symbol_table::get(self.default_clock.unwrap()).unwrap();
+        self.clock_event(self.default_clock.unwrap());
+    }
+
+    fn clock_event(&mut self, clock: SymbolId) {
+        let symbol = symbol_table::get(clock).unwrap();
+        let renamed = renamed_port(&symbol, &self.build_opt);
         let (clock_kind, prefix, suffix) = match symbol.kind {
             SymbolKind::Port(x) => (
                 x.r#type.clone().unwrap().kind,
@@ -501,7 +719,11 @@ impl Emitter {
         }
         self.space(1);
 
-        if prefix.is_some() || suffix.is_some() {
+        if let Some(gated) = self.gated_clock.clone() {
+            self.str(&gated);
+        } else if let Some(x) = renamed {
+            self.str(&x);
+        } else if prefix.is_some() || suffix.is_some() {
             let token = VerylToken::new(symbol.token).append(&prefix, &suffix);
             self.str(&token.token.to_string());
         } else {
@@ -524,6 +746,7 @@ impl Emitter {
 
     fn always_ff_implicit_reset_event(&mut self) {
         let symbol = symbol_table::get(self.default_reset.unwrap()).unwrap();
+        let renamed = renamed_port(&symbol, &self.build_opt);
         let (reset_kind, prefix, suffix) = match symbol.kind {
             SymbolKind::Port(x) => (
                 x.r#type.clone().unwrap().kind,
@@ -542,7 +765,11 @@ impl Emitter {
             _ => unreachable!(),
         };
 
-        let token = if prefix.is_some() || suffix.is_some() {
+        let token = if let Some(synced) = self.synced_reset.clone() {
+            VerylToken::new(symbol.token).replace(&synced).token
+        } else if let Some(x) = renamed {
+            VerylToken::new(symbol.token).replace(&x).token
+        } else if prefix.is_some() || suffix.is_some() {
             VerylToken::new(symbol.token).append(&prefix, &suffix).token
         } else {
             symbol.token
@@ -595,6 +822,45 @@ impl Emitter {
         }
     }
 
+    fn protect_begin(&mut self) {
+        for pragma in self.build_opt.protect.pragmas() {
+            self.str(&format!("`pragma protect {}", pragma));
+            self.newline();
+        }
+        self.str("`pragma protect begin");
+        self.newline();
+        self.protected = true;
+    }
+
+    fn protect_end(&mut self) {
+        self.newline();
+        self.str("`pragma protect end");
+    }
+
+    /// Emits attribute as preprocessor directive closed by `attribute_end`
+    fn directive_attribute<F: FnOnce(&mut Self)>(&mut self, arg: &Attribute, directive: F) {
+        let comma = if self.string.trim_end().ends_with(',') {
+            self.unindent();
+            self.truncate(self.string.len() - format!(",{}", NEWLINE).len());
+            self.newline();
+            true
+        } else {
+            false
+        };
+
+        self.consume_adjust_line(&arg.identifier.identifier_token.token);
+        directive(self);
+        self.newline();
+        self.attribute.push(AttributeType::Ifdef);
+
+        if comma {
+            self.str(",");
+            self.newline();
+        }
+
+        self.clear_adjust_line();
+    }
+
     fn attribute_end(&mut self) {
         match self.attribute.pop() {
             Some(AttributeType::Ifdef) => {
@@ -635,6 +901,257 @@ impl Emitter {
             .contains(&BuiltinType::Type)
     }
 
+    /// Returns the module name renamed by `[build.rename]`
+    fn renamed_module(&mut self, name: &str) -> String {
+        if let Some(x) = self.build_opt.rename.module(name) {
+            self.renames.push(RenameReport {
+                module: None,
+                original: name.to_string(),
+                renamed: x.clone(),
+            });
+            x
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Whether `symbol` is a module emitted with `parameter type` instead of monomorphized modules
+    fn is_parameterized_generic(&self, symbol: &Symbol) -> bool {
+        if self.build_opt.type_generic_style != TypeGenericStyle::Parameter
+            || !matches!(symbol.kind, SymbolKind::Module(_))
+        {
+            return false;
+        }
+        let params = symbol.generic_parameters();
+        !params.is_empty()
+            && params
+                .iter()
+                .all(|(_, x)| x.bound == GenericBoundKind::Type)
+    }
+
+    /// Emits generic parameters as `parameter type` items
+    fn generic_type_parameters(&mut self, arg: &WithGenericParameter) {
+        let items: Vec<WithGenericParameterItem> = arg.with_generic_parameter_list.as_ref().into();
+        for (i, x) in items.iter().enumerate() {
+            if i != 0 {
+                self.str(",");
+                self.newline();
+            }
+            self.str("parameter type ");
+            self.str(&x.identifier.identifier_token.to_string());
+            self.str(" = ");
+            if let Some(ref x) = x.with_generic_parameter_item_opt {
+                self.with_generic_argument_item(&x.with_generic_argument_item);
+            } else {
+                self.str("logic");
+            }
+        }
+    }
+
+    /// Emits generic arguments as parameter overrides of an instance
+    fn generic_type_arguments(&mut self, base: &Symbol, arg: &WithGenericArgument) {
+        let params = base.generic_parameters();
+        let items: Vec<WithGenericArgumentItem> = if let Some(ref x) = arg.with_generic_argument_opt
+        {
+            x.with_generic_argument_list.as_ref().into()
+        } else {
+            Vec::new()
+        };
+        for (i, (x, (name, _))) in items.iter().zip(params.iter()).enumerate() {
+            if i != 0 {
+                self.str(",");
+                if self.single_line {
+                    self.space(1);
+                } else {
+                    self.newline();
+                }
+            }
+            self.str(".");
+            self.str(&name.to_string());
+            self.str(" (");
+            self.with_generic_argument_item(x);
+            self.str(")");
+        }
+    }
+
+    /// Emits a reference to a member of modport port with `#[flatten]` as the flattened port
+    fn flattened_reference(&mut self, port: &Identifier, dot: &Dot, member: &Identifier) -> bool {
+        let Ok(symbol) = symbol_table::resolve(port) else {
+            return false;
+        };
+        if flattened_members(&symbol.found).is_none() {
+            return false;
+        }
+        let text = format!("{}_{}", port.identifier_token, member.identifier_token);
+        self.veryl_token(&port.identifier_token.replace(&text));
+        self.token(&dot.dot_token.replace(""));
+        self.token(&member.identifier_token.replace(""));
+        true
+    }
+
+    /// Emits connections of the flattened ports of an instance
+    fn flattened_connection(&mut self, arg: &InstPortItem, members: &[FlattenedMember]) {
+        let port = arg.identifier.identifier_token.to_string();
+        let (target, is_flattened) = if let Some(ref x) = arg.inst_port_item_opt {
+            let mut stringifier = Stringifier::new();
+            stringifier.expression(&x.expression);
+            if stringifier.as_str() == "_" {
+                (None, false)
+            } else {
+                let mut emitter = Emitter {
+                    project_name: self.project_name,
+                    build_opt: self.build_opt.clone(),
+                    format_opt: self.format_opt.clone(),
+                    generic_map: self.generic_map.clone(),
+                    ..Default::default()
+                };
+                emitter.expression(&x.expression);
+                let namespace =
+                    namespace_table::get(arg.identifier.identifier_token.token.id).unwrap();
+                let path = SymbolPath::new(&[resource_table::insert_str(stringifier.as_str())]);
+                let is_flattened = symbol_table::resolve((&path, &namespace))
+                    .is_ok_and(|x| flattened_members(&x.found).is_some());
+                (Some(emitter.as_str().to_string()), is_flattened)
+            }
+        } else {
+            let is_flattened = symbol_table::resolve(arg.identifier.as_ref())
+                .is_ok_and(|x| flattened_members(&x.found).is_some());
+            (Some(port.clone()), is_flattened)
+        };
+
+        self.token(&arg.identifier.identifier_token.replace(""));
+        for (i, member) in members.iter().enumerate() {
+            if i != 0 {
+                self.str(",");
+                self.newline();
+            }
+            self.str(&format!(".{}_{} (", port, member.name));
+            match target {
+                Some(ref x) if is_flattened => self.str(&format!("{}_{}", x, member.name)),
+                Some(ref x) => self.str(&format!("{}.{}", x, member.name)),
+                None => (),
+            }
+            self.str(")");
+        }
+        if let Some(ref x) = arg.inst_port_item_opt {
+            self.token(&x.colon.colon_token.replace(""));
+        }
+    }
+
+    /// Emits connections of the ports connected by `#[auto_connect]`, and explicit empty
+    /// connections of the ports which are not connected
+    fn implicit_connection(
+        &mut self,
+        arg: &InstDeclaration,
+        connections: &[(Port, Symbol)],
+        unconnected: &[Port],
+    ) {
+        let with_prefix_suffix = |name: &str, prefix: Option<String>, suffix: Option<String>| {
+            let name = name.strip_prefix("r#").unwrap_or(name);
+            format!(
+                "{}{}{}",
+                prefix.unwrap_or_default(),
+                name,
+                suffix.unwrap_or_default()
+            )
+        };
+
+        let mut items = Vec::new();
+        for (port, target) in connections {
+            let property = port.property();
+            let port = with_prefix_suffix(&port.name.to_string(), property.prefix, property.suffix);
+            let port = self.build_opt.rename.port(&port).unwrap_or(port);
+            let (prefix, suffix) = match &target.kind {
+                SymbolKind::Port(x) => (x.prefix.clone(), x.suffix.clone()),
+                SymbolKind::Variable(x) => (x.prefix.clone(), x.suffix.clone()),
+                _ => (None, None),
+            };
+            let target = renamed_port(target, &self.build_opt)
+                .unwrap_or_else(|| with_prefix_suffix(&target.token.to_string(), prefix, suffix));
+            items.push(format!(".{} ({})", port, target));
+            self.auto_connects.push(AutoConnectReport {
+                module: self.module_name.clone(),
+                instance: arg.identifier.identifier_token.to_string(),
+                port,
+                target,
+            });
+        }
+        for port in unconnected {
+            if let Some(members) = self.flattened_inst_ports.get(&port.name) {
+                for member in members {
+                    items.push(format!(".{}_{} ()", port.name, member.name));
+                }
+            } else {
+                let property = port.property();
+                let port =
+                    with_prefix_suffix(&port.name.to_string(), property.prefix, property.suffix);
+                let port = self.build_opt.rename.port(&port).unwrap_or(port);
+                items.push(format!(".{} ()", port));
+            }
+        }
+
+        for (i, item) in items.iter().enumerate() {
+            if i != 0 {
+                self.str(",");
+                self.newline();
+            }
+            self.str(item);
+        }
+    }
+
+    /// Emits `$log_*` according to `log_style`
+    fn log_function_call(&mut self, level: &str, ident: &ExpressionIdentifier, arg: &FunctionCall) {
+        let token = ident.identifier();
+        let args = |this: &mut Self| {
+            if let Some(ref x) = arg.function_call_opt {
+                this.str("$sformatf(");
+                this.argument_list(&x.argument_list);
+                this.str(")");
+            } else {
+                this.str("\"\"");
+            }
+        };
+
+        match self.build_opt.log_style {
+            LogStyle::Display => {
+                self.token(&token.replace("$display"));
+                self.l_paren(&arg.l_paren);
+                self.str(&format!("\"[{}] %s\",", level.to_uppercase()));
+                self.space(1);
+                args(self);
+                self.r_paren(&arg.r_paren);
+                if level == "fatal" {
+                    self.str(";");
+                    self.space(1);
+                    self.str("$finish");
+                }
+            }
+            LogStyle::Severity => {
+                self.token(&token.replace(&format!("${level}")));
+                self.l_paren(&arg.l_paren);
+                if level == "fatal" {
+                    self.str("1,");
+                    self.space(1);
+                }
+                args(self);
+                self.r_paren(&arg.r_paren);
+            }
+            LogStyle::Uvm => {
+                self.token(&token.replace(&format!("`uvm_{level}")));
+                self.l_paren(&arg.l_paren);
+                self.str("\"VERYL\",");
+                self.space(1);
+                args(self);
+                if level == "info" {
+                    self.str(",");
+                    self.space(1);
+                    self.str("UVM_MEDIUM");
+                }
+                self.r_paren(&arg.r_paren);
+            }
+        }
+    }
+
     fn emit_generate_named_block(&mut self, arg: &GenerateNamedBlock, prefix: &str) {
         self.default_block = Some(arg.identifier.identifier_token.to_string());
         self.token_will_push(
@@ -707,6 +1224,473 @@ impl Emitter {
             _ => 0,
         }
     }
+
+    /// Returns enable signal specified by `#[clock_en]`
+    fn always_ff_clock_enable(&mut self, arg: &AlwaysFfDeclaration) -> Option<String> {
+        let token = attribute_table::get(&arg.always_ff.always_ff_token.token)
+            .into_iter()
+            .find_map(|x| match x {
+                Attr::ClockEn(x) => Some(x),
+                _ => None,
+            })?;
+        signal_name(&token, &self.build_opt)
+    }
+
+    fn always_ff_clock_signal(&mut self, arg: &AlwaysFfDeclaration) -> String {
+        if let Some(ref x) = arg.always_ff_declaration_opt {
+            let clock = &x
+                .alwayf_ff_event_list
+                .always_ff_clock
+                .hierarchical_identifier;
+            let found = symbol_table::resolve(clock.as_ref()).map(|x| x.found);
+            let (prefix, suffix) = match found.clone().map(|x| x.kind) {
+                Ok(SymbolKind::Port(x)) => (x.prefix, x.suffix),
+                Ok(SymbolKind::Variable(x)) => (x.prefix, x.suffix),
+                _ => (None, None),
+            };
+            let mut stringifier = Stringifier::new();
+            if let Some(x) = found
+                .ok()
+                .and_then(|x| renamed_hierarchical_identifier(clock, &x, &self.build_opt))
+            {
+                stringifier.hierarchical_identifier(&x);
+            } else {
+                stringifier.hierarchical_identifier_with_prefix_suffix(clock, &prefix, &suffix);
+            }
+            stringifier.as_str().to_string()
+        } else {
+            let symbol = symbol_table::get(self.default_clock.unwrap()).unwrap();
+            if let Some(x) = renamed_port(&symbol, &self.build_opt) {
+                return x;
+            }
+            let (prefix, suffix) = match symbol.kind {
+                SymbolKind::Port(x) => (x.prefix, x.suffix),
+                SymbolKind::Variable(x) => (x.prefix, x.suffix),
+                _ => (None, None),
+            };
+            VerylToken::new(symbol.token)
+                .append(&prefix, &suffix)
+                .token
+                .to_string()
+        }
+    }
+
+    /// Emits registers between stages of `#[pipeline]`
+    fn emit_pipeline(&mut self, arg: &VarDeclaration, valid: Option<Token>) {
+        let Some(name) = signal_name(&arg.identifier.identifier_token.token, &self.build_opt)
+        else {
+            return;
+        };
+        let valid = valid.and_then(|x| signal_name(&x, &self.build_opt));
+        // Pipeline qualified by valid signal doesn't need reset
+        let reset = valid.is_none() && self.default_reset.is_some();
+        let value = match valid {
+            Some(valid) => format!("{valid}[i - 1] ? {name}[i - 1] : {name}[i]"),
+            None => format!("{name}[i - 1]"),
+        };
+
+        // Each stage is driven by its own always_ff to keep the stage index constant
+        self.newline();
+        self.str(&format!(
+            "for (genvar i = 1; i < $size({name}); i++) begin : __{name}_pipeline"
+        ));
+        self.newline_push();
+        self.str("always_ff @ (");
+        self.always_ff_implicit_clock_event();
+        if reset {
+            self.always_ff_implicit_reset_event();
+        }
+        self.str(") begin");
+        self.newline_push();
+        if reset {
+            let reset_signal = self.reset_signal.clone().unwrap();
+            self.str(&format!("if ({reset_signal}) begin"));
+            self.newline_push();
+            self.str(&format!("{name}[i] <= '0;"));
+            self.newline_pop();
+            self.str("end else begin");
+            self.newline_push();
+            self.str(&format!("{name}[i] <= {value};"));
+            self.newline_pop();
+            self.str("end");
+        } else {
+            self.str(&format!("{name}[i] <= {value};"));
+        }
+        self.newline_pop();
+        self.str("end");
+        self.newline_pop();
+        self.str("end");
+    }
+
+    /// Emits multi-flop synchronizer driving the variable with `#[sync]`
+    fn emit_synchronizer(
+        &mut self,
+        arg: &VarDeclaration,
+        source: &Token,
+        clock: &Token,
+        stages: usize,
+    ) {
+        let (Some(name), Some(source_name)) = (
+            signal_name(&arg.identifier.identifier_token.token, &self.build_opt),
+            signal_name(source, &self.build_opt),
+        ) else {
+            return;
+        };
+        let Some(clock) = namespace_table::get(clock.id).and_then(|namespace| {
+            symbol_table::resolve((&SymbolPath::new(&[clock.text]), &namespace)).ok()
+        }) else {
+            return;
+        };
+        let regs = format!("__{name}_sync");
+
+        self.newline();
+        self.str(&format!("logic [$bits({name})-1:0] {regs} [0:{stages}-1];"));
+        self.newline();
+        self.str("always_ff @ (");
+        self.clock_event(clock.found.id);
+        self.str(") begin");
+        self.newline_push();
+        self.str(&format!("{regs}[0] <= {source_name};"));
+        self.newline();
+        self.str(&format!(
+            "for (int unsigned i = 1; i < {stages}; i++) begin"
+        ));
+        self.newline_push();
+        self.str(&format!("{regs}[i] <= {regs}[i - 1];"));
+        self.newline_pop();
+        self.str("end");
+        self.newline_pop();
+        self.str("end");
+        self.newline();
+        self.str(&format!("always_comb {name} = {regs}[{stages}-1];"));
+    }
+
+    /// Emits parity bit and its checker of the register with `#[parity]`
+    fn emit_parity(&mut self, arg: &VarDeclaration) {
+        let Some(name) = signal_name(&arg.identifier.identifier_token.token, &self.build_opt)
+        else {
+            return;
+        };
+        self.newline();
+        self.str(&format!("logic __{name}_parity;"));
+        self.newline();
+        self.str(&format!("logic __{name}_parity_error;"));
+        self.newline();
+        self.str(&format!(
+            "always_comb __{name}_parity_error = (^{name}) ^ __{name}_parity;"
+        ));
+    }
+
+    /// Emits update of the parity bit with the value assigned to the register with `#[parity]`
+    fn emit_parity_update(&mut self, arg: &IdentifierStatement, assignment: &Assignment) {
+        let ident = arg.expression_identifier.as_ref();
+        // Partial assignment is rejected by analyzer
+        if !ident.expression_identifier_list.is_empty()
+            || !ident.expression_identifier_list0.is_empty()
+        {
+            return;
+        }
+        let Ok(symbol) = symbol_table::resolve(ident) else {
+            return;
+        };
+        let is_parity = attribute_table::get(&symbol.found.token)
+            .iter()
+            .any(|x| matches!(x, Attr::Parity));
+        if !is_parity {
+            return;
+        }
+        let Some(name) = signal_name(&ident.identifier().token, &self.build_opt) else {
+            return;
+        };
+
+        let mut emitter = Emitter {
+            project_name: self.project_name,
+            build_opt: self.build_opt.clone(),
+            format_opt: self.format_opt.clone(),
+            generic_map: self.generic_map.clone(),
+            ..Default::default()
+        };
+        emitter.expression(&assignment.expression);
+        let value = match &*assignment.assignment_group {
+            AssignmentGroup::Equ(_) => emitter.as_str().to_string(),
+            AssignmentGroup::AssignmentOperator(x) => {
+                let token = x
+                    .assignment_operator
+                    .assignment_operator_token
+                    .token
+                    .to_string();
+                format!(
+                    "{name} {} ({})",
+                    &token[0..token.len() - 1],
+                    emitter.as_str()
+                )
+            }
+        };
+
+        // The value is casted to the register width so that fill literals like `'1` are expanded
+        self.newline();
+        self.str(&format!("__{name}_parity <= ^(($bits({name}))'({value}));"));
+    }
+
+    /// Emits `bind` attaching the protocol checkers of `#[protocol]` to every interface instance
+    fn emit_protocol_bind(&mut self, arg: &InterfaceDeclaration, interface: &str) {
+        if self.build_opt.protocol_check == ProtocolCheck::None {
+            return;
+        }
+        let context: SymbolContext = self.into();
+        let checkers: Vec<_> = attribute_table::get(&arg.identifier.identifier_token.token)
+            .into_iter()
+            .filter_map(|x| match x {
+                Attr::Protocol(x) => {
+                    let namespace = namespace_table::get(x.id)?;
+                    let symbol =
+                        symbol_table::resolve((&SymbolPath::new(&[x.text]), &namespace)).ok()?;
+                    let name = format!(
+                        "{}{}",
+                        namespace_string(&symbol.found.namespace, &context),
+                        x
+                    );
+                    Some((name, x))
+                }
+                _ => None,
+            })
+            .collect();
+        if checkers.is_empty() {
+            return;
+        }
+
+        let simulation_only = self.build_opt.protocol_check == ProtocolCheck::Simulation;
+        self.newline();
+        if simulation_only {
+            self.str("`ifndef SYNTHESIS");
+            self.newline_push();
+        }
+        for (i, (name, checker)) in checkers.iter().enumerate() {
+            if i != 0 {
+                self.newline();
+            }
+            self.str(&format!("bind {interface} {name} u__{checker} (.*);"));
+        }
+        if simulation_only {
+            self.newline_pop();
+            self.str("`endif");
+        }
+    }
+
+    /// Emits clock gating cell driving the clock of always_ff
+    fn emit_clock_gating_cell(
+        &mut self,
+        arg: &AlwaysFfDeclaration,
+        cell: &ClockGatingCell,
+        enable: &str,
+    ) {
+        let clock = self.always_ff_clock_signal(arg);
+        let name: String = format!("__{clock}_{enable}_gated")
+            .chars()
+            .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+            .collect();
+
+        // The same gated clock is shared in the module
+        if self.gated_clocks.insert(name.clone()) {
+            self.str(&format!("logic {name};"));
+            self.newline();
+            self.str(&format!(
+                "{} u{name} (.{}({clock}), .{}({enable}), .{}({name}));",
+                cell.module, cell.clock_port, cell.enable_port, cell.gated_clock_port
+            ));
+            self.newline();
+        }
+        self.gated_clock = Some(name);
+    }
+
+    /// Emits synchronizer of asynchronous reset of always_ff for the clock domain
+    fn emit_reset_synchronizer(&mut self, arg: &AlwaysFfDeclaration, stages: usize) {
+        let Some((reset, reset_type)) = self.always_ff_reset_signal(arg) else {
+            return;
+        };
+        let (edge, op, asserted, deasserted) = match reset_type {
+            ResetType::AsyncHigh => ("posedge", "", "'1", "1'b0"),
+            ResetType::AsyncLow => ("negedge", "!", "'0", "1'b1"),
+            ResetType::SyncHigh | ResetType::SyncLow => return,
+        };
+        let clock = self.always_ff_clock_signal(arg);
+        let clock_edge = match self.always_ff_clock_type(arg) {
+            ClockType::PosEdge => "posedge",
+            ClockType::NegEdge => "negedge",
+        };
+        let name: String = format!("__{reset}_{clock}_sync")
+            .chars()
+            .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+            .collect();
+
+        // The same synchronized reset is shared in the clock domain of the module
+        if self.synced_resets.insert(name.clone()) {
+            let regs = format!("{name}_ff");
+            self.str(&format!("logic [{stages}-1:0] {regs};"));
+            self.newline();
+            self.str(&format!(
+                "always_ff @ ({clock_edge} {clock}, {edge} {reset}) begin"
+            ));
+            self.newline_push();
+            self.str(&format!("if ({op}{reset}) begin"));
+            self.newline_push();
+            self.str(&format!("{regs} <= {asserted};"));
+            self.newline_pop();
+            self.str("end else begin");
+            self.newline_push();
+            self.str(&format!("{regs}[0] <= {deasserted};"));
+            self.newline();
+            self.str(&format!(
+                "for (int unsigned i = 1; i < {stages}; i++) begin"
+            ));
+            self.newline_push();
+            self.str(&format!("{regs}[i] <= {regs}[i - 1];"));
+            self.newline_pop();
+            self.str("end");
+            self.newline_pop();
+            self.str("end");
+            self.newline_pop();
+            self.str("end");
+            self.newline();
+            self.str(&format!("logic {name};"));
+            self.newline();
+            self.str(&format!("always_comb {name} = {regs}[{stages}-1];"));
+            self.newline();
+
+            self.reset_synchronizers.push(ResetSynchronizerReport {
+                module: self.module_name.clone(),
+                clock,
+                reset,
+                name: name.clone(),
+            });
+        }
+        self.synced_reset = Some(name);
+    }
+
+    /// Returns reset signal and its type used by always_ff
+    fn always_ff_reset_signal(&mut self, arg: &AlwaysFfDeclaration) -> Option<(String, ResetType)> {
+        let (kind, name) = if let Some(ref x) = arg.always_ff_declaration_opt {
+            let reset = &x
+                .alwayf_ff_event_list
+                .alwayf_ff_event_list_opt
+                .as_ref()?
+                .always_ff_reset
+                .hierarchical_identifier;
+            let found = symbol_table::resolve(reset.as_ref()).ok()?.found;
+            let (kind, prefix, suffix) = match found.kind.clone() {
+                SymbolKind::Port(x) => (x.r#type?.kind, x.prefix, x.suffix),
+                SymbolKind::Variable(x) => (x.r#type.kind, x.prefix, x.suffix),
+                _ => return None,
+            };
+            let mut stringifier = Stringifier::new();
+            if let Some(x) = renamed_hierarchical_identifier(reset, &found, &self.build_opt) {
+                stringifier.hierarchical_identifier(&x);
+            } else {
+                stringifier.hierarchical_identifier_with_prefix_suffix(reset, &prefix, &suffix);
+            }
+            (kind, stringifier.as_str().to_string())
+        } else {
+            if !self.always_ff_if_reset_exists(arg) {
+                return None;
+            }
+            let symbol = symbol_table::get(self.default_reset?)?;
+            let renamed = renamed_port(&symbol, &self.build_opt);
+            let (kind, prefix, suffix) = match symbol.kind {
+                SymbolKind::Port(x) => (x.r#type?.kind, x.prefix, x.suffix),
+                SymbolKind::Variable(x) => (x.r#type.kind, x.prefix, x.suffix),
+                _ => return None,
+            };
+            let name = renamed.unwrap_or_else(|| {
+                VerylToken::new(symbol.token)
+                    .append(&prefix, &suffix)
+                    .token
+                    .to_string()
+            });
+            (kind, name)
+        };
+        let reset_type = match kind {
+            TypeKind::ResetAsyncHigh => ResetType::AsyncHigh,
+            TypeKind::ResetAsyncLow => ResetType::AsyncLow,
+            TypeKind::ResetSyncHigh => ResetType::SyncHigh,
+            TypeKind::ResetSyncLow => ResetType::SyncLow,
+            TypeKind::Reset => self.build_opt.reset_type,
+            _ => return None,
+        };
+        Some((name, reset_type))
+    }
+
+    fn always_ff_clock_type(&mut self, arg: &AlwaysFfDeclaration) -> ClockType {
+        let symbol = if let Some(ref x) = arg.always_ff_declaration_opt {
+            let clock = &x
+                .alwayf_ff_event_list
+                .always_ff_clock
+                .hierarchical_identifier;
+            symbol_table::resolve(clock.as_ref()).ok().map(|x| x.found)
+        } else {
+            self.default_clock.and_then(symbol_table::get)
+        };
+        let kind = match symbol.map(|x| x.kind) {
+            Some(SymbolKind::Port(x)) => x.r#type.map(|x| x.kind),
+            Some(SymbolKind::Variable(x)) => Some(x.r#type.kind),
+            _ => None,
+        };
+        match kind {
+            Some(TypeKind::ClockPosedge) => ClockType::PosEdge,
+            Some(TypeKind::ClockNegedge) => ClockType::NegEdge,
+            _ => self.build_opt.clock_type,
+        }
+    }
+
+    fn always_ff_reset_identifier(&mut self, arg: &AlwaysFfReset) {
+        if let Some(synced) = self.synced_reset.clone() {
+            let token = &arg.hierarchical_identifier.identifier.identifier_token;
+            self.token(&token.replace(&synced));
+        } else {
+            self.hierarchical_identifier(&arg.hierarchical_identifier);
+        }
+    }
+
+    /// Emits branches except reset under `else if (enable)`
+    fn if_reset_statement_with_enable(&mut self, arg: &IfResetStatement, enable: &str) {
+        if let Some(first) = arg.if_reset_statement_list.first() {
+            self.space(1);
+            self.r#else(&first.r#else);
+            self.space(1);
+            self.str(&format!("if ({enable})"));
+            self.space(1);
+            self.str("begin");
+            self.newline_push();
+            for (i, x) in arg.if_reset_statement_list.iter().enumerate() {
+                if i != 0 {
+                    self.space(1);
+                    self.r#else(&x.r#else);
+                    self.space(1);
+                }
+                self.r#if(&x.r#if);
+                self.space(1);
+                self.str("(");
+                self.expression(&x.expression);
+                self.str(")");
+                self.space(1);
+                self.statement_block(&x.statement_block);
+            }
+            if let Some(ref x) = arg.if_reset_statement_opt {
+                self.space(1);
+                self.r#else(&x.r#else);
+                self.space(1);
+                self.statement_block(&x.statement_block);
+            }
+            self.newline_pop();
+            self.str("end");
+        } else if let Some(ref x) = arg.if_reset_statement_opt {
+            self.space(1);
+            self.r#else(&x.r#else);
+            self.space(1);
+            self.str(&format!("if ({enable})"));
+            self.space(1);
+            self.statement_block(&x.statement_block);
+        }
+    }
 }
 
 fn is_var_declaration(arg: &StatementBlockItem) -> bool {
@@ -717,6 +1701,45 @@ fn is_let_statement(arg: &StatementBlockItem) -> bool {
     matches!(arg, StatementBlockItem::LetStatement(_))
 }
 
+fn description_names(arg: &DescriptionGroup) -> Vec<String> {
+    match &*arg.description_group_group {
+        DescriptionGroupGroup::LBraceDescriptionGroupGroupListRBrace(x) => x
+            .description_group_group_list
+            .iter()
+            .flat_map(|x| description_names(&x.description_group))
+            .collect(),
+        DescriptionGroupGroup::DescriptionItem(x) => match &*x.description_item {
+            DescriptionItem::ModuleDeclaration(x) => {
+                vec![x.module_declaration.identifier.identifier_token.to_string()]
+            }
+            DescriptionItem::InterfaceDeclaration(x) => {
+                vec![x
+                    .interface_declaration
+                    .identifier
+                    .identifier_token
+                    .to_string()]
+            }
+            DescriptionItem::PackageDeclaration(x) => {
+                vec![x
+                    .package_declaration
+                    .identifier
+                    .identifier_token
+                    .to_string()]
+            }
+            _ => Vec::new(),
+        },
+    }
+}
+
+fn is_import(arg: &DescriptionGroup) -> bool {
+    match &*arg.description_group_group {
+        DescriptionGroupGroup::DescriptionItem(x) => {
+            matches!(&*x.description_item, DescriptionItem::ImportDeclaration(_))
+        }
+        _ => false,
+    }
+}
+
 impl VerylWalker for Emitter {
     /// Semantic action for non-terminal 'VerylToken'
     fn veryl_token(&mut self, arg: &VerylToken) {
@@ -892,36 +1915,36 @@ impl VerylWalker for Emitter {
 
     /// Semantic action for non-terminal 'Identifier'
     fn identifier(&mut self, arg: &Identifier) {
-        let (prefix, suffix) = if let Ok(found) = symbol_table::resolve(arg) {
-            match &found.found.kind {
-                SymbolKind::Port(x) => (x.prefix.clone(), x.suffix.clone()),
-                SymbolKind::Variable(x) => (x.prefix.clone(), x.suffix.clone()),
-                _ => (None, None),
-            }
-        } else {
-            (None, None)
-        };
-        self.veryl_token(&identifier_with_prefix_suffix(arg, &prefix, &suffix));
+        let symbol = symbol_table::resolve(arg).ok().map(|x| x.found);
+        self.veryl_token(&signal_token(arg, symbol.as_ref(), &self.build_opt));
     }
 
     /// Semantic action for non-terminal 'HierarchicalIdentifier'
     fn hierarchical_identifier(&mut self, arg: &HierarchicalIdentifier) {
-        let list_len = &arg.hierarchical_identifier_list0.len();
-        let (prefix, suffix) = if let Ok(found) = symbol_table::resolve(arg) {
-            match &found.found.kind {
-                SymbolKind::Port(x) => (x.prefix.clone(), x.suffix.clone()),
-                SymbolKind::Variable(x) => (x.prefix.clone(), x.suffix.clone()),
-                _ => (None, None),
+        if let (true, [x]) = (
+            arg.hierarchical_identifier_list.is_empty(),
+            arg.hierarchical_identifier_list0.as_slice(),
+        ) {
+            if self.flattened_reference(&arg.identifier, &x.dot, &x.identifier) {
+                for x in &x.hierarchical_identifier_list0_list {
+                    self.select(&x.select);
+                }
+                return;
             }
+        }
+
+        let list_len = &arg.hierarchical_identifier_list0.len();
+        let symbol = if let Ok(found) = symbol_table::resolve(arg) {
+            found.found
         } else {
             unreachable!()
         };
 
         if *list_len == 0 {
-            self.veryl_token(&identifier_with_prefix_suffix(
+            self.veryl_token(&signal_token(
                 &arg.identifier,
-                &prefix,
-                &suffix,
+                Some(&symbol),
+                &self.build_opt,
             ));
         } else {
             self.identifier(&arg.identifier);
@@ -934,11 +1957,7 @@ impl VerylWalker for Emitter {
         for (i, x) in arg.hierarchical_identifier_list0.iter().enumerate() {
             self.dot(&x.dot);
             if (i + 1) == *list_len {
-                self.veryl_token(&identifier_with_prefix_suffix(
-                    &x.identifier,
-                    &prefix,
-                    &suffix,
-                ));
+                self.veryl_token(&signal_token(&x.identifier, Some(&symbol), &self.build_opt));
             } else {
                 self.identifier(&x.identifier);
             }
@@ -979,7 +1998,20 @@ impl VerylWalker for Emitter {
         path.apply_map(&self.generic_map);
         if let Ok(symbol) = symbol_table::resolve((&path.mangled_path(), &namespace)) {
             let context: SymbolContext = self.into();
-            let text = symbol_string(arg.identifier(), &symbol.found, &context);
+            let text = match &symbol.found.kind {
+                SymbolKind::GenericInstance(x) => {
+                    let base = symbol_table::get(x.base).unwrap();
+                    if self.is_parameterized_generic(&base) {
+                        symbol_string(arg.identifier(), &base, &context)
+                    } else {
+                        symbol_string(arg.identifier(), &symbol.found, &context)
+                    }
+                }
+                SymbolKind::GenericParameter(_) if self.in_parameterized_generic => {
+                    symbol.found.token.to_string()
+                }
+                _ => symbol_string(arg.identifier(), &symbol.found, &context),
+            };
             self.veryl_token(&arg.identifier().replace(&text));
         } else if !path.is_resolvable() {
             // emit literal by generics
@@ -990,11 +2022,30 @@ impl VerylWalker for Emitter {
 
     /// Semantic action for non-terminal 'ExpressionIdentifier'
     fn expression_identifier(&mut self, arg: &ExpressionIdentifier) {
-        self.scoped_identifier(&arg.scoped_identifier);
-        for x in &arg.expression_identifier_list {
-            self.select(&x.select);
+        let mut list0 = arg.expression_identifier_list0.iter();
+        let flattened = match (
+            arg.scoped_identifier.scoped_identifier_group.as_ref(),
+            arg.expression_identifier_list0.first(),
+        ) {
+            (ScopedIdentifierGroup::IdentifierScopedIdentifierOpt(x), Some(y))
+                if arg.scoped_identifier.scoped_identifier_list.is_empty()
+                    && arg.expression_identifier_list.is_empty() =>
+            {
+                self.flattened_reference(&x.identifier, &y.dot, &y.identifier)
+            }
+            _ => false,
+        };
+        if flattened {
+            for x in &list0.next().unwrap().expression_identifier_list0_list {
+                self.select(&x.select);
+            }
+        } else {
+            self.scoped_identifier(&arg.scoped_identifier);
+            for x in &arg.expression_identifier_list {
+                self.select(&x.select);
+            }
         }
-        for x in &arg.expression_identifier_list0 {
+        for x in list0 {
             self.dot(&x.dot);
             self.identifier(&x.identifier);
             for x in &x.expression_identifier_list0_list {
@@ -1612,6 +2663,13 @@ impl VerylWalker for Emitter {
 
     /// Semantic action for non-terminal 'IdentifierStatement'
     fn identifier_statement(&mut self, arg: &IdentifierStatement) {
+        if let IdentifierStatementGroup::FunctionCall(x) = &*arg.identifier_statement_group {
+            if let Some(level) = log_level(&arg.expression_identifier) {
+                self.log_function_call(level, &arg.expression_identifier, &x.function_call);
+                self.semicolon(&arg.semicolon);
+                return;
+            }
+        }
         self.expression_identifier(&arg.expression_identifier);
         self.assignment_lefthand_side = Some(*arg.expression_identifier.clone());
         match &*arg.identifier_statement_group {
@@ -1623,6 +2681,12 @@ impl VerylWalker for Emitter {
             }
         }
         self.semicolon(&arg.semicolon);
+
+        if let IdentifierStatementGroup::Assignment(x) = &*arg.identifier_statement_group {
+            if self.in_always_ff {
+                self.emit_parity_update(arg, &x.assignment);
+            }
+        }
     }
 
     /// Semantic action for non-terminal 'Assignment'
@@ -1724,6 +2788,10 @@ impl VerylWalker for Emitter {
         self.str(")");
         self.space(1);
         self.statement_block(&arg.statement_block);
+        if let Some(enable) = self.clock_enable.take() {
+            self.if_reset_statement_with_enable(arg, &enable);
+            return;
+        }
         for x in &arg.if_reset_statement_list {
             self.space(1);
             self.r#else(&x.r#else);
@@ -1857,33 +2925,22 @@ impl VerylWalker for Emitter {
         match identifier.as_str() {
             "ifdef" | "ifndef" => {
                 if let Some(ref x) = arg.attribute_opt {
-                    let comma = if self.string.trim_end().ends_with(',') {
-                        self.unindent();
-                        self.truncate(self.string.len() - format!(",{}", NEWLINE).len());
-                        self.newline();
-                        true
-                    } else {
-                        false
-                    };
-
-                    self.consume_adjust_line(&arg.identifier.identifier_token.token);
-                    self.str("`");
-                    self.identifier(&arg.identifier);
-                    self.space(1);
-                    if let AttributeItem::Identifier(x) = &*x.attribute_list.attribute_item {
-                        self.identifier(&x.identifier);
-                    }
-                    self.newline();
-                    self.attribute.push(AttributeType::Ifdef);
-
-                    if comma {
-                        self.str(",");
-                        self.newline();
-                    }
-
-                    self.clear_adjust_line();
+                    self.directive_attribute(arg, |this| {
+                        this.str("`");
+                        this.identifier(&arg.identifier);
+                        this.space(1);
+                        if let AttributeItem::Identifier(x) = &*x.attribute_list.attribute_item {
+                            this.identifier(&x.identifier);
+                        }
+                    });
                 }
             }
+            "sim_only" => {
+                // Simulation only code is excluded from synthesis
+                self.directive_attribute(arg, |this| {
+                    this.token(&arg.identifier.identifier_token.replace("`ifndef SYNTHESIS"));
+                });
+            }
             "sv" => {
                 if let Some(ref x) = arg.attribute_opt {
                     self.str("(*");
@@ -1899,6 +2956,20 @@ impl VerylWalker for Emitter {
                     self.newline();
                 }
             }
+            "memory" => {
+                if let Ok(Attr::Memory(_, latency)) = Attr::try_from(arg) {
+                    // Hint to infer block RAM, or LUT RAM for asynchronous read
+                    let style = match (self.build_opt.memory_style, latency) {
+                        (MemoryStyle::Generic, _) => return,
+                        (MemoryStyle::Xilinx, 0) => "ram_style = \"distributed\"",
+                        (MemoryStyle::Xilinx, _) => "ram_style = \"block\"",
+                        (MemoryStyle::Intel, 0) => "ramstyle = \"MLAB\"",
+                        (MemoryStyle::Intel, _) => "ramstyle = \"M20K\"",
+                    };
+                    self.token(&arg.hash.hash_token.replace(&format!("(* {style} *)")));
+                    self.newline();
+                }
+            }
             "test" => {
                 if let Some(ref x) = arg.attribute_opt {
                     if let AttributeItem::Identifier(x) = &*x.attribute_list.attribute_item {
@@ -1934,6 +3005,13 @@ impl VerylWalker for Emitter {
                     }
                 }
             }
+            "flatten" => {
+                // consume to keep the following ports from being separated by a blank line
+                self.token(&arg.hash.hash_token.replace(""));
+                self.token(&arg.l_bracket.l_bracket_token.replace(""));
+                self.token(&arg.identifier.identifier_token.replace(""));
+                self.token(&arg.r_bracket.r_bracket_token.replace(""));
+            }
             _ => (),
         }
     }
@@ -1980,6 +3058,35 @@ impl VerylWalker for Emitter {
             self.array(&x.array);
         }
         self.semicolon(&arg.semicolon);
+
+        let pipeline = attribute_table::get(&arg.identifier.identifier_token.token)
+            .into_iter()
+            .find_map(|x| match x {
+                Attr::Pipeline(x) => Some(x),
+                _ => None,
+            });
+        if let Some(valid) = pipeline {
+            if self.default_clock.is_some() {
+                self.emit_pipeline(arg, valid);
+            }
+        }
+
+        let sync = attribute_table::get(&arg.identifier.identifier_token.token)
+            .into_iter()
+            .find_map(|x| match x {
+                Attr::Sync(x, y, z) => Some((x, y, z)),
+                _ => None,
+            });
+        if let Some((source, clock, stages)) = sync {
+            self.emit_synchronizer(arg, &source, &clock, stages);
+        }
+
+        let parity = attribute_table::get(&arg.identifier.identifier_token.token)
+            .iter()
+            .any(|x| matches!(x, Attr::Parity));
+        if parity {
+            self.emit_parity(arg);
+        }
     }
 
     /// Semantic action for non-terminal 'ConstDeclaration'
@@ -2031,6 +3138,21 @@ impl VerylWalker for Emitter {
     /// Semantic action for non-terminal 'AlwaysFfDeclaration'
     fn always_ff_declaration(&mut self, arg: &AlwaysFfDeclaration) {
         self.in_always_ff = true;
+        if let Some(x) = self.build_opt.reset_synchronizer.clone() {
+            self.emit_reset_synchronizer(arg, x.stages);
+        }
+        let enable = self.always_ff_clock_enable(arg);
+        let mut wrap_enable = None;
+        if let Some(enable) = enable {
+            if let Some(cell) = self.build_opt.clock_gating_cell.clone() {
+                self.emit_clock_gating_cell(arg, &cell, &enable);
+            } else if self.always_ff_if_reset_exists(arg) {
+                // enable is inserted after reset branch to keep asynchronous reset
+                self.clock_enable = Some(enable);
+            } else {
+                wrap_enable = Some(enable);
+            }
+        }
         self.always_ff(&arg.always_ff);
         self.space(1);
         self.str("@");
@@ -2041,8 +3163,15 @@ impl VerylWalker for Emitter {
             self.always_ff_implicit_event_list(arg);
         }
         self.space(1);
+        if let Some(enable) = wrap_enable {
+            self.str(&format!("if ({enable})"));
+            self.space(1);
+        }
         self.statement_block(&arg.statement_block);
         self.in_always_ff = false;
+        self.clock_enable = None;
+        self.gated_clock = None;
+        self.synced_reset = None;
     }
 
     /// Semantic action for non-terminal 'AlwayfFfEventList'
@@ -2079,7 +3208,12 @@ impl VerylWalker for Emitter {
                 ClockType::NegEdge => self.str("negedge"),
             }
             self.space(1);
-            self.hierarchical_identifier(&arg.hierarchical_identifier);
+            if let Some(gated) = self.gated_clock.clone() {
+                let token = &arg.hierarchical_identifier.identifier.identifier_token;
+                self.token(&token.replace(&gated));
+            } else {
+                self.hierarchical_identifier(&arg.hierarchical_identifier);
+            }
         } else {
             unreachable!()
         }
@@ -2109,26 +3243,31 @@ impl VerylWalker for Emitter {
                 ResetType::AsyncHigh => {
                     self.str("posedge");
                     self.space(1);
-                    self.hierarchical_identifier(&arg.hierarchical_identifier);
+                    self.always_ff_reset_identifier(arg);
                     ""
                 }
                 ResetType::AsyncLow => {
                     self.str("negedge");
                     self.space(1);
-                    self.hierarchical_identifier(&arg.hierarchical_identifier);
+                    self.always_ff_reset_identifier(arg);
                     "!"
                 }
                 ResetType::SyncHigh => "",
                 ResetType::SyncLow => "!",
             };
 
-            let mut stringifier = Stringifier::new();
-            stringifier.hierarchical_identifier_with_prefix_suffix(
-                &arg.hierarchical_identifier,
-                &prefix,
-                &suffix,
-            );
-            self.reset_signal = Some(format!("{}{}", prefix_op, stringifier.as_str()));
+            let reset = if let Some(synced) = self.synced_reset.clone() {
+                synced
+            } else {
+                let mut stringifier = Stringifier::new();
+                stringifier.hierarchical_identifier_with_prefix_suffix(
+                    &arg.hierarchical_identifier,
+                    &prefix,
+                    &suffix,
+                );
+                stringifier.as_str().to_string()
+            };
+            self.reset_signal = Some(format!("{}{}", prefix_op, reset));
         } else {
             unreachable!()
         }
@@ -2419,9 +3558,46 @@ impl VerylWalker for Emitter {
         self.token(&arg.inst.inst_token.replace(""));
         self.scoped_identifier(&arg.scoped_identifier);
         self.space(1);
-        if let Some(ref x) = arg.inst_declaration_opt0 {
-            self.inst_parameter(&x.inst_parameter);
-            self.space(1);
+        let namespace = namespace_table::get(arg.identifier.identifier_token.token.id).unwrap();
+        let generic_args: Vec<Option<WithGenericArgument>> = arg.scoped_identifier.as_ref().into();
+        let generic_args = generic_args.last().cloned().flatten();
+        let path: GenericSymbolPath = arg.scoped_identifier.as_ref().into();
+        self.flattened_inst_ports = flattened_inst_ports(arg);
+        self.renamed_inst_ports = renamed_inst_ports(arg, &self.build_opt);
+        let parameterized = symbol_table::resolve((&path.generic_path(), &namespace))
+            .ok()
+            .map(|x| x.found)
+            .filter(|x| self.is_parameterized_generic(x));
+        match (parameterized, generic_args) {
+            (Some(base), Some(generic_args)) => {
+                self.str("#(");
+                if !self.single_line {
+                    self.newline_push();
+                }
+                self.generic_type_arguments(&base, &generic_args);
+                if let Some(ref x) = arg.inst_declaration_opt0 {
+                    if let Some(ref x) = x.inst_parameter.inst_parameter_opt {
+                        self.str(",");
+                        if self.single_line {
+                            self.space(1);
+                        } else {
+                            self.newline();
+                        }
+                        self.inst_parameter_list(&x.inst_parameter_list);
+                    }
+                }
+                if !self.single_line {
+                    self.newline_pop();
+                }
+                self.str(")");
+                self.space(1);
+            }
+            _ => {
+                if let Some(ref x) = arg.inst_declaration_opt0 {
+                    self.inst_parameter(&x.inst_parameter);
+                    self.space(1);
+                }
+            }
         }
         self.identifier(&arg.identifier);
         if let Some(ref x) = arg.inst_declaration_opt {
@@ -2429,19 +3605,35 @@ impl VerylWalker for Emitter {
             self.array(&x.array);
         }
         self.space(1);
+        let auto_connections = auto_connections(arg);
+        let unconnected_ports = unconnected_ports(arg);
+        let implicit = !auto_connections.is_empty() || !unconnected_ports.is_empty();
         if let Some(ref x) = arg.inst_declaration_opt1 {
             self.token_will_push(&x.l_paren.l_paren_token.replace("("));
             self.newline_push();
             if let Some(ref x) = x.inst_declaration_opt2 {
                 self.inst_port_list(&x.inst_port_list);
+                if implicit {
+                    self.str(",");
+                    self.newline();
+                }
             }
+            self.implicit_connection(arg, &auto_connections, &unconnected_ports);
             self.newline_pop();
             self.token(&x.r_paren.r_paren_token.replace(")"));
+        } else if implicit {
+            self.str("(");
+            self.newline_push();
+            self.implicit_connection(arg, &auto_connections, &unconnected_ports);
+            self.newline_pop();
+            self.str(")");
         } else {
             self.str("()");
         }
         self.semicolon(&arg.semicolon);
         self.single_line = false;
+        self.flattened_inst_ports.clear();
+        self.renamed_inst_ports.clear();
     }
 
     /// Semantic action for non-terminal 'InstParameter'
@@ -2543,8 +3735,24 @@ impl VerylWalker for Emitter {
 
     /// Semantic action for non-terminal 'InstPortItem'
     fn inst_port_item(&mut self, arg: &InstPortItem) {
+        if let Some(members) = self
+            .flattened_inst_ports
+            .get(&arg.identifier.identifier_token.token.text)
+            .cloned()
+        {
+            self.flattened_connection(arg, &members);
+            return;
+        }
         self.str(".");
-        self.identifier(&arg.identifier);
+        if let Some(x) = self
+            .renamed_inst_ports
+            .get(&arg.identifier.identifier_token.token.text)
+            .cloned()
+        {
+            self.veryl_token(&arg.identifier.identifier_token.replace(&x));
+        } else {
+            self.identifier(&arg.identifier);
+        }
         self.space(1);
         self.str("(");
         if let Some(ref x) = arg.inst_port_item_opt {
@@ -2555,17 +3763,12 @@ impl VerylWalker for Emitter {
                 self.expression(&x.expression);
             }
         } else {
-            let (prefix, suffix) = if let Ok(found) = symbol_table::resolve(arg.identifier.as_ref())
-            {
-                match &found.found.kind {
-                    SymbolKind::Port(x) => (x.prefix.clone(), x.suffix.clone()),
-                    SymbolKind::Variable(x) => (x.prefix.clone(), x.suffix.clone()),
-                    _ => (None, None),
-                }
+            let symbol = if let Ok(found) = symbol_table::resolve(arg.identifier.as_ref()) {
+                found.found
             } else {
                 unreachable!()
             };
-            let token = identifier_with_prefix_suffix(&arg.identifier, &prefix, &suffix);
+            let token = signal_token(&arg.identifier, Some(&symbol), &self.build_opt);
             self.duplicated_token(&token, 0);
         }
         self.str(")");
@@ -2648,7 +3851,13 @@ impl VerylWalker for Emitter {
         self.space(1);
         self.equ(&arg.equ);
         self.space(1);
-        self.expression(&arg.expression);
+        let token = arg.identifier.identifier_token.token.id;
+        if let Some(value) = self.param_overrides.get(&token) {
+            let token = arg.equ.equ_token.replace(value);
+            self.duplicated_token(&token, 0);
+        } else {
+            self.expression(&arg.expression);
+        }
     }
 
     /// Semantic action for non-terminal 'PortDeclaration'
@@ -2698,6 +3907,53 @@ impl VerylWalker for Emitter {
 
     /// Semantic action for non-terminal 'PortDeclarationItem'
     fn port_declaration_item(&mut self, arg: &PortDeclarationItem) {
+        let members = symbol_table::resolve(arg.identifier.as_ref())
+            .ok()
+            .and_then(|x| flattened_members(&x.found));
+        if let Some(members) = members {
+            let port = arg.identifier.identifier_token.to_string();
+            self.token(&arg.identifier.identifier_token.replace(""));
+            for (i, member) in members.iter().enumerate() {
+                if i != 0 {
+                    self.str(",");
+                    self.newline();
+                }
+                let direction = match member.direction {
+                    SymDirection::Output => "output",
+                    SymDirection::Inout => "inout",
+                    _ => "input",
+                };
+                let mut r#type = match member.kind {
+                    TypeKind::Bit => "bit".to_string(),
+                    _ => "logic".to_string(),
+                };
+                if member.signed {
+                    r#type.push_str(" signed");
+                }
+                if !member.width.is_empty() {
+                    r#type.push(' ');
+                    for x in &member.width {
+                        r#type.push_str(&format!("[{}-1:0]", x));
+                    }
+                }
+                self.str(&format!(
+                    "{} {} {}_{}",
+                    direction, r#type, port, member.name
+                ));
+            }
+            return;
+        }
+
+        if let Ok(symbol) = symbol_table::resolve(arg.identifier.as_ref()) {
+            if let Some(renamed) = renamed_port(&symbol.found, &self.build_opt) {
+                self.renames.push(RenameReport {
+                    module: Some(self.module_name.clone()),
+                    original: module_port_name(&symbol.found).unwrap(),
+                    renamed,
+                });
+            }
+        }
+
         match &*arg.port_declaration_item_group {
             PortDeclarationItemGroup::PortTypeConcrete(x) => {
                 let x = x.port_type_concrete.as_ref();
@@ -2829,24 +4085,54 @@ impl VerylWalker for Emitter {
             self.default_clock = x.default_clock;
             self.default_reset = x.default_reset;
         }
+        self.gated_clocks.clear();
+        self.module_name = arg.identifier.identifier_token.to_string();
 
-        let maps = symbol.found.generic_maps();
+        let protect = self
+            .build_opt
+            .protect
+            .is_target(&arg.identifier.identifier_token.to_string());
+
+        let parameterized = self.is_parameterized_generic(&symbol.found);
+        let maps = if parameterized {
+            vec![GenericMap::default()]
+        } else {
+            symbol.found.generic_maps()
+        };
+        self.in_parameterized_generic = parameterized;
         for (i, map) in maps.iter().enumerate() {
             if i != 0 {
                 self.newline();
             }
             self.generic_map.push(map.clone());
+            self.synced_resets.clear();
 
+            if self.build_opt.annotate {
+                self.annotation(&symbol.found);
+            }
+            if protect {
+                self.protect_begin();
+            }
             self.module(&arg.module);
             self.space(1);
             if map.generic() {
-                self.str(&map.name.clone());
+                let name = self.renamed_module(&map.name);
+                self.str(&name);
             } else {
-                if let Ok(symbol) = symbol_table::resolve(arg.identifier.as_ref()) {
-                    let context: SymbolContext = self.into();
-                    self.str(&namespace_string(&symbol.found.namespace, &context));
+                let context: SymbolContext = self.into();
+                let namespace = namespace_string(&symbol.found.namespace, &context);
+                let name = format!(
+                    "{}{}",
+                    namespace,
+                    arg.identifier.identifier_token.strip_prefix("r#")
+                );
+                let renamed = self.renamed_module(&name);
+                if renamed != name {
+                    self.veryl_token(&arg.identifier.identifier_token.replace(&renamed));
+                } else {
+                    self.str(&namespace);
+                    self.identifier(&arg.identifier);
                 }
-                self.identifier(&arg.identifier);
             }
             let file_scope_import = self.file_scope_import.clone();
             if !file_scope_import.is_empty() {
@@ -2861,9 +4147,34 @@ impl VerylWalker for Emitter {
             if !file_scope_import.is_empty() {
                 self.newline_pop();
             }
-            if let Some(ref x) = arg.module_declaration_opt2 {
-                self.space(1);
-                self.with_parameter(&x.with_parameter);
+            match (&arg.module_declaration_opt0, &arg.module_declaration_opt2) {
+                (Some(x), Some(y)) if parameterized => {
+                    self.space(1);
+                    self.hash(&y.with_parameter.hash);
+                    self.token_will_push(&y.with_parameter.l_paren.l_paren_token);
+                    self.newline_push();
+                    self.generic_type_parameters(&x.with_generic_parameter);
+                    if let Some(ref y) = y.with_parameter.with_parameter_opt {
+                        self.str(",");
+                        self.newline();
+                        self.with_parameter_list(&y.with_parameter_list);
+                    }
+                    self.newline_pop();
+                    self.r_paren(&y.with_parameter.r_paren);
+                }
+                (Some(x), None) if parameterized => {
+                    self.space(1);
+                    self.str("#(");
+                    self.newline_push();
+                    self.generic_type_parameters(&x.with_generic_parameter);
+                    self.newline_pop();
+                    self.str(")");
+                }
+                (_, Some(y)) => {
+                    self.space(1);
+                    self.with_parameter(&y.with_parameter);
+                }
+                _ => (),
             }
             if let Some(ref x) = arg.module_declaration_opt3 {
                 self.space(1);
@@ -2876,10 +4187,14 @@ impl VerylWalker for Emitter {
             }
             self.newline_list_post(arg.module_declaration_list.is_empty());
             self.token(&arg.r_brace.r_brace_token.replace("endmodule"));
+            if protect {
+                self.protect_end();
+            }
 
             self.generic_map.pop();
         }
 
+        self.in_parameterized_generic = false;
         self.default_clock = None;
         self.default_reset = None;
     }
@@ -2909,12 +4224,14 @@ impl VerylWalker for Emitter {
     fn interface_declaration(&mut self, arg: &InterfaceDeclaration) {
         let symbol = symbol_table::resolve(arg.identifier.as_ref()).unwrap();
         let maps = symbol.found.generic_maps();
+        self.module_name = arg.identifier.identifier_token.to_string();
 
         for (i, map) in maps.iter().enumerate() {
             if i != 0 {
                 self.newline();
             }
             self.generic_map.push(map.clone());
+            self.synced_resets.clear();
 
             self.interface(&arg.interface);
             self.space(1);
@@ -2952,6 +4269,18 @@ impl VerylWalker for Emitter {
             self.newline_list_post(arg.interface_declaration_list.is_empty());
             self.token(&arg.r_brace.r_brace_token.replace("endinterface"));
 
+            let interface = if map.generic() {
+                map.name.clone()
+            } else {
+                let context: SymbolContext = self.into();
+                format!(
+                    "{}{}",
+                    namespace_string(&symbol.found.namespace, &context),
+                    arg.identifier.identifier_token
+                )
+            };
+            self.emit_protocol_bind(arg, &interface);
+
             self.generic_map.pop();
         }
     }
@@ -3239,6 +4568,9 @@ impl VerylWalker for Emitter {
 
     /// Semantic action for non-terminal 'Veryl'
     fn veryl(&mut self, arg: &Veryl) {
+        if self.build_opt.header.enable {
+            self.header();
+        }
         self.in_start_token = true;
         self.start(&arg.start);
         self.in_start_token = false;
@@ -3269,7 +4601,8 @@ impl VerylWalker for Emitter {
         self.newline();
 
         // build map and insert link to map
-        if self.build_opt.sourcemap_target != SourceMapTarget::None {
+        // protected modules have no map because it contains the original source
+        if self.build_opt.sourcemap_target != SourceMapTarget::None && !self.protected {
             self.source_map.as_mut().unwrap().build();
             self.str(&self.source_map.as_ref().unwrap().get_link());
             self.newline();
@@ -3277,6 +4610,75 @@ impl VerylWalker for Emitter {
     }
 }
 
+#[derive(Default)]
+struct ParamOverrideCollector<'a> {
+    values: Option<&'a BTreeMap<String, String>>,
+    ret: HashMap<TokenId, String>,
+}
+
+impl VerylWalker for ParamOverrideCollector<'_> {
+    fn with_parameter_item(&mut self, arg: &WithParameterItem) {
+        let token = &arg.identifier.identifier_token;
+        if let Some(x) = self.values.and_then(|x| x.get(&token.to_string())) {
+            self.ret.insert(token.token.id, x.clone());
+        }
+    }
+}
+
+/// Overridden default values of parameters keyed by the identifier of each parameter
+pub(crate) fn param_overrides(build_opt: &Build, input: &Veryl) -> HashMap<TokenId, String> {
+    let mut collector = ParamOverrideCollector::default();
+    if build_opt.param_overrides.is_empty() {
+        return collector.ret;
+    }
+
+    for x in &input.veryl_list {
+        let items: Vec<DescriptionItem> = x.description_group.as_ref().into();
+        for item in items {
+            if let DescriptionItem::ModuleDeclaration(x) = item {
+                let x = &x.module_declaration;
+                let name = x.identifier.identifier_token.to_string();
+                if let (Some(values), Some(params)) = (
+                    build_opt.param_overrides.get(&name),
+                    &x.module_declaration_opt2,
+                ) {
+                    collector.values = Some(values);
+                    collector.with_parameter(&params.with_parameter);
+                }
+            }
+        }
+    }
+    collector.ret
+}
+
+/// Members of modport port with `#[flatten]`
+fn flattened_members(symbol: &Symbol) -> Option<Vec<FlattenedMember>> {
+    if attribute_table::contains(&symbol.token, Attr::Flatten) {
+        symbol.flattened_members().ok()
+    } else {
+        None
+    }
+}
+
+/// Ports with `#[flatten]` of the module instantiated by `arg`
+pub(crate) fn flattened_inst_ports(arg: &InstDeclaration) -> HashMap<StrId, Vec<FlattenedMember>> {
+    let mut ret = HashMap::new();
+    let namespace = namespace_table::get(arg.identifier.identifier_token.token.id).unwrap();
+    let path: GenericSymbolPath = arg.scoped_identifier.as_ref().into();
+    if let Ok(symbol) = symbol_table::resolve((&path.generic_path(), &namespace)) {
+        if let SymbolKind::Module(ref x) = symbol.found.kind {
+            for port in &x.ports {
+                if let Some(members) =
+                    symbol_table::get(port.symbol).and_then(|x| flattened_members(&x))
+                {
+                    ret.insert(port.name, members);
+                }
+            }
+        }
+    }
+    ret
+}
+
 pub struct SymbolContext {
     pub project_name: Option<StrId>,
     pub build_opt: Build,
@@ -3314,14 +4716,14 @@ fn namespace_string(namespace: &Namespace, context: &SymbolContext) -> String {
             if text == "$sv_" {
                 in_sv_namespace = true;
             } else {
-                let emit_prj_prefix = if context.build_opt.omit_project_prefix {
-                    context.project_name != Some(*path)
-                } else {
-                    true
-                };
+                let own_project = context.project_name == Some(*path);
+                let emit_prj_prefix = !(context.build_opt.omit_project_prefix && own_project);
 
                 if emit_prj_prefix {
-                    ret.push_str(&text);
+                    match context.build_opt.project_prefix {
+                        Some(ref x) if own_project => ret.push_str(x),
+                        _ => ret.push_str(&text),
+                    }
                 }
             }
         } else {
@@ -3351,11 +4753,55 @@ fn namespace_string(namespace: &Namespace, context: &SymbolContext) -> String {
     ret
 }
 
+/// Returns the level of logging function like `$log_info`
+pub fn log_level(arg: &ExpressionIdentifier) -> Option<&'static str> {
+    if !matches!(
+        *arg.scoped_identifier.scoped_identifier_group,
+        ScopedIdentifierGroup::DollarIdentifier(_)
+    ) {
+        return None;
+    }
+    match arg.identifier().to_string().as_str() {
+        "$log_info" => Some("info"),
+        "$log_warning" => Some("warning"),
+        "$log_error" => Some("error"),
+        "$log_fatal" => Some("fatal"),
+        _ => None,
+    }
+}
+
+/// Returns the name of signal referred by attribute argument
+fn signal_name(token: &Token, build_opt: &Build) -> Option<String> {
+    let namespace = namespace_table::get(token.id)?;
+    let symbol = symbol_table::resolve((&SymbolPath::new(&[token.text]), &namespace)).ok()?;
+    if let Some(x) = renamed_port(&symbol.found, build_opt) {
+        return Some(x);
+    }
+    let (prefix, suffix) = match symbol.found.kind {
+        SymbolKind::Port(x) => (x.prefix, x.suffix),
+        SymbolKind::Variable(x) => (x.prefix, x.suffix),
+        _ => (None, None),
+    };
+    Some(
+        VerylToken::new(*token)
+            .append(&prefix, &suffix)
+            .token
+            .to_string(),
+    )
+}
+
 pub fn symbol_string(token: &VerylToken, symbol: &Symbol, context: &SymbolContext) -> String {
     let mut ret = String::new();
     let namespace = namespace_table::get(token.token.id).unwrap();
     match &symbol.kind {
-        SymbolKind::Module(_) | SymbolKind::Interface(_) | SymbolKind::Package(_) => {
+        SymbolKind::Module(_) => {
+            ret.push_str(&namespace_string(&symbol.namespace, context));
+            ret.push_str(&symbol.token.to_string());
+            if let Some(x) = context.build_opt.rename.module(&ret) {
+                ret = x;
+            }
+        }
+        SymbolKind::Interface(_) | SymbolKind::Package(_) => {
             ret.push_str(&namespace_string(&symbol.namespace, context));
             ret.push_str(&symbol.token.to_string());
         }
@@ -3365,8 +4811,8 @@ pub fn symbol_string(token: &VerylToken, symbol: &Symbol, context: &SymbolContex
         | SymbolKind::Union(_)
         | SymbolKind::TypeDef(_)
         | SymbolKind::Enum(_) => {
-            let visible = namespace.included(&symbol.namespace)
-                || symbol.imported.iter().any(|x| *x == namespace);
+            let visible =
+                namespace.included(&symbol.namespace) || symbol.imported.contains(&namespace);
             if visible & !context.in_import {
                 ret.push_str(&symbol.token.to_string());
             } else {
@@ -3396,8 +4842,7 @@ pub fn symbol_string(token: &VerylToken, symbol: &Symbol, context: &SymbolContex
         }
         SymbolKind::GenericInstance(x) => {
             let base = symbol_table::get(x.base).unwrap();
-            let visible = namespace.included(&base.namespace)
-                || base.imported.iter().any(|x| *x == namespace);
+            let visible = namespace.included(&base.namespace) || base.imported.contains(&namespace);
             let top_level = matches!(
                 base.kind,
                 SymbolKind::Module(_) | SymbolKind::Interface(_) | SymbolKind::Package(_)
@@ -3406,15 +4851,24 @@ pub fn symbol_string(token: &VerylToken, symbol: &Symbol, context: &SymbolContex
                 ret.push_str(&namespace_string(&base.namespace, context));
             }
             ret.push_str(&symbol.token.to_string());
+            if matches!(base.kind, SymbolKind::Module(_)) {
+                if let Some(x) = context.build_opt.rename.module(&ret) {
+                    ret = x;
+                }
+            }
         }
         SymbolKind::GenericParameter(_) | SymbolKind::ProtoModule(_) => (),
         SymbolKind::Port(x) => {
-            if let Some(ref x) = x.prefix {
-                ret.push_str(x);
-            }
-            ret.push_str(&symbol.token.to_string());
-            if let Some(ref x) = x.suffix {
-                ret.push_str(x);
+            if let Some(x) = renamed_port(symbol, &context.build_opt) {
+                ret.push_str(&x);
+            } else {
+                if let Some(ref x) = x.prefix {
+                    ret.push_str(x);
+                }
+                ret.push_str(&symbol.token.to_string());
+                if let Some(ref x) = x.suffix {
+                    ret.push_str(x);
+                }
             }
         }
         SymbolKind::Variable(x) => {
@@ -3454,3 +4908,272 @@ pub fn identifier_with_prefix_suffix(
         identifier.identifier_token.strip_prefix("r#")
     }
 }
+
+/// Returns the name of module port including prefix and suffix
+fn module_port_name(symbol: &Symbol) -> Option<String> {
+    let SymbolKind::Port(ref x) = symbol.kind else {
+        return None;
+    };
+    let mut namespace = symbol.namespace.clone();
+    let parent = namespace.pop()?;
+    let parent = symbol_table::resolve((&SymbolPath::new(&[parent]), &namespace)).ok()?;
+    if !matches!(parent.found.kind, SymbolKind::Module(_)) {
+        return None;
+    }
+    let name = VerylToken::new(symbol.token)
+        .strip_prefix("r#")
+        .append(&x.prefix, &x.suffix);
+    Some(name.token.to_string())
+}
+
+/// Returns the module port name renamed by `[build.rename]`
+pub fn renamed_port(symbol: &Symbol, build_opt: &Build) -> Option<String> {
+    if build_opt.rename.port.is_empty() {
+        return None;
+    }
+    build_opt.rename.port(&module_port_name(symbol)?)
+}
+
+/// Returns the renamed ports of the module instantiated by `arg`
+pub(crate) fn renamed_inst_ports(
+    arg: &InstDeclaration,
+    build_opt: &Build,
+) -> HashMap<StrId, String> {
+    let mut ret = HashMap::new();
+    if build_opt.rename.port.is_empty() {
+        return ret;
+    }
+    let namespace = namespace_table::get(arg.identifier.identifier_token.token.id).unwrap();
+    let path: GenericSymbolPath = arg.scoped_identifier.as_ref().into();
+    if let Ok(symbol) = symbol_table::resolve((&path.generic_path(), &namespace)) {
+        if let SymbolKind::Module(ref x) = symbol.found.kind {
+            for port in &x.ports {
+                if let Some(renamed) =
+                    symbol_table::get(port.symbol).and_then(|x| renamed_port(&x, build_opt))
+                {
+                    ret.insert(port.name, renamed);
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// Returns the token of port or variable `symbol` referred by `identifier`
+pub fn signal_token(
+    identifier: &Identifier,
+    symbol: Option<&Symbol>,
+    build_opt: &Build,
+) -> VerylToken {
+    if let Some(x) = symbol.and_then(|x| renamed_port(x, build_opt)) {
+        return identifier.identifier_token.replace(&x);
+    }
+    let (prefix, suffix) = match symbol.map(|x| &x.kind) {
+        Some(SymbolKind::Port(x)) => (x.prefix.clone(), x.suffix.clone()),
+        Some(SymbolKind::Variable(x)) => (x.prefix.clone(), x.suffix.clone()),
+        _ => (None, None),
+    };
+    identifier_with_prefix_suffix(identifier, &prefix, &suffix)
+}
+
+fn renamed_hierarchical_identifier(
+    arg: &HierarchicalIdentifier,
+    symbol: &Symbol,
+    build_opt: &Build,
+) -> Option<HierarchicalIdentifier> {
+    if !arg.hierarchical_identifier_list0.is_empty() {
+        return None;
+    }
+    let renamed = renamed_port(symbol, build_opt)?;
+    let mut ret = arg.clone();
+    ret.identifier.identifier_token = ret.identifier.identifier_token.replace(&renamed);
+    Some(ret)
+}
+
+/// Emits `bind` file attaching the checker modules of `[verification.bind]`.
+/// The name of the module which can't be found is returned as error.
+pub fn emit_bind(metadata: &Metadata) -> Result<String, String> {
+    let project_name = resource_table::insert_str(&metadata.project.name);
+    let context = SymbolContext {
+        project_name: Some(project_name),
+        build_opt: metadata.build.clone(),
+        in_import: false,
+        generic_map: Vec::new(),
+    };
+    let mut namespace = Namespace::new();
+    namespace.push(project_name);
+    let indent = " ".repeat(metadata.format.indent_width);
+
+    let mut ret = format!("`ifndef SYNTHESIS{NEWLINE}");
+    for (module, checkers) in &metadata.verification.bind {
+        let path = SymbolPath::new(&[resource_table::insert_str(module)]);
+        let symbol = match symbol_table::resolve((&path, &namespace)) {
+            Ok(x) if matches!(x.found.kind, SymbolKind::Module(_)) => x.found,
+            _ => return Err(module.clone()),
+        };
+        let module = format!(
+            "{}{}",
+            namespace_string(&symbol.namespace, &context),
+            symbol.token
+        );
+
+        // Checker ports are connected to the signals which have the same name
+        for checker in checkers {
+            ret.push_str(&format!(
+                "{indent}bind {module} {checker} u__{checker} (.*);{NEWLINE}"
+            ));
+        }
+    }
+    ret.push_str(&format!("`endif{NEWLINE}"));
+    Ok(ret)
+}
+
+/// Emits UVM register model package of `[verification.ral]`.
+/// The register blocks, registers and an adapter skeleton are generated.
+pub fn emit_ral(metadata: &Metadata) -> Result<String, String> {
+    let project = &metadata.project.name;
+    let i1 = " ".repeat(metadata.format.indent_width);
+    let i2 = i1.repeat(2);
+    let i3 = i1.repeat(3);
+
+    let mut ret = format!("package {project}_ral_pkg;{NEWLINE}");
+    ret.push_str(&format!("{i1}import uvm_pkg::*;{NEWLINE}"));
+    ret.push_str(&format!("{i1}`include \"uvm_macros.svh\"{NEWLINE}"));
+
+    for block in &metadata.verification.ral.blocks {
+        let width = block.width;
+        if width == 0 || width % 8 != 0 {
+            return Err(format!(
+                "width of register block \"{}\" should be a multiple of 8",
+                block.name
+            ));
+        }
+
+        for register in &block.registers {
+            let class = format!("{}_{}", block.name, register.name);
+
+            let mut used = vec![false; width];
+            for field in &register.fields {
+                let msb = field.lsb + field.width;
+                if field.width == 0 || msb > width {
+                    return Err(format!(
+                        "field \"{}.{}\" is out of the register width",
+                        class, field.name
+                    ));
+                }
+                if used[field.lsb..msb].iter().any(|x| *x) {
+                    return Err(format!(
+                        "field \"{}.{}\" overlaps with other field",
+                        class, field.name
+                    ));
+                }
+                used[field.lsb..msb].fill(true);
+            }
+
+            ret.push_str(NEWLINE);
+            ret.push_str(&format!("{i1}class {class} extends uvm_reg;{NEWLINE}"));
+            ret.push_str(&format!("{i2}`uvm_object_utils({class}){NEWLINE}"));
+            ret.push_str(NEWLINE);
+            for field in &register.fields {
+                ret.push_str(&format!("{i2}rand uvm_reg_field {};{NEWLINE}", field.name));
+            }
+            ret.push_str(NEWLINE);
+            ret.push_str(&format!(
+                "{i2}function new(string name = \"{class}\");{NEWLINE}"
+            ));
+            ret.push_str(&format!(
+                "{i3}super.new(name, {width}, UVM_NO_COVERAGE);{NEWLINE}"
+            ));
+            ret.push_str(&format!("{i2}endfunction{NEWLINE}"));
+            ret.push_str(NEWLINE);
+            ret.push_str(&format!("{i2}virtual function void build();{NEWLINE}"));
+            for field in &register.fields {
+                let is_rand = if field.access == "RO" { 0 } else { 1 };
+                ret.push_str(&format!(
+                    "{i3}{} = uvm_reg_field::type_id::create(\"{}\");{NEWLINE}",
+                    field.name, field.name
+                ));
+                ret.push_str(&format!(
+                    "{i3}{}.configure(this, {}, {}, \"{}\", 0, {}'h{:x}, 1, {is_rand}, 0);{NEWLINE}",
+                    field.name, field.width, field.lsb, field.access, field.width, field.reset
+                ));
+            }
+            ret.push_str(&format!("{i2}endfunction{NEWLINE}"));
+            ret.push_str(&format!("{i1}endclass{NEWLINE}"));
+        }
+
+        let class = format!("{}_block", block.name);
+        ret.push_str(NEWLINE);
+        ret.push_str(&format!(
+            "{i1}class {class} extends uvm_reg_block;{NEWLINE}"
+        ));
+        ret.push_str(&format!("{i2}`uvm_object_utils({class}){NEWLINE}"));
+        ret.push_str(NEWLINE);
+        for register in &block.registers {
+            ret.push_str(&format!(
+                "{i2}rand {}_{} {};{NEWLINE}",
+                block.name, register.name, register.name
+            ));
+        }
+        ret.push_str(NEWLINE);
+        ret.push_str(&format!(
+            "{i2}function new(string name = \"{class}\");{NEWLINE}"
+        ));
+        ret.push_str(&format!("{i3}super.new(name, UVM_NO_COVERAGE);{NEWLINE}"));
+        ret.push_str(&format!("{i2}endfunction{NEWLINE}"));
+        ret.push_str(NEWLINE);
+        ret.push_str(&format!("{i2}virtual function void build();{NEWLINE}"));
+        ret.push_str(&format!(
+            "{i3}default_map = create_map(\"default_map\", 0, {}, UVM_LITTLE_ENDIAN);{NEWLINE}",
+            width / 8
+        ));
+        for register in &block.registers {
+            let name = &register.name;
+            ret.push_str(&format!(
+                "{i3}{name} = {}_{name}::type_id::create(\"{name}\");{NEWLINE}",
+                block.name
+            ));
+            ret.push_str(&format!("{i3}{name}.configure(this);{NEWLINE}"));
+            ret.push_str(&format!("{i3}{name}.build();{NEWLINE}"));
+            ret.push_str(&format!(
+                "{i3}default_map.add_reg({name}, 'h{:x}, \"RW\");{NEWLINE}",
+                register.offset
+            ));
+        }
+        ret.push_str(&format!("{i3}lock_model();{NEWLINE}"));
+        ret.push_str(&format!("{i2}endfunction{NEWLINE}"));
+        ret.push_str(&format!("{i1}endclass{NEWLINE}"));
+    }
+
+    // Conversion between register operations and bus transactions depends on the bus protocol
+    let class = format!("{project}_reg_adapter");
+    ret.push_str(NEWLINE);
+    ret.push_str(&format!(
+        "{i1}class {class} extends uvm_reg_adapter;{NEWLINE}"
+    ));
+    ret.push_str(&format!("{i2}`uvm_object_utils({class}){NEWLINE}"));
+    ret.push_str(NEWLINE);
+    ret.push_str(&format!(
+        "{i2}function new(string name = \"{class}\");{NEWLINE}"
+    ));
+    ret.push_str(&format!("{i3}super.new(name);{NEWLINE}"));
+    ret.push_str(&format!("{i2}endfunction{NEWLINE}"));
+    ret.push_str(NEWLINE);
+    ret.push_str(&format!(
+        "{i2}virtual function uvm_sequence_item reg2bus(const ref uvm_reg_bus_op rw);{NEWLINE}"
+    ));
+    ret.push_str(&format!(
+        "{i3}// TODO: convert `rw` to a bus transaction{NEWLINE}"
+    ));
+    ret.push_str(&format!("{i3}return null;{NEWLINE}"));
+    ret.push_str(&format!("{i2}endfunction{NEWLINE}"));
+    ret.push_str(NEWLINE);
+    ret.push_str(&format!(
+        "{i2}virtual function void bus2reg(uvm_sequence_item bus_item, ref uvm_reg_bus_op rw);{NEWLINE}"
+    ));
+    ret.push_str(&format!("{i3}// TODO: convert `bus_item` to `rw`{NEWLINE}"));
+    ret.push_str(&format!("{i2}endfunction{NEWLINE}"));
+    ret.push_str(&format!("{i1}endclass{NEWLINE}"));
+    ret.push_str(&format!("endpackage{NEWLINE}"));
+    Ok(ret)
+}
@@ -1,5 +1,7 @@
 pub mod aligner;
 pub mod emitter;
+pub mod provenance;
 pub use emitter::Emitter;
+pub use provenance::Provenance;
 #[cfg(test)]
 mod tests;
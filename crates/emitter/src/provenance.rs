@@ -0,0 +1,76 @@
+use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+use veryl_metadata::Metadata;
+
+/// Provenance written in the header of emitted files
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Provenance {
+    pub source: Option<String>,
+    pub commit: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+impl Provenance {
+    /// Collect the commit and the timestamp of the current build
+    pub fn new(metadata: &Metadata) -> Self {
+        let timestamp = if metadata.build.header.omit_timestamp {
+            None
+        } else {
+            Some(timestamp(now()))
+        };
+
+        Self {
+            source: None,
+            commit: metadata.revision(),
+            timestamp,
+        }
+    }
+
+    pub fn with_source(&self, source: &str) -> Self {
+        let mut ret = self.clone();
+        ret.source = Some(source.to_string());
+        ret
+    }
+}
+
+/// Seconds since the epoch, which can be pinned by `SOURCE_DATE_EPOCH`
+fn now() -> u64 {
+    if let Some(x) = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|x| x.parse().ok())
+    {
+        return x;
+    }
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format seconds since the epoch as RFC 3339 in UTC
+pub fn timestamp(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let secs = secs % 86400;
+
+    // Convert days to civil date (Howard Hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
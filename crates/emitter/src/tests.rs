@@ -1,17 +1,22 @@
-use crate::Emitter;
+use crate::emitter::{emit_bind, emit_ral};
+use crate::provenance::timestamp;
+use crate::{Emitter, Provenance};
 use std::path::PathBuf;
 use veryl_analyzer::Analyzer;
-use veryl_metadata::{ClockType, Metadata, ResetType};
+use veryl_metadata::{
+    ClockGatingCell, ClockType, LogStyle, MemoryStyle, Metadata, ProtocolCheck, ResetSynchronizer,
+    ResetType, TypeGenericStyle,
+};
 use veryl_parser::Parser;
 
 #[track_caller]
 fn emit(metadata: &Metadata, code: &str) -> String {
-    let parser = Parser::parse(&code, &"").unwrap();
+    let parser = Parser::parse(code, &"").unwrap();
     let analyzer = Analyzer::new(metadata);
 
-    analyzer.analyze_pass1(&"prj", &code, &"", &parser.veryl);
+    analyzer.analyze_pass1("prj", code, "", &parser.veryl);
     Analyzer::analyze_post_pass1();
-    analyzer.analyze_pass2(&"prj", &code, &"", &parser.veryl);
+    analyzer.analyze_pass2("prj", code, "", &parser.veryl);
 
     let mut emitter = Emitter::new(
         metadata,
@@ -19,7 +24,7 @@ fn emit(metadata: &Metadata, code: &str) -> String {
         &PathBuf::from("test.sv"),
         &PathBuf::from("test.sv.map"),
     );
-    emitter.emit(&"prj", &parser.veryl);
+    emitter.emit("prj", &parser.veryl);
     emitter.as_str().to_string()
 }
 
@@ -467,3 +472,1423 @@ endmodule
 
     assert_eq!(ret, expect);
 }
+
+#[test]
+fn protect_module() {
+    let code = r#"module ModuleA {
+}
+
+module ModuleB {
+}
+"#;
+
+    let expect = r#"`pragma protect key_keyowner = "Acme"
+`pragma protect data_method = "aes128-cbc"
+`pragma protect begin
+module prj_ModuleA;
+endmodule
+`pragma protect end
+
+module prj_ModuleB;
+endmodule
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    metadata.build.protect.modules = vec!["ModuleA".to_string()];
+    metadata.build.protect.key_keyowner = Some("Acme".to_string());
+    metadata.build.protect.data_method = Some("aes128-cbc".to_string());
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn emit_only_module() {
+    let code = r#"package PackageA {
+    const A: u32 = 1;
+}
+
+module ModuleA {
+}
+
+module ModuleB {
+    let _a: logic = PackageA::A;
+}
+"#;
+
+    let expect = r#"module prj_ModuleB;
+    logic _a;
+    always_comb _a = prj_PackageA::A;
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let parser = Parser::parse(code, &"").unwrap();
+    let analyzer = Analyzer::new(&metadata);
+
+    analyzer.analyze_pass1("prj", code, "", &parser.veryl);
+    Analyzer::analyze_post_pass1();
+    analyzer.analyze_pass2("prj", code, "", &parser.veryl);
+
+    let mut emitter = Emitter::new(
+        &metadata,
+        &PathBuf::from("test.veryl"),
+        &PathBuf::from("test.sv"),
+        &PathBuf::from("test.sv.map"),
+    );
+    assert!(emitter.emit_only("prj", &parser.veryl, &["ModuleB"]));
+
+    let ret = if cfg!(windows) {
+        emitter.as_str().replace("\r\n", "\n")
+    } else {
+        emitter.as_str().to_string()
+    };
+
+    assert_eq!(ret, expect);
+
+    let mut emitter = Emitter::new(
+        &metadata,
+        &PathBuf::from("test.veryl"),
+        &PathBuf::from("test.sv"),
+        &PathBuf::from("test.sv.map"),
+    );
+    assert!(!emitter.emit_only("prj", &parser.veryl, &["ModuleC"]));
+}
+
+#[test]
+fn sim_only() {
+    let code = r#"module ModuleA {
+    #[sim_only]
+    inst u: ModuleB;
+}
+
+#[sim_only]
+module ModuleB {
+    initial {
+        $display("hello");
+    }
+}
+"#;
+
+    let expect = r#"module prj_ModuleA;
+    `ifndef SYNTHESIS
+    prj_ModuleB u ();
+    `endif
+endmodule
+
+`ifndef SYNTHESIS
+module prj_ModuleB;
+    initial begin
+        $display("hello");
+    end
+endmodule
+`endif
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn log_style() {
+    let code = r#"module ModuleA {
+    initial {
+        $log_info("a: %d", 1);
+        $log_fatal();
+    }
+}
+"#;
+
+    let expect = r#"module prj_ModuleA;
+    initial begin
+        $display("[INFO] %s", $sformatf("a: %d", 1));
+        $display("[FATAL] %s", ""); $finish;
+    end
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+
+    let expect = r#"module prj_ModuleA;
+    initial begin
+        $info($sformatf("a: %d", 1));
+        $fatal(1, "");
+    end
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    metadata.build.log_style = LogStyle::Severity;
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+
+    let expect = r#"module prj_ModuleA;
+    initial begin
+        `uvm_info("VERYL", $sformatf("a: %d", 1), UVM_MEDIUM);
+        `uvm_fatal("VERYL", "");
+    end
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    metadata.build.log_style = LogStyle::Uvm;
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn clock_enable() {
+    let code = r#"module ModuleA (
+    i_clk: input clock,
+    i_rst: input reset,
+    i_en : input logic,
+) {
+    var a: logic;
+    var b: logic;
+
+    #[clock_en(i_en)]
+    always_ff {
+        if_reset {
+            a = 0;
+        } else {
+            a = ~a;
+        }
+    }
+
+    #[clock_en(i_en)]
+    always_ff (i_clk) {
+        b = a;
+    }
+}
+"#;
+
+    let expect = r#"module prj_ModuleA (
+    input logic i_clk,
+    input logic i_rst,
+    input logic i_en 
+);
+    logic a;
+    logic b;
+
+    always_ff @ (posedge i_clk, negedge i_rst) begin
+        if (!i_rst) begin
+            a <= 0;
+        end else if (i_en) begin
+            a <= ~a;
+        end
+    end
+
+    always_ff @ (posedge i_clk) if (i_en) begin
+        b <= a;
+    end
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+
+    let expect = r#"module prj_ModuleA (
+    input logic i_clk,
+    input logic i_rst,
+    input logic i_en 
+);
+    logic a;
+    logic b;
+    logic __i_clk_i_en_gated;
+    CKLNQD1 u__i_clk_i_en_gated (.CP(i_clk), .E(i_en), .Q(__i_clk_i_en_gated));
+
+    always_ff @ (posedge __i_clk_i_en_gated, negedge i_rst) begin
+        if (!i_rst) begin
+            a <= 0;
+        end else begin
+            a <= ~a;
+        end
+    end
+
+    always_ff @ (posedge __i_clk_i_en_gated) begin
+        b <= a;
+    end
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    metadata.build.clock_gating_cell = Some(ClockGatingCell {
+        module: "CKLNQD1".to_string(),
+        clock_port: "CP".to_string(),
+        enable_port: "E".to_string(),
+        gated_clock_port: "Q".to_string(),
+    });
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn reset_synchronizer() {
+    let code = r#"module ModuleA (
+    i_clk_a: input clock,
+    i_clk_b: input clock,
+    i_rst  : input reset,
+) {
+    var a: logic;
+    var b: logic;
+    var c: logic;
+
+    always_ff (i_clk_a, i_rst) {
+        if_reset {
+            a = 0;
+        } else {
+            a = ~a;
+        }
+    }
+
+    always_ff (i_clk_a, i_rst) {
+        if_reset {
+            b = 0;
+        } else {
+            b = a;
+        }
+    }
+
+    always_ff (i_clk_b) {
+        c = b;
+    }
+}
+"#;
+
+    let expect = r#"module prj_ModuleA (
+    input logic i_clk_a,
+    input logic i_clk_b,
+    input logic i_rst  
+);
+    logic a;
+    logic b;
+    logic c;
+    logic [2-1:0] __i_rst_i_clk_a_sync_ff;
+    always_ff @ (posedge i_clk_a, negedge i_rst) begin
+        if (!i_rst) begin
+            __i_rst_i_clk_a_sync_ff <= '0;
+        end else begin
+            __i_rst_i_clk_a_sync_ff[0] <= 1'b1;
+            for (int unsigned i = 1; i < 2; i++) begin
+                __i_rst_i_clk_a_sync_ff[i] <= __i_rst_i_clk_a_sync_ff[i - 1];
+            end
+        end
+    end
+    logic __i_rst_i_clk_a_sync;
+    always_comb __i_rst_i_clk_a_sync = __i_rst_i_clk_a_sync_ff[2-1];
+
+    always_ff @ (posedge i_clk_a, negedge __i_rst_i_clk_a_sync) begin
+        if (!__i_rst_i_clk_a_sync) begin
+            a <= 0;
+        end else begin
+            a <= ~a;
+        end
+    end
+
+    always_ff @ (posedge i_clk_a, negedge __i_rst_i_clk_a_sync) begin
+        if (!__i_rst_i_clk_a_sync) begin
+            b <= 0;
+        end else begin
+            b <= a;
+        end
+    end
+
+    always_ff @ (posedge i_clk_b) begin
+        c <= b;
+    end
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+    metadata.build.reset_synchronizer = Some(ResetSynchronizer::default());
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn parity() {
+    let code = r#"module ModuleA (
+    i_clk: input  clock   ,
+    i_rst: input  reset   ,
+    i_dat: input  logic<8>,
+    o_dat: output logic<8>,
+) {
+    #[parity]
+    var dat: logic<8>;
+
+    always_ff {
+        if_reset {
+            dat = '1;
+        } else {
+            dat += i_dat;
+        }
+    }
+    assign o_dat = dat;
+}
+"#;
+
+    let expect = r#"module prj_ModuleA (
+    input  logic         i_clk,
+    input  logic         i_rst,
+    input  logic [8-1:0] i_dat,
+    output logic [8-1:0] o_dat
+);
+
+    logic [8-1:0] dat;
+    logic __dat_parity;
+    logic __dat_parity_error;
+    always_comb __dat_parity_error = (^dat) ^ __dat_parity;
+
+    always_ff @ (posedge i_clk, negedge i_rst) begin
+        if (!i_rst) begin
+            dat <= '1;
+            __dat_parity <= ^(($bits(dat))'('1));
+        end else begin
+            dat <= dat + (i_dat);
+            __dat_parity <= ^(($bits(dat))'(dat + (i_dat)));
+        end
+    end
+    always_comb o_dat = dat;
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn memory_style() {
+    let code = r#"module ModuleA (
+    i_clk: input  clock   ,
+    i_we : input  logic   ,
+    i_adr: input  logic<4>,
+    i_dat: input  logic<8>,
+    o_dat: output logic<8>,
+) {
+    #[memory(simple_dual_port)]
+    var mem: logic<8> [16];
+
+    always_ff {
+        if i_we {
+            mem[i_adr] = i_dat;
+        }
+        o_dat = mem[i_adr];
+    }
+}
+"#;
+
+    let expect = r#"module prj_ModuleA (
+    input  logic         i_clk,
+    input  logic         i_we ,
+    input  logic [4-1:0] i_adr,
+    input  logic [8-1:0] i_dat,
+    output logic [8-1:0] o_dat
+);
+
+    logic [8-1:0] mem [0:16-1];
+
+    always_ff @ (posedge i_clk) begin
+        if (i_we) begin
+            mem[i_adr] <= i_dat;
+        end
+        o_dat <= mem[i_adr];
+    end
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+
+    let expect = r#"module prj_ModuleA (
+    input  logic         i_clk,
+    input  logic         i_we ,
+    input  logic [4-1:0] i_adr,
+    input  logic [8-1:0] i_dat,
+    output logic [8-1:0] o_dat
+);
+    (* ram_style = "block" *)
+    logic [8-1:0] mem [0:16-1];
+
+    always_ff @ (posedge i_clk) begin
+        if (i_we) begin
+            mem[i_adr] <= i_dat;
+        end
+        o_dat <= mem[i_adr];
+    end
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    metadata.build.memory_style = MemoryStyle::Xilinx;
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn type_generic_style() {
+    let code = r#"module ModuleA::<T: type, U: type = PackageA::B> #(
+    param N: u32 = 1,
+) (
+    i_d: input  T,
+    o_d: output T,
+    o_u: output U,
+) {
+    assign o_d = i_d;
+    assign o_u = 0;
+}
+
+module ModuleB (
+    i_d: input  logic<8>,
+    o_d: output logic<8>,
+) {
+    var u: PackageA::B;
+
+    inst u0: ModuleA::<PackageA::B> #(
+        N: 2,
+    ) (
+        i_d,
+        o_d,
+        o_u: u,
+    );
+}
+
+package PackageA {
+    type B = logic<8>;
+}
+"#;
+
+    let expect = r#"module prj_ModuleA #(
+    parameter type T = logic,
+    parameter type U = prj_PackageA::B,
+    parameter int unsigned N = 1
+) (
+    input  T i_d,
+    output T o_d,
+    output U o_u
+);
+    always_comb o_d = i_d;
+    always_comb o_u = 0;
+endmodule
+
+module prj_ModuleB (
+    input  logic [8-1:0] i_d,
+    output logic [8-1:0] o_d
+);
+    prj_PackageA::B u;
+
+    prj_ModuleA #(
+        .T (prj_PackageA::B),
+        .N (2)
+    ) u0 (
+        .i_d (i_d),
+        .o_d (o_d),
+        .o_u (u  )
+    );
+endmodule
+
+package prj_PackageA;
+    typedef logic [8-1:0] B;
+endpackage
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+    metadata.build.type_generic_style = TypeGenericStyle::Parameter;
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn flatten() {
+    let code = r#"interface InterfaceA {
+    var a: logic<8>;
+    var b: logic   ;
+    modport master {
+        a: output,
+        b: input ,
+    }
+}
+
+module ModuleA (
+    i_d: input logic<8>,
+    #[flatten]
+    bus: modport InterfaceA::master,
+) {
+    assign bus.a = i_d;
+}
+
+module ModuleB (
+    #[flatten]
+    bus: modport InterfaceA::master,
+) {
+    inst u: ModuleA (
+        i_d: 0,
+        bus   ,
+    );
+}
+
+module ModuleC {
+    inst bus_if: InterfaceA;
+    inst u     : ModuleB (
+        bus: bus_if,
+    );
+    assign bus_if.b = 0;
+}
+"#;
+
+    let expect = r#"interface prj_InterfaceA;
+    logic [8-1:0] a;
+    logic         b;
+    modport master (
+        output a,
+        input  b
+    );
+endinterface
+
+module prj_ModuleA (
+    input logic [8-1:0] i_d,
+    output logic [8-1:0] bus_a,
+    input logic bus_b
+);
+    always_comb bus_a = i_d;
+endmodule
+
+module prj_ModuleB (
+    output logic [8-1:0] bus_a,
+    input logic bus_b
+);
+    prj_ModuleA u (
+        .i_d (0),
+        .bus_a (bus_a),
+        .bus_b (bus_b)
+    );
+endmodule
+
+module prj_ModuleC;
+    prj_InterfaceA bus_if ();
+    prj_ModuleB u (
+        .bus_a (bus_if.a),
+        .bus_b (bus_if.b)
+    );
+    always_comb bus_if.b = 0;
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn auto_connect() {
+    let code = r#"module ModuleA (
+    i_clk: input  clock   ,
+    i_a  : input  logic<8>,
+    o_b  : output logic<8>,
+) {
+    assign o_b = i_a;
+}
+
+module ModuleB (
+    i_clk: input  clock   ,
+    o_b  : output logic<8>,
+) {
+    var a: logic<8>;
+    assign a = 1;
+
+    #[auto_connect]
+    inst u0: ModuleA (
+        i_a: a,
+    );
+
+    #[auto_connect]
+    inst u1: ModuleC;
+}
+
+module ModuleC (
+    i_clk: input clock,
+) {}
+"#;
+
+    let expect = r#"module prj_ModuleA (
+    input  logic         i_clk,
+    input  logic [8-1:0] i_a  ,
+    output logic [8-1:0] o_b  
+);
+    always_comb o_b = i_a;
+endmodule
+
+module prj_ModuleB (
+    input  logic         i_clk,
+    output logic [8-1:0] o_b  
+);
+    logic [8-1:0] a;
+    always_comb a = 1;
+
+    prj_ModuleA u0 (
+        .i_a (a),
+        .i_clk (i_clk),
+        .o_b (o_b)
+    );
+
+    prj_ModuleC u1 (
+        .i_clk (i_clk)
+    );
+endmodule
+
+module prj_ModuleC (
+    input logic i_clk
+);
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn unconnected_port() {
+    let code = r#"module ModuleA (
+    i_clk: input  clock,
+    i_rst: input  reset,
+    o_a  : output logic,
+) {
+    assign o_a = 1;
+}
+
+module ModuleB (
+    i_clk: input clock,
+) {
+    #[allow(missing_port)]
+    inst u: ModuleA (
+        i_clk,
+        o_a  : _,
+    );
+}
+"#;
+
+    let expect = r#"module prj_ModuleA (
+    input  logic i_clk,
+    input  logic i_rst,
+    output logic o_a  
+);
+    always_comb o_a = 1;
+endmodule
+
+module prj_ModuleB (
+    input logic i_clk
+);
+
+    prj_ModuleA u (
+        .i_clk (i_clk     ),
+        .o_a   (),
+        .i_rst ()
+    );
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn rename() {
+    let code = r#"module ModuleA (
+    i_clk: input  clock   ,
+    i_rst: input  reset   ,
+    i_a  : input  logic<8>,
+    o_b  : output logic<8>,
+) {
+    always_ff {
+        if_reset {
+            o_b = 0;
+        } else {
+            o_b = i_a;
+        }
+    }
+}
+
+module ModuleB (
+    i_clk: input  clock   ,
+    i_rst: input  reset   ,
+    o_b  : output logic<8>,
+) {
+    var a: logic<8>;
+    assign a = 1;
+
+    inst u: ModuleA (
+        i_clk    ,
+        i_rst    ,
+        i_a  : a ,
+        o_b      ,
+    );
+}
+"#;
+
+    let expect = r#"module acme_a (
+    input  logic         clk_i,
+    input  logic         rst_i,
+    input  logic [8-1:0] a_i  ,
+    output logic [8-1:0] o_b  
+);
+    always_ff @ (posedge clk_i, negedge rst_i) begin
+        if (!rst_i) begin
+            o_b <= 0;
+        end else begin
+            o_b <= a_i;
+        end
+    end
+endmodule
+
+module prj_ModuleB (
+    input  logic         clk_i,
+    input  logic         rst_i,
+    output logic [8-1:0] o_b  
+);
+    logic [8-1:0] a;
+    always_comb a = 1;
+
+    acme_a u (
+        .clk_i (clk_i),
+        .rst_i (rst_i),
+        .a_i   (a    ),
+        .o_b   (o_b  )
+    );
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    metadata.build.rename = toml::from_str(
+        r#"
+module = [{pattern = "^prj_ModuleA$", replace = "acme_a"}]
+port = [{pattern = "^i_(.*)$", replace = "${1}_i"}]
+"#,
+    )
+    .unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn pipeline() {
+    let code = r#"module ModuleA (
+    i_clk  : input  clock   ,
+    i_rst  : input  reset   ,
+    i_valid: input  logic   ,
+    i_dat  : input  logic<8>,
+    o_valid: output logic   ,
+    o_dat  : output logic<8>,
+) {
+    #[pipeline]
+    var valid: logic [3];
+    #[pipeline(valid)]
+    var dat: logic<8> [3];
+
+    assign valid[0] = i_valid;
+    assign dat[0]   = i_dat;
+    assign o_valid  = valid[2];
+    assign o_dat    = dat[2];
+}
+"#;
+
+    let expect = r#"module prj_ModuleA (
+    input  logic         i_clk  ,
+    input  logic         i_rst  ,
+    input  logic         i_valid,
+    input  logic [8-1:0] i_dat  ,
+    output logic         o_valid,
+    output logic [8-1:0] o_dat  
+);
+
+    logic valid [0:3-1];
+    for (genvar i = 1; i < $size(valid); i++) begin : __valid_pipeline
+        always_ff @ (posedge i_clk, negedge i_rst) begin
+            if (!i_rst) begin
+                valid[i] <= '0;
+            end else begin
+                valid[i] <= valid[i - 1];
+            end
+        end
+    end
+
+    logic [8-1:0] dat [0:3-1];
+    for (genvar i = 1; i < $size(dat); i++) begin : __dat_pipeline
+        always_ff @ (posedge i_clk) begin
+            dat[i] <= valid[i - 1] ? dat[i - 1] : dat[i];
+        end
+    end
+
+    always_comb valid[0] = i_valid;
+    always_comb dat[0]   = i_dat;
+    always_comb o_valid  = valid[2];
+    always_comb o_dat    = dat[2];
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn sync() {
+    let code = r#"module ModuleA (
+    i_clk_a: input  `a clock   ,
+    i_dat_a: input  `a logic<2>,
+    i_clk_b: input  `b clock   ,
+    o_dat_b: output `b logic<2>,
+) {
+    #[sync(i_dat_a, i_clk_b, "3")]
+    var dat_b: `b logic<2>;
+
+    assign o_dat_b = dat_b;
+}
+"#;
+
+    let expect = r#"module prj_ModuleA (
+    input  logic         i_clk_a,
+    input  logic [2-1:0] i_dat_a,
+    input  logic         i_clk_b,
+    output logic [2-1:0] o_dat_b
+);
+
+    logic [2-1:0] dat_b;
+    logic [$bits(dat_b)-1:0] __dat_b_sync [0:3-1];
+    always_ff @ (posedge i_clk_b) begin
+        __dat_b_sync[0] <= i_dat_a;
+        for (int unsigned i = 1; i < 3; i++) begin
+            __dat_b_sync[i] <= __dat_b_sync[i - 1];
+        end
+    end
+    always_comb dat_b = __dat_b_sync[3-1];
+
+    always_comb o_dat_b = dat_b;
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn protocol_check() {
+    let code = r#"module CheckerA (
+    clk  : input clock,
+    valid: input logic,
+    ready: input logic,
+) {
+    always_ff (clk) {
+        if valid && !ready {
+            $display("stall");
+        }
+    }
+}
+
+#[protocol(CheckerA)]
+interface InterfaceA {
+    var clk  : clock;
+    var valid: logic;
+    var ready: logic;
+}
+"#;
+
+    let expect = r#"module prj_CheckerA (
+    input logic clk  ,
+    input logic valid,
+    input logic ready
+);
+    always_ff @ (posedge clk) begin
+        if (valid && !ready) begin
+            $display("stall");
+        end
+    end
+endmodule
+
+interface prj_InterfaceA;
+    logic clk  ;
+    logic valid;
+    logic ready;
+endinterface
+`ifndef SYNTHESIS
+    bind prj_InterfaceA prj_CheckerA u__CheckerA (.*);
+`endif
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+
+    let expect = r#"module prj_CheckerA (
+    input logic clk  ,
+    input logic valid,
+    input logic ready
+);
+    always_ff @ (posedge clk) begin
+        if (valid && !ready) begin
+            $display("stall");
+        end
+    end
+endmodule
+
+interface prj_InterfaceA;
+    logic clk  ;
+    logic valid;
+    logic ready;
+endinterface
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    metadata.build.protocol_check = ProtocolCheck::None;
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn bind_file() {
+    let code = r#"module ModuleA {
+}
+"#;
+
+    let expect = r#"`ifndef SYNTHESIS
+    bind prj_ModuleA checker_a u__checker_a (.*);
+    bind prj_ModuleA checker_b u__checker_b (.*);
+`endif
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    metadata.verification.bind.insert(
+        "ModuleA".to_string(),
+        vec!["checker_a".to_string(), "checker_b".to_string()],
+    );
+
+    emit(&metadata, code);
+    let ret = emit_bind(&metadata).unwrap();
+    let ret = if cfg!(windows) {
+        ret.replace("\r\n", "\n")
+    } else {
+        ret
+    };
+
+    assert_eq!(ret, expect);
+
+    metadata
+        .verification
+        .bind
+        .insert("ModuleB".to_string(), vec!["checker_a".to_string()]);
+
+    assert_eq!(emit_bind(&metadata), Err("ModuleB".to_string()));
+}
+
+#[test]
+fn ral_file() {
+    let expect = r#"package prj_ral_pkg;
+    import uvm_pkg::*;
+    `include "uvm_macros.svh"
+
+    class ctrl_CTRL extends uvm_reg;
+        `uvm_object_utils(ctrl_CTRL)
+
+        rand uvm_reg_field EN;
+        rand uvm_reg_field MODE;
+
+        function new(string name = "ctrl_CTRL");
+            super.new(name, 32, UVM_NO_COVERAGE);
+        endfunction
+
+        virtual function void build();
+            EN = uvm_reg_field::type_id::create("EN");
+            EN.configure(this, 1, 0, "RW", 0, 1'h1, 1, 1, 0);
+            MODE = uvm_reg_field::type_id::create("MODE");
+            MODE.configure(this, 4, 4, "RO", 0, 4'ha, 1, 0, 0);
+        endfunction
+    endclass
+
+    class ctrl_block extends uvm_reg_block;
+        `uvm_object_utils(ctrl_block)
+
+        rand ctrl_CTRL CTRL;
+
+        function new(string name = "ctrl_block");
+            super.new(name, UVM_NO_COVERAGE);
+        endfunction
+
+        virtual function void build();
+            default_map = create_map("default_map", 0, 4, UVM_LITTLE_ENDIAN);
+            CTRL = ctrl_CTRL::type_id::create("CTRL");
+            CTRL.configure(this);
+            CTRL.build();
+            default_map.add_reg(CTRL, 'h10, "RW");
+            lock_model();
+        endfunction
+    endclass
+
+    class prj_reg_adapter extends uvm_reg_adapter;
+        `uvm_object_utils(prj_reg_adapter)
+
+        function new(string name = "prj_reg_adapter");
+            super.new(name);
+        endfunction
+
+        virtual function uvm_sequence_item reg2bus(const ref uvm_reg_bus_op rw);
+            // TODO: convert `rw` to a bus transaction
+            return null;
+        endfunction
+
+        virtual function void bus2reg(uvm_sequence_item bus_item, ref uvm_reg_bus_op rw);
+            // TODO: convert `bus_item` to `rw`
+        endfunction
+    endclass
+endpackage
+"#;
+
+    let toml = format!(
+        r#"{}
+[[verification.ral.blocks]]
+name = "ctrl"
+registers = [
+    {{name = "CTRL", offset = 0x10, fields = [
+        {{name = "EN", lsb = 0, width = 1, reset = 1}},
+        {{name = "MODE", lsb = 4, width = 4, access = "RO", reset = 10}},
+    ]}},
+]
+"#,
+        Metadata::create_default_toml("prj").unwrap()
+    );
+    let mut metadata: Metadata = toml::from_str(&toml).unwrap();
+
+    let ret = emit_ral(&metadata).unwrap();
+    let ret = if cfg!(windows) {
+        ret.replace("\r\n", "\n")
+    } else {
+        ret
+    };
+
+    assert_eq!(ret, expect);
+
+    metadata.verification.ral.blocks[0].registers[0].fields[1].lsb = 31;
+    assert_eq!(
+        emit_ral(&metadata),
+        Err("field \"ctrl_CTRL.MODE\" is out of the register width".to_string())
+    );
+
+    metadata.verification.ral.blocks[0].registers[0].fields[1].lsb = 0;
+    assert_eq!(
+        emit_ral(&metadata),
+        Err("field \"ctrl_CTRL.MODE\" overlaps with other field".to_string())
+    );
+}
+
+#[test]
+fn header() {
+    let code = r#"module ModuleA {
+}
+"#;
+
+    let expect = format!(
+        r#"// Generated by Veryl {}. DO NOT EDIT.
+// Source    : src/a.veryl
+// Commit    : 0123abcd
+// Timestamp : 2024-02-29T12:34:56Z
+// Copyright (c) 2024 Example
+//
+// SPDX-License-Identifier: MIT
+
+module prj_ModuleA;
+endmodule
+//# sourceMappingURL=test.sv.map
+"#,
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+    metadata.build.header.enable = true;
+    metadata.build.header.text =
+        vec!["Copyright (c) 2024 Example\n\nSPDX-License-Identifier: MIT".to_string()];
+
+    let parser = Parser::parse(code, &"").unwrap();
+    let analyzer = Analyzer::new(&metadata);
+    analyzer.analyze_pass1("prj", code, "", &parser.veryl);
+    Analyzer::analyze_post_pass1();
+    analyzer.analyze_pass2("prj", code, "", &parser.veryl);
+
+    let provenance = Provenance {
+        commit: Some("0123abcd".to_string()),
+        timestamp: Some(timestamp(1709210096)),
+        ..Default::default()
+    };
+    let mut emitter = Emitter::new(
+        &metadata,
+        &PathBuf::from("test.veryl"),
+        &PathBuf::from("test.sv"),
+        &PathBuf::from("test.sv.map"),
+    );
+    emitter.set_provenance(provenance.with_source("src/a.veryl"));
+    emitter.emit("prj", &parser.veryl);
+
+    let ret = if cfg!(windows) {
+        emitter.as_str().replace("\r\n", "\n")
+    } else {
+        emitter.as_str().to_string()
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn param_overrides() {
+    let code = r#"module ModuleA #(
+    param N: u32 = 1,
+    param M: u32 = 2,
+) {}
+
+module ModuleB #(
+    param N: u32 = 1,
+) {}
+"#;
+
+    let expect = r#"module prj_ModuleA #(
+    parameter int unsigned N = 16,
+    parameter int unsigned M = 2 
+);
+endmodule
+
+module prj_ModuleB #(
+    parameter int unsigned N = 1
+);
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+    metadata.build.param_overrides.insert(
+        "ModuleA".to_string(),
+        [("N".to_string(), "16".to_string())].into(),
+    );
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn annotate() {
+    let code = r#"module ModuleA #(
+    param WIDTH: u32 = 4 * 2,
+    param DEPTH: u32 = WIDTH + 1,
+) (
+    i_a: input  logic<WIDTH>,
+    o_b: output logic<DEPTH>,
+    o_c: output logic<2> [4],
+) {
+    assign o_b = 0;
+    assign o_c = '{default: 0};
+}
+"#;
+
+    let mut metadata: Metadata =
+        toml::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+    metadata.build.annotate = true;
+
+    let expect = r#"// ModuleA                                    src/a.veryl:1:8
+//   param WIDTH: u32 = 8                     src/a.veryl:2:11
+//   param DEPTH: u32 = 9                     src/a.veryl:3:11
+//   port  i_a: input logic<WIDTH> (8 bits)   src/a.veryl:5:5
+//   port  o_b: output logic<DEPTH> (9 bits)  src/a.veryl:6:5
+//   port  o_c: output logic<2> [4]           src/a.veryl:7:5
+module prj_ModuleA #(
+"#;
+
+    let path = "src/a.veryl";
+    let parser = Parser::parse(code, &path).unwrap();
+    let analyzer = Analyzer::new(&metadata);
+    analyzer.analyze_pass1("prj", code, path, &parser.veryl);
+    Analyzer::analyze_post_pass1();
+    analyzer.analyze_pass2("prj", code, path, &parser.veryl);
+
+    let mut emitter = Emitter::new(
+        &metadata,
+        &PathBuf::from("test.veryl"),
+        &PathBuf::from("test.sv"),
+        &PathBuf::from("test.sv.map"),
+    );
+    emitter.emit("prj", &parser.veryl);
+
+    let ret = if cfg!(windows) {
+        emitter.as_str().replace("\r\n", "\n")
+    } else {
+        emitter.as_str().to_string()
+    };
+    assert!(ret.starts_with(expect));
+}
@@ -1,4 +1,5 @@
 use crate::aligner::{Aligner, Location};
+use std::collections::HashMap;
 use veryl_metadata::{Format, Metadata};
 use veryl_parser::resource_table;
 use veryl_parser::veryl_grammar_trait::*;
@@ -21,6 +22,7 @@ pub struct Formatter {
     single_line: bool,
     adjust_line: bool,
     case_item_indent: Vec<usize>,
+    line_indents: HashMap<u32, Option<usize>>,
 }
 
 impl Default for Formatter {
@@ -36,6 +38,7 @@ impl Default for Formatter {
             single_line: false,
             adjust_line: false,
             case_item_indent: Vec::new(),
+            line_indents: HashMap::new(),
         }
     }
 }
@@ -57,6 +60,12 @@ impl Formatter {
         &self.string
     }
 
+    /// Returns the indent width of `line` (1-origin) of the source in the formatted text.
+    /// `None` is returned if the first token of `line` is not placed at the beginning of line.
+    pub fn line_indent(&self, line: u32) -> Option<usize> {
+        self.line_indents.get(&line).copied().flatten()
+    }
+
     fn column(&self) -> usize {
         self.string.len() - self.string.rfind('\n').unwrap_or(0)
     }
@@ -141,6 +150,11 @@ impl Formatter {
 
     fn push_token(&mut self, x: &Token) {
         self.consume_adjust_line(x);
+
+        let head = &self.string[self.string.rfind('\n').map(|x| x + 1).unwrap_or(0)..];
+        let indent = head.bytes().all(|x| x == b' ').then_some(head.len());
+        self.line_indents.entry(x.line).or_insert(indent);
+
         let text = resource_table::get_str_value(x.text).unwrap();
         let text = if text.ends_with('\n') {
             self.consumed_next_newline = true;
@@ -1,9 +1,14 @@
+use crate::code_lens::PREVIEW_COMMAND;
+use crate::on_type_formatting::{ON_TYPE_FORMATTING_MORE_TRIGGER, ON_TYPE_FORMATTING_TRIGGER};
 use crate::server::{semantic_legend, MsgFromServer, MsgToServer, Server, ServerConfigItem};
+use crate::signature_help::SIGNATURE_HELP_TRIGGER;
 use async_channel::{unbounded, Receiver, Sender};
-use serde_json::Value;
-use tower_lsp::jsonrpc::Result;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
+use veryl_metadata::CancellationToken;
 
 const COMPLETION_TRIGGER: &[&str] = &["<", ">", "=", "!", "."];
 
@@ -12,19 +17,26 @@ pub struct Backend {
     client: Client,
     rcv: Receiver<MsgFromServer>,
     snd: Sender<MsgToServer>,
+    type_hierarchy_registration: AtomicBool,
+    watched_files_registration: AtomicBool,
+    cancellation: CancellationToken,
 }
 
 impl Backend {
     pub fn new(client: Client) -> Self {
         let (tx_from, rx_from) = unbounded();
         let (tx_to, rx_to) = unbounded();
-        let mut server = Server::new(client.clone(), rx_to, tx_from);
+        let cancellation = CancellationToken::new();
+        let mut server = Server::new(client.clone(), rx_to, tx_from, cancellation.clone());
         std::thread::spawn(move || server.serve());
 
         Self {
             client,
             rcv: rx_from,
             snd: tx_to,
+            type_hierarchy_registration: AtomicBool::new(false),
+            watched_files_registration: AtomicBool::new(false),
+            cancellation,
         }
     }
 
@@ -47,7 +59,27 @@ impl Backend {
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _params: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        // ServerCapabilities of lsp-types doesn't have typeHierarchyProvider,
+        // so type hierarchy is registered dynamically if the client supports it.
+        let type_hierarchy_registration = params
+            .capabilities
+            .text_document
+            .and_then(|x| x.type_hierarchy)
+            .and_then(|x| x.dynamic_registration)
+            .unwrap_or(false);
+        self.type_hierarchy_registration
+            .store(type_hierarchy_registration, Ordering::Relaxed);
+
+        let watched_files_registration = params
+            .capabilities
+            .workspace
+            .and_then(|x| x.did_change_watched_files)
+            .and_then(|x| x.dynamic_registration)
+            .unwrap_or(false);
+        self.watched_files_registration
+            .store(watched_files_registration, Ordering::Relaxed);
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
@@ -62,6 +94,15 @@ impl LanguageServer for Backend {
                 }),
                 definition_provider: Some(OneOf::Left(true)),
                 document_formatting_provider: Some(OneOf::Left(true)),
+                document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                    first_trigger_character: ON_TYPE_FORMATTING_TRIGGER.to_string(),
+                    more_trigger_character: Some(
+                        ON_TYPE_FORMATTING_MORE_TRIGGER
+                            .iter()
+                            .map(|x| x.to_string())
+                            .collect(),
+                    ),
+                }),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 references_provider: Some(OneOf::Left(true)),
@@ -89,6 +130,31 @@ impl LanguageServer for Backend {
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                     completion_item: None,
                 }),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(false),
+                }),
+                document_link_provider: Some(DocumentLinkOptions {
+                    resolve_provider: Some(false),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![PREVIEW_COMMAND.to_string()],
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(
+                        SIGNATURE_HELP_TRIGGER
+                            .iter()
+                            .map(|x| x.to_string())
+                            .collect(),
+                    ),
+                    retrigger_characters: None,
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
                 ..ServerCapabilities::default()
             },
             server_info: Some(ServerInfo {
@@ -102,6 +168,35 @@ impl LanguageServer for Backend {
         self.client
             .log_message(MessageType::INFO, "server initialized!")
             .await;
+
+        let mut registrations = Vec::new();
+        if self.type_hierarchy_registration.load(Ordering::Relaxed) {
+            registrations.push(Registration {
+                id: "type_hierarchy".to_string(),
+                method: "textDocument/prepareTypeHierarchy".to_string(),
+                register_options: Some(json!({
+                    "documentSelector": [{ "language": "veryl" }]
+                })),
+            });
+        }
+        if self.watched_files_registration.load(Ordering::Relaxed) {
+            let options = DidChangeWatchedFilesRegistrationOptions {
+                watchers: vec![FileSystemWatcher {
+                    glob_pattern: GlobPattern::String("**/Veryl.toml".to_string()),
+                    kind: None,
+                }],
+            };
+            registrations.push(Registration {
+                id: "watched_files".to_string(),
+                method: "workspace/didChangeWatchedFiles".to_string(),
+                register_options: serde_json::to_value(options).ok(),
+            });
+        }
+        if !registrations.is_empty() {
+            if let Err(x) = self.client.register_capability(registrations).await {
+                self.client.log_message(MessageType::ERROR, x).await;
+            }
+        }
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
@@ -123,6 +218,8 @@ impl LanguageServer for Backend {
         let text = std::mem::take(&mut params.content_changes[0].text);
         let version = params.text_document.version;
 
+        // Abort the in-flight analysis because it may be outdated by this change
+        self.cancellation.cancel();
         self.send(MsgToServer::DidChange { url, text, version })
             .await;
     }
@@ -135,6 +232,14 @@ impl LanguageServer for Backend {
                     format!("did_change_watched_files: {change:?}"),
                 )
                 .await;
+
+            if change.uri.path().ends_with("/Veryl.toml") {
+                let url = change.uri;
+                let deleted = change.typ == FileChangeType::DELETED;
+                self.cancellation.cancel();
+                self.send(MsgToServer::DidChangeMetadata { url, deleted })
+                    .await;
+            }
         }
     }
 
@@ -259,6 +364,230 @@ impl LanguageServer for Backend {
         }
     }
 
+    async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let url = params.text_document_position.text_document.uri;
+        let line = params.text_document_position.position.line as usize + 1;
+        let ch = params.ch;
+
+        self.send(MsgToServer::OnTypeFormatting { url, line, ch })
+            .await;
+
+        if let Some(MsgFromServer::OnTypeFormatting(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let url = params.text_document_position_params.text_document.uri;
+        let line = params.text_document_position_params.position.line as usize + 1;
+        let column = params.text_document_position_params.position.character as usize + 1;
+
+        self.send(MsgToServer::SignatureHelp { url, line, column })
+            .await;
+
+        if let Some(MsgFromServer::SignatureHelp(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let url = params.text_document.uri;
+
+        self.send(MsgToServer::FoldingRange { url }).await;
+
+        if let Some(MsgFromServer::FoldingRange(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        let url = params.text_document.uri;
+        let positions = params.positions;
+
+        self.send(MsgToServer::SelectionRange { url, positions })
+            .await;
+
+        if let Some(MsgFromServer::SelectionRange(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn document_link(&self, params: DocumentLinkParams) -> Result<Option<Vec<DocumentLink>>> {
+        let url = params.text_document.uri;
+
+        self.send(MsgToServer::DocumentLink { url }).await;
+
+        if let Some(MsgFromServer::DocumentLink(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let url = params.text_document.uri;
+
+        self.send(MsgToServer::CodeLens { url }).await;
+
+        if let Some(MsgFromServer::CodeLens(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let url = params.text_document.uri;
+        let range = params.range;
+
+        self.send(MsgToServer::CodeAction { url, range }).await;
+
+        if let Some(MsgFromServer::CodeAction(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<Value>> {
+        if params.command != PREVIEW_COMMAND {
+            return Err(Error::invalid_params(format!(
+                "unknown command: {}",
+                params.command
+            )));
+        }
+
+        let (Some(url), Some(module)) = (
+            params.arguments.first().and_then(|x| x.as_str()),
+            params.arguments.get(1).and_then(|x| x.as_str()),
+        ) else {
+            return Err(Error::invalid_params("URI and module name are required"));
+        };
+        let Ok(url) = Url::parse(url) else {
+            return Err(Error::invalid_params(format!("invalid URI: {url}")));
+        };
+        let module = module.to_string();
+
+        self.send(MsgToServer::PreviewSystemVerilog { url, module })
+            .await;
+
+        if let Some(MsgFromServer::ExecuteCommand(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let url = params.text_document_position_params.text_document.uri;
+        let line = params.text_document_position_params.position.line as usize + 1;
+        let column = params.text_document_position_params.position.character as usize + 1;
+
+        self.send(MsgToServer::PrepareCallHierarchy { url, line, column })
+            .await;
+
+        if let Some(MsgFromServer::PrepareCallHierarchy(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        let item = params.item;
+
+        self.send(MsgToServer::IncomingCalls { item }).await;
+
+        if let Some(MsgFromServer::IncomingCalls(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        let item = params.item;
+
+        self.send(MsgToServer::OutgoingCalls { item }).await;
+
+        if let Some(MsgFromServer::OutgoingCalls(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let url = params.text_document_position_params.text_document.uri;
+        let line = params.text_document_position_params.position.line as usize + 1;
+        let column = params.text_document_position_params.position.character as usize + 1;
+
+        self.send(MsgToServer::PrepareTypeHierarchy { url, line, column })
+            .await;
+
+        if let Some(MsgFromServer::PrepareTypeHierarchy(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let item = params.item;
+
+        self.send(MsgToServer::Supertypes { item }).await;
+
+        if let Some(MsgFromServer::Supertypes(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let item = params.item;
+
+        self.send(MsgToServer::Subtypes { item }).await;
+
+        if let Some(MsgFromServer::Subtypes(x)) = self.recv().await {
+            Ok(x)
+        } else {
+            Ok(None)
+        }
+    }
+
     async fn shutdown(&self) -> Result<()> {
         Ok(())
     }
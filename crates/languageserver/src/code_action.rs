@@ -0,0 +1,71 @@
+use ropey::Rope;
+use std::collections::HashMap;
+use tower_lsp::lsp_types::*;
+use veryl_parser::veryl_grammar_trait::Veryl;
+
+/// Name of the module created by "Extract module".
+/// It can be renamed through "Rename Symbol" after the extraction.
+pub const EXTRACTED_MODULE_NAME: &str = "ExtractedModule";
+
+fn to_position(rope: &Rope, offset: usize) -> Position {
+    let char = rope.byte_to_char(offset.min(rope.len_bytes()));
+    let line = rope.char_to_line(char);
+    let column = char - rope.line_to_char(line);
+    Position::new(line as u32, column as u32)
+}
+
+/// Lines fully covered by `range` in 1-origin
+fn selected_lines(range: &Range) -> Option<(u32, u32)> {
+    if range.start == range.end {
+        return None;
+    }
+    let first = range.start.line + 1;
+    // The selection ending at the beginning of a line doesn't include the line
+    let last = if range.end.character == 0 && range.end.line > range.start.line {
+        range.end.line
+    } else {
+        range.end.line + 1
+    };
+    Some((first, last))
+}
+
+pub fn code_actions(
+    veryl: &Veryl,
+    rope: &Rope,
+    url: &Url,
+    range: &Range,
+    indent_width: usize,
+) -> Vec<CodeActionOrCommand> {
+    let mut ret = Vec::new();
+
+    if let Some((first, last)) = selected_lines(range) {
+        let text = rope.to_string();
+        if let Ok(edits) = veryl_refactor::extract_module(
+            veryl,
+            &text,
+            first..=last,
+            EXTRACTED_MODULE_NAME,
+            indent_width,
+        ) {
+            let edits = edits
+                .into_iter()
+                .map(|x| TextEdit {
+                    range: Range::new(to_position(rope, x.start), to_position(rope, x.end)),
+                    new_text: x.text,
+                })
+                .collect();
+            let changes = HashMap::from([(url.clone(), edits)]);
+            ret.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: "Extract module".to_string(),
+                kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }));
+        }
+    }
+
+    ret
+}
@@ -0,0 +1,110 @@
+use serde_json::json;
+use std::path::Path;
+use tower_lsp::lsp_types::*;
+use veryl_analyzer::namespace_table;
+use veryl_analyzer::symbol_path::SymbolPath;
+use veryl_analyzer::symbol_table;
+use veryl_emitter::Emitter;
+use veryl_metadata::{Metadata, SourceMapTarget};
+use veryl_parser::veryl_grammar_trait::{
+    DescriptionGroup, DescriptionGroupGroup, DescriptionItem, ModuleDeclaration, Veryl,
+};
+use veryl_parser::veryl_token::Token;
+
+pub const PREVIEW_COMMAND: &str = "veryl-ls.previewSystemVerilog";
+pub const SHOW_REFERENCES_COMMAND: &str = "editor.action.showReferences";
+
+fn module_declaration(arg: &DescriptionGroup) -> Vec<&ModuleDeclaration> {
+    match &*arg.description_group_group {
+        DescriptionGroupGroup::LBraceDescriptionGroupGroupListRBrace(x) => x
+            .description_group_group_list
+            .iter()
+            .flat_map(|x| module_declaration(&x.description_group))
+            .collect(),
+        DescriptionGroupGroup::DescriptionItem(x) => match &*x.description_item {
+            DescriptionItem::ModuleDeclaration(x) => vec![&*x.module_declaration],
+            _ => Vec::new(),
+        },
+    }
+}
+
+fn token_range(token: &Token) -> Range {
+    let line = token.line - 1;
+    let column = token.column - 1;
+    Range::new(
+        Position::new(line, column),
+        Position::new(line, column + token.length),
+    )
+}
+
+fn to_location(token: &Token) -> Option<Location> {
+    let uri = Url::from_file_path(token.source.to_string()).ok()?;
+    Some(Location::new(uri, token_range(token)))
+}
+
+/// Returns code lenses showing references and SystemVerilog preview over modules.
+pub fn code_lenses(veryl: &Veryl, url: &Url) -> Vec<CodeLens> {
+    let mut ret = Vec::new();
+
+    for x in &veryl.veryl_list {
+        for module in module_declaration(&x.description_group) {
+            let token = module.identifier.identifier_token.token;
+            let range = token_range(&token);
+
+            let references: Vec<_> = namespace_table::get(token.id)
+                .and_then(|namespace| {
+                    let path = SymbolPath::new(&[token.text]);
+                    symbol_table::resolve((&path, &namespace)).ok()
+                })
+                .map(|x| x.found.references.iter().filter_map(to_location).collect())
+                .unwrap_or_default();
+
+            let title = if references.len() == 1 {
+                "1 reference".to_string()
+            } else {
+                format!("{} references", references.len())
+            };
+            ret.push(CodeLens {
+                range,
+                command: Some(Command {
+                    title,
+                    command: SHOW_REFERENCES_COMMAND.to_string(),
+                    arguments: Some(vec![json!(url), json!(range.start), json!(references)]),
+                }),
+                data: None,
+            });
+
+            ret.push(CodeLens {
+                range,
+                command: Some(Command {
+                    title: "Preview generated SystemVerilog".to_string(),
+                    command: PREVIEW_COMMAND.to_string(),
+                    arguments: Some(vec![json!(url), json!(token.to_string())]),
+                }),
+                data: None,
+            });
+        }
+    }
+
+    ret
+}
+
+/// Emits SystemVerilog of the specified module only.
+pub fn emit_module(
+    metadata: &Metadata,
+    path: &Path,
+    veryl: &Veryl,
+    module: &str,
+) -> Option<String> {
+    let mut metadata = metadata.clone();
+    metadata.build.sourcemap_target = SourceMapTarget::None;
+
+    let dst = path.with_extension("sv");
+    let map = path.with_extension("sv.map");
+    let mut emitter = Emitter::new(&metadata, path, &dst, &map);
+    if emitter.emit_only(&metadata.project.name, veryl, &[module]) {
+        Some(emitter.as_str().to_string())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types::{DocumentLink, Position, Range, Url};
+use veryl_metadata::{Lockfile, Metadata};
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::Token;
+use veryl_parser::veryl_walker::{Handler, HandlerPoint, VerylWalker};
+use veryl_parser::ParolError;
+
+pub struct DependencyLink {
+    pub name: String,
+    pub target: Url,
+    pub tooltip: String,
+}
+
+/// Gathers dependencies which are locked and checked out.
+pub fn dependency_links(metadata: &Metadata) -> Vec<DependencyLink> {
+    let mut ret = Vec::new();
+    let Ok(lockfile) = Lockfile::load(&metadata.lockfile_path) else {
+        return ret;
+    };
+
+    for locks in lockfile.lock_table.values() {
+        for lock in locks {
+            let Ok(path) = Lockfile::dependency_path(&lock.url, &lock.revision) else {
+                continue;
+            };
+            let toml = path.join("Veryl.toml");
+            if !toml.exists() {
+                continue;
+            }
+            if let Ok(target) = Url::from_file_path(toml) {
+                ret.push(DependencyLink {
+                    name: lock.name.clone(),
+                    target,
+                    tooltip: format!("{} @ {}", lock.url, lock.version),
+                });
+            }
+        }
+    }
+    ret
+}
+
+fn token_range(token: &Token) -> Range {
+    let line = token.line - 1;
+    let column = token.column - 1;
+    Range::new(
+        Position::new(line, column),
+        Position::new(line, column + token.length),
+    )
+}
+
+struct LinkHandler<'a> {
+    point: HandlerPoint,
+    base: PathBuf,
+    dependencies: &'a [DependencyLink],
+    links: Vec<DocumentLink>,
+}
+
+impl Handler for LinkHandler<'_> {
+    fn set_point(&mut self, p: HandlerPoint) {
+        self.point = p;
+    }
+}
+
+impl VerylGrammarTrait for LinkHandler<'_> {
+    fn scoped_identifier(&mut self, arg: &ScopedIdentifier) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            // Dependency name is placed at the head of path like `dependency::PackageA`
+            if arg.scoped_identifier_list.is_empty() {
+                return Ok(());
+            }
+            let token = arg.identifier().token;
+            let name = token.to_string();
+            if let Some(dependency) = self.dependencies.iter().find(|x| x.name == name) {
+                self.links.push(DocumentLink {
+                    range: token_range(&token),
+                    target: Some(dependency.target.clone()),
+                    tooltip: Some(dependency.tooltip.clone()),
+                    data: None,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn include_declaration(&mut self, arg: &IncludeDeclaration) -> Result<(), ParolError> {
+        if let HandlerPoint::Before = self.point {
+            let token = arg.string_literal.string_literal_token.token;
+            let text = token.to_string();
+            let path = text.trim_start_matches('"').trim_end_matches('"');
+            let path = self.base.join(path);
+            if path.exists() {
+                if let Ok(target) = Url::from_file_path(&path) {
+                    self.links.push(DocumentLink {
+                        range: token_range(&token),
+                        target: Some(target),
+                        tooltip: Some(path.to_string_lossy().to_string()),
+                        data: None,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct LinkCollector<'a> {
+    handler: LinkHandler<'a>,
+}
+
+impl VerylWalker for LinkCollector<'_> {
+    fn get_handlers(&mut self) -> Option<Vec<&mut dyn Handler>> {
+        Some(vec![&mut self.handler as &mut dyn Handler])
+    }
+}
+
+/// Returns links of dependency references and included files in `path`.
+pub fn document_links(
+    veryl: &Veryl,
+    path: &Path,
+    dependencies: &[DependencyLink],
+) -> Vec<DocumentLink> {
+    let base = path.parent().map(|x| x.to_path_buf()).unwrap_or_default();
+    let mut collector = LinkCollector {
+        handler: LinkHandler {
+            point: HandlerPoint::Before,
+            base,
+            dependencies,
+            links: Vec::new(),
+        },
+    };
+    collector.veryl(veryl);
+    collector.handler.links
+}
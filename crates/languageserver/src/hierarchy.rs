@@ -0,0 +1,178 @@
+use serde_json::Value;
+use tower_lsp::lsp_types::*;
+use veryl_analyzer::call_graph;
+use veryl_analyzer::instance_graph::InstanceGraph;
+use veryl_analyzer::symbol::SymbolKind as VerylSymbolKind;
+use veryl_analyzer::symbol::{Symbol, SymbolId};
+use veryl_analyzer::symbol_table;
+use veryl_parser::veryl_token::{Token, TokenRange};
+
+fn token_range(token: &Token) -> Range {
+    let line = token.line - 1;
+    let column = token.column - 1;
+    Range::new(
+        Position::new(line, column),
+        Position::new(line, column + token.length),
+    )
+}
+
+fn declaration_range(symbol: &Symbol) -> Range {
+    let range: Option<&TokenRange> = match &symbol.kind {
+        VerylSymbolKind::Module(x) => Some(&x.range),
+        VerylSymbolKind::Interface(x) => Some(&x.range),
+        VerylSymbolKind::Function(x) => Some(&x.range),
+        _ => None,
+    };
+    if let Some(range) = range {
+        let beg = token_range(&range.beg);
+        let end = token_range(&range.end);
+        Range::new(beg.start, end.end)
+    } else {
+        token_range(&symbol.token)
+    }
+}
+
+fn item_parts(symbol: &Symbol) -> Option<(String, SymbolKind, String, Url, Range, Range)> {
+    let kind = match symbol.kind {
+        VerylSymbolKind::Module(_) => SymbolKind::MODULE,
+        VerylSymbolKind::Interface(_) => SymbolKind::INTERFACE,
+        VerylSymbolKind::Function(_) => SymbolKind::FUNCTION,
+        _ => return None,
+    };
+    let uri = Url::from_file_path(symbol.token.source.to_string()).ok()?;
+    Some((
+        symbol.token.to_string(),
+        kind,
+        symbol.namespace.to_string(),
+        uri,
+        declaration_range(symbol),
+        token_range(&symbol.token),
+    ))
+}
+
+fn symbol_id(data: &Option<Value>) -> Option<SymbolId> {
+    data.as_ref()
+        .and_then(|x| x.as_u64())
+        .map(|x| SymbolId(x as usize))
+}
+
+pub fn call_hierarchy_item(symbol: &Symbol) -> Option<CallHierarchyItem> {
+    if !matches!(
+        symbol.kind,
+        VerylSymbolKind::Function(_) | VerylSymbolKind::Module(_) | VerylSymbolKind::Interface(_)
+    ) {
+        return None;
+    }
+
+    let (name, kind, detail, uri, range, selection_range) = item_parts(symbol)?;
+    Some(CallHierarchyItem {
+        name,
+        kind,
+        tags: None,
+        detail: Some(detail),
+        uri,
+        range,
+        selection_range,
+        data: Some(Value::from(symbol.id.0)),
+    })
+}
+
+pub fn type_hierarchy_item(symbol: &Symbol) -> Option<TypeHierarchyItem> {
+    if !matches!(
+        symbol.kind,
+        VerylSymbolKind::Module(_) | VerylSymbolKind::Interface(_)
+    ) {
+        return None;
+    }
+
+    let (name, kind, detail, uri, range, selection_range) = item_parts(symbol)?;
+    Some(TypeHierarchyItem {
+        name,
+        kind,
+        tags: None,
+        detail: Some(detail),
+        uri,
+        range,
+        selection_range,
+        data: Some(Value::from(symbol.id.0)),
+    })
+}
+
+/// Groups tokens by symbol in the order of appearance
+fn group(items: Vec<(SymbolId, Token)>) -> Vec<(Symbol, Vec<Range>)> {
+    let mut ret: Vec<(SymbolId, Vec<Range>)> = Vec::new();
+    for (id, token) in items {
+        if let Some(x) = ret.iter_mut().find(|x| x.0 == id) {
+            x.1.push(token_range(&token));
+        } else {
+            ret.push((id, vec![token_range(&token)]));
+        }
+    }
+    ret.into_iter()
+        .filter_map(|(id, ranges)| symbol_table::get(id).map(|x| (x, ranges)))
+        .collect()
+}
+
+pub fn incoming_calls(item: &CallHierarchyItem) -> Option<Vec<CallHierarchyIncomingCall>> {
+    let id = symbol_id(&item.data)?;
+    let calls = call_graph::get_callers(id)
+        .into_iter()
+        .map(|x| (x.caller, x.token))
+        .collect();
+
+    let ret = group(calls)
+        .into_iter()
+        .filter_map(|(symbol, from_ranges)| {
+            call_hierarchy_item(&symbol).map(|from| CallHierarchyIncomingCall { from, from_ranges })
+        })
+        .collect();
+    Some(ret)
+}
+
+pub fn outgoing_calls(item: &CallHierarchyItem) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let id = symbol_id(&item.data)?;
+    let calls = call_graph::get_callees(id)
+        .into_iter()
+        .map(|x| (x.callee, x.token))
+        .collect();
+
+    let ret = group(calls)
+        .into_iter()
+        .filter_map(|(symbol, from_ranges)| {
+            call_hierarchy_item(&symbol).map(|to| CallHierarchyOutgoingCall { to, from_ranges })
+        })
+        .collect();
+    Some(ret)
+}
+
+/// Returns modules and interfaces instantiating the given item
+pub fn supertypes(item: &TypeHierarchyItem) -> Option<Vec<TypeHierarchyItem>> {
+    let id = symbol_id(&item.data)?;
+    let instances = InstanceGraph::new()
+        .get_parents(id)
+        .into_iter()
+        .map(|x| (x.parent, x.token))
+        .collect();
+
+    let ret = group(instances)
+        .iter()
+        .filter_map(|(symbol, _)| type_hierarchy_item(symbol))
+        .collect();
+    Some(ret)
+}
+
+/// Returns modules and interfaces instantiated in the given item
+pub fn subtypes(item: &TypeHierarchyItem) -> Option<Vec<TypeHierarchyItem>> {
+    let id = symbol_id(&item.data)?;
+    let instances = InstanceGraph::new()
+        .get_children(id)
+        .into_iter()
+        .map(|x| (x.child, x.token))
+        .collect();
+
+    let ret = group(instances)
+        .iter()
+        .filter_map(|(symbol, _)| type_hierarchy_item(symbol))
+        .collect();
+    Some(ret)
+}
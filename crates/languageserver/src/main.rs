@@ -1,8 +1,15 @@
 #![recursion_limit = "256"]
 
 mod backend;
+mod code_action;
+mod code_lens;
+mod document_link;
+mod hierarchy;
 mod keyword;
+mod on_type_formatting;
 mod server;
+mod signature_help;
+mod syntax_range;
 #[cfg(test)]
 mod tests;
 
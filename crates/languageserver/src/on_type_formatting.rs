@@ -0,0 +1,132 @@
+use tower_lsp::lsp_types::*;
+use veryl_formatter::Formatter;
+use veryl_metadata::Metadata;
+use veryl_parser::resource_table;
+use veryl_parser::veryl_token::VerylToken;
+use veryl_parser::veryl_walker::VerylWalker;
+use veryl_parser::Parser;
+
+pub const ON_TYPE_FORMATTING_TRIGGER: &str = "}";
+pub const ON_TYPE_FORMATTING_MORE_TRIGGER: &[&str] = &[";", "\n"];
+
+/// Placeholder to get the indent of blank line from formatter
+const PLACEHOLDER: &str = "//";
+
+#[derive(Default)]
+struct TokenCollector {
+    /// 0-origin line and text of tokens
+    tokens: Vec<(usize, String)>,
+}
+
+impl VerylWalker for TokenCollector {
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        let text = resource_table::get_str_value(arg.token.text).unwrap();
+        self.tokens.push((arg.token.line as usize - 1, text));
+    }
+}
+
+impl TokenCollector {
+    fn last_in_line(&self, line: usize, text: &str) -> Option<usize> {
+        self.tokens
+            .iter()
+            .rposition(|(x, y)| *x == line && y == text)
+    }
+
+    /// Returns the first line of the block closed by `}` in `line`
+    fn block_begin(&self, line: usize) -> Option<usize> {
+        let close = self.last_in_line(line, "}")?;
+        let mut depth = 0;
+        for (x, text) in self.tokens[..close].iter().rev() {
+            match text.as_str() {
+                "}" => depth += 1,
+                "{" if depth == 0 => return Some(x + 1),
+                "{" => depth -= 1,
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Returns the first line of the statement terminated by `;` in `line`
+    fn statement_begin(&self, line: usize) -> Option<usize> {
+        let term = self.last_in_line(line, ";")?;
+        let begin = self.tokens[..term]
+            .iter()
+            .rposition(|(_, x)| matches!(x.as_str(), ";" | "{" | "}"))
+            .map(|x| x + 1)
+            .unwrap_or(0);
+        Some(self.tokens[begin].0)
+    }
+}
+
+fn indent_edit(line: &str, index: usize, indent: usize) -> Option<TextEdit> {
+    let current = line.len() - line.trim_start().len();
+    let indent = " ".repeat(indent);
+
+    if line[..current] == indent {
+        None
+    } else {
+        Some(TextEdit {
+            range: Range::new(
+                Position::new(index as u32, 0),
+                Position::new(index as u32, current as u32),
+            ),
+            new_text: indent,
+        })
+    }
+}
+
+/// Returns edits re-indenting lines around `line` (0-origin) after `ch` is typed.
+/// Indentation is taken from the result of formatter, and lines which can't be parsed are kept.
+pub fn on_type_formatting(text: &str, line: usize, ch: &str, metadata: &Metadata) -> Vec<TextEdit> {
+    let lines: Vec<_> = text.split('\n').map(|x| x.trim_end_matches('\r')).collect();
+
+    if line >= lines.len() {
+        return Vec::new();
+    }
+
+    // Blank line has no token, so a comment is put to be indented by formatter
+    let input = if lines[line].trim().is_empty() {
+        let mut input: Vec<_> = text.split('\n').map(|x| x.to_string()).collect();
+        let cr = if input[line].ends_with('\r') {
+            "\r"
+        } else {
+            ""
+        };
+        input[line] = format!("{}{PLACEHOLDER}{cr}", lines[line]);
+        input.join("\n")
+    } else {
+        text.to_string()
+    };
+
+    let (Some(parser), _) = Parser::parse_with_recovery(&input, &"") else {
+        return Vec::new();
+    };
+
+    let mut formatter = Formatter::new(metadata);
+    formatter.format(&parser.veryl);
+
+    let mut collector = TokenCollector::default();
+    collector.veryl(&parser.veryl);
+
+    let targets: Vec<usize> = match ch {
+        // Re-indent the whole block closed by the typed bracket
+        "}" => {
+            let begin = collector.block_begin(line).unwrap_or(line);
+            (begin.min(line)..=line).collect()
+        }
+        // Re-indent the first line of the terminated statement
+        ";" => vec![collector.statement_begin(line).unwrap_or(line)],
+        "\n" => vec![line],
+        _ => Vec::new(),
+    };
+
+    targets
+        .into_iter()
+        .filter(|&x| x == line || !lines[x].trim().is_empty())
+        .filter_map(|x| {
+            let indent = formatter.line_indent(x as u32 + 1)?;
+            indent_edit(lines[x], x, indent)
+        })
+        .collect()
+}
@@ -1,9 +1,19 @@
+use crate::code_action::code_actions;
+use crate::code_lens::{code_lenses, emit_module};
+use crate::document_link::{dependency_links, document_links};
+use crate::hierarchy::{
+    call_hierarchy_item, incoming_calls, outgoing_calls, subtypes, supertypes, type_hierarchy_item,
+};
 use crate::keyword::KEYWORDS;
+use crate::on_type_formatting::on_type_formatting;
+use crate::signature_help::{find_call, signature_help, CallContext};
+use crate::syntax_range::{folding_ranges, selection_ranges};
 use async_channel::{Receiver, Sender};
 use dashmap::DashMap;
 use futures::executor::block_on;
 use ropey::Rope;
-use std::collections::VecDeque;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
 use tower_lsp::lsp_types::*;
 use tower_lsp::Client;
@@ -11,9 +21,11 @@ use veryl_analyzer::namespace::Namespace;
 use veryl_analyzer::symbol::SymbolKind as VerylSymbolKind;
 use veryl_analyzer::symbol::{Symbol, TypeKind};
 use veryl_analyzer::symbol_path::SymbolPath;
-use veryl_analyzer::{namespace_table, symbol_table, Analyzer, AnalyzerError};
+use veryl_analyzer::{
+    call_graph, namespace_table, symbol_table, Analyzer, AnalyzerContext, AnalyzerError,
+};
 use veryl_formatter::Formatter;
-use veryl_metadata::Metadata;
+use veryl_metadata::{CancellationToken, Format, Lint, Metadata, MetadataError};
 use veryl_parser::veryl_token::Token;
 use veryl_parser::veryl_walker::VerylWalker;
 use veryl_parser::{resource_table, Finder, Parser, ParserError};
@@ -31,6 +43,10 @@ pub enum MsgToServer {
         version: i32,
     },
     DidChangeConfiguration(ServerConfigItem),
+    DidChangeMetadata {
+        url: Url,
+        deleted: bool,
+    },
     Completion {
         url: Url,
         line: usize,
@@ -61,6 +77,90 @@ pub enum MsgToServer {
     Formatting {
         url: Url,
     },
+    OnTypeFormatting {
+        url: Url,
+        line: usize,
+        ch: String,
+    },
+    SignatureHelp {
+        url: Url,
+        line: usize,
+        column: usize,
+    },
+    FoldingRange {
+        url: Url,
+    },
+    SelectionRange {
+        url: Url,
+        positions: Vec<Position>,
+    },
+    DocumentLink {
+        url: Url,
+    },
+    CodeLens {
+        url: Url,
+    },
+    CodeAction {
+        url: Url,
+        range: Range,
+    },
+    PreviewSystemVerilog {
+        url: Url,
+        module: String,
+    },
+    PrepareCallHierarchy {
+        url: Url,
+        line: usize,
+        column: usize,
+    },
+    IncomingCalls {
+        item: CallHierarchyItem,
+    },
+    OutgoingCalls {
+        item: CallHierarchyItem,
+    },
+    PrepareTypeHierarchy {
+        url: Url,
+        line: usize,
+        column: usize,
+    },
+    Supertypes {
+        item: TypeHierarchyItem,
+    },
+    Subtypes {
+        item: TypeHierarchyItem,
+    },
+}
+
+impl MsgToServer {
+    fn url(&self) -> Option<&Url> {
+        match self {
+            MsgToServer::DidOpen { url, .. }
+            | MsgToServer::DidChange { url, .. }
+            | MsgToServer::DidChangeMetadata { url, .. }
+            | MsgToServer::Completion { url, .. }
+            | MsgToServer::GotoDefinition { url, .. }
+            | MsgToServer::Hover { url, .. }
+            | MsgToServer::References { url, .. }
+            | MsgToServer::SemanticTokens { url }
+            | MsgToServer::Formatting { url }
+            | MsgToServer::OnTypeFormatting { url, .. }
+            | MsgToServer::SignatureHelp { url, .. }
+            | MsgToServer::FoldingRange { url }
+            | MsgToServer::SelectionRange { url, .. }
+            | MsgToServer::DocumentLink { url }
+            | MsgToServer::CodeLens { url }
+            | MsgToServer::CodeAction { url, .. }
+            | MsgToServer::PreviewSystemVerilog { url, .. }
+            | MsgToServer::PrepareCallHierarchy { url, .. }
+            | MsgToServer::PrepareTypeHierarchy { url, .. } => Some(url),
+            MsgToServer::IncomingCalls { item } | MsgToServer::OutgoingCalls { item } => {
+                Some(&item.uri)
+            }
+            MsgToServer::Supertypes { item } | MsgToServer::Subtypes { item } => Some(&item.uri),
+            MsgToServer::DidChangeConfiguration(_) | MsgToServer::Symbol { .. } => None,
+        }
+    }
 }
 
 pub enum MsgFromServer {
@@ -71,6 +171,20 @@ pub enum MsgFromServer {
     References(Vec<Location>),
     SemanticTokens(Option<SemanticTokensResult>),
     Formatting(Option<Vec<TextEdit>>),
+    OnTypeFormatting(Option<Vec<TextEdit>>),
+    SignatureHelp(Option<SignatureHelp>),
+    FoldingRange(Option<Vec<FoldingRange>>),
+    SelectionRange(Option<Vec<SelectionRange>>),
+    DocumentLink(Option<Vec<DocumentLink>>),
+    CodeLens(Option<Vec<CodeLens>>),
+    CodeAction(Option<CodeActionResponse>),
+    ExecuteCommand(Option<Value>),
+    PrepareCallHierarchy(Option<Vec<CallHierarchyItem>>),
+    IncomingCalls(Option<Vec<CallHierarchyIncomingCall>>),
+    OutgoingCalls(Option<Vec<CallHierarchyOutgoingCall>>),
+    PrepareTypeHierarchy(Option<Vec<TypeHierarchyItem>>),
+    Supertypes(Option<Vec<TypeHierarchyItem>>),
+    Subtypes(Option<Vec<TypeHierarchyItem>>),
 }
 
 pub struct BackgroundTask {
@@ -102,6 +216,7 @@ pub struct Server {
     rcv: Receiver<MsgToServer>,
     snd: Sender<MsgFromServer>,
     document_map: DashMap<PathBuf, Rope>,
+    version_map: DashMap<PathBuf, i32>,
     parser_map: DashMap<PathBuf, Parser>,
     metadata_map: DashMap<PathBuf, Metadata>,
     cache_dir: PathBuf,
@@ -110,15 +225,25 @@ pub struct Server {
     background_done: bool,
     config: ServerConfig,
     latest_change: Option<(Url, String, i32)>,
+    cancellation: CancellationToken,
+    pending_changes: Vec<(Url, String, i32)>,
+    contexts: HashMap<PathBuf, AnalyzerContext>,
+    project: Option<PathBuf>,
 }
 
 impl Server {
-    pub fn new(client: Client, rcv: Receiver<MsgToServer>, snd: Sender<MsgFromServer>) -> Self {
+    pub fn new(
+        client: Client,
+        rcv: Receiver<MsgToServer>,
+        snd: Sender<MsgFromServer>,
+        cancellation: CancellationToken,
+    ) -> Self {
         Server {
             client,
             rcv,
             snd,
             document_map: DashMap::new(),
+            version_map: DashMap::new(),
             parser_map: DashMap::new(),
             metadata_map: DashMap::new(),
             cache_dir: veryl_path::cache_path(),
@@ -127,12 +252,24 @@ impl Server {
             background_done: true,
             config: ServerConfig::default(),
             latest_change: None,
+            cancellation,
+            pending_changes: Vec::new(),
+            contexts: HashMap::new(),
+            project: None,
         }
     }
 
     pub fn serve(&mut self) {
         loop {
             if let Ok(msg) = self.rcv.recv_blocking() {
+                // Cancellation is requested before sending a new message
+                self.cancellation.reset();
+
+                if let Some(url) = msg.url() {
+                    let url = url.clone();
+                    self.enter_project(&url);
+                }
+
                 match msg {
                     MsgToServer::DidOpen { url, text, version } => {
                         self.did_open(&url, &text, version);
@@ -143,6 +280,9 @@ impl Server {
                         self.latest_change = Some((url, text, version));
                     }
                     MsgToServer::DidChangeConfiguration(x) => self.config.set(x),
+                    MsgToServer::DidChangeMetadata { url, deleted } => {
+                        self.did_change_metadata(&url, deleted)
+                    }
                     MsgToServer::Completion {
                         url,
                         line,
@@ -159,20 +299,82 @@ impl Server {
                     }
                     MsgToServer::SemanticTokens { url } => self.semantic_tokens(&url),
                     MsgToServer::Formatting { url } => self.formatting(&url),
+                    MsgToServer::OnTypeFormatting { url, line, ch } => {
+                        self.on_type_formatting(&url, line, &ch)
+                    }
+                    MsgToServer::SignatureHelp { url, line, column } => {
+                        self.signature_help(&url, line, column)
+                    }
+                    MsgToServer::FoldingRange { url } => self.folding_range(&url),
+                    MsgToServer::SelectionRange { url, positions } => {
+                        self.selection_range(&url, &positions)
+                    }
+                    MsgToServer::DocumentLink { url } => self.document_link(&url),
+                    MsgToServer::CodeLens { url } => self.code_lens(&url),
+                    MsgToServer::CodeAction { url, range } => self.code_action(&url, &range),
+                    MsgToServer::PreviewSystemVerilog { url, module } => {
+                        self.preview_system_verilog(&url, &module)
+                    }
+                    MsgToServer::PrepareCallHierarchy { url, line, column } => {
+                        let ret = self
+                            .find_symbol(&url, line, column)
+                            .and_then(|x| call_hierarchy_item(&x))
+                            .map(|x| vec![x]);
+                        self.send(MsgFromServer::PrepareCallHierarchy(ret));
+                    }
+                    MsgToServer::IncomingCalls { item } => {
+                        self.send(MsgFromServer::IncomingCalls(incoming_calls(&item)))
+                    }
+                    MsgToServer::OutgoingCalls { item } => {
+                        self.send(MsgFromServer::OutgoingCalls(outgoing_calls(&item)))
+                    }
+                    MsgToServer::PrepareTypeHierarchy { url, line, column } => {
+                        let ret = self
+                            .find_symbol(&url, line, column)
+                            .and_then(|x| type_hierarchy_item(&x))
+                            .map(|x| vec![x]);
+                        self.send(MsgFromServer::PrepareTypeHierarchy(ret));
+                    }
+                    MsgToServer::Supertypes { item } => {
+                        self.send(MsgFromServer::Supertypes(supertypes(&item)))
+                    }
+                    MsgToServer::Subtypes { item } => {
+                        self.send(MsgFromServer::Subtypes(subtypes(&item)))
+                    }
                 }
             }
 
+            // Retry changes aborted by cancellation if these are not superseded
+            while self.rcv.is_empty() {
+                let Some((url, text, version)) = self.pending_changes.pop() else {
+                    break;
+                };
+                self.cancellation.reset();
+                self.did_change(&url, &text, version);
+            }
+
             while self.rcv.is_empty() && !self.background_tasks.is_empty() {
                 if let Some(mut task) = self.background_tasks.pop_front() {
                     if !task.progress {
                         self.progress_start("background analyze");
                         task.progress = true;
                     }
+                    self.switch_context(&task.metadata.metadata_path);
                     if let Some(path) = task.paths.pop() {
-                        self.background_analyze(&path, &task.metadata);
-                        let pcnt = (task.total - task.paths.len()) * 100 / task.total;
+                        if !self.background_analyze(&path, &task.metadata) {
+                            task.paths.push(path);
+                            self.background_tasks.push_front(task);
+                            continue;
+                        }
+                        let count = task.total - task.paths.len();
+                        let pcnt = count * 100 / task.total;
                         self.progress_report(
-                            &format!("{}", path.src.file_name().unwrap().to_string_lossy()),
+                            &format!(
+                                "[{}/{}] {}",
+                                count,
+                                task.total,
+                                path.src.file_name().unwrap().to_string_lossy()
+                            ),
                             pcnt as u32,
                         );
                     }
@@ -198,24 +400,13 @@ impl Server {
 
 impl Server {
     fn did_open(&mut self, url: &Url, text: &str, version: i32) {
-        if let Some(mut metadata) = self.get_metadata(url) {
+        if let Some(metadata) = self.get_metadata(url) {
             self.background_done = false;
             self.on_change(&metadata.project.name, url, text, version);
 
             if let Ok(path) = url.to_file_path() {
                 if !path.starts_with(&self.cache_dir) {
-                    if let Ok(paths) = metadata.paths::<&str>(&[], true) {
-                        let total = paths.len();
-                        let task = BackgroundTask {
-                            metadata,
-                            paths,
-                            total,
-                            progress: false,
-                        };
-                        self.background_tasks.push_back(task);
-                    } else {
-                        self.background_done = true;
-                    }
+                    self.resolve_dependencies(metadata);
                 } else {
                     self.background_done = true;
                 }
@@ -235,6 +426,100 @@ impl Server {
         }
     }
 
+    fn resolve_dependencies(&mut self, mut metadata: Metadata) {
+        // Fetching dependencies may take a long time at the first time
+        let fetch = !metadata.dependencies.is_empty();
+        if fetch {
+            self.progress_start("resolve dependencies");
+        }
+        // Dependency fetch is not aborted by editing because it is restarted from scratch
+        let cancellation = std::mem::take(&mut metadata.cancellation);
+        let ret = metadata.paths::<&str>(&[], true);
+        metadata.cancellation = cancellation;
+        if fetch {
+            self.progress_done("resolve dependencies done");
+        }
+
+        match ret {
+            Ok(paths) => {
+                self.publish_metadata_diagnostics(&metadata.metadata_path, None);
+                let total = paths.len();
+                let task = BackgroundTask {
+                    metadata,
+                    paths,
+                    total,
+                    progress: false,
+                };
+                self.background_tasks.push_back(task);
+            }
+            Err(err) => {
+                self.publish_metadata_diagnostics(&metadata.metadata_path, Some(&err));
+                self.background_done = true;
+            }
+        }
+    }
+
+    fn did_change_metadata(&mut self, url: &Url, deleted: bool) {
+        let Ok(path) = url.to_file_path() else {
+            return;
+        };
+
+        if deleted {
+            self.metadata_map.retain(|_, x| x.metadata_path != path);
+            self.publish_metadata_diagnostics(&path, None);
+            return;
+        }
+
+        match Metadata::load(&path) {
+            Ok(mut metadata) => {
+                metadata.cancellation = self.cancellation.clone();
+
+                // Analyze the project from scratch because dependencies may be changed
+                self.switch_context(&metadata.metadata_path);
+                AnalyzerContext::new().swap();
+
+                for mut x in self.metadata_map.iter_mut() {
+                    if x.metadata_path == metadata.metadata_path {
+                        *x = metadata.clone();
+                    }
+                }
+
+                self.background_done = false;
+                self.resolve_dependencies(metadata.clone());
+
+                // Re-analyze opened documents because lint settings may be changed
+                let project_path = metadata.project_path();
+                let documents: Vec<_> = self
+                    .document_map
+                    .iter()
+                    .filter(|x| x.key().starts_with(&project_path))
+                    .map(|x| (x.key().clone(), x.value().to_string()))
+                    .collect();
+                for (path, text) in documents {
+                    if let Ok(url) = Url::from_file_path(&path) {
+                        let version = self.version_map.get(&path).map(|x| *x).unwrap_or(0);
+                        self.on_change(&metadata.project.name, &url, &text, version);
+                    }
+                }
+            }
+            // Keep the previous metadata until the error is fixed
+            Err(err) => self.publish_metadata_diagnostics(&path, Some(&err)),
+        }
+    }
+
+    /// Publishes failures of metadata loading or dependency resolution to Veryl.toml
+    fn publish_metadata_diagnostics(&self, path: &Path, err: Option<&MetadataError>) {
+        if let Ok(url) = Url::from_file_path(path) {
+            let diag = if let Some(err) = err {
+                let text = std::fs::read_to_string(path).unwrap_or_default();
+                vec![to_metadata_diag(err, &text)]
+            } else {
+                Vec::new()
+            };
+            block_on(self.client.publish_diagnostics(url, diag, None));
+        }
+    }
+
     fn get_line(&self, url: &Url, line: usize) -> Option<String> {
         if let Ok(path) = url.to_file_path() {
             if let Some(rope) = self.document_map.get(&path) {
@@ -292,36 +577,35 @@ impl Server {
             .unwrap();
     }
 
-    fn goto_definition(&mut self, url: &Url, line: usize, column: usize) {
-        if let Ok(path) = url.to_file_path() {
-            if let Some(parser) = self.parser_map.get(&path) {
-                let mut finder = Finder::new();
-                finder.line = line as u32;
-                finder.column = column as u32;
-                finder.veryl(&parser.veryl);
+    fn send(&self, msg: MsgFromServer) {
+        self.snd.send_blocking(msg).unwrap();
+    }
 
-                if let Some(token) = finder.token {
-                    if let Some(namespace) = namespace_table::get(token.id) {
-                        let path = if finder.token_group.is_empty() {
-                            SymbolPath::new(&[token.text])
-                        } else {
-                            SymbolPath::from(finder.token_group.as_slice())
-                        };
-                        if let Ok(symbol) = symbol_table::resolve((&path, &namespace)) {
-                            let location = to_location(&symbol.found.token);
-                            self.snd
-                                .send_blocking(MsgFromServer::GotoDefinition(Some(location)))
-                                .unwrap();
-                            return;
-                        }
-                    }
-                }
-            }
-        }
+    fn find_symbol(&self, url: &Url, line: usize, column: usize) -> Option<Symbol> {
+        let path = url.to_file_path().ok()?;
+        let parser = self.parser_map.get(&path)?;
+        let mut finder = Finder::new();
+        finder.line = line as u32;
+        finder.column = column as u32;
+        finder.veryl(&parser.veryl);
+
+        let token = finder.token?;
+        let namespace = namespace_table::get(token.id)?;
+        let path = if finder.token_group.is_empty() {
+            SymbolPath::new(&[token.text])
+        } else {
+            SymbolPath::from(finder.token_group.as_slice())
+        };
+        symbol_table::resolve((&path, &namespace))
+            .ok()
+            .map(|x| x.found)
+    }
 
-        self.snd
-            .send_blocking(MsgFromServer::GotoDefinition(None))
-            .unwrap();
+    fn goto_definition(&mut self, url: &Url, line: usize, column: usize) {
+        let location = self
+            .find_symbol(url, line, column)
+            .map(|x| to_location(&x.token));
+        self.send(MsgFromServer::GotoDefinition(location));
     }
 
     fn symbol(&mut self, query: &str) {
@@ -511,7 +795,7 @@ impl Server {
                 if let Some(rope) = self.document_map.get(&path) {
                     let line = rope.len_lines() as u32;
                     if let Some(parser) = self.parser_map.get(&path) {
-                        let mut formatter = Formatter::new(&metadata);
+                        let mut formatter = Formatter::new(&metadata.for_file(&path));
                         formatter.format(&parser.veryl);
 
                         let text_edit = TextEdit {
@@ -532,6 +816,142 @@ impl Server {
             .send_blocking(MsgFromServer::Formatting(None))
             .unwrap();
     }
+
+    fn on_type_formatting(&mut self, url: &Url, line: usize, ch: &str) {
+        if let Ok(path) = url.to_file_path() {
+            let metadata = self.get_metadata(url).unwrap_or_else(|| {
+                Metadata::create_default_toml("prj")
+                    .unwrap()
+                    .parse()
+                    .unwrap()
+            });
+            if let Some(rope) = self.document_map.get(&path) {
+                let text = rope.to_string();
+                let edits = on_type_formatting(&text, line - 1, ch, &metadata.for_file(&path));
+
+                self.snd
+                    .send_blocking(MsgFromServer::OnTypeFormatting(Some(edits)))
+                    .unwrap();
+                return;
+            }
+        }
+
+        self.snd
+            .send_blocking(MsgFromServer::OnTypeFormatting(None))
+            .unwrap();
+    }
+
+    fn signature_help(&mut self, url: &Url, line: usize, column: usize) {
+        let mut ret = None;
+        if let Ok(path) = url.to_file_path() {
+            if let Some(rope) = self.document_map.get(&path) {
+                let line_index = line - 1;
+                if line_index < rope.len_lines() {
+                    let pos = rope.line_to_char(line_index) + column - 1;
+                    let text = rope.slice(..pos.min(rope.len_chars())).to_string();
+                    if let Some(context) = find_call(&text) {
+                        ret = resolve_call(url, line, column, &context)
+                            .and_then(|x| signature_help(&x, &context));
+                    }
+                }
+            }
+        }
+
+        self.snd
+            .send_blocking(MsgFromServer::SignatureHelp(ret))
+            .unwrap();
+    }
+
+    fn folding_range(&mut self, url: &Url) {
+        let mut ret = None;
+        if let Ok(path) = url.to_file_path() {
+            if let Some(parser) = self.parser_map.get(&path) {
+                ret = Some(folding_ranges(&parser.veryl));
+            }
+        }
+
+        self.snd
+            .send_blocking(MsgFromServer::FoldingRange(ret))
+            .unwrap();
+    }
+
+    fn selection_range(&mut self, url: &Url, positions: &[Position]) {
+        let mut ret = None;
+        if let Ok(path) = url.to_file_path() {
+            if let Some(parser) = self.parser_map.get(&path) {
+                ret = Some(selection_ranges(&parser.veryl, positions));
+            }
+        }
+
+        self.snd
+            .send_blocking(MsgFromServer::SelectionRange(ret))
+            .unwrap();
+    }
+
+    fn document_link(&mut self, url: &Url) {
+        let mut ret = None;
+        if let Ok(path) = url.to_file_path() {
+            let dependencies = self
+                .get_metadata(url)
+                .map(|x| dependency_links(&x))
+                .unwrap_or_default();
+            if let Some(parser) = self.parser_map.get(&path) {
+                ret = Some(document_links(&parser.veryl, &path, &dependencies));
+            }
+        }
+
+        self.send(MsgFromServer::DocumentLink(ret));
+    }
+
+    fn code_lens(&mut self, url: &Url) {
+        let mut ret = None;
+        if let Ok(path) = url.to_file_path() {
+            if let Some(parser) = self.parser_map.get(&path) {
+                ret = Some(code_lenses(&parser.veryl, url));
+            }
+        }
+
+        self.send(MsgFromServer::CodeLens(ret));
+    }
+
+    fn code_action(&mut self, url: &Url, range: &Range) {
+        let mut ret = None;
+        if let Ok(path) = url.to_file_path() {
+            let indent_width = self
+                .get_metadata(url)
+                .map(|x| x.format.indent_width)
+                .unwrap_or(Format::default().indent_width);
+            if let (Some(parser), Some(rope)) =
+                (self.parser_map.get(&path), self.document_map.get(&path))
+            {
+                ret = Some(code_actions(&parser.veryl, &rope, url, range, indent_width));
+            }
+        }
+
+        self.send(MsgFromServer::CodeAction(ret));
+    }
+
+    fn preview_system_verilog(&mut self, url: &Url, module: &str) {
+        let mut ret = None;
+        if let Ok(path) = url.to_file_path() {
+            if let Some(metadata) = self.get_metadata(url) {
+                if let Some(parser) = self.parser_map.get(&path) {
+                    if let Some(text) = emit_module(&metadata, &path, &parser.veryl, module) {
+                        // The client shows the text as a virtual document
+                        let dir = path.parent().unwrap_or(&path);
+                        let uri = format!("veryl-preview:{}/{module}.sv", dir.to_string_lossy());
+                        ret = Some(json!({
+                            "uri": uri,
+                            "languageId": "systemverilog",
+                            "text": text,
+                        }));
+                    }
+                }
+            }
+        }
+
+        self.send(MsgFromServer::ExecuteCommand(ret));
+    }
 }
 
 impl Server {
@@ -593,19 +1013,21 @@ impl Server {
         );
     }
 
-    fn background_analyze(&self, path: &PathSet, metadata: &Metadata) {
+    /// Returns false if the analysis is aborted by cancellation
+    fn background_analyze(&self, path: &PathSet, metadata: &Metadata) -> bool {
         let src = path.src.clone();
         if let Ok(text) = std::fs::read_to_string(&src) {
             if self.document_map.contains_key(&src) {
-                return;
+                return true;
             }
-            if let Ok(x) = Parser::parse(&text, &src) {
-                if let Some(src) = resource_table::get_path_id(&src) {
-                    symbol_table::drop(src);
-                    namespace_table::drop(src);
-                }
-                let analyzer = Analyzer::new(metadata);
+            if let (Some(x), _) = Parser::parse_with_recovery(&text, &src) {
+                drop_tables(&src);
+                let analyzer = Analyzer::new(&metadata.for_file(&src));
                 let _ = analyzer.analyze_pass1(&path.prj, &text, &src, &x.veryl);
+                if self.cancellation.is_cancelled() {
+                    drop_tables(&src);
+                    return false;
+                }
 
                 block_on(self.client.log_message(
                     MessageType::INFO,
@@ -613,6 +1035,36 @@ impl Server {
                 ));
             }
         }
+        true
+    }
+
+    /// Switches the analyzer context to the project including `url`.
+    /// Files of dependencies are analyzed in the context of the dependent project.
+    fn enter_project(&mut self, url: &Url) {
+        let Ok(path) = url.to_file_path() else {
+            return;
+        };
+        if path.starts_with(&self.cache_dir) {
+            return;
+        }
+        let project = self
+            .get_metadata(url)
+            .map(|x| x.metadata_path)
+            .unwrap_or_default();
+        self.switch_context(&project);
+    }
+
+    fn switch_context(&mut self, project: &Path) {
+        if self.project.as_deref() == Some(project) {
+            return;
+        }
+
+        let mut context = self.contexts.remove(project).unwrap_or_default();
+        context.swap();
+        if let Some(prev) = self.project.take() {
+            self.contexts.insert(prev, context);
+        }
+        self.project = Some(project.to_path_buf());
     }
 
     fn get_metadata(&mut self, url: &Url) -> Option<Metadata> {
@@ -620,7 +1072,8 @@ impl Server {
             if let Some(metadata) = self.metadata_map.get(&path) {
                 return Some(metadata.to_owned());
             } else if let Ok(metadata_path) = Metadata::search_from(&path) {
-                if let Ok(metadata) = Metadata::load(metadata_path) {
+                if let Ok(mut metadata) = Metadata::load(metadata_path) {
+                    metadata.cancellation = self.cancellation.clone();
                     self.metadata_map.insert(path, metadata.clone());
                     return Some(metadata);
                 }
@@ -630,6 +1083,8 @@ impl Server {
     }
 
     fn on_change(&mut self, prj: &str, url: &Url, text: &str, version: i32) {
+        self.enter_project(url);
+
         if let Ok(path) = url.to_file_path() {
             let rope = Rope::from_str(text);
 
@@ -638,20 +1093,35 @@ impl Server {
             }
 
             if let Some(metadata) = self.get_metadata(url) {
-                let diag = match Parser::parse(text, &path) {
-                    Ok(x) => {
-                        if let Some(path) =
-                            resource_table::get_path_id(Path::new(&path).to_path_buf())
-                        {
-                            symbol_table::drop(path);
-                            namespace_table::drop(path);
-                        }
+                let metadata = metadata.for_file(&path).into_owned();
+                self.pending_changes.retain(|(x, _, _)| x != url);
+
+                let (parser, syntax_errors) = Parser::parse_with_recovery(text, &path);
+                let mut diag: Vec<_> = syntax_errors
+                    .into_iter()
+                    .map(|x| to_diag(x.into(), &rope, url, &metadata.lint))
+                    .collect();
+
+                match parser {
+                    Some(x) => {
+                        drop_tables(&path);
                         let analyzer = Analyzer::new(&metadata);
                         let mut errors = analyzer.analyze_pass1(prj, text, &path, &x.veryl);
                         Analyzer::analyze_post_pass1();
                         errors.append(&mut analyzer.analyze_pass2(prj, text, &path, &x.veryl));
                         errors.append(&mut analyzer.analyze_pass3(prj, text, &path, &x.veryl));
-                        let ret: Vec<_> = errors
+
+                        // Discard the partial result and retry after the following messages
+                        if self.cancellation.is_cancelled() {
+                            drop_tables(&path);
+                            self.pending_changes
+                                .push((url.clone(), text.to_string(), version));
+                            self.document_map.insert(path.clone(), rope);
+                            self.version_map.insert(path.clone(), version);
+                            return;
+                        }
+
+                        let mut ret: Vec<_> = errors
                             .drain(0..)
                             .filter(|x| {
                                 // Filter errors caused by unresolve error until background completion
@@ -668,17 +1138,16 @@ impl Server {
                             })
                             .map(|x| {
                                 let x: miette::ErrReport = x.into();
-                                to_diag(x, &rope)
+                                to_diag(x, &rope, url, &metadata.lint)
                             })
                             .collect();
+                        diag.append(&mut ret);
                         self.parser_map.insert(path.clone(), x);
-                        ret
                     }
-                    Err(x) => {
+                    None => {
                         self.parser_map.remove(&path);
-                        vec![to_diag(x.into(), &rope)]
                     }
-                };
+                }
 
                 block_on(
                     self.client
@@ -692,22 +1161,43 @@ impl Server {
             }
 
             self.document_map.insert(path.clone(), rope);
+            self.version_map.insert(path.clone(), version);
         }
     }
 }
 
-fn to_diag(err: miette::ErrReport, rope: &Rope) -> Diagnostic {
+fn drop_tables(path: &Path) {
+    if let Some(path) = resource_table::get_path_id(path.to_path_buf()) {
+        symbol_table::drop(path);
+        namespace_table::drop(path);
+        call_graph::drop(path);
+    }
+}
+
+fn to_range(label: &miette::LabeledSpan, rope: &Rope) -> Range {
+    let line = rope.byte_to_line(label.offset());
+    let pos = label.offset() - rope.line_to_byte(line);
+    let line = line as u32;
+    let pos = pos as u32;
+    let len = label.len() as u32;
+    Range::new(Position::new(line, pos), Position::new(line, pos + len))
+}
+
+fn to_diag(err: miette::ErrReport, rope: &Rope, url: &Url, lint: &Lint) -> Diagnostic {
     let miette_diag: &dyn miette::Diagnostic = err.as_ref();
 
+    let mut related_information = Vec::new();
     let range = if let Some(mut labels) = miette_diag.labels() {
-        labels.next().map_or(Range::default(), |label| {
-            let line = rope.byte_to_line(label.offset());
-            let pos = label.offset() - rope.line_to_byte(line);
-            let line = line as u32;
-            let pos = pos as u32;
-            let len = label.len() as u32;
-            Range::new(Position::new(line, pos), Position::new(line, pos + len))
-        })
+        let range = labels
+            .next()
+            .map_or(Range::default(), |label| to_range(&label, rope));
+        for label in labels {
+            related_information.push(DiagnosticRelatedInformation {
+                location: Location::new(url.clone(), to_range(&label, rope)),
+                message: label.label().unwrap_or_default().to_string(),
+            });
+        }
+        range
     } else {
         Range::default()
     };
@@ -733,24 +1223,81 @@ fn to_diag(err: miette::ErrReport, rope: &Rope) -> Diagnostic {
         };
         (DiagnosticSeverity::ERROR, msg)
     } else if let Some(x) = err.downcast_ref::<AnalyzerError>() {
-        use miette::Diagnostic;
-        let (severity, text) = match x.severity() {
-            Some(miette::Severity::Error) => (DiagnosticSeverity::ERROR, "Error"),
-            Some(miette::Severity::Warning) => (DiagnosticSeverity::WARNING, "Warning"),
-            Some(miette::Severity::Advice) => (DiagnosticSeverity::HINT, "Hint"),
-            None => (DiagnosticSeverity::ERROR, "Error"),
+        let (severity, text) = match x.severity_with(lint) {
+            miette::Severity::Error => (DiagnosticSeverity::ERROR, "Error"),
+            miette::Severity::Warning => (DiagnosticSeverity::WARNING, "Warning"),
+            miette::Severity::Advice => (DiagnosticSeverity::HINT, "Hint"),
         };
         (severity, format!("Semantic {text}: {err}"))
     } else {
         (DiagnosticSeverity::ERROR, format!("Semantic Error: {err}"))
     };
 
+    let related_information = if related_information.is_empty() {
+        None
+    } else {
+        Some(related_information)
+    };
+
     Diagnostic::new(
         range,
         Some(severity),
         code,
         Some(String::from("veryl-ls")),
         message,
+        related_information,
+        None,
+    )
+}
+
+pub(crate) fn to_metadata_diag(err: &MetadataError, text: &str) -> Diagnostic {
+    let (message, span) = match err {
+        MetadataError::Deserialize(x) => (x.message().to_string(), x.span()),
+        MetadataError::Git(x) => (x.to_string(), None),
+        _ => (err.to_string(), None),
+    };
+
+    let lines: Vec<_> = text.lines().collect();
+    let range = if let Some(span) = span {
+        let rope = Rope::from_str(text);
+        let to_position = |x: usize| {
+            let x = x.min(rope.len_bytes());
+            let line = rope.byte_to_line(x);
+            let column = rope.byte_slice(rope.line_to_byte(line)..x).len_chars();
+            Position::new(line as u32, column as u32)
+        };
+        Range::new(to_position(span.start), to_position(span.end))
+    } else {
+        // Point the dependency entry which caused the error if possible
+        let line = lines
+            .iter()
+            .position(|x| {
+                x.trim_start()
+                    .strip_prefix('"')
+                    .and_then(|x| x.split_once('"'))
+                    .is_some_and(|(x, _)| message.contains(x))
+            })
+            .or_else(|| lines.iter().position(|x| x.trim() == "[dependencies]"))
+            .unwrap_or(0);
+        if let Some(x) = lines.get(line) {
+            let begin = x.len() - x.trim_start().len();
+            Range::new(
+                Position::new(line as u32, begin as u32),
+                Position::new(line as u32, x.trim_end().len() as u32),
+            )
+        } else {
+            Range::default()
+        }
+    };
+
+    let code = miette::Diagnostic::code(err).map(|d| NumberOrString::String(format!("{d}")));
+
+    Diagnostic::new(
+        range,
+        Some(DiagnosticSeverity::ERROR),
+        code,
+        Some(String::from("veryl-ls")),
+        format!("Metadata Error: {message}"),
         None,
         None,
     )
@@ -1029,33 +1576,25 @@ fn current_namespace(url: &Url, line: usize, column: usize) -> Option<Namespace>
     let mut ret_func = None;
     for symbol in symbol_table::get_all() {
         match symbol.kind {
-            VerylSymbolKind::Module(x) => {
-                if x.range.include(url, line as u32, column as u32) {
-                    let mut namespace = symbol.namespace;
-                    namespace.push(symbol.token.text);
-                    ret = Some(namespace);
-                }
+            VerylSymbolKind::Module(x) if x.range.include(url, line as u32, column as u32) => {
+                let mut namespace = symbol.namespace;
+                namespace.push(symbol.token.text);
+                ret = Some(namespace);
             }
-            VerylSymbolKind::Function(x) => {
-                if x.range.include(url, line as u32, column as u32) {
-                    let mut namespace = symbol.namespace;
-                    namespace.push(symbol.token.text);
-                    ret_func = Some(namespace);
-                }
+            VerylSymbolKind::Function(x) if x.range.include(url, line as u32, column as u32) => {
+                let mut namespace = symbol.namespace;
+                namespace.push(symbol.token.text);
+                ret_func = Some(namespace);
             }
-            VerylSymbolKind::Interface(x) => {
-                if x.range.include(url, line as u32, column as u32) {
-                    let mut namespace = symbol.namespace;
-                    namespace.push(symbol.token.text);
-                    ret = Some(namespace);
-                }
+            VerylSymbolKind::Interface(x) if x.range.include(url, line as u32, column as u32) => {
+                let mut namespace = symbol.namespace;
+                namespace.push(symbol.token.text);
+                ret = Some(namespace);
             }
-            VerylSymbolKind::Package(x) => {
-                if x.range.include(url, line as u32, column as u32) {
-                    let mut namespace = symbol.namespace;
-                    namespace.push(symbol.token.text);
-                    ret = Some(namespace);
-                }
+            VerylSymbolKind::Package(x) if x.range.include(url, line as u32, column as u32) => {
+                let mut namespace = symbol.namespace;
+                namespace.push(symbol.token.text);
+                ret = Some(namespace);
             }
             _ => (),
         }
@@ -1064,6 +1603,17 @@ fn current_namespace(url: &Url, line: usize, column: usize) -> Option<Namespace>
     ret_func.or(ret)
 }
 
+fn resolve_call(url: &Url, line: usize, column: usize, context: &CallContext) -> Option<Symbol> {
+    let namespace = current_namespace(url, line, column)?;
+    let mut path = Vec::new();
+    for x in &context.path {
+        path.push(resource_table::get_str_id(x)?);
+    }
+    symbol_table::resolve((&path, &namespace))
+        .ok()
+        .map(|x| x.found)
+}
+
 fn completion_keyword(line: usize, column: usize) -> Vec<CompletionItem> {
     let line = (line - 1) as u32;
     let character = (column - 2) as u32;
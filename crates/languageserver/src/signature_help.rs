@@ -0,0 +1,258 @@
+use tower_lsp::lsp_types::*;
+use veryl_analyzer::symbol::SymbolKind as VerylSymbolKind;
+use veryl_analyzer::symbol::{Parameter, Port, Symbol};
+use veryl_parser::veryl_walker::VerylWalker;
+use veryl_parser::Stringifier;
+
+pub const SIGNATURE_HELP_TRIGGER: &[&str] = &["(", ","];
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CallContext {
+    /// Path of the called function or the instantiated module (e.g. `PackageA::FuncA`)
+    pub path: Vec<String>,
+    /// Whether the cursor is in parameter list `#()`
+    pub is_param: bool,
+    /// Index of argument at the cursor
+    pub active: usize,
+    /// Name of argument at the cursor if it is connected by name
+    pub name: Option<String>,
+}
+
+struct Frame {
+    bracket: char,
+    open: usize,
+    active: usize,
+    arg_start: usize,
+}
+
+/// Finds the innermost unclosed call from the text before the cursor.
+pub fn find_call(text: &str) -> Option<CallContext> {
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut in_line_comment = false;
+    let mut in_block_comment = false;
+    let mut in_string = false;
+
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if in_line_comment {
+            in_line_comment = c != '\n';
+            continue;
+        }
+        if in_block_comment {
+            if c == '*' && matches!(chars.peek(), Some((_, '/'))) {
+                chars.next();
+                in_block_comment = false;
+            }
+            continue;
+        }
+        if in_string {
+            if c == '\\' {
+                chars.next();
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match c {
+            '/' if matches!(chars.peek(), Some((_, '/'))) => in_line_comment = true,
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                chars.next();
+                in_block_comment = true;
+            }
+            '"' => in_string = true,
+            '(' | '{' | '[' => stack.push(Frame {
+                bracket: c,
+                open: i,
+                active: 0,
+                arg_start: i + 1,
+            }),
+            ')' | '}' | ']' => {
+                stack.pop();
+            }
+            ',' => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.active += 1;
+                    frame.arg_start = i + 1;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let frame = stack.last().filter(|x| x.bracket == '(')?;
+
+    let mut callee = text[..frame.open].trim_end();
+    let is_param = callee.ends_with('#');
+    if is_param {
+        callee = callee[..callee.len() - 1].trim_end();
+    } else if callee.ends_with(')') {
+        // Skip parameter list of instance like `inst u: ModuleA #(...) (`
+        let mut depth = 0;
+        let open = callee.rfind(|c| {
+            match c {
+                ')' => depth += 1,
+                '(' => depth -= 1,
+                _ => (),
+            }
+            depth == 0
+        })?;
+        callee = callee[..open].trim_end().strip_suffix('#')?.trim_end();
+    }
+
+    let start = callee
+        .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == ':'))
+        .map(|x| x + 1)
+        .unwrap_or(0);
+    let callee = callee[start..].trim_start_matches(':');
+    if callee.is_empty() {
+        return None;
+    }
+    let path: Vec<_> = callee.split("::").map(|x| x.to_string()).collect();
+    if path.iter().any(|x| x.is_empty() || x.contains(':')) {
+        return None;
+    }
+
+    let arg = &text[frame.arg_start..];
+    let name = arg
+        .split_once(':')
+        .map(|(x, _)| x.trim().to_string())
+        .filter(|x| !x.is_empty());
+
+    Some(CallContext {
+        path,
+        is_param,
+        active: frame.active,
+        name,
+    })
+}
+
+struct SignatureBuilder {
+    label: String,
+    parameters: Vec<ParameterInformation>,
+    names: Vec<String>,
+}
+
+impl SignatureBuilder {
+    fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            parameters: Vec::new(),
+            names: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, name: &str, text: &str) {
+        if !self.parameters.is_empty() {
+            self.label.push_str(", ");
+        }
+        let begin = self.label.encode_utf16().count() as u32;
+        self.label.push_str(text);
+        let end = self.label.encode_utf16().count() as u32;
+
+        self.parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([begin, end]),
+            documentation: None,
+        });
+        self.names.push(name.to_string());
+    }
+
+    fn push_port(&mut self, port: &Port) {
+        let property = port.property();
+        let text = if let Some(x) = property.r#type {
+            format!("{}: {} {}", port.name, property.direction, x)
+        } else {
+            format!("{}: {}", port.name, property.direction)
+        };
+        self.push(&port.name.to_string(), &text);
+    }
+
+    fn push_parameter(&mut self, parameter: &Parameter) {
+        let property = parameter.property();
+        let mut stringifier = Stringifier::new();
+        stringifier.expression(&property.value);
+        let text = format!(
+            "{}: {} = {}",
+            parameter.name,
+            property.r#type,
+            stringifier.as_str()
+        );
+        self.push(&parameter.name.to_string(), &text);
+    }
+
+    fn finish(self, symbol: &Symbol, context: &CallContext) -> SignatureHelp {
+        let active = context
+            .name
+            .as_ref()
+            .and_then(|x| self.names.iter().position(|y| y == x))
+            .unwrap_or(context.active);
+
+        let documentation = if !symbol.doc_comment.is_empty() {
+            let content = MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: symbol.doc_comment.format(false),
+            };
+            Some(Documentation::MarkupContent(content))
+        } else {
+            None
+        };
+
+        let signature = SignatureInformation {
+            label: self.label,
+            documentation,
+            parameters: Some(self.parameters),
+            active_parameter: Some(active as u32),
+        };
+
+        SignatureHelp {
+            signatures: vec![signature],
+            active_signature: Some(0),
+            active_parameter: Some(active as u32),
+        }
+    }
+}
+
+pub fn signature_help(symbol: &Symbol, context: &CallContext) -> Option<SignatureHelp> {
+    let name = symbol.token.to_string();
+
+    let builder = match &symbol.kind {
+        VerylSymbolKind::Function(x) if !context.is_param => {
+            let mut builder = SignatureBuilder::new(&format!("{name}("));
+            for port in &x.ports {
+                builder.push_port(port);
+            }
+            builder.label.push(')');
+            if let Some(ref ret) = x.ret {
+                builder.label.push_str(&format!(" -> {ret}"));
+            }
+            builder
+        }
+        VerylSymbolKind::Module(x) if context.is_param => {
+            let mut builder = SignatureBuilder::new(&format!("{name} #("));
+            for parameter in &x.parameters {
+                builder.push_parameter(parameter);
+            }
+            builder.label.push(')');
+            builder
+        }
+        VerylSymbolKind::Module(x) => {
+            let mut builder = SignatureBuilder::new(&format!("{name} ("));
+            for port in &x.ports {
+                builder.push_port(port);
+            }
+            builder.label.push(')');
+            builder
+        }
+        VerylSymbolKind::Interface(x) if context.is_param => {
+            let mut builder = SignatureBuilder::new(&format!("{name} #("));
+            for parameter in &x.parameters {
+                builder.push_parameter(parameter);
+            }
+            builder.label.push(')');
+            builder
+        }
+        _ => return None,
+    };
+
+    Some(builder.finish(symbol, context))
+}
@@ -0,0 +1,261 @@
+use std::cmp::Reverse;
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind, Position, Range, SelectionRange};
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, VerylToken};
+use veryl_parser::veryl_walker::{Handler, HandlerPoint, VerylWalker};
+use veryl_parser::ParolError;
+
+#[derive(Default)]
+struct Bounds {
+    beg: Option<Token>,
+    end: Option<Token>,
+}
+
+impl VerylWalker for Bounds {
+    /// Semantic action for non-terminal 'VerylToken'
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        // Skip tokens which are not in source like the start token
+        if arg.token.line == 0 {
+            return;
+        }
+        if self.beg.is_none() {
+            self.beg = Some(arg.token);
+        }
+        self.end = Some(arg.token);
+    }
+}
+
+fn to_range(beg: &Token, end: &Token) -> Range {
+    let text = end.to_string();
+    let lines = text.matches('\n').count() as u32;
+    let end_column = if lines == 0 {
+        end.column - 1 + text.chars().count() as u32
+    } else {
+        text.rsplit('\n').next().unwrap().chars().count() as u32
+    };
+    Range::new(
+        Position::new(beg.line - 1, beg.column - 1),
+        Position::new(end.line - 1 + lines, end_column),
+    )
+}
+
+fn contains(range: &Range, position: &Position) -> bool {
+    range.start <= *position && *position <= range.end
+}
+
+#[derive(Default)]
+struct RangeHandler {
+    point: HandlerPoint,
+    /// Ranges of syntax nodes for selection range
+    nodes: Vec<Range>,
+    /// Ranges of blocks for folding range
+    blocks: Vec<Range>,
+}
+
+impl Handler for RangeHandler {
+    fn set_point(&mut self, p: HandlerPoint) {
+        self.point = p;
+    }
+}
+
+macro_rules! node {
+    ($name:ident, $typename:ty) => {
+        fn $name(&mut self, arg: &$typename) -> Result<(), ParolError> {
+            if let HandlerPoint::Before = self.point {
+                let mut bounds = Bounds::default();
+                bounds.$name(arg);
+                if let (Some(beg), Some(end)) = (bounds.beg, bounds.end) {
+                    self.nodes.push(to_range(&beg, &end));
+                }
+            }
+            Ok(())
+        }
+    };
+}
+
+macro_rules! block {
+    ($name:ident, $typename:ty) => {
+        fn $name(&mut self, arg: &$typename) -> Result<(), ParolError> {
+            if let HandlerPoint::Before = self.point {
+                let mut bounds = Bounds::default();
+                bounds.$name(arg);
+                if let (Some(beg), Some(end)) = (bounds.beg, bounds.end) {
+                    let range = to_range(&beg, &end);
+                    self.nodes.push(range);
+                    self.blocks.push(range);
+                }
+            }
+            Ok(())
+        }
+    };
+}
+
+impl VerylGrammarTrait for RangeHandler {
+    node!(identifier, Identifier);
+    node!(scoped_identifier, ScopedIdentifier);
+    node!(expression_identifier, ExpressionIdentifier);
+    node!(expression, Expression);
+    node!(factor, Factor);
+    node!(argument_item, ArgumentItem);
+    node!(select, Select);
+    node!(r#type, Type);
+    node!(scalar_type, ScalarType);
+    node!(array_type, ArrayType);
+    node!(statement, Statement);
+    node!(statement_block_item, StatementBlockItem);
+    node!(let_statement, LetStatement);
+    node!(identifier_statement, IdentifierStatement);
+    node!(return_statement, ReturnStatement);
+    node!(case_item, CaseItem);
+    node!(switch_item, SwitchItem);
+    node!(port_declaration_item, PortDeclarationItem);
+    node!(with_parameter_item, WithParameterItem);
+    node!(inst_parameter_item, InstParameterItem);
+    node!(inst_port_item, InstPortItem);
+    node!(struct_union_item, StructUnionItem);
+    node!(enum_item, EnumItem);
+    node!(modport_item, ModportItem);
+    node!(let_declaration, LetDeclaration);
+    node!(var_declaration, VarDeclaration);
+    node!(const_declaration, ConstDeclaration);
+    node!(type_def_declaration, TypeDefDeclaration);
+    node!(assign_declaration, AssignDeclaration);
+    node!(import_declaration, ImportDeclaration);
+    node!(module_group, ModuleGroup);
+    node!(interface_group, InterfaceGroup);
+    node!(package_group, PackageGroup);
+    node!(description_group, DescriptionGroup);
+    block!(statement_block, StatementBlock);
+    block!(if_statement, IfStatement);
+    block!(if_reset_statement, IfResetStatement);
+    block!(case_statement, CaseStatement);
+    block!(switch_statement, SwitchStatement);
+    block!(for_statement, ForStatement);
+    block!(port_declaration, PortDeclaration);
+    block!(with_parameter, WithParameter);
+    block!(inst_declaration, InstDeclaration);
+    block!(struct_union_declaration, StructUnionDeclaration);
+    block!(enum_declaration, EnumDeclaration);
+    block!(modport_declaration, ModportDeclaration);
+    block!(always_ff_declaration, AlwaysFfDeclaration);
+    block!(always_comb_declaration, AlwaysCombDeclaration);
+    block!(initial_declaration, InitialDeclaration);
+    block!(final_declaration, FinalDeclaration);
+    block!(function_declaration, FunctionDeclaration);
+    block!(generate_if_declaration, GenerateIfDeclaration);
+    block!(generate_for_declaration, GenerateForDeclaration);
+    block!(generate_named_block, GenerateNamedBlock);
+    block!(module_declaration, ModuleDeclaration);
+    block!(interface_declaration, InterfaceDeclaration);
+    block!(package_declaration, PackageDeclaration);
+    block!(embed_declaration, EmbedDeclaration);
+}
+
+#[derive(Default)]
+struct RangeCollector {
+    handler: RangeHandler,
+    comments: Vec<Token>,
+}
+
+impl VerylWalker for RangeCollector {
+    /// Semantic action for non-terminal 'VerylToken'
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        self.comments.extend(arg.comments.iter().copied());
+    }
+
+    fn get_handlers(&mut self) -> Option<Vec<&mut dyn Handler>> {
+        Some(vec![&mut self.handler as &mut dyn Handler])
+    }
+}
+
+fn collect(veryl: &Veryl) -> RangeCollector {
+    let mut collector = RangeCollector::default();
+    collector.veryl(veryl);
+    collector
+}
+
+pub fn folding_ranges(veryl: &Veryl) -> Vec<FoldingRange> {
+    let collector = collect(veryl);
+    let mut ret = Vec::new();
+
+    for block in &collector.handler.blocks {
+        // The last line including the closing bracket is kept visible
+        if block.end.line > block.start.line + 1 {
+            ret.push(FoldingRange {
+                start_line: block.start.line,
+                end_line: block.end.line - 1,
+                kind: Some(FoldingRangeKind::Region),
+                ..Default::default()
+            });
+        }
+    }
+
+    // Consecutive line comments and multi-line block comments are folded as a group
+    let mut group: Option<(u32, u32, bool)> = None;
+    for comment in &collector.comments {
+        let text = comment.to_string();
+        let is_block = text.starts_with("/*");
+        let start = comment.line - 1;
+        let end = start + text.trim_end().matches('\n').count() as u32;
+        group = match group {
+            Some((x, y, false)) if !is_block && y + 1 == start => Some((x, end, false)),
+            x => {
+                if let Some((x, y, _)) = x {
+                    push_comment(&mut ret, x, y);
+                }
+                Some((start, end, is_block))
+            }
+        };
+    }
+    if let Some((x, y, _)) = group {
+        push_comment(&mut ret, x, y);
+    }
+
+    // Only the outermost range is kept if some ranges start at the same line
+    ret.sort_by_key(|x| (x.start_line, Reverse(x.end_line)));
+    ret.dedup_by_key(|x| x.start_line);
+    ret
+}
+
+fn push_comment(ranges: &mut Vec<FoldingRange>, start_line: u32, end_line: u32) {
+    if end_line > start_line {
+        ranges.push(FoldingRange {
+            start_line,
+            end_line,
+            kind: Some(FoldingRangeKind::Comment),
+            ..Default::default()
+        });
+    }
+}
+
+pub fn selection_ranges(veryl: &Veryl, positions: &[Position]) -> Vec<SelectionRange> {
+    let collector = collect(veryl);
+
+    let mut ret = Vec::new();
+    for position in positions {
+        let mut ranges: Vec<_> = collector
+            .handler
+            .nodes
+            .iter()
+            .filter(|x| contains(x, position))
+            .copied()
+            .collect();
+        // Inner node is placed at first
+        ranges.sort_by_key(|x| (Reverse(x.start), x.end));
+        ranges.dedup();
+
+        let mut selection: Option<SelectionRange> = None;
+        for range in ranges.into_iter().rev() {
+            selection = Some(SelectionRange {
+                range,
+                parent: selection.map(Box::new),
+            });
+        }
+
+        ret.push(selection.unwrap_or(SelectionRange {
+            range: Range::new(*position, *position),
+            parent: None,
+        }));
+    }
+    ret
+}
@@ -1,12 +1,22 @@
+use crate::code_action::code_actions;
+use crate::code_lens::emit_module;
+use crate::document_link::{document_links, DependencyLink};
+use crate::on_type_formatting::on_type_formatting;
+use crate::server::to_metadata_diag;
+use crate::signature_help::find_call;
+use crate::syntax_range::{folding_ranges, selection_ranges};
 use crate::Backend;
 use serde_json::{json, Value};
 use std::collections::VecDeque;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
 use tower_lsp::jsonrpc::{Id, Request, Response};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
+use veryl_analyzer::Analyzer;
+use veryl_metadata::{Metadata, MetadataError};
 
 struct TestServer {
     req_stream: DuplexStream,
@@ -38,12 +48,13 @@ impl TestServer {
         format!("Content-Length: {}\r\n\r\n{}", payload.len(), payload)
     }
 
-    fn decode(text: &str) -> Vec<String> {
+    /// Returns `None` if the last message is incomplete
+    fn decode(text: &str) -> Option<Vec<String>> {
         let mut ret = Vec::new();
         let mut temp = text;
 
         while !temp.is_empty() {
-            let p = temp.find("\r\n\r\n").unwrap();
+            let p = temp.find("\r\n\r\n")?;
             let (header, body) = temp.split_at(p + 4);
             let len = header
                 .strip_prefix("Content-Length: ")
@@ -51,12 +62,33 @@ impl TestServer {
                 .strip_suffix("\r\n\r\n")
                 .unwrap();
             let len: usize = len.parse().unwrap();
+            if body.len() < len {
+                return None;
+            }
             let (body, rest) = body.split_at(len);
             ret.push(body.to_string());
             temp = rest;
         }
 
-        ret
+        Some(ret)
+    }
+
+    async fn read_messages(&mut self) {
+        let mut buf = Vec::new();
+        loop {
+            let mut chunk = vec![0; 1024];
+            let n = self.res_stream.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(x) = String::from_utf8(buf.clone())
+                .ok()
+                .and_then(|x| Self::decode(&x))
+            {
+                for x in x {
+                    self.responses.push_front(x);
+                }
+                return;
+            }
+        }
     }
 
     async fn send_request(&mut self, req: Request) {
@@ -74,12 +106,7 @@ impl TestServer {
 
     async fn recv_response(&mut self) -> Response {
         if self.responses.is_empty() {
-            let mut buf = vec![0; 1024];
-            let n = self.res_stream.read(&mut buf).await.unwrap();
-            let ret = String::from_utf8(buf[..n].to_vec()).unwrap();
-            for x in Self::decode(&ret) {
-                self.responses.push_front(x);
-            }
+            self.read_messages().await;
         }
         let res = self.responses.pop_back().unwrap();
         serde_json::from_str(&res).unwrap()
@@ -87,12 +114,7 @@ impl TestServer {
 
     async fn recv_notification(&mut self) -> Request {
         if self.responses.is_empty() {
-            let mut buf = vec![0; 1024];
-            let n = self.res_stream.read(&mut buf).await.unwrap();
-            let ret = String::from_utf8(buf[..n].to_vec()).unwrap();
-            for x in Self::decode(&ret) {
-                self.responses.push_front(x);
-            }
+            self.read_messages().await;
         }
         let res = self.responses.pop_back().unwrap();
         serde_json::from_str(&res).unwrap()
@@ -233,9 +255,9 @@ async fn progress() {
         let res = server.recv_notification().await;
         dbg!(&res);
         if res.method() == "$/progress" {
-            if res.params().unwrap()["value"]["kind"] == Value::from("end") {
+            if res.params().unwrap()["value"]["kind"] == "end" {
                 progress_finish = true;
-            } else if res.params().unwrap()["value"]["kind"] == Value::from("report") {
+            } else if res.params().unwrap()["value"]["kind"] == "report" {
                 percentage = res.params().unwrap()["value"]["percentage"]
                     .as_i64()
                     .unwrap();
@@ -244,3 +266,318 @@ async fn progress() {
     }
     assert_eq!(percentage, 100);
 }
+
+#[test]
+fn on_type_formatting_block() {
+    let mut metadata = Metadata::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+
+    let text = "module A {\nalways_comb {\na = 1;\n  }\n}";
+
+    let edits = on_type_formatting(text, 3, "}", &metadata);
+    assert_eq!(edits.len(), 2);
+    assert_eq!(edits[0].range.start.line, 2);
+    assert_eq!(edits[0].new_text, "        ");
+    assert_eq!(edits[1].range.start.line, 3);
+    assert_eq!(edits[1].range.end.character, 2);
+    assert_eq!(edits[1].new_text, "    ");
+
+    let edits = on_type_formatting(text, 2, ";", &metadata);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].range.start.line, 2);
+
+    // The first line of statement across lines is re-indented
+    let text = "module A {\nassign a =\n    1;\n}";
+    let edits = on_type_formatting(text, 2, ";", &metadata);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].range.start.line, 1);
+    assert_eq!(edits[0].new_text, "    ");
+
+    metadata.format.indent_width = 2;
+
+    let text = "module A (\n  a: input logic, // {\n) {\n\n}";
+    let edits = on_type_formatting(text, 1, "\n", &metadata);
+    assert!(edits.is_empty());
+    let edits = on_type_formatting(text, 3, "\n", &metadata);
+    assert_eq!(edits[0].new_text, "  ");
+
+    // Indent of case item is aligned by formatter
+    let text = "module A {\nalways_comb {\ncase a {\n0: b = 1;\n}\n}\n}";
+    let edits = on_type_formatting(text, 3, ";", &metadata);
+    assert_eq!(edits[0].new_text, "      ");
+}
+
+#[test]
+fn signature_help_call() {
+    let call = find_call("assign a = PackageA::FuncA(x, f(y), ").unwrap();
+    assert_eq!(call.path, vec!["PackageA", "FuncA"]);
+    assert!(!call.is_param);
+    assert_eq!(call.active, 2);
+
+    let call = find_call("inst u: ModuleA #(\n    N: 1,\n    M: ").unwrap();
+    assert_eq!(call.path, vec!["ModuleA"]);
+    assert!(call.is_param);
+    assert_eq!(call.name, Some("M".to_string()));
+
+    let call = find_call("inst u: ModuleA #(N: f(1)) (\n    a: x,\n    b").unwrap();
+    assert_eq!(call.path, vec!["ModuleA"]);
+    assert!(!call.is_param);
+    assert_eq!(call.active, 1);
+
+    assert!(find_call("module A { // FuncA(").is_none());
+    assert!(find_call("assign a = FuncA(x);").is_none());
+}
+
+#[test]
+fn folding_range() {
+    let text = r#"// comment a
+// comment b
+module A {
+    always_comb {
+        if x {
+            a = 1;
+        }
+    }
+}
+"#;
+    let parser = veryl_parser::Parser::parse(text, &"").unwrap();
+    let ranges: Vec<_> = folding_ranges(&parser.veryl)
+        .into_iter()
+        .map(|x| (x.start_line, x.end_line, x.kind))
+        .collect();
+    assert_eq!(
+        ranges,
+        vec![
+            (0, 1, Some(FoldingRangeKind::Comment)),
+            (2, 7, Some(FoldingRangeKind::Region)),
+            (3, 6, Some(FoldingRangeKind::Region)),
+            (4, 5, Some(FoldingRangeKind::Region)),
+        ]
+    );
+}
+
+#[test]
+fn selection_range() {
+    let text = "module A {\n    assign a = b + c;\n}\n";
+    let parser = veryl_parser::Parser::parse(text, &"").unwrap();
+    let ranges = selection_ranges(&parser.veryl, &[Position::new(1, 15)]);
+
+    let mut range = Some(&ranges[0]);
+    let mut texts = Vec::new();
+    while let Some(x) = range {
+        texts.push((x.range.start.character, x.range.end.character));
+        range = x.parent.as_deref();
+    }
+    assert_eq!(texts[0], (15, 16));
+    assert_eq!(texts[1], (15, 20));
+    assert_eq!(texts[2], (4, 21));
+}
+
+#[test]
+fn document_link() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let dir = tempdir.path();
+    std::fs::write(dir.join("inc.sv"), "").unwrap();
+    let path = dir.join("top.veryl");
+
+    let text = r#"include(inline, "inc.sv");
+include(inline, "missing.sv");
+module A {
+    import DepA::PkgA::*;
+    assign a = DepB::PkgB::X;
+}
+"#;
+    let parser = veryl_parser::Parser::parse(text, &path).unwrap();
+    let dependency = DependencyLink {
+        name: "DepA".to_string(),
+        target: Url::parse("file:///dep_a/Veryl.toml").unwrap(),
+        tooltip: "https://example.com/dep_a @ 0.1.0".to_string(),
+    };
+    let links: Vec<_> = document_links(&parser.veryl, &path, &[dependency])
+        .into_iter()
+        .map(|x| (x.range.start, x.target.unwrap()))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            (
+                Position::new(0, 16),
+                Url::from_file_path(dir.join("inc.sv")).unwrap()
+            ),
+            (
+                Position::new(3, 11),
+                Url::parse("file:///dep_a/Veryl.toml").unwrap()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn emit_module_only() {
+    let text = r#"module A {}
+module B {
+    inst u: A;
+}
+"#;
+    let metadata = Metadata::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+    let parser = veryl_parser::Parser::parse(text, &"").unwrap();
+    let analyzer = Analyzer::new(&metadata);
+    analyzer.analyze_pass1("prj", text, "", &parser.veryl);
+    Analyzer::analyze_post_pass1();
+    analyzer.analyze_pass2("prj", text, "", &parser.veryl);
+
+    let path = Path::new("test.veryl");
+    let sv = emit_module(&metadata, path, &parser.veryl, "B").unwrap();
+    assert!(sv.contains("module prj_B;"));
+    assert!(sv.contains("prj_A u ();"));
+    assert!(!sv.contains("module prj_A;"));
+    assert!(emit_module(&metadata, path, &parser.veryl, "C").is_none());
+}
+
+#[test]
+fn code_action_extract_module() {
+    let text = r#"module A (
+    i_a: input  logic,
+    o_b: output logic,
+) {
+    assign o_b = i_a;
+}
+"#;
+    let metadata = Metadata::from_str(&Metadata::create_default_toml("prj").unwrap()).unwrap();
+    let parser = veryl_parser::Parser::parse(text, &"").unwrap();
+    let analyzer = Analyzer::new(&metadata);
+    analyzer.analyze_pass1("prj", text, "", &parser.veryl);
+    Analyzer::analyze_post_pass1();
+    analyzer.analyze_pass2("prj", text, "", &parser.veryl);
+    analyzer.analyze_pass3("prj", text, "", &parser.veryl);
+
+    let rope = ropey::Rope::from_str(text);
+    let url = Url::parse("file:///test.veryl").unwrap();
+
+    let range = Range::new(Position::new(4, 0), Position::new(5, 0));
+    let ret = code_actions(&parser.veryl, &rope, &url, &range, 4);
+    let Some(CodeActionOrCommand::CodeAction(action)) = ret.first() else {
+        unreachable!();
+    };
+    let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&url];
+    assert_eq!(edits[0].range, range);
+    assert_eq!(
+        edits[0].new_text,
+        "    inst u_extracted_module: ExtractedModule (\n        i_a,\n        o_b,\n    );\n"
+    );
+    assert_eq!(edits[1].range.start, Position::new(5, 1));
+
+    // Selection including the module header can't be extracted
+    let range = Range::new(Position::new(0, 0), Position::new(5, 0));
+    assert!(code_actions(&parser.veryl, &rope, &url, &range, 4).is_empty());
+}
+
+#[test]
+fn metadata_diag_deserialize() {
+    // Column is counted by characters, not bytes
+    let text = "[project]\nname = \"\u{3042}\" version\n";
+    let err = Metadata::from_str(text).unwrap_err();
+    assert!(matches!(err, MetadataError::Deserialize(_)));
+
+    let diag = to_metadata_diag(&err, text);
+    assert_eq!(diag.range.start, Position::new(1, 11));
+    assert_eq!(diag.severity, Some(DiagnosticSeverity::ERROR));
+    assert!(diag.message.starts_with("Metadata Error: "));
+}
+
+#[test]
+fn metadata_diag_dependency() {
+    let text = r#"[project]
+name = "a"
+version = "0.1.0"
+
+[dependencies]
+"https://github.com/veryl-lang/sample" = {}
+"#;
+    let url = "https://github.com/veryl-lang/sample".parse().unwrap();
+
+    // The entry of the failed dependency
+    let diag = to_metadata_diag(&MetadataError::GitSpec(url), text);
+    assert_eq!(
+        diag.range,
+        Range::new(Position::new(5, 0), Position::new(5, 43))
+    );
+    assert_eq!(
+        diag.code,
+        Some(NumberOrString::String("MetadataError::GitSpec".to_string()))
+    );
+
+    // `[dependencies]` if the entry can't be found
+    let diag = to_metadata_diag(&MetadataError::NameConflict("b".to_string()), text);
+    assert_eq!(
+        diag.range,
+        Range::new(Position::new(4, 0), Position::new(4, 14))
+    );
+
+    // The first line if there is no dependency
+    let text = "[project]\nname = \"a\"\n";
+    let diag = to_metadata_diag(&MetadataError::NameConflict("b".to_string()), text);
+    assert_eq!(
+        diag.range,
+        Range::new(Position::new(0, 0), Position::new(0, 9))
+    );
+}
+
+#[tokio::test]
+async fn did_change_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Veryl.toml");
+    std::fs::write(&path, "[project]\nname = 1\n").unwrap();
+    let uri = Url::from_file_path(&path).unwrap();
+
+    let mut server = TestServer::new(Backend::new);
+
+    let req = build_initialize(1);
+    server.send_request(req).await;
+    let res = server.recv_response().await;
+    assert!(res.is_ok());
+
+    let req = build_initialized();
+    server.send_request(req).await;
+    let res = server.recv_notification().await;
+    assert_eq!(res.method(), "window/logMessage");
+
+    let params = DidChangeWatchedFilesParams {
+        changes: vec![FileEvent::new(uri.clone(), FileChangeType::CHANGED)],
+    };
+    let req = Request::build("workspace/didChangeWatchedFiles")
+        .params(json!(params))
+        .finish();
+    server.send_request(req).await;
+
+    let res = server.recv_notification().await;
+    assert_eq!(res.method(), "window/logMessage");
+
+    let res = server.recv_notification().await;
+    assert_eq!(res.method(), "textDocument/publishDiagnostics");
+    let params = res.params().unwrap();
+    assert_eq!(params["uri"], Value::from(uri.as_str()));
+    let diags = params["diagnostics"].as_array().unwrap();
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0]["code"], Value::from("MetadataError::Deserialize"));
+    assert_eq!(diags[0]["range"]["start"]["line"], Value::from(1));
+    assert_eq!(diags[0]["range"]["start"]["character"], Value::from(7));
+
+    // Deleting Veryl.toml clears the diagnostics
+    let params = DidChangeWatchedFilesParams {
+        changes: vec![FileEvent::new(uri.clone(), FileChangeType::DELETED)],
+    };
+    let req = Request::build("workspace/didChangeWatchedFiles")
+        .params(json!(params))
+        .finish();
+    server.send_request(req).await;
+
+    let res = server.recv_notification().await;
+    assert_eq!(res.method(), "window/logMessage");
+
+    let res = server.recv_notification().await;
+    assert_eq!(res.method(), "textDocument/publishDiagnostics");
+    assert!(res.params().unwrap()["diagnostics"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
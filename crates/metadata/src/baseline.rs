@@ -0,0 +1,58 @@
+use crate::MetadataError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Baseline {
+    #[serde(default)]
+    pub diagnostics: Vec<BaselineEntry>,
+}
+
+/// Diagnostic recorded in baseline.
+/// Line numbers are not recorded so that unrelated edits don't invalidate the entry.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BaselineEntry {
+    pub code: String,
+    pub path: String,
+    pub message: String,
+}
+
+impl Baseline {
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Self, MetadataError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_str(&text)
+    }
+
+    pub fn save<T: AsRef<Path>>(&self, path: T) -> Result<(), MetadataError> {
+        let mut text = String::new();
+        text.push_str("# This file is automatically @generated by Veryl.\n");
+        text.push_str("# Diagnostics listed here are suppressed by `veryl check`.\n");
+        text.push_str(&toml::to_string(&self)?);
+        fs::write(&path, text.as_bytes())?;
+        Ok(())
+    }
+
+    /// Removes an entry matched with the given one, and returns whether it is found.
+    /// Each entry suppresses only one diagnostic.
+    pub fn take(&mut self, entry: &BaselineEntry) -> bool {
+        if let Some(index) = self.diagnostics.iter().position(|x| x == entry) {
+            self.diagnostics.remove(index);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl FromStr for Baseline {
+    type Err = MetadataError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let baseline: Baseline = toml::from_str(s)?;
+        Ok(baseline)
+    }
+}
@@ -1,4 +1,6 @@
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -24,6 +26,8 @@ pub struct Build {
     pub implicit_parameter_types: Vec<BuiltinType>,
     #[serde(default)]
     pub omit_project_prefix: bool,
+    /// Prefix of identifiers in the project instead of `<project name>_`
+    pub project_prefix: Option<String>,
     #[serde(default)]
     pub strip_comments: bool,
     #[serde(default)]
@@ -32,6 +36,112 @@ pub struct Build {
     pub expand_inside_operation: bool,
     #[serde(default)]
     pub exclude_std: bool,
+    #[serde(default)]
+    pub protect: Protect,
+    #[serde(default)]
+    pub log_style: LogStyle,
+    #[serde(default)]
+    pub clock_gating_cell: Option<ClockGatingCell>,
+    #[serde(default)]
+    pub reset_synchronizer: Option<ResetSynchronizer>,
+    #[serde(default)]
+    pub memory_style: MemoryStyle,
+    #[serde(default)]
+    pub protocol_check: ProtocolCheck,
+    #[serde(default)]
+    pub header: Header,
+    /// Annotate emitted modules with resolved parameter values, port widths and source locations
+    #[serde(default)]
+    pub annotate: bool,
+    #[serde(default)]
+    pub type_generic_style: TypeGenericStyle,
+    #[serde(default)]
+    pub rename: Rename,
+    /// Glob patterns of Veryl sources relative to the project root.
+    /// All `.veryl` files under the project are sources if empty.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Glob patterns of files which are not treated as Veryl sources
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Additional targets emitted with their own settings
+    #[serde(default)]
+    pub targets: Vec<BuildTarget>,
+    /// Default values of parameters overridden for each module
+    #[serde(skip)]
+    pub param_overrides: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Build {
+    /// Settings of the build for `target`
+    pub fn with_target(&self, target: &BuildTarget) -> Build {
+        let mut ret = self.clone();
+        ret.target = Target::Directory {
+            path: target.path.clone(),
+        };
+        ret.targets.clear();
+        if target.prefix.is_some() {
+            ret.project_prefix = target.prefix.clone();
+            ret.omit_project_prefix = false;
+        }
+        if target.dialect == Dialect::Compatible {
+            ret.expand_inside_operation = true;
+            ret.log_style = LogStyle::Display;
+            ret.type_generic_style = TypeGenericStyle::Monomorphize;
+        }
+        if let Some(ref top) = target.top {
+            if !target.params.is_empty() {
+                ret.param_overrides
+                    .insert(top.clone(), target.params.clone());
+            }
+        }
+        ret
+    }
+}
+
+/// Provenance header prepended to emitted files
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Header {
+    #[serde(default)]
+    pub enable: bool,
+    /// Omit the timestamp to make emitted files reproducible
+    #[serde(default)]
+    pub omit_timestamp: bool,
+    /// Additional lines like copyright notice
+    #[serde(default)]
+    pub text: Vec<String>,
+}
+
+/// Emission target of `[[build.targets]]`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BuildTarget {
+    /// Output directory of SystemVerilog files and filelist
+    pub path: PathBuf,
+    #[serde(default)]
+    pub dialect: Dialect,
+    /// Prefix of identifiers in the project instead of `<project name>_`
+    pub prefix: Option<String>,
+    /// Macros defined in the filelist
+    #[serde(default)]
+    pub defines: BTreeMap<String, String>,
+    /// Only files used from the top module are listed in the filelist
+    pub top: Option<String>,
+    /// Default values of parameters of the top module
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Dialect {
+    /// SystemVerilog with the settings of `[build]`
+    #[default]
+    #[serde(rename = "systemverilog")]
+    SystemVerilog,
+    /// SystemVerilog avoiding constructs unsupported by some tools
+    #[serde(rename = "compatible")]
+    Compatible,
 }
 
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -67,6 +177,51 @@ pub enum FilelistType {
     Flgen,
 }
 
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LogStyle {
+    #[default]
+    #[serde(rename = "display")]
+    Display,
+    #[serde(rename = "severity")]
+    Severity,
+    #[serde(rename = "uvm")]
+    Uvm,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MemoryStyle {
+    #[default]
+    #[serde(rename = "generic")]
+    Generic,
+    #[serde(rename = "xilinx")]
+    Xilinx,
+    #[serde(rename = "intel")]
+    Intel,
+}
+
+/// Emission style of modules which have only type generic parameters
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TypeGenericStyle {
+    /// A module is emitted for each generic instance
+    #[default]
+    #[serde(rename = "monomorphize")]
+    Monomorphize,
+    /// A module with `parameter type` is emitted and instances override it
+    #[serde(rename = "parameter")]
+    Parameter,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProtocolCheck {
+    #[default]
+    #[serde(rename = "simulation")]
+    Simulation,
+    #[serde(rename = "always")]
+    Always,
+    #[serde(rename = "none")]
+    None,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
 pub enum Target {
@@ -110,3 +265,116 @@ pub enum BuiltinType {
     #[serde(rename = "type")]
     Type,
 }
+
+/// Integrated clock gating cell instantiated for `#[clock_en]`
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ClockGatingCell {
+    pub module: String,
+    pub clock_port: String,
+    pub enable_port: String,
+    pub gated_clock_port: String,
+}
+
+/// Synchronizer of asynchronous reset inserted for each clock domain
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ResetSynchronizer {
+    /// Number of flip-flops deasserting reset synchronously
+    #[serde(default = "default_reset_synchronizer_stages")]
+    pub stages: usize,
+}
+
+impl Default for ResetSynchronizer {
+    fn default() -> Self {
+        Self {
+            stages: default_reset_synchronizer_stages(),
+        }
+    }
+}
+
+fn default_reset_synchronizer_stages() -> usize {
+    2
+}
+
+/// Regex-based renaming of emitted module and port names
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rename {
+    #[serde(default)]
+    pub module: Vec<RenameRule>,
+    #[serde(default)]
+    pub port: Vec<RenameRule>,
+    /// Output path of cross-reference between the original and renamed names
+    pub report: Option<PathBuf>,
+}
+
+/// `pattern` is replaced by `replace` which can refer capture groups like `$1`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RenameRule {
+    #[serde(with = "serde_regex")]
+    pub pattern: Regex,
+    pub replace: String,
+}
+
+impl Rename {
+    pub fn module(&self, name: &str) -> Option<String> {
+        Self::apply(&self.module, name)
+    }
+
+    pub fn port(&self, name: &str) -> Option<String> {
+        Self::apply(&self.port, name)
+    }
+
+    /// The first matched rule is applied
+    fn apply(rules: &[RenameRule], name: &str) -> Option<String> {
+        let rule = rules.iter().find(|x| x.pattern.is_match(name))?;
+        let ret = rule
+            .pattern
+            .replace(name, rule.replace.as_str())
+            .into_owned();
+        if ret == name {
+            None
+        } else {
+            Some(ret)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Protect {
+    #[serde(default)]
+    pub modules: Vec<String>,
+    pub key_keyowner: Option<String>,
+    pub key_keyname: Option<String>,
+    pub key_method: Option<String>,
+    pub data_method: Option<String>,
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl Protect {
+    pub fn is_target(&self, name: &str) -> bool {
+        self.modules.iter().any(|x| x == name)
+    }
+
+    pub fn pragmas(&self) -> Vec<String> {
+        let mut ret = Vec::new();
+        if let Some(ref x) = self.key_keyowner {
+            ret.push(format!("key_keyowner = \"{x}\""));
+        }
+        if let Some(ref x) = self.key_keyname {
+            ret.push(format!("key_keyname = \"{x}\""));
+        }
+        if let Some(ref x) = self.key_method {
+            ret.push(format!("key_method = \"{x}\""));
+        }
+        if let Some(ref x) = self.data_method {
+            ret.push(format!("data_method = \"{x}\""));
+        }
+        ret
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bundle {
+    #[serde(default = "default_path")]
+    pub path: PathBuf,
+    #[serde(default = "default_true")]
+    pub doc: bool,
+    #[serde(default = "default_true")]
+    pub sdc: bool,
+    #[serde(default = "default_clock_period")]
+    pub clock_period: f64,
+    #[serde(default)]
+    pub license_files: Vec<PathBuf>,
+    #[serde(default)]
+    pub files: Vec<PathBuf>,
+}
+
+impl Default for Bundle {
+    fn default() -> Self {
+        Self {
+            path: default_path(),
+            doc: true,
+            sdc: true,
+            clock_period: default_clock_period(),
+            license_files: Vec::new(),
+            files: Vec::new(),
+        }
+    }
+}
+
+fn default_path() -> PathBuf {
+    "bundle".into()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_clock_period() -> f64 {
+    10.0
+}
@@ -6,12 +6,24 @@ use std::path::PathBuf;
 pub struct Doc {
     #[serde(default = "default_path")]
     pub path: PathBuf,
+    #[serde(default)]
+    pub theme: DocTheme,
+    /// CSS file applied after the builtin style
+    pub custom_css: Option<PathBuf>,
+    /// Image shown at the top of the sidebar and the index page
+    pub logo: Option<PathBuf>,
+    /// Text shown at the bottom of all pages
+    pub footer: Option<String>,
 }
 
 impl Default for Doc {
     fn default() -> Self {
         Self {
             path: default_path(),
+            theme: DocTheme::default(),
+            custom_css: None,
+            logo: None,
+            footer: None,
         }
     }
 }
@@ -19,3 +31,15 @@ impl Default for Doc {
 fn default_path() -> PathBuf {
     "doc".into()
 }
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DocTheme {
+    /// Follow the color scheme preferred by the browser
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "light")]
+    Light,
+    #[serde(rename = "dark")]
+    Dark,
+}
@@ -1,26 +1,46 @@
+mod baseline;
 mod build;
+mod bundle;
+mod cancellation;
 mod doc;
 mod format;
 mod git;
 mod lint;
 mod lockfile;
+mod matrix;
 mod metadata;
 mod metadata_error;
+mod overrides;
 mod project;
 mod pubfile;
 mod publish;
 mod test;
+mod test_manifest;
 #[cfg(test)]
 mod tests;
-pub use build::{Build, BuiltinType, ClockType, FilelistType, ResetType, SourceMapTarget, Target};
-pub use doc::Doc;
+mod verification;
+pub use baseline::{Baseline, BaselineEntry};
+pub use build::{
+    Build, BuildTarget, BuiltinType, ClockGatingCell, ClockType, Dialect, FilelistType, Header,
+    LogStyle, MemoryStyle, Protect, ProtocolCheck, Rename, RenameRule, ResetSynchronizer,
+    ResetType, SourceMapTarget, Target, TypeGenericStyle,
+};
+pub use bundle::Bundle;
+pub use cancellation::CancellationToken;
+pub use doc::{Doc, DocTheme};
 pub use format::Format;
-pub use lint::{Case, Lint};
-pub use lockfile::Lockfile;
+pub use lint::{Case, Lint, LintDft, LintSeverity};
+pub use lockfile::{Lock, Lockfile};
+pub use matrix::{Matrix, MatrixValue, MatrixVariant};
 pub use metadata::{BumpKind, Metadata};
 pub use metadata_error::MetadataError;
+pub use overrides::Override;
 pub use project::Project;
 pub use pubfile::{Pubfile, Release};
 pub use publish::Publish;
 pub use semver;
-pub use test::{SimType, Test, WaveFormTarget};
+pub use test::{
+    CocotbProperty, CoverageProperty, SimType, Test, TestArgs, WaveFormFormat, WaveFormTarget,
+};
+pub use test_manifest::TestManifest;
+pub use verification::{Ral, Register, RegisterBlock, RegisterField, Verification};
@@ -1,12 +1,59 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Lint {
     #[serde(default)]
     pub naming: LintNaming,
+    /// Severity overrides keyed by check code like "unused_variable".
+    /// The value is "error", "warning" or "info".
+    #[serde(default)]
+    pub severity: BTreeMap<String, LintSeverity>,
+    /// Baseline file suppressing recorded diagnostics
+    #[serde(default)]
+    pub baseline: Option<PathBuf>,
+    /// Allow hierarchical references into module instances which are emitted as XMRs
+    #[serde(default)]
+    pub allow_hierarchical_reference: bool,
+    #[serde(default)]
+    pub dft: LintDft,
+}
+
+/// Design-for-test checks of structures which break scan insertion
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintDft {
+    /// Enable the checks (default: false)
+    #[serde(default)]
+    pub enable: bool,
+    /// Severity of clocks generated by logic in the module
+    #[serde(default = "default_dft_severity")]
+    pub generated_clock: LintSeverity,
+    /// Severity of resets derived from logic in the module
+    #[serde(default = "default_dft_severity")]
+    pub generated_reset: LintSeverity,
+    /// Severity of latches caused by branches which don't assign all variables
+    #[serde(default = "default_dft_severity")]
+    pub latch: LintSeverity,
+}
+
+impl Default for LintDft {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            generated_clock: default_dft_severity(),
+            generated_reset: default_dft_severity(),
+            latch: default_dft_severity(),
+        }
+    }
+}
+
+fn default_dft_severity() -> LintSeverity {
+    LintSeverity::Warning
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -258,3 +305,24 @@ impl fmt::Display for Case {
         text.fmt(f)
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LintSeverity {
+    #[serde(rename = "error")]
+    Error,
+    #[serde(rename = "warning")]
+    Warning,
+    #[serde(rename = "info")]
+    Info,
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            LintSeverity::Error => "error",
+            LintSeverity::Warning => "warning",
+            LintSeverity::Info => "info",
+        };
+        text.fmt(f)
+    }
+}
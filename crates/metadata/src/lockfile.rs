@@ -1,3 +1,4 @@
+use crate::cancellation::CancellationToken;
 use crate::git::Git;
 use crate::metadata::{Dependency, Metadata};
 use crate::metadata_error::MetadataError;
@@ -7,7 +8,7 @@ use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use url::Url;
 use uuid::Uuid;
@@ -21,6 +22,8 @@ pub struct Lockfile {
     pub lock_table: HashMap<Url, Vec<Lock>>,
     #[serde(skip)]
     force_update: bool,
+    #[serde(skip)]
+    cancellation: CancellationToken,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -82,7 +85,10 @@ impl Lockfile {
     }
 
     pub fn new(metadata: &Metadata) -> Result<Self, MetadataError> {
-        let mut ret = Lockfile::default();
+        let mut ret = Lockfile {
+            cancellation: metadata.cancellation.clone(),
+            ..Default::default()
+        };
 
         let mut name_table = HashSet::new();
         let mut uuid_table = HashSet::new();
@@ -106,6 +112,7 @@ impl Lockfile {
         force_update: bool,
     ) -> Result<bool, MetadataError> {
         self.force_update = force_update;
+        self.cancellation = metadata.cancellation.clone();
 
         let mut name_table = HashSet::new();
         let mut uuid_table = HashSet::new();
@@ -160,7 +167,7 @@ impl Lockfile {
                 let metadata = self.get_metadata(&lock.url, &lock.revision)?;
                 let path = metadata.project_path();
 
-                for src in &veryl_path::gather_files_with_extension(&path, "veryl", false)? {
+                for src in &metadata.source_files(false)? {
                     let rel = src.strip_prefix(&path)?;
                     let mut dst = base_dst.join(&lock.name);
                     dst.push(rel);
@@ -337,6 +344,8 @@ impl Lockfile {
         url: &Url,
         version_req: &VersionReq,
     ) -> Result<Release, MetadataError> {
+        self.cancellation.check()?;
+
         let resolve_dir = veryl_path::cache_path().join("resolve");
 
         if !resolve_dir.exists() {
@@ -346,6 +355,7 @@ impl Lockfile {
         let uuid = Self::gen_uuid(url, "")?;
 
         let path = resolve_dir.join(uuid.simple().encode_lower(&mut Uuid::encode_buffer()));
+        info!("Resolving dependency ({} {})", url, version_req);
         let lock = veryl_path::lock_dir("resolve")?;
         let git = Git::clone(url, &path)?;
         git.fetch()?;
@@ -369,19 +379,28 @@ impl Lockfile {
         })
     }
 
+    /// Returns the path where the dependency is checked out
+    pub fn dependency_path(url: &Url, revision: &str) -> Result<PathBuf, MetadataError> {
+        let dependencies_dir = veryl_path::cache_path().join("dependencies");
+        let uuid = Self::gen_uuid(url, revision)?;
+        Ok(dependencies_dir.join(uuid.simple().encode_lower(&mut Uuid::encode_buffer())))
+    }
+
     fn get_metadata(&self, url: &Url, revision: &str) -> Result<Metadata, MetadataError> {
+        // Check before git operations to keep the checked out directory consistent
+        self.cancellation.check()?;
+
         let dependencies_dir = veryl_path::cache_path().join("dependencies");
 
         if !dependencies_dir.exists() {
             fs::create_dir_all(&dependencies_dir)?;
         }
 
-        let uuid = Self::gen_uuid(url, revision)?;
-
-        let path = dependencies_dir.join(uuid.simple().encode_lower(&mut Uuid::encode_buffer()));
+        let path = Self::dependency_path(url, revision)?;
         let toml = path.join("Veryl.toml");
 
         if !path.exists() {
+            info!("Fetching dependency ({} @ {})", url, revision);
             let lock = veryl_path::lock_dir("dependencies")?;
             let git = Git::clone(url, &path)?;
             git.fetch()?;
@@ -389,10 +408,11 @@ impl Lockfile {
             veryl_path::unlock_dir(lock)?;
         } else {
             let git = Git::open(&path)?;
-            let ret = git.is_clean().map_or(false, |x| x);
+            let ret = git.is_clean().is_ok_and(|x| x);
 
             // If the existing path is not git repository, cleanup and re-try
             if !ret || !toml.exists() {
+                info!("Fetching dependency ({} @ {})", url, revision);
                 let lock = veryl_path::lock_dir("dependencies")?;
                 fs::remove_dir_all(&path)?;
                 let git = Git::clone(url, &path)?;
@@ -0,0 +1,120 @@
+use crate::build::BuildTarget;
+use crate::MetadataError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Parameter sweep of the top module loaded by `veryl build --matrix`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Matrix {
+    pub top: String,
+    /// Output directory including a directory of each variant
+    #[serde(default = "default_path")]
+    pub path: PathBuf,
+    /// Values of each parameter, and all combinations of them are emitted
+    #[serde(default)]
+    pub params: BTreeMap<String, Vec<MatrixValue>>,
+    /// Variants with explicit name in addition to the combinations
+    #[serde(default)]
+    pub variants: Vec<MatrixVariant>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatrixVariant {
+    pub name: String,
+    #[serde(default)]
+    pub params: BTreeMap<String, MatrixValue>,
+}
+
+/// Parameter value which is an integer or SystemVerilog expression
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum MatrixValue {
+    Integer(i64),
+    Boolean(bool),
+    Expression(String),
+}
+
+impl fmt::Display for MatrixValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatrixValue::Integer(x) => x.fmt(f),
+            MatrixValue::Boolean(x) => (*x as u8).fmt(f),
+            MatrixValue::Expression(x) => x.fmt(f),
+        }
+    }
+}
+
+fn default_path() -> PathBuf {
+    PathBuf::from("target/matrix")
+}
+
+impl Matrix {
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Self, MetadataError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_str(&text)
+    }
+
+    /// Build targets of all variants.
+    /// A combination is named like `DEPTH16_WIDTH8` from parameter names and values.
+    pub fn targets(&self) -> Vec<BuildTarget> {
+        let mut combinations = vec![(Vec::new(), BTreeMap::new())];
+        for (name, values) in &self.params {
+            let mut next = Vec::new();
+            for (names, params) in &combinations {
+                for value in values {
+                    let mut names: Vec<String> = names.clone();
+                    let mut params: BTreeMap<String, String> = params.clone();
+                    let value_name: String = value
+                        .to_string()
+                        .chars()
+                        .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+                        .collect();
+                    names.push(format!("{}{}", name, value_name));
+                    params.insert(name.clone(), value.to_string());
+                    next.push((names, params));
+                }
+            }
+            combinations = next;
+        }
+        if self.params.is_empty() {
+            combinations.clear();
+        }
+
+        let mut ret: Vec<_> = combinations
+            .into_iter()
+            .map(|(names, params)| (names.join("_"), params))
+            .collect();
+        for variant in &self.variants {
+            let params = variant
+                .params
+                .iter()
+                .map(|(name, value)| (name.clone(), value.to_string()))
+                .collect();
+            ret.push((variant.name.clone(), params));
+        }
+
+        ret.into_iter()
+            .map(|(name, params)| BuildTarget {
+                path: self.path.join(name),
+                top: Some(self.top.clone()),
+                params,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Matrix {
+    type Err = MetadataError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let matrix: Matrix = toml::from_str(s)?;
+        Ok(matrix)
+    }
+}
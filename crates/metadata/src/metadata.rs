@@ -1,20 +1,25 @@
 use crate::build::{Build, Target};
+use crate::bundle::Bundle;
+use crate::cancellation::CancellationToken;
 use crate::doc::Doc;
 use crate::format::Format;
 use crate::git::Git;
-use crate::lint::Lint;
+use crate::lint::{Lint, LintSeverity};
 use crate::lockfile::Lockfile;
 use crate::project::Project;
 use crate::pubfile::{Pubfile, Release};
 use crate::publish::Publish;
 use crate::test::Test;
-use crate::{FilelistType, MetadataError, SourceMapTarget};
+use crate::verification::Verification;
+use crate::{FilelistType, MetadataError, Override, SourceMapTarget};
+use glob::{MatchOptions, Pattern};
 use log::{debug, info};
 use once_cell::sync::Lazy;
 use regex::Regex;
-use semver::VersionReq;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use spdx::Expression;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::env;
 use std::fs;
@@ -47,6 +52,10 @@ pub struct Metadata {
     #[serde(default)]
     pub test: Test,
     #[serde(default)]
+    pub verification: Verification,
+    #[serde(default)]
+    pub bundle: Bundle,
+    #[serde(default)]
     pub dependencies: HashMap<Url, Dependency>,
     #[serde(skip)]
     pub metadata_path: PathBuf,
@@ -58,8 +67,25 @@ pub struct Metadata {
     pub lockfile_path: PathBuf,
     #[serde(skip)]
     pub lockfile: Lockfile,
+    #[serde(skip)]
+    pub cancellation: CancellationToken,
+    /// Settings overridden by `Veryl.toml` fragments in subdirectories
+    #[serde(skip)]
+    pub overrides: Vec<Override>,
+    /// `Veryl.toml` is not found and the default settings are used
+    #[serde(skip)]
+    pub standalone: bool,
 }
 
+/// Check codes reported as info in standalone mode
+const STANDALONE_INFO_CODES: &[&str] = &[
+    "unused_variable",
+    "unused_function",
+    "unused_function_input",
+    "unused_return",
+    "unknown_doc_target",
+];
+
 static VALID_PROJECT_NAME: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^[a-zA-Z_][0-9a-zA-Z_]*$").unwrap());
 
@@ -71,7 +97,7 @@ impl Metadata {
     pub fn search_from<T: AsRef<Path>>(from: T) -> Result<PathBuf, MetadataError> {
         for path in from.as_ref().ancestors() {
             let path = path.join("Veryl.toml");
-            if path.is_file() {
+            if path.is_file() && veryl_path::is_project_toml(&path) {
                 return Ok(path);
             }
         }
@@ -79,6 +105,25 @@ impl Metadata {
         Err(MetadataError::FileNotFound)
     }
 
+    /// Metadata with the default settings for files outside of any project.
+    /// The current directory is regarded as the project root, but no file is created there.
+    ///
+    /// Warnings about unused declarations are downgraded to info
+    /// because they are common in snippets which are not a complete design.
+    pub fn standalone() -> Result<Self, MetadataError> {
+        let text = Self::create_default_toml("standalone")?;
+        let mut metadata = Self::from_str(&text)?;
+        metadata.metadata_path = env::current_dir()?.join("Veryl.toml");
+        metadata.standalone = true;
+        for code in STANDALONE_INFO_CODES {
+            metadata
+                .lint
+                .severity
+                .insert(code.to_string(), LintSeverity::Info);
+        }
+        Ok(metadata)
+    }
+
     pub fn load<T: AsRef<Path>>(path: T) -> Result<Self, MetadataError> {
         let path = path.as_ref().canonicalize()?;
         let text = fs::read_to_string(&path)?;
@@ -87,6 +132,7 @@ impl Metadata {
         metadata.pubfile_path = path.with_file_name("Veryl.pub");
         metadata.lockfile_path = path.with_file_name("Veryl.lock");
         metadata.check()?;
+        metadata.load_overrides()?;
 
         if metadata.pubfile_path.exists() {
             metadata.pubfile = Pubfile::load(&metadata.pubfile_path)?;
@@ -99,6 +145,59 @@ impl Metadata {
         Ok(metadata)
     }
 
+    fn load_overrides(&mut self) -> Result<(), MetadataError> {
+        let mut fragments = veryl_path::gather_metadata_fragments(self.project_path(), true);
+
+        // Fragments in parent directories are loaded first to be inherited
+        fragments.sort_by_key(|x| x.components().count());
+        for path in fragments {
+            let dir = path.parent().unwrap();
+            let parent = self
+                .overrides
+                .iter()
+                .rev()
+                .find(|x| dir.starts_with(&x.path));
+            let (lint, format) = match parent {
+                Some(x) => (&x.lint, &x.format),
+                None => (&self.lint, &self.format),
+            };
+            let x = Override::load(&path, lint, format)?;
+            debug!("Loaded metadata fragment ({})", path.to_string_lossy());
+            self.overrides.push(x);
+        }
+        Ok(())
+    }
+
+    /// Metadata with the settings overridden by `Veryl.toml` fragments for `path`
+    pub fn for_file<T: AsRef<Path>>(&self, path: T) -> Cow<'_, Metadata> {
+        let path = path.as_ref();
+        match self
+            .overrides
+            .iter()
+            .rev()
+            .find(|x| path.starts_with(&x.path))
+        {
+            Some(x) => {
+                let mut ret = self.clone();
+                ret.lint = x.lint.clone();
+                ret.format = x.format.clone();
+                Cow::Owned(ret)
+            }
+            None => Cow::Borrowed(self),
+        }
+    }
+
+    /// Git revision of the project with `-dirty` suffix if the working tree is modified
+    pub fn revision(&self) -> Option<String> {
+        let git = Git::open(&self.project_path()).ok()?;
+        let revision = git.get_revision().ok()?;
+        if git.is_clean().ok()? {
+            Some(revision)
+        } else {
+            Some(format!("{revision}-dirty"))
+        }
+    }
+
     pub fn publish(&mut self) -> Result<(), MetadataError> {
         let prj_path = self.project_path();
         let git = Git::open(&prj_path)?;
@@ -150,6 +249,23 @@ impl Metadata {
         Ok(())
     }
 
+    /// Checks whether `version` of Veryl toolchain satisfies `project.veryl_version`
+    pub fn check_veryl_version(&self, version: &Version) -> Result<(), MetadataError> {
+        if let Some(ref required) = self.project.veryl_version {
+            // Pre-release build like nightly is treated as the release it is based on
+            let mut base = version.clone();
+            base.pre = semver::Prerelease::EMPTY;
+            if !required.matches(&base) {
+                return Err(MetadataError::IncompatibleToolchain {
+                    required: required.clone(),
+                    current: version.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn bump_version(&mut self, kind: BumpKind) -> Result<(), MetadataError> {
         let prj_path = self.project_path();
         let git = Git::open(&prj_path)?;
@@ -215,6 +331,43 @@ impl Metadata {
         Ok(())
     }
 
+    /// Gather Veryl sources of the project filtered by `[build] include` and `exclude`
+    pub fn source_files(&self, symlink: bool) -> Result<Vec<PathBuf>, MetadataError> {
+        let base = self.project_path();
+
+        let compile = |patterns: &[String]| -> Result<Vec<Pattern>, MetadataError> {
+            patterns
+                .iter()
+                .map(|x| {
+                    Pattern::new(x).map_err(|source| MetadataError::InvalidGlob {
+                        pattern: x.clone(),
+                        source,
+                    })
+                })
+                .collect()
+        };
+        let include = compile(&self.build.include)?;
+        let exclude = compile(&self.build.exclude)?;
+
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        let matches = |patterns: &[Pattern], path: &Path| {
+            patterns.iter().any(|x| x.matches_path_with(path, options))
+        };
+
+        let mut ret = Vec::new();
+        for src in veryl_path::gather_files_with_extension(&base, "veryl", symlink)? {
+            let rel = src.strip_prefix(&base).unwrap_or(&src);
+            let included = include.is_empty() || matches(&include, rel);
+            if included && !matches(&exclude, rel) {
+                ret.push(src);
+            }
+        }
+        Ok(ret)
+    }
+
     pub fn paths<T: AsRef<Path>>(
         &mut self,
         files: &[T],
@@ -223,11 +376,13 @@ impl Metadata {
         let base = self.project_path();
 
         let src_files = if files.is_empty() {
-            veryl_path::gather_files_with_extension(&base, "veryl", symlink)?
+            self.source_files(symlink)?
         } else {
             let mut ret = Vec::new();
             for file in files {
-                ret.push(fs::canonicalize(file.as_ref())?);
+                for file in expand_glob(file.as_ref())? {
+                    ret.push(fs::canonicalize(file)?);
+                }
             }
             ret
         };
@@ -262,6 +417,16 @@ impl Metadata {
         }
 
         let base_dst = self.project_dependencies_path();
+
+        // Dependencies can't be resolved without Veryl.toml
+        if self.standalone {
+            if !self.build.exclude_std {
+                veryl_std::expand()?;
+                ret.append(&mut veryl_std::paths(&base_dst)?);
+            }
+            return Ok(ret);
+        }
+
         if !base_dst.exists() {
             fs::create_dir(&base_dst)?;
         }
@@ -309,9 +474,90 @@ version = "0.1.0""###
         self.metadata_path.with_file_name(filelist_name)
     }
 
+    pub fn bind_path(&self) -> PathBuf {
+        if let Some(ref x) = self.verification.bind_path {
+            self.metadata_path.parent().unwrap().join(x)
+        } else {
+            self.metadata_path
+                .with_file_name(format!("{}_bind.sv", self.project.name))
+        }
+    }
+
+    pub fn ral_path(&self) -> PathBuf {
+        if let Some(ref x) = self.verification.ral.path {
+            self.metadata_path.parent().unwrap().join(x)
+        } else {
+            self.metadata_path
+                .with_file_name(format!("{}_ral_pkg.sv", self.project.name))
+        }
+    }
+
+    pub fn cocotb_tests_path(&self) -> PathBuf {
+        self.metadata_path
+            .parent()
+            .unwrap()
+            .join(&self.test.cocotb.tests_path)
+    }
+
+    pub fn coverage_path(&self) -> PathBuf {
+        self.metadata_path
+            .parent()
+            .unwrap()
+            .join(&self.test.coverage.path)
+    }
+
+    pub fn snapshot_path(&self) -> PathBuf {
+        self.metadata_path
+            .parent()
+            .unwrap()
+            .join("tests")
+            .join("snapshots")
+    }
+
+    pub fn test_manifest_path(&self) -> PathBuf {
+        self.metadata_path.with_file_name("tests.toml")
+    }
+
     pub fn doc_path(&self) -> PathBuf {
         self.metadata_path.parent().unwrap().join(&self.doc.path)
     }
+
+    pub fn bundle_path(&self) -> PathBuf {
+        self.metadata_path.parent().unwrap().join(&self.bundle.path)
+    }
+
+    pub fn baseline_path(&self) -> Option<PathBuf> {
+        self.lint
+            .baseline
+            .as_ref()
+            .map(|x| self.metadata_path.parent().unwrap().join(x))
+    }
+}
+
+/// Files matched with `path` if it is a glob pattern like `src/**/*.veryl`
+fn expand_glob(path: &Path) -> Result<Vec<PathBuf>, MetadataError> {
+    let text = path.to_string_lossy();
+    if !text.contains(['*', '?', '[']) || path.exists() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let paths = glob::glob(&text).map_err(|source| MetadataError::InvalidGlob {
+        pattern: text.to_string(),
+        source,
+    })?;
+    let mut ret = Vec::new();
+    for path in paths {
+        let path = path.map_err(|x| x.into_error())?;
+        if path.is_file() {
+            ret.push(path);
+        }
+    }
+
+    if ret.is_empty() {
+        Err(MetadataError::NoMatchedFile(text.to_string()))
+    } else {
+        Ok(ret)
+    }
 }
 
 impl FromStr for Metadata {
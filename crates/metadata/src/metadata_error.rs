@@ -1,5 +1,5 @@
 use miette::{self, Diagnostic};
-use semver::Version;
+use semver::{Version, VersionReq};
 use std::path::PathBuf;
 use thiserror::Error;
 use url::Url;
@@ -11,7 +11,10 @@ pub enum MetadataError {
     #[error("file I/O error")]
     FileIO(#[from] std::io::Error),
 
-    #[diagnostic(code(MetadataError::FileNotFound), help(""))]
+    #[diagnostic(
+        code(MetadataError::FileNotFound),
+        help("create a project by `veryl init`, or specify files like `veryl check foo.veryl`")
+    )]
     #[error("Veryl.toml is not found")]
     FileNotFound,
 
@@ -45,6 +48,18 @@ pub enum MetadataError {
     #[error("\"{0}\" is already published")]
     PublishedVersion(Version),
 
+    #[diagnostic(
+        code(MetadataError::IncompatibleToolchain),
+        help("update Veryl by `veryl self update`")
+    )]
+    #[error(
+        "Veryl {current} doesn't satisfy the version requirement \"{required}\" of the project"
+    )]
+    IncompatibleToolchain {
+        required: VersionReq,
+        current: Version,
+    },
+
     #[diagnostic(code(MetadataError::ModifiedProject), help(""))]
     #[error("There are modified files in {0}")]
     ModifiedProject(PathBuf),
@@ -65,6 +80,36 @@ pub enum MetadataError {
     #[error("project name \"{0}\" is used multiply in dependencies")]
     NameConflict(String),
 
+    #[diagnostic(code(MetadataError::Cancelled), help(""))]
+    #[error("operation is cancelled")]
+    Cancelled,
+
+    #[diagnostic(
+        code(MetadataError::InvalidOverride),
+        help("only [lint] and [format] can be overridden in subdirectories")
+    )]
+    #[error("Veryl.toml fragment {path} is invalid")]
+    InvalidOverride {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    #[diagnostic(
+        code(MetadataError::InvalidGlob),
+        help("check [build] include and exclude, or file arguments")
+    )]
+    #[error("glob pattern \"{pattern}\" is invalid")]
+    InvalidGlob {
+        pattern: String,
+        #[source]
+        source: glob::PatternError,
+    },
+
+    #[diagnostic(code(MetadataError::NoMatchedFile), help(""))]
+    #[error("no file matches \"{0}\"")]
+    NoMatchedFile(String),
+
     #[diagnostic(code(MetadataError::Path), help(""))]
     #[error("path error")]
     Path(#[from] PathError),
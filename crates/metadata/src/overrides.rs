@@ -0,0 +1,64 @@
+use crate::format::Format;
+use crate::lint::Lint;
+use crate::MetadataError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `Veryl.toml` fragment in a subdirectory of the project
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Fragment {
+    lint: Option<toml::Table>,
+    format: Option<toml::Table>,
+}
+
+/// Settings applied to the files under `path`
+#[derive(Clone, Debug)]
+pub struct Override {
+    pub path: PathBuf,
+    pub lint: Lint,
+    pub format: Format,
+}
+
+impl Override {
+    /// Load the fragment at `path`.
+    /// Settings not specified in it are inherited from `lint` and `format`.
+    pub fn load(path: &Path, lint: &Lint, format: &Format) -> Result<Self, MetadataError> {
+        let error = |source| MetadataError::InvalidOverride {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        let text = fs::read_to_string(path)?;
+        let fragment: Fragment = toml::from_str(&text).map_err(error)?;
+        Ok(Self {
+            path: path.parent().unwrap().to_path_buf(),
+            lint: merge(lint, fragment.lint).map_err(error)?,
+            format: merge(format, fragment.format).map_err(error)?,
+        })
+    }
+}
+
+fn merge<T: Serialize + DeserializeOwned>(
+    base: &T,
+    x: Option<toml::Table>,
+) -> Result<T, toml::de::Error> {
+    let mut table = toml::Table::try_from(base).map_err(serde::de::Error::custom)?;
+    if let Some(x) = x {
+        merge_table(&mut table, x);
+    }
+    toml::Value::Table(table).try_into()
+}
+
+fn merge_table(base: &mut toml::Table, x: toml::Table) {
+    for (key, value) in x {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) => merge_table(base, value),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use semver::Version;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -11,4 +11,6 @@ pub struct Project {
     pub description: Option<String>,
     pub license: Option<String>,
     pub repository: Option<String>,
+    /// Required version of Veryl toolchain (e.g. `>=0.5`)
+    pub veryl_version: Option<VersionReq>,
 }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -14,6 +15,72 @@ pub struct Test {
     pub vivado: VivadoProperty,
     #[serde(default)]
     pub waveform_target: WaveFormTarget,
+    #[serde(default)]
+    pub waveform_format: WaveFormFormat,
+    #[serde(default)]
+    pub cocotb: CocotbProperty,
+    /// Parameters of the top module overridden in all tests
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    /// Plusargs passed to the simulation of all tests
+    #[serde(default)]
+    pub plusargs: Vec<String>,
+    #[serde(default)]
+    pub coverage: CoverageProperty,
+}
+
+impl Test {
+    pub fn args(&self) -> TestArgs {
+        TestArgs {
+            params: self.params.clone(),
+            plusargs: self.plusargs.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CoverageProperty {
+    /// Directory to write coverage databases of tests
+    #[serde(default = "default_coverage_path")]
+    pub path: PathBuf,
+    /// Minimum line coverage in percent
+    pub line_threshold: Option<f64>,
+    /// Minimum toggle coverage in percent
+    pub toggle_threshold: Option<f64>,
+}
+
+impl Default for CoverageProperty {
+    fn default() -> Self {
+        Self {
+            path: default_coverage_path(),
+            line_threshold: None,
+            toggle_threshold: None,
+        }
+    }
+}
+
+fn default_coverage_path() -> PathBuf {
+    "target/coverage".into()
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestArgs {
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    #[serde(default)]
+    pub plusargs: Vec<String>,
+}
+
+impl TestArgs {
+    /// Parameters of `other` take precedence, and plusargs of `other` are appended
+    pub fn merge(&mut self, other: &TestArgs) {
+        for (name, value) in &other.params {
+            self.params.insert(name.clone(), value.clone());
+        }
+        self.plusargs.extend(other.plusargs.iter().cloned());
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -56,12 +123,65 @@ pub struct VivadoProperty {
     pub simulate_args: Vec<String>,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CocotbProperty {
+    /// Directory to discover Python tests named `test_<module>.py`
+    #[serde(default = "default_tests_path")]
+    pub tests_path: PathBuf,
+    #[serde(default)]
+    pub build_args: Vec<String>,
+    #[serde(default)]
+    pub test_args: Vec<String>,
+}
+
+impl Default for CocotbProperty {
+    fn default() -> Self {
+        Self {
+            tests_path: default_tests_path(),
+            build_args: Vec::new(),
+            test_args: Vec::new(),
+        }
+    }
+}
+
+fn default_tests_path() -> PathBuf {
+    "tests".into()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type")]
 pub enum WaveFormTarget {
-    #[default]
+    /// Waveforms are placed beside the source file of each test
     #[serde(rename = "target")]
     Target,
+    /// Waveforms are placed under `<path>/<test>`
     #[serde(rename = "directory")]
     Directory { path: PathBuf },
 }
+
+impl Default for WaveFormTarget {
+    fn default() -> Self {
+        WaveFormTarget::Directory {
+            path: "target/waves".into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WaveFormFormat {
+    #[default]
+    #[serde(rename = "vcd")]
+    Vcd,
+    #[serde(rename = "fst")]
+    Fst,
+}
+
+impl WaveFormFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            WaveFormFormat::Vcd => "vcd",
+            WaveFormFormat::Fst => "fst",
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::test::TestArgs;
+use crate::MetadataError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Per-test overrides of parameters and plusargs loaded from `tests.toml`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TestManifest {
+    #[serde(flatten)]
+    pub tests: BTreeMap<String, TestArgs>,
+}
+
+impl TestManifest {
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Self, MetadataError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_str(&text)
+    }
+}
+
+impl FromStr for TestManifest {
+    type Err = MetadataError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let manifest: TestManifest = toml::from_str(s)?;
+        Ok(manifest)
+    }
+}
@@ -2,14 +2,14 @@ use crate::git::Git;
 use crate::*;
 use semver::Version;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
-const GIT_IGNORE: &'static str = r#"
+const GIT_IGNORE: &str = r#"
 Veryl.lock
 "#;
 
-const TEST_TOML: &'static str = r#"
+const TEST_TOML: &str = r#"
 [project]
 name = "test"
 version = "0.1.0"
@@ -23,9 +23,32 @@ target = {type = "source"}
 
 [format]
 indent_width = 4
+
+[doc]
+theme = "dark"
+footer = "Copyright (c) Example Inc."
+
+[lint.severity]
+unused_variable = "error"
+
+[test]
+waveform_format = "fst"
+coverage = {line_threshold = 80.0}
+
+[test.cocotb]
+tests_path = "sim"
+
+[verification.bind]
+ModuleA = ["checker_a"]
+
+[[verification.ral.blocks]]
+name = "ctrl"
+registers = [
+    {name = "CTRL", offset = 0x4, fields = [{name = "EN", lsb = 0, width = 1, reset = 1}]},
+]
 "#;
 
-const MAIN_TOML: &'static str = r#"
+const MAIN_TOML: &str = r#"
 [project]
 name = "main"
 version = "0.1.0"
@@ -39,7 +62,7 @@ version = "0.1.0"
 ]
 "#;
 
-const SUB1_TOML: &'static str = r#"
+const SUB1_TOML: &str = r#"
 [project]
 name = "sub1"
 version = "0.1.0"
@@ -52,7 +75,7 @@ publish_commit = true
 "file://{}/sub3" = "0.1.0"
 "#;
 
-const SUB2_TOML: &'static str = r#"
+const SUB2_TOML: &str = r#"
 [project]
 name = "sub2"
 version = "0.1.0"
@@ -62,7 +85,7 @@ bump_commit = true
 publish_commit = true
 "#;
 
-const SUB3_TOML: &'static str = r#"
+const SUB3_TOML: &str = r#"
 [project]
 name = "sub2"
 version = "0.1.0"
@@ -103,7 +126,7 @@ fn create_project(root: &Path, name: &str, toml: &str, publish: bool) -> Metadat
     let toml_path = path.join("Veryl.toml");
     fs::write(
         &toml_path,
-        &toml.replace("{}", &root.to_string_lossy().replace("\\", "/")),
+        toml.replace("{}", &root.to_string_lossy().replace("\\", "/")),
     )
     .unwrap();
     let git_ignore_path = path.join(".gitignore");
@@ -111,7 +134,7 @@ fn create_project(root: &Path, name: &str, toml: &str, publish: bool) -> Metadat
     let git = Git::init(&path).unwrap();
     git.add(&toml_path).unwrap();
     git.add(&git_ignore_path).unwrap();
-    git.commit(&"Add Veryl.toml").unwrap();
+    git.commit("Add Veryl.toml").unwrap();
     let mut metadata = Metadata::load(&toml_path).unwrap();
     if publish {
         metadata.publish().unwrap();
@@ -141,6 +164,82 @@ fn check_toml() {
     assert!(metadata.build.reset_low_prefix.is_none());
     assert_eq!(metadata.build.reset_low_suffix.unwrap(), "_n");
     assert_eq!(metadata.format.indent_width, 4);
+    assert_eq!(metadata.doc.theme, DocTheme::Dark);
+    assert_eq!(
+        metadata.doc.footer.as_deref(),
+        Some("Copyright (c) Example Inc.")
+    );
+    assert!(metadata.doc.logo.is_none());
+    assert_eq!(
+        metadata.lint.severity.get("unused_variable"),
+        Some(&LintSeverity::Error)
+    );
+    assert_eq!(
+        metadata.verification.bind.get("ModuleA"),
+        Some(&vec!["checker_a".to_string()])
+    );
+    let block = &metadata.verification.ral.blocks[0];
+    assert_eq!(block.name, "ctrl");
+    assert_eq!(block.width, 32);
+    assert_eq!(block.registers[0].offset, 4);
+    assert_eq!(block.registers[0].fields[0].access, "RW");
+    assert_eq!(block.registers[0].fields[0].reset, 1);
+    assert_eq!(metadata.test.cocotb.tests_path, PathBuf::from("sim"));
+    assert_eq!(metadata.test.waveform_format, WaveFormFormat::Fst);
+    assert_eq!(metadata.test.coverage.line_threshold, Some(80.0));
+    assert_eq!(metadata.test.coverage.toggle_threshold, None);
+    assert_eq!(
+        metadata.test.waveform_target,
+        WaveFormTarget::Directory {
+            path: PathBuf::from("target/waves")
+        }
+    );
+}
+
+#[test]
+fn build_targets() {
+    let toml = r#"
+[project]
+name = "test"
+version = "0.1.0"
+
+[build]
+omit_project_prefix = true
+type_generic_style = "parameter"
+
+[[build.targets]]
+path = "out/asic"
+prefix = "acme_"
+defines = {SYNTHESIS = ""}
+top = "Top"
+
+[[build.targets]]
+path = "out/fpga"
+dialect = "compatible"
+"#;
+    let metadata: Metadata = toml::from_str(toml).unwrap();
+    let targets = &metadata.build.targets;
+    assert_eq!(targets.len(), 2);
+    assert_eq!(targets[0].dialect, Dialect::SystemVerilog);
+    assert_eq!(targets[0].top.as_deref(), Some("Top"));
+    assert_eq!(targets[1].dialect, Dialect::Compatible);
+
+    let build = metadata.build.with_target(&targets[0]);
+    assert_eq!(
+        build.target,
+        Target::Directory {
+            path: PathBuf::from("out/asic")
+        }
+    );
+    assert_eq!(build.project_prefix.as_deref(), Some("acme_"));
+    assert!(!build.omit_project_prefix);
+    assert!(build.targets.is_empty());
+    assert_eq!(build.type_generic_style, TypeGenericStyle::Parameter);
+
+    let build = metadata.build.with_target(&targets[1]);
+    assert!(build.omit_project_prefix);
+    assert!(build.expand_inside_operation);
+    assert_eq!(build.type_generic_style, TypeGenericStyle::Monomorphize);
 }
 
 #[test]
@@ -233,6 +332,143 @@ fn bump_version_with_commit() {
     assert!(git.is_clean().unwrap());
 }
 
+#[test]
+fn overrides() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let root = tempdir.path().join("test");
+    fs::create_dir_all(root.join("src/legacy/old")).unwrap();
+    fs::write(root.join("Veryl.toml"), TEST_TOML).unwrap();
+    fs::write(
+        root.join("src/legacy/Veryl.toml"),
+        "[format]\nindent_width = 2\n\n[lint.severity]\nunused_variable = \"warning\"\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("src/legacy/old/Veryl.toml"),
+        "[lint.naming]\ncase_module = \"snake\"\n",
+    )
+    .unwrap();
+
+    let metadata = Metadata::load(root.join("Veryl.toml")).unwrap();
+    assert_eq!(metadata.overrides.len(), 2);
+
+    let root = metadata.project_path();
+    let x = metadata.for_file(root.join("src/a.veryl"));
+    assert_eq!(x.format.indent_width, 4);
+
+    let x = metadata.for_file(root.join("src/legacy/old/a.veryl"));
+    assert_eq!(x.format.indent_width, 2);
+    assert_eq!(
+        x.lint.severity.get("unused_variable"),
+        Some(&LintSeverity::Warning)
+    );
+    assert!(matches!(x.lint.naming.case_module, Some(Case::Snake)));
+
+    // Fragment in a subdirectory doesn't stop searching the project
+    let path = Metadata::search_from(root.join("src/legacy")).unwrap();
+    assert_eq!(path, root.join("Veryl.toml"));
+
+    fs::write(
+        root.join("src/legacy/Veryl.toml"),
+        "[build]\nstrip_comments = true\n",
+    )
+    .unwrap();
+    assert!(matches!(
+        Metadata::load(root.join("Veryl.toml")),
+        Err(MetadataError::InvalidOverride { .. })
+    ));
+}
+
+#[test]
+fn source_files() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let root = tempdir.path().join("test");
+    fs::create_dir_all(root.join("src/gen")).unwrap();
+    fs::create_dir_all(root.join("sandbox")).unwrap();
+    fs::write(root.join("Veryl.toml"), TEST_TOML).unwrap();
+    for file in ["src/a.veryl", "src/gen/b.veryl", "sandbox/c.veryl"] {
+        fs::write(root.join(file), "").unwrap();
+    }
+
+    let mut metadata = Metadata::load(root.join("Veryl.toml")).unwrap();
+    let root = metadata.project_path();
+    let files = |metadata: &Metadata| {
+        let mut ret: Vec<_> = metadata
+            .source_files(false)
+            .unwrap()
+            .into_iter()
+            .map(|x| x.strip_prefix(&root).unwrap().to_path_buf())
+            .collect();
+        ret.sort();
+        ret
+    };
+
+    assert_eq!(files(&metadata).len(), 3);
+
+    metadata.build.include = vec!["src/**/*.veryl".to_string()];
+    assert_eq!(
+        files(&metadata),
+        vec![
+            PathBuf::from("src/a.veryl"),
+            PathBuf::from("src/gen/b.veryl")
+        ]
+    );
+
+    metadata.build.exclude = vec!["src/gen/*".to_string()];
+    assert_eq!(files(&metadata), vec![PathBuf::from("src/a.veryl")]);
+
+    metadata.build.include = vec!["*.veryl".to_string()];
+    assert!(files(&metadata).is_empty());
+
+    metadata.build.exclude = vec!["[".to_string()];
+    assert!(matches!(
+        metadata.source_files(false),
+        Err(MetadataError::InvalidGlob { .. })
+    ));
+}
+
+#[test]
+fn standalone_paths() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let root = tempdir.path().canonicalize().unwrap();
+    fs::create_dir_all(root.join("gen")).unwrap();
+    for file in ["gen/a.veryl", "gen/b.veryl", "c.veryl"] {
+        fs::write(root.join(file), "").unwrap();
+    }
+
+    let mut metadata = Metadata::standalone().unwrap();
+    metadata.metadata_path = root.join("Veryl.toml");
+    metadata.build.exclude_std = true;
+    assert!(metadata.standalone);
+    assert_eq!(
+        metadata.lint.severity.get("unused_variable"),
+        Some(&LintSeverity::Info)
+    );
+
+    let mut paths: Vec<_> = metadata
+        .paths(&[root.join("gen/*.veryl"), root.join("c.veryl")], false)
+        .unwrap()
+        .into_iter()
+        .map(|x| x.src.strip_prefix(&root).unwrap().to_path_buf())
+        .collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("c.veryl"),
+            PathBuf::from("gen/a.veryl"),
+            PathBuf::from("gen/b.veryl")
+        ]
+    );
+    assert!(!root.join("dependencies").exists());
+    assert!(!root.join("Veryl.lock").exists());
+
+    assert!(matches!(
+        metadata.paths(&[root.join("src/*.veryl")], false),
+        Err(MetadataError::NoMatchedFile(_))
+    ));
+}
+
 #[test]
 fn lockfile() {
     let (metadata, _tempdir) = create_metadata_multi();
@@ -264,3 +500,136 @@ fn lockfile() {
     assert_eq!(sub3_2.unwrap().version, Version::parse("0.2.0").unwrap());
     assert_eq!(sub3_3.unwrap().version, Version::parse("1.0.0").unwrap());
 }
+
+#[test]
+fn baseline() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("baseline.toml");
+
+    let entry = BaselineEntry {
+        code: "unused_variable".to_string(),
+        path: "src/a.veryl".to_string(),
+        message: "a is unused".to_string(),
+    };
+    let baseline = Baseline {
+        diagnostics: vec![entry.clone(), entry.clone()],
+    };
+    baseline.save(&path).unwrap();
+
+    let mut baseline = Baseline::load(&path).unwrap();
+    assert_eq!(baseline.diagnostics.len(), 2);
+    assert!(baseline.take(&entry));
+    assert!(baseline.take(&entry));
+    assert!(!baseline.take(&entry));
+}
+
+#[test]
+fn veryl_version() {
+    let toml = TEST_TOML.replace(
+        "version = \"0.1.0\"",
+        "version = \"0.1.0\"\nveryl_version = \">=0.5, <0.14\"",
+    );
+    let metadata: Metadata = toml::from_str(&toml).unwrap();
+
+    let check = |x| metadata.check_veryl_version(&Version::parse(x).unwrap());
+    assert!(check("0.13.2").is_ok());
+    assert!(check("0.13.3-nightly").is_ok());
+    assert!(check("0.4.0").is_err());
+    assert!(check("0.14.0").is_err());
+
+    let metadata: Metadata = toml::from_str(TEST_TOML).unwrap();
+    assert!(metadata
+        .check_veryl_version(&Version::parse("0.1.0").unwrap())
+        .is_ok());
+}
+
+#[test]
+fn build_rename() {
+    let toml = r#"
+[project]
+name = "test"
+version = "0.1.0"
+
+[build.rename]
+report = "rename.txt"
+
+[[build.rename.module]]
+pattern = "^test_(.*)$"
+replace = "acme_$1"
+
+[[build.rename.port]]
+pattern = "^i_(.*)$"
+replace = "${1}_i"
+
+[[build.rename.port]]
+pattern = "^i_.*$"
+replace = "unused"
+"#;
+    let metadata: Metadata = toml::from_str(toml).unwrap();
+    let rename = &metadata.build.rename;
+    assert_eq!(rename.report, Some(PathBuf::from("rename.txt")));
+    assert_eq!(rename.module("test_Top").as_deref(), Some("acme_Top"));
+    assert_eq!(rename.module("Top"), None);
+    assert_eq!(rename.port("i_data").as_deref(), Some("data_i"));
+    assert_eq!(rename.port("o_data"), None);
+}
+
+#[test]
+fn test_manifest() {
+    let toml = TEST_TOML.replace(
+        "[test]\n",
+        "[test]\nparams = {WIDTH = \"8\", DEPTH = \"4\"}\nplusargs = [\"+seed=1\"]\n",
+    );
+    let metadata: Metadata = toml::from_str(&toml).unwrap();
+
+    let manifest: TestManifest = r#"
+[test1]
+params = {WIDTH = "16"}
+plusargs = ["+verbose"]
+"#
+    .parse()
+    .unwrap();
+
+    let mut args = metadata.test.args();
+    args.merge(&manifest.tests["test1"]);
+    assert_eq!(args.params["WIDTH"], "16");
+    assert_eq!(args.params["DEPTH"], "4");
+    assert_eq!(args.plusargs, vec!["+seed=1", "+verbose"]);
+}
+
+#[test]
+fn matrix() {
+    let matrix: Matrix = r#"
+top = "Fifo"
+
+[params]
+DEPTH = [16, 32]
+WIDTH = [8, "W"]
+
+[[variants]]
+name = "tiny"
+params = {DEPTH = 2, BYPASS = true}
+"#
+    .parse()
+    .unwrap();
+
+    let targets = matrix.targets();
+    let paths: Vec<_> = targets.iter().map(|x| x.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("target/matrix/DEPTH16_WIDTH8"),
+            PathBuf::from("target/matrix/DEPTH16_WIDTHW"),
+            PathBuf::from("target/matrix/DEPTH32_WIDTH8"),
+            PathBuf::from("target/matrix/DEPTH32_WIDTHW"),
+            PathBuf::from("target/matrix/tiny"),
+        ]
+    );
+    assert_eq!(targets[1].params["DEPTH"], "16");
+    assert_eq!(targets[1].params["WIDTH"], "W");
+    assert_eq!(targets[4].params["BYPASS"], "1");
+    assert!(targets.iter().all(|x| x.top.as_deref() == Some("Fifo")));
+
+    let build = Build::default().with_target(&targets[0]);
+    assert_eq!(build.param_overrides["Fifo"]["WIDTH"], "8");
+}
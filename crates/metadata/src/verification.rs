@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Verification {
+    /// Checker modules bound to each module
+    #[serde(default)]
+    pub bind: BTreeMap<String, Vec<String>>,
+    pub bind_path: Option<PathBuf>,
+    /// Register blocks which UVM register model is generated from
+    #[serde(default)]
+    pub ral: Ral,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Ral {
+    pub path: Option<PathBuf>,
+    #[serde(default)]
+    pub blocks: Vec<RegisterBlock>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterBlock {
+    pub name: String,
+    /// Bit width of each register
+    #[serde(default = "default_register_width")]
+    pub width: usize,
+    #[serde(default)]
+    pub registers: Vec<Register>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Register {
+    pub name: String,
+    /// Byte offset from the base address of the block
+    pub offset: u64,
+    #[serde(default)]
+    pub fields: Vec<RegisterField>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterField {
+    pub name: String,
+    pub lsb: usize,
+    pub width: usize,
+    /// UVM access policy like "RW", "RO" and "W1C"
+    #[serde(default = "default_access")]
+    pub access: String,
+    #[serde(default)]
+    pub reset: u64,
+}
+
+fn default_register_width() -> usize {
+    32
+}
+
+fn default_access() -> String {
+    "RW".to_string()
+}
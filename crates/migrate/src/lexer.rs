@@ -0,0 +1,221 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Identifier,
+    Number,
+    String,
+    Comment,
+    Attribute,
+    Directive,
+    Macro,
+    Symbol,
+}
+
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub kind: Kind,
+    pub text: String,
+    pub beg: usize,
+    pub end: usize,
+}
+
+impl Token {
+    pub fn is(&self, text: &str) -> bool {
+        matches!(self.kind, Kind::Identifier | Kind::Symbol) && self.text == text
+    }
+}
+
+const SYMBOLS: &[&str] = &[
+    "<<<=", ">>>=", "<<=", ">>=", "===", "!==", "==?", "!=?", "<<<", ">>>", "'{", "->", "+:", "-:",
+    "::", "**", "==", "!=", "<=", ">=", "&&", "||", "<<", ">>", "++", "--", "+=", "-=", "*=", "/=",
+    "%=", "&=", "|=", "^=", "~&", "~|", "~^", "^~", ".*",
+];
+
+/// Directives occupying the rest of line
+const LINE_DIRECTIVES: &[&str] = &["define", "include", "timescale", "pragma", "line"];
+
+/// Directives taking an identifier
+const IDENT_DIRECTIVES: &[&str] = &["ifdef", "ifndef", "elsif", "undef", "default_nettype"];
+
+/// Directives without argument
+const BARE_DIRECTIVES: &[&str] = &[
+    "else",
+    "endif",
+    "undefineall",
+    "resetall",
+    "celldefine",
+    "endcelldefine",
+    "nounconnected_drive",
+];
+
+fn is_ident_start(x: u8) -> bool {
+    x.is_ascii_alphabetic() || x == b'_' || x == b'$'
+}
+
+fn is_ident(x: u8) -> bool {
+    x.is_ascii_alphanumeric() || x == b'_' || x == b'$'
+}
+
+pub fn tokenize(input: &str) -> Vec<Token> {
+    let text = input.as_bytes();
+    let mut ret = Vec::new();
+    let mut i = 0;
+
+    while i < text.len() {
+        let beg = i;
+        let c = text[i];
+        let next = text.get(i + 1).copied().unwrap_or(0);
+
+        let kind = if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        } else if c == b'/' && next == b'/' {
+            while i < text.len() && text[i] != b'\n' {
+                i += 1;
+            }
+            Kind::Comment
+        } else if c == b'/' && next == b'*' {
+            i += 2;
+            while i < text.len() && !text[i..].starts_with(b"*/") {
+                i += 1;
+            }
+            i = (i + 2).min(text.len());
+            Kind::Comment
+        } else if c == b'(' && next == b'*' && !matches!(text.get(i + 2), Some(b')')) {
+            i += 2;
+            while i < text.len() && !text[i..].starts_with(b"*)") {
+                i += 1;
+            }
+            i = (i + 2).min(text.len());
+            Kind::Attribute
+        } else if c == b'"' {
+            i += 1;
+            while i < text.len() && text[i] != b'"' {
+                if text[i] == b'\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(text.len());
+            Kind::String
+        } else if c == b'`' {
+            i += 1;
+            while i < text.len() && is_ident(text[i]) {
+                i += 1;
+            }
+            let name = &input[beg + 1..i];
+            if LINE_DIRECTIVES.contains(&name) {
+                while i < text.len() && text[i] != b'\n' {
+                    // Line continuation of macro definition
+                    if text[i] == b'\\' && text.get(i + 1) == Some(&b'\n') {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                Kind::Directive
+            } else if IDENT_DIRECTIVES.contains(&name) {
+                while i < text.len() && matches!(text[i], b' ' | b'\t') {
+                    i += 1;
+                }
+                while i < text.len() && is_ident(text[i]) {
+                    i += 1;
+                }
+                Kind::Directive
+            } else if BARE_DIRECTIVES.contains(&name) {
+                Kind::Directive
+            } else {
+                Kind::Macro
+            }
+        } else if c == b'\\' {
+            // Escaped identifier
+            while i < text.len() && !text[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            Kind::Identifier
+        } else if is_ident_start(c) {
+            while i < text.len() && is_ident(text[i]) {
+                i += 1;
+            }
+            Kind::Identifier
+        } else if c.is_ascii_digit() || (c == b'\'' && is_based(&text[i + 1..])) {
+            i = number_end(text, i);
+            Kind::Number
+        } else {
+            let len = SYMBOLS
+                .iter()
+                .find(|x| text[i..].starts_with(x.as_bytes()))
+                .map(|x| x.len())
+                .unwrap_or(1);
+            i += len;
+            Kind::Symbol
+        };
+
+        let end = i.min(text.len());
+        ret.push(Token {
+            kind,
+            text: input[beg..end].to_string(),
+            beg,
+            end,
+        });
+    }
+
+    ret
+}
+
+fn is_based(text: &[u8]) -> bool {
+    match text.first() {
+        Some(b's' | b'S') => matches!(
+            text.get(1),
+            Some(b'b' | b'B' | b'o' | b'O' | b'd' | b'D' | b'h' | b'H')
+        ),
+        Some(b'b' | b'B' | b'o' | b'O' | b'd' | b'D' | b'h' | b'H') => true,
+        Some(b'0' | b'1' | b'x' | b'X' | b'z' | b'Z') => !text.get(1).is_some_and(|x| is_ident(*x)),
+        _ => false,
+    }
+}
+
+fn number_end(text: &[u8], mut i: usize) -> usize {
+    while i < text.len() && (text[i].is_ascii_digit() || text[i] == b'_') {
+        i += 1;
+    }
+
+    // Real number
+    if text.get(i) == Some(&b'.') && text.get(i + 1).is_some_and(|x| x.is_ascii_digit()) {
+        i += 1;
+        while i < text.len() && (text[i].is_ascii_digit() || text[i] == b'_') {
+            i += 1;
+        }
+    }
+    if matches!(text.get(i), Some(b'e' | b'E'))
+        && text
+            .get(i + 1)
+            .is_some_and(|x| x.is_ascii_digit() || *x == b'-' || *x == b'+')
+    {
+        i += 2;
+        while i < text.len() && text[i].is_ascii_digit() {
+            i += 1;
+        }
+    }
+
+    // Based number
+    if text.get(i) == Some(&b'\'') && is_based(&text[i + 1..]) {
+        i += 1;
+        if matches!(text[i], b's' | b'S') {
+            i += 1;
+        }
+        if matches!(text[i], b'0' | b'1' | b'x' | b'X' | b'z' | b'Z') {
+            return i + 1;
+        }
+        i += 1;
+        while i < text.len() && (text[i].is_ascii_alphanumeric() || matches!(text[i], b'_' | b'?'))
+        {
+            i += 1;
+        }
+    }
+
+    // Time literal
+    while i < text.len() && text[i].is_ascii_alphabetic() {
+        i += 1;
+    }
+
+    i
+}
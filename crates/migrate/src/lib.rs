@@ -0,0 +1,2397 @@
+use std::collections::HashMap;
+
+mod lexer;
+mod preprocessor;
+#[cfg(test)]
+mod tests;
+use lexer::{Kind, Token};
+pub use preprocessor::Preprocessor;
+
+pub struct Migrated {
+    pub text: String,
+    pub todos: usize,
+}
+
+/// Converts SystemVerilog source to Veryl on a best-effort basis.
+/// Constructs which can't be converted are kept as comments with TODO markers.
+pub fn migrate(input: &str) -> Migrated {
+    let mut converter = Converter::new(input);
+    let mut out = Vec::new();
+    while !converter.is_eof() {
+        converter.convert(&mut out, 0, |s, out, indent| s.description(out, indent));
+    }
+    converter.flush_comments(&mut out, 0);
+
+    let mut text = out.join("\n");
+    text.push('\n');
+    Migrated {
+        text,
+        todos: converter.todos,
+    }
+}
+
+const VERYL_KEYWORDS: &[&str] = &[
+    "always_comb",
+    "always_ff",
+    "as",
+    "assign",
+    "bit",
+    "break",
+    "case",
+    "clock",
+    "clock_negedge",
+    "clock_posedge",
+    "const",
+    "default",
+    "else",
+    "embed",
+    "enum",
+    "export",
+    "f32",
+    "f64",
+    "final",
+    "for",
+    "function",
+    "i32",
+    "i64",
+    "if",
+    "if_reset",
+    "import",
+    "in",
+    "include",
+    "initial",
+    "inout",
+    "input",
+    "inside",
+    "inst",
+    "interface",
+    "let",
+    "logic",
+    "lsb",
+    "modport",
+    "module",
+    "msb",
+    "output",
+    "outside",
+    "package",
+    "param",
+    "proto",
+    "pub",
+    "ref",
+    "repeat",
+    "reset",
+    "reset_async_high",
+    "reset_async_low",
+    "reset_sync_high",
+    "reset_sync_low",
+    "return",
+    "signed",
+    "step",
+    "string",
+    "struct",
+    "switch",
+    "tri",
+    "type",
+    "u32",
+    "u64",
+    "union",
+    "unsafe",
+    "var",
+];
+
+const NET_TYPES: &[&str] = &[
+    "wire", "tri", "uwire", "wand", "wor", "triand", "trior", "tri0", "tri1", "supply0", "supply1",
+];
+
+const DATA_TYPES: &[&str] = &[
+    "var",
+    "reg",
+    "logic",
+    "bit",
+    "int",
+    "integer",
+    "byte",
+    "shortint",
+    "longint",
+    "real",
+    "realtime",
+    "shortreal",
+    "string",
+    "time",
+    "signed",
+    "unsigned",
+];
+
+/// Keywords opening and closing blocks which are skipped together
+const BLOCK_BEGIN: &[&str] = &[
+    "begin",
+    "case",
+    "casez",
+    "casex",
+    "fork",
+    "function",
+    "task",
+    "generate",
+    "module",
+    "package",
+    "interface",
+    "program",
+    "class",
+    "checker",
+    "primitive",
+    "config",
+    "property",
+    "sequence",
+    "covergroup",
+    "clocking",
+    "specify",
+];
+const BLOCK_END: &[&str] = &[
+    "end",
+    "endcase",
+    "join",
+    "join_any",
+    "join_none",
+    "endfunction",
+    "endtask",
+    "endgenerate",
+    "endmodule",
+    "endpackage",
+    "endinterface",
+    "endprogram",
+    "endclass",
+    "endchecker",
+    "endprimitive",
+    "endconfig",
+    "endproperty",
+    "endsequence",
+    "endgroup",
+    "endclocking",
+    "endspecify",
+];
+
+struct Unsupported(String);
+
+type Conv<T> = Result<T, Unsupported>;
+
+fn unsupported<T>(reason: impl Into<String>) -> Conv<T> {
+    Err(Unsupported(reason.into()))
+}
+
+#[derive(Clone)]
+struct Type {
+    base: String,
+    signed: bool,
+    widths: Vec<String>,
+}
+
+impl Type {
+    fn logic() -> Self {
+        Self {
+            base: "logic".to_string(),
+            signed: false,
+            widths: Vec::new(),
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut ret = String::new();
+        if self.signed {
+            ret.push_str("signed ");
+        }
+        ret.push_str(&self.base);
+        if !self.widths.is_empty() {
+            ret.push_str(&format!("<{}>", self.widths.join(", ")));
+        }
+        ret
+    }
+}
+
+struct Port {
+    name: String,
+    direction: String,
+    ty: String,
+    array: String,
+}
+
+impl Port {
+    fn render(&self) -> String {
+        // inout port requires tri type modifier
+        let tri = if self.direction == "inout" {
+            "tri "
+        } else {
+            ""
+        };
+        format!(
+            "{}: {} {tri}{}{},",
+            self.name, self.direction, self.ty, self.array
+        )
+    }
+}
+
+struct Param {
+    kind: &'static str,
+    name: String,
+    ty: String,
+    value: String,
+}
+
+impl Param {
+    fn render(&self) -> String {
+        format!("{} {}: {} = {},", self.kind, self.name, self.ty, self.value)
+    }
+}
+
+#[derive(Default)]
+struct Scope {
+    ports: Vec<Port>,
+    params: Vec<Param>,
+    /// `parameter` in body is local if the header has parameter list
+    header_params: bool,
+    clocks: HashMap<String, &'static str>,
+    resets: HashMap<String, &'static str>,
+    function: Option<String>,
+}
+
+struct State {
+    pos: usize,
+    comment_pos: usize,
+    notes: usize,
+    todos: usize,
+    labels: usize,
+}
+
+struct Converter<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    comments: Vec<Token>,
+    pos: usize,
+    comment_pos: usize,
+    notes: Vec<String>,
+    todos: usize,
+    labels: usize,
+    /// Qualified names of enum members which SystemVerilog refers without enum name
+    enum_members: HashMap<String, String>,
+    eof: Token,
+}
+
+fn push(out: &mut Vec<String>, indent: usize, text: impl AsRef<str>) {
+    let text = text.as_ref();
+    if text.is_empty() {
+        out.push(String::new());
+    } else {
+        out.push(format!("{}{}", "    ".repeat(indent), text));
+    }
+}
+
+impl<'a> Converter<'a> {
+    fn new(input: &'a str) -> Self {
+        let (comments, tokens) = lexer::tokenize(input)
+            .into_iter()
+            .partition(|x| matches!(x.kind, Kind::Comment | Kind::Attribute));
+        Self {
+            input,
+            tokens,
+            comments,
+            pos: 0,
+            comment_pos: 0,
+            notes: Vec::new(),
+            todos: 0,
+            labels: 0,
+            enum_members: HashMap::new(),
+            eof: Token {
+                kind: Kind::Symbol,
+                text: String::new(),
+                beg: input.len(),
+                end: input.len(),
+            },
+        }
+    }
+
+    fn is_eof(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn peek(&self) -> &Token {
+        self.peek_n(0)
+    }
+
+    fn peek_n(&self, n: usize) -> &Token {
+        self.tokens.get(self.pos + n).unwrap_or(&self.eof)
+    }
+
+    fn at(&self, text: &str) -> bool {
+        self.peek().is(text)
+    }
+
+    fn at_any(&self, texts: &[&str]) -> bool {
+        texts.iter().any(|x| self.at(x))
+    }
+
+    fn bump(&mut self) -> Token {
+        let ret = self.peek().clone();
+        if !self.is_eof() {
+            self.pos += 1;
+        }
+        ret
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.at(text) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, text: &str) -> Conv<()> {
+        if self.eat(text) {
+            Ok(())
+        } else if self.is_eof() {
+            unsupported(format!("'{text}' is not found"))
+        } else {
+            unsupported(format!("unsupported syntax near '{}'", self.peek().text))
+        }
+    }
+
+    fn ident(&mut self) -> Conv<String> {
+        let token = self.peek();
+        if token.kind == Kind::Identifier && !token.text.starts_with('$') {
+            let text = token.text.clone();
+            self.bump();
+            escape(&text)
+        } else {
+            unsupported(format!("identifier is expected near '{}'", token.text))
+        }
+    }
+
+    fn eat_label(&mut self) {
+        if self.at(":") && self.peek_n(1).kind == Kind::Identifier {
+            self.bump();
+            self.bump();
+        }
+    }
+
+    fn new_label(&mut self) -> String {
+        self.labels += 1;
+        format!("gen_{}", self.labels)
+    }
+
+    fn note(&mut self, note: impl Into<String>) {
+        let note = note.into();
+        if !self.notes.contains(&note) {
+            self.notes.push(note);
+        }
+    }
+
+    fn save(&self) -> State {
+        State {
+            pos: self.pos,
+            comment_pos: self.comment_pos,
+            notes: self.notes.len(),
+            todos: self.todos,
+            labels: self.labels,
+        }
+    }
+
+    fn restore(&mut self, state: State) {
+        self.pos = state.pos;
+        self.comment_pos = state.comment_pos;
+        self.notes.truncate(state.notes);
+        self.todos = state.todos;
+        self.labels = state.labels;
+    }
+
+    /// Returns lines of `text` placed at `beg` without the indent of the original source
+    fn dedent(&self, beg: usize, text: &str) -> Vec<String> {
+        let line_beg = self.input[..beg].rfind('\n').map(|x| x + 1).unwrap_or(0);
+        let column = beg - line_beg;
+        text.lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let line = if i == 0 {
+                    line
+                } else {
+                    let width = line.len() - line.trim_start().len();
+                    &line[width.min(column)..]
+                };
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    fn flush_comments(&mut self, out: &mut Vec<String>, indent: usize) {
+        self.flush_comments_before(out, indent, self.peek().beg);
+    }
+
+    fn flush_comments_before(&mut self, out: &mut Vec<String>, indent: usize, beg: usize) {
+        while let Some(x) = self.comments.get(self.comment_pos) {
+            if x.beg >= beg {
+                break;
+            }
+            let attribute = x.kind == Kind::Attribute;
+            let lines = self.dedent(x.beg, &x.text);
+            self.comment_pos += 1;
+            if attribute {
+                push(out, indent, "// TODO: migrate: attribute is not converted");
+                self.todos += 1;
+            }
+            for line in lines {
+                if attribute {
+                    push(out, indent, format!("// {line}"));
+                } else {
+                    push(out, indent, line);
+                }
+            }
+        }
+    }
+
+    /// Outputs notes added after `from`
+    fn flush_notes(&mut self, out: &mut Vec<String>, indent: usize, from: usize) {
+        for note in self.notes.split_off(from) {
+            push(out, indent, format!("// TODO: migrate: {note}"));
+            self.todos += 1;
+        }
+    }
+
+    fn todo(&mut self, out: &mut Vec<String>, indent: usize, reason: &str, beg: usize, raw: &str) {
+        push(out, indent, format!("// TODO: migrate: {reason}"));
+        self.todos += 1;
+        for line in self.dedent(beg, raw) {
+            push(out, indent, format!("// {line}").trim_end());
+        }
+    }
+
+    /// Skips comments placed before `end`
+    fn skip_comments(&mut self, end: usize) {
+        while self
+            .comments
+            .get(self.comment_pos)
+            .is_some_and(|x| x.beg < end)
+        {
+            self.comment_pos += 1;
+        }
+    }
+
+    /// Converts an item or a statement by `f`, or comments it out if it can't be converted
+    fn convert<F>(&mut self, out: &mut Vec<String>, indent: usize, f: F)
+    where
+        F: FnOnce(&mut Self, &mut Vec<String>, usize) -> Conv<()>,
+    {
+        // Keep blank line between items
+        let prev = if self.pos == 0 {
+            None
+        } else {
+            Some(self.tokens[self.pos - 1].end)
+        };
+        if let Some(prev) = prev {
+            let next = self
+                .comments
+                .get(self.comment_pos)
+                .map(|x| x.beg)
+                .unwrap_or(usize::MAX)
+                .clamp(prev, self.peek().beg);
+            let blank = self.input[prev..next].matches('\n').count() > 1;
+            let opened = out.last().is_some_and(|x| x.ends_with('{') || x.is_empty());
+            if blank && !out.is_empty() && !opened {
+                out.push(String::new());
+            }
+        }
+
+        self.flush_comments(out, indent);
+
+        if self.peek().kind == Kind::Directive {
+            let token = self.bump();
+            let directive = token.text.split_whitespace().next().unwrap_or_default();
+            if !matches!(directive, "`timescale" | "`default_nettype" | "`resetall") {
+                self.todo(
+                    out,
+                    indent,
+                    "preprocessor directive",
+                    token.beg,
+                    &token.text,
+                );
+            }
+            return;
+        }
+
+        let state = self.save();
+        let notes = state.notes;
+        let mut lines = Vec::new();
+        match f(self, &mut lines, indent) {
+            Ok(()) => {
+                self.flush_notes(out, indent, notes);
+                // Trailing comment in the same line
+                let end = self.tokens[self.pos - 1].end;
+                if let Some(x) = self.comments.get(self.comment_pos).filter(|x| x.beg >= end) {
+                    let same_line = !self.input[end..x.beg].contains('\n');
+                    if same_line && x.text.starts_with("//") && !lines.is_empty() {
+                        let last = lines.last_mut().unwrap();
+                        last.push(' ');
+                        last.push_str(&x.text);
+                        self.comment_pos += 1;
+                    }
+                }
+                out.append(&mut lines);
+            }
+            Err(Unsupported(reason)) => {
+                self.restore(state);
+                let beg = self.pos;
+                self.skip_item();
+                if self.pos == beg {
+                    self.bump();
+                }
+                let beg = self.tokens[beg].beg;
+                let end = self.tokens[self.pos - 1].end;
+                self.skip_comments(end);
+                let raw = &self.input[beg..end];
+                self.todo(out, indent, &reason, beg, raw);
+            }
+        }
+    }
+
+    /// Skips tokens until the end of the current item or statement
+    fn skip_item(&mut self) {
+        let mut depth = 0;
+        while !self.is_eof() {
+            let token = self.peek();
+            let text = token.text.as_str();
+            let is_ident = token.kind == Kind::Identifier;
+            let close = matches!(text, ")" | "]" | "}") && token.kind == Kind::Symbol
+                || is_ident && BLOCK_END.contains(&text);
+            if close && depth == 0 {
+                break;
+            }
+
+            let token = self.bump();
+            let text = token.text.as_str();
+            if token.kind == Kind::Symbol && matches!(text, "(" | "[" | "{" | "'{") {
+                depth += 1;
+            } else if close {
+                depth -= 1;
+                if depth == 0 && is_ident {
+                    self.eat_label();
+                    if !self.at("else") {
+                        break;
+                    }
+                }
+            } else if is_ident && BLOCK_BEGIN.contains(&text) {
+                depth += 1;
+            } else if token.is(";") && depth == 0 && !self.at("else") {
+                break;
+            }
+        }
+    }
+
+    // -----------------------------------------------------------------------------------------
+    // Description
+    // -----------------------------------------------------------------------------------------
+
+    fn description(&mut self, out: &mut Vec<String>, indent: usize) -> Conv<()> {
+        let token = self.peek().clone();
+        match token.text.as_str() {
+            "module" | "macromodule" => {
+                let state = self.save();
+                if let Err(Unsupported(reason)) = self.module(out, indent) {
+                    self.restore(state);
+                    out.clear();
+                    self.embed(out, indent, "endmodule", &reason);
+                }
+                Ok(())
+            }
+            "package" => self.package(out, indent),
+            "interface" => {
+                self.embed(out, indent, "endinterface", "interface is not converted");
+                Ok(())
+            }
+            "program" | "class" | "checker" | "primitive" | "config" => {
+                let end = format!("end{}", token.text);
+                let reason = format!("{} is not converted", token.text);
+                self.embed(out, indent, &end, &reason);
+                Ok(())
+            }
+            ";" => {
+                self.bump();
+                Ok(())
+            }
+            _ => unsupported(format!("'{}' at file level is not supported", token.text)),
+        }
+    }
+
+    /// Embeds the original SystemVerilog until `end` keyword
+    fn embed(&mut self, out: &mut Vec<String>, indent: usize, end: &str, reason: &str) {
+        let beg = self.pos;
+        while !self.is_eof() && !self.at(end) {
+            self.bump();
+        }
+        self.bump();
+        self.eat_label();
+
+        let beg = self.tokens[beg].beg;
+        let end = self.tokens[self.pos - 1].end;
+        self.skip_comments(end);
+
+        let notes = self.notes.len();
+        self.note(format!("{reason}; it is embedded as SystemVerilog"));
+        self.flush_notes(out, indent, notes);
+        push(out, indent, "embed (inline) sv {{{");
+        for line in self.dedent(beg, &self.input[beg..end]) {
+            out.push(line);
+        }
+        push(out, indent, "}}}");
+    }
+
+    fn module(&mut self, out: &mut Vec<String>, indent: usize) -> Conv<()> {
+        self.bump();
+        self.eat("automatic");
+        self.eat("static");
+        let name = self.ident()?;
+
+        let mut scope = Scope::default();
+        let mut body = Vec::new();
+        let enum_members = self.enum_members.clone();
+
+        while self.at("import") {
+            self.import(&mut body, indent + 1)?;
+        }
+        if self.eat("#") {
+            scope.header_params = true;
+            self.param_port_list(&mut scope)?;
+        }
+        if self.at("(") {
+            self.port_list(&mut scope)?;
+        }
+        self.expect(";")?;
+
+        // Comments in header are placed before module
+        let mut header = Vec::new();
+        self.flush_comments_before(&mut header, indent, self.tokens[self.pos - 1].end);
+
+        while !self.at("endmodule") {
+            if self.is_eof() {
+                return unsupported("'endmodule' is not found");
+            }
+            self.convert(&mut body, indent + 1, |s, out, indent| {
+                s.module_item(out, indent, &mut scope)
+            });
+        }
+        self.flush_comments(&mut body, indent + 1);
+        self.bump();
+        self.eat_label();
+        self.enum_members = enum_members;
+
+        // Apply clock and reset types inferred from always_ff
+        let types: Vec<_> = scope
+            .clocks
+            .iter()
+            .chain(scope.resets.iter())
+            .map(|(x, y)| (x.clone(), *y))
+            .collect();
+        for (name, ty) in &types {
+            for port in &mut scope.ports {
+                if &port.name == name && port.ty == "logic" && port.array.is_empty() {
+                    port.ty = ty.to_string();
+                }
+            }
+            let from = format!("var {name}: logic;");
+            for line in &mut body {
+                if line.trim() == from {
+                    *line = line.replace(&from, &format!("var {name}: {ty};"));
+                }
+            }
+        }
+
+        if scope.clocks.len() > 1 {
+            self.note("clock domain annotation is required for multiple clocks");
+        }
+        for port in &mut scope.ports {
+            if port.direction.is_empty() {
+                self.note(format!("direction of port '{}' is not found", port.name));
+                port.direction = "inout".to_string();
+            }
+        }
+
+        out.append(&mut header);
+        let mut head = format!("module {name}");
+        if !scope.params.is_empty() {
+            push(out, indent, format!("{head} #("));
+            for param in &scope.params {
+                push(out, indent + 1, param.render());
+            }
+            head = ")".to_string();
+        }
+        if !scope.ports.is_empty() {
+            push(out, indent, format!("{head} ("));
+            for port in &scope.ports {
+                push(out, indent + 1, port.render());
+            }
+            head = ")".to_string();
+        }
+        push(out, indent, format!("{head} {{"));
+        out.append(&mut body);
+        push(out, indent, "}");
+
+        Ok(())
+    }
+
+    fn package(&mut self, out: &mut Vec<String>, indent: usize) -> Conv<()> {
+        self.bump();
+        self.eat("automatic");
+        self.eat("static");
+        let name = self.ident()?;
+        self.expect(";")?;
+
+        // `parameter` in package is local
+        let mut scope = Scope {
+            header_params: true,
+            ..Default::default()
+        };
+        let mut body = Vec::new();
+        let mut enum_members = self.enum_members.clone();
+        while !self.at("endpackage") {
+            if self.is_eof() {
+                return unsupported("'endpackage' is not found");
+            }
+            self.convert(&mut body, indent + 1, |s, out, indent| {
+                s.module_item(out, indent, &mut scope)
+            });
+        }
+        self.flush_comments(&mut body, indent + 1);
+        self.bump();
+        self.eat_label();
+
+        // Members of enum in package are referred through the package outside it
+        for (member, path) in &self.enum_members {
+            if !enum_members.contains_key(member) {
+                let path = format!("{name}::{path}");
+                enum_members.insert(format!("{name}::{member}"), path.clone());
+                enum_members.insert(member.clone(), path);
+            }
+        }
+        self.enum_members = enum_members;
+
+        push(out, indent, format!("package {name} {{"));
+        out.append(&mut body);
+        push(out, indent, "}");
+        Ok(())
+    }
+
+    fn param_port_list(&mut self, scope: &mut Scope) -> Conv<()> {
+        self.expect("(")?;
+        let mut kind = "param";
+        while !self.at(")") {
+            if self.eat("parameter") {
+                kind = "param";
+            } else if self.eat("localparam") {
+                kind = "const";
+            }
+            let param = self.param_item(kind)?;
+            scope.params.push(param);
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")
+    }
+
+    fn param_item(&mut self, kind: &'static str) -> Conv<Param> {
+        if self.eat("type") {
+            let name = self.ident()?;
+            if !self.eat("=") {
+                return unsupported("type parameter without default type");
+            }
+            let value = match self.data_type()? {
+                Some(x) => x.render(),
+                None => {
+                    let mut path = self.ident()?;
+                    while self.eat("::") {
+                        path.push_str("::");
+                        path.push_str(&self.ident()?);
+                    }
+                    path
+                }
+            };
+            return Ok(Param {
+                kind,
+                name,
+                ty: "type".to_string(),
+                value,
+            });
+        }
+
+        let ty = self.data_type()?;
+        let name = self.ident()?;
+        if self.at("[") {
+            return unsupported("array parameter is not supported");
+        }
+        if !self.eat("=") {
+            return unsupported("parameter without default value");
+        }
+        let value = self.expr()?;
+        let ty = if let Some(ty) = ty {
+            ty.render()
+        } else if value.starts_with('"') {
+            "string".to_string()
+        } else if value.parse::<f64>().is_ok() && value.contains('.') {
+            "f64".to_string()
+        } else {
+            "u32".to_string()
+        };
+        Ok(Param {
+            kind,
+            name,
+            ty,
+            value,
+        })
+    }
+
+    fn port_list(&mut self, scope: &mut Scope) -> Conv<()> {
+        // Non-ANSI style port list whose directions are declared in body
+        let first = self.peek_n(1);
+        let second = self.peek_n(2);
+        if first.kind == Kind::Identifier
+            && !DATA_TYPES.contains(&first.text.as_str())
+            && !NET_TYPES.contains(&first.text.as_str())
+            && !matches!(first.text.as_str(), "input" | "output" | "inout" | "ref")
+            && (second.is(",") || second.is(")"))
+        {
+            self.bump();
+            loop {
+                let name = self.ident()?;
+                scope.ports.push(Port {
+                    name,
+                    direction: String::new(),
+                    ty: "logic".to_string(),
+                    array: String::new(),
+                });
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            return self.expect(")");
+        }
+
+        scope.ports = self.ansi_ports()?;
+        Ok(())
+    }
+
+    fn ansi_ports(&mut self) -> Conv<Vec<Port>> {
+        self.expect("(")?;
+        let mut ret = Vec::new();
+        let mut direction = "input".to_string();
+        let mut ty: Option<Type> = None;
+
+        while !self.at(")") {
+            let explicit = self.at_any(&["input", "output", "inout", "ref"]);
+            if explicit {
+                direction = self.bump().text;
+            }
+
+            if self.at("interface") {
+                return unsupported("generic interface port is not supported");
+            }
+
+            // Interface port with modport
+            if self.peek().kind == Kind::Identifier
+                && self.peek_n(1).is(".")
+                && self.peek_n(3).kind == Kind::Identifier
+            {
+                let interface = self.ident()?;
+                self.bump();
+                let modport = self.ident()?;
+                let name = self.ident()?;
+                let array = self.unpacked_dims()?;
+                ret.push(Port {
+                    name,
+                    direction: "modport".to_string(),
+                    ty: format!("{interface}::{modport}"),
+                    array,
+                });
+            } else {
+                match self.data_type()? {
+                    Some(x) => ty = Some(x),
+                    None if explicit => ty = Some(Type::logic()),
+                    None if ty.is_none() => {
+                        return unsupported("interface port is not supported");
+                    }
+                    None => (),
+                }
+                let name = self.ident()?;
+                let array = self.unpacked_dims()?;
+                if self.at("=") {
+                    return unsupported("default value of port is not supported");
+                }
+                ret.push(Port {
+                    name,
+                    direction: direction.clone(),
+                    ty: ty.as_ref().unwrap().render(),
+                    array,
+                });
+            }
+
+            if !self.eat(",") {
+                break;
+            }
+        }
+
+        self.expect(")")?;
+        Ok(ret)
+    }
+
+    // -----------------------------------------------------------------------------------------
+    // Module item
+    // -----------------------------------------------------------------------------------------
+
+    fn module_item(&mut self, out: &mut Vec<String>, indent: usize, scope: &mut Scope) -> Conv<()> {
+        let token = self.peek().clone();
+        if token.kind != Kind::Identifier {
+            if token.is(";") {
+                self.bump();
+                return Ok(());
+            }
+            return unsupported(format!("'{}' is not supported", token.text));
+        }
+
+        match token.text.as_str() {
+            "input" | "output" | "inout" => self.port_decl(scope),
+            "localparam" => {
+                self.bump();
+                self.const_decl(out, indent)
+            }
+            "parameter" => {
+                self.bump();
+                if scope.header_params {
+                    self.const_decl(out, indent)
+                } else {
+                    // `parameter` in body is overridable if there is no parameter list in header
+                    loop {
+                        let param = self.param_item("param")?;
+                        scope.params.push(param);
+                        if !self.eat(",") {
+                            break;
+                        }
+                    }
+                    self.expect(";")
+                }
+            }
+            "typedef" => self.typedef(out, indent),
+            "assign" => self.assign(out, indent),
+            "always" | "always_comb" | "always_latch" | "always_ff" => {
+                self.always(out, indent, scope)
+            }
+            "initial" | "final" => {
+                self.bump();
+                push(out, indent, format!("{} {{", token.text));
+                self.block(out, indent + 1, scope)?;
+                push(out, indent, "}");
+                Ok(())
+            }
+            "function" => self.function(out, indent, scope),
+            "genvar" | "timeunit" | "timeprecision" => {
+                while !self.is_eof() && !self.eat(";") {
+                    self.bump();
+                }
+                Ok(())
+            }
+            "generate" | "endgenerate" => {
+                self.bump();
+                Ok(())
+            }
+            "for" => self.generate_for(out, indent, scope),
+            "if" => self.generate_if(out, indent, scope),
+            "begin" => {
+                self.bump();
+                let label = if self.eat(":") {
+                    Some(self.ident()?)
+                } else {
+                    None
+                };
+                let mut lines = Vec::new();
+                self.generate_items(&mut lines, indent + 1, scope)?;
+                if let Some(label) = label {
+                    push(out, indent, format!(":{label} {{"));
+                    out.append(&mut lines);
+                    push(out, indent, "}");
+                } else {
+                    // Unnamed block can be flatten
+                    for line in lines {
+                        out.push(line.strip_prefix("    ").unwrap_or(&line).to_string());
+                    }
+                }
+                Ok(())
+            }
+            "import" => self.import(out, indent),
+            _ if self.is_type_start() => self.var_decl(out, indent, Some(scope), false),
+            _ if !DATA_TYPES.contains(&token.text.as_str()) && !token.text.starts_with('$') => {
+                self.instance(out, indent)
+            }
+            _ => unsupported(format!("'{}' is not supported", token.text)),
+        }
+    }
+
+    fn port_decl(&mut self, scope: &mut Scope) -> Conv<()> {
+        let direction = self.bump().text;
+        let ty = self.data_type()?.unwrap_or_else(Type::logic);
+        loop {
+            let name = self.ident()?;
+            let array = self.unpacked_dims()?;
+            let Some(port) = scope.ports.iter_mut().find(|x| x.name == name) else {
+                return unsupported(format!("port '{name}' is not in port list"));
+            };
+            port.direction = direction.clone();
+            port.ty = ty.render();
+            port.array = array;
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(";")
+    }
+
+    fn const_decl(&mut self, out: &mut Vec<String>, indent: usize) -> Conv<()> {
+        loop {
+            let param = self.param_item("const")?;
+            push(
+                out,
+                indent,
+                format!("const {}: {} = {};", param.name, param.ty, param.value),
+            );
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(";")
+    }
+
+    fn typedef(&mut self, out: &mut Vec<String>, indent: usize) -> Conv<()> {
+        self.bump();
+        if self.eat("enum") {
+            let ty = self.data_type()?;
+            self.expect("{")?;
+            let mut items = Vec::new();
+            let mut members = Vec::new();
+            while !self.at("}") {
+                let name = self.ident()?;
+                if self.at("[") {
+                    return unsupported("enum item with range is not supported");
+                }
+                members.push(name.clone());
+                let item = if self.eat("=") {
+                    format!("{name} = {},", self.expr()?)
+                } else {
+                    format!("{name},")
+                };
+                items.push(item);
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect("}")?;
+            let name = self.ident()?;
+            self.expect(";")?;
+
+            for member in &members {
+                self.enum_members
+                    .insert(member.clone(), format!("{name}::{member}"));
+            }
+
+            let ty = ty.map(|x| format!(": {}", x.render())).unwrap_or_default();
+            push(out, indent, format!("enum {name}{ty} {{"));
+            for item in items {
+                push(out, indent + 1, item);
+            }
+            push(out, indent, "}");
+        } else if self.at_any(&["struct", "union"]) {
+            let kind = self.bump().text;
+            self.eat("packed");
+            if self.at_any(&["signed", "unsigned"]) {
+                return unsupported(format!("signed {kind} is not supported"));
+            }
+            self.expect("{")?;
+            let mut members = Vec::new();
+            while !self.at("}") {
+                let Some(ty) = self.data_type()? else {
+                    return unsupported(format!("{kind} member without type"));
+                };
+                loop {
+                    let name = self.ident()?;
+                    if self.at("[") {
+                        return unsupported(format!("array in {kind} member is not supported"));
+                    }
+                    members.push(format!("{name}: {},", ty.render()));
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+                self.expect(";")?;
+            }
+            self.expect("}")?;
+            let name = self.ident()?;
+            self.expect(";")?;
+
+            push(out, indent, format!("{kind} {name} {{"));
+            for member in members {
+                push(out, indent + 1, member);
+            }
+            push(out, indent, "}");
+        } else {
+            let Some(ty) = self.data_type()? else {
+                return unsupported("typedef without type");
+            };
+            let name = self.ident()?;
+            let array = self.unpacked_dims()?;
+            self.expect(";")?;
+            push(
+                out,
+                indent,
+                format!("type {name} = {}{array};", ty.render()),
+            );
+        }
+        Ok(())
+    }
+
+    fn assign(&mut self, out: &mut Vec<String>, indent: usize) -> Conv<()> {
+        self.bump();
+        if self.at_any(&["#", "("]) {
+            return unsupported("delay or strength of assign is not supported");
+        }
+        loop {
+            let lhs = self.lvalue()?;
+            self.expect("=")?;
+            let rhs = self.expr()?;
+            push(out, indent, format!("assign {lhs} = {rhs};"));
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(";")
+    }
+
+    fn import(&mut self, out: &mut Vec<String>, indent: usize) -> Conv<()> {
+        self.bump();
+        loop {
+            let package = self.ident()?;
+            self.expect("::")?;
+            let item = if self.eat("*") {
+                "*".to_string()
+            } else {
+                self.ident()?
+            };
+            push(out, indent, format!("import {package}::{item};"));
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(";")
+    }
+
+    fn always(&mut self, out: &mut Vec<String>, indent: usize, scope: &mut Scope) -> Conv<()> {
+        let keyword = self.bump().text;
+
+        let mut events = Vec::new();
+        let mut star = false;
+        if self.eat("@") {
+            if self.eat("*") {
+                star = true;
+            } else {
+                self.expect("(")?;
+                if self.eat("*") {
+                    star = true;
+                } else {
+                    loop {
+                        let edge = if self.at_any(&["posedge", "negedge"]) {
+                            Some(self.bump().text)
+                        } else {
+                            None
+                        };
+                        let name = self.lvalue()?;
+                        events.push((edge, name));
+                        if !self.eat("or") && !self.eat(",") {
+                            break;
+                        }
+                    }
+                }
+                self.expect(")")?;
+            }
+        }
+
+        let edges = events.iter().filter(|x| x.0.is_some()).count();
+        let comb = match keyword.as_str() {
+            "always_comb" => true,
+            "always_latch" => {
+                self.note("always_latch is converted to always_comb");
+                true
+            }
+            "always_ff" => false,
+            _ if edges == 0 && !star && events.is_empty() => {
+                return unsupported("always without event control is not supported");
+            }
+            _ => edges == 0,
+        };
+
+        if comb {
+            if !star && !events.is_empty() {
+                self.note("sensitivity list is replaced by always_comb");
+            }
+            push(out, indent, "always_comb {");
+            self.block(out, indent + 1, scope)?;
+            push(out, indent, "}");
+            return Ok(());
+        }
+
+        if edges != events.len() || edges > 2 {
+            return unsupported("sensitivity list of always_ff is not supported");
+        }
+
+        let (edge, clock) = &events[0];
+        let clock_type = if edge.as_deref() == Some("posedge") {
+            "clock_posedge"
+        } else {
+            "clock_negedge"
+        };
+        scope.clocks.insert(clock.clone(), clock_type);
+
+        let mut reset = None;
+        if let Some((edge, name)) = events.get(1) {
+            let low = edge.as_deref() == Some("negedge");
+            let reset_type = if low {
+                "reset_async_low"
+            } else {
+                "reset_async_high"
+            };
+            scope.resets.insert(name.clone(), reset_type);
+            reset = Some(name.clone());
+        } else if let Some((name, low)) = self.sync_reset() {
+            let reset_type = if low {
+                "reset_sync_low"
+            } else {
+                "reset_sync_high"
+            };
+            scope.resets.insert(name.clone(), reset_type);
+            reset = Some(name);
+        }
+
+        let list = if let Some(ref reset) = reset {
+            format!("{clock}, {reset}")
+        } else {
+            clock.clone()
+        };
+        push(out, indent, format!("always_ff ({list}) {{"));
+
+        let begin = self.eat("begin");
+        if begin {
+            self.eat_label();
+        }
+        let mut first = true;
+        loop {
+            if begin && self.at("end") {
+                break;
+            }
+            if self.is_eof() {
+                return unsupported("'end' is not found");
+            }
+            let is_reset = first
+                && reset
+                    .as_ref()
+                    .is_some_and(|x| self.reset_if(0).is_some_and(|y| &y.0 == x));
+            if is_reset {
+                self.flush_comments(out, indent + 1);
+                let (_, _, len) = self.reset_if(0).unwrap();
+                for _ in 0..len {
+                    self.bump();
+                }
+                self.if_statement(out, indent + 1, scope, "if_reset".to_string())?;
+            } else {
+                self.convert(out, indent + 1, |s, out, indent| {
+                    s.statement(out, indent, scope)
+                });
+            }
+            first = false;
+            if !begin {
+                break;
+            }
+        }
+        if begin {
+            self.flush_comments(out, indent + 1);
+            self.bump();
+            self.eat_label();
+        }
+        push(out, indent, "}");
+        Ok(())
+    }
+
+    /// Returns the name, polarity and the number of tokens of `if (rst)` like condition
+    fn reset_if(&self, offset: usize) -> Option<(String, bool, usize)> {
+        if !self.peek_n(offset).is("if") || !self.peek_n(offset + 1).is("(") {
+            return None;
+        }
+        let mut i = offset + 2;
+        let negated = self.peek_n(i).is("!") || self.peek_n(i).is("~");
+        if negated {
+            i += 1;
+        }
+        let name = self.peek_n(i);
+        if name.kind != Kind::Identifier {
+            return None;
+        }
+        let name = escape(&name.text).ok()?;
+        i += 1;
+
+        let mut low = negated;
+        if !negated && (self.peek_n(i).is("==") || self.peek_n(i).is("===")) {
+            let value = &self.peek_n(i + 1).text;
+            low = match value.as_str() {
+                "0" | "1'b0" | "'0" => true,
+                "1" | "1'b1" | "'1" => false,
+                _ => return None,
+            };
+            i += 2;
+        }
+        if !self.peek_n(i).is(")") {
+            return None;
+        }
+        Some((name, low, i + 1 - offset))
+    }
+
+    /// Detects synchronous reset by the first `if` statement having reset like name
+    fn sync_reset(&self) -> Option<(String, bool)> {
+        let mut offset = 0;
+        if self.peek().is("begin") {
+            offset = 1;
+            if self.peek_n(1).is(":") {
+                offset = 3;
+            }
+        }
+        let (name, low, _) = self.reset_if(offset)?;
+        let lower = name.to_ascii_lowercase();
+        if lower.contains("rst") || lower.contains("reset") {
+            Some((name, low))
+        } else {
+            None
+        }
+    }
+
+    fn function(&mut self, out: &mut Vec<String>, indent: usize, scope: &mut Scope) -> Conv<()> {
+        self.bump();
+        self.eat("automatic");
+        self.eat("static");
+        let ret = if self.eat("void") {
+            None
+        } else {
+            self.data_type()?
+        };
+        let name = self.ident()?;
+        let ports = if self.at("(") {
+            self.ansi_ports()?
+        } else {
+            Vec::new()
+        };
+        self.expect(";")?;
+        if self.at_any(&["input", "output", "inout"]) {
+            return unsupported("non-ANSI style function port is not supported");
+        }
+
+        let mut body = Vec::new();
+        scope.function = Some(name.clone());
+        while !self.at("endfunction") {
+            if self.is_eof() {
+                scope.function = None;
+                return unsupported("'endfunction' is not found");
+            }
+            self.convert(&mut body, indent + 1, |s, out, indent| {
+                s.statement(out, indent, scope)
+            });
+        }
+        scope.function = None;
+        self.flush_comments(&mut body, indent + 1);
+        self.bump();
+        self.eat_label();
+
+        let ret = ret
+            .map(|x| format!(" -> {}", x.render()))
+            .unwrap_or_default();
+        if ports.is_empty() {
+            push(out, indent, format!("function {name}{ret} {{"));
+        } else {
+            push(out, indent, format!("function {name} ("));
+            for port in &ports {
+                push(out, indent + 1, port.render());
+            }
+            push(out, indent, format!("){ret} {{"));
+        }
+        out.append(&mut body);
+        push(out, indent, "}");
+        Ok(())
+    }
+
+    fn generate_items(
+        &mut self,
+        out: &mut Vec<String>,
+        indent: usize,
+        scope: &mut Scope,
+    ) -> Conv<()> {
+        while !self.at("end") {
+            if self.is_eof() {
+                return unsupported("'end' is not found");
+            }
+            self.convert(out, indent, |s, out, indent| {
+                s.module_item(out, indent, scope)
+            });
+        }
+        self.flush_comments(out, indent);
+        self.bump();
+        self.eat_label();
+        Ok(())
+    }
+
+    /// Returns the label and the items of generate block
+    fn generate_block(
+        &mut self,
+        indent: usize,
+        scope: &mut Scope,
+    ) -> Conv<(Option<String>, Vec<String>)> {
+        let mut lines = Vec::new();
+        if self.eat("begin") {
+            let label = if self.eat(":") {
+                Some(self.ident()?)
+            } else {
+                None
+            };
+            self.generate_items(&mut lines, indent, scope)?;
+            Ok((label, lines))
+        } else {
+            self.convert(&mut lines, indent, |s, out, indent| {
+                s.module_item(out, indent, scope)
+            });
+            Ok((None, lines))
+        }
+    }
+
+    fn generate_if(&mut self, out: &mut Vec<String>, indent: usize, scope: &mut Scope) -> Conv<()> {
+        self.bump();
+        self.expect("(")?;
+        let cond = self.expr()?;
+        self.expect(")")?;
+        let (label, mut lines) = self.generate_block(indent + 1, scope)?;
+        let label = label.unwrap_or_else(|| self.new_label());
+        push(out, indent, format!("if {cond} :{label} {{"));
+        out.append(&mut lines);
+
+        while self.eat("else") {
+            let head = if self.eat("if") {
+                self.expect("(")?;
+                format!("}} else if {}", self.expr()?)
+            } else {
+                "} else".to_string()
+            };
+            if head.starts_with("} else if") {
+                self.expect(")")?;
+            }
+            let (else_label, mut lines) = self.generate_block(indent + 1, scope)?;
+            // The same label as `if` is omitted
+            let else_label = else_label
+                .filter(|x| x != &label)
+                .map(|x| format!(" :{x}"))
+                .unwrap_or_default();
+            push(out, indent, format!("{head}{else_label} {{"));
+            out.append(&mut lines);
+            if head == "} else" {
+                break;
+            }
+        }
+        push(out, indent, "}");
+        Ok(())
+    }
+
+    fn generate_for(
+        &mut self,
+        out: &mut Vec<String>,
+        indent: usize,
+        scope: &mut Scope,
+    ) -> Conv<()> {
+        let (var, range) = self.for_header()?;
+        let (label, mut lines) = self.generate_block(indent + 1, scope)?;
+        let label = label.unwrap_or_else(|| self.new_label());
+        push(out, indent, format!("for {var} in {range} :{label} {{"));
+        out.append(&mut lines);
+        push(out, indent, "}");
+        Ok(())
+    }
+
+    /// Returns the loop variable and the range of `for (i = 0; i < N; i++)`
+    fn for_header(&mut self) -> Conv<(String, String)> {
+        self.bump();
+        self.expect("(")?;
+        self.eat("genvar");
+        self.eat("automatic");
+        if self.is_type_start() {
+            self.data_type()?;
+        }
+        let var = self.ident()?;
+        self.expect("=")?;
+        let from = self.expr()?;
+        self.expect(";")?;
+
+        if self.ident()? != var {
+            return unsupported("loop condition is not supported");
+        }
+        let range = match self.bump().text.as_str() {
+            "<" => "..",
+            "<=" => "..=",
+            _ => return unsupported("loop condition is not supported"),
+        };
+        let to = self.binary(0)?;
+        self.expect(";")?;
+
+        let step = if self.eat("++") {
+            self.ident()?;
+            "1".to_string()
+        } else {
+            if self.ident()? != var {
+                return unsupported("loop step is not supported");
+            }
+            if self.eat("++") {
+                "1".to_string()
+            } else if self.eat("+=") {
+                self.expr()?
+            } else if self.eat("=") {
+                if self.ident()? != var || !self.eat("+") {
+                    return unsupported("loop step is not supported");
+                }
+                self.expr()?
+            } else {
+                return unsupported("loop step is not supported");
+            }
+        };
+        self.expect(")")?;
+
+        let mut range = format!("{from}{range}{to}");
+        if step != "1" {
+            range.push_str(&format!(" step += {step}"));
+        }
+        Ok((var, range))
+    }
+
+    fn instance(&mut self, out: &mut Vec<String>, indent: usize) -> Conv<()> {
+        let mut module = self.ident()?;
+        while self.eat("::") {
+            module.push_str("::");
+            module.push_str(&self.ident()?);
+        }
+
+        let params = if self.eat("#") {
+            if !self.at("(") {
+                return unsupported("delay of instance is not supported");
+            }
+            self.connections("parameter")?
+        } else {
+            Vec::new()
+        };
+
+        loop {
+            let name = self.ident()?;
+            let array = self.unpacked_dims()?;
+            let ports = self.connections("port")?;
+
+            let mut head = format!("inst {name}: {module}{array}");
+            if !params.is_empty() {
+                push(out, indent, format!("{head} #("));
+                for param in &params {
+                    push(out, indent + 1, format!("{param},"));
+                }
+                head = ")".to_string();
+            }
+            if ports.is_empty() {
+                push(out, indent, format!("{head};"));
+            } else {
+                push(out, indent, format!("{head} ("));
+                for port in &ports {
+                    push(out, indent + 1, format!("{port},"));
+                }
+                push(out, indent, ");");
+            }
+
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(";")
+    }
+
+    /// Returns named connections of parameters or ports
+    fn connections(&mut self, kind: &str) -> Conv<Vec<String>> {
+        self.expect("(")?;
+        let mut ret = Vec::new();
+        while !self.at(")") {
+            if self.at(".*") {
+                return unsupported(format!("'.*' {kind} connection is not supported"));
+            }
+            if !self.eat(".") {
+                return unsupported(format!("positional {kind} connection is not supported"));
+            }
+            let name = self.ident()?;
+            if self.eat("(") {
+                if self.eat(")") {
+                    ret.push(format!("{name}: _"));
+                } else {
+                    let expr = self.expr()?;
+                    self.expect(")")?;
+                    ret.push(format!("{name}: {expr}"));
+                }
+            } else {
+                ret.push(name);
+            }
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        Ok(ret)
+    }
+
+    // -----------------------------------------------------------------------------------------
+    // Declaration and type
+    // -----------------------------------------------------------------------------------------
+
+    /// Checks whether a data declaration starts at the current position
+    fn is_type_start(&self) -> bool {
+        let token = self.peek();
+        if token.kind != Kind::Identifier {
+            return false;
+        }
+        let text = token.text.as_str();
+        if DATA_TYPES.contains(&text) || NET_TYPES.contains(&text) || text == "automatic" {
+            return true;
+        }
+        self.user_type_len().is_some()
+    }
+
+    /// Returns the number of tokens of user defined type followed by a declared name
+    fn user_type_len(&self) -> Option<usize> {
+        let mut i = 0;
+        if self.peek_n(i).kind != Kind::Identifier || self.peek_n(i).text.starts_with('$') {
+            return None;
+        }
+        i += 1;
+        while self.peek_n(i).is("::") && self.peek_n(i + 1).kind == Kind::Identifier {
+            i += 2;
+        }
+        let len = i;
+        // Packed dimensions
+        while self.peek_n(i).is("[") {
+            let mut depth = 0;
+            loop {
+                let token = self.peek_n(i);
+                if token.text.is_empty() {
+                    return None;
+                }
+                if token.is("[") {
+                    depth += 1;
+                } else if token.is("]") {
+                    depth -= 1;
+                }
+                i += 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+        }
+        // Declared name followed by delimiter excludes instance
+        let name = self.peek_n(i);
+        let next = self.peek_n(i + 1);
+        let is_decl = name.kind == Kind::Identifier
+            && (next.is(";") || next.is(",") || next.is("=") || next.is("[") || next.is(")"));
+        is_decl.then_some(len)
+    }
+
+    fn data_type(&mut self) -> Conv<Option<Type>> {
+        let mut base: Option<String> = None;
+        let mut found = false;
+        let mut signed = None;
+        let mut widths = Vec::new();
+
+        loop {
+            let token = self.peek();
+            if token.kind != Kind::Identifier {
+                break;
+            }
+            let text = token.text.as_str();
+            match text {
+                "var" => (),
+                _ if NET_TYPES.contains(&text) => (),
+                "reg" | "logic" => base = Some("logic".to_string()),
+                "bit" => base = Some("bit".to_string()),
+                "int" | "integer" => base = Some("i32".to_string()),
+                "longint" => base = Some("i64".to_string()),
+                "shortint" | "byte" => {
+                    base = Some("logic".to_string());
+                    widths.push(if text == "byte" { "8" } else { "16" }.to_string());
+                    signed.get_or_insert(true);
+                }
+                "real" | "realtime" => base = Some("f64".to_string()),
+                "shortreal" => base = Some("f32".to_string()),
+                "string" => base = Some("string".to_string()),
+                "time" => base = Some("u64".to_string()),
+                "signed" => signed = Some(true),
+                "unsigned" => signed = Some(false),
+                _ => break,
+            }
+            found = true;
+            self.bump();
+        }
+
+        if !found {
+            if let Some(len) = self.user_type_len() {
+                let mut path = Vec::new();
+                for i in 0..len {
+                    if i % 2 == 0 {
+                        path.push(self.ident()?);
+                    } else {
+                        self.bump();
+                    }
+                }
+                base = Some(path.join("::"));
+            }
+        }
+
+        while self.at("[") {
+            widths.push(self.dimension(true)?);
+        }
+
+        if !found && base.is_none() && widths.is_empty() {
+            return Ok(None);
+        }
+
+        let mut base = base.unwrap_or_else(|| "logic".to_string());
+        if signed == Some(false) && matches!(base.as_str(), "i32" | "i64") {
+            base = base.replace('i', "u");
+        }
+        let mut signed = signed.unwrap_or(false);
+        if matches!(
+            base.as_str(),
+            "i32" | "i64" | "u32" | "u64" | "f32" | "f64" | "string"
+        ) {
+            if !widths.is_empty() {
+                return unsupported("packed dimension of fixed type is not supported");
+            }
+            signed = false;
+        }
+
+        Ok(Some(Type {
+            base,
+            signed,
+            widths,
+        }))
+    }
+
+    /// Returns the width of `[msb:lsb]`
+    fn dimension(&mut self, packed: bool) -> Conv<String> {
+        self.expect("[")?;
+        let msb = self.expr()?;
+        let ret = if self.eat(":") {
+            let lsb = self.expr()?;
+            self.range_width(&msb, &lsb, packed)
+        } else {
+            msb
+        };
+        self.expect("]")?;
+        Ok(ret)
+    }
+
+    fn unpacked_dims(&mut self) -> Conv<String> {
+        let mut dims = Vec::new();
+        while self.at("[") {
+            dims.push(self.dimension(false)?);
+        }
+        if dims.is_empty() {
+            Ok(String::new())
+        } else {
+            Ok(format!(" [{}]", dims.join(", ")))
+        }
+    }
+
+    fn range_width(&mut self, msb: &str, lsb: &str, packed: bool) -> String {
+        if lsb == "0" {
+            plus_one(msb)
+        } else if msb == "0" {
+            // Ascending range is usual for unpacked array
+            if packed {
+                self.note(format!("ascending range [0:{lsb}] is converted to width"));
+            }
+            plus_one(lsb)
+        } else {
+            self.note(format!("range [{msb}:{lsb}] is converted to width"));
+            format!("{msb} - {lsb} + 1")
+        }
+    }
+
+    fn var_decl(
+        &mut self,
+        out: &mut Vec<String>,
+        indent: usize,
+        mut scope: Option<&mut Scope>,
+        procedural: bool,
+    ) -> Conv<()> {
+        self.eat("automatic");
+        let is_net = self.at_any(NET_TYPES);
+        let ty = self.data_type()?.unwrap_or_else(Type::logic);
+        loop {
+            let name = self.ident()?;
+            let array = self.unpacked_dims()?;
+            let ty = format!("{}{array}", ty.render());
+
+            // Redeclaration of non-ANSI port
+            let port = scope
+                .as_mut()
+                .and_then(|x| x.ports.iter_mut().find(|x| x.name == name));
+            if let Some(port) = port {
+                if port.ty == "logic" {
+                    port.ty = ty;
+                }
+            } else if self.eat("=") {
+                let value = self.expr()?;
+                if is_net || procedural {
+                    push(out, indent, format!("let {name}: {ty} = {value};"));
+                } else {
+                    self.note(format!("initial value of '{name}' is dropped"));
+                    push(out, indent, format!("var {name}: {ty};"));
+                }
+            } else {
+                push(out, indent, format!("var {name}: {ty};"));
+            }
+
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(";")
+    }
+
+    // -----------------------------------------------------------------------------------------
+    // Statement
+    // -----------------------------------------------------------------------------------------
+
+    /// Converts `begin` ... `end` or a single statement
+    fn block(&mut self, out: &mut Vec<String>, indent: usize, scope: &mut Scope) -> Conv<()> {
+        if self.eat("begin") {
+            self.eat_label();
+            while !self.at("end") {
+                if self.is_eof() {
+                    return unsupported("'end' is not found");
+                }
+                self.convert(out, indent, |s, out, indent| {
+                    s.statement(out, indent, scope)
+                });
+            }
+            self.flush_comments(out, indent);
+            self.bump();
+            self.eat_label();
+        } else {
+            self.convert(out, indent, |s, out, indent| {
+                s.statement(out, indent, scope)
+            });
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self, out: &mut Vec<String>, indent: usize, scope: &mut Scope) -> Conv<()> {
+        let token = self.peek().clone();
+        match token.text.as_str() {
+            ";" => {
+                self.bump();
+                Ok(())
+            }
+            "begin" => self.block(out, indent, scope),
+            "if" => {
+                self.bump();
+                self.expect("(")?;
+                let cond = self.expr()?;
+                self.expect(")")?;
+                self.if_statement(out, indent, scope, format!("if {cond}"))
+            }
+            "unique" | "unique0" | "priority" => {
+                self.bump();
+                self.note(format!("'{}' is removed", token.text));
+                self.statement(out, indent, scope)
+            }
+            "case" | "casez" | "casex" => self.case_statement(out, indent, scope),
+            "for" => {
+                let (var, range) = self.for_header()?;
+                push(out, indent, format!("for {var}: u32 in {range} {{"));
+                self.block(out, indent + 1, scope)?;
+                push(out, indent, "}");
+                Ok(())
+            }
+            "return" => {
+                self.bump();
+                if self.at(";") {
+                    return unsupported("return without value is not supported");
+                }
+                let value = self.expr()?;
+                self.expect(";")?;
+                push(out, indent, format!("return {value};"));
+                Ok(())
+            }
+            "break" => {
+                self.bump();
+                self.expect(";")?;
+                push(out, indent, "break;");
+                Ok(())
+            }
+            _ if token.kind == Kind::Identifier && self.is_type_start() => {
+                self.var_decl(out, indent, None, true)
+            }
+            _ if token.kind == Kind::Identifier => self.identifier_statement(out, indent, scope),
+            _ => unsupported(format!("'{}' is not supported", token.text)),
+        }
+    }
+
+    fn if_statement(
+        &mut self,
+        out: &mut Vec<String>,
+        indent: usize,
+        scope: &mut Scope,
+        head: String,
+    ) -> Conv<()> {
+        push(out, indent, format!("{head} {{"));
+        self.block(out, indent + 1, scope)?;
+        while self.eat("else") {
+            if self.eat("if") {
+                self.expect("(")?;
+                let cond = self.expr()?;
+                self.expect(")")?;
+                push(out, indent, format!("}} else if {cond} {{"));
+                self.block(out, indent + 1, scope)?;
+            } else {
+                push(out, indent, "} else {");
+                self.block(out, indent + 1, scope)?;
+                break;
+            }
+        }
+        push(out, indent, "}");
+        Ok(())
+    }
+
+    fn case_statement(
+        &mut self,
+        out: &mut Vec<String>,
+        indent: usize,
+        scope: &mut Scope,
+    ) -> Conv<()> {
+        let keyword = self.bump().text;
+        if keyword != "case" {
+            self.note(format!(
+                "'{keyword}' is converted to 'case'; check wildcard patterns"
+            ));
+        }
+        self.expect("(")?;
+        let value = self.expr()?;
+        self.expect(")")?;
+        if self.at("inside") {
+            return unsupported("case inside is not supported");
+        }
+
+        push(out, indent, format!("case {value} {{"));
+        while !self.at("endcase") {
+            if self.is_eof() {
+                return unsupported("'endcase' is not found");
+            }
+            self.flush_comments(out, indent + 1);
+            let cond = if self.eat("default") {
+                self.eat(":");
+                "default".to_string()
+            } else {
+                let mut conds = vec![self.expr()?];
+                while self.eat(",") {
+                    conds.push(self.expr()?);
+                }
+                self.expect(":")?;
+                conds.join(", ")
+            };
+
+            let mut lines = Vec::new();
+            self.block(&mut lines, indent + 2, scope)?;
+            if lines.is_empty() {
+                push(out, indent + 1, format!("{cond}: {{}}"));
+            } else if lines.len() == 1 && !lines[0].trim_start().starts_with("//") {
+                push(
+                    out,
+                    indent + 1,
+                    format!("{cond}: {}", lines[0].trim_start()),
+                );
+            } else {
+                push(out, indent + 1, format!("{cond}: {{"));
+                out.append(&mut lines);
+                push(out, indent + 1, "}");
+            }
+        }
+        self.flush_comments(out, indent + 1);
+        self.bump();
+        push(out, indent, "}");
+        Ok(())
+    }
+
+    fn identifier_statement(
+        &mut self,
+        out: &mut Vec<String>,
+        indent: usize,
+        scope: &mut Scope,
+    ) -> Conv<()> {
+        let lhs = self.lvalue()?;
+
+        if self.at("(") {
+            let args = self.arguments()?;
+            self.expect(";")?;
+            push(out, indent, format!("{lhs}{args};"));
+            return Ok(());
+        }
+        if lhs.starts_with('$') && self.eat(";") {
+            push(out, indent, format!("{lhs}();"));
+            return Ok(());
+        }
+        if self.eat("++") || self.eat("--") {
+            let op = if self.tokens[self.pos - 1].is("++") {
+                "+="
+            } else {
+                "-="
+            };
+            self.expect(";")?;
+            push(out, indent, format!("{lhs} {op} 1;"));
+            return Ok(());
+        }
+
+        let op = self.peek().text.clone();
+        let op = match op.as_str() {
+            "=" | "<=" => "=",
+            "+=" | "-=" | "*=" | "/=" | "%=" | "&=" | "|=" | "^=" | "<<=" | ">>=" | "<<<="
+            | ">>>=" => op.as_str(),
+            _ => return unsupported(format!("unsupported syntax near '{op}'")),
+        }
+        .to_string();
+        self.bump();
+        if self.at_any(&["#", "@"]) {
+            return unsupported("intra-assignment delay is not supported");
+        }
+        let rhs = self.expr()?;
+        self.expect(";")?;
+
+        // Assignment to function name is the return value
+        if scope.function.as_ref() == Some(&lhs) && op == "=" {
+            if !self.at_any(&["endfunction", "end"]) {
+                self.note("assignment to function name is converted to 'return'");
+            }
+            push(out, indent, format!("return {rhs};"));
+        } else {
+            push(out, indent, format!("{lhs} {op} {rhs};"));
+        }
+        Ok(())
+    }
+
+    // -----------------------------------------------------------------------------------------
+    // Expression
+    // -----------------------------------------------------------------------------------------
+
+    fn lvalue(&mut self) -> Conv<String> {
+        if self.at("{") {
+            return unsupported("concatenation in left-hand side is not supported");
+        }
+        let token = self.peek();
+        let mut ret = if token.kind == Kind::Identifier && token.text.starts_with('$') {
+            self.bump().text
+        } else {
+            self.ident()?
+        };
+        while self.eat("::") {
+            ret.push_str("::");
+            ret.push_str(&self.ident()?);
+        }
+        loop {
+            if self.at("[") {
+                ret.push_str(&self.select()?);
+            } else if self.at(".") && self.peek_n(1).kind == Kind::Identifier {
+                self.bump();
+                ret.push('.');
+                ret.push_str(&self.ident()?);
+            } else {
+                break;
+            }
+        }
+        Ok(ret)
+    }
+
+    fn select(&mut self) -> Conv<String> {
+        self.expect("[")?;
+        let mut ret = format!("[{}", self.expr()?);
+        if self.at_any(&[":", "+:", "-:"]) {
+            let op = self.bump().text;
+            ret.push_str(&format!("{op}{}", self.expr()?));
+        }
+        self.expect("]")?;
+        ret.push(']');
+        Ok(ret)
+    }
+
+    fn arguments(&mut self) -> Conv<String> {
+        self.expect("(")?;
+        let mut args = Vec::new();
+        while !self.at(")") {
+            args.push(self.expr()?);
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        Ok(format!("({})", args.join(", ")))
+    }
+
+    fn expr(&mut self) -> Conv<String> {
+        let cond = self.binary(0)?;
+        if self.eat("?") {
+            let x = self.expr()?;
+            self.expect(":")?;
+            let y = self.expr()?;
+            // Nested conditional operator is converted to `else if`
+            if y.starts_with("if ") {
+                Ok(format!("if {cond} {{ {x} }} else {y}"))
+            } else {
+                Ok(format!("if {cond} {{ {x} }} else {{ {y} }}"))
+            }
+        } else {
+            Ok(cond)
+        }
+    }
+
+    fn binary(&mut self, min: u8) -> Conv<String> {
+        let mut ret = self.unary()?;
+        loop {
+            let token = self.peek();
+            if token.is("inside") {
+                return unsupported("inside operator is not supported");
+            }
+            if token.kind != Kind::Symbol {
+                break;
+            }
+            let Some(prec) = precedence(&token.text) else {
+                break;
+            };
+            if prec < min {
+                break;
+            }
+            let op = self.bump().text;
+            let rhs = self.binary(prec + 1)?;
+            let op = match op.as_str() {
+                "<" => "<:",
+                ">" => ">:",
+                x => x,
+            };
+            ret = format!("{ret} {op} {rhs}");
+        }
+        Ok(ret)
+    }
+
+    fn unary(&mut self) -> Conv<String> {
+        const UNARY: &[&str] = &["!", "~", "&", "|", "^", "~&", "~|", "~^", "^~", "-", "+"];
+        if self.peek().kind == Kind::Symbol && self.at_any(UNARY) {
+            let op = self.bump().text;
+            let x = self.unary()?;
+            Ok(format!("{op}{x}"))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Conv<String> {
+        let token = self.peek().clone();
+        let ret = match token.kind {
+            Kind::Number => {
+                self.bump();
+                number(&token.text)?
+            }
+            Kind::String => {
+                self.bump();
+                token.text
+            }
+            Kind::Macro => {
+                self.bump();
+                self.note(format!("macro '{}' is converted to identifier", token.text));
+                let mut ret = token.text[1..].to_string();
+                if self.at("(") {
+                    ret.push_str(&self.arguments()?);
+                }
+                ret
+            }
+            Kind::Identifier => {
+                let mut ret = self.lvalue()?;
+                if let Some(x) = self.enum_members.get(&ret) {
+                    ret = x.clone();
+                }
+                if self.at("(") {
+                    ret.push_str(&self.arguments()?);
+                }
+                ret
+            }
+            _ if token.is("(") => {
+                self.bump();
+                let ret = self.expr()?;
+                self.expect(")")?;
+                format!("({ret})")
+            }
+            _ if token.is("{") => format!("{{{}}}", self.concatenation()?.join(", ")),
+            _ if token.is("'{") => {
+                self.bump();
+                let mut items = Vec::new();
+                while !self.at("}") {
+                    if self.eat("default") {
+                        self.expect(":")?;
+                        items.push(format!("default: {}", self.expr()?));
+                    } else {
+                        items.push(self.expr()?);
+                    }
+                    if !self.eat(",") {
+                        break;
+                    }
+                }
+                self.expect("}")?;
+                format!("'{{{}}}", items.join(", "))
+            }
+            _ => return unsupported(format!("'{}' in expression is not supported", token.text)),
+        };
+
+        // Casting
+        if self.at("'") && self.peek_n(1).is("(") {
+            self.bump();
+            self.bump();
+            let x = self.expr()?;
+            self.expect(")")?;
+            let ty = match ret.as_str() {
+                "int" | "integer" => Some("i32"),
+                "signed" | "unsigned" | "logic" | "bit" => None,
+                _ if token.kind == Kind::Identifier => Some(ret.as_str()),
+                _ => None,
+            };
+            if let Some(ty) = ty {
+                return Ok(format!("({x}) as {ty}"));
+            } else {
+                self.note(format!("cast to '{ret}' is removed"));
+                return Ok(format!("({x})"));
+            }
+        }
+
+        Ok(ret)
+    }
+
+    /// Returns items of concatenation including replication
+    fn concatenation(&mut self) -> Conv<Vec<String>> {
+        self.expect("{")?;
+        let first = self.expr()?;
+        if self.at("{") {
+            let inner = self.concatenation()?;
+            self.expect("}")?;
+            let item = if inner.len() == 1 && !inner[0].contains(" repeat ") {
+                inner[0].clone()
+            } else {
+                format!("{{{}}}", inner.join(", "))
+            };
+            return Ok(vec![format!("{item} repeat {first}")]);
+        }
+
+        let mut ret = vec![first];
+        while self.eat(",") {
+            ret.push(self.expr()?);
+        }
+        self.expect("}")?;
+        Ok(ret)
+    }
+}
+
+fn escape(name: &str) -> Conv<String> {
+    if name.starts_with('\\') {
+        unsupported(format!("escaped identifier '{name}' is not supported"))
+    } else if VERYL_KEYWORDS.contains(&name) {
+        Ok(format!("r#{name}"))
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+fn precedence(op: &str) -> Option<u8> {
+    let ret = match op {
+        "||" => 1,
+        "&&" => 2,
+        "|" => 3,
+        "^" | "~^" | "^~" => 4,
+        "&" => 5,
+        "==" | "!=" | "===" | "!==" | "==?" | "!=?" => 6,
+        "<" | "<=" | ">" | ">=" => 7,
+        "<<" | ">>" | "<<<" | ">>>" => 8,
+        "+" | "-" => 9,
+        "*" | "/" | "%" => 10,
+        "**" => 11,
+        _ => return None,
+    };
+    Some(ret)
+}
+
+fn number(text: &str) -> Conv<String> {
+    let normalize = |x: &str| {
+        x.split('_')
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<_>>()
+            .join("_")
+    };
+
+    if let Some((size, value)) = text.split_once('\'') {
+        let (signed, value) = match value.strip_prefix(['s', 'S']) {
+            Some(x) => ("s", x),
+            None => ("", value),
+        };
+        let mut chars = value.chars();
+        let base = chars.next().unwrap_or_default().to_ascii_lowercase();
+        let digits = chars.as_str();
+        if digits.is_empty() {
+            return Ok(format!("{size}'{base}"));
+        }
+        let digits = normalize(&digits.replace('?', "z"));
+        if !digits
+            .chars()
+            .all(|x| x.is_ascii_hexdigit() || "xzXZ_".contains(x))
+        {
+            return unsupported(format!("number '{text}' is not supported"));
+        }
+        Ok(format!("{}'{signed}{base}{digits}", normalize(size)))
+    } else if text.ends_with(|x: char| x.is_ascii_alphabetic() && x != 'e' && x != 'E')
+        || text.ends_with("ns")
+    {
+        unsupported(format!("time literal '{text}' is not supported"))
+    } else {
+        Ok(normalize(text))
+    }
+}
+
+/// Returns `x + 1` simplifying `N - 1` to `N`
+fn plus_one(x: &str) -> String {
+    if let Some(x) = x
+        .replace('_', "")
+        .parse::<u64>()
+        .ok()
+        .and_then(|x| x.checked_add(1))
+    {
+        return x.to_string();
+    }
+    if let Some(inner) = x.strip_prefix('(').and_then(|x| x.strip_suffix(')')) {
+        if !inner.contains(['(', ')']) {
+            return plus_one(inner);
+        }
+    }
+    if let Some(x) = x.strip_suffix(" - 1") {
+        if !x.contains(['<', '>', '&', '|', '^', '?', '=', '!']) && !x.starts_with("if ") {
+            return x.to_string();
+        }
+    }
+    if x.contains(' ') {
+        format!("({x}) + 1")
+    } else {
+        format!("{x} + 1")
+    }
+}
@@ -0,0 +1,332 @@
+use crate::lexer::{self, Kind, Token};
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Limit of nested macro expansion and include
+const MAX_DEPTH: usize = 64;
+
+/// Placeholder of `` `" `` during argument substitution
+const QUOTE: &str = "\u{1}";
+
+#[derive(Clone)]
+struct Define {
+    params: Option<Vec<(String, Option<String>)>>,
+    body: String,
+}
+
+struct Condition {
+    active: bool,
+    taken: bool,
+    parent: bool,
+}
+
+/// Minimal SystemVerilog preprocessor resolving `define`, `ifdef` and `include`.
+/// Defines are shared through all processed files like a compilation unit.
+pub struct Preprocessor {
+    defines: HashMap<String, Define>,
+    include_dirs: Vec<PathBuf>,
+}
+
+impl Preprocessor {
+    /// `defines` are `NAME` or `NAME=VALUE`
+    pub fn new(defines: &[String], include_dirs: &[PathBuf]) -> Self {
+        let mut ret = Self {
+            defines: HashMap::new(),
+            include_dirs: include_dirs.to_vec(),
+        };
+        for define in defines {
+            let (name, body) = define.split_once('=').unwrap_or((define, ""));
+            ret.defines.insert(
+                name.to_string(),
+                Define {
+                    params: None,
+                    body: body.to_string(),
+                },
+            );
+        }
+        ret
+    }
+
+    pub fn process(&mut self, input: &str, path: &Path) -> Result<String> {
+        self.process_text(input, path, 0)
+    }
+
+    fn process_text(&mut self, input: &str, path: &Path, depth: usize) -> Result<String> {
+        if depth > MAX_DEPTH {
+            bail!(
+                "macro expansion or include is too deep ({})",
+                path.to_string_lossy()
+            );
+        }
+
+        let tokens = lexer::tokenize(input);
+        let mut ret = String::new();
+        let mut conditions: Vec<Condition> = Vec::new();
+        let mut prev = 0;
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let token = &tokens[i];
+            let active = conditions.last().map(|x| x.active).unwrap_or(true);
+            let gap = &input[prev..token.beg];
+            i += 1;
+            prev = token.end;
+
+            match token.kind {
+                Kind::Directive => {
+                    let (name, arg) = split_directive(&token.text);
+                    match name {
+                        "ifdef" | "ifndef" => {
+                            let cond = self.defines.contains_key(arg) == (name == "ifdef");
+                            conditions.push(Condition {
+                                active: active && cond,
+                                taken: cond,
+                                parent: active,
+                            });
+                        }
+                        "elsif" | "else" => {
+                            let Some(x) = conditions.last_mut() else {
+                                bail!("`{name} without `ifdef ({})", path.to_string_lossy());
+                            };
+                            let cond =
+                                !x.taken && (name == "else" || self.defines.contains_key(arg));
+                            x.active = x.parent && cond;
+                            x.taken |= cond;
+                        }
+                        "endif" => {
+                            if conditions.pop().is_none() {
+                                bail!("`endif without `ifdef ({})", path.to_string_lossy());
+                            }
+                        }
+                        _ if !active => (),
+                        "define" => {
+                            let (name, define) = parse_define(arg);
+                            self.defines.insert(name, define);
+                        }
+                        "undef" => {
+                            self.defines.remove(arg);
+                        }
+                        "undefineall" => self.defines.clear(),
+                        "include" => {
+                            ret.push_str(gap);
+                            ret.push_str(&self.include(arg, path, depth)?);
+                        }
+                        _ => {
+                            ret.push_str(gap);
+                            ret.push_str(&token.text);
+                        }
+                    }
+                }
+                _ if !active => (),
+                Kind::Macro => {
+                    ret.push_str(gap);
+                    let name = &token.text[1..];
+                    match name {
+                        "__FILE__" => ret.push_str(&format!("\"{}\"", path.to_string_lossy())),
+                        "__LINE__" => {
+                            let line = input[..token.beg].matches('\n').count() + 1;
+                            ret.push_str(&line.to_string());
+                        }
+                        _ => {
+                            let Some(define) = self.defines.get(name).cloned() else {
+                                // Undefined macro is kept for the converter
+                                ret.push_str(&token.text);
+                                continue;
+                            };
+                            let body = if let Some(params) = &define.params {
+                                if !tokens.get(i).is_some_and(|x| x.is("(")) {
+                                    bail!(
+                                        "arguments of macro `{name} are not found ({})",
+                                        path.to_string_lossy()
+                                    );
+                                }
+                                let (args, next) = arguments(input, &tokens, i);
+                                i = next;
+                                prev = tokens[i - 1].end;
+                                if args.len() > params.len() && !(params.is_empty() && args == [""])
+                                {
+                                    bail!(
+                                        "too many arguments of macro `{name} ({})",
+                                        path.to_string_lossy()
+                                    );
+                                }
+                                substitute(&define.body, params, &args)
+                            } else {
+                                define.body.clone()
+                            };
+                            ret.push_str(&self.process_text(&body, path, depth + 1)?);
+                        }
+                    }
+                }
+                _ => {
+                    ret.push_str(gap);
+                    ret.push_str(&token.text);
+                }
+            }
+        }
+
+        if !conditions.is_empty() {
+            bail!("`ifdef is not closed ({})", path.to_string_lossy());
+        }
+        ret.push_str(&input[prev..]);
+        Ok(ret)
+    }
+
+    fn include(&mut self, arg: &str, path: &Path, depth: usize) -> Result<String> {
+        // Trailing comment may follow the file name
+        let arg = arg.trim();
+        let name = arg
+            .strip_prefix('"')
+            .and_then(|x| x.split_once('"'))
+            .or_else(|| arg.strip_prefix('<').and_then(|x| x.split_once('>')))
+            .map(|x| x.0);
+        let Some(name) = name else {
+            bail!(
+                "include file \"{arg}\" is not supported ({})",
+                path.to_string_lossy()
+            );
+        };
+
+        let base = path.parent().map(|x| x.to_path_buf()).unwrap_or_default();
+        let found = std::iter::once(&base)
+            .chain(self.include_dirs.iter())
+            .map(|x| x.join(name))
+            .find(|x| x.exists());
+        let Some(file) = found else {
+            bail!(
+                "include file \"{name}\" is not found ({})",
+                path.to_string_lossy()
+            );
+        };
+
+        let text = fs::read_to_string(&file)
+            .into_diagnostic()
+            .wrap_err(format!("failed to read {}", file.to_string_lossy()))?;
+        self.process_text(&text, &file, depth + 1)
+    }
+}
+
+/// Splits the text of directive into the name and the argument
+fn split_directive(text: &str) -> (&str, &str) {
+    let text = &text[1..];
+    let end = text
+        .find(|x: char| !x.is_ascii_alphanumeric() && x != '_')
+        .unwrap_or(text.len());
+    (&text[..end], text[end..].trim())
+}
+
+fn parse_define(arg: &str) -> (String, Define) {
+    let end = arg
+        .find(|x: char| !x.is_ascii_alphanumeric() && x != '_' && x != '$')
+        .unwrap_or(arg.len());
+    let name = arg[..end].to_string();
+    let mut rest = &arg[end..];
+
+    // Function-like macro requires `(` just after the name
+    let mut params = None;
+    if let Some(x) = rest.strip_prefix('(') {
+        let close = x.find(')').unwrap_or(x.len());
+        let list: Vec<_> = x[..close]
+            .split(',')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .map(|x| match x.split_once('=') {
+                Some((name, default)) => {
+                    (name.trim().to_string(), Some(default.trim().to_string()))
+                }
+                None => (x.to_string(), None),
+            })
+            .collect();
+        params = Some(list);
+        rest = &x[(close + 1).min(x.len())..];
+    }
+
+    let body = strip_line_comments(&rest.replace("\\\r\n", "\n").replace("\\\n", "\n"));
+    (
+        name,
+        Define {
+            params,
+            body: body.trim().to_string(),
+        },
+    )
+}
+
+/// One-line comments are not a part of macro text
+fn strip_line_comments(text: &str) -> String {
+    let mut ret = String::new();
+    let mut prev = 0;
+    for token in lexer::tokenize(text) {
+        ret.push_str(&text[prev..token.beg]);
+        if !(token.kind == Kind::Comment && token.text.starts_with("//")) {
+            ret.push_str(&token.text);
+        }
+        prev = token.end;
+    }
+    ret.push_str(&text[prev..]);
+    ret
+}
+
+/// Returns arguments of macro call starting at `(` of `tokens[i]`, and the next index
+fn arguments(input: &str, tokens: &[Token], mut i: usize) -> (Vec<String>, usize) {
+    let mut ret = Vec::new();
+    let mut depth = 0;
+    let mut start = tokens[i].end;
+    i += 1;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        i += 1;
+        if token.is("(") || token.is("[") || token.is("{") || token.is("'{") {
+            depth += 1;
+        } else if token.is(")") && depth == 0 {
+            ret.push(input[start..token.beg].trim().to_string());
+            break;
+        } else if token.is(")") || token.is("]") || token.is("}") {
+            depth -= 1;
+        } else if token.is(",") && depth == 0 {
+            ret.push(input[start..token.beg].trim().to_string());
+            start = token.end;
+        }
+    }
+    (ret, i)
+}
+
+/// Replaces parameters in `body` by arguments
+fn substitute(body: &str, params: &[(String, Option<String>)], args: &[String]) -> String {
+    let values: HashMap<_, _> = params
+        .iter()
+        .enumerate()
+        .map(|(i, (name, default))| {
+            let value = match args.get(i) {
+                Some(x) if !x.is_empty() => x.clone(),
+                _ => default.clone().unwrap_or_default(),
+            };
+            (name.as_str(), value)
+        })
+        .collect();
+
+    // Parameters in `"...`" are replaced, so the quote is hidden from the lexer
+    let body = body.replace("`\\`\"", "\\\"").replace("`\"", QUOTE);
+
+    // "``" concatenates tokens
+    let ret: Vec<_> = body
+        .split("``")
+        .map(|part| {
+            let mut ret = String::new();
+            let mut prev = 0;
+            for token in lexer::tokenize(part) {
+                ret.push_str(&part[prev..token.beg]);
+                match values.get(token.text.as_str()) {
+                    Some(value) if token.kind == Kind::Identifier => ret.push_str(value),
+                    _ => ret.push_str(&token.text),
+                }
+                prev = token.end;
+            }
+            ret.push_str(&part[prev..]);
+            ret
+        })
+        .collect();
+
+    ret.concat().replace(QUOTE, "\"")
+}
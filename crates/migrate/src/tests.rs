@@ -0,0 +1,244 @@
+use crate::{migrate, Preprocessor};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn migrate_module() {
+    let testcases = [
+        (
+            // ports and parameters
+            r#"module ModuleA #(
+    parameter int WIDTH = 8,
+    localparam int DEPTH = 4
+) (
+    input  logic             i_clk,
+    input  logic [WIDTH-1:0] i_d,
+    output logic [7:0]       o_q
+);
+endmodule
+"#,
+            r#"module ModuleA #(
+    param WIDTH: i32 = 8,
+    const DEPTH: i32 = 4,
+) (
+    i_clk: input logic,
+    i_d: input logic<WIDTH>,
+    o_q: output logic<8>,
+) {
+}
+"#,
+            0,
+        ),
+        (
+            // always_ff with reset inference
+            r#"module ModuleA (
+    input  logic clk,
+    input  logic rst_n,
+    input  logic d,
+    output logic q
+);
+    always_ff @(posedge clk or negedge rst_n) begin
+        if (!rst_n) begin
+            q <= '0;
+        end else begin
+            q <= d;
+        end
+    end
+endmodule
+"#,
+            r#"module ModuleA (
+    clk: input clock_posedge,
+    rst_n: input reset_async_low,
+    d: input logic,
+    q: output logic,
+) {
+    always_ff (clk, rst_n) {
+        if_reset {
+            q = '0;
+        } else {
+            q = d;
+        }
+    }
+}
+"#,
+            0,
+        ),
+        (
+            // enum and case
+            r#"module ModuleA;
+    typedef enum logic [1:0] {
+        IDLE,
+        RUN,
+        DONE
+    } state_t;
+
+    state_t state;
+
+    always_comb begin
+        case (state)
+            IDLE: a = 1;
+            RUN, DONE: a = 2;
+            default: a = 0;
+        endcase
+    end
+endmodule
+"#,
+            r#"module ModuleA {
+    enum state_t: logic<2> {
+        IDLE,
+        RUN,
+        DONE,
+    }
+
+    var state: state_t;
+
+    always_comb {
+        case state {
+            state_t::IDLE: a = 1;
+            state_t::RUN, state_t::DONE: a = 2;
+            default: a = 0;
+        }
+    }
+}
+"#,
+            0,
+        ),
+        (
+            // instance with open port
+            r#"module ModuleA;
+    ModuleB u_b (
+        .a (x),
+        .b (),
+        .c
+    );
+endmodule
+"#,
+            r#"module ModuleA {
+    inst u_b: ModuleB (
+        a: x,
+        b: _,
+        c,
+    );
+}
+"#,
+            0,
+        ),
+        (
+            // unsupported construct is kept with TODO marker
+            r#"module ModuleA;
+    covergroup cg @(posedge clk);
+        coverpoint a;
+    endgroup
+endmodule
+"#,
+            r#"module ModuleA {
+    // TODO: migrate: unsupported syntax near '@'
+    // covergroup cg @(posedge clk);
+    //     coverpoint a;
+    // endgroup
+}
+"#,
+            1,
+        ),
+        (
+            // width which overflows u64 is kept as expression
+            r#"module ModuleA;
+    logic [18446744073709551615:0] a;
+endmodule
+"#,
+            r#"module ModuleA {
+    var a: logic<18446744073709551615 + 1>;
+}
+"#,
+            0,
+        ),
+    ];
+
+    for (input, expect, todos) in testcases {
+        let ret = migrate(input);
+        assert_eq!(ret.text, expect);
+        assert_eq!(ret.todos, todos);
+    }
+}
+
+#[test]
+fn preprocess_macro() {
+    let code = r#"`define ADD(a, b = 1) ((a) + (b))
+`define STR(x) `"x`"
+assign x = `ADD(y, `VALUE) + `ADD(z);
+string s = `STR(hello);
+"#;
+    let expect = r#"
+assign x = ((y) + (3)) + ((z) + (1));
+string s = "hello";
+"#;
+
+    let mut preprocessor = Preprocessor::new(&["VALUE=3".to_string()], &[]);
+    let ret = preprocessor.process(code, Path::new("a.sv")).unwrap();
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn preprocess_condition() {
+    let code = r#"`ifdef A
+a
+`elsif B
+`ifndef C
+b
+`else
+c
+`endif
+`else
+d
+`endif
+"#;
+
+    let testcases = [
+        (vec!["A"], "\na\n"),
+        (vec!["B"], "\nb\n"),
+        (vec!["B", "C"], "\nc\n"),
+        (vec![], "\nd\n"),
+    ];
+
+    for (defines, expect) in testcases {
+        let defines: Vec<_> = defines.iter().map(|x| x.to_string()).collect();
+        let mut preprocessor = Preprocessor::new(&defines, &[]);
+        let ret = preprocessor.process(code, Path::new("a.sv")).unwrap();
+        assert_eq!(ret, expect);
+    }
+}
+
+#[test]
+fn preprocess_include() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let include_dir = tempdir.path().join("include");
+    fs::create_dir_all(&include_dir).unwrap();
+    fs::write(include_dir.join("a.svh"), "`define VALUE 7\nwire a;\n").unwrap();
+    fs::write(include_dir.join("loop.svh"), "`include \"loop.svh\"\n").unwrap();
+
+    let path = tempdir.path().join("a.sv");
+    let mut preprocessor = Preprocessor::new(&[], &[include_dir]);
+    let ret = preprocessor
+        .process("`include \"a.svh\"\nassign x = `VALUE;\n", &path)
+        .unwrap();
+    assert_eq!(ret, "\nwire a;\n\nassign x = 7;\n");
+
+    assert!(preprocessor.process("`include \"b.svh\"\n", &path).is_err());
+
+    // Recursive include reaches the depth limit
+    let ret = preprocessor.process("`include \"loop.svh\"\n", &path);
+    assert!(ret
+        .unwrap_err()
+        .to_string()
+        .starts_with("macro expansion or include is too deep"));
+}
+
+#[test]
+fn preprocess_depth_limit() {
+    let mut preprocessor = Preprocessor::new(&[], &[]);
+    let ret = preprocessor.process("`define A `A\n`A\n", Path::new("a.sv"));
+    assert!(ret
+        .unwrap_err()
+        .to_string()
+        .starts_with("macro expansion or include is too deep"));
+}
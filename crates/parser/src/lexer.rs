@@ -0,0 +1,163 @@
+use crate::parser::Parser;
+use crate::parser_error::ParserError;
+use crate::veryl_token::VerylToken;
+use crate::veryl_walker::VerylWalker;
+use std::fmt;
+use std::path::Path;
+
+/// Kind of `LexToken`.
+/// Variants may be added, but the existing variants and their names are kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    Identifier,
+    Number,
+    StringLiteral,
+    Operator,
+    Punctuation,
+    /// Content of `embed` declaration
+    EmbedContent,
+    Comment,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            TokenKind::Keyword => "keyword",
+            TokenKind::Identifier => "identifier",
+            TokenKind::Number => "number",
+            TokenKind::StringLiteral => "string_literal",
+            TokenKind::Operator => "operator",
+            TokenKind::Punctuation => "punctuation",
+            TokenKind::EmbedContent => "embed_content",
+            TokenKind::Comment => "comment",
+        };
+        text.fmt(f)
+    }
+}
+
+impl TokenKind {
+    /// Kind of the token owned by the terminal non-terminal like `module` or `identifier`
+    fn from_terminal(name: &str) -> Self {
+        match name {
+            "identifier" | "dollar_identifier" => TokenKind::Identifier,
+            "exponent" | "fixed_point" | "based" | "base_less" | "all_bit" => TokenKind::Number,
+            "string_literal" => TokenKind::StringLiteral,
+            "embed_content" => TokenKind::EmbedContent,
+            "assignment_operator" | "unary_operator" => TokenKind::Operator,
+            x if x.starts_with("operator") => TokenKind::Operator,
+            "back_quote"
+            | "colon"
+            | "colon_colon"
+            | "colon_colon_l_angle"
+            | "comma"
+            | "dot_dot"
+            | "dot_dot_equ"
+            | "dot"
+            | "equ"
+            | "hash"
+            | "quote_l_brace"
+            | "l_angle"
+            | "l_brace"
+            | "l_bracket"
+            | "l_paren"
+            | "minus_colon"
+            | "minus_g_t"
+            | "plus_colon"
+            | "r_angle"
+            | "r_brace"
+            | "r_bracket"
+            | "r_paren"
+            | "semicolon"
+            | "star" => TokenKind::Punctuation,
+            _ => TokenKind::Keyword,
+        }
+    }
+}
+
+/// Token with the kind and the position in the source
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LexToken {
+    pub kind: TokenKind,
+    pub text: String,
+    /// 1-origin line number
+    pub line: u32,
+    /// 1-origin column number
+    pub column: u32,
+    /// Byte offset of the beginning
+    pub pos: u32,
+    /// Length in bytes
+    pub length: u32,
+}
+
+/// Converts `input` to the token stream including comments in the source order
+#[allow(clippy::result_large_err)]
+pub fn tokenize<T: AsRef<Path>>(input: &str, file: &T) -> Result<Vec<LexToken>, ParserError> {
+    let parser = Parser::parse(input, file)?;
+    let mut lexer = Lexer {
+        input,
+        terminal: "",
+        tokens: Vec::new(),
+    };
+    lexer.veryl(&parser.veryl);
+    lexer.tokens.sort_by_key(|x| x.pos);
+    Ok(lexer.tokens)
+}
+
+/// Returns comments following `token`.
+/// Positions of comments in `VerylToken` are relative to the comment group,
+/// so they are located by searching `input` after `token`.
+pub fn comments(input: &str, token: &VerylToken) -> Vec<LexToken> {
+    let mut pos = (token.token.pos + token.token.length) as usize;
+    let mut ret = Vec::new();
+    for comment in &token.comments {
+        let text = comment.to_string();
+        let start = input
+            .get(pos..)
+            .and_then(|x| x.find(&text))
+            .map(|x| x + pos)
+            .unwrap_or(pos);
+        let head = input.get(..start).unwrap_or_default();
+        let line = head.matches('\n').count() as u32 + 1;
+        let column = (start - head.rfind('\n').map(|x| x + 1).unwrap_or(0)) as u32 + 1;
+        pos = start + text.len();
+        ret.push(LexToken {
+            kind: TokenKind::Comment,
+            length: text.len() as u32,
+            text,
+            line,
+            column,
+            pos: start as u32,
+        });
+    }
+    ret
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    /// Name of the innermost non-terminal
+    terminal: &'static str,
+    tokens: Vec<LexToken>,
+}
+
+impl VerylWalker for Lexer<'_> {
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        // The start token is an empty token holding leading comments
+        if self.terminal != "start" {
+            let name = self.terminal.strip_prefix("r#").unwrap_or(self.terminal);
+            self.tokens.push(LexToken {
+                kind: TokenKind::from_terminal(name),
+                text: arg.to_string(),
+                line: arg.token.line,
+                column: arg.token.column,
+                pos: arg.token.pos,
+                length: arg.token.length,
+            });
+        }
+        self.tokens.append(&mut comments(self.input, arg));
+    }
+
+    fn enter_non_terminal(&mut self, name: &'static str) {
+        self.terminal = name;
+    }
+}
@@ -2,10 +2,12 @@ pub mod doc_comment_table;
 pub mod finder;
 pub mod generated;
 pub mod last_token;
+pub mod lexer;
 pub mod parser;
 pub mod parser_error;
 pub mod resource_table;
 pub mod stringifier;
+pub mod syntax_tree;
 pub mod veryl_grammar;
 pub mod veryl_grammar_trait;
 pub mod veryl_parser;
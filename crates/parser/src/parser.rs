@@ -10,6 +10,12 @@ pub struct Parser {
     pub veryl: Veryl,
 }
 
+/// Limit of reparse after skipping erroneous part
+const MAX_RECOVERY: usize = 16;
+
+/// Limit of the total bytes parsed through recovery
+const MAX_RECOVERY_BYTES: usize = 4 * 1024 * 1024;
+
 impl Parser {
     #[allow(clippy::result_large_err)]
     pub fn parse<T: AsRef<Path>>(input: &str, file: &T) -> Result<Self, ParserError> {
@@ -23,4 +29,166 @@ impl Parser {
 
         Ok(Parser { veryl })
     }
+
+    /// Parses `input` skipping statements and declarations which have syntax error.
+    /// All syntax errors are returned with the parse result of the rest of `input`.
+    /// Skipped parts are replaced by whitespace, so the positions of the other tokens are kept.
+    ///
+    /// Only the first syntax error is found by each parse, so the whole `input` is parsed again
+    /// after each skip. The cost is up to `MAX_RECOVERY` times of `Parser::parse`,
+    /// and reparse is stopped when the total parsed bytes exceed `MAX_RECOVERY_BYTES`.
+    pub fn parse_with_recovery<T: AsRef<Path>>(
+        input: &str,
+        file: &T,
+    ) -> (Option<Self>, Vec<ParserError>) {
+        Self::parse_with_recovery_limit(input, file, MAX_RECOVERY_BYTES)
+    }
+
+    pub(crate) fn parse_with_recovery_limit<T: AsRef<Path>>(
+        input: &str,
+        file: &T,
+        max_bytes: usize,
+    ) -> (Option<Self>, Vec<ParserError>) {
+        let mut text = input.as_bytes().to_vec();
+        let mut errors = Vec::new();
+        let mut parsed = 0;
+
+        for i in 0..MAX_RECOVERY {
+            // The first parse is always done regardless of the limit
+            parsed += text.len();
+            if i != 0 && parsed > max_bytes {
+                break;
+            }
+
+            // `text` is kept valid UTF-8 because only ASCII and whole strings are replaced
+            let input = String::from_utf8_lossy(&text).to_string();
+            match Self::parse(&input, file) {
+                Ok(x) => return (Some(x), errors),
+                Err(ParserError::SyntaxError(x)) => {
+                    let offset = x.error_location.offset();
+                    errors.push(ParserError::SyntaxError(x));
+                    if !skip_erroneous_part(&mut text, offset) {
+                        return (None, errors);
+                    }
+                }
+                Err(x) => {
+                    errors.push(x);
+                    return (None, errors);
+                }
+            }
+        }
+
+        (None, errors)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Code,
+    Comment,
+    String,
+}
+
+fn classify(text: &[u8]) -> Vec<Class> {
+    let mut ret = vec![Class::Code; text.len()];
+    let mut i = 0;
+    while i < text.len() {
+        let next = text.get(i + 1).copied();
+        let (class, end) = match (text[i], next) {
+            (b'/', Some(b'/')) => {
+                let end = text[i..]
+                    .iter()
+                    .position(|x| *x == b'\n')
+                    .map(|x| i + x)
+                    .unwrap_or(text.len());
+                (Class::Comment, end)
+            }
+            (b'/', Some(b'*')) => {
+                let end = text[i + 2..]
+                    .windows(2)
+                    .position(|x| x == b"*/")
+                    .map(|x| i + 2 + x + 2)
+                    .unwrap_or(text.len());
+                (Class::Comment, end)
+            }
+            (b'"', _) => {
+                let mut end = i + 1;
+                while end < text.len() && text[end] != b'"' {
+                    if text[end] == b'\\' {
+                        end += 1;
+                    }
+                    end += 1;
+                }
+                (Class::String, (end + 1).min(text.len()))
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        for x in &mut ret[i..end] {
+            *x = class;
+        }
+        i = end;
+    }
+    ret
+}
+
+/// Replaces the statement or declaration including `offset` by whitespace.
+/// Returns false if there is nothing to be replaced.
+fn skip_erroneous_part(text: &mut [u8], offset: usize) -> bool {
+    if offset >= text.len() {
+        return false;
+    }
+
+    let class = classify(text);
+    let is_code = |i: usize, c: u8| class[i] == Class::Code && text[i] == c;
+
+    // Search the end of the previous statement or declaration
+    let mut begin = offset;
+    while begin > 0 {
+        let i = begin - 1;
+        if is_code(i, b';') || is_code(i, b'{') || is_code(i, b'}') {
+            break;
+        }
+        begin = i;
+    }
+
+    // Search the end of the erroneous statement or declaration including its body
+    let mut end = offset;
+    let mut depth = 0;
+    while end < text.len() {
+        if is_code(end, b'{') {
+            depth += 1;
+        } else if is_code(end, b'}') {
+            if depth == 0 {
+                break;
+            }
+            depth -= 1;
+            if depth == 0 {
+                end += 1;
+                break;
+            }
+        } else if is_code(end, b';') && depth == 0 {
+            end += 1;
+            break;
+        }
+        end += 1;
+    }
+
+    let mut replaced = false;
+    for i in begin..end {
+        if class[i] != Class::Comment && !text[i].is_ascii_whitespace() {
+            text[i] = b' ';
+            replaced = true;
+        }
+    }
+
+    // Unexpected closing brace itself
+    if !replaced && class[offset] == Class::Code && text[offset] == b'}' {
+        text[offset] = b' ';
+        replaced = true;
+    }
+
+    replaced
 }
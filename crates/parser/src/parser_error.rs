@@ -33,19 +33,14 @@ pub struct SyntaxError {
 impl std::fmt::Display for SyntaxError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if !self.unexpected_tokens.is_empty() {
-            let token = self.unexpected_tokens[0].token_type;
-            if token == TokenType::LAngle {
+            let token = &self.unexpected_tokens[0];
+            if let Some(x) = token.suggestion() {
                 f.write_str(&format!(
-                    "Unexpected token: '{}', do you mean \"less than operator '<:'\" ?",
-                    token
-                ))
-            } else if token == TokenType::RAngle {
-                f.write_str(&format!(
-                    "Unexpected token: '{}', do you mean \"greater than operator '>:'\" ?",
-                    token
+                    "Unexpected token: '{}', do you mean \"{}\" ?",
+                    token.token_type, x
                 ))
             } else {
-                f.write_str(&format!("Unexpected token: '{}'", token))
+                f.write_str(&format!("Unexpected token: '{}'", token.token_type))
             }
         } else {
             f.write_str("Syntax Error")
@@ -55,11 +50,13 @@ impl std::fmt::Display for SyntaxError {
 
 impl From<parol_runtime::SyntaxError> for SyntaxError {
     fn from(value: parol_runtime::SyntaxError) -> Self {
+        let input = value.input.unwrap();
+        let unexpected_tokens = UnexpectedTokens(value.unexpected_tokens, &input.input).into();
         Self {
             cause: value.cause,
-            input: value.input.map(|e| FileSource(*e).into()).unwrap(),
+            input: FileSource(*input).into(),
             error_location: Location(*value.error_location).into(),
-            unexpected_tokens: UnexpectedTokens(value.unexpected_tokens).into(),
+            unexpected_tokens,
             expected_tokens: value.expected_tokens,
         }
     }
@@ -71,10 +68,120 @@ impl From<parol_runtime::SyntaxError> for SyntaxError {
 pub struct UnexpectedToken {
     name: String,
     token_type: TokenType,
+    pub text: String,
     #[label("Unexpected token")]
     pub(crate) token: SourceSpan,
 }
 
+impl UnexpectedToken {
+    /// Returns the Veryl equivalent of SystemVerilog constructs and misspelled keywords
+    pub fn suggestion(&self) -> Option<String> {
+        let ret = match self.token_type {
+            TokenType::LAngle => "less than operator '<:'",
+            TokenType::RAngle => "greater than operator '>:'",
+            TokenType::Logic | TokenType::Bit => "variable declaration 'var <name>: logic;'",
+            TokenType::Input | TokenType::Output | TokenType::Inout => {
+                "port declaration '<name>: input logic'"
+            }
+            _ if self.text == "<=" => "assignment operator '='",
+            TokenType::Identifier => match self.text.as_str() {
+                "begin" => "'{'",
+                "end" | "endmodule" | "endinterface" | "endpackage" | "endfunction" | "endcase"
+                | "endgenerate" => "'}'",
+                "always" => "'always_ff' or 'always_comb'",
+                "reg" | "wire" => "'logic'",
+                "int" | "integer" => "'i32'",
+                "longint" => "'i64'",
+                "real" | "shortreal" => "'f64' or 'f32'",
+                "parameter" => "'param'",
+                "localparam" => "'const'",
+                "genvar" | "generate" => "'for' or 'if' with label",
+                "typedef" => "'type', 'struct' or 'enum'",
+                "elsif" => "'else if'",
+                "unique" | "priority" => "'case'",
+                "casez" | "casex" => "'case' with wildcard pattern",
+                x => return near_miss_keyword(x),
+            },
+            _ => return None,
+        };
+        Some(ret.to_string())
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "always_comb",
+    "always_ff",
+    "assign",
+    "clock",
+    "clock_posedge",
+    "clock_negedge",
+    "const",
+    "default",
+    "else",
+    "embed",
+    "enum",
+    "export",
+    "final",
+    "for",
+    "function",
+    "if_reset",
+    "import",
+    "include",
+    "initial",
+    "inout",
+    "input",
+    "inside",
+    "inst",
+    "interface",
+    "logic",
+    "modport",
+    "module",
+    "output",
+    "outside",
+    "package",
+    "param",
+    "proto",
+    "repeat",
+    "reset",
+    "reset_async_high",
+    "reset_async_low",
+    "reset_sync_high",
+    "reset_sync_low",
+    "return",
+    "break",
+    "signed",
+    "string",
+    "struct",
+    "switch",
+    "union",
+    "unsafe",
+];
+
+/// Returns the keyword which differs from `text` by one edit
+fn near_miss_keyword(text: &str) -> Option<String> {
+    if text.len() < 3 {
+        return None;
+    }
+    KEYWORDS
+        .iter()
+        .find(|x| edit_distance(text, x) == 1)
+        .map(|x| format!("keyword '{x}'"))
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+    let mut prev: Vec<_> = (0..=b.len()).collect();
+    for (i, x) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, y) in b.iter().enumerate() {
+            let cost = if x == *y { 0 } else { 1 };
+            cur.push((prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TokenType {
     Comments,
@@ -452,17 +559,26 @@ impl From<Location> for SourceSpan {
     }
 }
 
-struct UnexpectedTokens(Vec<parol_runtime::UnexpectedToken>);
+struct UnexpectedTokens<'a>(Vec<parol_runtime::UnexpectedToken>, &'a str);
 
-impl From<UnexpectedTokens> for Vec<UnexpectedToken> {
+impl From<UnexpectedTokens<'_>> for Vec<UnexpectedToken> {
     fn from(value: UnexpectedTokens) -> Self {
+        let input = value.1;
         value
             .0
             .into_iter()
-            .map(|v| UnexpectedToken {
-                name: v.name,
-                token_type: v.token_type.as_str().into(),
-                token: Location(v.token).into(),
+            .map(|v| {
+                let token: SourceSpan = Location(v.token).into();
+                let text = input
+                    .get(token.offset()..token.offset() + token.len())
+                    .unwrap_or_default()
+                    .to_string();
+                UnexpectedToken {
+                    name: v.name,
+                    token_type: v.token_type.as_str().into(),
+                    text,
+                    token,
+                }
             })
             .collect::<Vec<UnexpectedToken>>()
     }
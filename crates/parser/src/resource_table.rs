@@ -1,9 +1,11 @@
 use bimap::BiMap;
 use std::borrow::Borrow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::hash::Hash;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 #[derive(Default)]
 pub struct GlobalTable<T, U>
@@ -40,10 +42,39 @@ where
     }
 }
 
+/// Interned strings which are allocated only once and shared between id and value lookup
+#[derive(Default)]
+pub struct StringTable {
+    table: HashMap<Rc<str>, StrId>,
+    values: Vec<Rc<str>>,
+}
+
+impl StringTable {
+    pub fn insert(&mut self, value: &str) -> StrId {
+        if let Some(id) = self.table.get(value) {
+            *id
+        } else {
+            let id = StrId(self.values.len() as u32);
+            let value: Rc<str> = Rc::from(value);
+            self.values.push(value.clone());
+            self.table.insert(value, id);
+            id
+        }
+    }
+
+    pub fn get_value(&self, id: StrId) -> Option<&str> {
+        self.values.get(id.0 as usize).map(|x| x.as_ref())
+    }
+
+    pub fn get_id(&self, value: &str) -> Option<StrId> {
+        self.table.get(value).copied()
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct StrId(usize);
+pub struct StrId(u32);
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct PathId(usize);
+pub struct PathId(u32);
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct TokenId(usize);
 
@@ -51,12 +82,6 @@ pub trait Incrementable {
     fn inc(&mut self);
 }
 
-impl Incrementable for StrId {
-    fn inc(&mut self) {
-        self.0 += 1;
-    }
-}
-
 impl Incrementable for PathId {
     fn inc(&mut self) {
         self.0 += 1;
@@ -89,12 +114,12 @@ impl From<&str> for StrId {
     }
 }
 
-thread_local!(static STRING_TABLE: RefCell<GlobalTable<String, StrId>> = RefCell::new(GlobalTable::default()));
+thread_local!(static STRING_TABLE: RefCell<StringTable> = RefCell::new(StringTable::default()));
 thread_local!(static PATHBUF_TABLE: RefCell<GlobalTable<PathBuf, PathId>> = RefCell::new(GlobalTable::default()));
 thread_local!(static TOKEN_ID: RefCell<usize> = const { RefCell::new(0) });
 
 pub fn insert_str(value: &str) -> StrId {
-    STRING_TABLE.with(|f| f.borrow_mut().insert(value.to_owned()))
+    STRING_TABLE.with(|f| f.borrow_mut().insert(value))
 }
 
 pub fn insert_path(value: &Path) -> PathId {
@@ -110,7 +135,7 @@ pub fn get_path_value(id: PathId) -> Option<PathBuf> {
 }
 
 pub fn get_str_id<T: Borrow<String>>(value: T) -> Option<StrId> {
-    STRING_TABLE.with(|f| f.borrow().get_id(value).map(|x| x.to_owned()))
+    STRING_TABLE.with(|f| f.borrow().get_id(value.borrow()))
 }
 
 pub fn get_path_id<T: Borrow<PathBuf>>(value: T) -> Option<PathId> {
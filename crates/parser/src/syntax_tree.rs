@@ -0,0 +1,142 @@
+use crate::veryl_grammar_trait::Veryl;
+use crate::veryl_token::VerylToken;
+use crate::veryl_walker::VerylWalker;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Node of the concrete syntax tree
+#[derive(Clone, Debug)]
+pub enum SyntaxNode {
+    NonTerminal {
+        /// Name of the non-terminal like `ModuleDeclaration`
+        kind: String,
+        children: Vec<SyntaxNode>,
+    },
+    Token(VerylToken),
+}
+
+impl SyntaxNode {
+    /// Builds the concrete syntax tree of `arg`
+    pub fn new(arg: &Veryl) -> Self {
+        let mut builder = SyntaxTreeBuilder::default();
+        builder.veryl(arg);
+        builder.root.unwrap()
+    }
+
+    /// Tokens included in the node in the source order
+    pub fn tokens(&self) -> Vec<&VerylToken> {
+        let mut ret = Vec::new();
+        self.collect_tokens(&mut ret);
+        ret
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a VerylToken>) {
+        match self {
+            SyntaxNode::NonTerminal { children, .. } => {
+                for x in children {
+                    x.collect_tokens(tokens);
+                }
+            }
+            SyntaxNode::Token(x) => tokens.push(x),
+        }
+    }
+}
+
+#[derive(Default)]
+struct SyntaxTreeBuilder {
+    stack: Vec<(String, Vec<SyntaxNode>)>,
+    root: Option<SyntaxNode>,
+}
+
+fn to_kind(name: &str) -> String {
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    let mut ret = String::new();
+    for word in name.split('_') {
+        let mut chars = word.chars();
+        if let Some(x) = chars.next() {
+            ret.push(x.to_ascii_uppercase());
+            ret.extend(chars);
+        }
+    }
+    ret
+}
+
+impl VerylWalker for SyntaxTreeBuilder {
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        if let Some((_, children)) = self.stack.last_mut() {
+            children.push(SyntaxNode::Token(arg.clone()));
+        }
+    }
+
+    fn enter_non_terminal(&mut self, name: &'static str) {
+        self.stack.push((to_kind(name), Vec::new()));
+    }
+
+    fn leave_non_terminal(&mut self, _name: &'static str) {
+        let (kind, children) = self.stack.pop().unwrap();
+        let node = SyntaxNode::NonTerminal { kind, children };
+        if let Some((_, children)) = self.stack.last_mut() {
+            children.push(node);
+        } else {
+            self.root = Some(node);
+        }
+    }
+}
+
+/// Dumps the typed AST of `arg` in indented form.
+/// Tokens are shown as `VerylToken("text" line:column)` instead of their internal IDs.
+pub fn dump_ast(arg: &Veryl) -> String {
+    let root = SyntaxNode::new(arg);
+    let tokens: HashMap<_, _> = root
+        .tokens()
+        .into_iter()
+        .map(|x| (x.token.id.to_string(), x))
+        .collect();
+
+    let debug = format!("{arg:#?}");
+    let mut lines = debug.lines();
+    let mut ret = String::new();
+    while let Some(line) = lines.next() {
+        let Some(head) = line.strip_suffix("VerylToken {") else {
+            ret.push_str(line);
+            ret.push('\n');
+            continue;
+        };
+
+        // Skip the fields of VerylToken until the closing brace of the same indent
+        let indent = line.len() - line.trim_start().len();
+        let mut id = None;
+        let mut in_id = false;
+        let mut tail = "";
+        for inner in lines.by_ref() {
+            let trimmed = inner.trim_start();
+            if inner.len() - trimmed.len() == indent && trimmed.starts_with('}') {
+                tail = &trimmed[1..];
+                break;
+            }
+            // The first `TokenId` is the ID of the token itself and the followings are comments
+            if in_id {
+                id = trimmed.strip_suffix(',');
+                in_id = false;
+            } else if id.is_none() && trimmed == "id: TokenId(" {
+                in_id = true;
+            }
+        }
+
+        match id.and_then(|x| tokens.get(x)) {
+            Some(x) => {
+                let _ = writeln!(
+                    ret,
+                    "{head}VerylToken({:?} {}:{}){tail}",
+                    x.to_string(),
+                    x.token.line,
+                    x.token.column
+                );
+            }
+            None => {
+                let _ = writeln!(ret, "{head}VerylToken(?){tail}");
+            }
+        }
+    }
+    ret
+}
@@ -1,3 +1,4 @@
+use crate::parser_error::ParserError;
 use crate::Parser;
 
 #[track_caller]
@@ -150,3 +151,184 @@ fn assignment_statement() {
     success("always_comb { a <<<= 1; }");
     success("always_comb { a >>>= 1; }");
 }
+
+#[test]
+fn string_table() {
+    use crate::resource_table::StringTable;
+
+    let mut table = StringTable::default();
+    let a = table.insert("a");
+    let b = table.insert("b");
+    assert_ne!(a, b);
+    assert_eq!(table.insert("a"), a);
+    assert_eq!(table.get_value(b), Some("b"));
+    assert_eq!(table.get_id("a"), Some(a));
+    assert_eq!(table.get_id("c"), None);
+}
+
+#[test]
+fn syntax_tree() {
+    use crate::syntax_tree::SyntaxNode;
+
+    let code = "module A (a: input logic) { assign b = a; } // end";
+    let parser = Parser::parse(code, &"").unwrap();
+    let tree = SyntaxNode::new(&parser.veryl);
+
+    let SyntaxNode::NonTerminal {
+        ref kind,
+        ref children,
+    } = tree
+    else {
+        unreachable!()
+    };
+    assert_eq!(kind, "Veryl");
+    assert_eq!(children.len(), 2);
+
+    // The first token is the empty start token which holds leading comments
+    let tokens: Vec<_> = tree.tokens().iter().map(|x| x.to_string()).collect();
+    assert_eq!(tokens[0], "");
+    assert_eq!(
+        tokens[1..].join(" "),
+        "module A ( a : input logic ) { assign b = a ; }"
+    );
+    assert_eq!(tree.tokens().last().unwrap().comments.len(), 1);
+
+    fn find(node: &SyntaxNode, target: &str) -> bool {
+        match node {
+            SyntaxNode::NonTerminal { kind, children } => {
+                kind == target || children.iter().any(|x| find(x, target))
+            }
+            SyntaxNode::Token(_) => false,
+        }
+    }
+    assert!(find(&tree, "ModuleDeclaration"));
+    assert!(find(&tree, "PortTypeConcrete"));
+    assert!(find(&tree, "AssignDeclaration"));
+}
+
+#[test]
+fn dump_ast() {
+    use crate::syntax_tree::dump_ast;
+
+    let code = "module A {\n    var a: logic;\n}";
+    let parser = Parser::parse(code, &"").unwrap();
+    let dump = dump_ast(&parser.veryl);
+
+    assert!(dump.starts_with("Veryl {\n"));
+    assert!(dump.contains("module_token: VerylToken(\"module\" 1:1),"));
+    assert!(dump.contains("var_token: VerylToken(\"var\" 2:5),"));
+    assert!(!dump.contains("TokenId"));
+}
+
+#[test]
+fn tokenize() {
+    use crate::lexer::{tokenize, LexToken, TokenKind};
+
+    let code = "// head\nmodule A {\n    let a: logic = 1 + b; // tail\n}";
+    let tokens = tokenize(code, &"").unwrap();
+    let token = |kind, text: &str, line, column, pos| LexToken {
+        kind,
+        text: text.to_string(),
+        line,
+        column,
+        pos,
+        length: text.len() as u32,
+    };
+
+    assert_eq!(tokens.len(), 15);
+    assert_eq!(tokens[0], token(TokenKind::Comment, "// head\n", 1, 1, 0));
+    assert_eq!(tokens[1], token(TokenKind::Keyword, "module", 2, 1, 8));
+    assert_eq!(tokens[2], token(TokenKind::Identifier, "A", 2, 8, 15));
+    assert_eq!(tokens[3], token(TokenKind::Punctuation, "{", 2, 10, 17));
+    assert_eq!(tokens[9], token(TokenKind::Number, "1", 3, 20, 38));
+    assert_eq!(tokens[10], token(TokenKind::Operator, "+", 3, 22, 40));
+    assert_eq!(
+        tokens[13],
+        token(TokenKind::Comment, "// tail\n", 3, 27, 45)
+    );
+    assert_eq!(tokens[14], token(TokenKind::Punctuation, "}", 4, 1, 53));
+}
+
+#[test]
+fn recovery() {
+    // Erroneous statements are skipped
+    let code = r#"
+    module A {
+        var a: logic;
+        var b: logic;
+        assign a = ;
+        assign b = 1 +;
+    }
+    module B {}
+    "#;
+    let (parser, errors) = Parser::parse_with_recovery(code, &"");
+    assert!(parser.is_some());
+    assert_eq!(errors.len(), 2);
+
+    // Erroneous declaration is skipped with its body
+    let code = r#"
+    module A (
+        a: input logic
+        b: input logic,
+    ) {
+        // comment including } and ;
+        assign b = a;
+    }
+    module B {
+        var a: logic;
+    }}
+    "#;
+    let (parser, errors) = Parser::parse_with_recovery(code, &"");
+    assert!(parser.is_some());
+    assert_eq!(errors.len(), 2);
+
+    // Valid code has no error
+    let code = "module A {}";
+    let (parser, errors) = Parser::parse_with_recovery(code, &"");
+    assert!(parser.is_some());
+    assert!(errors.is_empty());
+
+    // Reparse is stopped by the limit of total parsed bytes
+    let code = "module A { assign a = ; assign b = ; }";
+    let (parser, errors) = Parser::parse_with_recovery_limit(code, &"", code.len() * 2);
+    assert!(parser.is_none());
+    assert_eq!(errors.len(), 2);
+    let (parser, errors) = Parser::parse_with_recovery_limit(code, &"", code.len() * 3);
+    assert!(parser.is_some());
+    assert_eq!(errors.len(), 2);
+}
+
+#[track_caller]
+fn suggestion(code: &str, expected: &str) {
+    let code = format!("module A {{ {} }}", code);
+    let parser = Parser::parse(&code, &"");
+    let Err(ParserError::SyntaxError(x)) = parser else {
+        panic!("syntax error is expected");
+    };
+    assert_eq!(x.to_string(), expected);
+}
+
+#[test]
+fn did_you_mean() {
+    suggestion(
+        "logic a;",
+        "Unexpected token: 'logic', do you mean \"variable declaration 'var <name>: logic;'\" ?",
+    );
+    suggestion(
+        "always_comb begin a = 1; end",
+        "Unexpected token: 'identifier', do you mean \"'{'\" ?",
+    );
+    suggestion(
+        "always_comb { a <= 1; }",
+        "Unexpected token: 'operator', do you mean \"assignment operator '='\" ?",
+    );
+    suggestion(
+        "parameter A = 1;",
+        "Unexpected token: 'identifier', do you mean \"'param'\" ?",
+    );
+    suggestion(
+        "asign a = 1;",
+        "Unexpected token: 'identifier', do you mean \"keyword 'assign'\" ?",
+    );
+    suggestion("a b c;", "Unexpected token: 'identifier'");
+}
@@ -3,6 +3,7 @@ use crate::veryl_token::VerylToken;
 
 macro_rules! before {
     ($x:ident, $y:ident, $z:ident) => {
+        $x.enter_non_terminal(stringify!($y));
         if let Some(mut handlers) = $x.get_handlers() {
             for handler in handlers.iter_mut() {
                 handler.set_point(HandlerPoint::Before);
@@ -20,6 +21,7 @@ macro_rules! after {
                 let _ = handler.$y($z);
             }
         }
+        $x.leave_non_terminal(stringify!($y));
     };
 }
 
@@ -27,6 +29,12 @@ pub trait VerylWalker {
     /// Semantic action for non-terminal 'VerylToken'
     fn veryl_token(&mut self, _arg: &VerylToken) {}
 
+    /// Called before walking any non-terminal with the method name like `module_declaration`
+    fn enter_non_terminal(&mut self, _name: &'static str) {}
+
+    /// Called after walking any non-terminal with the method name like `module_declaration`
+    fn leave_non_terminal(&mut self, _name: &'static str) {}
+
     /// Semantic action for non-terminal 'Start'
     fn start(&mut self, arg: &Start) {
         before!(self, start, arg);
@@ -2447,15 +2455,18 @@ pub trait VerylWalker {
 
     /// Semantic action for non-terminal 'PortTypeConcrete'
     fn port_type_concrete(&mut self, arg: &PortTypeConcrete) {
+        self.enter_non_terminal("port_type_concrete");
         if let Some(ref x) = arg.port_type_concrete_opt {
             self.clock_domain(&x.clock_domain);
         }
         self.direction(&arg.direction);
         self.array_type(&arg.array_type);
+        self.leave_non_terminal("port_type_concrete");
     }
 
     /// Semantic action for non-terminal 'PortTypeAbstract'
     fn port_type_abstract(&mut self, arg: &PortTypeAbstract) {
+        self.enter_non_terminal("port_type_abstract");
         if let Some(ref x) = arg.port_type_abstract_opt {
             self.clock_domain(&x.clock_domain);
         }
@@ -2463,6 +2474,7 @@ pub trait VerylWalker {
         if let Some(ref x) = arg.port_type_abstract_opt0 {
             self.array(&x.array);
         }
+        self.leave_non_terminal("port_type_abstract");
     }
 
     /// Semantic action for non-terminal 'Direction'
@@ -2853,6 +2865,7 @@ pub trait VerylWalker {
         if let Some(ref x) = arg.proto_module_declaration_opt {
             self.r#pub(&x.r#pub);
         }
+        self.proto(&arg.proto);
         self.module(&arg.module);
         self.identifier(&arg.identifier);
         if let Some(ref x) = arg.proto_module_declaration_opt0 {
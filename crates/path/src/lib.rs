@@ -23,12 +23,17 @@ pub fn cache_path() -> PathBuf {
     project_dir.cache_dir().to_path_buf()
 }
 
-pub fn gather_files_with_extension<T: AsRef<Path>>(
-    base_dir: T,
-    ext: &str,
-    symlink: bool,
-) -> Result<Vec<PathBuf>, PathError> {
+/// Whether `path` is `Veryl.toml` of a project.
+/// `Veryl.toml` without `[project]` is a fragment overriding settings of a subdirectory.
+pub fn is_project_toml<T: AsRef<Path>>(path: T) -> bool {
+    std::fs::read_to_string(path)
+        .map(|x| x.lines().any(|x| x.trim_start().starts_with("[project]")))
+        .unwrap_or(false)
+}
+
+fn gather_metadata<T: AsRef<Path>>(base_dir: T, symlink: bool) -> (Vec<PathBuf>, Vec<PathBuf>) {
     let mut inner_prj = Vec::new();
+    let mut fragments = Vec::new();
     for entry in WalkDir::new(base_dir.as_ref())
         .follow_links(symlink)
         .into_iter()
@@ -39,14 +44,34 @@ pub fn gather_files_with_extension<T: AsRef<Path>>(
                 if x == "Veryl.toml" {
                     let prj_dir = entry.path().parent().unwrap();
                     if prj_dir != base_dir.as_ref() {
-                        debug!("Found inner project ({})", prj_dir.to_string_lossy());
-                        inner_prj.push(prj_dir.to_path_buf());
+                        if is_project_toml(entry.path()) {
+                            debug!("Found inner project ({})", prj_dir.to_string_lossy());
+                            inner_prj.push(prj_dir.to_path_buf());
+                        } else {
+                            fragments.push(entry.path().to_path_buf());
+                        }
                     }
                 }
             }
         }
     }
 
+    fragments.retain(|x| !inner_prj.iter().any(|y| x.starts_with(y)));
+    (inner_prj, fragments)
+}
+
+/// Gather `Veryl.toml` fragments in subdirectories except inner projects
+pub fn gather_metadata_fragments<T: AsRef<Path>>(base_dir: T, symlink: bool) -> Vec<PathBuf> {
+    gather_metadata(base_dir, symlink).1
+}
+
+pub fn gather_files_with_extension<T: AsRef<Path>>(
+    base_dir: T,
+    ext: &str,
+    symlink: bool,
+) -> Result<Vec<PathBuf>, PathError> {
+    let (inner_prj, _) = gather_metadata(base_dir.as_ref(), symlink);
+
     let mut ret = Vec::new();
     for entry in WalkDir::new(base_dir.as_ref())
         .follow_links(symlink)
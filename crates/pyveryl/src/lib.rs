@@ -0,0 +1,232 @@
+//! Python bindings of Veryl to script against the design database.
+//!
+//! ```python
+//! import pyveryl
+//!
+//! metadata = pyveryl.Metadata.load("Veryl.toml")
+//! design = metadata.analyze()
+//! for module in design.modules():
+//!     print(module.name, [(x.name, x.direction) for x in module.ports])
+//! for path, code in design.emit().items():
+//!     print(path, len(code))
+//! ```
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use veryl_analyzer::namespace::Namespace;
+use veryl_analyzer::symbol::{Symbol as VerylSymbol, SymbolKind};
+use veryl_analyzer::symbol_path::SymbolPath;
+use veryl_analyzer::{symbol_table, Analyzer};
+use veryl_emitter::Emitter;
+use veryl_metadata::Metadata as VerylMetadata;
+use veryl_parser::{resource_table, veryl_token::TokenSource, Parser};
+use veryl_path::PathSet;
+
+fn to_py_err<T: ToString>(x: T) -> PyErr {
+    PyRuntimeError::new_err(x.to_string())
+}
+
+#[pyclass(unsendable)]
+pub struct Metadata {
+    metadata: VerylMetadata,
+}
+
+#[pymethods]
+impl Metadata {
+    /// Loads `Veryl.toml`, which is searched from the current directory if `path` is omitted
+    #[staticmethod]
+    #[pyo3(signature = (path=None))]
+    fn load(path: Option<PathBuf>) -> PyResult<Self> {
+        let path = match path {
+            Some(x) => x,
+            None => VerylMetadata::search_from_current().map_err(to_py_err)?,
+        };
+        let metadata = VerylMetadata::load(path).map_err(to_py_err)?;
+        Ok(Self { metadata })
+    }
+
+    #[getter]
+    fn project_name(&self) -> String {
+        self.metadata.project.name.clone()
+    }
+
+    #[getter]
+    fn project_path(&self) -> PathBuf {
+        self.metadata.project_path()
+    }
+
+    /// Parses and analyzes all sources of the project
+    fn analyze(&self) -> PyResult<Design> {
+        let mut metadata = self.metadata.clone();
+        let paths = metadata.paths::<PathBuf>(&[], true).map_err(to_py_err)?;
+
+        Analyzer::new(&metadata).clear();
+
+        let mut sources = Vec::new();
+        let mut errors = Vec::new();
+        for path in paths {
+            let input = fs::read_to_string(&path.src).map_err(to_py_err)?;
+            let parser = Parser::parse(&input, &path.src).map_err(to_py_err)?;
+            let analyzer = Analyzer::new(&metadata.for_file(&path.src));
+            errors.append(&mut analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl));
+            sources.push(Source {
+                path,
+                input,
+                parser,
+                analyzer,
+            });
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        for x in &sources {
+            errors.append(&mut x.analyzer.analyze_pass2(
+                &x.path.prj,
+                &x.input,
+                &x.path.src,
+                &x.parser.veryl,
+            ));
+        }
+        for x in &sources {
+            errors.append(&mut x.analyzer.analyze_pass3(
+                &x.path.prj,
+                &x.input,
+                &x.path.src,
+                &x.parser.veryl,
+            ));
+        }
+
+        let errors = errors.iter().map(|x| x.to_string()).collect();
+        Ok(Design {
+            metadata,
+            sources,
+            errors,
+        })
+    }
+}
+
+struct Source {
+    path: PathSet,
+    input: String,
+    parser: Parser,
+    analyzer: Analyzer,
+}
+
+/// Analyzed project.
+/// Symbol queries refer the tables of the thread, so only the latest `Design` is valid.
+#[pyclass(unsendable)]
+pub struct Design {
+    metadata: VerylMetadata,
+    sources: Vec<Source>,
+    /// Messages of errors and warnings found by the analysis
+    #[pyo3(get)]
+    errors: Vec<String>,
+}
+
+#[pymethods]
+impl Design {
+    /// Modules defined in the project
+    fn modules(&self) -> Vec<Symbol> {
+        let project = resource_table::insert_str(&self.metadata.project.name);
+        symbol_table::get_all()
+            .iter()
+            .filter(|x| {
+                matches!(x.kind, SymbolKind::Module(_))
+                    && x.namespace.paths.first() == Some(&project)
+            })
+            .map(Symbol::from)
+            .collect()
+    }
+
+    /// Returns the symbol of `path` like `ModuleA` or `PackageA::TypeA`
+    fn symbol(&self, path: &str) -> Option<Symbol> {
+        let mut namespace = Namespace::new();
+        namespace.push(resource_table::insert_str(&self.metadata.project.name));
+        let path: Vec<_> = path.split("::").map(resource_table::insert_str).collect();
+        let path = SymbolPath::new(&path);
+        symbol_table::resolve((&path, &namespace))
+            .ok()
+            .map(|x| Symbol::from(&x.found))
+    }
+
+    /// Emits SystemVerilog and returns the contents for each output path
+    fn emit(&self) -> BTreeMap<PathBuf, String> {
+        let mut ret = BTreeMap::new();
+        for x in &self.sources {
+            let metadata = self.metadata.for_file(&x.path.src);
+            let mut emitter = Emitter::new(&metadata, &x.path.src, &x.path.dst, &x.path.map);
+            emitter.emit(&x.path.prj, &x.parser.veryl);
+            ret.insert(x.path.dst.clone(), emitter.as_str().to_string());
+        }
+        ret
+    }
+}
+
+#[pyclass]
+#[derive(Clone)]
+pub struct Port {
+    #[pyo3(get)]
+    name: String,
+    /// Direction like `input`, `output` or `modport`
+    #[pyo3(get)]
+    direction: String,
+    #[pyo3(get, name = "type")]
+    r#type: Option<String>,
+}
+
+#[pyclass(get_all)]
+#[derive(Clone)]
+pub struct Symbol {
+    name: String,
+    kind: String,
+    namespace: String,
+    /// Source file defining the symbol, which is `None` for builtin symbols
+    path: Option<PathBuf>,
+    line: u32,
+    /// Ports if the symbol is module
+    ports: Vec<Port>,
+}
+
+impl From<&VerylSymbol> for Symbol {
+    fn from(value: &VerylSymbol) -> Self {
+        let path = if let TokenSource::File(x) = value.token.source {
+            Some(PathBuf::from(x.to_string()))
+        } else {
+            None
+        };
+
+        let mut ports = Vec::new();
+        if let SymbolKind::Module(ref x) = value.kind {
+            for port in &x.ports {
+                if let Some(SymbolKind::Port(x)) = symbol_table::get(port.symbol).map(|x| x.kind) {
+                    ports.push(Port {
+                        name: port.name.to_string(),
+                        direction: x.direction.to_string(),
+                        r#type: x.r#type.map(|x| x.to_string()),
+                    });
+                }
+            }
+        }
+
+        Self {
+            name: value.token.to_string(),
+            kind: value.kind.to_string(),
+            namespace: value.namespace.to_string(),
+            path,
+            line: value.token.line,
+            ports,
+        }
+    }
+}
+
+#[pymodule]
+fn pyveryl(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Metadata>()?;
+    m.add_class::<Design>()?;
+    m.add_class::<Symbol>()?;
+    m.add_class::<Port>()?;
+    Ok(())
+}
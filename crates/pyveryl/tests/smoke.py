@@ -0,0 +1,35 @@
+# Smoke test of pyveryl which is run after `maturin build` and installing the wheel
+
+import pathlib
+import tempfile
+
+import pyveryl
+
+with tempfile.TemporaryDirectory() as dir:
+    dir = pathlib.Path(dir)
+    (dir / "Veryl.toml").write_text(
+        '[project]\nname = "prj"\nversion = "0.1.0"\n\n[build]\ntarget = {type = "source"}\n'
+    )
+    (dir / "src").mkdir()
+    (dir / "src" / "a.veryl").write_text(
+        "module ModuleA (\n    i_a: input logic,\n    o_b: output logic,\n) {\n    assign o_b = i_a;\n}\n"
+    )
+
+    metadata = pyveryl.Metadata.load(str(dir / "Veryl.toml"))
+    assert metadata.project_name == "prj"
+
+    design = metadata.analyze()
+    assert design.errors == [], design.errors
+
+    modules = design.modules()
+    assert [x.name for x in modules] == ["ModuleA"]
+    assert [(x.name, x.direction) for x in modules[0].ports] == [
+        ("i_a", "input"),
+        ("o_b", "output"),
+    ]
+
+    emitted = design.emit()
+    assert len(emitted) == 1
+    assert "module prj_ModuleA" in next(iter(emitted.values()))
+
+print("ok")
@@ -0,0 +1,315 @@
+use crate::{symbol_tokens, Site, Source, TextEdit};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use veryl_analyzer::symbol::{Symbol, SymbolId, SymbolKind};
+use veryl_analyzer::symbol_table::is_sv_keyword;
+use veryl_analyzer::{namespace_table, symbol_table, AnalyzerError};
+use veryl_metadata::Case;
+use veryl_parser::resource_table::TokenId;
+use veryl_parser::veryl_token::{Token, TokenSource};
+
+/// Renaming of a symbol
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Rename {
+    pub path: PathBuf,
+    pub line: u32,
+    pub column: u32,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NamingChange {
+    pub edits: Vec<(PathBuf, Vec<TextEdit>)>,
+    pub renames: Vec<Rename>,
+    /// Violations which can't be fixed automatically
+    pub skipped: Vec<Site>,
+}
+
+/// Renames symbols reported by `invalid_identifier` in `errors` to satisfy the naming rules.
+/// Only symbols declared in `sources` are renamed.
+///
+/// Case rules are applied before prefix and suffix rules.
+/// Violations of regular expression rules are skipped because the compliant name can't be
+/// inferred.
+pub fn apply_naming(sources: &[Source], errors: &[AnalyzerError]) -> NamingChange {
+    let symbols = symbol_table::get_all();
+    let tokens = symbol_tokens(&symbols);
+    let editable = |x: &Token| sources.iter().any(|y| y.path == path_of(x));
+
+    let symbol_map: HashMap<SymbolId, &Symbol> = symbols.iter().map(|x| (x.id, x)).collect();
+    let mut symbols_by_name: HashMap<String, Vec<&Symbol>> = HashMap::new();
+    for x in &symbols {
+        symbols_by_name
+            .entry(x.token.to_string())
+            .or_default()
+            .push(x);
+    }
+    let mut tokens_by_pos: HashMap<u32, Vec<(Token, SymbolId)>> = HashMap::new();
+    for (token, id) in &tokens {
+        tokens_by_pos
+            .entry(token.pos)
+            .or_default()
+            .push((*token, *id));
+    }
+
+    // Violated rules of each symbol
+    let mut violations: BTreeMap<SymbolId, Vec<String>> = BTreeMap::new();
+    for error in errors {
+        let AnalyzerError::InvalidIdentifier {
+            identifier,
+            rule,
+            input,
+            error_location,
+        } = error
+        else {
+            continue;
+        };
+
+        let Some(candidates) = tokens_by_pos.get(&(error_location.offset() as u32)) else {
+            continue;
+        };
+        let ids = candidates.iter().filter_map(|(token, id)| {
+            let found =
+                token.source.to_string() == input.name() && token.to_string() == *identifier;
+            found.then_some(*id)
+        });
+        for id in ids {
+            let symbol = symbol_map[&id];
+            if symbol.token.to_string() != *identifier || !editable(&symbol.token) {
+                continue;
+            }
+            let rules = violations.entry(id).or_default();
+            if !rules.contains(rule) {
+                rules.push(rule.clone());
+            }
+        }
+    }
+
+    let mut ret = NamingChange::default();
+    let mut renamed: HashMap<SymbolId, String> = HashMap::new();
+    let mut renamed_by_name: HashMap<String, Vec<&Symbol>> = HashMap::new();
+    for (id, rules) in &violations {
+        let symbol = symbol_map[id];
+        let from = symbol.token.to_string();
+        let skip = |message: String| Site {
+            path: path_of(&symbol.token),
+            line: symbol.token.line,
+            column: symbol.token.column,
+            message,
+        };
+
+        let Some(to) = conform(&from, rules) else {
+            ret.skipped.push(skip(format!(
+                "`{from}` violates \"{}\" which can't be fixed automatically",
+                rules.join("\", \"")
+            )));
+            continue;
+        };
+        if to == from {
+            continue;
+        }
+
+        let conflict = [&symbols_by_name, &renamed_by_name].iter().any(|x| {
+            x.get(&to)
+                .map(|x| x.iter().any(|x| x.namespace == symbol.namespace))
+                .unwrap_or(false)
+        });
+        if conflict || is_sv_keyword(&to) {
+            ret.skipped
+                .push(skip(format!("`{from}` can't be renamed to `{to}`")));
+            continue;
+        }
+
+        ret.renames.push(Rename {
+            path: path_of(&symbol.token),
+            line: symbol.token.line,
+            column: symbol.token.column,
+            from,
+            to: to.clone(),
+        });
+        renamed_by_name.entry(to.clone()).or_default().push(symbol);
+        renamed.insert(*id, to);
+    }
+
+    // Symbols referred by each token
+    let mut referred: Vec<(Token, Vec<SymbolId>)> = Vec::new();
+    let mut referred_index: HashMap<TokenId, usize> = HashMap::new();
+    for (token, id) in &tokens {
+        match referred_index.get(&token.id) {
+            Some(x) => referred[*x].1.push(*id),
+            None => {
+                referred_index.insert(token.id, referred.len());
+                referred.push((*token, vec![*id]));
+            }
+        }
+    }
+
+    let mut edits: HashMap<PathBuf, Vec<TextEdit>> = HashMap::new();
+    for (token, ids) in &referred {
+        if !ids.iter().any(|x| renamed.contains_key(x)) {
+            continue;
+        }
+        if !matches!(token.source, TokenSource::File(_)) || !editable(token) {
+            continue;
+        }
+
+        let text = token.to_string();
+        let targets: Vec<_> = ids
+            .iter()
+            .filter_map(|x| symbol_map.get(x).copied())
+            .filter(|x| x.token.to_string() == text)
+            .collect();
+
+        // The token is a part of hierarchical reference like `a.b`
+        if targets.iter().all(|x| !renamed.contains_key(&x.id)) {
+            for x in ids.iter().filter(|x| renamed.contains_key(x)) {
+                let symbol = symbol_map[x];
+                ret.skipped.push(Site {
+                    path: path_of(token),
+                    line: token.line,
+                    column: token.column,
+                    message: format!(
+                        "reference to `{}` should be renamed to `{}` manually",
+                        symbol.token, renamed[x]
+                    ),
+                });
+            }
+            continue;
+        }
+
+        let name = |x: &SymbolId| renamed.get(x).cloned().unwrap_or(text.clone());
+        let new_text = match targets.as_slice() {
+            [x] => name(&x.id),
+            _ => {
+                // Implicit connection like `inst u: M (a);` refers both the port and the signal
+                let namespace = namespace_table::get(token.id);
+                let is_port = |x: &Symbol| {
+                    matches!(x.kind, SymbolKind::Port(_))
+                        && namespace
+                            .as_ref()
+                            .map(|y| !y.included(&x.namespace))
+                            .unwrap_or(false)
+                };
+                let port = targets.iter().find(|x| is_port(x));
+                let signal = targets.iter().find(|x| !is_port(x));
+                match (port, signal) {
+                    (Some(port), Some(signal)) if name(&port.id) != name(&signal.id) => {
+                        format!("{}: {}", name(&port.id), name(&signal.id))
+                    }
+                    _ => name(&targets[0].id),
+                }
+            }
+        };
+
+        edits.entry(path_of(token)).or_default().push(TextEdit {
+            start: token.pos as usize,
+            end: (token.pos + token.length) as usize,
+            text: new_text,
+        });
+    }
+
+    ret.edits = edits.into_iter().collect();
+    ret.edits.sort_by(|a, b| a.0.cmp(&b.0));
+    ret.renames.sort();
+    ret.skipped.sort();
+    ret.skipped.dedup();
+    ret
+}
+
+fn path_of(token: &Token) -> PathBuf {
+    PathBuf::from(token.source.to_string())
+}
+
+/// Compliant name of `identifier` for the violated `rules`
+fn conform(identifier: &str, rules: &[String]) -> Option<String> {
+    let mut ret = identifier.to_string();
+
+    for rule in rules {
+        if let Some(case) = rule.strip_prefix("case: ") {
+            let case = [
+                Case::Snake,
+                Case::ScreamingSnake,
+                Case::UpperCamel,
+                Case::LowerCamel,
+            ]
+            .into_iter()
+            .find(|x| x.to_string() == case)?;
+            ret = convert_case(&ret, &case);
+        }
+    }
+
+    for rule in rules {
+        if let Some(prefix) = rule.strip_prefix("prefix: ") {
+            if !ret.starts_with(prefix) {
+                ret = format!("{prefix}{ret}");
+            }
+        } else if let Some(suffix) = rule.strip_prefix("suffix: ") {
+            if !ret.ends_with(suffix) {
+                ret = format!("{ret}{suffix}");
+            }
+        } else if !rule.starts_with("case: ") {
+            return None;
+        }
+    }
+
+    Some(ret)
+}
+
+/// Words of the identifier split by `_` and case boundaries
+fn words(identifier: &str) -> Vec<String> {
+    let mut ret = Vec::new();
+    for part in identifier.split('_').filter(|x| !x.is_empty()) {
+        let chars: Vec<_> = part.chars().collect();
+        let mut word = String::new();
+        for (i, c) in chars.iter().enumerate() {
+            let boundary = i != 0
+                && c.is_ascii_uppercase()
+                && (chars[i - 1].is_ascii_lowercase()
+                    || chars[i - 1].is_ascii_digit()
+                    || chars.get(i + 1).map(|x| x.is_ascii_lowercase()) == Some(true)
+                        && chars[i - 1].is_ascii_uppercase());
+            if boundary {
+                ret.push(std::mem::take(&mut word));
+            }
+            word.push(*c);
+        }
+        ret.push(word);
+    }
+    ret
+}
+
+fn convert_case(identifier: &str, case: &Case) -> String {
+    let capitalize = |x: &str| {
+        let lower = x.to_ascii_lowercase();
+        let mut chars = lower.chars();
+        match chars.next() {
+            Some(head) => format!("{}{}", head.to_ascii_uppercase(), chars.as_str()),
+            None => String::new(),
+        }
+    };
+
+    let words = words(identifier);
+    match case {
+        Case::Snake => {
+            let words: Vec<_> = words.iter().map(|x| x.to_ascii_lowercase()).collect();
+            words.join("_")
+        }
+        Case::ScreamingSnake => {
+            let words: Vec<_> = words.iter().map(|x| x.to_ascii_uppercase()).collect();
+            words.join("_")
+        }
+        Case::UpperCamel => words.iter().map(|x| capitalize(x)).collect(),
+        Case::LowerCamel => words
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                if i == 0 {
+                    x.to_ascii_lowercase()
+                } else {
+                    capitalize(x)
+                }
+            })
+            .collect(),
+    }
+}
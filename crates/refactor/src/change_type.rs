@@ -0,0 +1,356 @@
+use crate::refactor_error::RefactorError;
+use crate::{symbol_tokens, Source, TextEdit, TokenRange};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use veryl_analyzer::symbol::{Symbol, SymbolId, SymbolKind};
+use veryl_analyzer::{namespace_table, symbol_table};
+use veryl_parser::resource_table::TokenId;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, VerylToken};
+use veryl_parser::veryl_walker::VerylWalker;
+use veryl_parser::Parser;
+
+/// Location in the source with the description
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Site {
+    pub path: PathBuf,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TypeChange {
+    pub edits: Vec<(PathBuf, Vec<TextEdit>)>,
+    /// Declarations whose types are changed
+    pub changes: Vec<Site>,
+    /// Sites which may need to be adjusted manually
+    pub reviews: Vec<Site>,
+}
+
+/// Changes the declared type of `target` like `ModuleA::a` to `new_type`.
+///
+/// Declarations connected to the changed one by plain copies like `assign b = a;` are changed too
+/// if they have the same type as the original one, and instance connections to the changed port
+/// are followed in the same way. Other references are reported as sites to be reviewed.
+pub fn change_type(
+    sources: &[Source],
+    target: &str,
+    new_type: &str,
+) -> Result<TypeChange, RefactorError> {
+    let dummy = format!("module RefactorChangeType #(param P: {new_type} = 0) {{}}");
+    if Parser::parse(&dummy, &"").is_err() {
+        return Err(RefactorError::InvalidType(new_type.to_string()));
+    }
+
+    let symbols = symbol_table::get_all();
+    let candidates: Vec<_> = symbols
+        .iter()
+        .filter(|x| {
+            let mut path: Vec<_> = x
+                .namespace
+                .paths
+                .iter()
+                .skip(1)
+                .map(|x| x.to_string())
+                .collect();
+            path.push(x.token.to_string());
+            path.join("::") == target
+        })
+        .collect();
+    let target = match candidates.as_slice() {
+        [] => {
+            return Err(RefactorError::InvalidSelection(format!(
+                "`{target}` is not found"
+            )));
+        }
+        [x] => *x,
+        _ => {
+            return Err(RefactorError::InvalidSelection(format!(
+                "`{target}` is ambiguous"
+            )));
+        }
+    };
+
+    // Symbols referred by each token
+    let mut referred: HashMap<TokenId, Vec<SymbolId>> = HashMap::new();
+    for (token, id) in symbol_tokens(&symbols) {
+        referred.entry(token.id).or_default().push(id);
+    }
+
+    let mut collector = Collector::default();
+    for (i, x) in sources.iter().enumerate() {
+        collector.source = i;
+        collector.veryl(x.veryl);
+    }
+
+    let type_of = |symbol: &Symbol| -> Option<&TypeRange> { collector.types.get(&symbol.token.id) };
+    let type_text =
+        |x: &TypeRange| -> String { sources[x.source].input[x.start..x.end].to_string() };
+    let normalize = |x: &str| -> String { x.split_whitespace().collect() };
+
+    let Some(target_type) = type_of(target) else {
+        return Err(RefactorError::Unsupported(format!(
+            "changing type of {} `{}`",
+            target.kind.to_kind_name(),
+            target.token
+        )));
+    };
+    let old_type = type_text(target_type);
+
+    let symbol_of = |token: &Token| -> Vec<&Symbol> {
+        referred
+            .get(&token.id)
+            .map(|x| {
+                x.iter()
+                    .filter_map(|x| symbols.iter().find(|y| y.id == *x))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut ret = TypeChange::default();
+    let mut changed = vec![target.id];
+    let mut queue = vec![target];
+    let mut handled = HashSet::new();
+    let mut reviews = Vec::new();
+
+    while let Some(symbol) = queue.pop() {
+        for edge in &collector.edges {
+            let (this, other) = if symbol_of(&edge.lhs).iter().any(|x| x.id == symbol.id) {
+                (&edge.lhs, &edge.rhs)
+            } else if symbol_of(&edge.rhs).iter().any(|x| x.id == symbol.id) {
+                (&edge.rhs, &edge.lhs)
+            } else {
+                continue;
+            };
+            handled.insert(this.id);
+            handled.insert(other.id);
+
+            // Shorthand connection refers both the port and the connected signal
+            let others: Vec<_> = symbol_of(other)
+                .into_iter()
+                .filter(|x| x.id != symbol.id)
+                .collect();
+            for other_symbol in others {
+                if changed.contains(&other_symbol.id) {
+                    continue;
+                }
+
+                // Ports of the instantiated module connected to the changed signal are not followed
+                let port_of_instance = edge.connection
+                    && matches!(other_symbol.kind, SymbolKind::Port(_))
+                    && namespace_table::get(edge.lhs.id)
+                        .map(|x| !x.included(&other_symbol.namespace))
+                        .unwrap_or(false);
+                if port_of_instance {
+                    reviews.push((
+                        *other,
+                        format!(
+                            "port `{}` connected to `{}` is not changed",
+                            other_symbol.token, symbol.token
+                        ),
+                    ));
+                    continue;
+                }
+
+                match type_of(other_symbol) {
+                    Some(x) if normalize(&type_text(x)) == normalize(&old_type) => {
+                        changed.push(other_symbol.id);
+                        queue.push(other_symbol);
+                    }
+                    Some(x) => {
+                        reviews.push((
+                            *other,
+                            format!(
+                                "`{}` has type `{}` different from `{old_type}`",
+                                other_symbol.token,
+                                type_text(x)
+                            ),
+                        ));
+                    }
+                    None => {
+                        reviews.push((
+                            *other,
+                            format!(
+                                "`{}` is connected to `{}`",
+                                other_symbol.token, symbol.token
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut edits: HashMap<PathBuf, Vec<TextEdit>> = HashMap::new();
+    for id in &changed {
+        let symbol = symbols.iter().find(|x| x.id == *id).unwrap();
+        let range = type_of(symbol).unwrap();
+        let path = sources[range.source].path.to_path_buf();
+        edits.entry(path.clone()).or_default().push(TextEdit {
+            start: range.start,
+            end: range.end,
+            text: new_type.to_string(),
+        });
+        ret.changes.push(Site {
+            path,
+            line: symbol.token.line,
+            column: symbol.token.column,
+            message: format!("`{}`: `{}` -> `{new_type}`", symbol.token, type_text(range)),
+        });
+
+        for token in &symbol.references {
+            if token.id != symbol.token.id && !handled.contains(&token.id) {
+                reviews.push((*token, format!("`{}` is referred", symbol.token)));
+            }
+        }
+    }
+
+    for (token, message) in reviews {
+        ret.reviews.push(Site {
+            path: PathBuf::from(token.source.to_string()),
+            line: token.line,
+            column: token.column,
+            message,
+        });
+    }
+
+    ret.edits = edits.into_iter().collect();
+    ret.edits.sort_by(|a, b| a.0.cmp(&b.0));
+    ret.changes.sort();
+    ret.reviews.sort();
+    ret.reviews.dedup();
+    Ok(ret)
+}
+
+/// Byte range of the declared type
+struct TypeRange {
+    source: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Plain copy between two identifiers
+struct Edge {
+    lhs: Token,
+    rhs: Token,
+    /// Connection of instance port
+    connection: bool,
+}
+
+#[derive(Default)]
+struct Collector {
+    source: usize,
+    types: HashMap<TokenId, TypeRange>,
+    edges: Vec<Edge>,
+}
+
+/// The token if `f` walks just a single token
+fn single_token<F: FnOnce(&mut TokenRange)>(f: F) -> Option<Token> {
+    let mut range = TokenRange::default();
+    f(&mut range);
+    match (range.first, range.last) {
+        (Some(first), Some(last)) if first.token.id == last.token.id => Some(first.token),
+        _ => None,
+    }
+}
+
+impl Collector {
+    fn add_type<F: FnOnce(&mut TokenRange)>(&mut self, identifier: &VerylToken, f: F) {
+        let mut range = TokenRange::default();
+        f(&mut range);
+        if let (Some(first), Some(last)) = (range.first, range.last) {
+            self.types.insert(
+                identifier.token.id,
+                TypeRange {
+                    source: self.source,
+                    start: first.token.pos as usize,
+                    end: (last.token.pos + last.token.length) as usize,
+                },
+            );
+        }
+    }
+
+    fn add_edge(&mut self, lhs: Option<Token>, rhs: Option<Token>, connection: bool) {
+        if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+            self.edges.push(Edge {
+                lhs,
+                rhs,
+                connection,
+            });
+        }
+    }
+}
+
+impl VerylWalker for Collector {
+    fn var_declaration(&mut self, arg: &VarDeclaration) {
+        self.add_type(&arg.identifier.identifier_token, |x| {
+            x.array_type(&arg.array_type)
+        });
+    }
+
+    fn let_declaration(&mut self, arg: &LetDeclaration) {
+        self.add_type(&arg.identifier.identifier_token, |x| {
+            x.array_type(&arg.array_type)
+        });
+        let rhs = single_token(|x| x.expression(&arg.expression));
+        self.add_edge(Some(arg.identifier.identifier_token.token), rhs, false);
+    }
+
+    fn const_declaration(&mut self, arg: &ConstDeclaration) {
+        self.add_type(&arg.identifier.identifier_token, |x| {
+            match arg.const_declaration_group.as_ref() {
+                ConstDeclarationGroup::ArrayType(y) => x.array_type(&y.array_type),
+                ConstDeclarationGroup::Type(y) => x.r#type(&y.r#type),
+            }
+        });
+        let rhs = single_token(|x| x.expression(&arg.expression));
+        self.add_edge(Some(arg.identifier.identifier_token.token), rhs, false);
+    }
+
+    fn with_parameter_item(&mut self, arg: &WithParameterItem) {
+        self.add_type(&arg.identifier.identifier_token, |x| {
+            match arg.with_parameter_item_group0.as_ref() {
+                WithParameterItemGroup0::ArrayType(y) => x.array_type(&y.array_type),
+                WithParameterItemGroup0::Type(y) => x.r#type(&y.r#type),
+            }
+        });
+        let rhs = single_token(|x| x.expression(&arg.expression));
+        self.add_edge(Some(arg.identifier.identifier_token.token), rhs, false);
+    }
+
+    fn port_declaration_item(&mut self, arg: &PortDeclarationItem) {
+        if let PortDeclarationItemGroup::PortTypeConcrete(ref x) = *arg.port_declaration_item_group
+        {
+            self.add_type(&arg.identifier.identifier_token, |y| {
+                y.array_type(&x.port_type_concrete.array_type)
+            });
+        }
+    }
+
+    fn assign_declaration(&mut self, arg: &AssignDeclaration) {
+        let lhs = single_token(|x| x.hierarchical_identifier(&arg.hierarchical_identifier));
+        let rhs = single_token(|x| x.expression(&arg.expression));
+        self.add_edge(lhs, rhs, false);
+    }
+
+    fn identifier_statement(&mut self, arg: &IdentifierStatement) {
+        if let IdentifierStatementGroup::Assignment(ref x) = *arg.identifier_statement_group {
+            if let AssignmentGroup::Equ(_) = *x.assignment.assignment_group {
+                let lhs = single_token(|x| x.expression_identifier(&arg.expression_identifier));
+                let rhs = single_token(|y| y.expression(&x.assignment.expression));
+                self.add_edge(lhs, rhs, false);
+            }
+        }
+    }
+
+    fn inst_port_item(&mut self, arg: &InstPortItem) {
+        let lhs = arg.identifier.identifier_token.token;
+        let rhs = match &arg.inst_port_item_opt {
+            Some(x) => single_token(|y| y.expression(&x.expression)),
+            None => Some(lhs),
+        };
+        self.add_edge(Some(lhs), rhs, true);
+    }
+}
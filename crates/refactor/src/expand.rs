@@ -0,0 +1,526 @@
+use crate::refactor_error::RefactorError;
+use crate::{render, symbol_tokens, Source, TextEdit, TokenRange};
+use std::collections::HashMap;
+use std::path::Path;
+use veryl_analyzer::evaluator::{Evaluated, Evaluator};
+use veryl_analyzer::namespace::Namespace;
+use veryl_analyzer::symbol::{Symbol, SymbolId, SymbolKind};
+use veryl_analyzer::{namespace_table, symbol_table};
+use veryl_parser::resource_table::{self, TokenId};
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, VerylToken};
+use veryl_parser::veryl_walker::VerylWalker;
+use veryl_parser::Parser;
+
+/// Upper limit of iterations of a generate-for declaration
+const MAX_ITERATIONS: usize = 65536;
+
+/// Upper limit of nested parameter references to be inlined
+const MAX_DEPTH: usize = 16;
+
+/// Expands generate-if and generate-for declarations of `module` according to the parameter
+/// values, and returns the module as Veryl pseudo-source.
+///
+/// `params` overrides the default values of the module parameters.
+/// Each iteration of generate-for is shown as a block labeled like `:g[0]`, and the loop
+/// variable in it is replaced by the value.
+/// Declarations whose conditions or ranges can't be evaluated are kept as they are.
+pub fn expand(
+    sources: &[Source],
+    module: &str,
+    params: &[(String, String)],
+) -> Result<String, RefactorError> {
+    let mut found = Vec::new();
+    for x in sources {
+        let mut finder = ModuleFinder {
+            name: module.to_string(),
+            found: Vec::new(),
+        };
+        finder.veryl(x.veryl);
+        found.extend(finder.found.into_iter().map(|y| (x, y)));
+    }
+    let (source, declaration) = match found.as_slice() {
+        [] => {
+            return Err(RefactorError::InvalidSelection(format!(
+                "module `{module}` is not found"
+            )));
+        }
+        [x] => x,
+        _ => {
+            return Err(RefactorError::InvalidSelection(format!(
+                "module `{module}` is ambiguous"
+            )));
+        }
+    };
+
+    let symbols = symbol_table::get_all();
+    let mut referred: HashMap<TokenId, Vec<SymbolId>> = HashMap::new();
+    for (token, id) in symbol_tokens(&symbols) {
+        referred.entry(token.id).or_default().push(id);
+    }
+    let Some(namespace) = symbols
+        .iter()
+        .find(|x| x.token.id == declaration.identifier.identifier_token.token.id)
+        .map(|x| x.inner_namespace())
+    else {
+        return Err(RefactorError::InvalidSelection(format!(
+            "module `{module}` is not analyzed"
+        )));
+    };
+
+    let mut tokens = Tokens::default();
+    tokens.module_declaration(declaration);
+
+    let mut expander = Expander {
+        input: source.input,
+        symbols: &symbols,
+        referred,
+        tokens: tokens.tokens,
+        namespace,
+        overrides: HashMap::new(),
+    };
+
+    // Default values of overridden parameters are replaced in the header
+    let mut edits = Vec::new();
+    let items = parameter_items(declaration);
+    for (name, value) in params {
+        let item = items.iter().find(|x| {
+            x.identifier.identifier_token.to_string() == *name
+                && matches!(
+                    *x.with_parameter_item_group,
+                    WithParameterItemGroup::Param(_)
+                )
+        });
+        let Some(item) = item else {
+            return Err(RefactorError::InvalidSelection(format!(
+                "`{name}` is not a parameter of `{module}`"
+            )));
+        };
+        if parse_expression(value).is_none() {
+            return Err(RefactorError::InvalidExpression(value.clone()));
+        }
+
+        let (start, end) = range(|x| x.expression(&item.expression)).unwrap();
+        edits.push(TextEdit {
+            start,
+            end,
+            text: value.clone(),
+        });
+        let token = item.identifier.identifier_token.token;
+        if let Some(symbol) = symbols.iter().find(|x| x.token.id == token.id) {
+            expander.overrides.insert(symbol.id, value.clone());
+        }
+    }
+
+    let (start, end) = range(|x| x.module_declaration(declaration)).unwrap();
+    let mut generates = Generates::default();
+    generates.module_declaration(declaration);
+    edits.extend(expander.edits(start, end, &generates.items, &HashMap::new()));
+    let mut ret = render(source.input, start, end, &edits);
+    ret.push('\n');
+    Ok(ret)
+}
+
+fn parameter_items(arg: &ModuleDeclaration) -> Vec<WithParameterItem> {
+    let mut items = ParameterItems::default();
+    if let Some(ref x) = arg.module_declaration_opt2 {
+        items.with_parameter(&x.with_parameter);
+    }
+    items.items
+}
+
+/// Byte range of the nodes walked by `f`
+fn range<F: FnOnce(&mut TokenRange)>(f: F) -> Option<(usize, usize)> {
+    let mut range = TokenRange::default();
+    f(&mut range);
+    match (range.first, range.last) {
+        (Some(first), Some(last)) => Some((
+            first.token.pos as usize,
+            (last.token.pos + last.token.length) as usize,
+        )),
+        _ => None,
+    }
+}
+
+fn parse_expression(text: &str) -> Option<(Parser, Expression)> {
+    let input = format!("module RefactorExpand #(param P: u32 = {text}) {{}}");
+    let parser = Parser::parse(&input, &"").ok()?;
+    let mut finder = ExpressionFinder::default();
+    finder.veryl(&parser.veryl);
+    let expression = finder.expression?;
+    Some((parser, expression))
+}
+
+struct Expander<'a> {
+    input: &'a str,
+    symbols: &'a [Symbol],
+    referred: HashMap<TokenId, Vec<SymbolId>>,
+    /// Tokens of the module in order
+    tokens: Vec<Token>,
+    /// Inner namespace of the module
+    namespace: Namespace,
+    overrides: HashMap<SymbolId, String>,
+}
+
+impl Expander<'_> {
+    /// Edits for the range `start..end` to expand `generates` in it and to replace loop variables
+    fn edits(
+        &self,
+        start: usize,
+        end: usize,
+        generates: &[Generate],
+        values: &HashMap<SymbolId, isize>,
+    ) -> Vec<TextEdit> {
+        let mut ret = Vec::new();
+        for x in generates {
+            ret.push(TextEdit {
+                start: x.start,
+                end: x.end,
+                text: self.generate(x, values),
+            });
+        }
+
+        for token in &self.tokens {
+            let pos = token.pos as usize;
+            let outside = pos < start || end <= pos;
+            if outside || generates.iter().any(|x| x.start <= pos && pos < x.end) {
+                continue;
+            }
+            if let Some(value) = self.symbol(token).and_then(|x| values.get(&x.id)) {
+                ret.push(TextEdit {
+                    start: pos,
+                    end: pos + token.length as usize,
+                    text: value.to_string(),
+                });
+            }
+        }
+        ret
+    }
+
+    /// Source text of the block walked by `f` with nested generate declarations expanded
+    fn block<F: Fn(&mut dyn VerylWalker)>(
+        &self,
+        f: F,
+        values: &HashMap<SymbolId, isize>,
+    ) -> String {
+        let Some((start, end)) = range(|x| f(x)) else {
+            return String::new();
+        };
+        let mut generates = Generates::default();
+        f(&mut generates);
+        let edits = self.edits(start, end, &generates.items, values);
+        render(self.input, start, end, &edits)
+    }
+
+    fn generate(&self, arg: &Generate, values: &HashMap<SymbolId, isize>) -> String {
+        let expanded = match &arg.declaration {
+            GenerateDeclaration::If(x) => self.generate_if(x, values),
+            GenerateDeclaration::For(x) => self.generate_for(x, arg.column, values),
+        };
+
+        // The declaration is kept if it can't be expanded
+        expanded.unwrap_or_else(|| {
+            let edits = self.edits(arg.start, arg.end, &[], values);
+            render(self.input, arg.start, arg.end, &edits)
+        })
+    }
+
+    fn generate_if(
+        &self,
+        arg: &GenerateIfDeclaration,
+        values: &HashMap<SymbolId, isize>,
+    ) -> Option<String> {
+        if self.evaluate(&arg.expression, values)? != 0 {
+            return Some(self.block(
+                |x| x.generate_named_block(&arg.generate_named_block),
+                values,
+            ));
+        }
+
+        // Unnamed blocks are labeled by the first one like SystemVerilog
+        let label = arg
+            .generate_named_block
+            .identifier
+            .identifier_token
+            .to_string();
+        let optional = |x: &GenerateOptionalNamedBlock| {
+            let block = self.block(|y| y.generate_optional_named_block(x), values);
+            if x.generate_optional_named_block_opt.is_some() {
+                block
+            } else {
+                format!(":{label} {block}")
+            }
+        };
+
+        for x in &arg.generate_if_declaration_list {
+            if self.evaluate(&x.expression, values)? != 0 {
+                return Some(optional(&x.generate_optional_named_block));
+            }
+        }
+        if let Some(ref x) = arg.generate_if_declaration_opt {
+            return Some(optional(&x.generate_optional_named_block));
+        }
+        Some("// no branch of generate-if is selected".to_string())
+    }
+
+    fn generate_for(
+        &self,
+        arg: &GenerateForDeclaration,
+        column: usize,
+        values: &HashMap<SymbolId, isize>,
+    ) -> Option<String> {
+        let genvar = self
+            .symbols
+            .iter()
+            .find(|x| x.token.id == arg.identifier.identifier_token.token.id)?;
+
+        let first = self.evaluate(&arg.range.expression, values)?;
+        let (last, inclusive) = match &arg.range.range_opt {
+            Some(x) => (
+                self.evaluate(&x.expression, values)?,
+                matches!(*x.range_operator, RangeOperator::DotDotEqu(_)),
+            ),
+            None => (first, true),
+        };
+        let step = match &arg.generate_for_declaration_opt {
+            Some(x) => Some((
+                x.assignment_operator.assignment_operator_token.to_string(),
+                self.evaluate(&x.expression, values)?,
+            )),
+            None => None,
+        };
+
+        let mut iterations = Vec::new();
+        let mut i = first;
+        while if inclusive { i <= last } else { i < last } {
+            if iterations.len() >= MAX_ITERATIONS {
+                return None;
+            }
+            iterations.push(i);
+
+            let next = match &step {
+                Some((operator, x)) => match operator.as_str() {
+                    "+=" => i.checked_add(*x),
+                    "-=" => i.checked_sub(*x),
+                    "*=" => i.checked_mul(*x),
+                    "/=" => i.checked_div(*x),
+                    "<<=" | "<<<=" => i.checked_shl(u32::try_from(*x).ok()?),
+                    ">>=" | ">>>=" => i.checked_shr(u32::try_from(*x).ok()?),
+                    _ => None,
+                },
+                None => i.checked_add(1),
+            }?;
+            if next == i {
+                return None;
+            }
+            i = next;
+        }
+
+        if iterations.is_empty() {
+            return Some("// no iteration of generate-for".to_string());
+        }
+
+        let block = &arg.generate_named_block;
+        let label = block.identifier.identifier_token.to_string();
+        let iterations: Vec<_> = iterations
+            .into_iter()
+            .map(|i| {
+                let mut values = values.clone();
+                values.insert(genvar.id, i);
+                let body = self.block(
+                    |x| {
+                        x.l_brace(&block.l_brace);
+                        for y in &block.generate_named_block_list {
+                            x.generate_group(&y.generate_group);
+                        }
+                        x.r_brace(&block.r_brace);
+                    },
+                    &values,
+                );
+                format!(":{label}[{i}] {body}")
+            })
+            .collect();
+        let indent = format!("\n{}", " ".repeat(column));
+        Some(iterations.join(&indent))
+    }
+
+    /// Symbol which `token` refers
+    fn symbol(&self, token: &Token) -> Option<&Symbol> {
+        let ids = self.referred.get(&token.id)?;
+        ids.iter()
+            .filter_map(|x| self.symbols.iter().find(|y| y.id == *x))
+            .find(|x| x.token.text == token.text)
+    }
+
+    fn evaluate(&self, arg: &Expression, values: &HashMap<SymbolId, isize>) -> Option<isize> {
+        let (start, end) = range(|x| x.expression(arg))?;
+        let (text, substituted) = self.expression_text(start, end, values, 0);
+
+        let evaluated = if substituted {
+            let (_, expression) = parse_expression(&text)?;
+
+            // Identifiers in the substituted expression are resolved at the original position
+            let first = self.tokens.iter().find(|x| x.pos as usize == start)?;
+            let namespace = namespace_table::get(first.id)?;
+            let path = resource_table::insert_path(Path::new(""));
+            let mut tokens = Tokens::default();
+            tokens.expression(&expression);
+            for x in &tokens.tokens {
+                namespace_table::insert(x.id, path, &namespace);
+            }
+
+            Evaluator::new().expression(&expression)
+        } else {
+            Evaluator::new().expression(arg)
+        };
+
+        match evaluated {
+            Evaluated::Fixed { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Text of the expression at `start..end` whose loop variables, overridden parameters and
+    /// parameters depending on them are substituted
+    fn expression_text(
+        &self,
+        start: usize,
+        end: usize,
+        values: &HashMap<SymbolId, isize>,
+        depth: usize,
+    ) -> (String, bool) {
+        let mut ret = Vec::new();
+        let mut substituted = false;
+        for token in &self.tokens {
+            let pos = token.pos as usize;
+            if pos < start || end <= pos {
+                continue;
+            }
+
+            let symbol = self.symbol(token);
+            let text = symbol.and_then(|x| {
+                if let Some(value) = values.get(&x.id) {
+                    return Some(value.to_string());
+                }
+                if let Some(value) = self.overrides.get(&x.id) {
+                    return Some(format!("({value})"));
+                }
+
+                // Local parameters are inlined because they may depend on substituted ones
+                let SymbolKind::Parameter(ref property) = x.kind else {
+                    return None;
+                };
+                if !x.namespace.included(&self.namespace) || depth >= MAX_DEPTH {
+                    return None;
+                }
+                let (start, end) = range(|y| y.expression(&property.value))?;
+                let (text, inner) = self.expression_text(start, end, values, depth + 1);
+                inner.then(|| format!("({text})"))
+            });
+
+            match text {
+                Some(x) => {
+                    ret.push(x);
+                    substituted = true;
+                }
+                None => ret.push(token.to_string()),
+            }
+        }
+        (ret.join(" "), substituted)
+    }
+}
+
+enum GenerateDeclaration {
+    If(GenerateIfDeclaration),
+    For(GenerateForDeclaration),
+}
+
+/// Generate declaration with the byte range in the source
+struct Generate {
+    declaration: GenerateDeclaration,
+    start: usize,
+    end: usize,
+    /// 0-origin column of the beginning
+    column: usize,
+}
+
+/// Collects generate declarations which are not nested in other generate declarations
+#[derive(Default)]
+struct Generates {
+    items: Vec<Generate>,
+}
+
+impl Generates {
+    fn push<F: FnOnce(&mut TokenRange)>(&mut self, declaration: GenerateDeclaration, f: F) {
+        let mut range = TokenRange::default();
+        f(&mut range);
+        if let (Some(first), Some(last)) = (range.first, range.last) {
+            self.items.push(Generate {
+                declaration,
+                start: first.token.pos as usize,
+                end: (last.token.pos + last.token.length) as usize,
+                column: first.token.column.saturating_sub(1) as usize,
+            });
+        }
+    }
+}
+
+impl VerylWalker for Generates {
+    fn generate_if_declaration(&mut self, arg: &GenerateIfDeclaration) {
+        self.push(GenerateDeclaration::If(arg.clone()), |x| {
+            x.generate_if_declaration(arg)
+        });
+    }
+
+    fn generate_for_declaration(&mut self, arg: &GenerateForDeclaration) {
+        self.push(GenerateDeclaration::For(arg.clone()), |x| {
+            x.generate_for_declaration(arg)
+        });
+    }
+}
+
+struct ModuleFinder {
+    name: String,
+    found: Vec<ModuleDeclaration>,
+}
+
+impl VerylWalker for ModuleFinder {
+    fn module_declaration(&mut self, arg: &ModuleDeclaration) {
+        if arg.identifier.identifier_token.to_string() == self.name {
+            self.found.push(arg.clone());
+        }
+    }
+}
+
+#[derive(Default)]
+struct ExpressionFinder {
+    expression: Option<Expression>,
+}
+
+impl VerylWalker for ExpressionFinder {
+    fn with_parameter_item(&mut self, arg: &WithParameterItem) {
+        self.expression = Some(arg.expression.as_ref().clone());
+    }
+}
+
+#[derive(Default)]
+struct ParameterItems {
+    items: Vec<WithParameterItem>,
+}
+
+impl VerylWalker for ParameterItems {
+    fn with_parameter_item(&mut self, arg: &WithParameterItem) {
+        self.items.push(arg.clone());
+    }
+}
+
+#[derive(Default)]
+struct Tokens {
+    tokens: Vec<Token>,
+}
+
+impl VerylWalker for Tokens {
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        self.tokens.push(arg.token);
+    }
+}
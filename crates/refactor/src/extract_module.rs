@@ -0,0 +1,426 @@
+use crate::refactor_error::RefactorError;
+use crate::{expression_text, line_end, line_start, type_text, TextEdit, TokenRange};
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use veryl_analyzer::symbol::{Direction, Symbol, SymbolKind, Type};
+use veryl_analyzer::symbol_table;
+use veryl_analyzer::var_ref::VarRefPathItem;
+use veryl_parser::resource_table::{StrId, TokenId};
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::Token;
+use veryl_parser::veryl_walker::VerylWalker;
+use veryl_parser::Stringifier;
+
+/// Moves the module items in `lines` to a new module named `name`,
+/// and replaces them by the instance of the new module.
+///
+/// Ports of the new module are inferred from the dataflow across the selection,
+/// so the analysis of `veryl` must have been finished before calling this.
+pub fn extract_module(
+    veryl: &Veryl,
+    input: &str,
+    lines: RangeInclusive<u32>,
+    name: &str,
+    indent_width: usize,
+) -> Result<Vec<TextEdit>, RefactorError> {
+    let (first_line, last_line) = (*lines.start(), *lines.end());
+    if first_line > last_line {
+        return Err(RefactorError::InvalidSelection(
+            "the first line is after the last line".to_string(),
+        ));
+    }
+
+    let mut finder = ModuleFinder {
+        lines: lines.clone(),
+        found: None,
+    };
+    finder.veryl(veryl);
+    let Some(module) = finder.found else {
+        return Err(RefactorError::InvalidSelection(
+            "the lines are not inside a module body".to_string(),
+        ));
+    };
+
+    let module_token = module.identifier.identifier_token.token;
+    let Some(module_symbol) = symbol_table::get_all()
+        .into_iter()
+        .find(|x| x.token.id == module_token.id)
+    else {
+        return Err(RefactorError::InvalidSelection(format!(
+            "module `{}` is not analyzed",
+            module_token
+        )));
+    };
+    let SymbolKind::Module(ref property) = module_symbol.kind else {
+        unreachable!();
+    };
+    let mut namespace = module_symbol.namespace.clone();
+    namespace.push(module_symbol.token.text);
+
+    // Module items in the selection
+    let mut selected = Vec::new();
+    for x in &module.module_declaration_list {
+        let mut range = TokenRange::default();
+        range.module_group(&x.module_group);
+        let (Some(first), Some(last)) = (range.first, range.last) else {
+            continue;
+        };
+        let (first, last) = (first.token.line, last.token.line);
+        if first_line <= first && last <= last_line {
+            selected.push(x.module_group.as_ref());
+        } else if first <= last_line && first_line <= last {
+            return Err(RefactorError::InvalidSelection(format!(
+                "the declaration at lines {first}:{last} is partially selected"
+            )));
+        }
+    }
+    if selected.is_empty() {
+        return Err(RefactorError::InvalidSelection(
+            "no declaration is selected".to_string(),
+        ));
+    }
+
+    // Declarations in the selection which may be kept in the original module
+    let mut declarations = HashMap::new();
+    let mut usage = ClockResetUsage::default();
+    for x in &selected {
+        if let Some((id, lines)) = declaration(x) {
+            declarations.insert(id, lines);
+        }
+        usage.module_group(x);
+    }
+
+    let start = line_start(input, first_line);
+    let end = line_end(input, last_line);
+    let in_selection = |token: &Token| {
+        token.source == module_token.source && (start..end).contains(&(token.pos as usize))
+    };
+
+    let assigns = symbol_table::get_assign_list();
+    let assigned_in_selection = |symbol: &Symbol| {
+        assigns.iter().any(|x| {
+            matches!(x.path.0.first(), Some(VarRefPathItem::Identifier { symbol_id }) if *symbol_id == symbol.id)
+                && x.position.0.iter().any(|x| in_selection(x.token()))
+        })
+    };
+
+    let mut ports = Vec::new();
+    let mut parameters = Vec::new();
+    let mut candidates = Vec::new();
+    let mut kept_lines = Vec::new();
+    for symbol in symbol_table::get_all() {
+        if !symbol.namespace.included(&namespace) {
+            continue;
+        }
+
+        let declared_in_selection = in_selection(&symbol.token);
+        let mut references = symbol.references.iter().filter(|x| x.id != symbol.token.id);
+        let crossing = if declared_in_selection {
+            references.any(|x| !in_selection(x))
+        } else {
+            references.any(in_selection)
+        };
+        // `always_ff` without event list refers the default clock and reset implicitly
+        let implicit = !declared_in_selection
+            && ((usage.clock && property.default_clock == Some(symbol.id))
+                || (usage.reset && property.default_reset == Some(symbol.id)));
+
+        if matches!(symbol.kind, SymbolKind::Parameter(_)) && !declared_in_selection {
+            candidates.push(symbol.clone());
+        }
+        if !(crossing || implicit) {
+            continue;
+        }
+
+        match &symbol.kind {
+            SymbolKind::Parameter(_) if !declared_in_selection => {
+                parameters.push(symbol.clone());
+            }
+            SymbolKind::Port(x) if !declared_in_selection => {
+                let direction = match x.direction {
+                    Direction::Input => "input",
+                    Direction::Output if assigned_in_selection(&symbol) => "output",
+                    Direction::Output => "input",
+                    Direction::Inout => "inout",
+                    Direction::Modport => "modport",
+                    _ => {
+                        return Err(RefactorError::Unsupported(format!(
+                            "{} port `{}`",
+                            x.direction, symbol.token
+                        )));
+                    }
+                };
+                let Some(ref r#type) = x.r#type else {
+                    return Err(RefactorError::Unsupported(format!(
+                        "port `{}` without type",
+                        symbol.token
+                    )));
+                };
+                ports.push((symbol.clone(), direction, r#type.clone()));
+            }
+            SymbolKind::Variable(x) => {
+                if declared_in_selection {
+                    match declarations.get(&symbol.token.id) {
+                        Some(Some(lines)) => kept_lines.push(lines.clone()),
+                        _ => {
+                            return Err(RefactorError::Unsupported(format!(
+                                "referencing `{}` declared in the selection from the outside",
+                                symbol.token
+                            )));
+                        }
+                    }
+                }
+                let direction = if assigned_in_selection(&symbol) {
+                    "output"
+                } else {
+                    "input"
+                };
+                ports.push((symbol.clone(), direction, x.r#type.clone()));
+            }
+            _ => {
+                return Err(RefactorError::Unsupported(format!(
+                    "referencing {} `{}` across the selection",
+                    symbol.kind.to_kind_name(),
+                    symbol.token
+                )));
+            }
+        }
+    }
+
+    // Parameters used in port types and default values of other parameters are required too
+    loop {
+        let mut ids = HashSet::new();
+        for (_, _, r#type) in &ports {
+            type_ids(r#type, &mut ids);
+        }
+        for x in &parameters {
+            if let SymbolKind::Parameter(ref x) = x.kind {
+                type_ids(&x.r#type, &mut ids);
+                expression_ids(&x.value, &mut ids);
+            }
+        }
+        let required: Vec<_> = candidates
+            .iter()
+            .filter(|x| ids.contains(&x.token.text) && !parameters.iter().any(|y| y.id == x.id))
+            .cloned()
+            .collect();
+        if required.is_empty() {
+            break;
+        }
+        parameters.extend(required);
+    }
+
+    ports.sort_by_key(|(x, _, _)| x.token.pos);
+    parameters.sort_by_key(|x| x.token.pos);
+    kept_lines.sort_by_key(|x| *x.start());
+
+    let unit = " ".repeat(indent_width);
+    let selection: Vec<_> = input[start..end].lines().collect();
+    let indent: String = selection
+        .iter()
+        .find(|x| !x.trim().is_empty())
+        .map(|x| x.chars().take_while(|x| x.is_whitespace()).collect())
+        .unwrap_or_default();
+    let is_kept = |line: u32| -> bool {
+        kept_lines
+            .iter()
+            .any(|x: &RangeInclusive<u32>| x.contains(&line))
+    };
+
+    // Replacement of the selection
+    let mut replacement = String::new();
+    for (i, line) in selection.iter().enumerate() {
+        if is_kept(first_line + i as u32) {
+            replacement.push_str(line);
+            replacement.push('\n');
+        }
+    }
+    let instance_name = format!("u_{}", snake_case(name));
+    replacement.push_str(&format!("{indent}inst {instance_name}: {name}"));
+    if !parameters.is_empty() {
+        replacement.push_str(" #(\n");
+        for x in &parameters {
+            replacement.push_str(&format!("{indent}{unit}{},\n", x.token));
+        }
+        replacement.push_str(&format!("{indent})"));
+    }
+    if !ports.is_empty() {
+        replacement.push_str(" (\n");
+        for (x, _, _) in &ports {
+            replacement.push_str(&format!("{indent}{unit}{},\n", x.token));
+        }
+        replacement.push_str(&format!("{indent})"));
+    }
+    replacement.push_str(";\n");
+
+    // The new module
+    let mut body: Vec<_> = selection
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !is_kept(first_line + *i as u32))
+        .map(|(_, x)| *x)
+        .collect();
+    while body.first().is_some_and(|x| x.trim().is_empty()) {
+        body.remove(0);
+    }
+    while body.last().is_some_and(|x| x.trim().is_empty()) {
+        body.pop();
+    }
+    let dedent = body
+        .iter()
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| x.len() - x.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut text = format!("\n\nmodule {name}");
+    if !parameters.is_empty() {
+        text.push_str(" #(\n");
+        for x in &parameters {
+            if let SymbolKind::Parameter(ref property) = x.kind {
+                text.push_str(&format!(
+                    "{unit}param {}: {} = {},\n",
+                    x.token,
+                    type_text(input, &property.r#type, &[]),
+                    expression_text(input, &property.value, &[])
+                ));
+            }
+        }
+        text.push(')');
+    }
+    if !ports.is_empty() {
+        text.push_str(" (\n");
+        for (x, direction, r#type) in &ports {
+            text.push_str(&format!(
+                "{unit}{}: {direction} {},\n",
+                x.token,
+                type_text(input, r#type, &[])
+            ));
+        }
+        text.push(')');
+    }
+    text.push_str(" {\n");
+    for line in &body {
+        if line.trim().is_empty() {
+            text.push('\n');
+        } else {
+            text.push_str(&format!("{unit}{}\n", &line[dedent..]));
+        }
+    }
+    text.push('}');
+
+    let r_brace = module.r_brace.r_brace_token.token;
+    let r_brace = (r_brace.pos + r_brace.length) as usize;
+
+    Ok(vec![
+        TextEdit {
+            start,
+            end,
+            text: replacement,
+        },
+        TextEdit {
+            start: r_brace,
+            end: r_brace,
+            text,
+        },
+    ])
+}
+
+struct ModuleFinder {
+    lines: RangeInclusive<u32>,
+    found: Option<ModuleDeclaration>,
+}
+
+impl VerylWalker for ModuleFinder {
+    fn module_declaration(&mut self, arg: &ModuleDeclaration) {
+        let begin = arg.l_brace.l_brace_token.token.line;
+        let end = arg.r_brace.r_brace_token.token.line;
+        if begin < *self.lines.start() && *self.lines.end() < end {
+            self.found = Some(arg.clone());
+        }
+    }
+}
+
+/// Identifier and lines of `var` declaration, or `None` for `let` declaration
+fn declaration(arg: &ModuleGroup) -> Option<(TokenId, Option<RangeInclusive<u32>>)> {
+    let ModuleGroupGroup::ModuleItem(ref x) = *arg.module_group_group else {
+        return None;
+    };
+    let mut range = TokenRange::default();
+    range.module_group(arg);
+    let lines = range.first?.token.line..=range.last?.token.line;
+
+    match x.module_item.generate_item.as_ref() {
+        GenerateItem::VarDeclaration(x) => Some((
+            x.var_declaration.identifier.identifier_token.token.id,
+            Some(lines),
+        )),
+        GenerateItem::LetDeclaration(x) => {
+            Some((x.let_declaration.identifier.identifier_token.token.id, None))
+        }
+        _ => None,
+    }
+}
+
+/// Whether the default clock and reset are used by `always_ff` without explicit event list
+#[derive(Default)]
+struct ClockResetUsage {
+    clock: bool,
+    reset: bool,
+}
+
+impl VerylWalker for ClockResetUsage {
+    fn always_ff_declaration(&mut self, arg: &AlwaysFfDeclaration) {
+        let (clock, reset) = match &arg.always_ff_declaration_opt {
+            Some(x) => (
+                true,
+                x.alwayf_ff_event_list.alwayf_ff_event_list_opt.is_some(),
+            ),
+            None => (false, false),
+        };
+        self.clock |= !clock;
+        if !reset {
+            let mut finder = IfResetFinder::default();
+            finder.statement_block(&arg.statement_block);
+            self.reset |= finder.found;
+        }
+    }
+}
+
+#[derive(Default)]
+struct IfResetFinder {
+    found: bool,
+}
+
+impl VerylWalker for IfResetFinder {
+    fn if_reset_statement(&mut self, _arg: &IfResetStatement) {
+        self.found = true;
+    }
+}
+
+fn expression_ids(arg: &Expression, ids: &mut HashSet<StrId>) {
+    let mut stringifier = Stringifier::new();
+    stringifier.expression(arg);
+    ids.extend(stringifier.ids());
+}
+
+fn type_ids(arg: &Type, ids: &mut HashSet<StrId>) {
+    for x in arg.width.iter().chain(arg.array.iter()) {
+        expression_ids(x, ids);
+    }
+}
+
+pub(crate) fn snake_case(name: &str) -> String {
+    let mut ret = String::new();
+    for (i, x) in name.chars().enumerate() {
+        if x.is_ascii_uppercase() {
+            if i != 0 && !ret.ends_with('_') {
+                ret.push('_');
+            }
+            ret.push(x.to_ascii_lowercase());
+        } else {
+            ret.push(x);
+        }
+    }
+    ret
+}
@@ -0,0 +1,564 @@
+use crate::refactor_error::RefactorError;
+use crate::{
+    expression_text, line_end, line_start, render, type_text, Source, TextEdit, TokenRange,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use veryl_analyzer::instance_graph::InstanceGraph;
+use veryl_analyzer::namespace::Namespace;
+use veryl_analyzer::symbol::{Direction, Symbol, SymbolId, SymbolKind};
+use veryl_analyzer::symbol_table;
+use veryl_parser::resource_table;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, TokenSource, VerylToken};
+use veryl_parser::veryl_walker::VerylWalker;
+
+/// Replaces the instance named `instance` in `path` by the body of the instantiated module,
+/// and removes the module declaration.
+///
+/// The module must be instantiated only once in the analyzed sources.
+/// Ports are replaced by the connected signals, and internal signals colliding with
+/// the signals of the parent module are renamed with the prefix of the instance name.
+pub fn inline_instance(
+    sources: &[Source],
+    path: &Path,
+    instance: &str,
+    indent_width: usize,
+) -> Result<Vec<(PathBuf, Vec<TextEdit>)>, RefactorError> {
+    let Some(source) = sources.iter().find(|x| x.path == path) else {
+        return Err(RefactorError::InvalidSelection(format!(
+            "{} is not analyzed",
+            path.to_string_lossy()
+        )));
+    };
+
+    let mut finder = InstFinder {
+        name: instance,
+        found: Vec::new(),
+    };
+    finder.veryl(source.veryl);
+    let inst = match finder.found.len() {
+        0 => {
+            return Err(RefactorError::InvalidSelection(format!(
+                "instance `{instance}` is not found"
+            )));
+        }
+        1 => finder.found.pop().unwrap(),
+        _ => {
+            return Err(RefactorError::InvalidSelection(format!(
+                "instance `{instance}` is declared in multiple modules"
+            )));
+        }
+    };
+    if inst.inst_declaration_opt.is_some() {
+        return Err(RefactorError::Unsupported(
+            "inlining instance array".to_string(),
+        ));
+    }
+
+    let inst_token = inst.identifier.identifier_token.token;
+    let Some(inst_symbol) = symbol_table::get_all()
+        .into_iter()
+        .find(|x| x.token.id == inst_token.id)
+    else {
+        return Err(RefactorError::InvalidSelection(format!(
+            "instance `{instance}` is not analyzed"
+        )));
+    };
+    let SymbolKind::Instance(ref inst_property) = inst_symbol.kind else {
+        unreachable!();
+    };
+
+    let Ok(child) = symbol_table::resolve((&inst_property.type_name, &inst_symbol.namespace))
+    else {
+        return Err(RefactorError::InvalidSelection(format!(
+            "module of instance `{instance}` is not found"
+        )));
+    };
+    let child = child.found;
+    let SymbolKind::Module(ref child_property) = child.kind else {
+        return Err(RefactorError::Unsupported(format!(
+            "inlining {} `{}`",
+            child.kind.to_kind_name(),
+            child.token
+        )));
+    };
+    if !child_property.generic_parameters.is_empty() {
+        return Err(RefactorError::Unsupported(format!(
+            "inlining generic module `{}`",
+            child.token
+        )));
+    }
+    let uses = InstanceGraph::new()
+        .instances()
+        .iter()
+        .filter(|x| x.child == child.id)
+        .count();
+    if uses != 1 {
+        return Err(RefactorError::Unsupported(format!(
+            "inlining module `{}` instantiated {uses} times",
+            child.token
+        )));
+    }
+
+    let child_path = token_path(&child.token);
+    let Some(child_source) = sources
+        .iter()
+        .find(|x| Some(x.path) == child_path.as_deref())
+    else {
+        return Err(RefactorError::Unsupported(format!(
+            "inlining module `{}` outside of the analyzed sources",
+            child.token
+        )));
+    };
+    let mut collector = ModuleCollector::default();
+    collector.veryl(child_source.veryl);
+    let Some((child_range, child_module)) = collector
+        .modules
+        .into_iter()
+        .find(|(_, x)| x.identifier.identifier_token.token.id == child.token.id)
+    else {
+        unreachable!();
+    };
+
+    let mut child_namespace = child.namespace.clone();
+    child_namespace.push(child.token.text);
+    let inst_namespace = &inst_symbol.namespace;
+    let mut parent_namespace = Namespace::new();
+    for x in inst_namespace.paths.iter().take(2) {
+        parent_namespace.push(*x);
+    }
+    if parent_namespace.included(&child_namespace) {
+        return Err(RefactorError::Unsupported(format!(
+            "inlining module `{}` into itself",
+            child.token
+        )));
+    }
+
+    // Names visible from the instance which can't be used by the inlined declarations
+    let mut names: HashSet<String> = symbol_table::get_all()
+        .iter()
+        .filter(|x| {
+            x.id != inst_symbol.id
+                && x.namespace.included(&parent_namespace)
+                && inst_namespace.included(&x.namespace)
+        })
+        .map(|x| x.token.to_string())
+        .collect();
+    let mut new_name = |name: String| -> Result<String, RefactorError> {
+        let ret = if names.contains(&name) {
+            let ret = format!("{instance}_{name}");
+            if names.contains(&ret) {
+                return Err(RefactorError::Unsupported(format!(
+                    "renaming `{name}` to the existing `{ret}`"
+                )));
+            }
+            ret
+        } else {
+            name
+        };
+        names.insert(ret.clone());
+        Ok(ret)
+    };
+
+    let child_begin = child_module.module.module_token.token.pos as usize;
+    let child_end = (child_module.r_brace.r_brace_token.token.pos
+        + child_module.r_brace.r_brace_token.token.length) as usize;
+    let mut child_edits = BTreeMap::new();
+    let mut replace = |symbol: &Symbol, text: &str| {
+        for token in std::iter::once(&symbol.token).chain(symbol.references.iter()) {
+            let pos = token.pos as usize;
+            if token.source == child.token.source && (child_begin..child_end).contains(&pos) {
+                child_edits.insert(
+                    pos,
+                    TextEdit {
+                        start: pos,
+                        end: pos + token.length as usize,
+                        text: text.to_string(),
+                    },
+                );
+            }
+        }
+    };
+
+    let mut items = InstItems::default();
+    items.inst_declaration(&inst);
+
+    // Parameters are converted to constants
+    let mut parameters = Vec::new();
+    let mut parameter_ids = HashSet::new();
+    for x in &child_property.parameters {
+        let Some(symbol) = symbol_table::get(x.symbol) else {
+            continue;
+        };
+        let name = new_name(symbol.token.to_string())?;
+        replace(&symbol, &name);
+        let value = items
+            .parameters
+            .iter()
+            .find(|(x, _)| x.token.text == symbol.token.text)
+            .map(|(x, value)| match value {
+                Some(value) => expression_text(source.input, value, &[]),
+                None => x.to_string(),
+            });
+        parameter_ids.insert(symbol.id);
+        parameters.push((symbol, name, value));
+    }
+
+    // Declarations in the module
+    for symbol in symbol_table::get_all() {
+        if symbol.namespace.paths != child_namespace.paths
+            || parameter_ids.contains(&symbol.id)
+            || matches!(symbol.kind, SymbolKind::Port(_))
+        {
+            continue;
+        }
+        let name = new_name(symbol.token.to_string())?;
+        if name != symbol.token.to_string() {
+            replace(&symbol, &name);
+        }
+    }
+
+    // Ports are replaced by the connected signals or wires to the connected expressions
+    let mut wires = Vec::new();
+    let mut substitutes = HashMap::new();
+    for x in &child_property.ports {
+        let Some(symbol) = symbol_table::get(x.symbol) else {
+            continue;
+        };
+        let SymbolKind::Port(ref property) = symbol.kind else {
+            continue;
+        };
+        let connection = items
+            .ports
+            .iter()
+            .find(|(x, _)| x.token.text == symbol.token.text)
+            .map(|(x, value)| match value {
+                Some(value) => expression_text(source.input, value, &[]),
+                None => x.to_string(),
+            });
+
+        let substitute = match connection {
+            Some(ref x) if is_identifier(x) => x.clone(),
+            _ => {
+                let wire = format!("{instance}_{}", symbol.token);
+                if names.contains(&wire) {
+                    return Err(RefactorError::Unsupported(format!(
+                        "creating `{wire}` which exists already"
+                    )));
+                }
+                names.insert(wire.clone());
+
+                match (property.direction, connection, &property.r#type) {
+                    (Direction::Input, Some(x), Some(r#type)) => {
+                        wires.push((wire.clone(), r#type.clone(), Some(x), true));
+                    }
+                    (Direction::Output, x, Some(r#type)) => {
+                        wires.push((wire.clone(), r#type.clone(), x, false));
+                    }
+                    (Direction::Input, None, _) => {
+                        return Err(RefactorError::Unsupported(format!(
+                            "inlining unconnected input port `{}`",
+                            symbol.token
+                        )));
+                    }
+                    _ => {
+                        return Err(RefactorError::Unsupported(format!(
+                            "connecting expression to {} port `{}`",
+                            property.direction, symbol.token
+                        )));
+                    }
+                }
+                wire
+            }
+        };
+        replace(&symbol, &substitute);
+        substitutes.insert(symbol.id, substitute);
+    }
+
+    // Clock and reset of `always_ff` are made explicit because the default ones may differ in the parent
+    let default = |x: Option<SymbolId>| x.and_then(|x| substitutes.get(&x));
+    let mut sites = AlwaysFfSites::default();
+    sites.module_declaration(&child_module);
+    for x in &sites.sites {
+        match (&x.event_list, x.if_reset) {
+            (None, if_reset) => {
+                let pos = (x.always_ff.pos + x.always_ff.length) as usize;
+                let text = match (
+                    default(child_property.default_clock),
+                    default(child_property.default_reset),
+                ) {
+                    (Some(clock), Some(reset)) if if_reset => format!(" ({clock}, {reset})"),
+                    (Some(clock), _) => format!(" ({clock})"),
+                    _ => continue,
+                };
+                child_edits.insert(
+                    pos,
+                    TextEdit {
+                        start: pos,
+                        end: pos,
+                        text,
+                    },
+                );
+            }
+            (Some((false, r_paren)), true) => {
+                if let Some(reset) = default(child_property.default_reset) {
+                    let pos = r_paren.pos as usize;
+                    child_edits.insert(
+                        pos,
+                        TextEdit {
+                            start: pos,
+                            end: pos,
+                            text: format!(", {reset}"),
+                        },
+                    );
+                }
+            }
+            _ => (),
+        }
+    }
+    let child_edits: Vec<_> = child_edits.into_values().collect();
+    let child_input = child_source.input;
+
+    let mut declarations = Vec::new();
+    for (symbol, name, value) in &parameters {
+        if let SymbolKind::Parameter(ref x) = symbol.kind {
+            let value = value
+                .clone()
+                .unwrap_or_else(|| expression_text(child_input, &x.value, &child_edits));
+            declarations.push(format!(
+                "const {name}: {} = {value};",
+                type_text(child_input, &x.r#type, &child_edits)
+            ));
+        }
+    }
+    for (wire, r#type, connection, input) in &wires {
+        let r#type = type_text(child_input, r#type, &child_edits);
+        match (connection, input) {
+            (Some(x), true) => declarations.push(format!("let {wire}: {type} = {x};")),
+            (x, _) => {
+                declarations.push(format!("var {wire}: {type};"));
+                if let Some(x) = x {
+                    declarations.push(format!("assign {x} = {wire};"));
+                }
+            }
+        }
+    }
+
+    let l_brace = child_module.l_brace.l_brace_token.token;
+    let r_brace = child_module.r_brace.r_brace_token.token;
+    let body = if l_brace.line < r_brace.line {
+        render(
+            child_input,
+            line_end(child_input, l_brace.line),
+            line_start(child_input, r_brace.line),
+            &child_edits,
+        )
+    } else {
+        String::new()
+    };
+    let mut body: Vec<_> = body.lines().collect();
+    while body.first().is_some_and(|x| x.trim().is_empty()) {
+        body.remove(0);
+    }
+    while body.last().is_some_and(|x| x.trim().is_empty()) {
+        body.pop();
+    }
+    let dedent = body
+        .iter()
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| x.len() - x.trim_start().len())
+        .min()
+        .unwrap_or(indent_width);
+
+    // Replacement of the instance
+    let mut range = TokenRange::default();
+    range.inst_declaration(&inst);
+    let first_line = range.first.unwrap().token.line;
+    let last_line = range.last.unwrap().token.line;
+    let start = line_start(source.input, first_line);
+    let end = line_end(source.input, last_line);
+    let indent: String = source.input[start..]
+        .chars()
+        .take_while(|x| *x == ' ' || *x == '\t')
+        .collect();
+
+    let mut text = String::new();
+    for x in &declarations {
+        text.push_str(&format!("{indent}{x}\n"));
+    }
+    if !declarations.is_empty() && !body.is_empty() {
+        text.push('\n');
+    }
+    for x in &body {
+        if x.trim().is_empty() {
+            text.push('\n');
+        } else {
+            text.push_str(&format!("{indent}{}\n", &x[dedent..]));
+        }
+    }
+
+    let mut edits: HashMap<PathBuf, Vec<TextEdit>> = HashMap::new();
+    edits
+        .entry(source.path.to_path_buf())
+        .or_default()
+        .push(TextEdit { start, end, text });
+
+    // Removal of the module declaration with its doc comments and a blank line
+    let mut start = line_start(child_input, child_range.0);
+    let mut end = line_end(child_input, child_range.1);
+    while start > 0 {
+        let prev = line_start(child_input, line_of(child_input, start - 1));
+        if child_input[prev..start].trim_start().starts_with("///") {
+            start = prev;
+        } else {
+            break;
+        }
+    }
+    let next = line_end(child_input, line_of(child_input, end));
+    if end < child_input.len() && child_input[end..next].trim().is_empty() {
+        end = next;
+    } else if start > 0 {
+        let prev = line_start(child_input, line_of(child_input, start - 1));
+        if child_input[prev..start].trim().is_empty() {
+            start = prev;
+        }
+    }
+    edits
+        .entry(child_source.path.to_path_buf())
+        .or_default()
+        .push(TextEdit {
+            start,
+            end,
+            text: String::new(),
+        });
+
+    let mut ret: Vec<_> = edits.into_iter().collect();
+    ret.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(ret)
+}
+
+fn token_path(token: &Token) -> Option<PathBuf> {
+    if let TokenSource::File(x) = token.source {
+        resource_table::get_path_value(x)
+    } else {
+        None
+    }
+}
+
+/// 1-origin line number at byte offset `pos`
+fn line_of(input: &str, pos: usize) -> u32 {
+    input[..pos].matches('\n').count() as u32 + 1
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|x| x.is_ascii_alphabetic() || x == '_')
+        && chars.all(|x| x.is_ascii_alphanumeric() || x == '_')
+}
+
+struct InstFinder<'a> {
+    name: &'a str,
+    found: Vec<InstDeclaration>,
+}
+
+impl VerylWalker for InstFinder<'_> {
+    fn inst_declaration(&mut self, arg: &InstDeclaration) {
+        if arg.identifier.identifier_token.to_string() == self.name {
+            self.found.push(arg.clone());
+        }
+    }
+}
+
+/// Module declarations with the lines including attributes
+#[derive(Default)]
+struct ModuleCollector {
+    modules: Vec<((u32, u32), ModuleDeclaration)>,
+}
+
+impl VerylWalker for ModuleCollector {
+    fn description_group(&mut self, arg: &DescriptionGroup) {
+        match arg.description_group_group.as_ref() {
+            DescriptionGroupGroup::LBraceDescriptionGroupGroupListRBrace(x) => {
+                for x in &x.description_group_group_list {
+                    self.description_group(&x.description_group);
+                }
+            }
+            DescriptionGroupGroup::DescriptionItem(x) => {
+                if let DescriptionItem::ModuleDeclaration(ref x) = *x.description_item {
+                    let mut range = TokenRange::default();
+                    range.description_group(arg);
+                    let lines = (
+                        range.first.unwrap().token.line,
+                        range.last.unwrap().token.line,
+                    );
+                    self.modules.push((lines, *x.module_declaration.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Connections of the instance
+#[derive(Default)]
+struct InstItems {
+    parameters: Vec<(VerylToken, Option<Expression>)>,
+    ports: Vec<(VerylToken, Option<Expression>)>,
+}
+
+impl VerylWalker for InstItems {
+    fn inst_parameter_item(&mut self, arg: &InstParameterItem) {
+        self.parameters.push((
+            arg.identifier.identifier_token.clone(),
+            arg.inst_parameter_item_opt
+                .as_ref()
+                .map(|x| *x.expression.clone()),
+        ));
+    }
+
+    fn inst_port_item(&mut self, arg: &InstPortItem) {
+        self.ports.push((
+            arg.identifier.identifier_token.clone(),
+            arg.inst_port_item_opt
+                .as_ref()
+                .map(|x| *x.expression.clone()),
+        ));
+    }
+}
+
+struct AlwaysFfSite {
+    always_ff: Token,
+    /// Whether reset is specified, and the closing parenthesis
+    event_list: Option<(bool, Token)>,
+    if_reset: bool,
+}
+
+#[derive(Default)]
+struct AlwaysFfSites {
+    sites: Vec<AlwaysFfSite>,
+    if_reset: bool,
+}
+
+impl VerylWalker for AlwaysFfSites {
+    fn always_ff_declaration(&mut self, arg: &AlwaysFfDeclaration) {
+        self.if_reset = false;
+        self.statement_block(&arg.statement_block);
+        self.sites.push(AlwaysFfSite {
+            always_ff: arg.always_ff.always_ff_token.token,
+            event_list: arg.always_ff_declaration_opt.as_ref().map(|x| {
+                let x = &x.alwayf_ff_event_list;
+                (
+                    x.alwayf_ff_event_list_opt.is_some(),
+                    x.r_paren.r_paren_token.token,
+                )
+            }),
+            if_reset: self.if_reset,
+        });
+    }
+
+    fn if_reset_statement(&mut self, _arg: &IfResetStatement) {
+        self.if_reset = true;
+    }
+}
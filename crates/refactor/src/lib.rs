@@ -0,0 +1,178 @@
+pub mod apply_naming;
+pub mod change_type;
+pub mod expand;
+pub mod extract_module;
+pub mod inline_instance;
+pub mod refactor_error;
+#[cfg(test)]
+mod tests;
+
+pub use apply_naming::{apply_naming, NamingChange, Rename};
+pub use change_type::{change_type, Site, TypeChange};
+pub use expand::expand;
+pub use extract_module::extract_module;
+pub use inline_instance::inline_instance;
+pub use refactor_error::RefactorError;
+
+use std::path::Path;
+use veryl_analyzer::symbol::{Symbol, SymbolId, SymbolKind, Type};
+use veryl_analyzer::symbol_table;
+use veryl_parser::veryl_grammar_trait::{Expression, Veryl};
+use veryl_parser::veryl_token::{Token, VerylToken};
+use veryl_parser::veryl_walker::VerylWalker;
+use veryl_parser::Stringifier;
+
+/// Parsed source file
+pub struct Source<'a> {
+    pub path: &'a Path,
+    pub input: &'a str,
+    pub veryl: &'a Veryl,
+}
+
+/// Replacement of the byte range `start..end` of the source by `text`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// Applies `edits` which don't overlap each other to `input`
+pub fn apply_edits(input: &str, edits: &[TextEdit]) -> String {
+    let mut edits: Vec<_> = edits.iter().collect();
+    edits.sort_by_key(|x| x.start);
+
+    let mut ret = String::new();
+    let mut pos = 0;
+    for edit in edits {
+        ret.push_str(&input[pos..edit.start]);
+        ret.push_str(&edit.text);
+        pos = edit.end;
+    }
+    ret.push_str(&input[pos..]);
+    ret
+}
+
+/// Converts 1-origin `line` and `column` to the byte offset in `input`
+pub fn offset(input: &str, line: u32, column: u32) -> usize {
+    line_start(input, line) + column.saturating_sub(1) as usize
+}
+
+/// Byte offset of the beginning of 1-origin `line`
+pub(crate) fn line_start(input: &str, line: u32) -> usize {
+    if line <= 1 {
+        return 0;
+    }
+    input
+        .match_indices('\n')
+        .nth(line as usize - 2)
+        .map(|(i, _)| i + 1)
+        .unwrap_or(input.len())
+}
+
+/// Byte offset of the next line of 1-origin `line`
+pub(crate) fn line_end(input: &str, line: u32) -> usize {
+    line_start(input, line + 1)
+}
+
+/// Collects the first and the last tokens of the walked nodes
+#[derive(Default)]
+pub(crate) struct TokenRange {
+    pub first: Option<VerylToken>,
+    pub last: Option<VerylToken>,
+}
+
+impl VerylWalker for TokenRange {
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        if self.first.is_none() {
+            self.first = Some(arg.clone());
+        }
+        self.last = Some(arg.clone());
+    }
+}
+
+/// Source text of `start..end` with `edits` inside the range applied
+pub(crate) fn render(input: &str, start: usize, end: usize, edits: &[TextEdit]) -> String {
+    let edits: Vec<_> = edits
+        .iter()
+        .filter(|x| start <= x.start && x.end <= end)
+        .map(|x| TextEdit {
+            start: x.start - start,
+            end: x.end - start,
+            text: x.text.clone(),
+        })
+        .collect();
+    apply_edits(&input[start..end], &edits)
+}
+
+/// Source text of `arg` to keep the original spacing
+pub(crate) fn expression_text(input: &str, arg: &Expression, edits: &[TextEdit]) -> String {
+    let mut range = TokenRange::default();
+    range.expression(arg);
+    match (range.first, range.last) {
+        (Some(first), Some(last)) => {
+            let start = first.token.pos as usize;
+            let end = (last.token.pos + last.token.length) as usize;
+            if input.get(start..end).is_some() {
+                render(input, start, end, edits)
+            } else {
+                let mut stringifier = Stringifier::new();
+                stringifier.expression(arg);
+                stringifier.as_str().to_string()
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+pub(crate) fn type_text(input: &str, arg: &Type, edits: &[TextEdit]) -> String {
+    let mut base = arg.clone();
+    base.width.clear();
+    base.array.clear();
+
+    let mut ret = base.to_string();
+    if !arg.width.is_empty() {
+        let width: Vec<_> = arg
+            .width
+            .iter()
+            .map(|x| expression_text(input, x, edits))
+            .collect();
+        ret.push_str(&format!("<{}>", width.join(", ")));
+    }
+    if !arg.array.is_empty() {
+        let array: Vec<_> = arg
+            .array
+            .iter()
+            .map(|x| expression_text(input, x, edits))
+            .collect();
+        ret.push_str(&format!(" [{}]", array.join(", ")));
+    }
+    ret
+}
+
+/// Tokens referring each symbol including the declarations
+pub(crate) fn symbol_tokens(symbols: &[Symbol]) -> Vec<(Token, SymbolId)> {
+    let mut ret = Vec::new();
+    for x in symbols {
+        ret.push((x.token, x.id));
+        for token in &x.references {
+            ret.push((*token, x.id));
+        }
+
+        // Port names at instance connections refer the ports of the instantiated module
+        if let SymbolKind::Instance(ref property) = x.kind {
+            let Ok(module) = symbol_table::resolve((&property.type_name, &x.namespace)) else {
+                continue;
+            };
+            let SymbolKind::Module(ref module_property) = module.found.kind else {
+                continue;
+            };
+            for token in property.connects.keys() {
+                if let Some(port) = module_property.ports.iter().find(|x| x.name == token.text) {
+                    ret.push((*token, port.symbol));
+                }
+            }
+        }
+    }
+    ret
+}
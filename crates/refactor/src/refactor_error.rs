@@ -0,0 +1,24 @@
+use miette::{self, Diagnostic};
+use thiserror::Error;
+
+#[derive(Error, Diagnostic, Debug, PartialEq, Eq)]
+pub enum RefactorError {
+    #[diagnostic(code(RefactorError::InvalidSelection), help(""))]
+    #[error("selection is invalid: {0}")]
+    InvalidSelection(String),
+
+    #[diagnostic(
+        code(RefactorError::Unsupported),
+        help("modify the code manually before the refactoring")
+    )]
+    #[error("{0} is not supported")]
+    Unsupported(String),
+
+    #[diagnostic(code(RefactorError::InvalidType), help(""))]
+    #[error("`{0}` is not a valid type")]
+    InvalidType(String),
+
+    #[diagnostic(code(RefactorError::InvalidExpression), help(""))]
+    #[error("`{0}` is not a valid expression")]
+    InvalidExpression(String),
+}
@@ -0,0 +1,584 @@
+use crate::*;
+use veryl_analyzer::{attribute_table, symbol_table, Analyzer, AnalyzerError};
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+
+fn analyze(code: &str) -> (Parser, Vec<AnalyzerError>) {
+    analyze_with(code, "")
+}
+
+fn analyze_with(code: &str, toml: &str) -> (Parser, Vec<AnalyzerError>) {
+    symbol_table::clear();
+    attribute_table::clear();
+
+    let metadata = Metadata::create_default_toml("prj").unwrap() + toml;
+    let metadata: Metadata = toml::from_str(&metadata).unwrap();
+    let parser = Parser::parse(code, &"").unwrap();
+    let analyzer = Analyzer::new(&metadata);
+
+    let mut errors = vec![];
+    errors.append(&mut analyzer.analyze_pass1("prj", code, "", &parser.veryl));
+    Analyzer::analyze_post_pass1();
+    errors.append(&mut analyzer.analyze_pass2("prj", code, "", &parser.veryl));
+    errors.append(&mut analyzer.analyze_pass3("prj", code, "", &parser.veryl));
+    (parser, errors)
+}
+
+fn extract(code: &str, lines: std::ops::RangeInclusive<u32>) -> Result<String, RefactorError> {
+    let (parser, _) = analyze(code);
+    let edits = extract_module(&parser.veryl, code, lines, "ModuleB", 4)?;
+    Ok(apply_edits(code, &edits))
+}
+
+#[test]
+fn extract_module_ports() {
+    let code = r#"module ModuleA #(
+    param WIDTH: u32 = 8,
+) (
+    i_a: input  logic<WIDTH>,
+    o_b: output logic<WIDTH>,
+) {
+    var c: logic<WIDTH>;
+
+    assign c = i_a + 1;
+
+    assign o_b = c;
+}
+"#;
+
+    let expect = r#"module ModuleA #(
+    param WIDTH: u32 = 8,
+) (
+    i_a: input  logic<WIDTH>,
+    o_b: output logic<WIDTH>,
+) {
+    var c: logic<WIDTH>;
+    inst u_module_b: ModuleB #(
+        WIDTH,
+    ) (
+        i_a,
+        c,
+    );
+
+    assign o_b = c;
+}
+
+module ModuleB #(
+    param WIDTH: u32 = 8,
+) (
+    i_a: input logic<WIDTH>,
+    c: output logic<WIDTH>,
+) {
+    assign c = i_a + 1;
+}
+"#;
+
+    assert_eq!(extract(code, 7..=9).unwrap(), expect);
+}
+
+#[test]
+fn extract_module_default_clock() {
+    let code = r#"module ModuleA (
+    clk: input  clock,
+    rst: input  reset,
+    i_a: input  logic,
+    o_b: output logic,
+) {
+    always_ff {
+        if_reset {
+            o_b = 0;
+        } else {
+            o_b = i_a;
+        }
+    }
+}
+"#;
+
+    let expect = r#"module ModuleA (
+    clk: input  clock,
+    rst: input  reset,
+    i_a: input  logic,
+    o_b: output logic,
+) {
+    inst u_module_b: ModuleB (
+        clk,
+        rst,
+        i_a,
+        o_b,
+    );
+}
+
+module ModuleB (
+    clk: input clock,
+    rst: input reset,
+    i_a: input logic,
+    o_b: output logic,
+) {
+    always_ff {
+        if_reset {
+            o_b = 0;
+        } else {
+            o_b = i_a;
+        }
+    }
+}
+"#;
+
+    assert_eq!(extract(code, 7..=13).unwrap(), expect);
+}
+
+#[test]
+fn extract_module_error() {
+    let code = r#"module ModuleA (
+    i_a: input  logic,
+    o_b: output logic,
+) {
+    let c: logic = i_a;
+    assign o_b = c;
+    function f () -> logic {
+        return 1;
+    }
+    assign o_b = f();
+}
+"#;
+
+    assert!(matches!(
+        extract(code, 5..=5),
+        Err(RefactorError::Unsupported(_))
+    ));
+    assert!(matches!(
+        extract(code, 7..=7),
+        Err(RefactorError::InvalidSelection(_))
+    ));
+    assert!(matches!(
+        extract(code, 10..=10),
+        Err(RefactorError::Unsupported(_))
+    ));
+    assert!(matches!(
+        extract(code, 12..=12),
+        Err(RefactorError::InvalidSelection(_))
+    ));
+}
+
+#[test]
+fn apply_text_edits() {
+    let edits = vec![
+        TextEdit {
+            start: 4,
+            end: 5,
+            text: "X".to_string(),
+        },
+        TextEdit {
+            start: 0,
+            end: 0,
+            text: "Y".to_string(),
+        },
+    ];
+    assert_eq!(apply_edits("abcdefg", &edits), "YabcdXfg");
+    assert_eq!(offset("ab\ncd\nef", 3, 2), 7);
+}
+
+fn inline(code: &str, instance: &str) -> Result<String, RefactorError> {
+    let (parser, _) = analyze(code);
+    let path = std::path::Path::new("");
+    let sources = [Source {
+        path,
+        input: code,
+        veryl: &parser.veryl,
+    }];
+    let edits = inline_instance(&sources, path, instance, 4)?;
+    assert_eq!(edits.len(), 1);
+    Ok(apply_edits(code, &edits[0].1))
+}
+
+#[test]
+fn inline_instance_ports() {
+    let code = r#"module ModuleA (
+    clk: input  clock,
+    rst: input  reset,
+    i_a: input  logic<8>,
+    o_b: output logic<8>,
+) {
+    var c: logic<8>;
+
+    inst u_sub: ModuleB #(
+        WIDTH: 8,
+    ) (
+        clk   ,
+        rst   ,
+        i_x: i_a + 1,
+        o_y: c,
+    );
+
+    assign o_b = c;
+}
+
+/// Submodule
+module ModuleB #(
+    param WIDTH: u32 = 4,
+) (
+    clk: input  clock,
+    rst: input  reset,
+    i_x: input  logic<WIDTH>,
+    o_y: output logic<WIDTH>,
+) {
+    var c: logic<WIDTH>;
+
+    always_ff {
+        if_reset {
+            c = 0;
+        } else {
+            c = i_x;
+        }
+    }
+
+    assign o_y = c;
+}
+"#;
+
+    let expect = r#"module ModuleA (
+    clk: input  clock,
+    rst: input  reset,
+    i_a: input  logic<8>,
+    o_b: output logic<8>,
+) {
+    var c: logic<8>;
+
+    const WIDTH: u32 = 8;
+    let u_sub_i_x: logic<WIDTH> = i_a + 1;
+
+    var u_sub_c: logic<WIDTH>;
+
+    always_ff (clk, rst) {
+        if_reset {
+            u_sub_c = 0;
+        } else {
+            u_sub_c = u_sub_i_x;
+        }
+    }
+
+    assign c = u_sub_c;
+
+    assign o_b = c;
+}
+"#;
+
+    let ret = inline(code, "u_sub").unwrap();
+    assert_eq!(ret, expect);
+    assert!(analyze(&ret).1.is_empty());
+}
+
+#[test]
+fn inline_instance_error() {
+    let code = r#"module ModuleA (
+    i_a: input  logic,
+    o_b: output logic,
+    o_c: output logic,
+) {
+    inst u_sub0: ModuleB (
+        i_x: i_a,
+        o_y: o_b,
+    );
+    inst u_sub1: ModuleB (
+        i_x: i_a,
+        o_y: o_c,
+    );
+}
+
+module ModuleB (
+    i_x: input  logic,
+    o_y: output logic,
+) {
+    assign o_y = i_x;
+}
+"#;
+
+    assert!(matches!(
+        inline(code, "u_sub0"),
+        Err(RefactorError::Unsupported(_))
+    ));
+    assert!(matches!(
+        inline(code, "u_sub2"),
+        Err(RefactorError::InvalidSelection(_))
+    ));
+}
+
+fn change(code: &str, target: &str, new_type: &str) -> Result<(String, TypeChange), RefactorError> {
+    let (parser, _) = analyze(code);
+    let path = std::path::Path::new("");
+    let sources = [Source {
+        path,
+        input: code,
+        veryl: &parser.veryl,
+    }];
+    let ret = change_type(&sources, target, new_type)?;
+    assert_eq!(ret.edits.len(), 1);
+    Ok((apply_edits(code, &ret.edits[0].1), ret))
+}
+
+#[test]
+fn change_type_propagation() {
+    let code = r#"module ModuleA (
+    i_a: input  logic<8>,
+    o_b: output logic<8>,
+) {
+    var c: logic<8>;
+    var d: logic<4>;
+
+    assign c = i_a;
+    assign d = c[3:0];
+
+    inst u_sub: ModuleB (
+        i_x: c,
+        o_y: o_b,
+    );
+}
+
+module ModuleB (
+    i_x: input  logic<8>,
+    o_y: output logic<8>,
+) {
+    assign o_y = i_x + 1;
+}
+"#;
+
+    let expect = r#"module ModuleA (
+    i_a: input  logic<16>,
+    o_b: output logic<8>,
+) {
+    var c: logic<16>;
+    var d: logic<4>;
+
+    assign c = i_a;
+    assign d = c[3:0];
+
+    inst u_sub: ModuleB (
+        i_x: c,
+        o_y: o_b,
+    );
+}
+
+module ModuleB (
+    i_x: input  logic<8>,
+    o_y: output logic<8>,
+) {
+    assign o_y = i_x + 1;
+}
+"#;
+
+    let (ret, change) = change(code, "ModuleA::i_a", "logic<16>").unwrap();
+    assert_eq!(ret, expect);
+
+    let changes: Vec<_> = change.changes.iter().map(|x| x.line).collect();
+    assert_eq!(changes, vec![2, 5]);
+    let reviews: Vec<_> = change
+        .reviews
+        .iter()
+        .map(|x| (x.line, x.message.as_str()))
+        .collect();
+    assert_eq!(
+        reviews,
+        vec![
+            (9, "`c` is referred"),
+            (12, "port `i_x` connected to `c` is not changed"),
+        ]
+    );
+}
+
+#[test]
+fn change_type_error() {
+    let code = r#"module ModuleA (
+    i_a: input logic,
+) {
+    inst u_sub: ModuleB;
+}
+
+module ModuleB {}
+"#;
+
+    assert!(matches!(
+        change(code, "ModuleA::i_b", "logic<2>"),
+        Err(RefactorError::InvalidSelection(_))
+    ));
+    assert!(matches!(
+        change(code, "ModuleA::i_a", "logic<"),
+        Err(RefactorError::InvalidType(_))
+    ));
+    assert!(matches!(
+        change(code, "ModuleA::u_sub", "logic<2>"),
+        Err(RefactorError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn apply_naming_rename() {
+    let toml = r#"
+[lint.naming]
+case_module = "upper_camel"
+case_port_input = "snake"
+prefix_port_input = "i_"
+prefix_instance = "u_"
+re_required_var = "[a-z]+"
+"#;
+
+    let code = r#"module module_a (
+    dataIn: input  logic,
+    o_b   : output logic,
+) {
+    var c_1  : logic;
+    var valid: logic;
+
+    assign valid = dataIn;
+
+    inst sub: ModuleB (
+        valid,
+        o_y: c_1,
+    );
+
+    assign o_b = c_1;
+}
+
+module ModuleB (
+    valid: input  logic,
+    o_y  : output logic,
+) {
+    assign o_y = valid;
+}
+"#;
+
+    let expect = r#"module ModuleA (
+    i_data_in: input  logic,
+    o_b   : output logic,
+) {
+    var c_1  : logic;
+    var valid: logic;
+
+    assign valid = i_data_in;
+
+    inst u_sub: ModuleB (
+        i_valid: valid,
+        o_y: c_1,
+    );
+
+    assign o_b = c_1;
+}
+
+module ModuleB (
+    i_valid: input  logic,
+    o_y  : output logic,
+) {
+    assign o_y = i_valid;
+}
+"#;
+
+    let (parser, errors) = analyze_with(code, toml);
+    let sources = [Source {
+        path: std::path::Path::new(""),
+        input: code,
+        veryl: &parser.veryl,
+    }];
+    let change = apply_naming(&sources, &errors);
+    assert_eq!(change.edits.len(), 1);
+    let ret = apply_edits(code, &change.edits[0].1);
+    assert_eq!(ret, expect);
+
+    let renames: Vec<_> = change
+        .renames
+        .iter()
+        .map(|x| (x.from.as_str(), x.to.as_str()))
+        .collect();
+    assert_eq!(
+        renames,
+        vec![
+            ("module_a", "ModuleA"),
+            ("dataIn", "i_data_in"),
+            ("sub", "u_sub"),
+            ("valid", "i_valid"),
+        ]
+    );
+    assert_eq!(change.skipped.len(), 1);
+    assert_eq!(change.skipped[0].line, 5);
+
+    let (_, errors) = analyze_with(&ret, toml);
+    assert_eq!(errors.len(), 1);
+}
+
+fn expand_module(code: &str, params: &[(&str, &str)]) -> Result<String, RefactorError> {
+    let (parser, _) = analyze(code);
+    let sources = [Source {
+        path: std::path::Path::new(""),
+        input: code,
+        veryl: &parser.veryl,
+    }];
+    let params: Vec<_> = params
+        .iter()
+        .map(|(x, y)| (x.to_string(), y.to_string()))
+        .collect();
+    expand(&sources, "ModuleA", &params)
+}
+
+#[test]
+fn expand_generate() {
+    let code = r#"module ModuleA #(
+    param N     : u32  = 2,
+    param ENABLE: bit  = 1,
+) (
+    i_a: input  logic<N>,
+    o_b: output logic<N>,
+) {
+    const M: u32 = N * 2;
+
+    for i in 0..N :g {
+        if i == 0 :g_first {
+            assign o_b[i] = i_a[i];
+        } else {
+            assign o_b[i] = i_a[i] ^ i_a[i - 1];
+        }
+    }
+
+    if ENABLE && M >: 4 :g_enable {
+        var c: logic;
+        assign c = 1;
+    }
+}
+"#;
+
+    let expect = r#"module ModuleA #(
+    param N     : u32  = 2,
+    param ENABLE: bit  = 1,
+) (
+    i_a: input  logic<N>,
+    o_b: output logic<N>,
+) {
+    const M: u32 = N * 2;
+
+    :g[0] {
+        :g_first {
+            assign o_b[0] = i_a[0];
+        }
+    }
+    :g[1] {
+        :g_first {
+            assign o_b[1] = i_a[1] ^ i_a[1 - 1];
+        }
+    }
+
+    // no branch of generate-if is selected
+}
+"#;
+    assert_eq!(expand_module(code, &[]).unwrap(), expect);
+
+    let ret = expand_module(code, &[("N", "3")]).unwrap();
+    assert!(ret.contains("param N     : u32  = 3,"));
+    assert!(ret.contains("assign o_b[2] = i_a[2] ^ i_a[2 - 1];"));
+    assert!(ret.contains(":g_enable {"));
+
+    assert!(matches!(
+        expand_module(code, &[("M", "3")]),
+        Err(RefactorError::InvalidSelection(_))
+    ));
+    assert!(matches!(
+        expand_module(code, &[("N", "3 +")]),
+        Err(RefactorError::InvalidExpression(_))
+    ));
+}
@@ -26,17 +26,17 @@ fn criterion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("throughput");
     group.throughput(Throughput::Bytes(text.len() as u64));
     group.bench_function("parse", |b| {
-        b.iter_with_large_drop(|| Parser::parse(black_box(&text), &""))
+        b.iter_with_large_drop(|| Parser::parse(black_box(&text), &"").unwrap())
     });
     group.bench_function("analyze", |b| {
         b.iter_with_large_drop(|| {
             let parser = Parser::parse(black_box(&text), &"").unwrap();
             let prj = &metadata.project.name;
             let analyzer = Analyzer::new(black_box(&metadata));
-            analyzer.analyze_pass1(prj, black_box(&text), &"", &parser.veryl);
+            analyzer.analyze_pass1(prj, black_box(&text), "", &parser.veryl);
             Analyzer::analyze_post_pass1();
-            analyzer.analyze_pass2(prj, black_box(&text), &"", &parser.veryl);
-            analyzer.analyze_pass3(prj, black_box(&text), &"", &parser.veryl);
+            analyzer.analyze_pass2(prj, black_box(&text), "", &parser.veryl);
+            analyzer.analyze_pass3(prj, black_box(&text), "", &parser.veryl);
         })
     });
     group.bench_function("format", |b| {
@@ -11,7 +11,7 @@ mod parser {
         let input = fs::read_to_string(&file).unwrap();
         let ret = Parser::parse(&input, &file);
         match ret {
-            Ok(_) => assert!(true),
+            Ok(_) => (),
             Err(err) => println!("{}", err),
         }
     }
@@ -49,17 +49,17 @@ mod analyzer {
         let ret = Parser::parse(&input, &file).unwrap();
         let prj = &metadata.project.name;
         let analyzer = Analyzer::new(&metadata);
-        let errors = analyzer.analyze_pass1(&prj, &input, &file, &ret.veryl);
+        let errors = analyzer.analyze_pass1(prj, &input, &file, &ret.veryl);
         dbg!(&errors);
         assert!(errors.is_empty());
 
         Analyzer::analyze_post_pass1();
 
-        let errors = analyzer.analyze_pass2(&prj, &input, &file, &ret.veryl);
+        let errors = analyzer.analyze_pass2(prj, &input, &file, &ret.veryl);
         dbg!(&errors);
         assert!(errors.is_empty());
 
-        let errors = analyzer.analyze_pass3(&prj, &input, &file, &ret.veryl);
+        let errors = analyzer.analyze_pass3(prj, &input, &file, &ret.veryl);
         dbg!(&errors);
         assert!(errors.is_empty());
     }
@@ -136,11 +136,11 @@ mod emitter {
         let ret = Parser::parse(&input, &src_path).unwrap();
         let prj = &metadata.project.name;
         let analyzer = Analyzer::new(&metadata);
-        let _ = analyzer.analyze_pass1(&prj, &input, &src_path, &ret.veryl);
+        let _ = analyzer.analyze_pass1(prj, &input, &src_path, &ret.veryl);
         Analyzer::analyze_post_pass1();
-        let _ = analyzer.analyze_pass2(&prj, &input, &src_path, &ret.veryl);
+        let _ = analyzer.analyze_pass2(prj, &input, &src_path, &ret.veryl);
         let mut emitter = Emitter::new(&metadata, &src_path, &dst_path, &map_path);
-        emitter.emit(&prj, &ret.veryl);
+        emitter.emit(prj, &ret.veryl);
 
         let out_code = emitter.as_str();
         let ref_code = fs::read_to_string(&dst_path).unwrap();
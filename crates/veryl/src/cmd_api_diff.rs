@@ -0,0 +1,292 @@
+use crate::cmd_diff::{analyze, checkout};
+use crate::OptApiDiff;
+use log::{info, warn};
+use miette::{bail, Result};
+use std::collections::BTreeMap;
+use veryl_analyzer::symbol::{Parameter, Port, Symbol, SymbolKind};
+use veryl_analyzer::symbol_table;
+use veryl_metadata::semver::Version;
+use veryl_metadata::Metadata;
+use veryl_parser::veryl_walker::VerylWalker;
+use veryl_parser::Stringifier;
+
+pub struct CmdApiDiff {
+    opt: OptApiDiff,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Api {
+    kind: &'static str,
+    parameters: BTreeMap<String, (String, String)>,
+    ports: BTreeMap<String, (String, String)>,
+    /// Variables, functions and modports of interface keyed by name,
+    /// and members of each modport keyed by `modport.member`
+    members: BTreeMap<String, (String, String)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Change {
+    Compatible,
+    Breaking,
+}
+
+impl CmdApiDiff {
+    pub fn new(opt: OptApiDiff) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let base = if let Some(ref base) = self.opt.base {
+            base.clone()
+        } else if let Some(release) = metadata.pubfile.releases.last() {
+            info!("Comparing with release ({})", release.version);
+            release.revision.clone()
+        } else {
+            bail!("there is no published release, so \"--base\" is required");
+        };
+
+        let (base_version, base_apis) = checkout(metadata, &base, |x| {
+            analyze(x)?;
+            Ok((x.project.version.clone(), collect_apis(x)))
+        })?;
+        analyze(metadata)?;
+        let head_apis = collect_apis(metadata);
+
+        let changes = diff_apis(&base_apis, &head_apis);
+        for (change, text) in &changes {
+            match change {
+                Change::Breaking => warn!("Breaking change ({})", text),
+                Change::Compatible => info!("Compatible change ({})", text),
+            }
+        }
+
+        let required = required_version(&base_version, changes.iter().map(|x| x.0).max());
+        info!("Suggested version ({} -> {})", base_version, required);
+
+        let version = &metadata.project.version;
+        if *version < required {
+            warn!("Insufficient version ({} < {})", version, required);
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+}
+
+/// Returns the minimum version which the changes from `base` require.
+/// Under 0.x, breaking changes bump the minor version and compatible changes bump the patch version.
+pub(crate) fn required_version(base: &Version, change: Option<Change>) -> Version {
+    let mut ret = base.clone();
+    match (change, ret.major) {
+        (Some(Change::Breaking), 0) | (Some(Change::Compatible), 1..) => {
+            ret.minor += 1;
+            ret.patch = 0;
+        }
+        (Some(Change::Breaking), _) => {
+            ret.major += 1;
+            ret.minor = 0;
+            ret.patch = 0;
+        }
+        (Some(Change::Compatible), 0) => ret.patch += 1,
+        (None, _) => return ret,
+    }
+    ret.pre = Default::default();
+    ret.build = Default::default();
+    ret
+}
+
+pub(crate) fn diff_apis(
+    base_apis: &BTreeMap<String, Api>,
+    head_apis: &BTreeMap<String, Api>,
+) -> Vec<(Change, String)> {
+    let mut ret = Vec::new();
+    for (name, base) in base_apis {
+        if let Some(head) = head_apis.get(name) {
+            diff_api(name, base, head, &mut ret);
+        } else {
+            ret.push((Change::Breaking, format!("{} {name} is removed", base.kind)));
+        }
+    }
+    for (name, head) in head_apis {
+        if !base_apis.contains_key(name) {
+            ret.push((Change::Compatible, format!("{} {name} is added", head.kind)));
+        }
+    }
+    ret
+}
+
+pub(crate) fn collect_apis(metadata: &Metadata) -> BTreeMap<String, Api> {
+    let mut ret = BTreeMap::new();
+    for symbol in symbol_table::get_all() {
+        if format!("{}", symbol.namespace) != metadata.project.name || !symbol.public {
+            continue;
+        }
+        let (kind, parameters, ports) = match &symbol.kind {
+            SymbolKind::Module(x) => ("module", x.parameters.as_slice(), x.ports.as_slice()),
+            SymbolKind::Interface(x) => ("interface", x.parameters.as_slice(), [].as_slice()),
+            SymbolKind::Package(_) => ("package", [].as_slice(), [].as_slice()),
+            _ => continue,
+        };
+        let api = Api {
+            kind,
+            parameters: parameters.iter().map(parameter_entry).collect(),
+            ports: ports.iter().map(port_entry).collect(),
+            members: BTreeMap::new(),
+        };
+        ret.insert(symbol.token.to_string(), api);
+    }
+
+    for symbol in symbol_table::get_all() {
+        let paths = &symbol.namespace.paths;
+        if paths.len() != 2 || paths[0].to_string() != metadata.project.name {
+            continue;
+        }
+        if let Some(api) = ret.get_mut(&paths[1].to_string()) {
+            if api.kind == "interface" {
+                api.members.extend(member_entries(&symbol));
+            }
+        }
+    }
+    ret
+}
+
+fn member_entries(symbol: &Symbol) -> Vec<(String, (String, String))> {
+    let name = symbol.token.to_string();
+    match &symbol.kind {
+        SymbolKind::Variable(x) => vec![(name, ("var".to_string(), x.r#type.to_string()))],
+        SymbolKind::Function(x) => {
+            let ports: Vec<_> = x
+                .ports
+                .iter()
+                .map(|x| {
+                    let (name, (direction, r#type)) = port_entry(x);
+                    format!("{name}: {direction} {type}")
+                })
+                .collect();
+            let ret = x.ret.as_ref().map(|x| x.to_string()).unwrap_or_default();
+            let signature = format!("({}) -> {ret}", ports.join(", "));
+            vec![(name, ("function".to_string(), signature))]
+        }
+        SymbolKind::Modport(x) => {
+            let mut ret = vec![(name.clone(), ("modport".to_string(), String::new()))];
+            for member in x.members.iter().filter_map(|x| symbol_table::get(*x)) {
+                let direction = match member.kind {
+                    SymbolKind::ModportVariableMember(ref x) => x.direction.to_string(),
+                    SymbolKind::ModportFunctionMember(_) => "import".to_string(),
+                    _ => continue,
+                };
+                let key = format!("{name}.{}", member.token);
+                ret.push((key, ("modport member".to_string(), direction)));
+            }
+            ret
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn parameter_entry(x: &Parameter) -> (String, (String, String)) {
+    let property = x.property();
+    let mut stringifier = Stringifier::new();
+    stringifier.expression(&property.value);
+    (
+        x.name.to_string(),
+        (
+            property.r#type.to_string(),
+            stringifier.as_str().to_string(),
+        ),
+    )
+}
+
+fn port_entry(x: &Port) -> (String, (String, String)) {
+    let property = x.property();
+    let r#type = property.r#type.map(|x| x.to_string()).unwrap_or_default();
+    (x.name.to_string(), (property.direction.to_string(), r#type))
+}
+
+fn diff_api(name: &str, base: &Api, head: &Api, changes: &mut Vec<(Change, String)>) {
+    if base.kind != head.kind {
+        changes.push((
+            Change::Breaking,
+            format!("{} {name} is changed to {}", base.kind, head.kind),
+        ));
+        return;
+    }
+
+    for (param, (base_type, base_value)) in &base.parameters {
+        match head.parameters.get(param) {
+            Some((head_type, _)) if head_type != base_type => changes.push((
+                Change::Breaking,
+                format!("parameter {name}.{param} is retyped ({base_type} -> {head_type})"),
+            )),
+            Some((_, head_value)) if head_value != base_value => changes.push((
+                Change::Compatible,
+                format!("parameter {name}.{param} default ({base_value} -> {head_value})"),
+            )),
+            Some(_) => (),
+            None => changes.push((
+                Change::Breaking,
+                format!("parameter {name}.{param} is removed"),
+            )),
+        }
+    }
+    for param in head.parameters.keys() {
+        if !base.parameters.contains_key(param) {
+            changes.push((
+                Change::Compatible,
+                format!("parameter {name}.{param} is added"),
+            ));
+        }
+    }
+
+    for (port, base_port) in &base.ports {
+        match head.ports.get(port) {
+            Some(head_port) if head_port != base_port => changes.push((
+                Change::Breaking,
+                format!(
+                    "port {name}.{port} is changed ({} {} -> {} {})",
+                    base_port.0, base_port.1, head_port.0, head_port.1
+                ),
+            )),
+            Some(_) => (),
+            None => changes.push((Change::Breaking, format!("port {name}.{port} is removed"))),
+        }
+    }
+    for (port, (direction, _)) in &head.ports {
+        if !base.ports.contains_key(port) {
+            // Unconnected output ports don't break existing instances
+            let change = if direction == "output" {
+                Change::Compatible
+            } else {
+                Change::Breaking
+            };
+            changes.push((change, format!("{direction} port {name}.{port} is added")));
+        }
+    }
+
+    // Existing users of interface don't refer added members
+    for (member, (kind, base_detail)) in &base.members {
+        match head.members.get(member) {
+            Some((head_kind, _)) if head_kind != kind => changes.push((
+                Change::Breaking,
+                format!("{kind} {name}.{member} is changed to {head_kind}"),
+            )),
+            Some((_, head_detail)) if head_detail != base_detail => changes.push((
+                Change::Breaking,
+                format!("{kind} {name}.{member} is changed ({base_detail} => {head_detail})"),
+            )),
+            Some(_) => (),
+            None => changes.push((
+                Change::Breaking,
+                format!("{kind} {name}.{member} is removed"),
+            )),
+        }
+    }
+    for (member, (kind, _)) in &head.members {
+        if !base.members.contains_key(member) {
+            changes.push((
+                Change::Compatible,
+                format!("{kind} {name}.{member} is added"),
+            ));
+        }
+    }
+}
@@ -1,18 +1,23 @@
 use crate::cmd_check::CheckError;
+use crate::progress::Progress;
+use crate::verify::verify;
 use crate::OptBuild;
-use log::{debug, info};
-use miette::{IntoDiagnostic, Result, WrapErr};
-use std::collections::HashMap;
+use log::{debug, error, info};
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use tempfile::TempDir;
 use veryl_analyzer::namespace::Namespace;
 use veryl_analyzer::symbol::SymbolKind;
-use veryl_analyzer::{type_dag, Analyzer};
-use veryl_emitter::Emitter;
-use veryl_metadata::{FilelistType, Metadata, SourceMapTarget, Target};
+use veryl_analyzer::symbol_path::SymbolPath;
+use veryl_analyzer::{symbol_table, type_dag, Analyzer};
+use veryl_emitter::emitter::RenameReport;
+use veryl_emitter::{emitter, Emitter, Provenance};
+use veryl_metadata::{BuildTarget, FilelistType, Matrix, Metadata, SourceMapTarget, Target};
 use veryl_parser::{resource_table, veryl_token::TokenSource, Parser};
 use veryl_path::PathSet;
 
@@ -28,7 +33,7 @@ impl CmdBuild {
     pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
         let paths = metadata.paths(&self.opt.files, true)?;
 
-        let mut check_error = CheckError::default();
+        let mut check_error = CheckError::new(metadata)?;
         let mut contexts = Vec::new();
 
         for path in &paths {
@@ -39,8 +44,9 @@ impl CmdBuild {
                 .wrap_err("")?;
             let parser = Parser::parse(&input, &path.src)?;
 
-            let analyzer = Analyzer::new(metadata);
+            let analyzer = Analyzer::new(&metadata.for_file(&path.src));
             let mut errors = analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
             check_error = check_error.append(&mut errors).check_err()?;
 
             contexts.push((path, input, parser, analyzer));
@@ -48,23 +54,41 @@ impl CmdBuild {
 
         Analyzer::analyze_post_pass1();
 
+        let mut progress = Progress::new("Analyzing", contexts.len() * 2);
+
         for (path, input, parser, analyzer) in &contexts {
+            progress.inc(&path.src);
             let mut errors = analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
             check_error = check_error.append(&mut errors).check_err()?;
         }
 
         for (path, input, parser, analyzer) in &contexts {
+            progress.inc(&path.src);
             let mut errors = analyzer.analyze_pass3(&path.prj, input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
             check_error = check_error.append(&mut errors).check_err()?;
         }
 
+        drop(progress);
+
         let temp_dir = if let Target::Bundle { .. } = &metadata.build.target {
             Some(TempDir::new().into_diagnostic()?)
         } else {
             None
         };
 
+        let provenance = metadata
+            .build
+            .header
+            .enable
+            .then(|| Provenance::new(metadata));
+        let mut progress = Progress::new("Emitting", contexts.len());
+        let mut renames = Vec::new();
+
         for (path, input, parser, _) in &contexts {
+            metadata.cancellation.check()?;
+            progress.inc(&path.src);
             let (dst, map) = if let Some(ref temp_dir) = temp_dir {
                 let dst_temp = temp_dir.path().join(
                     path.dst
@@ -81,53 +105,291 @@ impl CmdBuild {
                 (path.dst.clone(), path.map.clone())
             };
 
-            let mut emitter = Emitter::new(metadata, &path.src, &dst, &map);
-            emitter.emit(&path.prj, &parser.veryl);
+            let mut ret = self.emit(
+                metadata,
+                path,
+                input,
+                parser,
+                &dst,
+                &map,
+                provenance.as_ref(),
+                &mut progress,
+            )?;
+            renames.append(&mut ret);
+        }
+
+        drop(progress);
+
+        self.gen_filelist(metadata, &paths, temp_dir)?;
+
+        if !metadata.verification.bind.is_empty() {
+            self.gen_bind(metadata)?;
+        }
+
+        if !metadata.verification.ral.blocks.is_empty() {
+            self.gen_ral(metadata)?;
+        }
+
+        if let Some(ref report) = metadata.build.rename.report {
+            self.gen_rename_report(metadata, report, &renames)?;
+        }
+
+        for target in &metadata.build.targets {
+            self.build_target(metadata, target, &contexts, provenance.as_ref())?;
+        }
+
+        if let Some(ref path) = self.opt.matrix {
+            let matrix = Matrix::load(path)?;
+            for target in &matrix.targets() {
+                self.build_target(metadata, target, &contexts, provenance.as_ref())?;
+            }
+        }
+
+        let _ = check_error.check_all(self.opt.deny_warnings)?;
+        Ok(true)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn emit(
+        &self,
+        metadata: &Metadata,
+        path: &PathSet,
+        input: &str,
+        parser: &Parser,
+        dst: &Path,
+        map: &Path,
+        provenance: Option<&Provenance>,
+        progress: &mut Progress,
+    ) -> Result<Vec<RenameReport>> {
+        let mut emitter = Emitter::new(metadata, &path.src, dst, map);
+        if let Some(x) = provenance {
+            emitter.set_provenance(x.with_source(&source_name(metadata, path)));
+        }
+        emitter.emit(&path.prj, &parser.veryl);
+
+        let dst_dir = dst.parent().unwrap();
+        if !dst_dir.exists() {
+            std::fs::create_dir_all(dst.parent().unwrap()).into_diagnostic()?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(dst)
+            .into_diagnostic()?;
+        file.write_all(emitter.as_str().as_bytes())
+            .into_diagnostic()?;
+        file.flush().into_diagnostic()?;
+
+        debug!("Output file ({})", dst.to_string_lossy());
+
+        if self.opt.verify_output {
+            let errors = verify(emitter.as_str());
+            if !errors.is_empty() {
+                progress.clear();
+                for x in &errors {
+                    error!(
+                        "Malformed output ({}:{} : {})",
+                        dst.to_string_lossy(),
+                        x.line,
+                        x.message
+                    );
+                }
+                bail!(
+                    "Emitted code is malformed ({}), which may be a bug of Veryl",
+                    dst.to_string_lossy()
+                );
+            }
+        }
+
+        if !emitter.reset_synchronizers().is_empty() || !emitter.auto_connects().is_empty() {
+            progress.clear();
+        }
+        for x in emitter.reset_synchronizers() {
+            info!(
+                "Insert reset synchronizer ({}: {} @ {} -> {})",
+                x.module, x.reset, x.clock, x.name
+            );
+        }
+        for x in emitter.auto_connects() {
+            info!(
+                "Auto-connect port ({}: {}.{} <-> {})",
+                x.module, x.instance, x.port, x.target
+            );
+        }
+
+        if emitter.protected() {
+            progress.clear();
+            self.protect(metadata, dst)?;
+        }
 
-            let dst_dir = dst.parent().unwrap();
-            if !dst_dir.exists() {
-                std::fs::create_dir_all(dst.parent().unwrap()).into_diagnostic()?;
+        // Source map of protected modules is not emitted because it contains the original source
+        if emitter.protected() {
+            if map.exists() {
+                std::fs::remove_file(map).into_diagnostic()?;
+            }
+        } else if metadata.build.sourcemap_target != SourceMapTarget::None {
+            let source_map = emitter.source_map();
+            source_map.set_source_content(input);
+            let source_map = source_map.to_bytes().into_diagnostic()?;
+
+            let map_dir = map.parent().unwrap();
+            if !map_dir.exists() {
+                std::fs::create_dir_all(map.parent().unwrap()).into_diagnostic()?;
             }
 
             let mut file = OpenOptions::new()
                 .create(true)
                 .write(true)
                 .truncate(true)
-                .open(&dst)
-                .into_diagnostic()?;
-            file.write_all(emitter.as_str().as_bytes())
+                .open(map)
                 .into_diagnostic()?;
+            file.write_all(&source_map).into_diagnostic()?;
             file.flush().into_diagnostic()?;
 
-            debug!("Output file ({})", dst.to_string_lossy());
+            debug!("Output map ({})", map.to_string_lossy());
+        }
 
-            if metadata.build.sourcemap_target != SourceMapTarget::None {
-                let source_map = emitter.source_map();
-                source_map.set_source_content(input);
-                let source_map = source_map.to_bytes().into_diagnostic()?;
+        Ok(emitter.renames().to_vec())
+    }
 
-                let map_dir = map.parent().unwrap();
-                if !map_dir.exists() {
-                    std::fs::create_dir_all(map.parent().unwrap()).into_diagnostic()?;
+    /// Emit all files again with the settings of `[[build.targets]]`
+    fn build_target(
+        &self,
+        metadata: &Metadata,
+        target: &BuildTarget,
+        contexts: &[(&PathSet, String, Parser, Analyzer)],
+        provenance: Option<&Provenance>,
+    ) -> Result<()> {
+        let mut metadata = metadata.clone();
+        metadata.build = metadata.build.with_target(target);
+
+        let top = if let Some(ref top) = target.top {
+            let mut namespace = Namespace::new();
+            namespace.push(resource_table::insert_str(&metadata.project.name));
+            let path = SymbolPath::new(&[resource_table::insert_str(top)]);
+            let symbol = match symbol_table::resolve((&path, &namespace)) {
+                Ok(x) if matches!(x.found.kind, SymbolKind::Module(_)) => x.found,
+                _ => bail!("top module \"{}\" of target is not found", top),
+            };
+            if let SymbolKind::Module(ref x) = symbol.kind {
+                for name in target.params.keys() {
+                    if !x.parameters.iter().any(|x| x.name.to_string() == *name) {
+                        bail!(
+                            "parameter \"{}\" of top module \"{}\" is not found",
+                            name,
+                            top
+                        );
+                    }
                 }
+            }
+            Some(symbol)
+        } else if !target.params.is_empty() {
+            bail!("params of target can't be used without top");
+        } else {
+            None
+        };
 
-                let mut file = OpenOptions::new()
-                    .create(true)
-                    .write(true)
-                    .truncate(true)
-                    .open(&map)
-                    .into_diagnostic()?;
-                file.write_all(&source_map).into_diagnostic()?;
-                file.flush().into_diagnostic()?;
+        let target_path = metadata.project_path().join(&target.path);
+        info!("Building target ({})", target_path.to_string_lossy());
 
-                debug!("Output map ({})", map.to_string_lossy());
-            }
+        let dependencies_path = metadata.project_dependencies_path();
+        let mut paths = Vec::new();
+        let mut progress = Progress::new("Emitting", contexts.len());
+
+        for (path, input, parser, _) in contexts {
+            metadata.cancellation.check()?;
+            progress.inc(&path.src);
+
+            // Files of dependencies keep the directory structure under `dependencies`
+            let dst = if path.prj == metadata.project.name {
+                target_path.join(path.src.with_extension("sv").file_name().unwrap())
+            } else if let Ok(x) = path.dst.strip_prefix(&dependencies_path) {
+                target_path.join("dependencies").join(x)
+            } else {
+                target_path.join(path.dst.file_name().unwrap())
+            };
+            let map = dst.with_extension("sv.map");
+
+            self.emit(
+                &metadata,
+                path,
+                input,
+                parser,
+                &dst,
+                &map,
+                provenance,
+                &mut progress,
+            )?;
+
+            paths.push(PathSet {
+                prj: path.prj.clone(),
+                src: path.src.clone(),
+                dst,
+                map,
+            });
         }
 
-        self.gen_filelist(metadata, &paths, temp_dir)?;
+        drop(progress);
+
+        let mut paths = Self::sort_filelist(&metadata, &paths);
+        if let Some(ref symbol) = top {
+            let used: HashSet<_> = type_dag::dependencies(symbol.id)
+                .into_iter()
+                .filter_map(|x| {
+                    if let TokenSource::File(x) = x.token.source {
+                        Some(PathBuf::from(format!("{}", x)))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            paths.retain(|x| used.contains(&x.src));
+        }
 
-        let _ = check_error.check_all()?;
-        Ok(true)
+        let mut text = String::new();
+        for (name, value) in &target.defines {
+            let line = match (metadata.build.filelist_type, value.is_empty()) {
+                (FilelistType::Flgen, true) => format!("define_macro :{name}\n"),
+                (FilelistType::Flgen, false) => format!("define_macro :{name}, '{value}'\n"),
+                (_, true) => format!("+define+{name}\n"),
+                (_, false) => format!("+define+{name}={value}\n"),
+            };
+            text.push_str(&line);
+        }
+        for path in &paths {
+            text.push_str(&self.gen_filelist_line(&metadata, &path.dst)?);
+        }
+
+        let filelist_path = target_path.join(metadata.filelist_path().file_name().unwrap());
+        info!("Output filelist ({})", filelist_path.to_string_lossy());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(filelist_path)
+            .into_diagnostic()?;
+        file.write_all(text.as_bytes()).into_diagnostic()?;
+        file.flush().into_diagnostic()?;
+
+        Ok(())
+    }
+
+    fn protect(&self, metadata: &Metadata, dst: &Path) -> Result<()> {
+        if let Some(ref command) = metadata.build.protect.command {
+            info!("Encrypting file ({})", dst.to_string_lossy());
+
+            let status = Command::new(command)
+                .args(&metadata.build.protect.args)
+                .arg(dst)
+                .status()
+                .into_diagnostic()?;
+            if !status.success() {
+                bail!("protect command \"{}\" failed ({})", command, status);
+            }
+        }
+        Ok(())
     }
 
     fn gen_filelist_line(&self, metadata: &Metadata, path: &Path) -> Result<String> {
@@ -200,7 +462,77 @@ impl CmdBuild {
         Ok(())
     }
 
-    fn sort_filelist(metadata: &Metadata, paths: &[PathSet]) -> Vec<PathSet> {
+    fn gen_bind(&self, metadata: &Metadata) -> Result<()> {
+        let text = match emitter::emit_bind(metadata) {
+            Ok(x) => x,
+            Err(x) => bail!("module \"{}\" in [verification.bind] is not found", x),
+        };
+
+        let bind_path = metadata.bind_path();
+        info!("Output bind file ({})", bind_path.to_string_lossy());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(bind_path)
+            .into_diagnostic()?;
+        file.write_all(text.as_bytes()).into_diagnostic()?;
+        file.flush().into_diagnostic()?;
+
+        Ok(())
+    }
+
+    fn gen_ral(&self, metadata: &Metadata) -> Result<()> {
+        let text = match emitter::emit_ral(metadata) {
+            Ok(x) => x,
+            Err(x) => bail!("{} in [verification.ral]", x),
+        };
+
+        let ral_path = metadata.ral_path();
+        info!("Output RAL file ({})", ral_path.to_string_lossy());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(ral_path)
+            .into_diagnostic()?;
+        file.write_all(text.as_bytes()).into_diagnostic()?;
+        file.flush().into_diagnostic()?;
+
+        Ok(())
+    }
+
+    /// Output cross-reference between the original and renamed names of `[build.rename]`
+    fn gen_rename_report(
+        &self,
+        metadata: &Metadata,
+        report: &Path,
+        renames: &[RenameReport],
+    ) -> Result<()> {
+        let mut text = String::new();
+        for x in renames {
+            if let Some(ref module) = x.module {
+                text.push_str(&format!("port\t{}.{}\t{}\n", module, x.original, x.renamed));
+            } else {
+                text.push_str(&format!("module\t{}\t{}\n", x.original, x.renamed));
+            }
+        }
+
+        let report_path = metadata.project_path().join(report);
+        info!("Output rename report ({})", report_path.to_string_lossy());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(report_path)
+            .into_diagnostic()?;
+        file.write_all(text.as_bytes()).into_diagnostic()?;
+        file.flush().into_diagnostic()?;
+
+        Ok(())
+    }
+
+    pub fn sort_filelist(metadata: &Metadata, paths: &[PathSet]) -> Vec<PathSet> {
         let mut table = HashMap::new();
         for path in paths {
             table.insert(path.src.clone(), path);
@@ -223,25 +555,30 @@ impl CmdBuild {
         }
 
         let mut ret = vec![];
-        let sorted_symbols = type_dag::toposort();
-        for symbol in sorted_symbols {
-            if matches!(
-                symbol.kind,
-                SymbolKind::Module(_) | SymbolKind::Interface(_) | SymbolKind::Package(_)
-            ) {
-                if let TokenSource::File(x) = symbol.token.source {
-                    let path = PathBuf::from(format!("{}", x));
-                    if let Some(x) = table.remove(&path) {
-                        ret.push(x.clone());
-                    }
-                }
+        for unit in type_dag::dependency_order() {
+            if let Some(x) = unit.path.and_then(|x| table.remove(&x)) {
+                ret.push(x.clone());
             }
         }
 
-        for path in table.into_values() {
+        let mut rest: Vec<_> = table.into_values().collect();
+        rest.sort_by(|a, b| a.src.cmp(&b.src));
+        for path in rest {
             ret.push(path.clone());
         }
 
         ret
     }
 }
+
+/// Name of the source written in the header, which doesn't depend on the checkout location
+fn source_name(metadata: &Metadata, path: &PathSet) -> String {
+    match path.src.strip_prefix(metadata.project_path()) {
+        Ok(x) => x.to_string_lossy().replace('\\', "/"),
+        Err(_) => format!(
+            "{}/{}",
+            path.prj,
+            path.src.file_name().unwrap().to_string_lossy()
+        ),
+    }
+}
@@ -0,0 +1,234 @@
+use crate::cmd_build::CmdBuild;
+use crate::cmd_doc::CmdDoc;
+use crate::{OptBuild, OptBundle, OptDoc};
+use log::info;
+use miette::{bail, IntoDiagnostic, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use veryl_analyzer::symbol::SymbolKind;
+use veryl_analyzer::{symbol_table, Analyzer};
+use veryl_metadata::{Metadata, Target};
+
+pub struct CmdBundle {
+    _opt: OptBundle,
+}
+
+#[derive(Serialize)]
+struct Manifest {
+    name: String,
+    version: String,
+    authors: Vec<String>,
+    description: Option<String>,
+    license: Option<String>,
+    repository: Option<String>,
+    veryl_version: String,
+    modules: Vec<String>,
+    files: Vec<String>,
+}
+
+impl CmdBundle {
+    pub fn new(opt: OptBundle) -> Self {
+        Self { _opt: opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let build = CmdBuild::new(OptBuild {
+            files: vec![],
+            deny_warnings: false,
+            verify_output: false,
+            matrix: None,
+        });
+        if !build.exec(metadata)? {
+            return Ok(false);
+        }
+
+        let base_path = metadata.project_path();
+        let bundle_dir = metadata.bundle_path().join(format!(
+            "{}-{}",
+            metadata.project.name, metadata.project.version
+        ));
+        if bundle_dir.exists() {
+            fs::remove_dir_all(&bundle_dir).into_diagnostic()?;
+        }
+
+        let mut files = Vec::new();
+
+        let rtl_dir = bundle_dir.join("rtl");
+        fs::create_dir_all(&rtl_dir).into_diagnostic()?;
+        let rtl_files = if let Target::Bundle { path } = &metadata.build.target {
+            vec![base_path.join(path)]
+        } else {
+            let paths = metadata.paths::<&str>(&[], true)?;
+            CmdBuild::sort_filelist(metadata, &paths)
+                .into_iter()
+                .map(|x| x.dst)
+                .collect()
+        };
+
+        // Files are placed flat in each directory, so files which have the same name are rejected
+        let mut names = HashSet::new();
+
+        let mut filelist = String::new();
+        for src in &rtl_files {
+            let name = self.bundle_name(Path::new("rtl"), src, &mut names)?;
+            self.copy_file(src, &bundle_dir.join(&name))?;
+            filelist.push_str(&format!("{}\n", name.to_string_lossy()));
+            files.push(name);
+        }
+
+        let filelist_name = PathBuf::from(format!("{}.f", metadata.project.name));
+        self.write_file(&bundle_dir.join(&filelist_name), &filelist)?;
+        files.push(filelist_name);
+
+        let modules = self.modules(metadata);
+
+        if metadata.bundle.sdc {
+            for (name, clocks, resets) in &modules {
+                let sdc_name = PathBuf::from("sdc").join(format!("{name}.sdc"));
+                let text = self.gen_sdc(metadata, name, clocks, resets);
+                self.write_file(&bundle_dir.join(&sdc_name), &text)?;
+                files.push(sdc_name);
+            }
+        }
+
+        let mut license_files = metadata.bundle.license_files.clone();
+        if license_files.is_empty() {
+            for entry in fs::read_dir(&base_path).into_diagnostic()? {
+                let path = entry.into_diagnostic()?.path();
+                let name = path.file_name().unwrap().to_string_lossy().to_uppercase();
+                if path.is_file() && (name.starts_with("LICENSE") || name.starts_with("COPYING")) {
+                    license_files.push(path);
+                }
+            }
+            license_files.sort();
+        }
+
+        for src in license_files.iter().chain(metadata.bundle.files.iter()) {
+            let src = base_path.join(src);
+            if !src.is_file() {
+                bail!("bundle file \"{}\" is not found", src.to_string_lossy());
+            }
+            let name = self.bundle_name(Path::new(""), &src, &mut names)?;
+            self.copy_file(&src, &bundle_dir.join(&name))?;
+            files.push(name);
+        }
+
+        if metadata.bundle.doc {
+            Analyzer::new(metadata).clear();
+
+            let mut doc_metadata = metadata.clone();
+            doc_metadata.doc.path = bundle_dir.join("doc");
+            let doc = CmdDoc::new(OptDoc {
+                files: vec![],
+                include_deps: false,
+                since: None,
+            });
+            if !doc.exec(&mut doc_metadata)? {
+                return Ok(false);
+            }
+            files.push(PathBuf::from("doc"));
+        }
+
+        let manifest = Manifest {
+            name: metadata.project.name.clone(),
+            version: metadata.project.version.to_string(),
+            authors: metadata.project.authors.clone(),
+            description: metadata.project.description.clone(),
+            license: metadata.project.license.clone(),
+            repository: metadata.project.repository.clone(),
+            veryl_version: env!("CARGO_PKG_VERSION").to_string(),
+            modules: modules.into_iter().map(|(x, _, _)| x).collect(),
+            files: files
+                .iter()
+                .map(|x| x.to_string_lossy().into_owned())
+                .collect(),
+        };
+        let manifest = serde_json::to_string_pretty(&manifest).into_diagnostic()?;
+        self.write_file(&bundle_dir.join("manifest.json"), &manifest)?;
+
+        info!("Output bundle ({})", bundle_dir.to_string_lossy());
+
+        Ok(true)
+    }
+
+    fn modules(&self, metadata: &Metadata) -> Vec<(String, Vec<String>, Vec<String>)> {
+        let mut ret = Vec::new();
+        for symbol in symbol_table::get_all() {
+            if format!("{}", symbol.namespace) != metadata.project.name || !symbol.public {
+                continue;
+            }
+            if let SymbolKind::Module(x) = &symbol.kind {
+                let name = if metadata.build.omit_project_prefix {
+                    symbol.token.to_string()
+                } else {
+                    format!("{}_{}", metadata.project.name, symbol.token)
+                };
+
+                let mut clocks = Vec::new();
+                let mut resets = Vec::new();
+                for port in &x.ports {
+                    if let Some(port_symbol) = symbol_table::get(port.symbol) {
+                        if port_symbol.kind.is_clock() {
+                            clocks.push(port.name.to_string());
+                        } else if port_symbol.kind.is_reset() {
+                            resets.push(port.name.to_string());
+                        }
+                    }
+                }
+                ret.push((name, clocks, resets));
+            }
+        }
+        ret.sort();
+        ret
+    }
+
+    fn gen_sdc(
+        &self,
+        metadata: &Metadata,
+        name: &str,
+        clocks: &[String],
+        resets: &[String],
+    ) -> String {
+        let mut text = format!("# Timing constraints stub for {name}\n");
+        for clock in clocks {
+            text.push_str(&format!(
+                "create_clock -name {clock} -period {:.3} [get_ports {clock}]\n",
+                metadata.bundle.clock_period
+            ));
+        }
+        for reset in resets {
+            text.push_str(&format!("set_false_path -from [get_ports {reset}]\n"));
+        }
+        text
+    }
+
+    /// Returns the path of `src` in the bundle, which is `dir` joined with the file name
+    fn bundle_name(&self, dir: &Path, src: &Path, names: &mut HashSet<PathBuf>) -> Result<PathBuf> {
+        let Some(name) = src.file_name() else {
+            bail!("path \"{}\" is not a file", src.to_string_lossy());
+        };
+        let name = dir.join(name);
+        if !names.insert(name.clone()) {
+            bail!(
+                "bundle file \"{}\" conflicts with another file ({})",
+                name.to_string_lossy(),
+                src.to_string_lossy()
+            );
+        }
+        Ok(name)
+    }
+
+    fn copy_file(&self, src: &Path, dst: &Path) -> Result<()> {
+        fs::create_dir_all(dst.parent().unwrap()).into_diagnostic()?;
+        fs::copy(src, dst).into_diagnostic()?;
+        Ok(())
+    }
+
+    fn write_file(&self, dst: &Path, text: &str) -> Result<()> {
+        fs::create_dir_all(dst.parent().unwrap()).into_diagnostic()?;
+        fs::write(dst, text).into_diagnostic()?;
+        Ok(())
+    }
+}
@@ -1,50 +1,125 @@
+use crate::progress::Progress;
 use crate::OptCheck;
 use log::info;
 use miette::{self, Diagnostic, IntoDiagnostic, Result, Severity, WrapErr};
 use std::fs;
+use std::path::PathBuf;
 use thiserror::Error;
-use veryl_analyzer::{Analyzer, AnalyzerError};
-use veryl_metadata::Metadata;
+use veryl_analyzer::{Analyzer, AnalyzerError, LintedError};
+use veryl_metadata::{Baseline, BaselineEntry, Lint, Metadata, Override};
 use veryl_parser::Parser;
 
 pub struct CmdCheck {
     opt: OptCheck,
 }
 
-#[derive(Error, Diagnostic, Debug, Default)]
+#[derive(Error, Diagnostic, Debug)]
 #[error("veryl check failed")]
 pub struct CheckError {
     #[related]
-    pub related: Vec<AnalyzerError>,
+    pub related: Vec<LintedError>,
+    lint: Lint,
+    overrides: Vec<Override>,
+    base_path: PathBuf,
+    baseline: Option<Baseline>,
+    suppressed: usize,
 }
 
 impl CheckError {
+    pub fn new(metadata: &Metadata) -> Result<Self> {
+        let baseline = match metadata.baseline_path() {
+            Some(path) if path.exists() => Some(Baseline::load(path)?),
+            _ => None,
+        };
+        Ok(Self {
+            related: Vec::new(),
+            lint: metadata.lint.clone(),
+            overrides: metadata.overrides.clone(),
+            base_path: metadata.project_path(),
+            baseline,
+            suppressed: 0,
+        })
+    }
+
+    pub fn set_baseline(&mut self, baseline: Option<Baseline>) {
+        self.baseline = baseline;
+    }
+
     pub fn append(mut self, x: &mut Vec<AnalyzerError>) -> Self {
-        self.related.append(x);
+        for x in x.drain(..) {
+            let path = source_path(&x);
+            let lint = self
+                .overrides
+                .iter()
+                .rev()
+                .find(|x| path.starts_with(&x.path))
+                .map(|x| &x.lint)
+                .unwrap_or(&self.lint);
+            let x = x.with_lint(lint);
+            let entry = self.baseline_entry(&x);
+            if let Some(ref mut baseline) = self.baseline {
+                if baseline.take(&entry) {
+                    self.suppressed += 1;
+                    continue;
+                }
+            }
+            self.related.push(x);
+        }
         self
     }
 
+    pub fn baseline_entry(&self, x: &LintedError) -> BaselineEntry {
+        let path = source_path(x);
+        let path = path.strip_prefix(&self.base_path).unwrap_or(&path);
+        BaselineEntry {
+            code: x.code().map(|x| x.to_string()).unwrap_or_default(),
+            path: path.to_string_lossy().replace('\\', "/"),
+            message: x.to_string(),
+        }
+    }
+
     pub fn check_err(self) -> Result<Self> {
-        if self
-            .related
-            .iter()
-            .all(|x| !matches!(x.severity(), Some(Severity::Error) | None))
-        {
+        if self.related.iter().all(|x| x.severity != Severity::Error) {
             Ok(self)
         } else {
             Err(self.into())
         }
     }
 
-    pub fn check_all(self) -> Result<Self> {
-        if self.related.is_empty() {
-            Ok(self)
-        } else {
+    /// Fails if any error remains, or any warning remains and `deny_warnings` is set.
+    /// Other diagnostics are only reported.
+    pub fn check_all(mut self, deny_warnings: bool) -> Result<Self> {
+        if self.suppressed != 0 {
+            info!("Suppressed diagnostics by baseline ({})", self.suppressed);
+        }
+
+        let deny = self.related.iter().any(|x| match x.severity {
+            Severity::Error => true,
+            Severity::Warning => deny_warnings,
+            Severity::Advice => false,
+        });
+        if deny {
             Err(self.into())
+        } else {
+            for x in self.related.drain(..) {
+                eprintln!("{:?}", miette::Report::new(x));
+            }
+            Ok(self)
         }
     }
 }
 
+/// Path of the source file which the first label of `x` points
+fn source_path(x: &dyn Diagnostic) -> PathBuf {
+    x.labels()
+        .and_then(|mut labels| labels.next())
+        .and_then(|label| {
+            let contents = x.source_code()?.read_span(label.inner(), 0, 0).ok()?;
+            contents.name().map(PathBuf::from)
+        })
+        .unwrap_or_default()
+}
+
 impl CmdCheck {
     pub fn new(opt: OptCheck) -> Self {
         Self { opt }
@@ -53,7 +128,17 @@ impl CmdCheck {
     pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
         let paths = metadata.paths(&self.opt.files, true)?;
 
-        let mut check_error = CheckError::default();
+        let mut check_error = CheckError::new(metadata)?;
+        if let Some(ref path) = self.opt.baseline {
+            check_error.set_baseline(Some(Baseline::load(path)?));
+        }
+
+        // all diagnostics are recorded even if there are errors
+        let recording = self.opt.write_baseline.is_some();
+        if recording {
+            check_error.set_baseline(None);
+        }
+        let check = |x: CheckError| if recording { Ok(x) } else { x.check_err() };
         let mut contexts = Vec::new();
 
         for path in &paths {
@@ -64,26 +149,46 @@ impl CmdCheck {
                 .wrap_err("")?;
             let parser = Parser::parse(&input, &path.src)?;
 
-            let analyzer = Analyzer::new(metadata);
+            let analyzer = Analyzer::new(&metadata.for_file(&path.src));
             let mut errors = analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
-            check_error = check_error.append(&mut errors).check_err()?;
+            metadata.cancellation.check()?;
+            check_error = check(check_error.append(&mut errors))?;
 
             contexts.push((path, input, parser, analyzer));
         }
 
         Analyzer::analyze_post_pass1();
 
+        let mut progress = Progress::new("Analyzing", contexts.len() * 2);
+
         for (path, input, parser, analyzer) in &contexts {
+            progress.inc(&path.src);
             let mut errors = analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
-            check_error = check_error.append(&mut errors).check_err()?;
+            metadata.cancellation.check()?;
+            check_error = check(check_error.append(&mut errors))?;
         }
 
         for (path, input, parser, analyzer) in &contexts {
+            progress.inc(&path.src);
             let mut errors = analyzer.analyze_pass3(&path.prj, input, &path.src, &parser.veryl);
-            check_error = check_error.append(&mut errors).check_err()?;
+            metadata.cancellation.check()?;
+            check_error = check(check_error.append(&mut errors))?;
+        }
+
+        drop(progress);
+
+        if let Some(ref path) = self.opt.write_baseline {
+            let diagnostics = check_error
+                .related
+                .iter()
+                .map(|x| check_error.baseline_entry(x))
+                .collect();
+            Baseline { diagnostics }.save(path)?;
+            info!("Output baseline ({})", path.to_string_lossy());
+            return Ok(true);
         }
 
-        let _ = check_error.check_all()?;
+        let _ = check_error.check_all(self.opt.deny_warnings)?;
         Ok(true)
     }
 }
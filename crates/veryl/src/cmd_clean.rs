@@ -47,6 +47,12 @@ impl CmdClean {
             fs::remove_dir_all(&doc_path).into_diagnostic()?;
         }
 
+        let bundle_path = metadata.bundle_path();
+        if bundle_path.exists() {
+            info!("Removing dir  ({})", bundle_path.to_string_lossy());
+            fs::remove_dir_all(&bundle_path).into_diagnostic()?;
+        }
+
         Ok(true)
     }
 }
@@ -0,0 +1,25 @@
+use crate::{CompletionShell, Opt, OptCompletions};
+use clap::CommandFactory;
+use clap_complete::aot::Shell;
+use miette::Result;
+use std::io::Write;
+
+pub struct CmdCompletions {
+    opt: OptCompletions,
+}
+
+impl CmdCompletions {
+    pub fn new(opt: OptCompletions) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self) -> Result<bool> {
+        generate(self.opt.shell.clone(), &mut std::io::stdout());
+        Ok(true)
+    }
+}
+
+pub fn generate(shell: CompletionShell, buf: &mut dyn Write) {
+    let shell: Shell = shell.into();
+    clap_complete::generate(shell, &mut Opt::command(), "veryl", buf);
+}
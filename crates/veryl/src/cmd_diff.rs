@@ -0,0 +1,218 @@
+use crate::cmd_check::CheckError;
+use crate::cmd_fmt::print_diff;
+use crate::OptDiff;
+use log::{debug, info};
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+use veryl_analyzer::Analyzer;
+use veryl_emitter::Emitter;
+use veryl_metadata::{Metadata, SourceMapTarget};
+use veryl_parser::Parser;
+use veryl_path::PathSet;
+
+pub struct CmdDiff {
+    opt: OptDiff,
+}
+
+static UNIT_BEGIN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(module|interface|package)\s+([a-zA-Z_][0-9a-zA-Z_$]*)").unwrap()
+});
+static UNIT_END: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*end(module|interface|package)\b").unwrap());
+
+impl CmdDiff {
+    pub fn new(opt: OptDiff) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let base = checkout(metadata, &self.opt.base, emit_units)?;
+        let head = emit_units(metadata)?;
+
+        let mut changed = false;
+        for (name, text) in &head {
+            match base.get(name) {
+                Some(base_text) if base_text == text => {
+                    debug!("Unchanged unit ({})", name);
+                }
+                Some(base_text) => {
+                    info!("Changed unit ({})", name);
+                    if !self.opt.name_only {
+                        print_diff(Path::new(name), base_text, text);
+                    }
+                    changed = true;
+                }
+                None => {
+                    info!("Added unit ({})", name);
+                    changed = true;
+                }
+            }
+        }
+        for name in base.keys() {
+            if !head.contains_key(name) {
+                info!("Removed unit ({})", name);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            info!(
+                "No change in emitted code from revision ({})",
+                self.opt.base
+            );
+        }
+
+        Ok(!changed)
+    }
+}
+
+/// Git worktree which is removed when dropped
+struct Worktree {
+    toplevel: PathBuf,
+    path: PathBuf,
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        let _ = git(
+            &self.toplevel,
+            &[
+                "worktree",
+                "remove",
+                "--force",
+                &self.path.to_string_lossy(),
+            ],
+        );
+    }
+}
+
+/// Runs `f` with the metadata of the project checked out at `rev`.
+/// The worktree is removed even if `f` fails or is cancelled by Ctrl-C.
+pub fn checkout<T, F>(metadata: &Metadata, rev: &str, f: F) -> Result<T>
+where
+    F: FnOnce(&mut Metadata) -> Result<T>,
+{
+    let project_path = metadata.project_path();
+    let toplevel = git(&project_path, &["rev-parse", "--show-toplevel"])?;
+    let toplevel = PathBuf::from(toplevel.trim())
+        .canonicalize()
+        .into_diagnostic()?;
+    let relative = project_path.strip_prefix(&toplevel).into_diagnostic()?;
+
+    let temp_dir = TempDir::new().into_diagnostic()?;
+    let path = temp_dir.path().join("base");
+
+    info!("Checking out revision ({})", rev);
+    git(
+        &toplevel,
+        &["worktree", "add", "--detach", &path.to_string_lossy(), rev],
+    )?;
+    let worktree = Worktree { toplevel, path };
+
+    let mut base = Metadata::load(worktree.path.join(relative).join("Veryl.toml"))?;
+    base.cancellation = metadata.cancellation.clone();
+    f(&mut base)
+}
+
+pub fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .into_diagnostic()?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed\n  {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn emit_units(metadata: &mut Metadata) -> Result<BTreeMap<String, String>> {
+    // Comments and source maps don't affect the functionality of emitted code
+    metadata.build.strip_comments = true;
+    metadata.build.sourcemap_target = SourceMapTarget::None;
+
+    let contexts = analyze(metadata)?;
+
+    let mut ret = BTreeMap::new();
+    for (path, parser) in &contexts {
+        let mut emitter = Emitter::new(metadata, &path.src, &path.dst, &path.map);
+        emitter.emit(&path.prj, &parser.veryl);
+        split_units(emitter.as_str(), &mut ret);
+    }
+
+    Ok(ret)
+}
+
+pub fn analyze(metadata: &mut Metadata) -> Result<Vec<(PathSet, Parser)>> {
+    Analyzer::new(metadata).clear();
+
+    let paths = metadata.paths::<&str>(&[], true)?;
+
+    let mut check_error = CheckError::new(metadata)?;
+    let mut contexts = Vec::new();
+
+    for path in paths {
+        info!("Processing file ({})", path.src.to_string_lossy());
+
+        let input = fs::read_to_string(&path.src)
+            .into_diagnostic()
+            .wrap_err("")?;
+        let parser = Parser::parse(&input, &path.src)?;
+
+        let analyzer = Analyzer::new(metadata);
+        let mut errors = analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+        metadata.cancellation.check()?;
+        check_error = check_error.append(&mut errors).check_err()?;
+
+        contexts.push((path, input, parser, analyzer));
+    }
+
+    Analyzer::analyze_post_pass1();
+
+    for (path, input, parser, analyzer) in &contexts {
+        let mut errors = analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+        metadata.cancellation.check()?;
+        check_error = check_error.append(&mut errors).check_err()?;
+    }
+
+    for (path, input, parser, analyzer) in &contexts {
+        let mut errors = analyzer.analyze_pass3(&path.prj, input, &path.src, &parser.veryl);
+        metadata.cancellation.check()?;
+        check_error = check_error.append(&mut errors).check_err()?;
+    }
+
+    Ok(contexts
+        .into_iter()
+        .map(|(path, _, parser, _)| (path, parser))
+        .collect())
+}
+
+pub fn split_units(text: &str, units: &mut BTreeMap<String, String>) {
+    let mut current: Option<(String, String)> = None;
+    for line in text.lines() {
+        if current.is_none() {
+            if let Some(caps) = UNIT_BEGIN.captures(line) {
+                current = Some((caps[2].to_string(), String::new()));
+            }
+        }
+        if let Some((name, mut unit)) = current.take() {
+            unit.push_str(line);
+            unit.push('\n');
+            if UNIT_END.is_match(line) {
+                units.insert(name, unit);
+            } else {
+                current = Some((name, unit));
+            }
+        }
+    }
+}
@@ -1,11 +1,14 @@
-use crate::doc::{DocBuilder, TopLevelItem};
+use crate::cmd_diff::{analyze, checkout};
+use crate::doc::{interfaces, DocBuilder, DocDependency, Since, SourceFile, TopLevelItem};
 use crate::OptDoc;
 use log::info;
 use miette::{IntoDiagnostic, Result, WrapErr};
 use std::collections::BTreeMap;
 use std::fs;
+use std::path::PathBuf;
 use veryl_analyzer::symbol::{SymbolId, SymbolKind};
 use veryl_analyzer::{symbol_table, Analyzer};
+use veryl_metadata::semver::Version;
 use veryl_metadata::Metadata;
 use veryl_parser::resource_table;
 use veryl_parser::Parser;
@@ -14,12 +17,46 @@ pub struct CmdDoc {
     opt: OptDoc,
 }
 
+#[derive(Default)]
+struct ProjectItems {
+    modules: BTreeMap<String, TopLevelItem>,
+    proto_modules: BTreeMap<String, TopLevelItem>,
+    interfaces: BTreeMap<String, TopLevelItem>,
+    packages: BTreeMap<String, TopLevelItem>,
+}
+
 impl CmdDoc {
     pub fn new(opt: OptDoc) -> Self {
         Self { opt }
     }
 
     pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let since = if let Some(ref since) = self.opt.since {
+            // Published version is resolved to its revision by Veryl.pub
+            let revision = Version::parse(since)
+                .ok()
+                .and_then(|x| {
+                    metadata
+                        .pubfile
+                        .releases
+                        .iter()
+                        .find(|r| r.version == x)
+                        .map(|r| r.revision.clone())
+                })
+                .unwrap_or_else(|| since.clone());
+            let interfaces = checkout(metadata, &revision, |metadata| {
+                analyze(metadata)?;
+                Ok(interfaces(&metadata.project.name))
+            })?;
+            Analyzer::new(metadata).clear();
+            Some(Since {
+                version: since.clone(),
+                interfaces,
+            })
+        } else {
+            None
+        };
+
         let paths = metadata.paths(&self.opt.files, true)?;
 
         let mut contexts = Vec::new();
@@ -47,16 +84,59 @@ impl CmdDoc {
             analyzer.analyze_pass3(&path.prj, input, &path.src, &parser.veryl);
         }
 
-        let mut modules = BTreeMap::new();
-        let mut proto_modules = BTreeMap::new();
-        let mut interfaces = BTreeMap::new();
-        let mut packages = BTreeMap::new();
+        let mut versions = BTreeMap::new();
+        if self.opt.include_deps {
+            for locks in metadata.lockfile.lock_table.values() {
+                for lock in locks {
+                    versions.insert(lock.name.clone(), lock.version.to_string());
+                }
+            }
+        }
+
+        let mut sources = Vec::new();
+        for (path, input, _, _) in &contexts {
+            let title = if path.prj == metadata.project.name {
+                let base = metadata.project_path();
+                path.src
+                    .strip_prefix(&base)
+                    .unwrap_or(&path.src)
+                    .to_path_buf()
+            } else if versions.contains_key(&path.prj) {
+                // Path in a dependency is shown from the root of the dependency project
+                let base = path
+                    .src
+                    .ancestors()
+                    .find(|x| veryl_path::is_project_toml(x.join("Veryl.toml")))
+                    .unwrap_or(&path.src);
+                let rel = path.src.strip_prefix(base).unwrap_or(&path.src);
+                PathBuf::from(&path.prj).join(rel)
+            } else {
+                continue;
+            };
+            let title = title.to_string_lossy().replace('\\', "/");
+            sources.push(SourceFile {
+                file_name: format!("source.{}", title.replace('/', ".")),
+                title,
+                path: path.src.clone(),
+                text: input.clone(),
+            });
+        }
+
+        let mut projects: BTreeMap<String, ProjectItems> = BTreeMap::new();
 
         for symbol in veryl_analyzer::symbol_table::get_all() {
             let text = resource_table::get_str_value(symbol.token.text).unwrap();
-            let file_name = text.clone();
+            let prj = format!("{}", symbol.namespace);
+            let file_name = if prj == metadata.project.name {
+                text.clone()
+            } else if versions.contains_key(&prj) {
+                format!("dep.{}.{}", prj, text)
+            } else {
+                continue;
+            };
             let symbol = symbol.clone();
-            if format!("{}", symbol.namespace) == metadata.project.name && symbol.public {
+            if symbol.public {
+                let items = projects.entry(prj).or_default();
                 match &symbol.kind {
                     SymbolKind::Module(x) => {
                         let html_name = fmt_generic_parameters(&text, &x.generic_parameters);
@@ -65,16 +145,16 @@ impl CmdDoc {
                             html_name,
                             symbol,
                         };
-                        modules.insert(text, item);
+                        items.modules.insert(text, item);
                     }
                     SymbolKind::ProtoModule(_) => {
-                        let html_name = file_name.clone();
+                        let html_name = text.clone();
                         let item = TopLevelItem {
                             file_name,
                             html_name,
                             symbol,
                         };
-                        proto_modules.insert(text, item);
+                        items.proto_modules.insert(text, item);
                     }
                     SymbolKind::Interface(x) => {
                         let html_name = fmt_generic_parameters(&text, &x.generic_parameters);
@@ -83,7 +163,7 @@ impl CmdDoc {
                             html_name,
                             symbol,
                         };
-                        interfaces.insert(text, item);
+                        items.interfaces.insert(text, item);
                     }
                     SymbolKind::Package(x) => {
                         let html_name = fmt_generic_parameters(&text, &x.generic_parameters);
@@ -92,19 +172,38 @@ impl CmdDoc {
                             html_name,
                             symbol,
                         };
-                        packages.insert(text, item);
+                        items.packages.insert(text, item);
                     }
                     _ => (),
                 }
             }
         }
 
-        let modules: Vec<_> = modules.into_values().collect();
-        let proto_modules: Vec<_> = proto_modules.into_values().collect();
-        let interfaces: Vec<_> = interfaces.into_values().collect();
-        let packages: Vec<_> = packages.into_values().collect();
-
-        let builder = DocBuilder::new(metadata, modules, proto_modules, interfaces, packages)?;
+        let items = projects.remove(&metadata.project.name).unwrap_or_default();
+        let modules: Vec<_> = items.modules.into_values().collect();
+        let proto_modules: Vec<_> = items.proto_modules.into_values().collect();
+        let interfaces: Vec<_> = items.interfaces.into_values().collect();
+        let packages: Vec<_> = items.packages.into_values().collect();
+
+        let dependencies: Vec<_> = projects
+            .into_iter()
+            .map(|(name, items)| DocDependency {
+                file_name: format!("dep.{}", name),
+                version: versions[&name].clone(),
+                name,
+                modules: items.modules.into_values().collect(),
+                proto_modules: items.proto_modules.into_values().collect(),
+                interfaces: items.interfaces.into_values().collect(),
+                packages: items.packages.into_values().collect(),
+            })
+            .collect();
+
+        let mut builder = DocBuilder::new(metadata, modules, proto_modules, interfaces, packages)?
+            .with_dependencies(dependencies)
+            .with_sources(sources);
+        if let Some(since) = since {
+            builder = builder.with_since(since);
+        }
         builder.build()?;
 
         Ok(true)
@@ -67,6 +67,14 @@ impl CmdDump {
             println!("{}", veryl_analyzer::unsafe_table::dump());
         }
 
+        if self.opt.call_graph {
+            println!("{}", veryl_analyzer::call_graph::dump());
+        }
+
+        if self.opt.instance_graph {
+            println!("{}", veryl_analyzer::instance_graph::InstanceGraph::new());
+        }
+
         Ok(true)
     }
 }
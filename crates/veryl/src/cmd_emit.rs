@@ -0,0 +1,174 @@
+use crate::cmd_check::CheckError;
+use crate::OptEmit;
+use log::info;
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use std::fs;
+use std::io::Write;
+use veryl_analyzer::namespace::Namespace;
+use veryl_analyzer::symbol::{Symbol, SymbolKind};
+use veryl_analyzer::symbol_path::SymbolPath;
+use veryl_analyzer::{namespace_table, symbol_table, type_dag, Analyzer};
+use veryl_emitter::Emitter;
+use veryl_metadata::{Metadata, SourceMapTarget};
+use veryl_parser::{resource_table, Parser};
+
+pub struct CmdEmit {
+    opt: OptEmit,
+}
+
+impl CmdEmit {
+    pub fn new(opt: OptEmit) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let paths = metadata.paths::<&str>(&[], true)?;
+
+        let mut check_error = CheckError::new(metadata)?;
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+
+            let analyzer = Analyzer::new(&metadata.for_file(&path.src));
+            let mut errors = analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
+            check_error = check_error.append(&mut errors).check_err()?;
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        for (path, input, parser, analyzer) in &contexts {
+            let mut errors = analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
+            check_error = check_error.append(&mut errors).check_err()?;
+        }
+
+        for (path, input, parser, analyzer) in &contexts {
+            let mut errors = analyzer.analyze_pass3(&path.prj, input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
+            check_error = check_error.append(&mut errors).check_err()?;
+        }
+
+        let _ = check_error.check_all(false)?;
+
+        let mut namespace = Namespace::new();
+        namespace.push(resource_table::insert_str(&metadata.project.name));
+        let path = SymbolPath::new(&[resource_table::insert_str(&self.opt.module)]);
+        let symbol = match symbol_table::resolve((&path, &namespace)) {
+            Ok(x) if matches!(x.found.kind, SymbolKind::Module(_)) => x.found,
+            _ => bail!("module \"{}\" is not found", self.opt.module),
+        };
+
+        let mut symbols = vec![symbol.clone()];
+        if self.opt.deps {
+            symbols.append(&mut dependencies(&symbol));
+        }
+
+        // Packages and interfaces are emitted before modules, and instantiated modules are
+        // emitted before instantiating modules
+        let sorted: Vec<_> = type_dag::toposort().iter().map(|x| x.id).collect();
+        symbols.sort_by_key(|x| {
+            let kind = match x.kind {
+                SymbolKind::Package(_) => 0,
+                SymbolKind::Interface(_) => 1,
+                _ => 2,
+            };
+            (kind, sorted.iter().position(|y| *y == x.id))
+        });
+
+        // Source map is not required because the output is not written to file
+        let mut metadata = metadata.clone();
+        metadata.build.sourcemap_target = SourceMapTarget::None;
+
+        let mut output = String::new();
+        for symbol in &symbols {
+            let src = symbol.token.source.to_string();
+            let name = symbol.token.to_string();
+            for (path, _, parser, _) in &contexts {
+                if path.src.to_string_lossy() != src {
+                    continue;
+                }
+
+                let mut emitter = Emitter::new(&metadata, &path.src, &path.dst, &path.map);
+                if emitter.emit_only(&path.prj, &parser.veryl, &[&name]) {
+                    if !output.is_empty() {
+                        output.push('\n');
+                    }
+                    output.push_str(emitter.as_str());
+                }
+            }
+        }
+
+        let mut stdout = std::io::stdout();
+        stdout.write_all(output.as_bytes()).into_diagnostic()?;
+        stdout.flush().into_diagnostic()?;
+
+        Ok(true)
+    }
+}
+
+fn is_description(symbol: &Symbol) -> bool {
+    matches!(
+        symbol.kind,
+        SymbolKind::Module(_) | SymbolKind::Interface(_) | SymbolKind::Package(_)
+    )
+}
+
+/// Returns the module, interface or package including `symbol`
+fn owner(symbol: &Symbol) -> Option<Symbol> {
+    if let SymbolKind::GenericInstance(ref x) = symbol.kind {
+        return symbol_table::get(x.base).and_then(|x| owner(&x));
+    }
+    if is_description(symbol) {
+        return Some(symbol.clone());
+    }
+    if symbol.namespace.paths.len() < 2 {
+        return None;
+    }
+
+    let path = SymbolPath::new(&[symbol.namespace.paths[1]]);
+    let mut namespace = Namespace::new();
+    namespace.push(symbol.namespace.paths[0]);
+    symbol_table::resolve((&path, &namespace))
+        .ok()
+        .map(|x| x.found)
+        .filter(is_description)
+}
+
+/// Returns modules, interfaces and packages referenced from `symbol` transitively
+fn dependencies(symbol: &Symbol) -> Vec<Symbol> {
+    let symbols = symbol_table::get_all();
+    let mut ret: Vec<Symbol> = Vec::new();
+    let mut stack = vec![symbol.clone()];
+
+    while let Some(target) = stack.pop() {
+        let mut namespace = target.namespace.clone();
+        namespace.push(target.token.text);
+
+        for x in &symbols {
+            let referenced = x
+                .references
+                .iter()
+                .any(|x| namespace_table::get(x.id).is_some_and(|x| x.included(&namespace)));
+            if !referenced {
+                continue;
+            }
+            if let Some(x) = owner(x) {
+                if x.id != symbol.id && x.id != target.id && !ret.iter().any(|y| y.id == x.id) {
+                    ret.push(x.clone());
+                    stack.push(x);
+                }
+            }
+        }
+    }
+
+    ret
+}
@@ -0,0 +1,229 @@
+use crate::cmd_build::CmdBuild;
+use crate::cmd_diff::{analyze, checkout, split_units};
+use crate::{EquivTool, OptEquiv};
+use log::{error, info};
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use veryl_emitter::Emitter;
+use veryl_metadata::{Metadata, SourceMapTarget};
+
+pub struct CmdEquiv {
+    opt: OptEquiv,
+}
+
+/// Emitted code of a design
+struct Design {
+    /// File name and emitted code
+    files: Vec<(String, String)>,
+    /// Emitted module name and its code
+    modules: BTreeMap<String, String>,
+}
+
+impl CmdEquiv {
+    pub fn new(opt: OptEquiv) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let gold = checkout(metadata, &self.opt.base, emit_design)?;
+        let gate = emit_design(metadata)?;
+
+        let prefix = if metadata.build.omit_project_prefix {
+            String::new()
+        } else {
+            metadata
+                .build
+                .project_prefix
+                .clone()
+                .unwrap_or_else(|| format!("{}_", metadata.project.name))
+        };
+
+        let mut modules = Vec::new();
+        if self.opt.modules.is_empty() {
+            for (name, text) in &gate.modules {
+                if matches!(gold.modules.get(name), Some(x) if x != text) {
+                    modules.push(name.clone());
+                }
+            }
+        } else {
+            for name in &self.opt.modules {
+                let emitted = [format!("{prefix}{name}"), name.clone()]
+                    .into_iter()
+                    .find(|x| gate.modules.contains_key(x));
+                match emitted {
+                    Some(x) if gold.modules.contains_key(&x) => modules.push(x),
+                    Some(_) => bail!(
+                        "module \"{}\" is not found in revision ({})",
+                        name,
+                        self.opt.base
+                    ),
+                    None => bail!("module \"{}\" is not found", name),
+                }
+            }
+        }
+
+        if modules.is_empty() {
+            info!("No changed module from revision ({})", self.opt.base);
+            return Ok(true);
+        }
+
+        let output = metadata.project_path().join(&self.opt.output);
+        write_design(&output.join("gold"), &gold)?;
+        write_design(&output.join("gate"), &gate)?;
+
+        let mut scripts = Vec::new();
+        for module in &modules {
+            let (path, text) = match self.opt.tool {
+                EquivTool::Yosys => (
+                    output.join(format!("{module}.ys")),
+                    yosys_script(module, &gold, &gate),
+                ),
+                EquivTool::Eqy => (
+                    output.join(format!("{module}.eqy")),
+                    eqy_script(module, &output, &gold, &gate),
+                ),
+            };
+            fs::write(&path, text).into_diagnostic()?;
+            info!("Output script ({})", path.to_string_lossy());
+            scripts.push((module, path));
+        }
+
+        if !self.opt.run {
+            return Ok(true);
+        }
+
+        let mut ret = true;
+        for (module, path) in &scripts {
+            info!("Checking equivalence ({})", module);
+            let mut command = match self.opt.tool {
+                EquivTool::Yosys => {
+                    let mut x = Command::new("yosys");
+                    x.arg("-q").arg("-s").arg(path);
+                    x
+                }
+                EquivTool::Eqy => {
+                    let mut x = Command::new("eqy");
+                    x.arg("-f").arg("-d").arg(path.with_extension("")).arg(path);
+                    x
+                }
+            };
+            let status = command
+                .current_dir(&output)
+                .status()
+                .into_diagnostic()
+                .wrap_err("Failed to run equivalence checker")?;
+            if status.success() {
+                info!("Proved equivalence ({})", module);
+            } else {
+                error!("Failed equivalence ({})", module);
+                ret = false;
+            }
+        }
+
+        Ok(ret)
+    }
+}
+
+fn emit_design(metadata: &mut Metadata) -> Result<Design> {
+    // Comments and source maps don't affect the functionality of emitted code
+    metadata.build.strip_comments = true;
+    metadata.build.sourcemap_target = SourceMapTarget::None;
+    metadata.build.header.enable = false;
+
+    let contexts = analyze(metadata)?;
+
+    // Files are read in order of dependency because packages should be read before use
+    let paths: Vec<_> = contexts.iter().map(|(x, _)| x.clone()).collect();
+    let paths = CmdBuild::sort_filelist(metadata, &paths);
+
+    let mut files = Vec::new();
+    let mut units = BTreeMap::new();
+    for path in &paths {
+        let Some((_, parser)) = contexts.iter().find(|(x, _)| x.src == path.src) else {
+            continue;
+        };
+        let mut emitter = Emitter::new(metadata, &path.src, &path.dst, &path.map);
+        emitter.emit(&path.prj, &parser.veryl);
+
+        let name = path.dst.file_name().unwrap().to_string_lossy();
+        files.push((
+            format!("{}_{}", path.prj, name),
+            emitter.as_str().to_string(),
+        ));
+        split_units(emitter.as_str(), &mut units);
+    }
+
+    let modules = units
+        .into_iter()
+        .filter(|(_, x)| x.trim_start().starts_with("module"))
+        .collect();
+
+    Ok(Design { files, modules })
+}
+
+fn write_design(dir: &Path, design: &Design) -> Result<()> {
+    if dir.exists() {
+        fs::remove_dir_all(dir).into_diagnostic()?;
+    }
+    fs::create_dir_all(dir).into_diagnostic()?;
+    for (name, text) in &design.files {
+        fs::write(dir.join(name), text).into_diagnostic()?;
+    }
+    Ok(())
+}
+
+fn read_files(dir: &str, design: &Design) -> String {
+    let mut ret = String::new();
+    for (name, _) in &design.files {
+        ret.push_str(&format!("read_verilog -sv {dir}/{name}\n"));
+    }
+    ret
+}
+
+/// Script of `equiv_make` flow, which proves equivalence by SAT-based induction
+fn yosys_script(module: &str, gold: &Design, gate: &Design) -> String {
+    let mut ret = String::new();
+    ret.push_str(&format!("# Equivalence check of {module}\n\n"));
+
+    for (name, design) in [("gold", gold), ("gate", gate)] {
+        ret.push_str(&read_files(name, design));
+        ret.push_str(&format!("prep -flatten -top {module}\n"));
+        ret.push_str("async2sync\n");
+        ret.push_str(&format!("rename {module} {name}\n"));
+        ret.push_str(&format!("design -stash {name}\n\n"));
+    }
+
+    ret.push_str("design -copy-from gold -as gold gold\n");
+    ret.push_str("design -copy-from gate -as gate gate\n");
+    ret.push_str("equiv_make gold gate equiv\n");
+    ret.push_str("hierarchy -top equiv\n");
+    ret.push_str("equiv_simple -seq 5\n");
+    ret.push_str("equiv_induct -seq 5\n");
+    ret.push_str("equiv_status -assert\n");
+    ret
+}
+
+/// Configuration of EQY, which refers emitted code by absolute path
+/// because EQY runs in its own work directory
+fn eqy_script(module: &str, output: &Path, gold: &Design, gate: &Design) -> String {
+    let mut ret = String::new();
+
+    for (name, design) in [("gold", gold), ("gate", gate)] {
+        let dir = output.join(name);
+        ret.push_str(&format!("[{name}]\n"));
+        ret.push_str(&read_files(&dir.to_string_lossy(), design));
+        ret.push_str(&format!("prep -top {module}\n\n"));
+    }
+
+    ret.push_str("[script]\n");
+    ret.push_str("async2sync\n\n");
+
+    ret.push_str("[strategy sby]\n");
+    ret.push_str("use sby\n");
+    ret.push_str("depth 10\n");
+    ret.push_str("engine smtbmc\n");
+    ret
+}
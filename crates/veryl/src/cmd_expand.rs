@@ -0,0 +1,59 @@
+use crate::OptExpand;
+use log::info;
+use miette::{IntoDiagnostic, Result, WrapErr};
+use std::fs;
+use veryl_analyzer::Analyzer;
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+use veryl_refactor::Source;
+
+pub struct CmdExpand {
+    opt: OptExpand,
+}
+
+impl CmdExpand {
+    pub fn new(opt: OptExpand) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let paths = metadata.paths::<&str>(&[], true)?;
+
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+            let analyzer = Analyzer::new(metadata);
+            analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        for (path, input, parser, analyzer) in &contexts {
+            analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+        }
+
+        // Modules of dependencies are not targets
+        let sources: Vec<_> = contexts
+            .iter()
+            .filter(|(path, _, _, _)| path.prj == metadata.project.name)
+            .map(|(path, input, parser, _)| Source {
+                path: &path.src,
+                input,
+                veryl: &parser.veryl,
+            })
+            .collect();
+
+        let expanded = veryl_refactor::expand(&sources, &self.opt.module, &self.opt.params)?;
+        print!("{expanded}");
+
+        Ok(true)
+    }
+}
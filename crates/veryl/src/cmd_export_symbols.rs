@@ -0,0 +1,206 @@
+use crate::cmd_check::CheckError;
+use crate::OptExportSymbols;
+use log::info;
+use miette::{IntoDiagnostic, Result, WrapErr};
+use serde::Serialize;
+use std::fs;
+use veryl_analyzer::symbol::Symbol;
+use veryl_analyzer::var_ref::{Assign, VarRefPathItem};
+use veryl_analyzer::{symbol_table, Analyzer};
+use veryl_metadata::Metadata;
+use veryl_parser::veryl_token::Token;
+use veryl_parser::Parser;
+
+/// Version of the exported format.
+/// It should be incremented when an incompatible change is made.
+const FORMAT_VERSION: u32 = 1;
+
+pub struct CmdExportSymbols {
+    opt: OptExportSymbols,
+}
+
+/// Root object of the exported JSON
+#[derive(Serialize)]
+pub(crate) struct SymbolExport {
+    /// Version of this format
+    format_version: u32,
+    /// Name of the exported project
+    project: String,
+    /// All symbols including dependencies and the standard library
+    symbols: Vec<SymbolEntry>,
+    /// All assignments to variables and ports
+    assignments: Vec<AssignEntry>,
+}
+
+#[derive(Serialize)]
+struct SymbolEntry {
+    /// Unique ID which is referred from other entries
+    id: usize,
+    name: String,
+    /// Kind name like "module", "port" or "variable"
+    kind: String,
+    /// Human readable description of the kind
+    detail: String,
+    /// Namespace path joined by "::"
+    namespace: String,
+    /// Resolved type for typed symbols
+    r#type: Option<String>,
+    public: bool,
+    doc_comment: String,
+    /// Location of the declaration
+    location: Location,
+    /// Locations referring this symbol
+    references: Vec<Location>,
+}
+
+#[derive(Serialize)]
+struct AssignEntry {
+    /// Assigned path like "a.b[3:0]"
+    path: String,
+    /// Symbol IDs included in the assigned path
+    symbols: Vec<usize>,
+    /// Whether the assignment covers a part of the variable
+    partial: bool,
+    /// Nesting of declarations and statements enclosing the assignment
+    position: Vec<Location>,
+}
+
+#[derive(Serialize)]
+struct Location {
+    /// Source file path, or "builtin" / "external" / "generated"
+    file: String,
+    /// 1-origin line number
+    line: u32,
+    /// 1-origin column number
+    column: u32,
+    /// Length in characters
+    length: u32,
+    text: String,
+}
+
+impl From<&Token> for Location {
+    fn from(x: &Token) -> Self {
+        Self {
+            file: x.source.to_string(),
+            line: x.line,
+            column: x.column,
+            length: x.length,
+            text: x.to_string(),
+        }
+    }
+}
+
+impl From<&Symbol> for SymbolEntry {
+    fn from(x: &Symbol) -> Self {
+        Self {
+            id: x.id.0,
+            name: x.token.to_string(),
+            kind: x.kind.to_kind_name(),
+            detail: x.kind.to_string(),
+            namespace: x.namespace.to_string(),
+            r#type: x.r#type.as_ref().map(|x| x.to_string()),
+            public: x.public,
+            doc_comment: x.doc_comment.format(false),
+            location: (&x.token).into(),
+            references: x.references.iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+impl From<&Assign> for AssignEntry {
+    fn from(x: &Assign) -> Self {
+        let symbols = x
+            .path
+            .0
+            .iter()
+            .filter_map(|x| match x {
+                VarRefPathItem::Identifier { symbol_id } => Some(symbol_id.0),
+                _ => None,
+            })
+            .collect();
+        Self {
+            path: x.path.to_string(),
+            symbols,
+            partial: x.partial,
+            position: x.position.0.iter().map(|x| x.token().into()).collect(),
+        }
+    }
+}
+
+impl CmdExportSymbols {
+    pub fn new(opt: OptExportSymbols) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let export = self.export(metadata)?;
+        let text = serde_json::to_string_pretty(&export).into_diagnostic()?;
+
+        if let Some(ref output) = self.opt.output {
+            fs::write(output, text).into_diagnostic()?;
+            info!("Output symbols ({})", output.to_string_lossy());
+        } else {
+            println!("{text}");
+        }
+
+        Ok(true)
+    }
+
+    /// Analyzes the target files and collects symbols and assignments.
+    /// It fails if the analysis reports any error, because the symbol table may be incomplete.
+    pub(crate) fn export(&self, metadata: &mut Metadata) -> Result<SymbolExport> {
+        let paths = metadata.paths(&self.opt.files, true)?;
+
+        let mut check_error = CheckError::new(metadata)?;
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+            let analyzer = Analyzer::new(metadata);
+            let mut errors = analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
+            check_error = check_error.append(&mut errors).check_err()?;
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        for (path, input, parser, analyzer) in &contexts {
+            let mut errors = analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
+            check_error = check_error.append(&mut errors).check_err()?;
+        }
+
+        for (path, input, parser, analyzer) in &contexts {
+            let mut errors = analyzer.analyze_pass3(&path.prj, input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
+            check_error = check_error.append(&mut errors).check_err()?;
+        }
+
+        let _ = check_error.check_all(false)?;
+
+        let mut symbols: Vec<_> = symbol_table::get_all()
+            .iter()
+            .map(SymbolEntry::from)
+            .collect();
+        symbols.sort_by_key(|x| x.id);
+
+        let assignments = symbol_table::get_assign_list()
+            .iter()
+            .map(AssignEntry::from)
+            .collect();
+
+        Ok(SymbolExport {
+            format_version: FORMAT_VERSION,
+            project: metadata.project.name.clone(),
+            symbols,
+            assignments,
+        })
+    }
+}
@@ -32,7 +32,7 @@ impl CmdFmt {
                 .into_diagnostic()
                 .wrap_err("")?;
             let parser = Parser::parse(&input, &path.src)?;
-            let mut formatter = Formatter::new(metadata);
+            let mut formatter = Formatter::new(&metadata.for_file(&path.src));
             formatter.format(&parser.veryl);
 
             let pass = input.as_str() == formatter.as_str();
@@ -71,7 +71,7 @@ impl fmt::Display for Line {
     }
 }
 
-fn print_diff(file: &Path, org: &str, new: &str) {
+pub fn print_diff(file: &Path, org: &str, new: &str) {
     let diff = TextDiff::from_lines(org, new);
 
     println!("Diff in {}", file.to_string_lossy());
@@ -0,0 +1,189 @@
+use crate::{GrepDirection, GrepKind, GrepScope, OptGrep};
+use log::info;
+use miette::{IntoDiagnostic, Result, WrapErr};
+use regex::Regex;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use veryl_analyzer::symbol::{Direction, Symbol, SymbolKind};
+use veryl_analyzer::{symbol_table, Analyzer};
+use veryl_metadata::Metadata;
+use veryl_parser::veryl_token::{Token, TokenSource};
+use veryl_parser::Parser;
+
+pub struct CmdGrep {
+    opt: OptGrep,
+}
+
+impl GrepKind {
+    fn matches(&self, kind: &SymbolKind) -> bool {
+        matches!(
+            (self, kind),
+            (GrepKind::Port, SymbolKind::Port(_))
+                | (GrepKind::Variable, SymbolKind::Variable(_))
+                | (GrepKind::Parameter, SymbolKind::Parameter(_))
+                | (GrepKind::Module, SymbolKind::Module(_))
+                | (GrepKind::Interface, SymbolKind::Interface(_))
+                | (GrepKind::Package, SymbolKind::Package(_))
+                | (GrepKind::Function, SymbolKind::Function(_))
+                | (GrepKind::Instance, SymbolKind::Instance(_))
+                | (GrepKind::Modport, SymbolKind::Modport(_))
+                | (GrepKind::Struct, SymbolKind::Struct(_))
+                | (GrepKind::Union, SymbolKind::Union(_))
+                | (GrepKind::Enum, SymbolKind::Enum(_))
+                | (GrepKind::EnumMember, SymbolKind::EnumMember(_))
+                | (GrepKind::TypeDef, SymbolKind::TypeDef(_))
+                | (GrepKind::Genvar, SymbolKind::Genvar)
+        )
+    }
+}
+
+impl From<GrepDirection> for Direction {
+    fn from(x: GrepDirection) -> Self {
+        match x {
+            GrepDirection::Input => Direction::Input,
+            GrepDirection::Output => Direction::Output,
+            GrepDirection::Inout => Direction::Inout,
+            GrepDirection::Ref => Direction::Ref,
+            GrepDirection::Modport => Direction::Modport,
+            GrepDirection::Import => Direction::Import,
+        }
+    }
+}
+
+impl CmdGrep {
+    pub fn new(opt: OptGrep) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let matches = self.search(metadata)?;
+
+        for x in &matches {
+            println!("{x}");
+        }
+
+        if matches.is_empty() {
+            info!("No match");
+        }
+
+        Ok(!matches.is_empty())
+    }
+
+    /// Returns matches formatted as `path:line:column: role kind name` in order of location
+    pub(crate) fn search(&self, metadata: &mut Metadata) -> Result<Vec<String>> {
+        let pattern = Regex::new(&self.opt.pattern).into_diagnostic()?;
+        let paths = metadata.paths(&self.opt.files, true)?;
+
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+            let analyzer = Analyzer::new(metadata);
+            analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        for (path, input, parser, analyzer) in &contexts {
+            analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+        }
+
+        // Only matches in the project are shown even if the symbol is declared in dependencies
+        let sources: HashSet<_> = contexts
+            .iter()
+            .filter(|(path, _, _, _)| path.prj == metadata.project.name)
+            .map(|(path, _, _, _)| path.src.clone())
+            .collect();
+        let in_sources = |token: &Token| {
+            if let TokenSource::File(x) = token.source {
+                sources.contains(&PathBuf::from(x.to_string()))
+            } else {
+                false
+            }
+        };
+
+        let mut matches = BTreeSet::new();
+        for symbol in symbol_table::get_all() {
+            if !self.filter(&pattern, &symbol) {
+                continue;
+            }
+
+            let kind = symbol.kind.to_kind_name();
+            let name = format!("{}::{}", symbol.namespace, symbol.token);
+
+            if !matches!(self.opt.scope, GrepScope::References) && in_sources(&symbol.token) {
+                matches.insert(Match::new(&symbol.token, "declaration", &kind, &name));
+            }
+            if !matches!(self.opt.scope, GrepScope::Declarations) {
+                for token in symbol.references.iter().filter(|x| in_sources(x)) {
+                    // The declaration itself may be registered as a reference
+                    if token.id != symbol.token.id {
+                        matches.insert(Match::new(token, "reference", &kind, &name));
+                    }
+                }
+            }
+        }
+
+        Ok(matches
+            .iter()
+            .map(|x| {
+                format!(
+                    "{}:{}:{}: {} {} {}",
+                    x.path, x.line, x.column, x.role, x.kind, x.name
+                )
+            })
+            .collect())
+    }
+
+    fn filter(&self, pattern: &Regex, symbol: &Symbol) -> bool {
+        if !pattern.is_match(&symbol.token.to_string()) {
+            return false;
+        }
+
+        if !self.opt.kinds.is_empty() && !self.opt.kinds.iter().any(|x| x.matches(&symbol.kind)) {
+            return false;
+        }
+
+        if let Some(direction) = self.opt.direction {
+            let SymbolKind::Port(ref x) = symbol.kind else {
+                return false;
+            };
+            if x.direction != direction.into() {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Match {
+    path: String,
+    line: u32,
+    column: u32,
+    role: &'static str,
+    kind: String,
+    name: String,
+}
+
+impl Match {
+    fn new(token: &Token, role: &'static str, kind: &str, name: &str) -> Self {
+        Self {
+            path: token.source.to_string(),
+            line: token.line,
+            column: token.column,
+            role,
+            kind: kind.to_string(),
+            name: name.to_string(),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{Opt, OptMan};
+use clap::{Arg, Command, CommandFactory};
+use log::debug;
+use miette::{IntoDiagnostic, Result};
+use std::fs;
+
+pub struct CmdMan {
+    opt: OptMan,
+}
+
+impl CmdMan {
+    pub fn new(opt: OptMan) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self) -> Result<bool> {
+        let mut command = Opt::command();
+        // Propagate global options and version to subcommands
+        command.build();
+
+        if let Some(ref output) = self.opt.output {
+            fs::create_dir_all(output).into_diagnostic()?;
+            let mut pages = Vec::new();
+            collect_pages(&command, &[], &mut pages);
+            for (name, page) in pages {
+                let path = output.join(format!("{name}.1"));
+                fs::write(&path, page).into_diagnostic()?;
+                debug!("Output file ({})", path.to_string_lossy());
+            }
+        } else {
+            print!("{}", render_page(&command, &[]));
+        }
+
+        Ok(true)
+    }
+}
+
+/// Collects pages of `command` and its subcommands recursively like `veryl-build.1`
+fn collect_pages(command: &Command, parents: &[&str], pages: &mut Vec<(String, String)>) {
+    let mut names = parents.to_vec();
+    names.push(command.get_name());
+    pages.push((names.join("-"), render_page(command, parents)));
+    for sub in visible_subcommands(command) {
+        collect_pages(sub, &names, pages);
+    }
+}
+
+fn visible_subcommands(command: &Command) -> impl Iterator<Item = &Command> {
+    command
+        .get_subcommands()
+        .filter(|x| !x.is_hide_set() && x.get_name() != "help")
+}
+
+fn render_page(command: &Command, parents: &[&str]) -> String {
+    let mut names = parents.to_vec();
+    names.push(command.get_name());
+    let version = command.get_version().unwrap_or_default();
+
+    let mut ret = String::new();
+    ret.push_str(&format!(
+        ".TH {} 1 \"\" \"{} {}\"\n",
+        escape(&names.join("-").to_uppercase()),
+        escape(names[0]),
+        escape(version)
+    ));
+
+    ret.push_str(".SH NAME\n");
+    let about = command
+        .get_about()
+        .map(|x| x.to_string())
+        .unwrap_or_default();
+    ret.push_str(&format!(
+        "{} \\- {}\n",
+        escape(&names.join("-")),
+        escape(&about)
+    ));
+
+    ret.push_str(".SH SYNOPSIS\n");
+    let usage = command.clone().render_usage().to_string();
+    let usage = usage.trim_start_matches("Usage: ");
+    ret.push_str(&format!("\\fB{}\\fR\n", escape(usage)));
+
+    if let Some(x) = command.get_long_about() {
+        ret.push_str(".SH DESCRIPTION\n");
+        ret.push_str(&paragraph(&x.to_string()));
+    }
+
+    let args: Vec<_> = command
+        .get_arguments()
+        .filter(|x| !x.is_hide_set())
+        .collect();
+    let (positionals, options): (Vec<_>, Vec<_>) =
+        args.into_iter().partition(|x| x.is_positional());
+    if !positionals.is_empty() {
+        ret.push_str(".SH ARGUMENTS\n");
+        for arg in positionals {
+            ret.push_str(&render_arg(arg));
+        }
+    }
+    if !options.is_empty() {
+        ret.push_str(".SH OPTIONS\n");
+        for arg in options {
+            ret.push_str(&render_arg(arg));
+        }
+    }
+
+    let subcommands: Vec<_> = visible_subcommands(command).collect();
+    if !subcommands.is_empty() {
+        ret.push_str(".SH SUBCOMMANDS\n");
+        for sub in subcommands {
+            let about = sub.get_about().map(|x| x.to_string()).unwrap_or_default();
+            ret.push_str(&format!(
+                ".TP\n\\fB{}\\-{}\\fR(1)\n{}",
+                escape(&names.join("-")),
+                escape(sub.get_name()),
+                paragraph(&about)
+            ));
+        }
+    }
+
+    ret
+}
+
+fn render_arg(arg: &Arg) -> String {
+    // Flags like `--quiet` take no value after `Command::build`
+    let takes_values = arg.get_num_args().is_some_and(|x| x.takes_values());
+    let values: Vec<_> = arg
+        .get_value_names()
+        .filter(|_| takes_values)
+        .map(|x| x.iter().map(|x| format!("<{x}>")).collect())
+        .unwrap_or_else(|| {
+            if takes_values {
+                vec![format!("<{}>", arg.get_id().as_str().to_uppercase())]
+            } else {
+                vec![]
+            }
+        });
+
+    let mut header = Vec::new();
+    if let Some(x) = arg.get_short() {
+        header.push(format!("\\-{x}"));
+    }
+    if let Some(x) = arg.get_long() {
+        header.push(format!("\\-\\-{}", escape(x)));
+    }
+    let mut header = format!("\\fB{}\\fR", header.join(", "));
+    if arg.is_positional() {
+        header = format!("\\fI{}\\fR", escape(&values.join(" ")));
+    } else if !values.is_empty() {
+        header.push_str(&format!(" \\fI{}\\fR", escape(&values.join(" "))));
+    }
+
+    let mut help = arg.get_help().map(|x| x.to_string()).unwrap_or_default();
+    let possible: Vec<_> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|x| !x.is_hide_set())
+        .map(|x| x.get_name().to_string())
+        .collect();
+    if !possible.is_empty() && takes_values {
+        help.push_str(&format!(" [possible values: {}]", possible.join(", ")));
+    }
+
+    format!(".TP\n{header}\n{}", paragraph(&help))
+}
+
+fn paragraph(text: &str) -> String {
+    let mut ret = String::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            ret.push_str(".PP\n");
+        } else {
+            ret.push_str(&escape(line));
+            ret.push('\n');
+        }
+    }
+    ret
+}
+
+/// Escapes roff special characters
+fn escape(text: &str) -> String {
+    let ret = text.replace('\\', "\\e").replace('-', "\\-");
+    if ret.starts_with('.') || ret.starts_with('\'') {
+        format!("\\&{ret}")
+    } else {
+        ret
+    }
+}
@@ -0,0 +1,84 @@
+use crate::OptMigrate;
+use log::{debug, info, warn};
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use veryl_formatter::Formatter;
+use veryl_metadata::Metadata;
+use veryl_migrate::{migrate, Preprocessor};
+use veryl_parser::Parser;
+
+pub struct CmdMigrate {
+    opt: OptMigrate,
+}
+
+impl CmdMigrate {
+    pub fn new(opt: OptMigrate) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &Metadata) -> Result<bool> {
+        let mut preprocessor = Preprocessor::new(&self.opt.defines, &self.opt.include_dirs);
+
+        for src in &self.opt.files {
+            info!("Migrating file ({})", src.to_string_lossy());
+
+            let mut input = fs::read_to_string(src).into_diagnostic().wrap_err("")?;
+            if self.opt.preprocess {
+                input = preprocessor.process(&input, src)?;
+            }
+            let migrated = migrate(&input);
+
+            let dst = if let Some(ref dir) = self.opt.output {
+                let Some(name) = src.file_name() else {
+                    bail!("path \"{}\" is not valid", src.to_string_lossy());
+                };
+                dir.join(name).with_extension("veryl")
+            } else {
+                src.with_extension("veryl")
+            };
+            if dst.exists() {
+                bail!("\"{}\" exists", dst.to_string_lossy());
+            }
+
+            // Format the result if it can be parsed, otherwise output for manual fix
+            let text = match Parser::parse(&migrated.text, &dst) {
+                Ok(parser) => {
+                    let mut formatter = Formatter::new(metadata);
+                    formatter.format(&parser.veryl);
+                    formatter.as_str().to_string()
+                }
+                Err(_) => {
+                    warn!("Migrated code has syntax error ({})", dst.to_string_lossy());
+                    migrated.text
+                }
+            };
+
+            if let Some(dir) = dst.parent() {
+                if !dir.as_os_str().is_empty() && !dir.exists() {
+                    fs::create_dir_all(dir).into_diagnostic()?;
+                }
+            }
+            let mut file = OpenOptions::new()
+                .create_new(true)
+                .write(true)
+                .open(&dst)
+                .into_diagnostic()?;
+            file.write_all(text.as_bytes()).into_diagnostic()?;
+            file.flush().into_diagnostic()?;
+
+            debug!("Output file ({})", dst.to_string_lossy());
+
+            if migrated.todos > 0 {
+                warn!(
+                    "Unconverted {} constructs are marked as TODO ({})",
+                    migrated.todos,
+                    dst.to_string_lossy()
+                );
+            }
+        }
+
+        Ok(true)
+    }
+}
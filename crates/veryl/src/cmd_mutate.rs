@@ -0,0 +1,285 @@
+use crate::cmd_build::CmdBuild;
+use crate::cmd_test::{parse_test_args, run_tests};
+use crate::runner::RunOption;
+use crate::{OptBuild, OptMutate};
+use log::{error, info, warn};
+use miette::{bail, IntoDiagnostic, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use veryl_metadata::{FilelistType, Metadata};
+use veryl_sourcemap::SourceMap;
+
+pub struct CmdMutate {
+    opt: OptMutate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MutationKind {
+    OperatorSwap,
+    ConstantTweak,
+    BranchInversion,
+}
+
+impl fmt::Display for MutationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            MutationKind::OperatorSwap => "operator swap",
+            MutationKind::ConstantTweak => "constant tweak",
+            MutationKind::BranchInversion => "branch inversion",
+        };
+        text.fmt(f)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Mutant {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub kind: MutationKind,
+    pub original: String,
+    pub mutated: String,
+}
+
+impl Mutant {
+    fn location(&self) -> String {
+        if let Ok(source_map) = SourceMap::from_src(&self.path) {
+            if let Some((path, line, column)) =
+                source_map.lookup(self.line as u32, self.column as u32)
+            {
+                return format!("{}:{}:{}", path.to_string_lossy(), line, column);
+            }
+        }
+        format!(
+            "{}:{}:{}",
+            self.path.to_string_lossy(),
+            self.line,
+            self.column
+        )
+    }
+}
+
+const OPERATORS: &[(&str, &str, MutationKind)] = &[
+    ("==", "!=", MutationKind::OperatorSwap),
+    ("!=", "==", MutationKind::OperatorSwap),
+    ("&&", "||", MutationKind::OperatorSwap),
+    ("||", "&&", MutationKind::OperatorSwap),
+    (" + ", " - ", MutationKind::OperatorSwap),
+    (" - ", " + ", MutationKind::OperatorSwap),
+    (" & ", " | ", MutationKind::OperatorSwap),
+    (" | ", " & ", MutationKind::OperatorSwap),
+    (" < ", " >= ", MutationKind::OperatorSwap),
+    (" >= ", " < ", MutationKind::OperatorSwap),
+    (" > ", " <= ", MutationKind::OperatorSwap),
+    ("1'b0", "1'b1", MutationKind::ConstantTweak),
+    ("1'b1", "1'b0", MutationKind::ConstantTweak),
+    ("'0", "'1", MutationKind::ConstantTweak),
+    ("'1", "'0", MutationKind::ConstantTweak),
+];
+
+static BRANCH: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bif \((.*)\) begin").unwrap());
+
+/// Collect mutants of the given SystemVerilog source.
+/// Comments and test blocks are not mutated.
+pub fn mutants(path: &Path, text: &str) -> Vec<Mutant> {
+    let mut ret = Vec::new();
+    let mut test_depth = 0;
+
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if test_depth > 0 {
+            if trimmed.starts_with("`ifdef") || trimmed.starts_with("`ifndef") {
+                test_depth += 1;
+            } else if trimmed.starts_with("`endif") {
+                test_depth -= 1;
+            }
+            continue;
+        }
+        if trimmed.starts_with("`ifdef __veryl_test_") {
+            test_depth = 1;
+            continue;
+        }
+        if trimmed.starts_with("//") || trimmed.starts_with('`') {
+            continue;
+        }
+
+        let code = match line.find("//") {
+            Some(x) => &line[..x],
+            None => line,
+        };
+
+        for (from, to, kind) in OPERATORS {
+            for (pos, _) in code.match_indices(from) {
+                let mutated = format!("{}{}{}", &line[..pos], to, &line[pos + from.len()..]);
+                ret.push(Mutant {
+                    path: path.to_path_buf(),
+                    line: i + 1,
+                    column: pos + 1,
+                    kind: *kind,
+                    original: line.to_string(),
+                    mutated,
+                });
+            }
+        }
+
+        if let Some(caps) = BRANCH.captures(code) {
+            let cond = caps.get(1).unwrap();
+            let mutated = format!(
+                "{}!({}){}",
+                &line[..cond.start()],
+                cond.as_str(),
+                &line[cond.end()..]
+            );
+            ret.push(Mutant {
+                path: path.to_path_buf(),
+                line: i + 1,
+                column: cond.start() + 1,
+                kind: MutationKind::BranchInversion,
+                original: line.to_string(),
+                mutated,
+            });
+        }
+    }
+
+    ret
+}
+
+fn apply(text: &str, mutant: &Mutant) -> String {
+    let mut ret = String::new();
+    for (i, line) in text.lines().enumerate() {
+        if i + 1 == mutant.line {
+            ret.push_str(&mutant.mutated);
+        } else {
+            ret.push_str(line);
+        }
+        ret.push('\n');
+    }
+    ret
+}
+
+impl CmdMutate {
+    pub fn new(opt: OptMutate) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        // force filelist_type to absolute which can be refered from temporary directory
+        metadata.build.filelist_type = FilelistType::Absolute;
+
+        let build = CmdBuild::new(OptBuild {
+            files: self.opt.files.clone(),
+            deny_warnings: false,
+            verify_output: false,
+            matrix: None,
+        });
+        build.exec(metadata)?;
+
+        let sim_type = if let Some(x) = self.opt.sim {
+            x.into()
+        } else {
+            metadata.test.simulator
+        };
+
+        let option = RunOption {
+            wave: None,
+            args: parse_test_args(&self.opt.test_args)?,
+            coverage: None,
+        };
+
+        let (success, failure) = run_tests(metadata, sim_type, &option)?;
+        if success == 0 {
+            bail!("No test to evaluate mutants");
+        }
+        if failure != 0 {
+            bail!("Tests should pass before mutation : {} failed", failure);
+        }
+
+        let mut targets = Vec::new();
+        for path in metadata.paths(&self.opt.files, false)? {
+            if path.prj != metadata.project.name {
+                continue;
+            }
+            let text = fs::read_to_string(&path.dst).into_diagnostic()?;
+            let mutants = mutants(&path.dst, &text);
+            targets.push((path.dst, text, mutants));
+        }
+
+        let total: usize = targets.iter().map(|(_, _, x)| x.len()).sum();
+        let total = self.opt.max.map(|x| x.min(total)).unwrap_or(total);
+        info!("Generated mutants : {}", total);
+
+        let mut killed = 0;
+        let mut survived = Vec::new();
+        let mut index = 0;
+        'outer: for (path, text, mutants) in &targets {
+            for mutant in mutants {
+                if index == total {
+                    break 'outer;
+                }
+                index += 1;
+
+                info!(
+                    "Testing mutant {}/{} ({} at {})",
+                    index,
+                    total,
+                    mutant.kind,
+                    mutant.location()
+                );
+
+                let mutated = apply(text, mutant);
+                fs::write(path, &mutated).into_diagnostic()?;
+                let ret = run_tests(metadata, sim_type, &option);
+                fs::write(path, text).into_diagnostic()?;
+                let (_, failure) = ret?;
+
+                if failure == 0 {
+                    if let Some(ref dir) = self.opt.output {
+                        fs::create_dir_all(dir).into_diagnostic()?;
+                        let name = format!(
+                            "{}_{}.sv",
+                            path.file_stem().unwrap().to_string_lossy(),
+                            index
+                        );
+                        fs::write(dir.join(name), &mutated).into_diagnostic()?;
+                    }
+                    survived.push(mutant);
+                } else {
+                    killed += 1;
+                }
+            }
+        }
+
+        for mutant in &survived {
+            warn!("Survived mutant ({} at {})", mutant.kind, mutant.location());
+            warn!("  - {}", mutant.original.trim());
+            warn!("  + {}", mutant.mutated.trim());
+        }
+
+        let score = if total == 0 {
+            100.0
+        } else {
+            killed as f64 * 100.0 / total as f64
+        };
+
+        if survived.is_empty() {
+            info!(
+                "Completed mutation : {} killed, {} survived (score {:.1}%)",
+                killed,
+                survived.len(),
+                score
+            );
+            Ok(true)
+        } else {
+            error!(
+                "Completed mutation : {} killed, {} survived (score {:.1}%)",
+                killed,
+                survived.len(),
+                score
+            );
+            Ok(false)
+        }
+    }
+}
@@ -1,8 +1,11 @@
+use crate::cmd_diff::git;
 use crate::OptNew;
-use log::info;
+use log::{debug, info};
 use miette::{bail, IntoDiagnostic, Result};
 use std::fs::{self, File};
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
 use veryl_metadata::Metadata;
 
 pub struct CmdNew {
@@ -21,12 +24,24 @@ impl CmdNew {
 
         if let Some(name) = self.opt.path.file_name() {
             let name = name.to_string_lossy();
-            let toml = Metadata::create_default_toml(&name).into_diagnostic()?;
 
-            fs::create_dir_all(&self.opt.path).into_diagnostic()?;
-            let mut file = File::create(self.opt.path.join("Veryl.toml")).into_diagnostic()?;
-            write!(file, "{toml}").into_diagnostic()?;
-            file.flush().into_diagnostic()?;
+            if let Some(ref template) = self.opt.template {
+                if let Err(x) = self.create_from_template(template, &name) {
+                    let _ = fs::remove_dir_all(&self.opt.path);
+                    return Err(x);
+                }
+            }
+
+            // Template without Veryl.toml is completed by the default one
+            let toml_path = self.opt.path.join("Veryl.toml");
+            if !toml_path.exists() {
+                let toml = Metadata::create_default_toml(&name).into_diagnostic()?;
+
+                fs::create_dir_all(&self.opt.path).into_diagnostic()?;
+                let mut file = File::create(toml_path).into_diagnostic()?;
+                write!(file, "{toml}").into_diagnostic()?;
+                file.flush().into_diagnostic()?;
+            }
 
             info!("Created \"{}\" project", name);
         } else {
@@ -35,4 +50,94 @@ impl CmdNew {
 
         Ok(true)
     }
+
+    fn create_from_template(&self, template: &str, name: &str) -> Result<()> {
+        let temp_dir = TempDir::new().into_diagnostic()?;
+
+        let src = if Path::new(template).is_dir() {
+            PathBuf::from(template)
+        } else {
+            info!("Fetching template ({})", template);
+            git(
+                temp_dir.path(),
+                &["clone", "--depth", "1", template, "template"],
+            )?;
+            temp_dir.path().join("template")
+        };
+
+        let placeholders = Placeholders {
+            project_name: name.to_string(),
+            author: author(),
+            license: self.opt.license.clone(),
+        };
+        copy_template(&src, &self.opt.path, &placeholders)
+    }
+}
+
+struct Placeholders {
+    project_name: String,
+    author: String,
+    license: Option<String>,
+}
+
+impl Placeholders {
+    /// Replaces `{{project_name}}`, `{{author}}` and `{{license}}`
+    fn apply(&self, text: &str) -> Result<String> {
+        let mut ret = text
+            .replace("{{project_name}}", &self.project_name)
+            .replace("{{author}}", &self.author);
+        if ret.contains("{{license}}") {
+            let Some(ref license) = self.license else {
+                bail!("template requires license, specify it by --license");
+            };
+            ret = ret.replace("{{license}}", license);
+        }
+        Ok(ret)
+    }
+}
+
+/// Returns `name <email>` from git configuration
+fn author() -> String {
+    let config = |key| {
+        git(Path::new("."), &["config", "--get", key])
+            .map(|x| x.trim().to_string())
+            .unwrap_or_default()
+    };
+    let name = config("user.name");
+    let email = config("user.email");
+    match (name.is_empty(), email.is_empty()) {
+        (false, false) => format!("{name} <{email}>"),
+        (false, true) => name,
+        (true, false) => email,
+        (true, true) => String::new(),
+    }
+}
+
+fn copy_template(src: &Path, dst: &Path, placeholders: &Placeholders) -> Result<()> {
+    fs::create_dir_all(dst).into_diagnostic()?;
+
+    for entry in fs::read_dir(src).into_diagnostic()? {
+        let entry = entry.into_diagnostic()?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if file_name == ".git" {
+            continue;
+        }
+
+        let src = entry.path();
+        let dst = dst.join(placeholders.apply(&file_name)?);
+        if entry.file_type().into_diagnostic()?.is_dir() {
+            copy_template(&src, &dst, placeholders)?;
+        } else {
+            let bytes = fs::read(&src).into_diagnostic()?;
+            // Binary files are copied as is
+            let bytes = match String::from_utf8(bytes) {
+                Ok(text) => placeholders.apply(&text)?.into_bytes(),
+                Err(x) => x.into_bytes(),
+            };
+            fs::write(&dst, bytes).into_diagnostic()?;
+            debug!("Created file ({})", dst.to_string_lossy());
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,170 @@
+use crate::{OptParse, ParseFormat};
+use log::info;
+use miette::{IntoDiagnostic, Result, WrapErr};
+use serde::Serialize;
+use std::fs;
+use veryl_parser::lexer::{self, LexToken};
+use veryl_parser::syntax_tree::{dump_ast, SyntaxNode};
+use veryl_parser::veryl_token::Token;
+use veryl_parser::Parser;
+
+/// Version of the dumped format.
+/// It should be incremented when an incompatible change is made.
+const FORMAT_VERSION: u32 = 1;
+
+pub struct CmdParse {
+    opt: OptParse,
+}
+
+/// Root object of the dumped JSON
+#[derive(Serialize)]
+struct CstExport {
+    /// Version of this format
+    format_version: u32,
+    /// Source file path
+    path: String,
+    root: CstNode,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum CstNode {
+    NonTerminal {
+        /// Name of the non-terminal like "ModuleDeclaration"
+        kind: String,
+        /// Span from the first token to the last token, or null if no token is included
+        span: Option<Span>,
+        children: Vec<CstNode>,
+    },
+    Token {
+        #[serde(flatten)]
+        token: CstToken,
+        /// Comments following the token
+        comments: Vec<CstToken>,
+    },
+}
+
+#[derive(Serialize)]
+struct CstToken {
+    text: String,
+    span: Span,
+}
+
+#[derive(Clone, Copy, Serialize)]
+struct Span {
+    /// Byte offset of the beginning
+    start: u32,
+    /// Byte offset of the end (exclusive)
+    end: u32,
+    /// 1-origin line number of the beginning
+    line: u32,
+    /// 1-origin column number of the beginning
+    column: u32,
+}
+
+impl From<&LexToken> for CstToken {
+    fn from(x: &LexToken) -> Self {
+        Self {
+            text: x.text.clone(),
+            span: Span {
+                start: x.pos,
+                end: x.pos + x.length,
+                line: x.line,
+                column: x.column,
+            },
+        }
+    }
+}
+
+impl From<&Token> for CstToken {
+    fn from(x: &Token) -> Self {
+        Self {
+            text: x.to_string(),
+            span: Span {
+                start: x.pos,
+                end: x.pos + x.length,
+                line: x.line,
+                column: x.column,
+            },
+        }
+    }
+}
+
+/// Converts `SyntaxNode` to `CstNode` locating comments in `input`
+fn convert(node: &SyntaxNode, input: &str) -> CstNode {
+    match node {
+        SyntaxNode::NonTerminal { kind, children } => {
+            let tokens = node.tokens();
+            let span = match (tokens.first(), tokens.last()) {
+                (Some(first), Some(last)) => {
+                    let first = CstToken::from(&first.token).span;
+                    let last = CstToken::from(&last.token).span;
+                    Some(Span {
+                        end: last.end,
+                        ..first
+                    })
+                }
+                _ => None,
+            };
+            CstNode::NonTerminal {
+                kind: kind.clone(),
+                span,
+                children: children.iter().map(|x| convert(x, input)).collect(),
+            }
+        }
+        SyntaxNode::Token(x) => CstNode::Token {
+            token: (&x.token).into(),
+            comments: lexer::comments(input, x).iter().map(|x| x.into()).collect(),
+        },
+    }
+}
+
+impl CmdParse {
+    pub fn new(opt: OptParse) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self) -> Result<bool> {
+        let path = &self.opt.file;
+        info!("Processing file ({})", path.to_string_lossy());
+
+        let input = fs::read_to_string(path).into_diagnostic().wrap_err("")?;
+        let parser = Parser::parse(&input, path)?;
+
+        let text = match self.opt.format {
+            ParseFormat::CstJson => {
+                let export = CstExport {
+                    format_version: FORMAT_VERSION,
+                    path: path.to_string_lossy().to_string(),
+                    root: convert(&SyntaxNode::new(&parser.veryl), &input),
+                };
+                serde_json::to_string_pretty(&export).into_diagnostic()?
+            }
+            ParseFormat::Ast => dump_ast(&parser.veryl),
+            ParseFormat::Tokens => {
+                let mut text = String::new();
+                for x in lexer::tokenize(&input, path)? {
+                    text.push_str(&format!(
+                        "{}:{} {}..{} {} {:?}\n",
+                        x.line,
+                        x.column,
+                        x.pos,
+                        x.pos + x.length,
+                        x.kind,
+                        x.text
+                    ));
+                }
+                text
+            }
+        };
+
+        if let Some(ref output) = self.opt.output {
+            fs::write(output, text).into_diagnostic()?;
+            info!("Output parse result ({})", output.to_string_lossy());
+        } else {
+            println!("{text}");
+        }
+
+        Ok(true)
+    }
+}
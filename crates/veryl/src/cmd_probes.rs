@@ -0,0 +1,158 @@
+use crate::{OptProbes, ProbesFormat};
+use log::info;
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use regex::Regex;
+use std::fs;
+use veryl_analyzer::instance_graph::InstanceGraph;
+use veryl_analyzer::symbol::{Symbol, SymbolId, SymbolKind};
+use veryl_analyzer::{symbol_table, Analyzer};
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+
+pub struct CmdProbes {
+    opt: OptProbes,
+}
+
+impl CmdProbes {
+    pub fn new(opt: OptProbes) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let paths = metadata.paths::<&str>(&[], true)?;
+
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+            let analyzer = Analyzer::new(metadata);
+            analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        let top = symbol_table::get_all().into_iter().find(|x| {
+            matches!(x.kind, SymbolKind::Module(_))
+                && x.token.to_string() == self.opt.top
+                && x.namespace.paths.len() == 1
+                && x.namespace.paths[0].to_string() == metadata.project.name
+        });
+        let Some(top) = top else {
+            bail!("top module \"{}\" is not found", self.opt.top);
+        };
+
+        let patterns = self
+            .opt
+            .patterns
+            .iter()
+            .map(|x| wildcard(x))
+            .collect::<Result<Vec<_>>>()?;
+
+        // The top module is emitted with project prefix, but instances and signals are not
+        let prefix = if metadata.build.omit_project_prefix {
+            String::new()
+        } else {
+            metadata
+                .build
+                .project_prefix
+                .clone()
+                .unwrap_or_else(|| format!("{}_", metadata.project.name))
+        };
+
+        let graph = InstanceGraph::new();
+        let mut probes = Vec::new();
+        let mut stack = Vec::new();
+        collect(
+            &graph,
+            &top,
+            vec![format!("{}{}", prefix, top.token)],
+            &mut stack,
+            &mut probes,
+        );
+        probes.retain(|x| {
+            let path = x.join(".");
+            patterns.is_empty() || patterns.iter().any(|p| p.is_match(&path))
+        });
+        probes.sort();
+        probes.dedup();
+
+        if probes.is_empty() {
+            bail!("no signal matches in top module \"{}\"", self.opt.top);
+        }
+
+        let mut text = String::new();
+        for probe in &probes {
+            let line = match self.opt.format {
+                ProbesFormat::List => probe.join("."),
+                // Nets of Vivado are referred from the top module by `/`
+                ProbesFormat::Xdc => format!(
+                    "set_property MARK_DEBUG true [get_nets -hierarchical {{{}}}]",
+                    probe[1..].join("/")
+                ),
+                ProbesFormat::Force => format!("force {} = '0;", probe.join(".")),
+            };
+            text.push_str(&line);
+            text.push('\n');
+        }
+
+        if let Some(ref output) = self.opt.output {
+            fs::write(output, text).into_diagnostic()?;
+            info!("Output probes ({})", output.to_string_lossy());
+        } else {
+            print!("{text}");
+        }
+
+        Ok(true)
+    }
+}
+
+/// Collects hierarchical paths of variables and ports declared directly in `symbol`
+/// and in the instantiated modules and interfaces
+fn collect(
+    graph: &InstanceGraph,
+    symbol: &Symbol,
+    path: Vec<String>,
+    stack: &mut Vec<SymbolId>,
+    probes: &mut Vec<Vec<String>>,
+) {
+    // Recursive instantiation is reported by analyzer, so it is just cut off here
+    if stack.contains(&symbol.id) {
+        return;
+    }
+    stack.push(symbol.id);
+
+    let namespace = symbol.inner_namespace();
+    for x in symbol_table::get_all() {
+        if matches!(x.kind, SymbolKind::Variable(_) | SymbolKind::Port(_))
+            && x.namespace.matched(&namespace)
+        {
+            let mut path = path.clone();
+            path.push(x.token.to_string());
+            probes.push(path);
+        }
+    }
+
+    for instance in graph.get_children(symbol.id) {
+        if let Some(child) = symbol_table::get(instance.child) {
+            let mut path = path.clone();
+            path.push(instance.token.to_string());
+            collect(graph, &child, path, stack, probes);
+        }
+    }
+
+    stack.pop();
+}
+
+fn wildcard(pattern: &str) -> Result<Regex> {
+    let regex = regex::escape(pattern)
+        .replace(r"\*", ".*")
+        .replace(r"\?", ".");
+    Regex::new(&format!("^{regex}$")).into_diagnostic()
+}
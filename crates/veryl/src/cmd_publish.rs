@@ -29,7 +29,7 @@ impl CmdPublish {
             }
         }
 
-        let mut check_error = CheckError::default();
+        let mut check_error = CheckError::new(metadata)?;
         let mut contexts = Vec::new();
 
         for path in &paths {
@@ -40,7 +40,7 @@ impl CmdPublish {
                 .wrap_err("")?;
             let parser = Parser::parse(&input, &path.src)?;
 
-            let analyzer = Analyzer::new(metadata);
+            let analyzer = Analyzer::new(&metadata.for_file(&path.src));
             let mut errors = analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
             check_error = check_error.append(&mut errors).check_err()?;
 
@@ -59,7 +59,7 @@ impl CmdPublish {
             check_error = check_error.append(&mut errors).check_err()?;
         }
 
-        let _ = check_error.check_all()?;
+        let _ = check_error.check_all(false)?;
 
         if let Some(kind) = self.opt.bump {
             metadata.bump_version(kind.into()).into_diagnostic()?;
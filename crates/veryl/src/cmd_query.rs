@@ -0,0 +1,145 @@
+use crate::{OptQuery, QueryCommand};
+use log::info;
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use std::collections::BTreeMap;
+use std::fs;
+use veryl_analyzer::structural_hash::{module_hashes, StructuralHash};
+use veryl_analyzer::symbol::{Symbol, SymbolKind};
+use veryl_analyzer::{call_graph, symbol_table, Analyzer};
+use veryl_metadata::Metadata;
+use veryl_parser::veryl_token::Token;
+use veryl_parser::Parser;
+
+pub struct CmdQuery {
+    opt: OptQuery,
+}
+
+impl CmdQuery {
+    pub fn new(opt: OptQuery) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let paths = metadata.paths::<&str>(&[], true)?;
+
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+            let analyzer = Analyzer::new(metadata);
+            analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        for (path, input, parser, analyzer) in &contexts {
+            analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+        }
+
+        match &self.opt.command {
+            QueryCommand::Callers { name } => self.callers(name),
+            QueryCommand::Duplicates { min_size } => {
+                let modules: Vec<_> = contexts
+                    .iter()
+                    .flat_map(|(path, _, parser, _)| {
+                        module_hashes(&parser.veryl)
+                            .into_iter()
+                            .map(|(token, hash)| (path.prj.as_str(), token, hash))
+                    })
+                    .filter(|(_, _, hash)| hash.size >= *min_size)
+                    .collect();
+                self.duplicates(&modules)
+            }
+        }
+    }
+
+    fn duplicates(&self, modules: &[(&str, Token, StructuralHash)]) -> Result<bool> {
+        let mut exact: BTreeMap<u64, Vec<_>> = BTreeMap::new();
+        let mut near: BTreeMap<u64, Vec<_>> = BTreeMap::new();
+        for x in modules {
+            exact.entry(x.2.exact).or_default().push(x);
+            near.entry(x.2.near).or_default().push(x);
+        }
+
+        let print = |title: &str, group: &[&(&str, Token, StructuralHash)]| {
+            println!("{} ({} tokens)", title, group[0].2.size);
+            for (prj, token, _) in group {
+                println!(
+                    "    {}::{} ({}:{}:{})",
+                    prj, token, token.source, token.line, token.column
+                );
+            }
+        };
+
+        let mut found = false;
+        for group in exact.values().filter(|x| x.len() > 1) {
+            print("Exact duplicates", group);
+            found = true;
+        }
+        for group in near.values().filter(|x| x.len() > 1) {
+            // Groups of exact duplicates are already reported
+            if group.iter().all(|x| x.2.exact == group[0].2.exact) {
+                continue;
+            }
+            print(
+                "Near duplicates differing in constants, which can be parameterized",
+                group,
+            );
+            found = true;
+        }
+
+        if !found {
+            info!("No duplicated module");
+        }
+
+        Ok(true)
+    }
+
+    fn callers(&self, name: &str) -> Result<bool> {
+        let functions: Vec<_> = symbol_table::get_all()
+            .into_iter()
+            .filter(|x| matches!(x.kind, SymbolKind::Function(_)) && matches_path(x, name))
+            .collect();
+
+        if functions.is_empty() {
+            bail!("function \"{}\" is not found", name);
+        }
+
+        for function in &functions {
+            println!("{}::{}", function.namespace, function.token);
+            for call in call_graph::get_callers(function.id) {
+                let caller = symbol_table::get(call.caller).unwrap();
+                println!(
+                    "    {}:{}:{} ({} {})",
+                    call.token.source,
+                    call.token.line,
+                    call.token.column,
+                    caller.kind.to_kind_name(),
+                    caller.token,
+                );
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+fn matches_path(symbol: &Symbol, name: &str) -> bool {
+    let mut path: Vec<_> = symbol
+        .namespace
+        .paths
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+    path.push(symbol.token.to_string());
+
+    let name: Vec<_> = name.split("::").collect();
+    path.ends_with(&name.iter().map(|x| x.to_string()).collect::<Vec<_>>())
+}
@@ -0,0 +1,329 @@
+use crate::{OptRefactor, RefactorCommand};
+use log::info;
+use miette::{self, bail, Diagnostic, IntoDiagnostic, Result, WrapErr};
+use std::collections::HashMap;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use veryl_analyzer::{Analyzer, AnalyzerError};
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+use veryl_path::PathSet;
+use veryl_refactor::{Source, TextEdit};
+
+pub struct CmdRefactor {
+    opt: OptRefactor,
+}
+
+#[derive(Error, Diagnostic, Debug)]
+#[error("refactoring introduces errors, so no file is modified")]
+pub struct RefactorValidationError {
+    #[related]
+    pub related: Vec<AnalyzerError>,
+}
+
+struct Context {
+    path: PathSet,
+    input: String,
+    parser: Parser,
+}
+
+impl CmdRefactor {
+    pub fn new(opt: OptRefactor) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        match &self.opt.command {
+            RefactorCommand::ExtractModule { file, lines, name } => {
+                self.extract_module(metadata, file, lines, name)
+            }
+            RefactorCommand::InlineInstance { file, instance } => {
+                self.inline_instance(metadata, file, instance)
+            }
+            RefactorCommand::ChangeType { target, r#type } => {
+                self.change_type(metadata, target, r#type)
+            }
+            RefactorCommand::ApplyNaming { dry_run } => self.apply_naming(metadata, *dry_run),
+        }
+    }
+
+    fn extract_module(
+        &self,
+        metadata: &mut Metadata,
+        file: &Path,
+        lines: &RangeInclusive<u32>,
+        name: &str,
+    ) -> Result<bool> {
+        let sources = read_sources(metadata)?;
+        let (contexts, _) = analyze(metadata, sources)?;
+        let context = find_context(&contexts, file)?;
+
+        info!(
+            "Extracting module ({}:{}:{})",
+            context.path.src.to_string_lossy(),
+            lines.start(),
+            lines.end()
+        );
+
+        let edits = veryl_refactor::extract_module(
+            &context.parser.veryl,
+            &context.input,
+            lines.clone(),
+            name,
+            metadata.format.indent_width,
+        )?;
+        let output = veryl_refactor::apply_edits(&context.input, &edits);
+
+        fs::write(&context.path.src, output).into_diagnostic()?;
+
+        Ok(true)
+    }
+
+    fn inline_instance(
+        &self,
+        metadata: &mut Metadata,
+        file: &Path,
+        instance: &str,
+    ) -> Result<bool> {
+        let sources = read_sources(metadata)?;
+        let (contexts, errors) = analyze(metadata, sources)?;
+        let context = find_context(&contexts, file)?;
+
+        info!(
+            "Inlining instance ({}:{})",
+            context.path.src.to_string_lossy(),
+            instance
+        );
+
+        let sources = to_sources(&contexts);
+        let edits = veryl_refactor::inline_instance(
+            &sources,
+            &context.path.src,
+            instance,
+            metadata.format.indent_width,
+        )?;
+        let modified = apply_and_validate(metadata, &contexts, &errors, edits)?;
+
+        for (path, output) in modified {
+            info!("Writing file ({})", path.to_string_lossy());
+            fs::write(&path, output).into_diagnostic()?;
+        }
+
+        Ok(true)
+    }
+
+    fn change_type(&self, metadata: &mut Metadata, target: &str, r#type: &str) -> Result<bool> {
+        let sources = read_sources(metadata)?;
+        let (contexts, _) = analyze(metadata, sources)?;
+
+        info!("Changing type ({} -> {})", target, r#type);
+
+        let change = veryl_refactor::change_type(&to_sources(&contexts), target, r#type)?;
+
+        for x in &change.changes {
+            println!(
+                "changed: {}:{}:{}: {}",
+                x.path.to_string_lossy(),
+                x.line,
+                x.column,
+                x.message
+            );
+        }
+        for x in &change.reviews {
+            println!(
+                "review : {}:{}:{}: {}",
+                x.path.to_string_lossy(),
+                x.line,
+                x.column,
+                x.message
+            );
+        }
+
+        for (path, edits) in &change.edits {
+            let Some(context) = contexts.iter().find(|x| &x.path.src == path) else {
+                continue;
+            };
+            let output = veryl_refactor::apply_edits(&context.input, edits);
+            info!("Writing file ({})", path.to_string_lossy());
+            fs::write(path, output).into_diagnostic()?;
+        }
+
+        Ok(true)
+    }
+
+    fn apply_naming(&self, metadata: &mut Metadata, dry_run: bool) -> Result<bool> {
+        let sources = read_sources(metadata)?;
+        let (contexts, errors) = analyze(metadata, sources)?;
+
+        info!("Applying naming rules");
+
+        // Sources of dependencies are not modified
+        let sources: Vec<_> = to_sources(&contexts)
+            .into_iter()
+            .zip(&contexts)
+            .filter(|(_, x)| x.path.prj == metadata.project.name)
+            .map(|(x, _)| x)
+            .collect();
+        let change = veryl_refactor::apply_naming(&sources, &errors);
+
+        for x in &change.renames {
+            println!(
+                "{} -> {} ({}:{}:{})",
+                x.from,
+                x.to,
+                x.path.to_string_lossy(),
+                x.line,
+                x.column
+            );
+        }
+        for x in &change.skipped {
+            println!(
+                "skipped: {}:{}:{}: {}",
+                x.path.to_string_lossy(),
+                x.line,
+                x.column,
+                x.message
+            );
+        }
+
+        if dry_run {
+            return Ok(true);
+        }
+
+        let modified = apply_and_validate(metadata, &contexts, &errors, change.edits)?;
+
+        for (path, output) in modified {
+            info!("Writing file ({})", path.to_string_lossy());
+            fs::write(&path, output).into_diagnostic()?;
+        }
+
+        Ok(true)
+    }
+}
+
+/// Applies `edits` and checks that no new error is introduced by comparing with `errors`
+fn apply_and_validate(
+    metadata: &Metadata,
+    contexts: &[Context],
+    errors: &[AnalyzerError],
+    edits: Vec<(PathBuf, Vec<TextEdit>)>,
+) -> Result<Vec<(PathBuf, String)>> {
+    let edits: HashMap<_, _> = edits.into_iter().collect();
+
+    let mut modified = Vec::new();
+    let mut outputs = Vec::new();
+    for x in contexts {
+        match edits.get(&x.path.src) {
+            Some(edits) => {
+                let output = veryl_refactor::apply_edits(&x.input, edits);
+                modified.push((x.path.src.clone(), output.clone()));
+                outputs.push((x.path.clone(), output));
+            }
+            None => outputs.push((x.path.clone(), x.input.clone())),
+        }
+    }
+
+    // The result is validated by comparing errors before and after the refactoring
+    info!("Validating the result");
+    let (_, new_errors) = analyze(metadata, outputs)?;
+    let mut known: HashMap<_, usize> = HashMap::new();
+    for x in errors {
+        *known.entry(x.to_string()).or_default() += 1;
+    }
+    let mut related = Vec::new();
+    for x in new_errors {
+        match known.get_mut(&x.to_string()) {
+            Some(n) if *n > 0 => *n -= 1,
+            _ => related.push(x),
+        }
+    }
+    if !related.is_empty() {
+        return Err(RefactorValidationError { related }.into());
+    }
+
+    Ok(modified)
+}
+
+fn to_sources(contexts: &[Context]) -> Vec<Source<'_>> {
+    contexts
+        .iter()
+        .map(|x| Source {
+            path: &x.path.src,
+            input: &x.input,
+            veryl: &x.parser.veryl,
+        })
+        .collect()
+}
+
+fn read_sources(metadata: &mut Metadata) -> Result<Vec<(PathSet, String)>> {
+    let mut ret = Vec::new();
+    for path in metadata.paths::<&str>(&[], false)? {
+        let input = fs::read_to_string(&path.src)
+            .into_diagnostic()
+            .wrap_err("")?;
+        ret.push((path, input));
+    }
+    Ok(ret)
+}
+
+fn analyze(
+    metadata: &Metadata,
+    sources: Vec<(PathSet, String)>,
+) -> Result<(Vec<Context>, Vec<AnalyzerError>)> {
+    Analyzer::new(metadata).clear();
+
+    let mut contexts = Vec::new();
+    let mut analyzers = Vec::new();
+    let mut errors = Vec::new();
+
+    for (path, input) in sources {
+        let parser = Parser::parse(&input, &path.src)?;
+        let analyzer = Analyzer::new(metadata);
+        errors.append(&mut analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl));
+
+        contexts.push(Context {
+            path,
+            input,
+            parser,
+        });
+        analyzers.push(analyzer);
+    }
+
+    Analyzer::analyze_post_pass1();
+
+    for (x, analyzer) in contexts.iter().zip(&analyzers) {
+        errors.append(&mut analyzer.analyze_pass2(
+            &x.path.prj,
+            &x.input,
+            &x.path.src,
+            &x.parser.veryl,
+        ));
+    }
+
+    for (x, analyzer) in contexts.iter().zip(&analyzers) {
+        errors.append(&mut analyzer.analyze_pass3(
+            &x.path.prj,
+            &x.input,
+            &x.path.src,
+            &x.parser.veryl,
+        ));
+    }
+
+    Ok((contexts, errors))
+}
+
+fn find_context<'a>(contexts: &'a [Context], file: &Path) -> Result<&'a Context> {
+    let target: PathBuf = fs::canonicalize(file)
+        .into_diagnostic()
+        .wrap_err(format!("{} is not found", file.to_string_lossy()))?;
+
+    let Some(context) = contexts
+        .iter()
+        .find(|x| fs::canonicalize(&x.path.src).ok().as_ref() == Some(&target))
+    else {
+        bail!("{} is not a source of the project", file.to_string_lossy());
+    };
+    Ok(context)
+}
@@ -0,0 +1,335 @@
+use crate::OptRepl;
+use log::info;
+use miette::{IntoDiagnostic, Result, WrapErr};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use veryl_analyzer::evaluator::{Evaluated, Evaluator};
+use veryl_analyzer::namespace::Namespace;
+use veryl_analyzer::symbol::{Symbol, SymbolKind, Type};
+use veryl_analyzer::{namespace_table, symbol_table, Analyzer};
+use veryl_metadata::Metadata;
+use veryl_parser::resource_table;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, VerylToken};
+use veryl_parser::veryl_walker::VerylWalker;
+use veryl_parser::Parser;
+
+pub struct CmdRepl {
+    opt: OptRepl,
+}
+
+const HELP: &str = r#"Commands:
+    <expression>         evaluate a constant expression
+    :type <expression>   show the type and width of the expression
+    :resolve <path>      show the symbol which the path refers to
+    :scope [<path>]      change the scope to the module, interface or package
+                         (the project root if omitted)
+    :help                show this help
+    :quit                exit"#;
+
+/// Dummy file name of the input lines
+const REPL_PATH: &str = "<repl>";
+
+impl CmdRepl {
+    pub fn new(opt: OptRepl) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let stdin = io::stdin();
+        self.run(metadata, &mut stdin.lock(), &mut io::stdout())
+    }
+
+    /// Analyzes the project, and evaluates lines from `input` until EOF or `:quit`
+    pub(crate) fn run(
+        &self,
+        metadata: &mut Metadata,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<bool> {
+        let paths = metadata.paths::<&str>(&[], true)?;
+
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+            let analyzer = Analyzer::new(metadata);
+            analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        for (path, input, parser, analyzer) in &contexts {
+            analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+        }
+
+        let mut repl = Repl::new(&metadata.project.name);
+        if let Some(ref scope) = self.opt.scope {
+            let ret = repl.scope(scope);
+            if !ret.is_empty() {
+                writeln!(output, "{ret}").into_diagnostic()?;
+            }
+        }
+
+        let mut lines = input.lines();
+        loop {
+            write!(output, "{}> ", repl.prompt()).into_diagnostic()?;
+            output.flush().into_diagnostic()?;
+
+            let Some(line) = lines.next() else {
+                writeln!(output).into_diagnostic()?;
+                break;
+            };
+            let line = line.into_diagnostic()?;
+            match repl.line(&line) {
+                Some(x) if x.is_empty() => (),
+                Some(x) => writeln!(output, "{x}").into_diagnostic()?,
+                None => break,
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+struct Repl {
+    root: Namespace,
+    scope: Namespace,
+}
+
+impl Repl {
+    fn new(project: &str) -> Self {
+        let mut root = Namespace::new();
+        root.push(resource_table::insert_str(project));
+        Self {
+            scope: root.clone(),
+            root,
+        }
+    }
+
+    fn prompt(&self) -> String {
+        self.scope.to_string()
+    }
+
+    /// Result of the input line, or `None` if the session is finished
+    fn line(&mut self, line: &str) -> Option<String> {
+        let line = line.trim();
+        let (command, arg) = match line.split_once(char::is_whitespace) {
+            Some((command, arg)) if command.starts_with(':') => (command, arg.trim()),
+            _ if line.starts_with(':') => (line, ""),
+            _ => ("", line),
+        };
+
+        let ret = match command {
+            "" if arg.is_empty() => String::new(),
+            "" => self.evaluate(arg),
+            ":type" | ":t" => self.r#type(arg),
+            ":resolve" | ":r" => self.resolve(arg),
+            ":scope" | ":s" => self.scope(arg),
+            ":help" | ":h" => HELP.to_string(),
+            ":quit" | ":q" => return None,
+            _ => format!("unknown command `{command}` (see `:help`)"),
+        };
+        Some(ret)
+    }
+
+    fn evaluate(&self, arg: &str) -> String {
+        match self.parse(arg) {
+            Ok(x) => evaluated_text(Evaluator::new().expression(&x)),
+            Err(x) => x,
+        }
+    }
+
+    fn r#type(&self, arg: &str) -> String {
+        let expression = match self.parse(arg) {
+            Ok(x) => x,
+            Err(x) => return x,
+        };
+
+        if let Some(symbol) = identifier(&expression).and_then(|x| symbol_table::resolve(x).ok()) {
+            if let Some(r#type) = type_of(&symbol.found) {
+                return match Evaluator::new().type_width(r#type.clone()) {
+                    Some(width) => format!("{type} (width: {width})"),
+                    None => format!("{type}"),
+                };
+            }
+        }
+
+        match Evaluator::new().expression(&expression) {
+            Evaluated::Fixed { width, .. } | Evaluated::Variable { width } => {
+                format!("width: {width}")
+            }
+            x => evaluated_text(x),
+        }
+    }
+
+    fn resolve(&self, arg: &str) -> String {
+        match self.symbol(arg) {
+            Ok(x) => {
+                let mut ret = format!(
+                    "{} {}::{} ({}:{}:{})",
+                    x.kind.to_kind_name(),
+                    x.namespace,
+                    x.token,
+                    x.token.source,
+                    x.token.line,
+                    x.token.column
+                );
+                if let Some(r#type) = type_of(&x) {
+                    ret.push_str(&format!("\n    type: {type}"));
+                }
+                ret
+            }
+            Err(x) => x,
+        }
+    }
+
+    fn scope(&mut self, arg: &str) -> String {
+        if arg.is_empty() {
+            self.scope = self.root.clone();
+            return String::new();
+        }
+
+        match self.symbol(arg) {
+            Ok(x) => match x.kind {
+                SymbolKind::Module(_) | SymbolKind::Interface(_) | SymbolKind::Package(_) => {
+                    self.scope = x.inner_namespace();
+                    String::new()
+                }
+                _ => format!("{} `{}` can't be a scope", x.kind.to_kind_name(), x.token),
+            },
+            Err(x) => x,
+        }
+    }
+
+    fn symbol(&self, arg: &str) -> Result<Symbol, String> {
+        let expression = self.parse(arg)?;
+        let Some(identifier) = identifier(&expression) else {
+            return Err(format!("`{arg}` is not an identifier"));
+        };
+        symbol_table::resolve(identifier)
+            .map(|x| x.found)
+            .map_err(|_| format!("`{arg}` is not found in `{}`", self.scope))
+    }
+
+    /// Parses `arg` as an expression in the current scope
+    fn parse(&self, arg: &str) -> Result<Expression, String> {
+        let input = format!("package ReplWrapper {{ const X: u32 = {arg}; }}");
+        let Ok(parser) = Parser::parse(&input, &REPL_PATH) else {
+            return Err(format!("`{arg}` is not an expression"));
+        };
+
+        let mut finder = ExpressionFinder::default();
+        finder.veryl(&parser.veryl);
+        let Some(expression) = finder.expression else {
+            return Err(format!("`{arg}` is not an expression"));
+        };
+
+        // Identifiers in the expression are resolved in the current scope
+        let path = resource_table::insert_path(Path::new(REPL_PATH));
+        let mut tokens = TokenCollector::default();
+        tokens.expression(&expression);
+        for x in &tokens.tokens {
+            namespace_table::insert(x.id, path, &self.scope);
+        }
+
+        Ok(expression)
+    }
+}
+
+fn evaluated_text(x: Evaluated) -> String {
+    match x {
+        Evaluated::Fixed { width, value } => format!("{value} (width: {width})"),
+        Evaluated::Variable { width } => format!("not constant (width: {width})"),
+        Evaluated::Clock | Evaluated::ClockPosedge | Evaluated::ClockNegedge => "clock".to_string(),
+        Evaluated::Reset
+        | Evaluated::ResetAsyncHigh
+        | Evaluated::ResetAsyncLow
+        | Evaluated::ResetSyncHigh
+        | Evaluated::ResetSyncLow => "reset".to_string(),
+        Evaluated::Unknown | Evaluated::UnknownStatic => "unknown".to_string(),
+    }
+}
+
+fn type_of(symbol: &Symbol) -> Option<Type> {
+    match &symbol.kind {
+        SymbolKind::Port(x) => x.r#type.clone(),
+        SymbolKind::Variable(x) => Some(x.r#type.clone()),
+        SymbolKind::Parameter(x) => Some(x.r#type.clone()),
+        SymbolKind::StructMember(x) => Some(x.r#type.clone()),
+        SymbolKind::UnionMember(x) => Some(x.r#type.clone()),
+        SymbolKind::TypeDef(x) => Some(x.r#type.clone()),
+        _ => None,
+    }
+}
+
+/// The identifier if the expression consists of only it
+fn identifier(arg: &Expression) -> Option<&ExpressionIdentifier> {
+    let mut tokens = TokenCollector::default();
+    tokens.expression(arg);
+
+    let mut finder = IdentifierFinder::default();
+    finder.expression(arg);
+    let identifier = finder.identifier?;
+
+    let mut identifier_tokens = TokenCollector::default();
+    identifier_tokens.expression_identifier(identifier);
+    (tokens.tokens.len() == identifier_tokens.tokens.len()).then_some(identifier)
+}
+
+#[derive(Default)]
+struct ExpressionFinder {
+    expression: Option<Expression>,
+}
+
+impl VerylWalker for ExpressionFinder {
+    fn const_declaration(&mut self, arg: &ConstDeclaration) {
+        self.expression = Some(arg.expression.as_ref().clone());
+    }
+}
+
+#[derive(Default)]
+struct IdentifierFinder<'a> {
+    identifier: Option<&'a ExpressionIdentifier>,
+}
+
+impl<'a> IdentifierFinder<'a> {
+    fn expression(&mut self, arg: &'a Expression) {
+        let factor = &arg
+            .expression01
+            .expression02
+            .expression03
+            .expression04
+            .expression05
+            .expression06
+            .expression07
+            .expression08
+            .expression09
+            .expression10
+            .expression11
+            .expression12
+            .factor;
+        if let Factor::ExpressionIdentifierFactorOpt(x) = factor.as_ref() {
+            self.identifier = Some(x.expression_identifier.as_ref());
+        }
+    }
+}
+
+#[derive(Default)]
+struct TokenCollector {
+    tokens: Vec<Token>,
+}
+
+impl VerylWalker for TokenCollector {
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        self.tokens.push(arg.token);
+    }
+}
@@ -0,0 +1,345 @@
+use crate::cmd_check::CheckError;
+use crate::{OptReport, ReportFormat};
+use handlebars::Handlebars;
+use log::info;
+use miette::{Diagnostic, IntoDiagnostic, Result, Severity, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use veryl_analyzer::Analyzer;
+use veryl_formatter::Formatter;
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+
+pub struct CmdReport {
+    opt: OptReport,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Report {
+    project: String,
+    veryl_version: String,
+    errors: usize,
+    warnings: usize,
+    advices: usize,
+    /// Number of files which don't follow the formatter
+    unformatted: usize,
+    /// Number of diagnostics for each check
+    checks: BTreeMap<String, usize>,
+    files: Vec<FileReport>,
+    diagnostics: Vec<DiagnosticReport>,
+    /// Difference from the previous report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trend: Option<Trend>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct FileReport {
+    path: String,
+    errors: usize,
+    warnings: usize,
+    advices: usize,
+    formatted: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiagnosticReport {
+    code: String,
+    severity: String,
+    path: String,
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Trend {
+    errors: i64,
+    warnings: i64,
+    advices: i64,
+    unformatted: i64,
+    checks: BTreeMap<String, i64>,
+}
+
+impl Report {
+    fn add(&mut self, diagnostic: DiagnosticReport) {
+        let file = self.files.iter_mut().find(|x| x.path == diagnostic.path);
+        let (total, count) = match diagnostic.severity.as_str() {
+            "error" => (&mut self.errors, file.map(|x| &mut x.errors)),
+            "warning" => (&mut self.warnings, file.map(|x| &mut x.warnings)),
+            _ => (&mut self.advices, file.map(|x| &mut x.advices)),
+        };
+        *total += 1;
+        if let Some(count) = count {
+            *count += 1;
+        }
+        *self.checks.entry(diagnostic.code.clone()).or_default() += 1;
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn trend(&self, previous: &Report) -> Trend {
+        let diff = |x: usize, y: usize| x as i64 - y as i64;
+        let mut checks = BTreeMap::new();
+        for code in self.checks.keys().chain(previous.checks.keys()) {
+            let x = self.checks.get(code).copied().unwrap_or(0);
+            let y = previous.checks.get(code).copied().unwrap_or(0);
+            if x != y {
+                checks.insert(code.clone(), diff(x, y));
+            }
+        }
+        Trend {
+            errors: diff(self.errors, previous.errors),
+            warnings: diff(self.warnings, previous.warnings),
+            advices: diff(self.advices, previous.advices),
+            unformatted: diff(self.unformatted, previous.unformatted),
+            checks,
+        }
+    }
+}
+
+fn severity(x: Severity) -> String {
+    match x {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Advice => "advice",
+    }
+    .to_string()
+}
+
+impl CmdReport {
+    pub fn new(opt: OptReport) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let paths = metadata.paths(&self.opt.files, true)?;
+        let base_path = metadata.project_path();
+
+        let mut report = Report {
+            project: metadata.project.name.clone(),
+            veryl_version: env!("CARGO_PKG_VERSION").to_string(),
+            ..Default::default()
+        };
+
+        // All diagnostics including ones suppressed by baseline are reported
+        let mut check_error = CheckError::new(metadata)?;
+        check_error.set_baseline(None);
+
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let relative = path.src.strip_prefix(&base_path).unwrap_or(&path.src);
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let is_project = path.prj == metadata.project.name;
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = match Parser::parse(&input, &path.src) {
+                Ok(x) => x,
+                Err(x) => {
+                    // Syntax error is reported and the file is excluded from analysis
+                    if is_project {
+                        report.files.push(FileReport {
+                            path: relative.clone(),
+                            ..Default::default()
+                        });
+                        report.unformatted += 1;
+                    }
+                    report.add(DiagnosticReport {
+                        code: x.code().map(|x| x.to_string()).unwrap_or_default(),
+                        severity: severity(Severity::Error),
+                        path: relative,
+                        message: x.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            if is_project {
+                let mut formatter = Formatter::new(&metadata.for_file(&path.src));
+                formatter.format(&parser.veryl);
+                let formatted = input.as_str() == formatter.as_str();
+                if !formatted {
+                    report.unformatted += 1;
+                }
+                report.files.push(FileReport {
+                    path: relative,
+                    formatted,
+                    ..Default::default()
+                });
+            }
+
+            let analyzer = Analyzer::new(&metadata.for_file(&path.src));
+            let mut errors = analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
+            check_error = check_error.append(&mut errors);
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        for (path, input, parser, analyzer) in &contexts {
+            let mut errors = analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
+            check_error = check_error.append(&mut errors);
+        }
+
+        for (path, input, parser, analyzer) in &contexts {
+            let mut errors = analyzer.analyze_pass3(&path.prj, input, &path.src, &parser.veryl);
+            metadata.cancellation.check()?;
+            check_error = check_error.append(&mut errors);
+        }
+
+        for x in &check_error.related {
+            let entry = check_error.baseline_entry(x);
+            report.add(DiagnosticReport {
+                code: entry.code,
+                severity: severity(x.severity),
+                path: entry.path,
+                message: entry.message,
+            });
+        }
+
+        if let Some(ref path) = self.opt.previous {
+            let text = fs::read_to_string(path)
+                .into_diagnostic()
+                .wrap_err(format!("failed to read {}", path.to_string_lossy()))?;
+            let previous: Report = serde_json::from_str(&text)
+                .into_diagnostic()
+                .wrap_err(format!("failed to parse {}", path.to_string_lossy()))?;
+            report.trend = Some(report.trend(&previous));
+        }
+
+        let text = match self.opt.format {
+            ReportFormat::Json => serde_json::to_string_pretty(&report).into_diagnostic()?,
+            ReportFormat::Html => render_html(&report)?,
+        };
+
+        if let Some(ref output) = self.opt.output {
+            fs::write(output, text).into_diagnostic()?;
+            info!("Output report ({})", output.to_string_lossy());
+        } else {
+            println!("{text}");
+        }
+
+        Ok(true)
+    }
+}
+
+const HTML_TMPL: &str = r###"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{{project}} - Veryl Report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }
+th { background: #eee; }
+.error { color: #c00; }
+.warning { color: #b60; }
+.advice { color: #06c; }
+</style>
+</head>
+<body>
+<h1>{{project}}</h1>
+<p>Generated by Veryl {{veryl_version}}</p>
+
+<h2>Summary</h2>
+<table>
+<tr><th></th><th>Count</th>{{#if has_trend}}<th>Trend</th>{{/if}}</tr>
+{{#each summary}}
+<tr><td>{{this.0}}</td><td>{{this.1}}</td>{{#if ../has_trend}}<td>{{this.2}}</td>{{/if}}</tr>
+{{/each}}
+</table>
+
+<h2>Checks</h2>
+<table>
+<tr><th>Check</th><th>Count</th>{{#if has_trend}}<th>Trend</th>{{/if}}</tr>
+{{#each checks}}
+<tr><td>{{this.0}}</td><td>{{this.1}}</td>{{#if ../has_trend}}<td>{{this.2}}</td>{{/if}}</tr>
+{{/each}}
+</table>
+
+<h2>Files</h2>
+<table>
+<tr><th>File</th><th>Errors</th><th>Warnings</th><th>Advices</th><th>Formatted</th></tr>
+{{#each files}}
+<tr><td>{{this.path}}</td><td>{{this.errors}}</td><td>{{this.warnings}}</td><td>{{this.advices}}</td><td>{{#if this.formatted}}yes{{else}}no{{/if}}</td></tr>
+{{/each}}
+</table>
+
+<h2>Diagnostics</h2>
+<table>
+<tr><th>Severity</th><th>Check</th><th>File</th><th>Message</th></tr>
+{{#each diagnostics}}
+<tr><td class="{{this.severity}}">{{this.severity}}</td><td>{{this.code}}</td><td>{{this.path}}</td><td>{{this.message}}</td></tr>
+{{/each}}
+</table>
+</body>
+</html>
+"###;
+
+#[derive(Serialize)]
+struct HtmlData<'a> {
+    project: &'a str,
+    veryl_version: &'a str,
+    has_trend: bool,
+    summary: Vec<(&'a str, usize, String)>,
+    checks: Vec<(&'a str, usize, String)>,
+    files: &'a [FileReport],
+    diagnostics: &'a [DiagnosticReport],
+}
+
+fn render_html(report: &Report) -> Result<String> {
+    let trend = |x: Option<i64>| match x {
+        Some(x) if x > 0 => format!("+{x}"),
+        Some(x) => format!("{x}"),
+        None => String::new(),
+    };
+    let t = report.trend.as_ref();
+
+    let summary = vec![
+        ("Errors", report.errors, trend(t.map(|x| x.errors))),
+        ("Warnings", report.warnings, trend(t.map(|x| x.warnings))),
+        ("Advices", report.advices, trend(t.map(|x| x.advices))),
+        (
+            "Unformatted files",
+            report.unformatted,
+            trend(t.map(|x| x.unformatted)),
+        ),
+    ];
+
+    let mut checks: Vec<_> = report
+        .checks
+        .iter()
+        .map(|(code, count)| {
+            let diff = t.map(|x| x.checks.get(code).copied().unwrap_or(0));
+            (code.as_str(), *count, trend(diff))
+        })
+        .collect();
+    // Checks which disappeared since the previous report
+    if let Some(t) = t {
+        for (code, diff) in &t.checks {
+            if !report.checks.contains_key(code) {
+                checks.push((code.as_str(), 0, trend(Some(*diff))));
+            }
+        }
+    }
+
+    let data = HtmlData {
+        project: &report.project,
+        veryl_version: &report.veryl_version,
+        has_trend: t.is_some(),
+        summary,
+        checks,
+        files: &report.files,
+        diagnostics: &report.diagnostics,
+    };
+
+    Handlebars::new()
+        .render_template(HTML_TMPL, &data)
+        .into_diagnostic()
+}
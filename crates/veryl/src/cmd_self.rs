@@ -0,0 +1,49 @@
+use crate::{OptSelf, SelfCommand};
+use log::info;
+use miette::{bail, Result};
+use std::process::Command;
+use veryl_metadata::semver::VersionReq;
+use veryl_metadata::Metadata;
+
+pub struct CmdSelf {
+    opt: OptSelf,
+}
+
+impl CmdSelf {
+    pub fn new(opt: OptSelf) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &Metadata) -> Result<bool> {
+        match &self.opt.command {
+            SelfCommand::Update { requirement } => {
+                let version = requirement
+                    .as_ref()
+                    .or(metadata.project.veryl_version.as_ref());
+                self.update(version)
+            }
+        }
+    }
+
+    fn update(&self, version: Option<&VersionReq>) -> Result<bool> {
+        let mut args = vec![
+            "install".to_string(),
+            "veryl".to_string(),
+            "--locked".to_string(),
+        ];
+        if let Some(version) = version {
+            info!("Updating veryl ({version})");
+            args.push("--version".to_string());
+            args.push(version.to_string());
+        } else {
+            info!("Updating veryl (latest)");
+        }
+
+        let status = Command::new("cargo").args(&args).status();
+        match status {
+            Ok(x) if x.success() => Ok(true),
+            Ok(_) => bail!("cargo install failed"),
+            Err(x) => bail!("cargo is not found: {x}"),
+        }
+    }
+}
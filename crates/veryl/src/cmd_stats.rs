@@ -0,0 +1,372 @@
+use crate::{OptStats, StatsFormat};
+use log::info;
+use miette::{IntoDiagnostic, Result, Severity, WrapErr};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::time::Instant;
+use veryl_analyzer::instance_graph::InstanceGraph;
+use veryl_analyzer::symbol::SymbolKind;
+use veryl_analyzer::{symbol_table, Analyzer, AnalyzerError};
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+
+pub struct CmdStats {
+    opt: OptStats,
+}
+
+#[derive(Default, Serialize)]
+pub struct Stats {
+    files: Vec<FileStats>,
+    lines: usize,
+    code_lines: usize,
+    modules: usize,
+    interfaces: usize,
+    packages: usize,
+    errors: usize,
+    warnings: usize,
+    /// Elapsed time of parse and analysis of all files in milliseconds
+    analyze_time: f64,
+    average_ports: f64,
+    /// Module names from the top of the deepest hierarchy
+    deepest_hierarchy: Vec<String>,
+}
+
+#[derive(Default, Serialize)]
+struct FileStats {
+    path: String,
+    lines: usize,
+    /// Lines except blank and comment-only lines
+    code_lines: usize,
+    modules: usize,
+    interfaces: usize,
+    packages: usize,
+    /// Diagnostics with the severity configured by `[lint.severity]`
+    errors: usize,
+    warnings: usize,
+    /// Elapsed time of parse and analysis in milliseconds
+    analyze_time: f64,
+}
+
+impl FileStats {
+    fn count(&mut self, metadata: &Metadata, errors: Vec<AnalyzerError>) {
+        for x in errors {
+            match x.with_lint(&metadata.lint).severity {
+                Severity::Error => self.errors += 1,
+                Severity::Warning => self.warnings += 1,
+                Severity::Advice => (),
+            }
+        }
+    }
+}
+
+impl CmdStats {
+    pub fn new(opt: OptStats) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let stats = self.stats(metadata)?;
+
+        match self.opt.format {
+            StatsFormat::Json => {
+                let text = serde_json::to_string_pretty(&stats).into_diagnostic()?;
+                println!("{text}");
+            }
+            StatsFormat::OpenMetrics => print!("{}", openmetrics(&metadata.project.name, &stats)),
+            StatsFormat::Pretty => print_table(&stats),
+        }
+
+        Ok(true)
+    }
+
+    pub fn stats(&self, metadata: &mut Metadata) -> Result<Stats> {
+        let paths = metadata.paths(&self.opt.files, true)?;
+        let base_path = metadata.project_path();
+
+        let mut contexts = Vec::new();
+        let mut stats = Stats::default();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let now = Instant::now();
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+            let analyzer = Analyzer::new(metadata);
+            let errors = analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+            let elapsed = now.elapsed();
+
+            if path.prj == metadata.project.name {
+                let relative = path.src.strip_prefix(&base_path).unwrap_or(&path.src);
+                let mut file = FileStats {
+                    path: relative.to_string_lossy().into_owned(),
+                    lines: input.lines().count(),
+                    code_lines: input
+                        .lines()
+                        .map(|x| x.trim())
+                        .filter(|x| !x.is_empty() && !x.starts_with("//"))
+                        .count(),
+                    ..Default::default()
+                };
+                file.analyze_time = elapsed.as_secs_f64() * 1000.0;
+                file.count(metadata, errors);
+                stats.files.push(file);
+            }
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        for (path, input, parser, analyzer) in &contexts {
+            let now = Instant::now();
+            let mut errors = analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+            errors.append(&mut analyzer.analyze_pass3(&path.prj, input, &path.src, &parser.veryl));
+            let elapsed = now.elapsed();
+
+            let relative = path.src.strip_prefix(&base_path).unwrap_or(&path.src);
+            let relative = relative.to_string_lossy();
+            if let Some(file) = stats.files.iter_mut().find(|x| x.path == relative) {
+                file.analyze_time += elapsed.as_secs_f64() * 1000.0;
+                file.count(metadata, errors);
+            }
+        }
+
+        self.collect(metadata, &mut stats);
+
+        Ok(stats)
+    }
+
+    fn collect(&self, metadata: &Metadata, stats: &mut Stats) {
+        let base_path = metadata.project_path();
+
+        let mut ports = 0;
+        let mut children: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for symbol in symbol_table::get_all() {
+            if symbol.namespace.paths.first().map(|x| x.to_string())
+                != Some(metadata.project.name.clone())
+            {
+                continue;
+            }
+
+            let source = symbol.token.source.to_string();
+            let relative = source
+                .strip_prefix(&*base_path.to_string_lossy())
+                .map(|x| x.trim_start_matches(['/', '\\']))
+                .unwrap_or(&source);
+            let file = stats.files.iter_mut().find(|x| x.path == relative);
+
+            match &symbol.kind {
+                SymbolKind::Module(x) => {
+                    children.entry(symbol.token.to_string()).or_default();
+                    // Ports are averaged over the modules of the target files only
+                    if let Some(file) = file {
+                        ports += x.ports.len();
+                        file.modules += 1;
+                    }
+                }
+                SymbolKind::Interface(_) => {
+                    if let Some(file) = file {
+                        file.interfaces += 1;
+                    }
+                }
+                SymbolKind::Package(_) => {
+                    if let Some(file) = file {
+                        file.packages += 1;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        for instance in InstanceGraph::new().instances() {
+            let parent = symbol_table::get(instance.parent).map(|x| x.token.to_string());
+            let child = symbol_table::get(instance.child).map(|x| x.token.to_string());
+            if let (Some(parent), Some(child)) = (parent, child) {
+                if let Some(x) = children.get_mut(&parent) {
+                    x.insert(child);
+                }
+            }
+        }
+
+        for file in &stats.files {
+            stats.lines += file.lines;
+            stats.code_lines += file.code_lines;
+            stats.modules += file.modules;
+            stats.interfaces += file.interfaces;
+            stats.packages += file.packages;
+            stats.errors += file.errors;
+            stats.warnings += file.warnings;
+            stats.analyze_time += file.analyze_time;
+        }
+        if stats.modules != 0 {
+            stats.average_ports = ports as f64 / stats.modules as f64;
+        }
+
+        let mut memo = HashMap::new();
+        for name in children.keys() {
+            let hierarchy = deepest_hierarchy(name, &children, &mut Vec::new(), &mut memo);
+            if hierarchy.len() > stats.deepest_hierarchy.len() {
+                stats.deepest_hierarchy = hierarchy;
+            }
+        }
+    }
+}
+
+/// Returns the deepest hierarchy from `name`.
+/// The result of each module is memoized because a module may be instantiated from many parents.
+fn deepest_hierarchy(
+    name: &str,
+    children: &BTreeMap<String, BTreeSet<String>>,
+    stack: &mut Vec<String>,
+    memo: &mut HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    if let Some(x) = memo.get(name) {
+        return x.clone();
+    }
+
+    // Instances of modules outside of the project are not traversed
+    if stack.iter().any(|x| x == name) || !children.contains_key(name) {
+        return Vec::new();
+    }
+
+    stack.push(name.to_string());
+    let mut ret = Vec::new();
+    for child in &children[name] {
+        let hierarchy = deepest_hierarchy(child, children, stack, memo);
+        if hierarchy.len() > ret.len() {
+            ret = hierarchy;
+        }
+    }
+    stack.pop();
+
+    ret.insert(0, name.to_string());
+    memo.insert(name.to_string(), ret.clone());
+    ret
+}
+
+fn print_table(stats: &Stats) {
+    let width = stats
+        .files
+        .iter()
+        .map(|x| x.path.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+
+    println!(
+        "{:<width$} {:>8} {:>8} {:>8} {:>10} {:>8} {:>10}",
+        "File", "Lines", "Code", "Modules", "Interfaces", "Packages", "Time[ms]"
+    );
+    for file in &stats.files {
+        println!(
+            "{:<width$} {:>8} {:>8} {:>8} {:>10} {:>8} {:>10.3}",
+            file.path,
+            file.lines,
+            file.code_lines,
+            file.modules,
+            file.interfaces,
+            file.packages,
+            file.analyze_time
+        );
+    }
+    println!(
+        "{:<width$} {:>8} {:>8} {:>8} {:>10} {:>8}",
+        "Total", stats.lines, stats.code_lines, stats.modules, stats.interfaces, stats.packages
+    );
+    println!();
+    println!(
+        "Diagnostics: {} errors, {} warnings",
+        stats.errors, stats.warnings
+    );
+    println!("Average ports per module: {:.2}", stats.average_ports);
+    println!(
+        "Deepest hierarchy ({}): {}",
+        stats.deepest_hierarchy.len(),
+        stats.deepest_hierarchy.join(" -> ")
+    );
+}
+
+fn escape_label(x: &str) -> String {
+    x.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Format stats as OpenMetrics text exposition, which can also be scraped as Prometheus format
+pub(crate) fn openmetrics(project: &str, stats: &Stats) -> String {
+    type Getter = fn(&FileStats) -> f64;
+    let file_metrics: [(&str, &str, &str, Getter); 6] = [
+        ("lines", "", "Number of lines", |x| x.lines as f64),
+        (
+            "code_lines",
+            "",
+            "Number of lines except blank and comment-only lines",
+            |x| x.code_lines as f64,
+        ),
+        ("modules", "", "Number of modules", |x| x.modules as f64),
+        ("interfaces", "", "Number of interfaces", |x| {
+            x.interfaces as f64
+        }),
+        ("packages", "", "Number of packages", |x| x.packages as f64),
+        (
+            "analyze_time_seconds",
+            "seconds",
+            "Elapsed time of parse and analysis",
+            |x| x.analyze_time / 1000.0,
+        ),
+    ];
+
+    let project = escape_label(project);
+    let mut ret = String::new();
+
+    for (name, unit, help, getter) in file_metrics {
+        ret.push_str(&format!("# TYPE veryl_{name} gauge\n"));
+        if !unit.is_empty() {
+            ret.push_str(&format!("# UNIT veryl_{name} {unit}\n"));
+        }
+        ret.push_str(&format!("# HELP veryl_{name} {help}\n"));
+        for file in &stats.files {
+            ret.push_str(&format!(
+                "veryl_{name}{{project=\"{project}\",file=\"{}\"}} {}\n",
+                escape_label(&file.path),
+                getter(file)
+            ));
+        }
+    }
+
+    ret.push_str("# TYPE veryl_diagnostics gauge\n");
+    ret.push_str("# HELP veryl_diagnostics Number of diagnostics by severity\n");
+    for file in &stats.files {
+        for (severity, count) in [("error", file.errors), ("warning", file.warnings)] {
+            ret.push_str(&format!(
+                "veryl_diagnostics{{project=\"{project}\",file=\"{}\",severity=\"{severity}\"}} {count}\n",
+                escape_label(&file.path),
+            ));
+        }
+    }
+
+    let project_metrics = [
+        (
+            "average_ports",
+            "Average number of ports per module",
+            stats.average_ports,
+        ),
+        (
+            "hierarchy_depth",
+            "Depth of the deepest module hierarchy",
+            stats.deepest_hierarchy.len() as f64,
+        ),
+    ];
+    for (name, help, value) in project_metrics {
+        ret.push_str(&format!("# TYPE veryl_{name} gauge\n"));
+        ret.push_str(&format!("# HELP veryl_{name} {help}\n"));
+        ret.push_str(&format!("veryl_{name}{{project=\"{project}\"}} {value}\n"));
+    }
+
+    ret.push_str("# EOF\n");
+    ret
+}
@@ -1,11 +1,22 @@
 use crate::cmd_build::CmdBuild;
-use crate::runner::{Cocotb, CocotbSource, Vcs, Verilator, Vivado};
+use crate::cmd_diff::{analyze, split_units};
+use crate::cmd_fmt::print_diff;
+use crate::coverage;
+use crate::runner::{Cocotb, CocotbSource, RunOption, Vcs, Verilator, Vivado};
 use crate::{OptBuild, OptTest};
-use log::{error, info};
-use miette::Result;
+use log::{debug, error, info};
+use miette::{bail, IntoDiagnostic, Report, Result, Severity};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use veryl_analyzer::symbol::{SymbolKind, TestType};
-use veryl_analyzer::symbol_table;
-use veryl_metadata::{FilelistType, Metadata, SimType};
+use veryl_analyzer::{symbol_table, Analyzer};
+use veryl_emitter::Emitter;
+use veryl_metadata::{FilelistType, Metadata, SimType, SourceMapTarget, TestArgs, TestManifest};
+use veryl_parser::resource_table;
+use veryl_parser::veryl_token::TokenSource;
+use veryl_parser::Parser;
+use veryl_path::PathSet;
 
 pub struct CmdTest {
     opt: OptTest,
@@ -17,61 +28,375 @@ impl CmdTest {
     }
 
     pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        if self.opt.emit_snapshots {
+            return self.exec_snapshots(metadata);
+        }
+        if self.opt.doc {
+            return self.exec_doc_tests(metadata);
+        }
+
         // force filelist_type to absolute which can be refered from temporary directory
         metadata.build.filelist_type = FilelistType::Absolute;
 
         let build = CmdBuild::new(OptBuild {
             files: self.opt.files.clone(),
+            deny_warnings: false,
+            verify_output: false,
+            matrix: None,
         });
         build.exec(metadata)?;
 
-        let tests: Vec<_> = symbol_table::get_all()
-            .into_iter()
-            .filter_map(|symbol| {
-                if symbol.namespace.to_string() == metadata.project.name {
-                    if let SymbolKind::Test(x) = symbol.kind {
-                        Some((symbol.token.text, x))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-            .collect();
-
         let sim_type = if let Some(x) = self.opt.sim {
             x.into()
         } else {
             metadata.test.simulator
         };
 
-        let mut success = 0;
+        let wave = self
+            .opt
+            .waves
+            .map(|x| x.map(|x| x.into()).unwrap_or(metadata.test.waveform_format));
+
+        let coverage = if self.opt.coverage {
+            let path = metadata.coverage_path();
+            if path.exists() {
+                fs::remove_dir_all(&path).into_diagnostic()?;
+            }
+            fs::create_dir_all(&path).into_diagnostic()?;
+            Some(path)
+        } else {
+            None
+        };
+
+        let option = RunOption {
+            wave,
+            args: parse_test_args(&self.opt.test_args)?,
+            coverage,
+        };
+
+        let (success, failure) = run_tests(metadata, sim_type, &option)?;
+
+        if failure == 0 {
+            info!("Completed tests : {} passed, {} failed", success, failure);
+        } else {
+            error!("Completed tests : {} passed, {} failed", success, failure);
+        }
+
+        let mut ret = failure == 0;
+        if self.opt.coverage {
+            let coverage = coverage::merge(metadata, sim_type)?;
+            ret &= coverage::report(metadata, &coverage);
+        }
+
+        Ok(ret)
+    }
+
+    /// Compare emitted code of each unit with the recorded snapshot
+    fn exec_snapshots(&self, metadata: &mut Metadata) -> Result<bool> {
+        // Comments and source maps are excluded to keep snapshots stable
+        metadata.build.strip_comments = true;
+        metadata.build.sourcemap_target = SourceMapTarget::None;
+
+        let contexts = analyze(metadata)?;
+
+        let mut units = BTreeMap::new();
+        for (path, parser) in &contexts {
+            if path.prj != metadata.project.name {
+                continue;
+            }
+            let mut emitter = Emitter::new(metadata, &path.src, &path.dst, &path.map);
+            emitter.emit(&path.prj, &parser.veryl);
+            split_units(emitter.as_str(), &mut units);
+        }
+
+        let snapshot_path = metadata.snapshot_path();
+        if self.opt.update && !snapshot_path.exists() {
+            fs::create_dir_all(&snapshot_path).into_diagnostic()?;
+        }
+
         let mut failure = 0;
-        for (test, property) in &tests {
-            let mut runner = match property.r#type {
-                TestType::Inline => match sim_type {
-                    SimType::Verilator => Verilator::new().runner(),
-                    SimType::Vcs => Vcs::new().runner(),
-                    SimType::Vivado => Vivado::new().runner(),
-                },
-                TestType::CocotbEmbed(x) => Cocotb::new(CocotbSource::Embed(x)).runner(),
-                TestType::CocotbInclude(x) => Cocotb::new(CocotbSource::Include(x)).runner(),
+        for (name, text) in &units {
+            let path = snapshot_path.join(format!("{name}.sv"));
+            let snapshot = fs::read_to_string(&path).ok();
+            if snapshot.as_ref() == Some(text) {
+                debug!("Matched snapshot ({})", name);
+            } else if self.opt.update {
+                fs::write(&path, text).into_diagnostic()?;
+                info!("Updated snapshot ({})", path.to_string_lossy());
+            } else if let Some(snapshot) = snapshot {
+                error!("Changed snapshot ({})", name);
+                print_diff(&path, &snapshot, text);
+                failure += 1;
+            } else {
+                error!("Missing snapshot ({})", name);
+                failure += 1;
+            }
+        }
+
+        if snapshot_path.exists() {
+            for entry in fs::read_dir(&snapshot_path).into_diagnostic()? {
+                let path = entry.into_diagnostic()?.path();
+                let name = path.file_stem().unwrap().to_string_lossy().to_string();
+                if path.extension().map(|x| x == "sv").unwrap_or(false)
+                    && !units.contains_key(&name)
+                {
+                    if self.opt.update {
+                        fs::remove_file(&path).into_diagnostic()?;
+                        info!("Removed snapshot ({})", path.to_string_lossy());
+                    } else {
+                        error!("Stale snapshot ({})", path.to_string_lossy());
+                        failure += 1;
+                    }
+                }
+            }
+        }
+
+        if failure == 0 {
+            info!("Completed snapshots : {} units", units.len());
+            Ok(true)
+        } else {
+            error!(
+                "Completed snapshots : {} mismatched (run with --update to accept)",
+                failure
+            );
+            Ok(false)
+        }
+    }
+
+    /// Compile Veryl code blocks in doc comments of the current project
+    fn exec_doc_tests(&self, metadata: &mut Metadata) -> Result<bool> {
+        let contexts = analyze(metadata)?;
+
+        let project = resource_table::insert_str(&metadata.project.name);
+        let mut tests = Vec::new();
+        for symbol in symbol_table::get_all() {
+            if symbol.namespace.paths.first() != Some(&project) {
+                continue;
+            }
+            let TokenSource::File(path) = symbol.token.source else {
+                continue;
             };
+            let path = resource_table::get_path_value(path).unwrap();
+            for code in symbol.doc_comment.code_blocks() {
+                tests.push((
+                    path.clone(),
+                    symbol.token.line,
+                    symbol.token.to_string(),
+                    code,
+                ));
+            }
+        }
+        tests.sort();
+
+        let mut inputs = Vec::new();
+        for (path, parser) in contexts {
+            let input = fs::read_to_string(&path.src).into_diagnostic()?;
+            inputs.push((path, input, parser));
+        }
+
+        let base = metadata.project_path();
+        let mut success = 0;
+        let mut failure = 0;
+        let mut index: BTreeMap<String, usize> = BTreeMap::new();
+        for (path, line, name, code) in &tests {
+            let location = format!(
+                "{}:{}",
+                path.strip_prefix(&base).unwrap_or(path).to_string_lossy(),
+                line
+            );
+            let count = index.entry(name.clone()).or_default();
+            *count += 1;
+            let test_path = PathBuf::from(format!("{}#{}_{}", path.to_string_lossy(), name, count));
 
-            if runner.run(metadata, *test, property.top, property.path, self.opt.wave)? {
+            let errors = doc_test(metadata, &inputs, &test_path, code);
+            if errors.is_empty() {
+                info!("Succeeded doc test ({} at {})", name, location);
                 success += 1;
             } else {
+                error!("Failed doc test ({} at {})", name, location);
+                for x in errors {
+                    eprintln!("{:?}", x);
+                }
                 failure += 1;
             }
         }
 
         if failure == 0 {
-            info!("Completed tests : {} passed, {} failed", success, failure);
+            info!(
+                "Completed doc tests : {} passed, {} failed",
+                success, failure
+            );
             Ok(true)
         } else {
-            error!("Completed tests : {} passed, {} failed", success, failure);
+            error!(
+                "Completed doc tests : {} passed, {} failed",
+                success, failure
+            );
             Ok(false)
         }
     }
 }
+
+/// Analyze a code block of doc comment with all files of the project,
+/// and return the errors of the code block
+fn doc_test(
+    metadata: &Metadata,
+    contexts: &[(PathSet, String, Parser)],
+    path: &Path,
+    code: &str,
+) -> Vec<Report> {
+    // Code blocks which are not top-level declarations are placed in a module
+    let code = if Parser::parse(code, &path).is_ok() {
+        code.to_string()
+    } else {
+        format!("module DocTest {{\n{code}\n}}\n")
+    };
+    let parser = match Parser::parse(&code, &path) {
+        Ok(x) => x,
+        Err(x) => return vec![Report::new(x)],
+    };
+
+    let prj = &metadata.project.name;
+    let analyzer = Analyzer::new(metadata);
+    analyzer.clear();
+
+    let mut errors = Vec::new();
+    for (path, input, parser) in contexts {
+        analyzer.analyze_pass1(&path.prj, input, &path.src, &parser.veryl);
+    }
+    errors.append(&mut analyzer.analyze_pass1(prj, &code, path, &parser.veryl));
+    Analyzer::analyze_post_pass1();
+    for (path, input, parser) in contexts {
+        analyzer.analyze_pass2(&path.prj, input, &path.src, &parser.veryl);
+    }
+    errors.append(&mut analyzer.analyze_pass2(prj, &code, path, &parser.veryl));
+    errors.append(&mut analyzer.analyze_pass3(prj, &code, path, &parser.veryl));
+
+    errors
+        .into_iter()
+        .map(|x| x.with_lint(&metadata.lint))
+        .filter(|x| x.severity == Severity::Error)
+        .map(Report::new)
+        .collect()
+}
+
+/// Parse `NAME=VALUE` as a parameter override and `+ARG` as a plusarg
+pub fn parse_test_args(x: &[String]) -> Result<TestArgs> {
+    let mut ret = TestArgs::default();
+    for arg in x {
+        if arg.starts_with('+') {
+            ret.plusargs.push(arg.clone());
+        } else if let Some((name, value)) = arg.split_once('=') {
+            ret.params.insert(name.to_string(), value.to_string());
+        } else {
+            bail!("Test argument should be NAME=VALUE or +ARG : {}", arg);
+        }
+    }
+    Ok(ret)
+}
+
+/// Discover Python tests named `test_<module>.py` which test `<module>` by cocotb
+fn cocotb_tests(metadata: &Metadata) -> Result<Vec<PathBuf>> {
+    let tests_path = metadata.cocotb_tests_path();
+    let mut ret = Vec::new();
+    if !tests_path.is_dir() {
+        return Ok(ret);
+    }
+
+    for entry in fs::read_dir(&tests_path).into_diagnostic()? {
+        let path = entry.into_diagnostic()?.path();
+        let is_test = path.extension().map(|x| x == "py").unwrap_or(false)
+            && path
+                .file_stem()
+                .map(|x| x.to_string_lossy().starts_with("test_"))
+                .unwrap_or(false);
+        if is_test {
+            ret.push(path);
+        }
+    }
+    ret.sort();
+
+    Ok(ret)
+}
+
+/// Run all tests of the current project, and return the number of passed and failed tests
+pub fn run_tests(
+    metadata: &Metadata,
+    sim_type: SimType,
+    option: &RunOption,
+) -> Result<(usize, usize)> {
+    let manifest_path = metadata.test_manifest_path();
+    let manifest = if manifest_path.exists() {
+        TestManifest::load(&manifest_path)?
+    } else {
+        TestManifest::default()
+    };
+
+    // Precedence: [test] in Veryl.toml < tests.toml < command line
+    let test_option = |test: &str| {
+        let mut args = metadata.test.args();
+        if let Some(x) = manifest.tests.get(test) {
+            args.merge(x);
+        }
+        args.merge(&option.args);
+        RunOption {
+            wave: option.wave,
+            args,
+            coverage: option.coverage.clone(),
+        }
+    };
+
+    let tests: Vec<_> = symbol_table::get_all()
+        .into_iter()
+        .filter_map(|symbol| {
+            if symbol.namespace.to_string() == metadata.project.name {
+                if let SymbolKind::Test(x) = symbol.kind {
+                    Some((symbol.token.text, x))
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut success = 0;
+    let mut failure = 0;
+    for (test, property) in &tests {
+        let mut runner = match property.r#type {
+            TestType::Inline => match sim_type {
+                SimType::Verilator => Verilator::new().runner(),
+                SimType::Vcs => Vcs::new().runner(),
+                SimType::Vivado => Vivado::new().runner(),
+            },
+            TestType::CocotbEmbed(x) => Cocotb::new(CocotbSource::Embed(x), sim_type).runner(),
+            TestType::CocotbInclude(x) => Cocotb::new(CocotbSource::Include(x), sim_type).runner(),
+        };
+
+        let option = test_option(&test.to_string());
+        if runner.run(metadata, *test, property.top, property.path, &option)? {
+            success += 1;
+        } else {
+            failure += 1;
+        }
+    }
+
+    for path in cocotb_tests(metadata)? {
+        let name = path.file_stem().unwrap().to_string_lossy().to_string();
+        let top = name.strip_prefix("test_").unwrap();
+        let option = test_option(&name);
+        let test = resource_table::insert_str(&name);
+        let top = resource_table::insert_str(top);
+        let path_id = resource_table::insert_path(&path);
+
+        let mut runner = Cocotb::new(CocotbSource::File(path), sim_type).runner();
+        if runner.run(metadata, test, Some(top), path_id, &option)? {
+            success += 1;
+        } else {
+            failure += 1;
+        }
+    }
+
+    Ok((success, failure))
+}
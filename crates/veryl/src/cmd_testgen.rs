@@ -0,0 +1,100 @@
+use crate::cmd_diff::analyze;
+use crate::testgen::{connectivity_testbench, testbench, Constraints, Dut, Lang};
+use crate::{OptTestgen, TestgenLang};
+use log::{info, warn};
+use miette::{bail, IntoDiagnostic, Result};
+use std::fs;
+use std::io::Write;
+use veryl_analyzer::namespace::Namespace;
+use veryl_analyzer::symbol::SymbolKind;
+use veryl_analyzer::symbol_path::SymbolPath;
+use veryl_analyzer::symbol_table;
+use veryl_metadata::Metadata;
+use veryl_parser::resource_table;
+
+pub struct CmdTestgen {
+    opt: OptTestgen,
+}
+
+impl CmdTestgen {
+    pub fn new(opt: OptTestgen) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        analyze(metadata)?;
+
+        let mut namespace = Namespace::new();
+        namespace.push(resource_table::insert_str(&metadata.project.name));
+        let path = SymbolPath::new(&[resource_table::insert_str(&self.opt.module)]);
+        let symbol = match symbol_table::resolve((&path, &namespace)) {
+            Ok(x) if matches!(x.found.kind, SymbolKind::Module(_)) => x.found,
+            _ => bail!("module \"{}\" is not found", self.opt.module),
+        };
+        let Some(dut) = Dut::new(metadata, &symbol) else {
+            bail!("module \"{}\" has a port without type", self.opt.module);
+        };
+
+        let lang = match self.opt.lang {
+            TestgenLang::Sv => Lang::SystemVerilog,
+            TestgenLang::Python => Lang::Python,
+        };
+
+        if self.opt.connectivity {
+            let text = connectivity_testbench(&dut, lang);
+            return self.output(&text);
+        }
+
+        let constraints = match &self.opt.constraints {
+            Some(x) => {
+                let text = fs::read_to_string(x).into_diagnostic()?;
+                Constraints::parse(&text).into_diagnostic()?
+            }
+            None => Constraints::default(),
+        };
+
+        let inputs = dut.inputs();
+        let names: Vec<_> = inputs.iter().map(|x| x.name.as_str()).collect();
+        constraints.check_names(&names).into_diagnostic()?;
+
+        let mut widths = Vec::new();
+        for port in &inputs {
+            let width = match port.width {
+                Some(x) => x,
+                None => {
+                    if !constraints.domains.contains_key(&port.name) {
+                        warn!(
+                            "Width of port \"{}\" can't be evaluated, and is treated as 32",
+                            port.name
+                        );
+                    }
+                    32
+                }
+            };
+            widths.push((port.name.as_str(), width));
+        }
+
+        let stimulus = constraints
+            .generate(&widths, self.opt.cycles, self.opt.seed)
+            .into_diagnostic()?;
+
+        let text = testbench(&dut, &stimulus, self.opt.seed, lang);
+        self.output(&text)
+    }
+
+    fn output(&self, text: &str) -> Result<bool> {
+        if let Some(ref output) = self.opt.output {
+            if let Some(dir) = output.parent() {
+                fs::create_dir_all(dir).into_diagnostic()?;
+            }
+            fs::write(output, text).into_diagnostic()?;
+            info!("Output testbench ({})", output.to_string_lossy());
+        } else {
+            let mut stdout = std::io::stdout();
+            stdout.write_all(text.as_bytes()).into_diagnostic()?;
+            stdout.flush().into_diagnostic()?;
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,199 @@
+use crate::OptUpf;
+use log::info;
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use std::collections::BTreeMap;
+use std::fs;
+use veryl_analyzer::attribute::Attribute as Attr;
+use veryl_analyzer::attribute_table;
+use veryl_analyzer::instance_graph::InstanceGraph;
+use veryl_analyzer::symbol::{Direction, Symbol, SymbolId, SymbolKind};
+use veryl_analyzer::{symbol_table, Analyzer};
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+
+pub struct CmdUpf {
+    opt: OptUpf,
+}
+
+/// Power domain of the top module if `#[power_domain]` is not specified
+const DEFAULT_DOMAIN: &str = "PD_TOP";
+
+#[derive(Default)]
+struct PowerIntent {
+    /// Instance paths belonging to each power domain
+    elements: BTreeMap<String, Vec<String>>,
+    /// Output ports crossing from each power domain to another one
+    isolation: BTreeMap<String, Vec<String>>,
+}
+
+impl CmdUpf {
+    pub fn new(opt: OptUpf) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        let paths = metadata.paths::<&str>(&[], true)?;
+
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+            let analyzer = Analyzer::new(metadata);
+            analyzer.analyze_pass1(&path.prj, &input, &path.src, &parser.veryl);
+
+            contexts.push((path, input, parser, analyzer));
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        let top = symbol_table::get_all().into_iter().find(|x| {
+            matches!(x.kind, SymbolKind::Module(_))
+                && x.token.to_string() == self.opt.top
+                && x.namespace.paths.len() == 1
+                && x.namespace.paths[0].to_string() == metadata.project.name
+        });
+        let Some(top) = top else {
+            bail!("top module \"{}\" is not found", self.opt.top);
+        };
+
+        let prefix = if metadata.build.omit_project_prefix {
+            String::new()
+        } else {
+            metadata
+                .build
+                .project_prefix
+                .clone()
+                .unwrap_or_else(|| format!("{}_", metadata.project.name))
+        };
+
+        let top_domain = power_domain(&top).unwrap_or_else(|| DEFAULT_DOMAIN.to_string());
+        let graph = InstanceGraph::new();
+        let mut intent = PowerIntent::default();
+        intent.elements.insert(top_domain.clone(), Vec::new());
+        collect(&graph, &top, &[], &top_domain, &mut Vec::new(), &mut intent);
+
+        let text = upf(&format!("{}{}", prefix, top.token), &top_domain, &intent);
+
+        if let Some(ref output) = self.opt.output {
+            fs::write(output, text).into_diagnostic()?;
+            info!("Output power intent ({})", output.to_string_lossy());
+        } else {
+            print!("{text}");
+        }
+
+        Ok(true)
+    }
+}
+
+fn power_domain(symbol: &Symbol) -> Option<String> {
+    attribute_table::get(&symbol.token)
+        .into_iter()
+        .find_map(|x| match x {
+            Attr::PowerDomain(x) => Some(x.to_string()),
+            _ => None,
+        })
+}
+
+/// Collects instances whose power domain differs from the parent.
+/// Modules without `#[power_domain]` belong to the power domain of the parent.
+fn collect(
+    graph: &InstanceGraph,
+    symbol: &Symbol,
+    path: &[String],
+    domain: &str,
+    stack: &mut Vec<SymbolId>,
+    intent: &mut PowerIntent,
+) {
+    // Recursive instantiation is reported by analyzer, so it is just cut off here
+    if stack.contains(&symbol.id) {
+        return;
+    }
+    stack.push(symbol.id);
+
+    for instance in graph.get_children(symbol.id) {
+        let Some(child) = symbol_table::get(instance.child) else {
+            continue;
+        };
+        if !matches!(child.kind, SymbolKind::Module(_)) {
+            continue;
+        }
+
+        let mut path = path.to_vec();
+        path.push(instance.token.to_string());
+        let element = path.join("/");
+
+        let child_domain = power_domain(&child).unwrap_or_else(|| domain.to_string());
+        if child_domain != domain {
+            intent
+                .elements
+                .entry(child_domain.clone())
+                .or_default()
+                .push(element.clone());
+
+            let namespace = child.inner_namespace();
+            let isolation = intent.isolation.entry(child_domain.clone()).or_default();
+            for x in symbol_table::get_all() {
+                if let SymbolKind::Port(ref port) = x.kind {
+                    if port.direction == Direction::Output && x.namespace.matched(&namespace) {
+                        isolation.push(format!("{}/{}", element, x.token));
+                    }
+                }
+            }
+        }
+
+        collect(graph, &child, &path, &child_domain, stack, intent);
+    }
+
+    stack.pop();
+}
+
+fn upf(top: &str, top_domain: &str, intent: &PowerIntent) -> String {
+    let mut ret = String::new();
+    ret.push_str(&format!("# Power intent of {top} generated by Veryl\n"));
+    ret.push_str("# Supplies, power switches and isolation controls should be completed\n\n");
+
+    ret.push_str(&format!("set_design_top {top}\n\n"));
+
+    for (domain, elements) in &intent.elements {
+        ret.push_str(&format!("create_power_domain {domain}"));
+        if domain == top_domain {
+            ret.push_str(" -include_scope");
+        }
+        if !elements.is_empty() {
+            ret.push_str(&format!(" -elements {{{}}}", elements.join(" ")));
+        }
+        ret.push('\n');
+    }
+    ret.push('\n');
+
+    ret.push_str("create_supply_net VSS\n");
+    for domain in intent.elements.keys() {
+        ret.push_str(&format!("create_supply_net VDD_{domain}\n"));
+    }
+    for domain in intent.elements.keys() {
+        ret.push_str(&format!(
+            "set_domain_supply_net {domain} -primary_power_net VDD_{domain} -primary_ground_net VSS\n"
+        ));
+    }
+
+    for (domain, ports) in &intent.isolation {
+        if ports.is_empty() {
+            continue;
+        }
+        ret.push('\n');
+        ret.push_str(&format!(
+            "set_isolation iso_{domain} -domain {domain} -elements {{{}}} -clamp_value 0\n",
+            ports.join(" ")
+        ));
+        ret.push_str(&format!(
+            "# set_isolation_control iso_{domain} -domain {domain} -isolation_signal <signal> -isolation_sense high -location parent\n"
+        ));
+    }
+
+    ret
+}
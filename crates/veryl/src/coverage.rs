@@ -0,0 +1,244 @@
+use log::{error, info};
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use veryl_metadata::{Metadata, SimType};
+
+/// Coverage of a module in percent
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Coverage {
+    pub line: Option<f64>,
+    pub toggle: Option<f64>,
+}
+
+#[derive(Default)]
+struct Points {
+    line: (u64, u64),
+    toggle: (u64, u64),
+}
+
+fn percent((covered, total): (u64, u64)) -> Option<f64> {
+    if total == 0 {
+        None
+    } else {
+        Some(covered as f64 * 100.0 / total as f64)
+    }
+}
+
+/// Parse coverage points of `coverage.dat` written by Verilator.
+/// Each point is a line like `C '<key>' <count>`, and the key consists of
+/// `\x01<name>\x02<value>` pairs where `page` is `v_<type>/<module>`.
+pub fn parse_verilator(text: &str) -> BTreeMap<String, Coverage> {
+    let mut points: BTreeMap<String, Points> = BTreeMap::new();
+
+    for line in text.lines() {
+        let Some(line) = line.strip_prefix("C '") else {
+            continue;
+        };
+        let Some((key, count)) = line.rsplit_once("' ") else {
+            continue;
+        };
+        let Ok(count) = count.trim().parse::<u64>() else {
+            continue;
+        };
+
+        let page = key
+            .split('\x01')
+            .filter_map(|x| x.split_once('\x02'))
+            .find(|(name, _)| *name == "page")
+            .map(|(_, value)| value);
+        let Some((kind, module)) = page.and_then(|x| x.split_once('/')) else {
+            continue;
+        };
+
+        let entry = points.entry(module.to_string()).or_default();
+        let target = match kind {
+            "v_line" | "v_branch" => &mut entry.line,
+            "v_toggle" => &mut entry.toggle,
+            _ => continue,
+        };
+        target.1 += 1;
+        if count > 0 {
+            target.0 += 1;
+        }
+    }
+
+    points
+        .into_iter()
+        .map(|(module, x)| {
+            let coverage = Coverage {
+                line: percent(x.line),
+                toggle: percent(x.toggle),
+            };
+            (module, coverage)
+        })
+        .collect()
+}
+
+/// Parse `modlist.txt` of URG text report.
+/// The header line has `LINE`, `TGL` and `NAME` columns, and `--` means no coverage point.
+pub fn parse_urg(text: &str) -> BTreeMap<String, Coverage> {
+    let mut ret = BTreeMap::new();
+    let mut header: Option<Vec<&str>> = None;
+
+    for line in text.lines() {
+        let columns: Vec<_> = line.split_whitespace().collect();
+        if columns.is_empty() {
+            if header.is_some() && !ret.is_empty() {
+                break;
+            }
+            continue;
+        }
+
+        if let Some(ref header) = header {
+            if columns.len() != header.len() {
+                continue;
+            }
+            let get = |name: &str| {
+                header
+                    .iter()
+                    .position(|x| *x == name)
+                    .and_then(|i| columns[i].parse::<f64>().ok())
+            };
+            let name = header.iter().position(|x| *x == "NAME").unwrap();
+            let coverage = Coverage {
+                line: get("LINE"),
+                toggle: get("TGL"),
+            };
+            ret.insert(columns[name].to_string(), coverage);
+        } else if columns.contains(&"NAME") {
+            header = Some(columns);
+        }
+    }
+
+    ret
+}
+
+fn databases(dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    let mut ret = Vec::new();
+    for entry in fs::read_dir(dir).into_diagnostic()? {
+        let path = entry.into_diagnostic()?.path();
+        let is_merged = path.file_stem().map(|x| x == "merged").unwrap_or(false);
+        if path.extension().map(|x| x == extension).unwrap_or(false) && !is_merged {
+            ret.push(path);
+        }
+    }
+    ret.sort();
+    Ok(ret)
+}
+
+/// Merge coverage databases of all tests in the coverage directory
+pub fn merge(metadata: &Metadata, sim_type: SimType) -> Result<BTreeMap<String, Coverage>> {
+    let dir = metadata.coverage_path();
+
+    match sim_type {
+        SimType::Verilator => {
+            let merged = dir.join("merged.dat");
+            let output = Command::new("verilator_coverage")
+                .arg("--write")
+                .arg(&merged)
+                .args(databases(&dir, "dat")?)
+                .output()
+                .into_diagnostic()
+                .wrap_err("Failed to run \"verilator_coverage\"")?;
+            if !output.status.success() {
+                bail!(
+                    "verilator_coverage failed\n  {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            let text = fs::read_to_string(&merged).into_diagnostic()?;
+            Ok(parse_verilator(&text))
+        }
+        SimType::Vcs => {
+            let report = dir.join("report");
+            let mut urg = Command::new("urg");
+            for db in databases(&dir, "vdb")? {
+                urg.arg("-dir").arg(db);
+            }
+            let output = urg
+                .arg("-dbname")
+                .arg(dir.join("merged"))
+                .arg("-report")
+                .arg(&report)
+                .arg("-format")
+                .arg("text")
+                .output()
+                .into_diagnostic()
+                .wrap_err("Failed to run \"urg\"")?;
+            if !output.status.success() {
+                bail!(
+                    "urg failed\n  {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            let text = fs::read_to_string(report.join("modlist.txt")).into_diagnostic()?;
+            Ok(parse_urg(&text))
+        }
+        SimType::Vivado => bail!("Coverage is not supported by Vivado Simulator"),
+    }
+}
+
+fn format(x: Option<f64>) -> String {
+    x.map(|x| format!("{:.2}", x))
+        .unwrap_or_else(|| "--".to_string())
+}
+
+fn average<T: Iterator<Item = Option<f64>>>(x: T) -> Option<f64> {
+    let x: Vec<_> = x.flatten().collect();
+    if x.is_empty() {
+        None
+    } else {
+        Some(x.iter().sum::<f64>() / x.len() as f64)
+    }
+}
+
+/// Print coverage summary, and return whether it satisfies the thresholds in metadata
+pub fn report(metadata: &Metadata, coverage: &BTreeMap<String, Coverage>) -> bool {
+    let width = coverage.keys().map(|x| x.len()).max().unwrap_or(0).max(6);
+
+    println!("{:<width$} {:>8} {:>9}", "Module", "Line[%]", "Toggle[%]");
+    for (module, x) in coverage {
+        println!(
+            "{:<width$} {:>8} {:>9}",
+            module,
+            format(x.line),
+            format(x.toggle)
+        );
+    }
+
+    let line = average(coverage.values().map(|x| x.line));
+    let toggle = average(coverage.values().map(|x| x.toggle));
+    println!(
+        "{:<width$} {:>8} {:>9}",
+        "Total",
+        format(line),
+        format(toggle)
+    );
+
+    let mut ret = true;
+    let thresholds = [
+        ("line", line, metadata.test.coverage.line_threshold),
+        ("toggle", toggle, metadata.test.coverage.toggle_threshold),
+    ];
+    for (name, actual, threshold) in thresholds {
+        if let Some(threshold) = threshold {
+            let actual = actual.unwrap_or(0.0);
+            if actual < threshold {
+                error!(
+                    "Coverage below threshold ({} : {:.2}% < {:.2}%)",
+                    name, actual, threshold
+                );
+                ret = false;
+            } else {
+                info!(
+                    "Coverage satisfied threshold ({} : {:.2}% >= {:.2}%)",
+                    name, actual, threshold
+                );
+            }
+        }
+    }
+    ret
+}
@@ -1,7 +1,11 @@
 mod doc_builder;
 mod mermaid;
+mod since;
+mod source;
 mod utils;
 mod wavedrom;
 pub use doc_builder::*;
 pub use mermaid::*;
+pub use since::*;
+pub use source::*;
 pub use wavedrom::*;
@@ -1,17 +1,23 @@
-use crate::doc::{Mermaid, Wavedrom};
+use crate::doc::{
+    interfaces, render_source, Comparison, Interface, Mermaid, Since, SourceFile, Wavedrom,
+};
 use handlebars::Handlebars;
 use mdbook::{Config, MDBook};
-use miette::{IntoDiagnostic, Result};
+use miette::{IntoDiagnostic, Result, WrapErr};
 use serde::Serialize;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::PathBuf;
 use tempfile::TempDir;
-use veryl_analyzer::symbol::{ClockDomain, ParameterKind, Symbol, SymbolKind};
+use veryl_analyzer::namespace::Namespace;
+use veryl_analyzer::symbol::{
+    ClockDomain, DocTag, ParameterKind, Symbol, SymbolKind, Type, TypeKind,
+};
 use veryl_analyzer::symbol_table;
-use veryl_metadata::Metadata;
-use veryl_parser::veryl_token::Token;
+use veryl_metadata::{DocTheme, Metadata};
+use veryl_parser::resource_table::{self, StrId};
+use veryl_parser::veryl_token::{Token, TokenSource};
 
 const SUMMARY_TMPL: &str = r###"
 # Summary
@@ -40,6 +46,26 @@ const SUMMARY_TMPL: &str = r###"
   {{#each packages}}
   - [{{this.0}}]({{this.1}}.md)
   {{/each}}
+
+- [All Items](items.md)
+
+- [Sources](sources.md)
+  {{#each sources}}
+  - [{{this.0}}]({{this.1}}.md)
+  {{/each}}
+
+{{#if dependencies}}
+---
+
+# Dependencies
+
+{{#each dependencies}}
+- [{{this.name}} {{this.version}}]({{this.file_name}}.md)
+  {{#each this.items}}
+  - [{{this.0}}]({{this.1}}.md)
+  {{/each}}
+{{/each}}
+{{/if}}
 "###;
 
 #[derive(Serialize)]
@@ -50,9 +76,47 @@ struct SummaryData {
     proto_modules: Vec<(String, String)>,
     interfaces: Vec<(String, String)>,
     packages: Vec<(String, String)>,
+    sources: Vec<(String, String)>,
+    dependencies: Vec<SummaryDependency>,
+}
+
+#[derive(Serialize)]
+struct SummaryDependency {
+    name: String,
+    version: String,
+    file_name: String,
+    items: Vec<(String, String)>,
+}
+
+const DEPENDENCY_TMPL: &str = r###"
+# {{name}}
+
+<table align="center" class="table_list">
+<tbody>
+<tr>
+    <th class="table_list_item">Version</th>
+    <td class="table_list_item">{{version}}</td>
+</tr>
+</tbody>
+</table>
+
+{{#each lists}}
+{{this}}
+{{/each}}
+"###;
+
+#[derive(Serialize)]
+struct DependencyData {
+    name: String,
+    version: String,
+    lists: Vec<String>,
 }
 
 const INDEX_TMPL: &str = r###"
+{{#if logo}}
+<img class="index_logo" src="{{logo}}" alt="{{name}}">
+{{/if}}
+
 # {{name}}
 
 {{description}}
@@ -78,6 +142,21 @@ const INDEX_TMPL: &str = r###"
 </tbody>
 </table>
 
+{{#if removed_items}}
+### Removed since {{since}}
+---
+
+<table class="table_list">
+<tbody>
+{{#each removed_items}}
+<tr>
+    <th class="table_list_item">{{this}}</th>
+</tr>
+{{/each}}
+</tbody>
+</table>
+{{/if}}
+
 {{{{raw}}}}
 {{#include modules.md}}
 {{#include proto_modules.md}}
@@ -89,10 +168,13 @@ const INDEX_TMPL: &str = r###"
 #[derive(Serialize)]
 struct IndexData {
     name: String,
+    logo: Option<String>,
     description: Option<String>,
     version: String,
     repository: Option<String>,
     license: Option<String>,
+    since: Option<String>,
+    removed_items: Vec<String>,
 }
 
 const LIST_TMPL: &str = r###"
@@ -128,8 +210,34 @@ struct ListItem {
 const MODULE_TMPL: &str = r#"
 ## {{name}}
 
+{{#if source}}
+<p><a href="{{source}}">[source]</a></p>
+{{/if}}
+
 {{description}}
 
+{{#if since}}
+{{#if added}}
+<p><span class="hljs-comment">New since {{since}}</span></p>
+{{/if}}
+{{#if removed}}
+### Removed since {{since}}
+---
+
+<table class="table_list">
+<tbody>
+{{#each removed}}
+<tr>
+    <th class="table_list_item">{{this.name}}</th>
+    <td class="table_list_item"><span class="hljs-keyword">{{this.kind}}</span></td>
+    <td class="table_list_item"><span class="hljs-type">{{this.signature}}</span></td>
+</tr>
+{{/each}}
+</tbody>
+</table>
+{{/if}}
+{{/if}}
+
 {{#if generic_parameters}}
 ### Generic Parameters
 ---
@@ -153,10 +261,13 @@ const MODULE_TMPL: &str = r#"
 <table class="table_list">
 <tbody>
 {{#each parameters}}
-<tr>
-    <th class="table_list_item">{{this.name}}</th>
+<tr id="param-{{this.name}}">
+    <th class="table_list_item">{{#if this.source}}<a href="{{this.source}}">{{this.name}}</a>{{else}}{{this.name}}{{/if}}</th>
     <td class="table_list_item"><span class="hljs-type">{{this.typ}}</span></td>
     <td class="table_list_item">{{this.description}}</td>
+    {{#if ../since}}
+    <td class="table_list_item"><span class="hljs-comment">{{this.change}}</span></td>
+    {{/if}}
 </tr>
 {{/each}}
 </tbody>
@@ -185,29 +296,54 @@ const MODULE_TMPL: &str = r#"
 <table class="table_list">
 <tbody>
 {{#each ports}}
-<tr>
-    <th class="table_list_item">{{this.name}}</th>
+<tr id="port-{{this.name}}">
+    <th class="table_list_item">{{#if this.source}}<a href="{{this.source}}">{{this.name}}</a>{{else}}{{this.name}}{{/if}}</th>
     <td class="table_list_item"><span class="hljs-keyword">{{this.direction}}</span></td>
     {{#if ../clock_domains}}
     <td class="table_list_item"><span class="hljs-attribute">{{this.clock_domain}}</span></td>
     {{/if}}
     <td class="table_list_item"><span class="hljs-type">{{this.typ}}</span></td>
     <td class="table_list_item">{{this.description}}</td>
+    {{#if ../since}}
+    <td class="table_list_item"><span class="hljs-comment">{{this.change}}</span></td>
+    {{/if}}
 </tr>
 {{/each}}
 </tbody>
 </table>
 {{/if}}
+
+{{#if examples}}
+### Examples
+---
+
+{{#each examples}}
+{{this}}
+
+{{/each}}
+{{/if}}
 "#;
 
 #[derive(Serialize)]
 struct ModuleData {
     name: String,
+    source: Option<String>,
     description: String,
     generic_parameters: Vec<GenericParameterData>,
     parameters: Vec<ParameterData>,
     clock_domains: Vec<String>,
     ports: Vec<PortData>,
+    examples: Vec<String>,
+    since: Option<String>,
+    added: bool,
+    removed: Vec<RemovedData>,
+}
+
+#[derive(Serialize)]
+struct RemovedData {
+    name: String,
+    kind: String,
+    signature: String,
 }
 
 #[derive(Serialize)]
@@ -219,24 +355,54 @@ struct GenericParameterData {
 #[derive(Serialize)]
 struct ParameterData {
     name: String,
+    source: Option<String>,
     typ: String,
     description: Option<String>,
+    change: Option<String>,
 }
 
 #[derive(Serialize)]
 struct PortData {
     name: String,
+    source: Option<String>,
     direction: String,
     clock_domain: Option<String>,
     typ: Option<String>,
     description: Option<String>,
+    change: Option<String>,
 }
 
 const PROTO_MODULE_TMPL: &str = r#"
 ## {{name}}
 
+{{#if source}}
+<p><a href="{{source}}">[source]</a></p>
+{{/if}}
+
 {{description}}
 
+{{#if since}}
+{{#if added}}
+<p><span class="hljs-comment">New since {{since}}</span></p>
+{{/if}}
+{{#if removed}}
+### Removed since {{since}}
+---
+
+<table class="table_list">
+<tbody>
+{{#each removed}}
+<tr>
+    <th class="table_list_item">{{this.name}}</th>
+    <td class="table_list_item"><span class="hljs-keyword">{{this.kind}}</span></td>
+    <td class="table_list_item"><span class="hljs-type">{{this.signature}}</span></td>
+</tr>
+{{/each}}
+</tbody>
+</table>
+{{/if}}
+{{/if}}
+
 {{#if parameters}}
 ### Parameters
 ---
@@ -244,10 +410,13 @@ const PROTO_MODULE_TMPL: &str = r#"
 <table class="table_list">
 <tbody>
 {{#each parameters}}
-<tr>
-    <th class="table_list_item">{{this.name}}</th>
+<tr id="param-{{this.name}}">
+    <th class="table_list_item">{{#if this.source}}<a href="{{this.source}}">{{this.name}}</a>{{else}}{{this.name}}{{/if}}</th>
     <td class="table_list_item"><span class="hljs-type">{{this.typ}}</span></td>
     <td class="table_list_item">{{this.description}}</td>
+    {{#if ../since}}
+    <td class="table_list_item"><span class="hljs-comment">{{this.change}}</span></td>
+    {{/if}}
 </tr>
 {{/each}}
 </tbody>
@@ -276,35 +445,79 @@ const PROTO_MODULE_TMPL: &str = r#"
 <table class="table_list">
 <tbody>
 {{#each ports}}
-<tr>
-    <th class="table_list_item">{{this.name}}</th>
+<tr id="port-{{this.name}}">
+    <th class="table_list_item">{{#if this.source}}<a href="{{this.source}}">{{this.name}}</a>{{else}}{{this.name}}{{/if}}</th>
     <td class="table_list_item"><span class="hljs-keyword">{{this.direction}}</span></td>
     {{#if ../clock_domains}}
     <td class="table_list_item"><span class="hljs-attribute">{{this.clock_domain}}</span></td>
     {{/if}}
     <td class="table_list_item"><span class="hljs-type">{{this.typ}}</span></td>
     <td class="table_list_item">{{this.description}}</td>
+    {{#if ../since}}
+    <td class="table_list_item"><span class="hljs-comment">{{this.change}}</span></td>
+    {{/if}}
 </tr>
 {{/each}}
 </tbody>
 </table>
 {{/if}}
+
+{{#if examples}}
+### Examples
+---
+
+{{#each examples}}
+{{this}}
+
+{{/each}}
+{{/if}}
 "#;
 
 #[derive(Serialize)]
 struct ProtoModuleData {
     name: String,
+    source: Option<String>,
     description: String,
     parameters: Vec<ParameterData>,
     clock_domains: Vec<String>,
     ports: Vec<PortData>,
+    examples: Vec<String>,
+    since: Option<String>,
+    added: bool,
+    removed: Vec<RemovedData>,
 }
 
 const INTERFACE_TMPL: &str = r#"
 ## {{name}}
 
+{{#if source}}
+<p><a href="{{source}}">[source]</a></p>
+{{/if}}
+
 {{description}}
 
+{{#if since}}
+{{#if added}}
+<p><span class="hljs-comment">New since {{since}}</span></p>
+{{/if}}
+{{#if removed}}
+### Removed since {{since}}
+---
+
+<table class="table_list">
+<tbody>
+{{#each removed}}
+<tr>
+    <th class="table_list_item">{{this.name}}</th>
+    <td class="table_list_item"><span class="hljs-keyword">{{this.kind}}</span></td>
+    <td class="table_list_item"><span class="hljs-type">{{this.signature}}</span></td>
+</tr>
+{{/each}}
+</tbody>
+</table>
+{{/if}}
+{{/if}}
+
 {{#if parameters}}
 ### Parameters
 ---
@@ -312,34 +525,159 @@ const INTERFACE_TMPL: &str = r#"
 <table class="table_list">
 <tbody>
 {{#each parameters}}
-<tr>
-    <th class="table_list_item">{{this.name}}</th>
+<tr id="param-{{this.name}}">
+    <th class="table_list_item">{{#if this.source}}<a href="{{this.source}}">{{this.name}}</a>{{else}}{{this.name}}{{/if}}</th>
     <td class="table_list_item"><span class="hljs-type">{{this.typ}}</span></td>
     <td class="table_list_item">{{this.description}}</td>
+    {{#if ../since}}
+    <td class="table_list_item"><span class="hljs-comment">{{this.change}}</span></td>
+    {{/if}}
 </tr>
 {{/each}}
 </tbody>
 </table>
 {{/if}}
+
+{{#if examples}}
+### Examples
+---
+
+{{#each examples}}
+{{this}}
+
+{{/each}}
+{{/if}}
 "#;
 
 #[derive(Serialize)]
 struct InterfaceData {
     name: String,
+    source: Option<String>,
     description: String,
     parameters: Vec<ParameterData>,
+    examples: Vec<String>,
+    since: Option<String>,
+    added: bool,
+    removed: Vec<RemovedData>,
 }
 
 const PACKAGE_TMPL: &str = r###"
 ## {{name}}
 
+{{#if source}}
+<p><a href="{{source}}">[source]</a></p>
+{{/if}}
+
 {{description}}
 
+{{#if types}}
+### Types
+---
+
+<table class="table_list">
+<tbody>
+{{#each types}}
+<tr id="type-{{this.name}}">
+    <th class="table_list_item">{{#if this.source}}<a href="{{this.source}}">{{this.name}}</a>{{else}}{{this.name}}{{/if}}</th>
+    <td class="table_list_item"><span class="hljs-keyword">{{this.kind}}</span></td>
+    <td class="table_list_item">{{this.description}}</td>
+</tr>
+{{/each}}
+</tbody>
+</table>
+{{/if}}
+
+{{#if examples}}
+### Examples
+---
+
+{{#each examples}}
+{{this}}
+
+{{/each}}
+{{/if}}
 "###;
 
 #[derive(Serialize)]
 struct PackageData {
     name: String,
+    source: Option<String>,
+    description: String,
+    types: Vec<TypeData>,
+    examples: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct TypeData {
+    name: String,
+    source: Option<String>,
+    kind: String,
+    description: String,
+}
+
+const ITEMS_TMPL: &str = r###"
+## All Items
+---
+
+<table class="table_list">
+<tbody>
+{{#each items}}
+<tr>
+    <th class="table_list_item"><a href="{{this.url}}">{{this.path}}</a></th>
+    <td class="table_list_item"><span class="hljs-keyword">{{this.kind}}</span></td>
+    <td class="table_list_item">{{this.description}}</td>
+    <td class="table_list_item">{{#if this.source}}<a href="{{this.source}}">[source]</a>{{/if}}</td>
+</tr>
+{{/each}}
+</tbody>
+</table>
+"###;
+
+#[derive(Serialize)]
+struct ItemsData {
+    items: Vec<SearchItem>,
+}
+
+const SOURCES_TMPL: &str = r###"
+## Sources
+---
+
+<table class="table_list">
+<tbody>
+{{#each sources}}
+<tr>
+    <th class="table_list_item"><a href="{{this.1}}.html">{{this.0}}</a></th>
+</tr>
+{{/each}}
+</tbody>
+</table>
+"###;
+
+#[derive(Serialize)]
+struct SourcesData {
+    sources: Vec<(String, String)>,
+}
+
+const SOURCE_TMPL: &str = r###"
+## {{title}}
+
+{{code}}
+"###;
+
+#[derive(Serialize)]
+struct SourceData {
+    title: String,
+    code: String,
+}
+
+/// Entry of `search_index.json`
+#[derive(Serialize)]
+struct SearchItem {
+    name: String,
+    kind: String,
+    path: String,
+    url: String,
+    source: Option<String>,
     description: String,
 }
 
@@ -354,6 +692,32 @@ pub struct DocBuilder {
     proto_modules: Vec<TopLevelItem>,
     interfaces: Vec<TopLevelItem>,
     packages: Vec<TopLevelItem>,
+    dependencies: Vec<DocDependency>,
+    since: Option<Since>,
+    pages: HashMap<String, String>,
+    sources: Vec<SourceFile>,
+}
+
+/// Top level items of a dependency project
+#[derive(Clone)]
+pub struct DocDependency {
+    pub name: String,
+    pub version: String,
+    pub file_name: String,
+    pub modules: Vec<TopLevelItem>,
+    pub proto_modules: Vec<TopLevelItem>,
+    pub interfaces: Vec<TopLevelItem>,
+    pub packages: Vec<TopLevelItem>,
+}
+
+impl DocDependency {
+    fn items(&self) -> impl Iterator<Item = &TopLevelItem> {
+        self.modules
+            .iter()
+            .chain(&self.proto_modules)
+            .chain(&self.interfaces)
+            .chain(&self.packages)
+    }
 }
 
 #[derive(Clone)]
@@ -378,6 +742,14 @@ impl DocBuilder {
         fs::create_dir(&src_dir).into_diagnostic()?;
         fs::create_dir(&theme_dir).into_diagnostic()?;
 
+        let pages = modules
+            .iter()
+            .chain(&proto_modules)
+            .chain(&interfaces)
+            .chain(&packages)
+            .map(|x| (full_name(&x.symbol), x.file_name.clone()))
+            .collect();
+
         Ok(Self {
             metadata: metadata.clone(),
             temp_dir,
@@ -388,9 +760,50 @@ impl DocBuilder {
             proto_modules,
             interfaces,
             packages,
+            dependencies: Vec::new(),
+            since: None,
+            pages,
+            sources: Vec::new(),
         })
     }
 
+    pub fn with_dependencies(mut self, dependencies: Vec<DocDependency>) -> Self {
+        for x in dependencies.iter().flat_map(|x| x.items()) {
+            self.pages.insert(full_name(&x.symbol), x.file_name.clone());
+        }
+        self.dependencies = dependencies;
+        self
+    }
+
+    pub fn with_sources(mut self, sources: Vec<SourceFile>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    pub fn with_since(mut self, since: Since) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Compare interface of the item with the previous version
+    fn compare(&self, symbol: &Symbol) -> Comparison {
+        let own = format!("{}", symbol.namespace) == self.metadata.project.name;
+        match (&self.since, Interface::new(symbol)) {
+            (Some(since), Some(current)) if own => {
+                since.compare(&symbol.token.to_string(), &current)
+            }
+            _ => Comparison::default(),
+        }
+    }
+
+    fn since(&self, symbol: &Symbol) -> Option<String> {
+        let own = format!("{}", symbol.namespace) == self.metadata.project.name;
+        self.since
+            .as_ref()
+            .filter(|_| own)
+            .map(|x| x.version.clone())
+    }
+
     pub fn build(&self) -> Result<()> {
         self.build_theme()?;
 
@@ -400,25 +813,29 @@ impl DocBuilder {
         self.build_component("proto_modules.md", self.build_proto_modules())?;
         self.build_component("interfaces.md", self.build_interfaces())?;
         self.build_component("packages.md", self.build_packages())?;
+        self.build_component("items.md", self.build_items())?;
+        self.build_component("sources.md", self.build_sources())?;
 
-        for x in &self.modules {
-            let file = format!("{}.md", x.file_name);
-            self.build_component(&file, self.build_module(&x.html_name, &x.symbol))?;
-        }
-
-        for x in &self.proto_modules {
+        for x in &self.sources {
             let file = format!("{}.md", x.file_name);
-            self.build_component(&file, self.build_proto_module(&x.html_name, &x.symbol))?;
+            self.build_component(&file, build_source(x))?;
         }
 
-        for x in &self.interfaces {
+        for x in &self.dependencies {
             let file = format!("{}.md", x.file_name);
-            self.build_component(&file, self.build_interface(&x.html_name, &x.symbol))?;
+            self.build_component(&file, self.build_dependency(x))?;
         }
 
-        for x in &self.packages {
+        let dependency_items = self.dependencies.iter().flat_map(|x| x.items());
+        for x in self.top_level_items().chain(dependency_items) {
             let file = format!("{}.md", x.file_name);
-            self.build_component(&file, self.build_package(&x.html_name, &x.symbol))?;
+            let content = match x.symbol.kind {
+                SymbolKind::Module(_) => self.build_module(&x.html_name, &x.symbol),
+                SymbolKind::ProtoModule(_) => self.build_proto_module(&x.html_name, &x.symbol),
+                SymbolKind::Interface(_) => self.build_interface(&x.html_name, &x.symbol),
+                _ => self.build_package(&x.html_name, &x.symbol),
+            };
+            self.build_component(&file, content)?;
         }
 
         let mut cfg = Config::default();
@@ -426,8 +843,21 @@ impl DocBuilder {
         cfg.set("output.html.no-section-label", true).unwrap();
         cfg.set("output.html.fold.enable", true).unwrap();
         cfg.set("output.html.fold.level", 1).unwrap();
-        cfg.set("output.html.additional-css", vec!["theme/custom.css"])
-            .unwrap();
+        let mut css = vec!["theme/custom.css"];
+        if self.metadata.doc.custom_css.is_some() {
+            css.push("theme/user.css");
+        }
+        cfg.set("output.html.additional-css", css).unwrap();
+        let theme = match self.metadata.doc.theme {
+            DocTheme::Auto => None,
+            DocTheme::Light => Some("light"),
+            DocTheme::Dark => Some("coal"),
+        };
+        if let Some(theme) = theme {
+            // Browser preference is ignored if theme is specified explicitly
+            cfg.set("output.html.default-theme", theme).unwrap();
+            cfg.set("output.html.preferred-dark-theme", theme).unwrap();
+        }
         cfg.set(
             "output.html.additional-js",
             vec![
@@ -444,9 +874,22 @@ impl DocBuilder {
         md.with_preprocessor(wavedrom);
         md.with_preprocessor(mermaid);
         md.build().unwrap();
+
+        let index = serde_json::to_string(&self.search_items()).into_diagnostic()?;
+        let file = self.metadata.doc_path().join("search_index.json");
+        fs::write(file, index).into_diagnostic()?;
+
         Ok(())
     }
 
+    fn top_level_items(&self) -> impl Iterator<Item = &TopLevelItem> {
+        self.modules
+            .iter()
+            .chain(&self.proto_modules)
+            .chain(&self.interfaces)
+            .chain(&self.packages)
+    }
+
     fn build_theme(&self) -> Result<()> {
         let custom_css = r##"
 .affix {
@@ -464,12 +907,62 @@ impl DocBuilder {
     border: unset;
     background-color: var(--bg);
 }
+
+.source_line {
+    color: var(--sidebar-non-existant);
+    user-select: none;
+}
+
+pre.source span:target {
+    background-color: var(--quote-bg);
+}
+
+.index_logo {
+    max-height: 120px;
+}
+
+.doc_footer {
+    margin-top: 4em;
+    color: var(--sidebar-non-existant);
+    font-size: 0.9em;
+}
         "##;
 
         let file = self.theme_dir.join("custom.css");
         let mut file = File::create(file).into_diagnostic()?;
         write!(file, "{}", custom_css).into_diagnostic()?;
 
+        if let Some(logo) = self.logo() {
+            let src = self
+                .metadata
+                .project_path()
+                .join(self.metadata.doc.logo.as_ref().unwrap());
+            fs::copy(&src, self.src_dir.join(&logo))
+                .into_diagnostic()
+                .wrap_err(format!("Failed to copy logo ({})", src.to_string_lossy()))?;
+
+            // All pages are placed at the root of the doc, and the CSS is under theme
+            let sidebar_logo = format!(
+                r##"
+.sidebar-scrollbox::before {{
+    content: "";
+    display: block;
+    height: 80px;
+    margin-bottom: 1em;
+    background: url("../{logo}") no-repeat center / contain;
+}}
+"##
+            );
+            write!(file, "{}", sidebar_logo).into_diagnostic()?;
+        }
+
+        if let Some(ref css) = self.metadata.doc.custom_css {
+            let src = self.metadata.project_path().join(css);
+            fs::copy(&src, self.theme_dir.join("user.css"))
+                .into_diagnostic()
+                .wrap_err(format!("Failed to copy CSS ({})", src.to_string_lossy()))?;
+        }
+
         let favicon = include_bytes!("../../resource/favicon.png");
         let file = self.theme_dir.join("favicon.png");
         let mut file = File::create(file).into_diagnostic()?;
@@ -497,9 +990,25 @@ impl DocBuilder {
         let file = self.src_dir.join(name);
         let mut file = File::create(file).into_diagnostic()?;
         write!(file, "{}", content).into_diagnostic()?;
+        if let Some(ref footer) = self.metadata.doc.footer {
+            if name != "SUMMARY.md" {
+                write!(file, "\n<footer class=\"doc_footer\">{}</footer>\n", footer)
+                    .into_diagnostic()?;
+            }
+        }
         Ok(())
     }
 
+    /// File name of the logo copied into the doc
+    fn logo(&self) -> Option<String> {
+        let logo = self.metadata.doc.logo.as_ref()?;
+        let ext = logo
+            .extension()
+            .map(|x| x.to_string_lossy())
+            .unwrap_or_default();
+        Some(format!("logo.{ext}"))
+    }
+
     fn build_summary(&self) -> String {
         let modules: Vec<_> = self
             .modules
@@ -525,6 +1034,19 @@ impl DocBuilder {
             .cloned()
             .map(|x| (x.html_name, x.file_name))
             .collect();
+        let dependencies: Vec<_> = self
+            .dependencies
+            .iter()
+            .map(|x| SummaryDependency {
+                name: x.name.clone(),
+                version: x.version.clone(),
+                file_name: x.file_name.clone(),
+                items: x
+                    .items()
+                    .map(|x| (x.html_name.clone(), x.file_name.clone()))
+                    .collect(),
+            })
+            .collect();
         let data = SummaryData {
             name: self.metadata.project.name.clone(),
             version: format!("{}", self.metadata.project.version),
@@ -532,6 +1054,8 @@ impl DocBuilder {
             proto_modules,
             interfaces,
             packages,
+            sources: self.source_list(),
+            dependencies,
         };
 
         let mut handlebars = Handlebars::new();
@@ -542,10 +1066,17 @@ impl DocBuilder {
     fn build_index(&self) -> String {
         let data = IndexData {
             name: self.metadata.project.name.clone(),
+            logo: self.logo(),
             version: format!("{}", self.metadata.project.version),
             description: self.metadata.project.description.clone(),
             repository: self.metadata.project.repository.clone(),
             license: self.metadata.project.license.clone(),
+            since: self.since.as_ref().map(|x| x.version.clone()),
+            removed_items: self
+                .since
+                .as_ref()
+                .map(|x| x.removed_items(&interfaces(&self.metadata.project.name)))
+                .unwrap_or_default(),
         };
 
         let mut handlebars = Handlebars::new();
@@ -554,91 +1085,163 @@ impl DocBuilder {
     }
 
     fn build_modules(&self) -> String {
-        let items: Vec<_> = self
-            .modules
-            .iter()
-            .map(|x| ListItem {
-                file_name: x.file_name.clone(),
-                html_name: x.html_name.clone(),
-                description: x.symbol.doc_comment.format(true),
-            })
-            .collect();
+        build_list("Modules", &self.modules)
+    }
+
+    fn build_proto_modules(&self) -> String {
+        build_list("Module Prototypes", &self.proto_modules)
+    }
+
+    fn build_interfaces(&self) -> String {
+        build_list("Interfaces", &self.interfaces)
+    }
 
-        let data = ListData {
-            name: "Modules".to_string(),
-            items,
+    fn build_packages(&self) -> String {
+        build_list("Packages", &self.packages)
+    }
+
+    fn build_dependency(&self, dependency: &DocDependency) -> String {
+        let lists = [
+            ("Modules", &dependency.modules),
+            ("Module Prototypes", &dependency.proto_modules),
+            ("Interfaces", &dependency.interfaces),
+            ("Packages", &dependency.packages),
+        ];
+        let data = DependencyData {
+            name: dependency.name.clone(),
+            version: dependency.version.clone(),
+            lists: lists
+                .into_iter()
+                .filter(|(_, x)| !x.is_empty())
+                .map(|(name, x)| build_list(name, x))
+                .collect(),
         };
 
         let mut handlebars = Handlebars::new();
         handlebars.register_escape_fn(handlebars::no_escape);
-        handlebars.render_template(LIST_TMPL, &data).unwrap()
+        handlebars.render_template(DEPENDENCY_TMPL, &data).unwrap()
     }
 
-    fn build_proto_modules(&self) -> String {
-        let items: Vec<_> = self
-            .proto_modules
-            .iter()
-            .map(|x| ListItem {
-                file_name: x.file_name.clone(),
-                html_name: x.html_name.clone(),
-                description: x.symbol.doc_comment.format(true),
-            })
-            .collect();
-
-        let data = ListData {
-            name: "Module Prototypes".to_string(),
-            items,
+    fn build_items(&self) -> String {
+        let data = ItemsData {
+            items: self.search_items(),
         };
 
         let mut handlebars = Handlebars::new();
         handlebars.register_escape_fn(handlebars::no_escape);
-        handlebars.render_template(LIST_TMPL, &data).unwrap()
+        handlebars.render_template(ITEMS_TMPL, &data).unwrap()
     }
 
-    fn build_interfaces(&self) -> String {
-        let items: Vec<_> = self
-            .interfaces
-            .iter()
-            .map(|x| ListItem {
-                file_name: x.file_name.clone(),
-                html_name: x.html_name.clone(),
-                description: x.symbol.doc_comment.format(true),
-            })
-            .collect();
-
-        let data = ListData {
-            name: "Interfaces".to_string(),
-            items,
+    fn build_sources(&self) -> String {
+        let data = SourcesData {
+            sources: self.source_list(),
         };
 
         let mut handlebars = Handlebars::new();
         handlebars.register_escape_fn(handlebars::no_escape);
-        handlebars.render_template(LIST_TMPL, &data).unwrap()
+        handlebars.render_template(SOURCES_TMPL, &data).unwrap()
     }
 
-    fn build_packages(&self) -> String {
-        let items: Vec<_> = self
-            .packages
+    fn source_list(&self) -> Vec<(String, String)> {
+        self.sources
             .iter()
-            .map(|x| ListItem {
-                file_name: x.file_name.clone(),
-                html_name: x.html_name.clone(),
-                description: x.symbol.doc_comment.format(true),
-            })
-            .collect();
+            .map(|x| (x.title.clone(), x.file_name.clone()))
+            .collect()
+    }
+
+    fn search_items(&self) -> Vec<SearchItem> {
+        let mut ret = Vec::new();
+        let dependency_items = self.dependencies.iter().flat_map(|x| x.items());
+        for item in self.top_level_items().chain(dependency_items) {
+            let symbol = &item.symbol;
+            let mut members = Vec::new();
+            match &symbol.kind {
+                SymbolKind::Module(x) => {
+                    members.extend(x.parameters.iter().map(|x| x.symbol));
+                    members.extend(x.ports.iter().map(|x| x.symbol));
+                }
+                SymbolKind::ProtoModule(x) => {
+                    members.extend(x.parameters.iter().map(|x| x.symbol));
+                    members.extend(x.ports.iter().map(|x| x.symbol));
+                }
+                SymbolKind::Interface(x) => {
+                    members.extend(x.parameters.iter().map(|x| x.symbol));
+                }
+                SymbolKind::Package(_) => {
+                    members.extend(member_types(symbol).iter().map(|x| x.id));
+                }
+                _ => (),
+            }
+
+            let tags = symbol.doc_comment.tags();
+            let members = members.into_iter().filter_map(symbol_table::get);
+            for x in std::iter::once(symbol.clone()).chain(members) {
+                if let Some(url) = self.url(&x) {
+                    let path = full_name(&x);
+                    let path = path
+                        .strip_prefix(&format!("{}::", self.metadata.project.name))
+                        .unwrap_or(&path)
+                        .to_string();
+                    ret.push(SearchItem {
+                        name: x.token.to_string(),
+                        kind: x.kind.to_kind_name(),
+                        path,
+                        url,
+                        source: self.source_url(&x.token),
+                        description: tag_description(
+                            &tags,
+                            &x.token.text,
+                            matches!(x.kind, SymbolKind::Port(_)),
+                        )
+                        .unwrap_or_else(|| x.doc_comment.format(true).trim().to_string()),
+                    });
+                }
+            }
+        }
+        ret
+    }
 
-        let data = ListData {
-            name: "Packages".to_string(),
-            items,
+    /// URL of the page documenting `symbol`, or the anchor in the page of its parent
+    fn url(&self, symbol: &Symbol) -> Option<String> {
+        if let Some(x) = self.pages.get(&full_name(symbol)) {
+            return Some(format!("{x}.html"));
+        }
+        let parent = self.pages.get(&symbol.namespace.to_string())?;
+        let prefix = match symbol.kind {
+            SymbolKind::Port(_) => "port",
+            SymbolKind::Parameter(_) => "param",
+            _ => "type",
         };
+        Some(format!("{parent}.html#{prefix}-{}", symbol.token))
+    }
 
-        let mut handlebars = Handlebars::new();
-        handlebars.register_escape_fn(handlebars::no_escape);
-        handlebars.render_template(LIST_TMPL, &data).unwrap()
+    /// URL of the line declaring `token` in the rendered source
+    fn source_url(&self, token: &Token) -> Option<String> {
+        let TokenSource::File(path) = token.source else {
+            return None;
+        };
+        let path = resource_table::get_path_value(path)?;
+        let source = self.sources.iter().find(|x| x.path == path)?;
+        Some(format!("{}.html#L{}", source.file_name, token.line))
+    }
+
+    /// Format type linking user defined type to its definition
+    fn format_type(&self, r#type: &Type, namespace: &Namespace) -> String {
+        let text = r#type.to_string();
+        if let TypeKind::UserDefined(path) = &r#type.kind {
+            if let Ok(x) = symbol_table::resolve((path, namespace)) {
+                if let Some(url) = self.url(&x.found) {
+                    return format!("<a href=\"{url}\">{text}</a>");
+                }
+            }
+        }
+        text
     }
 
     fn build_module(&self, name: &str, symbol: &Symbol) -> String {
         if let SymbolKind::Module(property) = &symbol.kind {
+            let tags = symbol.doc_comment.tags();
+            let comparison = self.compare(symbol);
             let generic_parameters: Vec<_> = property
                 .generic_parameters
                 .iter()
@@ -661,8 +1264,11 @@ impl DocBuilder {
                 .filter(|x| matches!(x.property().kind, ParameterKind::Param,))
                 .map(|x| ParameterData {
                     name: x.name.to_string(),
-                    typ: format!("{}", x.property().r#type),
-                    description: get_comment_from_token(&x.property().token),
+                    source: self.source_url(&x.property().token),
+                    typ: self.format_type(&x.property().r#type, &symbol.inner_namespace()),
+                    description: tag_description(&tags, &x.name, false)
+                        .or_else(|| get_comment_from_token(&x.property().token)),
+                    change: comparison.params.get(&x.name.to_string()).cloned(),
                 })
                 .collect();
 
@@ -691,21 +1297,41 @@ impl DocBuilder {
                     };
                     PortData {
                         name: x.name.to_string(),
+                        source: self.source_url(&x.property().token),
                         direction: format!("{}", x.property().direction),
                         clock_domain,
-                        typ: x.property().r#type.as_ref().map(|x| format!("{}", x)),
-                        description: get_comment_from_token(&x.property().token),
+                        typ: x
+                            .property()
+                            .r#type
+                            .as_ref()
+                            .map(|x| self.format_type(x, &symbol.inner_namespace())),
+                        description: tag_description(&tags, &x.name, true)
+                            .or_else(|| get_comment_from_token(&x.property().token)),
+                        change: comparison.ports.get(&x.name.to_string()).cloned(),
                     }
                 })
                 .collect();
 
             let data = ModuleData {
                 name: name.to_string(),
+                source: self.source_url(&symbol.token),
                 description: symbol.doc_comment.format(false),
                 generic_parameters,
                 parameters,
                 clock_domains,
                 ports,
+                examples: examples(&tags),
+                since: self.since(symbol),
+                added: comparison.added,
+                removed: comparison
+                    .removed
+                    .into_iter()
+                    .map(|(name, kind, signature)| RemovedData {
+                        name,
+                        kind,
+                        signature,
+                    })
+                    .collect(),
             };
 
             let mut handlebars = Handlebars::new();
@@ -718,14 +1344,19 @@ impl DocBuilder {
 
     fn build_proto_module(&self, name: &str, symbol: &Symbol) -> String {
         if let SymbolKind::ProtoModule(property) = &symbol.kind {
+            let tags = symbol.doc_comment.tags();
+            let comparison = self.compare(symbol);
             let parameters: Vec<_> = property
                 .parameters
                 .iter()
                 .filter(|x| matches!(x.property().kind, ParameterKind::Param,))
                 .map(|x| ParameterData {
                     name: x.name.to_string(),
-                    typ: format!("{}", x.property().r#type),
-                    description: get_comment_from_token(&x.property().token),
+                    source: self.source_url(&x.property().token),
+                    typ: self.format_type(&x.property().r#type, &symbol.inner_namespace()),
+                    description: tag_description(&tags, &x.name, false)
+                        .or_else(|| get_comment_from_token(&x.property().token)),
+                    change: comparison.params.get(&x.name.to_string()).cloned(),
                 })
                 .collect();
 
@@ -754,20 +1385,40 @@ impl DocBuilder {
                     };
                     PortData {
                         name: x.name.to_string(),
+                        source: self.source_url(&x.property().token),
                         direction: format!("{}", x.property().direction),
                         clock_domain,
-                        typ: x.property().r#type.as_ref().map(|x| format!("{}", x)),
-                        description: get_comment_from_token(&x.property().token),
+                        typ: x
+                            .property()
+                            .r#type
+                            .as_ref()
+                            .map(|x| self.format_type(x, &symbol.inner_namespace())),
+                        description: tag_description(&tags, &x.name, true)
+                            .or_else(|| get_comment_from_token(&x.property().token)),
+                        change: comparison.ports.get(&x.name.to_string()).cloned(),
                     }
                 })
                 .collect();
 
             let data = ProtoModuleData {
                 name: name.to_string(),
+                source: self.source_url(&symbol.token),
                 description: symbol.doc_comment.format(false),
                 parameters,
                 clock_domains,
                 ports,
+                examples: examples(&tags),
+                since: self.since(symbol),
+                added: comparison.added,
+                removed: comparison
+                    .removed
+                    .into_iter()
+                    .map(|(name, kind, signature)| RemovedData {
+                        name,
+                        kind,
+                        signature,
+                    })
+                    .collect(),
             };
 
             let mut handlebars = Handlebars::new();
@@ -782,21 +1433,39 @@ impl DocBuilder {
 
     fn build_interface(&self, name: &str, symbol: &Symbol) -> String {
         if let SymbolKind::Interface(property) = &symbol.kind {
+            let tags = symbol.doc_comment.tags();
+            let comparison = self.compare(symbol);
             let parameters: Vec<_> = property
                 .parameters
                 .iter()
                 .filter(|x| matches!(x.property().kind, ParameterKind::Param,))
                 .map(|x| ParameterData {
                     name: x.name.to_string(),
-                    typ: format!("{}", x.property().r#type),
-                    description: get_comment_from_token(&x.property().token),
+                    source: self.source_url(&x.property().token),
+                    typ: self.format_type(&x.property().r#type, &symbol.inner_namespace()),
+                    description: tag_description(&tags, &x.name, false)
+                        .or_else(|| get_comment_from_token(&x.property().token)),
+                    change: comparison.params.get(&x.name.to_string()).cloned(),
                 })
                 .collect();
 
             let data = InterfaceData {
                 name: name.to_string(),
+                source: self.source_url(&symbol.token),
                 description: symbol.doc_comment.format(false),
                 parameters,
+                examples: examples(&tags),
+                since: self.since(symbol),
+                added: comparison.added,
+                removed: comparison
+                    .removed
+                    .into_iter()
+                    .map(|(name, kind, signature)| RemovedData {
+                        name,
+                        kind,
+                        signature,
+                    })
+                    .collect(),
             };
 
             let mut handlebars = Handlebars::new();
@@ -809,9 +1478,22 @@ impl DocBuilder {
 
     fn build_package(&self, name: &str, symbol: &Symbol) -> String {
         if let SymbolKind::Package(_) = &symbol.kind {
+            let tags = symbol.doc_comment.tags();
+            let types = member_types(symbol)
+                .into_iter()
+                .map(|x| TypeData {
+                    name: x.token.to_string(),
+                    source: self.source_url(&x.token),
+                    kind: x.kind.to_kind_name(),
+                    description: x.doc_comment.format(true),
+                })
+                .collect();
             let data = PackageData {
                 name: name.to_string(),
+                source: self.source_url(&symbol.token),
                 description: symbol.doc_comment.format(false),
+                types,
+                examples: examples(&tags),
             };
 
             let mut handlebars = Handlebars::new();
@@ -823,6 +1505,82 @@ impl DocBuilder {
     }
 }
 
+fn build_list(name: &str, items: &[TopLevelItem]) -> String {
+    let items: Vec<_> = items
+        .iter()
+        .map(|x| ListItem {
+            file_name: x.file_name.clone(),
+            html_name: x.html_name.clone(),
+            description: x.symbol.doc_comment.format(true),
+        })
+        .collect();
+
+    let data = ListData {
+        name: name.to_string(),
+        items,
+    };
+
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.render_template(LIST_TMPL, &data).unwrap()
+}
+
+fn build_source(source: &SourceFile) -> String {
+    let data = SourceData {
+        title: source.title.clone(),
+        code: render_source(&source.text),
+    };
+
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.render_template(SOURCE_TMPL, &data).unwrap()
+}
+
+fn full_name(symbol: &Symbol) -> String {
+    format!("{}::{}", symbol.namespace, symbol.token)
+}
+
+/// Types declared in the package
+fn member_types(symbol: &Symbol) -> Vec<Symbol> {
+    let namespace = symbol.inner_namespace();
+    let mut ret: Vec<_> = symbol_table::get_all()
+        .into_iter()
+        .filter(|x| {
+            x.namespace == namespace
+                && matches!(
+                    x.kind,
+                    SymbolKind::Struct(_)
+                        | SymbolKind::Union(_)
+                        | SymbolKind::Enum(_)
+                        | SymbolKind::TypeDef(_)
+                )
+        })
+        .collect();
+    ret.sort_by_key(|x| x.token.to_string());
+    ret
+}
+
+fn tag_description(tags: &[DocTag], target: &StrId, port: bool) -> Option<String> {
+    let target = target.to_string();
+    tags.iter().find_map(|x| match x {
+        DocTag::Param { name, description } if !port && *name == target => {
+            Some(description.clone())
+        }
+        DocTag::Port { name, description } if port && *name == target => Some(description.clone()),
+        _ => None,
+    })
+}
+
+fn examples(tags: &[DocTag]) -> Vec<String> {
+    tags.iter()
+        .filter_map(|x| match x {
+            DocTag::Example(x) if x.contains("```") => Some(x.clone()),
+            DocTag::Example(x) => Some(format!("```veryl\n{}\n```", x)),
+            _ => None,
+        })
+        .collect()
+}
+
 fn get_comment_from_token(token: &Token) -> Option<String> {
     if let Ok(symbol) = symbol_table::resolve(token) {
         Some(symbol.found.doc_comment.format(false))
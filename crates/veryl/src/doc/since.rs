@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use veryl_analyzer::symbol::{ParameterKind, Symbol, SymbolKind};
+use veryl_analyzer::symbol_table;
+
+/// Parameters and ports of a top level item formatted to compare between versions
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Interface {
+    pub params: BTreeMap<String, String>,
+    pub ports: BTreeMap<String, String>,
+}
+
+impl Interface {
+    pub fn new(symbol: &Symbol) -> Option<Self> {
+        let (params, ports) = match &symbol.kind {
+            SymbolKind::Module(x) => (&x.parameters, x.ports.as_slice()),
+            SymbolKind::ProtoModule(x) => (&x.parameters, x.ports.as_slice()),
+            SymbolKind::Interface(x) => (&x.parameters, [].as_slice()),
+            _ => return None,
+        };
+
+        let params = params
+            .iter()
+            .filter(|x| matches!(x.property().kind, ParameterKind::Param))
+            .map(|x| (x.name.to_string(), x.property().r#type.to_string()))
+            .collect();
+        let ports = ports
+            .iter()
+            .map(|x| {
+                let property = x.property();
+                let text = match property.r#type {
+                    Some(ref t) => format!("{} {}", property.direction, t),
+                    None => property.direction.to_string(),
+                };
+                (x.name.to_string(), text)
+            })
+            .collect();
+
+        Some(Self { params, ports })
+    }
+}
+
+/// Collect interfaces of public items in the project
+pub fn interfaces(project: &str) -> BTreeMap<String, Interface> {
+    let mut ret = BTreeMap::new();
+    for symbol in symbol_table::get_all() {
+        if symbol.namespace.to_string() == project && symbol.public {
+            if let Some(x) = Interface::new(&symbol) {
+                ret.insert(symbol.token.to_string(), x);
+            }
+        }
+    }
+    ret
+}
+
+/// Interfaces of the previous version to annotate changes
+pub struct Since {
+    pub version: String,
+    pub interfaces: BTreeMap<String, Interface>,
+}
+
+#[derive(Default)]
+pub struct Comparison {
+    pub added: bool,
+    pub params: BTreeMap<String, String>,
+    pub ports: BTreeMap<String, String>,
+    pub removed: Vec<(String, String, String)>,
+}
+
+fn compare(
+    previous: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+    kind: &str,
+    removed: &mut Vec<(String, String, String)>,
+) -> BTreeMap<String, String> {
+    let mut ret = BTreeMap::new();
+    for (name, text) in current {
+        match previous.get(name) {
+            None => {
+                ret.insert(name.clone(), "new".to_string());
+            }
+            Some(x) if x != text => {
+                ret.insert(name.clone(), format!("changed from {x}"));
+            }
+            _ => (),
+        }
+    }
+    for (name, text) in previous {
+        if !current.contains_key(name) {
+            removed.push((name.clone(), kind.to_string(), text.clone()));
+        }
+    }
+    ret
+}
+
+impl Since {
+    pub fn compare(&self, name: &str, current: &Interface) -> Comparison {
+        let Some(previous) = self.interfaces.get(name) else {
+            return Comparison {
+                added: true,
+                ..Default::default()
+            };
+        };
+
+        let mut removed = Vec::new();
+        let params = compare(&previous.params, &current.params, "parameter", &mut removed);
+        let ports = compare(&previous.ports, &current.ports, "port", &mut removed);
+        Comparison {
+            added: false,
+            params,
+            ports,
+            removed,
+        }
+    }
+
+    /// Items of the previous version which are not in `current`
+    pub fn removed_items(&self, current: &BTreeMap<String, Interface>) -> Vec<String> {
+        self.interfaces
+            .keys()
+            .filter(|x| !current.contains_key(*x))
+            .cloned()
+            .collect()
+    }
+}
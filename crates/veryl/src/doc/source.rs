@@ -0,0 +1,168 @@
+use crate::doc::utils::escape_html;
+use std::path::PathBuf;
+
+/// Source file rendered into the doc
+#[derive(Clone)]
+pub struct SourceFile {
+    pub file_name: String,
+    pub title: String,
+    pub path: PathBuf,
+    pub text: String,
+}
+
+const KEYWORDS: &[&str] = &[
+    "always_comb",
+    "always_ff",
+    "as",
+    "assign",
+    "break",
+    "case",
+    "const",
+    "default",
+    "else",
+    "embed",
+    "enum",
+    "export",
+    "final",
+    "for",
+    "function",
+    "if",
+    "if_reset",
+    "import",
+    "in",
+    "include",
+    "initial",
+    "inout",
+    "input",
+    "inside",
+    "inst",
+    "interface",
+    "let",
+    "lsb",
+    "modport",
+    "module",
+    "msb",
+    "output",
+    "outside",
+    "package",
+    "param",
+    "proto",
+    "pub",
+    "ref",
+    "repeat",
+    "return",
+    "step",
+    "struct",
+    "switch",
+    "type",
+    "union",
+    "unsafe",
+    "var",
+];
+
+const TYPES: &[&str] = &[
+    "bit",
+    "clock",
+    "clock_negedge",
+    "clock_posedge",
+    "f32",
+    "f64",
+    "i32",
+    "i64",
+    "logic",
+    "reset",
+    "reset_async_high",
+    "reset_async_low",
+    "reset_sync_high",
+    "reset_sync_low",
+    "signed",
+    "string",
+    "tri",
+    "u32",
+    "u64",
+];
+
+fn span(class: &str, text: &str) -> String {
+    format!("<span class=\"{}\">{}</span>", class, escape_html(text))
+}
+
+/// Render Veryl source as highlighted HTML with `L<line>` anchors.
+/// `nohighlight` prevents highlight.js of mdbook from highlighting it again.
+pub fn render_source(text: &str) -> String {
+    let mut ret = String::from("<pre class=\"source\"><code class=\"hljs nohighlight\">");
+    let mut in_block_comment = false;
+
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        ret.push_str(&format!(
+            "<span id=\"L{number}\"><a class=\"source_line\" href=\"#L{number}\">{number:>4}</a>  "
+        ));
+
+        let chars: Vec<_> = line.char_indices().collect();
+        let mut pos = 0;
+        while pos < chars.len() {
+            let (start, c) = chars[pos];
+            let rest = &line[start..];
+            let end_of = |len: usize| start + len;
+
+            let (class, end) = if in_block_comment {
+                match rest.find("*/") {
+                    Some(x) => {
+                        in_block_comment = false;
+                        (Some("hljs-comment"), end_of(x + 2))
+                    }
+                    None => (Some("hljs-comment"), line.len()),
+                }
+            } else if rest.starts_with("//") {
+                (Some("hljs-comment"), line.len())
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                match comment.find("*/") {
+                    Some(x) => (Some("hljs-comment"), end_of(x + 4)),
+                    None => {
+                        in_block_comment = true;
+                        (Some("hljs-comment"), line.len())
+                    }
+                }
+            } else if c == '"' {
+                let len = rest[1..].find('"').map(|x| x + 2).unwrap_or(rest.len());
+                (Some("hljs-string"), end_of(len))
+            } else if c.is_ascii_digit() {
+                let len = rest
+                    .find(|x: char| !(x.is_ascii_alphanumeric() || matches!(x, '_' | '\'' | '.')))
+                    .unwrap_or(rest.len());
+                (Some("hljs-number"), end_of(len))
+            } else if c.is_ascii_alphabetic() || c == '_' || c == '$' {
+                let len = rest
+                    .find(|x: char| !(x.is_ascii_alphanumeric() || matches!(x, '_' | '$')))
+                    .unwrap_or(rest.len());
+                let word = &rest[..len];
+                let class = if KEYWORDS.contains(&word) {
+                    Some("hljs-keyword")
+                } else if TYPES.contains(&word) {
+                    Some("hljs-type")
+                } else if word.starts_with('$') {
+                    Some("hljs-built_in")
+                } else {
+                    None
+                };
+                (class, end_of(len))
+            } else {
+                (None, end_of(c.len_utf8()))
+            };
+
+            let token = &line[start..end];
+            match class {
+                Some(class) => ret.push_str(&span(class, token)),
+                None => ret.push_str(&escape_html(token)),
+            }
+            while pos < chars.len() && chars[pos].0 < end {
+                pos += 1;
+            }
+        }
+
+        ret.push_str("</span>\n");
+    }
+
+    ret.push_str("</code></pre>\n");
+    ret
+}
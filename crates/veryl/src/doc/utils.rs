@@ -11,3 +11,7 @@ pub fn escape_html(s: &str) -> String {
     }
     output
 }
+
+pub fn escape_script(s: &str) -> String {
+    s.replace("</", "<\\/")
+}
@@ -3,7 +3,7 @@
 // https://github.com/JasMoH/mdbook-wavedrom
 // Released under the MPL-2.0 license
 
-use crate::doc::utils::escape_html;
+use crate::doc::utils::escape_script;
 use mdbook::book::{Book, BookItem, Chapter};
 use mdbook::errors::Result;
 use mdbook::preprocess::{Preprocessor, PreprocessorContext};
@@ -39,7 +39,7 @@ impl Preprocessor for Wavedrom {
 }
 
 impl Wavedrom {
-    fn add_wavedrom(chapter: &mut Chapter) -> Result<String> {
+    pub(crate) fn add_wavedrom(chapter: &mut Chapter) -> Result<String> {
         let content = &chapter.content;
         let mut wavedrom_content = String::new();
         let mut in_wavedrom_block = false;
@@ -58,7 +58,7 @@ impl Wavedrom {
         let events = Parser::new_ext(content, opts);
         for (e, span) in events.into_offset_iter() {
             if let Event::Start(Tag::CodeBlock(Fenced(code))) = e.clone() {
-                if &*code == "wavedrom" {
+                if matches!(&*code, "wavedrom" | "wavejson") {
                     in_wavedrom_block = true;
                     wavedrom_content.clear();
                 }
@@ -87,7 +87,8 @@ impl Wavedrom {
                 in_wavedrom_block = false;
 
                 let wavedrom_content = &content[code_span.clone()];
-                let wavedrom_content = escape_html(wavedrom_content);
+                // Content of <script> is not decoded as HTML, so only closing tag is escaped
+                let wavedrom_content = escape_script(wavedrom_content);
                 let wavedrom_content = wavedrom_content.replace("\r\n", "\n");
                 let wavedrom_code = format!("<body onload=\"WaveDrom.ProcessAll()\">\n\n<script type=\"WaveDrom\">{}</script>\n\n", wavedrom_content);
                 wavedrom_blocks.push((span, wavedrom_code));
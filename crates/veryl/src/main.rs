@@ -1,30 +1,62 @@
-use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use clap_complete::aot::Shell;
 use console::Style;
 use fern::Dispatch;
-use log::debug;
+use log::{debug, info, warn};
 use log::{Level, LevelFilter};
 use miette::{IntoDiagnostic, Result};
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
 use std::process::ExitCode;
 use std::str::FromStr;
 use std::time::Instant;
-use veryl_metadata::Metadata;
+use veryl_analyzer::AnalyzerError;
+use veryl_metadata::semver::{Version, VersionReq};
+use veryl_metadata::{CancellationToken, Metadata, MetadataError};
 
+mod cmd_api_diff;
 mod cmd_build;
+mod cmd_bundle;
 mod cmd_check;
 mod cmd_clean;
+mod cmd_completions;
+mod cmd_diff;
 mod cmd_doc;
 mod cmd_dump;
+mod cmd_emit;
+mod cmd_equiv;
+mod cmd_expand;
+mod cmd_export_symbols;
 mod cmd_fmt;
+mod cmd_grep;
 mod cmd_init;
+mod cmd_man;
 mod cmd_metadata;
+mod cmd_migrate;
+mod cmd_mutate;
 mod cmd_new;
+mod cmd_parse;
+mod cmd_probes;
 mod cmd_publish;
+mod cmd_query;
+mod cmd_refactor;
+mod cmd_repl;
+mod cmd_report;
+mod cmd_self;
+mod cmd_stats;
 mod cmd_test;
+mod cmd_testgen;
 mod cmd_update;
+mod cmd_upf;
+mod coverage;
 mod doc;
+mod progress;
 mod runner;
+mod testgen;
+mod verify;
+
+#[cfg(test)]
+mod tests;
 
 // ---------------------------------------------------------------------------------------------------------------------
 // Opt
@@ -60,6 +92,18 @@ pub enum CompletionShell {
     Zsh,
 }
 
+impl From<CompletionShell> for Shell {
+    fn from(x: CompletionShell) -> Self {
+        match x {
+            CompletionShell::Bash => Shell::Bash,
+            CompletionShell::Elvish => Shell::Elvish,
+            CompletionShell::Fish => Shell::Fish,
+            CompletionShell::PowerShell => Shell::PowerShell,
+            CompletionShell::Zsh => Shell::Zsh,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     New(OptNew),
@@ -67,19 +111,50 @@ enum Commands {
     Fmt(OptFmt),
     Check(OptCheck),
     Build(OptBuild),
+    Emit(OptEmit),
+    Bundle(OptBundle),
     Clean(OptClean),
+    Diff(OptDiff),
+    ApiDiff(OptApiDiff),
+    Equiv(OptEquiv),
     Update(OptUpdate),
     Publish(OptPublish),
     Doc(OptDoc),
     Metadata(OptMetadata),
     Dump(OptDump),
+    Parse(OptParse),
+    Query(OptQuery),
+    Repl(OptRepl),
+    Expand(OptExpand),
+    Grep(OptGrep),
+    Refactor(OptRefactor),
+    Probes(OptProbes),
+    Upf(OptUpf),
+    ExportSymbols(OptExportSymbols),
+    Stats(OptStats),
+    Report(OptReport),
     Test(OptTest),
+    Mutate(OptMutate),
+    Testgen(OptTestgen),
+    Migrate(OptMigrate),
+    Completions(OptCompletions),
+    Man(OptMan),
+    #[command(name = "self")]
+    Toolchain(OptSelf),
 }
 
 /// Create a new project
 #[derive(Args)]
 pub struct OptNew {
     pub path: PathBuf,
+
+    /// Template git repository or local directory
+    #[arg(long)]
+    pub template: Option<String>,
+
+    /// License substituted to `{{license}}` in template
+    #[arg(long, requires = "template")]
+    pub license: Option<String>,
 }
 
 /// Create a new project in an existing directory
@@ -92,7 +167,7 @@ pub struct OptInit {
 /// Format the current project
 #[derive(Args)]
 pub struct OptFmt {
-    /// Target files
+    /// Target files or glob patterns like `src/**/*.veryl`
     pub files: Vec<PathBuf>,
 
     /// Run fmt in check mode
@@ -103,8 +178,20 @@ pub struct OptFmt {
 /// Analyze the current project
 #[derive(Args)]
 pub struct OptCheck {
-    /// Target files
+    /// Target files or glob patterns like `src/**/*.veryl`
     pub files: Vec<PathBuf>,
+
+    /// Treat warnings as errors
+    #[arg(long)]
+    pub deny_warnings: bool,
+
+    /// Baseline file suppressing recorded diagnostics (default: lint.baseline)
+    #[arg(long)]
+    pub baseline: Option<PathBuf>,
+
+    /// Record the current diagnostics to the baseline file
+    #[arg(long)]
+    pub write_baseline: Option<PathBuf>,
 }
 
 /// Build the target codes corresponding to the current project
@@ -112,12 +199,94 @@ pub struct OptCheck {
 pub struct OptBuild {
     /// Target files
     pub files: Vec<PathBuf>,
+
+    /// Treat warnings as errors
+    #[arg(long)]
+    pub deny_warnings: bool,
+
+    /// Check that brackets, blocks and directives of emitted SystemVerilog are balanced
+    /// (this is a lightweight structural check, not a full SystemVerilog parse)
+    #[arg(long)]
+    pub verify_output: bool,
+
+    /// Emit variants of the top module for combinations of parameters
+    #[arg(long)]
+    pub matrix: Option<PathBuf>,
+}
+
+/// Emit the target code of the specified module to stdout
+#[derive(Args)]
+pub struct OptEmit {
+    /// Module name
+    #[arg(long)]
+    pub module: String,
+
+    /// Emit modules, interfaces and packages the module depends on together
+    #[arg(long)]
+    pub deps: bool,
 }
 
+/// Build a distributable bundle of the current project
+#[derive(Args)]
+pub struct OptBundle {}
+
 /// Clean-up the current project
 #[derive(Args)]
 pub struct OptClean {}
 
+/// Compare the emitted codes with the given revision
+#[derive(Args)]
+pub struct OptDiff {
+    /// Base git revision
+    #[arg(long)]
+    pub base: String,
+
+    /// Show only names of changed units
+    #[arg(long)]
+    pub name_only: bool,
+}
+
+/// Compare the interface of modules with the given revision
+#[derive(Args)]
+pub struct OptApiDiff {
+    /// Base git revision (default: the last published release)
+    #[arg(long)]
+    pub base: Option<String>,
+}
+
+/// Generate scripts to check equivalence of modules with the given revision formally
+#[derive(Args)]
+pub struct OptEquiv {
+    /// Base git revision
+    #[arg(long)]
+    pub base: String,
+
+    /// Module name (default: all modules changed from the base revision)
+    #[arg(long = "module")]
+    pub modules: Vec<String>,
+
+    /// Equivalence checker
+    #[arg(long, value_enum, default_value_t)]
+    pub tool: EquivTool,
+
+    /// Output directory of scripts and emitted code
+    #[arg(long, default_value = "target/equiv")]
+    pub output: PathBuf,
+
+    /// Run the generated scripts
+    #[arg(long)]
+    pub run: bool,
+}
+
+#[derive(Clone, Copy, Default, Debug, ValueEnum)]
+pub enum EquivTool {
+    /// Yosys equiv_make flow
+    #[default]
+    Yosys,
+    /// Yosys EQY
+    Eqy,
+}
+
 /// Update dependencies
 #[derive(Args)]
 pub struct OptUpdate {}
@@ -135,6 +304,14 @@ pub struct OptPublish {
 pub struct OptDoc {
     /// Target files
     pub files: Vec<PathBuf>,
+
+    /// Document dependency projects too
+    #[arg(long)]
+    pub include_deps: bool,
+
+    /// Annotate changes of parameters and ports since the published version or git revision
+    #[arg(long, value_name = "VERSION|REV")]
+    pub since: Option<String>,
 }
 
 /// Execute tests
@@ -147,9 +324,109 @@ pub struct OptTest {
     #[arg(long, value_enum)]
     pub sim: Option<SimType>,
 
-    /// Dump waveform
+    /// Dump waveform (default format: test.waveform_format)
+    #[arg(long, alias = "wave", value_enum, num_args = 0..=1, require_equals = true)]
+    pub waves: Option<Option<WaveFormFormat>>,
+
+    /// Parameter override (NAME=VALUE) or plusarg (+ARG) passed to simulator
+    #[arg(long = "test-arg", value_name = "ARG", allow_hyphen_values = true)]
+    pub test_args: Vec<String>,
+
+    /// Collect and merge coverage of tests
     #[arg(long)]
-    pub wave: bool,
+    pub coverage: bool,
+
+    /// Compare emitted code of each module with snapshots instead of simulation
+    #[arg(long)]
+    pub emit_snapshots: bool,
+
+    /// Update snapshots by the current emitted code
+    #[arg(long, requires = "emit_snapshots")]
+    pub update: bool,
+
+    /// Compile Veryl code blocks in doc comments instead of simulation
+    #[arg(long, conflicts_with = "emit_snapshots")]
+    pub doc: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum WaveFormFormat {
+    /// Value Change Dump
+    Vcd,
+    /// Fast Signal Trace
+    Fst,
+}
+
+impl From<WaveFormFormat> for veryl_metadata::WaveFormFormat {
+    fn from(x: WaveFormFormat) -> Self {
+        match x {
+            WaveFormFormat::Vcd => veryl_metadata::WaveFormFormat::Vcd,
+            WaveFormFormat::Fst => veryl_metadata::WaveFormFormat::Fst,
+        }
+    }
+}
+
+/// Evaluate tests by mutating emitted code
+#[derive(Args)]
+pub struct OptMutate {
+    /// Target files
+    pub files: Vec<PathBuf>,
+
+    /// Simulator
+    #[arg(long, value_enum)]
+    pub sim: Option<SimType>,
+
+    /// Maximum number of mutants
+    #[arg(long)]
+    pub max: Option<usize>,
+
+    /// Directory to write surviving mutants
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// Parameter override (NAME=VALUE) or plusarg (+ARG) passed to simulator
+    #[arg(long = "test-arg", value_name = "ARG", allow_hyphen_values = true)]
+    pub test_args: Vec<String>,
+}
+
+/// Generate a testbench driving randomized stimulus to a module
+#[derive(Args)]
+pub struct OptTestgen {
+    /// Module name
+    pub module: String,
+
+    /// Constraint description of stimulus
+    #[arg(long)]
+    pub constraints: Option<PathBuf>,
+
+    /// Seed of random generator
+    #[arg(long, default_value_t = 1)]
+    pub seed: u64,
+
+    /// Number of stimulus cycles
+    #[arg(long, default_value_t = 100)]
+    pub cycles: usize,
+
+    /// Testbench language
+    #[arg(long, value_enum, default_value_t)]
+    pub lang: TestgenLang,
+
+    /// Generate connectivity test toggling each input instead of randomized stimulus
+    #[arg(long, conflicts_with = "constraints")]
+    pub connectivity: bool,
+
+    /// Output file (default: stdout)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Default, Debug, ValueEnum)]
+pub enum TestgenLang {
+    /// SystemVerilog testbench
+    #[default]
+    Sv,
+    /// cocotb test
+    Python,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -241,24 +518,410 @@ pub struct OptDump {
     /// output unsafe table
     #[arg(long)]
     pub unsafe_table: bool,
+
+    /// output call graph
+    #[arg(long)]
+    pub call_graph: bool,
+
+    /// output instance graph
+    #[arg(long)]
+    pub instance_graph: bool,
+}
+
+/// Parse a file and dump the syntax tree, AST or tokens
+#[derive(Args)]
+pub struct OptParse {
+    /// Target file
+    pub file: PathBuf,
+
+    /// Output format
+    #[arg(long, alias = "emit", value_enum, default_value_t)]
+    pub format: ParseFormat,
+
+    /// Output file (default: stdout)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Default, Debug, ValueEnum)]
+pub enum ParseFormat {
+    /// Concrete syntax tree including all tokens and comments with spans as JSON
+    #[default]
+    CstJson,
+    /// Typed AST in indented form
+    Ast,
+    /// Token stream with spans and kinds
+    Tokens,
+}
+
+/// Query analysis results of the current project
+#[derive(Args)]
+pub struct OptQuery {
+    #[command(subcommand)]
+    pub command: QueryCommand,
+}
+
+#[derive(Subcommand)]
+pub enum QueryCommand {
+    /// Show call sites of the function
+    Callers {
+        /// Function name (e.g. `FuncA` or `ModuleA::FuncA`)
+        name: String,
+    },
+    /// Show modules which have the same structure in the project and dependencies
+    Duplicates {
+        /// Minimum number of tokens of modules to be reported
+        #[arg(long, default_value_t = 50)]
+        min_size: usize,
+    },
+}
+
+/// Evaluate constant expressions and resolve identifiers of the current project interactively
+#[derive(Args)]
+pub struct OptRepl {
+    /// Initial scope (e.g. `ModuleA` or `PackageA`)
+    #[arg(long)]
+    pub scope: Option<String>,
+}
+
+/// Show a module with generate declarations expanded by the parameter values
+#[derive(Args)]
+pub struct OptExpand {
+    /// Target module
+    pub module: String,
+
+    /// Parameter value overriding the default like `WIDTH=16` (can be specified multiple times)
+    #[arg(long = "param", value_parser = parse_param)]
+    pub params: Vec<(String, String)>,
+}
+
+fn parse_param(s: &str) -> std::result::Result<(String, String), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("'{s}' is not formatted as `NAME=VALUE`"))?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+/// Search symbols of the current project by name and kind
+#[derive(Args)]
+pub struct OptGrep {
+    /// Regular expression matched to symbol names
+    pub pattern: String,
+
+    /// Target files
+    pub files: Vec<PathBuf>,
+
+    /// Kind of symbols (can be specified multiple times)
+    #[arg(long = "kind", value_enum)]
+    pub kinds: Vec<GrepKind>,
+
+    /// Direction of ports
+    #[arg(long, value_enum)]
+    pub direction: Option<GrepDirection>,
+
+    /// Locations to be searched
+    #[arg(long, value_enum, default_value_t)]
+    pub scope: GrepScope,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum GrepKind {
+    Port,
+    Variable,
+    Parameter,
+    Module,
+    Interface,
+    Package,
+    Function,
+    Instance,
+    Modport,
+    Struct,
+    Union,
+    Enum,
+    EnumMember,
+    #[value(name = "typedef")]
+    TypeDef,
+    Genvar,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum GrepDirection {
+    Input,
+    Output,
+    Inout,
+    Ref,
+    Modport,
+    Import,
+}
+
+#[derive(Clone, Copy, Default, Debug, ValueEnum)]
+pub enum GrepScope {
+    /// Declarations and references
+    #[default]
+    All,
+    /// Declarations only
+    Declarations,
+    /// References only
+    References,
+}
+
+/// Apply refactoring to the source code
+#[derive(Args)]
+pub struct OptRefactor {
+    #[command(subcommand)]
+    pub command: RefactorCommand,
+}
+
+#[derive(Subcommand)]
+pub enum RefactorCommand {
+    /// Move declarations in a module to a new module and instantiate it
+    ExtractModule {
+        /// Target file
+        #[arg(long)]
+        file: PathBuf,
+
+        /// Line range of the declarations like `20:80`
+        #[arg(long, value_parser = parse_lines)]
+        lines: RangeInclusive<u32>,
+
+        /// Name of the new module
+        #[arg(long)]
+        name: String,
+    },
+    /// Replace an instance of a module used only once by the module body
+    InlineInstance {
+        /// Target file
+        #[arg(long)]
+        file: PathBuf,
+
+        /// Name of the instance
+        #[arg(long)]
+        instance: String,
+    },
+    /// Change the type of a signal or parameter and the declarations depending on it
+    ChangeType {
+        /// Target signal or parameter like `ModuleA::a`
+        #[arg(long)]
+        target: String,
+
+        /// New type like `logic<16>`
+        #[arg(long = "type")]
+        r#type: String,
+    },
+    /// Rename symbols violating the naming rules to the compliant names
+    ApplyNaming {
+        /// Show the renaming without modifying files
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+fn parse_lines(s: &str) -> std::result::Result<RangeInclusive<u32>, String> {
+    let (start, end) = s
+        .split_once(':')
+        .ok_or_else(|| format!("'{s}' is not formatted as `START:END`"))?;
+    let start: u32 = start
+        .parse()
+        .map_err(|_| format!("invalid line: {start}"))?;
+    let end: u32 = end.parse().map_err(|_| format!("invalid line: {end}"))?;
+    Ok(start..=end)
+}
+
+/// Write hierarchical paths of signals in the design as a probe list
+#[derive(Args)]
+pub struct OptProbes {
+    /// Top module
+    #[arg(long)]
+    pub top: String,
+
+    /// Pattern of hierarchical paths like `*.state`, where `*` matches any characters
+    #[arg(long = "match")]
+    pub patterns: Vec<String>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    pub format: ProbesFormat,
+
+    /// Output file (default: stdout)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Default, Debug, ValueEnum)]
+pub enum ProbesFormat {
+    /// Hierarchical paths separated by `.`
+    #[default]
+    List,
+    /// `MARK_DEBUG` constraints of Vivado to connect nets to ILA
+    Xdc,
+    /// `force` commands of simulators like Questa and Xcelium
+    Force,
+}
+
+/// Write UPF skeleton of power domains given by `#[power_domain]`
+#[derive(Args)]
+pub struct OptUpf {
+    /// Top module
+    #[arg(long)]
+    pub top: String,
+
+    /// Output file (default: stdout)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Export the symbol table as JSON
+///
+/// The root object has `format_version`, `project`, `symbols` and `assignments`.
+/// Each symbol has `id`, `name`, `kind`, `detail`, `namespace`, `type`, `public`,
+/// `doc_comment`, `location` and `references`, and each assignment has `path`,
+/// `symbols`, `partial` and `position`.
+/// A location has `file`, `line`, `column`, `length` and `text`.
+#[derive(Args)]
+pub struct OptExportSymbols {
+    /// Target files
+    pub files: Vec<PathBuf>,
+
+    /// Output file (default: stdout)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Show statistics of the current project
+#[derive(Args)]
+pub struct OptStats {
+    /// Target files
+    pub files: Vec<PathBuf>,
+
+    /// output format
+    #[arg(long, value_enum, default_value_t)]
+    pub format: StatsFormat,
+}
+
+#[derive(Clone, Copy, Default, Debug, ValueEnum)]
+pub enum StatsFormat {
+    #[default]
+    Pretty,
+    Json,
+    /// OpenMetrics text format for Prometheus
+    #[value(name = "openmetrics")]
+    OpenMetrics,
+}
+
+/// Output a build report of diagnostics and formatting compliance
+#[derive(Args)]
+pub struct OptReport {
+    /// Target files
+    pub files: Vec<PathBuf>,
+
+    /// output format
+    #[arg(long, value_enum, default_value_t)]
+    pub format: ReportFormat,
+
+    /// Output file (default: stdout)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// Previous JSON report to show trends
+    #[arg(long)]
+    pub previous: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Default, Debug, ValueEnum)]
+pub enum ReportFormat {
+    #[default]
+    Html,
+    Json,
+}
+
+/// Convert SystemVerilog files to Veryl
+#[derive(Args)]
+pub struct OptMigrate {
+    /// Target SystemVerilog files
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Output directory (default: the same directory as each file)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// Resolve `define, `ifdef and `include before conversion
+    #[arg(long)]
+    pub preprocess: bool,
+
+    /// Macro definition for preprocessor (e.g. `NAME` or `NAME=VALUE`)
+    #[arg(long = "define", requires = "preprocess")]
+    pub defines: Vec<String>,
+
+    /// Include directory for preprocessor
+    #[arg(long = "include-dir", requires = "preprocess")]
+    pub include_dirs: Vec<PathBuf>,
+}
+
+/// Generate shell completion script to stdout
+#[derive(Args)]
+pub struct OptCompletions {
+    /// Target shell
+    #[arg(value_enum)]
+    pub shell: CompletionShell,
+}
+
+/// Generate man pages
+#[derive(Args)]
+pub struct OptMan {
+    /// Output directory of pages for all subcommands (default: the page of veryl to stdout)
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Manage Veryl toolchain
+#[derive(Args)]
+pub struct OptSelf {
+    #[command(subcommand)]
+    pub command: SelfCommand,
+}
+
+#[derive(Subcommand)]
+pub enum SelfCommand {
+    /// Update Veryl toolchain by `cargo install`
+    Update {
+        /// Version requirement (default: `veryl_version` of the current project or the latest)
+        #[arg(long)]
+        requirement: Option<VersionReq>,
+    },
 }
 
 // ---------------------------------------------------------------------------------------------------------------------
 // Main
 // ---------------------------------------------------------------------------------------------------------------------
 
+fn handle_ctrl_c(cancellation: CancellationToken) {
+    std::thread::spawn(move || {
+        let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        else {
+            return;
+        };
+        runtime.block_on(async {
+            // The first Ctrl-C cancels the operation at the next safe point,
+            // and the second one exits immediately
+            if tokio::signal::ctrl_c().await.is_ok() {
+                warn!("Cancelling (press Ctrl-C again to exit immediately)");
+                cancellation.cancel();
+            }
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        });
+    });
+}
+
 fn main() -> Result<ExitCode> {
     let opt = Opt::parse();
 
     if let Some(shell) = opt.completion {
-        let shell = match shell {
-            CompletionShell::Bash => Shell::Bash,
-            CompletionShell::Elvish => Shell::Elvish,
-            CompletionShell::Fish => Shell::Fish,
-            CompletionShell::PowerShell => Shell::PowerShell,
-            CompletionShell::Zsh => Shell::Zsh,
-        };
-        clap_complete::generate(shell, &mut Opt::command(), "veryl", &mut std::io::stdout());
+        cmd_completions::generate(shell, &mut std::io::stdout());
         return Ok(ExitCode::SUCCESS);
     }
 
@@ -298,11 +961,34 @@ fn main() -> Result<ExitCode> {
         .apply()
         .into_diagnostic()?;
 
+    let dummy_metadata = || {
+        let metadata = Metadata::create_default_toml("dummy").unwrap();
+        Metadata::from_str(&metadata)
+    };
+
     let mut metadata = match opt.command {
-        Commands::New(_) | Commands::Init(_) => {
-            // dummy metadata
-            let metadata = Metadata::create_default_toml("dummy").unwrap();
-            Metadata::from_str(&metadata)?
+        Commands::New(_) | Commands::Init(_) | Commands::Completions(_) | Commands::Man(_) => {
+            dummy_metadata()?
+        }
+        Commands::Migrate(_) | Commands::Parse(_) | Commands::Toolchain(_) => {
+            // Settings of the current project are used if exists
+            match Metadata::search_from_current().and_then(Metadata::load) {
+                Ok(x) => x,
+                Err(_) => dummy_metadata()?,
+            }
+        }
+        Commands::Fmt(OptFmt { ref files, .. }) | Commands::Check(OptCheck { ref files, .. })
+            if !files.is_empty() =>
+        {
+            // Explicit files can be processed without project
+            match Metadata::search_from_current() {
+                Ok(x) => Metadata::load(x)?,
+                Err(MetadataError::FileNotFound) => {
+                    info!("Veryl.toml is not found, so the default settings are used");
+                    Metadata::standalone()?
+                }
+                Err(x) => return Err(x.into()),
+            }
         }
         _ => {
             let metadata_path = Metadata::search_from_current()?;
@@ -310,6 +996,35 @@ fn main() -> Result<ExitCode> {
         }
     };
 
+    let version = Version::parse(env!("CARGO_PKG_VERSION")).into_diagnostic()?;
+    if let Err(x) = metadata.check_veryl_version(&version) {
+        match opt.command {
+            Commands::Build(_) | Commands::Bundle(_) | Commands::Publish(_) => return Err(x.into()),
+            Commands::Toolchain(_) => (),
+            _ => warn!("{x}"),
+        }
+    }
+
+    for x in AnalyzerError::unknown_severity_codes(&metadata.lint) {
+        warn!("Unknown code \"{x}\" in [lint.severity] is ignored");
+    }
+
+    // Commands which can be aborted gracefully at Ctrl-C
+    if matches!(
+        opt.command,
+        Commands::Check(_)
+            | Commands::Build(_)
+            | Commands::Emit(_)
+            | Commands::Update(_)
+            | Commands::Report(_)
+            | Commands::Diff(_)
+            | Commands::ApiDiff(_)
+            | Commands::Equiv(_)
+            | Commands::ExportSymbols(_)
+    ) {
+        handle_ctrl_c(metadata.cancellation.clone());
+    }
+
     let now = Instant::now();
 
     let ret = match opt.command {
@@ -318,13 +1033,37 @@ fn main() -> Result<ExitCode> {
         Commands::Fmt(x) => cmd_fmt::CmdFmt::new(x).exec(&mut metadata)?,
         Commands::Check(x) => cmd_check::CmdCheck::new(x).exec(&mut metadata)?,
         Commands::Build(x) => cmd_build::CmdBuild::new(x).exec(&mut metadata)?,
+        Commands::Emit(x) => cmd_emit::CmdEmit::new(x).exec(&mut metadata)?,
+        Commands::Bundle(x) => cmd_bundle::CmdBundle::new(x).exec(&mut metadata)?,
         Commands::Clean(x) => cmd_clean::CmdClean::new(x).exec(&mut metadata)?,
+        Commands::Diff(x) => cmd_diff::CmdDiff::new(x).exec(&mut metadata)?,
+        Commands::ApiDiff(x) => cmd_api_diff::CmdApiDiff::new(x).exec(&mut metadata)?,
+        Commands::Equiv(x) => cmd_equiv::CmdEquiv::new(x).exec(&mut metadata)?,
         Commands::Update(x) => cmd_update::CmdUpdate::new(x).exec(&mut metadata)?,
         Commands::Publish(x) => cmd_publish::CmdPublish::new(x).exec(&mut metadata)?,
         Commands::Doc(x) => cmd_doc::CmdDoc::new(x).exec(&mut metadata)?,
         Commands::Metadata(x) => cmd_metadata::CmdMetadata::new(x).exec(&metadata)?,
         Commands::Dump(x) => cmd_dump::CmdDump::new(x).exec(&mut metadata)?,
+        Commands::Parse(x) => cmd_parse::CmdParse::new(x).exec()?,
+        Commands::Query(x) => cmd_query::CmdQuery::new(x).exec(&mut metadata)?,
+        Commands::Repl(x) => cmd_repl::CmdRepl::new(x).exec(&mut metadata)?,
+        Commands::Expand(x) => cmd_expand::CmdExpand::new(x).exec(&mut metadata)?,
+        Commands::Grep(x) => cmd_grep::CmdGrep::new(x).exec(&mut metadata)?,
+        Commands::Refactor(x) => cmd_refactor::CmdRefactor::new(x).exec(&mut metadata)?,
+        Commands::Probes(x) => cmd_probes::CmdProbes::new(x).exec(&mut metadata)?,
+        Commands::Upf(x) => cmd_upf::CmdUpf::new(x).exec(&mut metadata)?,
+        Commands::ExportSymbols(x) => {
+            cmd_export_symbols::CmdExportSymbols::new(x).exec(&mut metadata)?
+        }
+        Commands::Stats(x) => cmd_stats::CmdStats::new(x).exec(&mut metadata)?,
+        Commands::Report(x) => cmd_report::CmdReport::new(x).exec(&mut metadata)?,
         Commands::Test(x) => cmd_test::CmdTest::new(x).exec(&mut metadata)?,
+        Commands::Mutate(x) => cmd_mutate::CmdMutate::new(x).exec(&mut metadata)?,
+        Commands::Testgen(x) => cmd_testgen::CmdTestgen::new(x).exec(&mut metadata)?,
+        Commands::Migrate(x) => cmd_migrate::CmdMigrate::new(x).exec(&metadata)?,
+        Commands::Completions(x) => cmd_completions::CmdCompletions::new(x).exec()?,
+        Commands::Man(x) => cmd_man::CmdMan::new(x).exec()?,
+        Commands::Toolchain(x) => cmd_self::CmdSelf::new(x).exec(&metadata)?,
     };
 
     let elapsed_time = now.elapsed();
@@ -0,0 +1,64 @@
+use console::{Style, Term};
+use log::{debug, log_enabled, Level};
+use std::path::Path;
+
+/// Progress line of a long operation updated in place on terminal
+pub struct Progress {
+    phase: &'static str,
+    total: usize,
+    count: usize,
+    term: Option<Term>,
+}
+
+impl Progress {
+    pub fn new(phase: &'static str, total: usize) -> Self {
+        let term = Term::stderr();
+
+        // Disable if stderr is redirected or debug logs may be interleaved
+        let enabled = term.is_term() && log_enabled!(Level::Info) && !log_enabled!(Level::Debug);
+
+        Self {
+            phase,
+            total,
+            count: 0,
+            term: enabled.then_some(term),
+        }
+    }
+
+    pub fn inc(&mut self, path: &Path) {
+        self.count += 1;
+
+        if let Some(ref term) = self.term {
+            let name = path
+                .file_name()
+                .map(|x| x.to_string_lossy())
+                .unwrap_or_default();
+            let line = format!(
+                "{} {:>12} [{}/{}] {}",
+                Style::new().green().bright().apply_to("[INFO ]"),
+                self.phase,
+                self.count,
+                self.total,
+                name
+            );
+            let width = term.size().1 as usize;
+            let line = console::truncate_str(&line, width.saturating_sub(1), "...");
+            let _ = term.clear_line();
+            let _ = term.write_str(&line);
+        }
+    }
+
+    /// Clears the progress line before other outputs
+    pub fn clear(&self) {
+        if let Some(ref term) = self.term {
+            let _ = term.clear_line();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.clear();
+        debug!("{} done ({}/{} files)", self.phase, self.count, self.total);
+    }
+}
@@ -1,11 +1,11 @@
 use anstyle::{AnsiColor, Style};
-use log::{debug, log_enabled, Level};
+use log::{debug, info, log_enabled, Level};
 use miette::{IntoDiagnostic, Result};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::fs;
 use std::path::{Path, PathBuf};
-use veryl_metadata::{Metadata, WaveFormTarget};
+use veryl_metadata::{Metadata, TestArgs, WaveFormFormat, WaveFormTarget};
 use veryl_parser::resource_table::{PathId, StrId};
 use veryl_sourcemap::SourceMap;
 
@@ -18,6 +18,14 @@ pub use vcs::*;
 pub use verilator::*;
 pub use vivado::*;
 
+/// Options of a test execution
+pub struct RunOption {
+    pub wave: Option<WaveFormFormat>,
+    pub args: TestArgs,
+    /// Directory to write the coverage database
+    pub coverage: Option<PathBuf>,
+}
+
 pub trait Runner {
     fn run(
         &mut self,
@@ -25,7 +33,7 @@ pub trait Runner {
         test: StrId,
         top: Option<StrId>,
         path: PathId,
-        wave: bool,
+        option: &RunOption,
     ) -> Result<bool>;
 
     fn name(&self) -> &'static str;
@@ -105,14 +113,21 @@ pub fn copy_wave(
     test_path: PathId,
     metadata: &Metadata,
     work_path: &Path,
+    format: WaveFormFormat,
 ) -> Result<()> {
+    // $dumpfile in emitted code always specifies ".vcd" even if the format is FST
     let wave_src_path = work_path.join(format!("{}.vcd", test_name));
+    let wave_name = format!("{}.{}", test_name, format.extension());
     let wave_dst_path = match &metadata.test.waveform_target {
         WaveFormTarget::Target => {
             let target = PathBuf::from(test_path.to_string());
-            target.parent().unwrap().join(format!("{}.vcd", test_name))
+            target.parent().unwrap().join(wave_name)
         }
-        WaveFormTarget::Directory { path } => path.join(format!("{}.vcd", test_name)),
+        WaveFormTarget::Directory { path } => metadata
+            .project_path()
+            .join(path)
+            .join(test_name.to_string())
+            .join(wave_name),
     };
 
     let wave_dst_dir = wave_dst_path.parent().unwrap();
@@ -120,6 +135,7 @@ pub fn copy_wave(
         fs::create_dir_all(wave_dst_dir).into_diagnostic()?;
     }
 
-    fs::copy(wave_src_path, wave_dst_path).into_diagnostic()?;
+    fs::copy(wave_src_path, &wave_dst_path).into_diagnostic()?;
+    info!("Dumped waveform ({})", wave_dst_path.to_string_lossy());
     Ok(())
 }
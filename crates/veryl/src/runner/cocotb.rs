@@ -1,14 +1,15 @@
-use crate::runner::Runner;
+use crate::runner::{RunOption, Runner};
 use futures::prelude::*;
 use log::{error, info};
-use miette::{IntoDiagnostic, Result, WrapErr};
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
 use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::path::PathBuf;
 use std::process::Stdio;
 use tokio::process::{Child, Command};
 use tokio::runtime::Runtime;
 use tokio_util::codec::{FramedRead, LinesCodec};
-use veryl_metadata::Metadata;
+use veryl_metadata::{Metadata, SimType};
 use veryl_parser::resource_table::{self, PathId, StrId};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -23,18 +24,21 @@ enum State {
 pub enum CocotbSource {
     Embed(StrId),
     Include(StrId),
+    File(PathBuf),
 }
 
 pub struct Cocotb {
     source: CocotbSource,
+    sim_type: SimType,
     state: State,
     success: bool,
 }
 
 impl Cocotb {
-    pub fn new(source: CocotbSource) -> Self {
+    pub fn new(source: CocotbSource, sim_type: SimType) -> Self {
         Self {
             source,
+            sim_type,
             state: State::Idle,
             success: true,
         }
@@ -96,6 +100,11 @@ impl Cocotb {
     }
 }
 
+fn python_list(x: &[String]) -> String {
+    let items: Vec<_> = x.iter().map(|x| format!("{x:?}")).collect();
+    format!("[{}]", items.join(","))
+}
+
 impl Runner for Cocotb {
     fn run(
         &mut self,
@@ -103,10 +112,16 @@ impl Runner for Cocotb {
         test: StrId,
         top: Option<StrId>,
         path: PathId,
-        _wave: bool,
+        option: &RunOption,
     ) -> Result<bool> {
         self.success = true;
 
+        let simulator = match self.sim_type {
+            SimType::Verilator => "verilator",
+            SimType::Vcs => "vcs",
+            SimType::Vivado => bail!("Vivado Simulator is not supported by cocotb"),
+        };
+
         let temp_dir = tempfile::tempdir().into_diagnostic()?;
 
         info!("Executing test ({})", test);
@@ -136,6 +151,9 @@ impl Runner for Cocotb {
                     .join(x.to_string().trim_matches('"'));
                 fs::copy(include_path, src_path).into_diagnostic()?;
             }
+            CocotbSource::File(ref x) => {
+                fs::copy(x, src_path).into_diagnostic()?;
+            }
         }
 
         let file_list = fs::read_to_string(metadata.filelist_path()).into_diagnostic()?;
@@ -146,6 +164,16 @@ impl Runner for Cocotb {
         sources = format!("[{}]", sources.strip_suffix(',').unwrap());
 
         let module = format!("{}_{}", metadata.project.name, top.unwrap());
+        let build_args = python_list(&metadata.test.cocotb.build_args);
+        let test_args = python_list(&metadata.test.cocotb.test_args);
+        let params: Vec<_> = option
+            .args
+            .params
+            .iter()
+            .map(|(name, value)| format!("{name:?}:{value}"))
+            .collect();
+        let params = format!("{{{}}}", params.join(","));
+        let plusargs = python_list(&option.args.plusargs);
 
         let runner_path = temp_dir.path().join("runner.py");
         let runner_text = format!(
@@ -155,16 +183,20 @@ import cocotb.runner
 
 sources = {sources}
 
-runner = cocotb.runner.get_runner("verilator")
+runner = cocotb.runner.get_runner("{simulator}")
 runner.build(
     verilog_sources=sources,
     hdl_toplevel="{module}",
+    build_args={build_args},
+    parameters={params},
     always=True,
 )
 
 runner.test(
     hdl_toplevel="{module}",
     test_module="{test},",
+    test_args={test_args},
+    plusargs={plusargs},
 )
 "#
         );
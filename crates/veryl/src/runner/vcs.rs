@@ -1,14 +1,14 @@
-use crate::runner::{copy_wave, remap_msg_by_regex, Runner};
+use crate::runner::{copy_wave, remap_msg_by_regex, RunOption, Runner};
 use futures::prelude::*;
 use log::{error, info};
-use miette::{IntoDiagnostic, Result, WrapErr};
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::process::Stdio;
 use tokio::process::{Child, Command};
 use tokio::runtime::Runtime;
 use tokio_util::codec::{FramedRead, LinesCodec};
-use veryl_metadata::Metadata;
+use veryl_metadata::{Metadata, WaveFormFormat};
 use veryl_parser::resource_table::{PathId, StrId};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -118,10 +118,14 @@ impl Runner for Vcs {
         test: StrId,
         _top: Option<StrId>,
         path: PathId,
-        wave: bool,
+        option: &RunOption,
     ) -> Result<bool> {
         self.success = true;
 
+        if option.wave == Some(WaveFormFormat::Fst) {
+            bail!("FST waveform is not supported by VCS");
+        }
+
         let temp_dir = tempfile::tempdir().into_diagnostic()?;
 
         info!("Compiling test ({})", test);
@@ -131,13 +135,29 @@ impl Runner for Vcs {
             metadata.project.name, test
         )];
 
-        if wave {
+        if option.wave.is_some() {
             defines.push(format!(
                 "+define+__veryl_wavedump_{}_{}__",
                 metadata.project.name, test
             ));
         }
 
+        let params: Vec<_> = option
+            .args
+            .params
+            .iter()
+            .map(|(name, value)| format!("-pvalue+{test}/{name}={value}"))
+            .collect();
+
+        let mut coverage = Vec::new();
+        if let Some(ref dir) = option.coverage {
+            let db = dir.join(format!("{}.vdb", test));
+            coverage.push("-cm".to_string());
+            coverage.push("line+tgl".to_string());
+            coverage.push("-cm_dir".to_string());
+            coverage.push(db.to_string_lossy().to_string());
+        }
+
         let rt = Runtime::new().unwrap();
 
         rt.block_on(async {
@@ -146,6 +166,8 @@ impl Runner for Vcs {
                 .arg("-f")
                 .arg(metadata.filelist_path())
                 .args(&defines)
+                .args(&params)
+                .args(&coverage)
                 .args(&metadata.test.vcs.compile_args)
                 .current_dir(temp_dir.path())
                 .stdout(Stdio::piped())
@@ -166,6 +188,8 @@ impl Runner for Vcs {
 
         rt.block_on(async {
             let simulate = Command::new("./simv")
+                .args(&option.args.plusargs)
+                .args(&coverage)
                 .args(&metadata.test.vcs.simulate_args)
                 .current_dir(temp_dir.path())
                 .stdout(Stdio::piped())
@@ -177,8 +201,8 @@ impl Runner for Vcs {
             self.parse(simulate).await
         })?;
 
-        if wave {
-            copy_wave(test, path, metadata, temp_dir.path())?;
+        if let Some(format) = option.wave {
+            copy_wave(test, path, metadata, temp_dir.path(), format)?;
         }
 
         if self.success {
@@ -1,4 +1,4 @@
-use crate::runner::{copy_wave, remap_msg_by_regex, Runner};
+use crate::runner::{copy_wave, remap_msg_by_regex, RunOption, Runner};
 use futures::prelude::*;
 use log::{error, info};
 use miette::{IntoDiagnostic, Result, WrapErr};
@@ -8,7 +8,7 @@ use std::process::Stdio;
 use tokio::process::{Child, Command};
 use tokio::runtime::Runtime;
 use tokio_util::codec::{FramedRead, LinesCodec};
-use veryl_metadata::Metadata;
+use veryl_metadata::{Metadata, WaveFormFormat};
 use veryl_parser::resource_table::{PathId, StrId};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -128,7 +128,7 @@ impl Runner for Verilator {
         test: StrId,
         _top: Option<StrId>,
         path: PathId,
-        wave: bool,
+        option: &RunOption,
     ) -> Result<bool> {
         self.success = true;
 
@@ -141,7 +141,7 @@ impl Runner for Verilator {
             metadata.project.name, test
         )];
 
-        if wave {
+        if option.wave.is_some() {
             defines.push(format!(
                 "+define+__veryl_wavedump_{}_{}__",
                 metadata.project.name, test
@@ -150,10 +150,26 @@ impl Runner for Verilator {
 
         let mut opt = vec!["--assert", "--binary", "-Wno-MULTITOP"];
 
-        if wave {
-            opt.push("--trace");
+        match option.wave {
+            Some(WaveFormFormat::Vcd) => opt.push("--trace"),
+            Some(WaveFormFormat::Fst) => opt.push("--trace-fst"),
+            None => (),
         }
 
+        let mut plusargs = option.args.plusargs.clone();
+        if let Some(ref dir) = option.coverage {
+            opt.push("--coverage");
+            let db = dir.join(format!("{}.dat", test));
+            plusargs.push(format!("+verilator+coverage+file+{}", db.to_string_lossy()));
+        }
+
+        let params: Vec<_> = option
+            .args
+            .params
+            .iter()
+            .map(|(name, value)| format!("-G{name}={value}"))
+            .collect();
+
         let rt = Runtime::new().unwrap();
 
         rt.block_on(async {
@@ -164,6 +180,7 @@ impl Runner for Verilator {
                 .arg("-o")
                 .arg("simv")
                 .args(&defines)
+                .args(&params)
                 .args(&metadata.test.verilator.compile_args)
                 .current_dir(temp_dir.path())
                 .stdout(Stdio::piped())
@@ -184,6 +201,7 @@ impl Runner for Verilator {
 
         rt.block_on(async {
             let simulate = Command::new("./obj_dir/simv")
+                .args(&plusargs)
                 .args(&metadata.test.verilator.simulate_args)
                 .current_dir(temp_dir.path())
                 .stdout(Stdio::piped())
@@ -195,8 +213,8 @@ impl Runner for Verilator {
             self.parse(simulate).await
         })?;
 
-        if wave {
-            copy_wave(test, path, metadata, temp_dir.path())?;
+        if let Some(format) = option.wave {
+            copy_wave(test, path, metadata, temp_dir.path(), format)?;
         }
 
         if self.success {
@@ -1,14 +1,14 @@
-use crate::runner::{copy_wave, remap_msg_by_regex, Runner};
+use crate::runner::{copy_wave, remap_msg_by_regex, RunOption, Runner};
 use futures::prelude::*;
 use log::{error, info};
-use miette::{IntoDiagnostic, Result, WrapErr};
+use miette::{bail, IntoDiagnostic, Result, WrapErr};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::process::Stdio;
 use tokio::process::{Child, Command};
 use tokio::runtime::Runtime;
 use tokio_util::codec::{FramedRead, LinesCodec};
-use veryl_metadata::Metadata;
+use veryl_metadata::{Metadata, WaveFormFormat};
 use veryl_parser::resource_table::{PathId, StrId};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -101,10 +101,18 @@ impl Runner for Vivado {
         test: StrId,
         _top: Option<StrId>,
         path: PathId,
-        wave: bool,
+        option: &RunOption,
     ) -> Result<bool> {
         self.success = true;
 
+        if option.wave == Some(WaveFormFormat::Fst) {
+            bail!("FST waveform is not supported by Vivado Simulator");
+        }
+
+        if option.coverage.is_some() {
+            bail!("Coverage is not supported by Vivado Simulator");
+        }
+
         let temp_dir = tempfile::tempdir().into_diagnostic()?;
 
         info!("Compiling test ({})", test);
@@ -114,7 +122,7 @@ impl Runner for Vivado {
             format!("__veryl_test_{}_{}__", metadata.project.name, test),
         ];
 
-        if wave {
+        if option.wave.is_some() {
             defines.push("-d".to_string());
             defines.push(format!(
                 "__veryl_wavedump_{}_{}__",
@@ -122,6 +130,18 @@ impl Runner for Vivado {
             ));
         }
 
+        let mut params = Vec::new();
+        for (name, value) in &option.args.params {
+            params.push("--generic_top".to_string());
+            params.push(format!("{name}={value}"));
+        }
+
+        let mut plusargs = Vec::new();
+        for arg in &option.args.plusargs {
+            plusargs.push("--testplusarg".to_string());
+            plusargs.push(arg.trim_start_matches('+').to_string());
+        }
+
         let rt = Runtime::new().unwrap();
 
         rt.block_on(async {
@@ -148,10 +168,14 @@ impl Runner for Vivado {
 
         info!("Elaborating test ({})", test);
 
-        let opt = if wave { vec!["-debug", "all"] } else { vec![] };
+        let opt = if option.wave.is_some() {
+            vec!["-debug", "all"]
+        } else {
+            vec![]
+        };
 
         let mut top = vec![test.to_string()];
-        if wave {
+        if option.wave.is_some() {
             top.push("__veryl_wavedump".to_string());
         }
 
@@ -161,6 +185,7 @@ impl Runner for Vivado {
                 .args(opt)
                 .arg("-s")
                 .arg("simv")
+                .args(&params)
                 .args(&metadata.test.vivado.elaborate_args)
                 .current_dir(temp_dir.path())
                 .stdout(Stdio::piped())
@@ -183,6 +208,7 @@ impl Runner for Vivado {
             let simulate = Command::new("xsim")
                 .arg("simv")
                 .arg("--runall")
+                .args(&plusargs)
                 .args(&metadata.test.vivado.simulate_args)
                 .current_dir(temp_dir.path())
                 .stdout(Stdio::piped())
@@ -194,8 +220,8 @@ impl Runner for Vivado {
             self.parse(simulate).await
         })?;
 
-        if wave {
-            copy_wave(test, path, metadata, temp_dir.path())?;
+        if let Some(format) = option.wave {
+            copy_wave(test, path, metadata, temp_dir.path(), format)?;
         }
 
         if self.success {
@@ -0,0 +1,405 @@
+use std::collections::BTreeMap;
+use veryl_analyzer::evaluator::Evaluator;
+use veryl_analyzer::symbol::{Direction, Symbol, SymbolKind, TypeKind};
+use veryl_metadata::{Metadata, ResetType};
+
+mod constraint;
+pub use constraint::Constraints;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortKind {
+    Clock,
+    Reset { active_low: bool },
+    Data,
+}
+
+#[derive(Clone, Debug)]
+pub struct DutPort {
+    /// Name in emitted code
+    pub name: String,
+    pub direction: Direction,
+    pub kind: PortKind,
+    /// `None` if the width can't be evaluated statically
+    pub width: Option<usize>,
+}
+
+/// Ports of the module under test
+#[derive(Clone, Debug)]
+pub struct Dut {
+    pub name: String,
+    pub module: String,
+    pub ports: Vec<DutPort>,
+}
+
+impl Dut {
+    pub fn new(metadata: &Metadata, symbol: &Symbol) -> Option<Self> {
+        let SymbolKind::Module(ref module) = symbol.kind else {
+            return None;
+        };
+
+        let mut ports = Vec::new();
+        for port in &module.ports {
+            let property = port.property();
+            let r#type = property.r#type?;
+            let kind = if r#type.kind.is_clock() {
+                PortKind::Clock
+            } else if r#type.kind.is_reset() {
+                let active_low = match r#type.kind {
+                    TypeKind::ResetAsyncLow | TypeKind::ResetSyncLow => true,
+                    TypeKind::ResetAsyncHigh | TypeKind::ResetSyncHigh => false,
+                    _ => matches!(
+                        metadata.build.reset_type,
+                        ResetType::AsyncLow | ResetType::SyncLow
+                    ),
+                };
+                PortKind::Reset { active_low }
+            } else {
+                PortKind::Data
+            };
+            let width = Evaluator::new().type_width(r#type);
+            // Clock and reset ports may be emitted with prefix and suffix
+            let name = format!(
+                "{}{}{}",
+                property.prefix.clone().unwrap_or_default(),
+                port.name,
+                property.suffix.clone().unwrap_or_default()
+            );
+            ports.push(DutPort {
+                name,
+                direction: property.direction,
+                kind,
+                width,
+            });
+        }
+
+        Some(Self {
+            name: symbol.token.to_string(),
+            module: format!("{}_{}", metadata.project.name, symbol.token),
+            ports,
+        })
+    }
+
+    pub fn clock(&self) -> Option<&DutPort> {
+        self.ports.iter().find(|x| x.kind == PortKind::Clock)
+    }
+
+    pub fn reset(&self) -> Option<&DutPort> {
+        self.ports
+            .iter()
+            .find(|x| matches!(x.kind, PortKind::Reset { .. }))
+    }
+
+    /// Data inputs driven by stimulus
+    pub fn inputs(&self) -> Vec<&DutPort> {
+        self.ports
+            .iter()
+            .filter(|x| x.direction == Direction::Input && x.kind == PortKind::Data)
+            .collect()
+    }
+
+    pub fn outputs(&self) -> Vec<&DutPort> {
+        self.ports
+            .iter()
+            .filter(|x| x.direction == Direction::Output)
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    SystemVerilog,
+    Python,
+}
+
+fn declaration(port: &DutPort) -> String {
+    match port.width {
+        Some(1) | None => format!("logic {};", port.name),
+        Some(x) => format!("logic [{}:0] {};", x - 1, port.name),
+    }
+}
+
+fn reset_value(port: &DutPort, active: bool) -> u64 {
+    match port.kind {
+        PortKind::Reset { active_low } => (active != active_low) as u64,
+        _ => 0,
+    }
+}
+
+/// Generate a testbench applying `stimulus` to the inputs of `dut` by one per cycle
+pub fn testbench(dut: &Dut, stimulus: &[BTreeMap<String, u64>], seed: u64, lang: Lang) -> String {
+    match lang {
+        Lang::SystemVerilog => testbench_sv(dut, stimulus, seed),
+        Lang::Python => testbench_python(dut, stimulus, seed),
+    }
+}
+
+/// Generate a testbench which toggles each input one by one, and checks that outputs are
+/// not unknown and that at least one output follows the toggled input
+pub fn connectivity_testbench(dut: &Dut, lang: Lang) -> String {
+    match lang {
+        Lang::SystemVerilog => connectivity_sv(dut),
+        Lang::Python => connectivity_python(dut),
+    }
+}
+
+fn connectivity_sv(dut: &Dut) -> String {
+    // Wait some cycles so that toggles reach outputs through registers
+    let settle = match dut.clock() {
+        Some(x) => format!("repeat (4) @(posedge {});", x.name),
+        None => "#10;".to_string(),
+    };
+    let outputs = dut.outputs();
+
+    let mut ret = String::new();
+    ret.push_str("// Generated by veryl testgen --connectivity\n");
+    ret.push_str(&format!("module tb_{}_connectivity;\n", dut.module));
+    for port in &dut.ports {
+        ret.push_str(&format!("    {}\n", declaration(port)));
+    }
+    for port in &outputs {
+        let prev = DutPort {
+            name: format!("prev_{}", port.name),
+            ..(*port).clone()
+        };
+        ret.push_str(&format!("    {}\n", declaration(&prev)));
+    }
+    ret.push('\n');
+    ret.push_str(&format!("    {} u_dut (.*);\n\n", dut.module));
+
+    if let Some(clock) = dut.clock() {
+        ret.push_str(&format!("    initial {} = 1'b0;\n", clock.name));
+        ret.push_str(&format!(
+            "    always #5 {} = ~{};\n\n",
+            clock.name, clock.name
+        ));
+    }
+
+    let check_unknown = |ret: &mut String, when: &str| {
+        for port in &outputs {
+            ret.push_str(&format!(
+                "        if ($isunknown({})) $error(\"output {} is unknown {}\");\n",
+                port.name, port.name, when
+            ));
+        }
+    };
+
+    ret.push_str("    initial begin\n");
+    for port in dut.inputs() {
+        ret.push_str(&format!("        {} = '0;\n", port.name));
+    }
+    if let Some(reset) = dut.reset() {
+        ret.push_str(&format!(
+            "        {} = 1'b{};\n",
+            reset.name,
+            reset_value(reset, true)
+        ));
+        ret.push_str(&format!("        {settle}\n"));
+        ret.push_str(&format!(
+            "        {} = 1'b{};\n",
+            reset.name,
+            reset_value(reset, false)
+        ));
+    }
+    ret.push_str(&format!("        {settle}\n"));
+    check_unknown(&mut ret, "after reset");
+
+    for input in dut.inputs() {
+        ret.push_str(&format!("\n        // {}\n", input.name));
+        for port in &outputs {
+            ret.push_str(&format!("        prev_{} = {};\n", port.name, port.name));
+        }
+        ret.push_str(&format!("        {} = '1;\n", input.name));
+        ret.push_str(&format!("        {settle}\n"));
+        check_unknown(&mut ret, &format!("when {} is toggled", input.name));
+        if !outputs.is_empty() {
+            let unchanged: Vec<_> = outputs
+                .iter()
+                .map(|x| format!("{} === prev_{}", x.name, x.name))
+                .collect();
+            ret.push_str(&format!(
+                "        if ({}) $error(\"input {} has no path to outputs\");\n",
+                unchanged.join(" && "),
+                input.name
+            ));
+        }
+        ret.push_str(&format!("        {} = '0;\n", input.name));
+        ret.push_str(&format!("        {settle}\n"));
+    }
+
+    ret.push_str("        $finish;\n");
+    ret.push_str("    end\n");
+    ret.push_str("endmodule\n");
+    ret
+}
+
+fn connectivity_python(dut: &Dut) -> String {
+    let settle = match dut.clock() {
+        Some(x) => format!("await ClockCycles(dut.{}, 4)", x.name),
+        None => "await Timer(10, units=\"ns\")".to_string(),
+    };
+    let outputs: Vec<_> = dut
+        .outputs()
+        .iter()
+        .map(|x| format!("\"{}\"", x.name))
+        .collect();
+    let inputs: Vec<_> = dut
+        .inputs()
+        .iter()
+        .map(|x| format!("\"{}\"", x.name))
+        .collect();
+
+    let mut ret = String::new();
+    ret.push_str("# Generated by veryl testgen --connectivity\n");
+    ret.push_str("import cocotb\n");
+    ret.push_str("from cocotb.clock import Clock\n");
+    ret.push_str("from cocotb.triggers import ClockCycles, Timer\n\n");
+    ret.push_str(&format!("INPUTS = [{}]\n", inputs.join(", ")));
+    ret.push_str(&format!("OUTPUTS = [{}]\n\n\n", outputs.join(", ")));
+    ret.push_str("def check_unknown(dut, when):\n");
+    ret.push_str("    for name in OUTPUTS:\n");
+    ret.push_str("        value = getattr(dut, name).value\n");
+    ret.push_str("        assert value.is_resolvable, f\"output {name} is unknown {when}\"\n\n\n");
+
+    ret.push_str("@cocotb.test()\n");
+    ret.push_str(&format!("async def test_{}_connectivity(dut):\n", dut.name));
+    if let Some(clock) = dut.clock() {
+        ret.push_str(&format!(
+            "    cocotb.start_soon(Clock(dut.{}, 10, units=\"ns\").start())\n",
+            clock.name
+        ));
+    }
+    ret.push_str("    for name in INPUTS:\n");
+    ret.push_str("        getattr(dut, name).value = 0\n");
+    if let Some(reset) = dut.reset() {
+        ret.push_str(&format!(
+            "    dut.{}.value = {}\n",
+            reset.name,
+            reset_value(reset, true)
+        ));
+        ret.push_str(&format!("    {settle}\n"));
+        ret.push_str(&format!(
+            "    dut.{}.value = {}\n",
+            reset.name,
+            reset_value(reset, false)
+        ));
+    }
+    ret.push_str(&format!("    {settle}\n"));
+    ret.push_str("    check_unknown(dut, \"after reset\")\n\n");
+    ret.push_str("    for name in INPUTS:\n");
+    ret.push_str("        prev = [getattr(dut, x).value for x in OUTPUTS]\n");
+    ret.push_str("        port = getattr(dut, name)\n");
+    ret.push_str("        port.value = (1 << len(port)) - 1\n");
+    ret.push_str(&format!("        {settle}\n"));
+    ret.push_str("        check_unknown(dut, f\"when {name} is toggled\")\n");
+    ret.push_str("        curr = [getattr(dut, x).value for x in OUTPUTS]\n");
+    ret.push_str(
+        "        assert not OUTPUTS or prev != curr, f\"input {name} has no path to outputs\"\n",
+    );
+    ret.push_str("        port.value = 0\n");
+    ret.push_str(&format!("        {settle}\n"));
+    ret
+}
+
+fn testbench_sv(dut: &Dut, stimulus: &[BTreeMap<String, u64>], seed: u64) -> String {
+    let mut ret = String::new();
+    let wait = match dut.clock() {
+        Some(x) => format!("@(posedge {});", x.name),
+        None => "#10;".to_string(),
+    };
+
+    ret.push_str(&format!("// Generated by veryl testgen (seed = {seed})\n"));
+    ret.push_str(&format!("module tb_{};\n", dut.module));
+    for port in &dut.ports {
+        ret.push_str(&format!("    {}\n", declaration(port)));
+    }
+    ret.push('\n');
+    ret.push_str(&format!("    {} u_dut (.*);\n\n", dut.module));
+
+    if let Some(clock) = dut.clock() {
+        ret.push_str(&format!("    initial {} = 1'b0;\n", clock.name));
+        ret.push_str(&format!(
+            "    always #5 {} = ~{};\n\n",
+            clock.name, clock.name
+        ));
+    }
+
+    ret.push_str("    initial begin\n");
+    for port in dut.inputs() {
+        ret.push_str(&format!("        {} = '0;\n", port.name));
+    }
+    if let Some(reset) = dut.reset() {
+        ret.push_str(&format!(
+            "        {} = 1'b{};\n",
+            reset.name,
+            reset_value(reset, true)
+        ));
+        ret.push_str(&format!("        repeat (2) {wait}\n"));
+        ret.push_str(&format!(
+            "        {} = 1'b{};\n",
+            reset.name,
+            reset_value(reset, false)
+        ));
+    }
+    for values in stimulus {
+        for (name, value) in values {
+            ret.push_str(&format!("        {name} = 'h{value:x};\n"));
+        }
+        ret.push_str(&format!("        {wait}\n"));
+    }
+    ret.push_str("        $finish;\n");
+    ret.push_str("    end\n");
+    ret.push_str("endmodule\n");
+    ret
+}
+
+fn testbench_python(dut: &Dut, stimulus: &[BTreeMap<String, u64>], seed: u64) -> String {
+    let mut ret = String::new();
+    let wait = match dut.clock() {
+        Some(x) => format!("await RisingEdge(dut.{})", x.name),
+        None => "await Timer(10, units=\"ns\")".to_string(),
+    };
+
+    ret.push_str(&format!("# Generated by veryl testgen (seed = {seed})\n"));
+    ret.push_str("import cocotb\n");
+    ret.push_str("from cocotb.clock import Clock\n");
+    ret.push_str("from cocotb.triggers import RisingEdge, Timer\n\n");
+    ret.push_str("STIMULUS = [\n");
+    for values in stimulus {
+        let items: Vec<_> = values
+            .iter()
+            .map(|(name, value)| format!("\"{name}\": {value:#x}"))
+            .collect();
+        ret.push_str(&format!("    {{{}}},\n", items.join(", ")));
+    }
+    ret.push_str("]\n\n\n");
+
+    ret.push_str("@cocotb.test()\n");
+    ret.push_str(&format!("async def test_{}(dut):\n", dut.name));
+    if let Some(clock) = dut.clock() {
+        ret.push_str(&format!(
+            "    cocotb.start_soon(Clock(dut.{}, 10, units=\"ns\").start())\n",
+            clock.name
+        ));
+    }
+    for port in dut.inputs() {
+        ret.push_str(&format!("    dut.{}.value = 0\n", port.name));
+    }
+    if let Some(reset) = dut.reset() {
+        ret.push_str(&format!(
+            "    dut.{}.value = {}\n",
+            reset.name,
+            reset_value(reset, true)
+        ));
+        ret.push_str(&format!("    for _ in range(2):\n        {wait}\n"));
+        ret.push_str(&format!(
+            "    dut.{}.value = {}\n",
+            reset.name,
+            reset_value(reset, false)
+        ));
+    }
+    ret.push_str("    for values in STIMULUS:\n");
+    ret.push_str("        for name, value in values.items():\n");
+    ret.push_str("            getattr(dut, name).value = value\n");
+    ret.push_str(&format!("        {wait}\n"));
+    ret
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ConstraintError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+
+    #[error("\"{name}\" is not an input port")]
+    UnknownPort { name: String },
+
+    #[error("constraints can't be satisfied after {tries} tries in cycle {cycle}")]
+    Unsatisfiable { cycle: usize, tries: usize },
+}
+
+/// Value domain of an input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Domain {
+    /// Half-open range `lo..hi`
+    Range(u64, u64),
+    /// Weighted choice of ranges
+    Dist(Vec<((u64, u64), u64)>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RelationOp {
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    Equal,
+    NotEqual,
+}
+
+impl RelationOp {
+    fn eval(&self, left: u64, right: u64) -> bool {
+        match self {
+            RelationOp::Less => left < right,
+            RelationOp::LessEq => left <= right,
+            RelationOp::Greater => left > right,
+            RelationOp::GreaterEq => left >= right,
+            RelationOp::Equal => left == right,
+            RelationOp::NotEqual => left != right,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Name(String),
+    Value(u64),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Relation {
+    pub left: String,
+    pub op: RelationOp,
+    pub right: Operand,
+}
+
+/// Constraints of randomized stimulus.
+///
+/// Each line of the description is one of the following forms, and `//` starts a comment.
+///
+/// ```text
+/// <name> in <lo>..<hi>                         // uniform in half-open range
+/// <name> in <lo>..=<hi>                        // uniform in closed range
+/// <name> dist { <value>: <weight>, <lo>..<hi>: <weight> }
+/// <name> <op> <name or value>                  // op is <, <=, >, >=, == or !=
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Constraints {
+    pub domains: BTreeMap<String, Domain>,
+    pub relations: Vec<Relation>,
+}
+
+fn parse_value(x: &str) -> Option<u64> {
+    let x = x.replace('_', "");
+    if let Some(x) = x.strip_prefix("0x") {
+        u64::from_str_radix(x, 16).ok()
+    } else if let Some(x) = x.strip_prefix("0b") {
+        u64::from_str_radix(x, 2).ok()
+    } else {
+        x.parse().ok()
+    }
+}
+
+fn parse_range(x: &str) -> Option<(u64, u64)> {
+    if let Some((lo, hi)) = x.split_once("..=") {
+        let hi = parse_value(hi.trim())?;
+        Some((parse_value(lo.trim())?, hi.checked_add(1)?))
+    } else if let Some((lo, hi)) = x.split_once("..") {
+        Some((parse_value(lo.trim())?, parse_value(hi.trim())?))
+    } else {
+        let x = parse_value(x.trim())?;
+        Some((x, x.checked_add(1)?))
+    }
+}
+
+impl Constraints {
+    pub fn parse(text: &str) -> Result<Self, ConstraintError> {
+        let mut ret = Constraints::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = match line.find("//") {
+                Some(x) => &line[..x],
+                None => line,
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |message: &str| ConstraintError::Syntax {
+                line: i + 1,
+                message: message.to_string(),
+            };
+
+            let (name, rest) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| error("constraint is incomplete"))?;
+            let name = name.to_string();
+            let rest = rest.trim();
+
+            if let Some(range) = rest.strip_prefix("in ") {
+                let (lo, hi) = parse_range(range).ok_or_else(|| error("invalid range"))?;
+                if lo >= hi {
+                    return Err(error("range is empty"));
+                }
+                ret.domains.insert(name, Domain::Range(lo, hi));
+            } else if let Some(items) = rest.strip_prefix("dist") {
+                let items = items
+                    .trim()
+                    .strip_prefix('{')
+                    .and_then(|x| x.strip_suffix('}'))
+                    .ok_or_else(|| error("dist should be enclosed by {}"))?;
+                let mut dist = Vec::new();
+                for item in items.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+                    let (range, weight) = item
+                        .rsplit_once(':')
+                        .ok_or_else(|| error("dist item should be <range>: <weight>"))?;
+                    let range = parse_range(range).ok_or_else(|| error("invalid range"))?;
+                    let weight =
+                        parse_value(weight.trim()).ok_or_else(|| error("invalid weight"))?;
+                    if range.0 >= range.1 {
+                        return Err(error("range is empty"));
+                    }
+                    if weight != 0 {
+                        dist.push((range, weight));
+                    }
+                }
+                if dist.is_empty() {
+                    return Err(error("dist has no item with positive weight"));
+                }
+                ret.domains.insert(name, Domain::Dist(dist));
+            } else {
+                let ops = [
+                    ("<=", RelationOp::LessEq),
+                    (">=", RelationOp::GreaterEq),
+                    ("==", RelationOp::Equal),
+                    ("!=", RelationOp::NotEqual),
+                    ("<", RelationOp::Less),
+                    (">", RelationOp::Greater),
+                ];
+                let (op, right) = ops
+                    .iter()
+                    .find_map(|(text, op)| rest.strip_prefix(text).map(|x| (*op, x.trim())))
+                    .ok_or_else(|| error("unknown constraint"))?;
+                let right = match parse_value(right) {
+                    Some(x) => Operand::Value(x),
+                    None if !right.is_empty() => Operand::Name(right.to_string()),
+                    None => return Err(error("relation is incomplete")),
+                };
+                ret.relations.push(Relation {
+                    left: name,
+                    op,
+                    right,
+                });
+            }
+        }
+
+        Ok(ret)
+    }
+
+    /// Check that all names in constraints are included in `inputs`
+    pub fn check_names(&self, inputs: &[&str]) -> Result<(), ConstraintError> {
+        let mut names: Vec<_> = self.domains.keys().collect();
+        for x in &self.relations {
+            names.push(&x.left);
+            if let Operand::Name(ref x) = x.right {
+                names.push(x);
+            }
+        }
+        for name in names {
+            if !inputs.contains(&name.as_str()) {
+                return Err(ConstraintError::UnknownPort { name: name.clone() });
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate `cycles` sets of input values which satisfy the constraints.
+    /// Inputs without domain take any value within their width.
+    pub fn generate(
+        &self,
+        inputs: &[(&str, usize)],
+        cycles: usize,
+        seed: u64,
+    ) -> Result<Vec<BTreeMap<String, u64>>, ConstraintError> {
+        const MAX_TRIES: usize = 10000;
+
+        let mut rng = Rng::new(seed);
+        let mut ret = Vec::new();
+
+        for cycle in 0..cycles {
+            let mut found = None;
+            for _ in 0..MAX_TRIES {
+                let mut values = BTreeMap::new();
+                for (name, width) in inputs {
+                    let value = match self.domains.get(*name) {
+                        Some(Domain::Range(lo, hi)) => rng.range(*lo, *hi),
+                        Some(Domain::Dist(x)) => {
+                            let total: u64 = x.iter().map(|(_, w)| w).sum();
+                            let mut pick = rng.range(0, total);
+                            let mut value = 0;
+                            for ((lo, hi), weight) in x {
+                                if pick < *weight {
+                                    value = rng.range(*lo, *hi);
+                                    break;
+                                }
+                                pick -= weight;
+                            }
+                            value
+                        }
+                        None if *width >= 64 => rng.next(),
+                        None => rng.next() & ((1u64 << width) - 1),
+                    };
+                    values.insert(name.to_string(), value);
+                }
+                if self.satisfied(&values) {
+                    found = Some(values);
+                    break;
+                }
+            }
+
+            match found {
+                Some(x) => ret.push(x),
+                None => {
+                    return Err(ConstraintError::Unsatisfiable {
+                        cycle,
+                        tries: MAX_TRIES,
+                    })
+                }
+            }
+        }
+
+        Ok(ret)
+    }
+
+    fn satisfied(&self, values: &BTreeMap<String, u64>) -> bool {
+        self.relations.iter().all(|x| {
+            let left = values[&x.left];
+            let right = match &x.right {
+                Operand::Name(x) => values[x],
+                Operand::Value(x) => *x,
+            };
+            x.op.eval(left, right)
+        })
+    }
+}
+
+/// xorshift64* generator to make stimulus reproducible from seed
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // state must not be zero
+        const MIX: u64 = 0x9e37_79b9_7f4a_7c15;
+        let state = seed ^ MIX;
+        Self(if state == 0 { MIX } else { state })
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn range(&mut self, lo: u64, hi: u64) -> u64 {
+        lo + self.next() % (hi - lo)
+    }
+}
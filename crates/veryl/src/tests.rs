@@ -0,0 +1,1397 @@
+use crate::cmd_api_diff::{collect_apis, diff_apis, required_version, Change};
+use crate::cmd_build::CmdBuild;
+use crate::cmd_bundle::CmdBundle;
+use crate::cmd_completions::generate;
+use crate::cmd_diff::{analyze, checkout, split_units};
+use crate::cmd_doc::CmdDoc;
+use crate::cmd_equiv::CmdEquiv;
+use crate::cmd_export_symbols::CmdExportSymbols;
+use crate::cmd_grep::CmdGrep;
+use crate::cmd_man::CmdMan;
+use crate::cmd_new::CmdNew;
+use crate::cmd_probes::CmdProbes;
+use crate::cmd_repl::CmdRepl;
+use crate::cmd_report::CmdReport;
+use crate::cmd_stats::{openmetrics, CmdStats};
+use crate::cmd_testgen::CmdTestgen;
+use crate::doc::{render_source, Wavedrom};
+use crate::verify::verify;
+use crate::{
+    CompletionShell, EquivTool, GrepDirection, GrepKind, GrepScope, OptBuild, OptBundle, OptDoc,
+    OptEquiv, OptExportSymbols, OptGrep, OptMan, OptNew, OptProbes, OptRepl, OptReport, OptStats,
+    OptTestgen, ProbesFormat, ReportFormat, StatsFormat, TestgenLang,
+};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use veryl_analyzer::Analyzer;
+use veryl_metadata::semver::Version;
+use veryl_metadata::Metadata;
+
+const PROTECT_TOML: &str = r#"
+[project]
+name = "test"
+version = "0.1.0"
+
+[build]
+target = {type = "source"}
+protect = {modules = ["ModuleA"]}
+"#;
+
+fn create_project(toml: &str, sources: &[(&str, &str)]) -> TempDir {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("Veryl.toml"), toml).unwrap();
+    for (name, code) in sources {
+        let path = tempdir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, code).unwrap();
+    }
+    tempdir
+}
+
+fn build(path: &Path) {
+    let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+    let build = CmdBuild::new(OptBuild {
+        files: vec![],
+        deny_warnings: false,
+        verify_output: false,
+        matrix: None,
+    });
+    assert!(build.exec(&mut metadata).unwrap());
+}
+
+#[test]
+fn build_protect_without_sourcemap() {
+    let tempdir = create_project(
+        PROTECT_TOML,
+        &[
+            ("src/a.veryl", "module ModuleA {}\n"),
+            ("src/b.veryl", "module ModuleB {}\n"),
+        ],
+    );
+    let path = tempdir.path();
+
+    // Source map generated before the module is protected should be removed
+    fs::write(path.join("src/a.sv.map"), "module ModuleA {}\n").unwrap();
+
+    build(path);
+
+    let protected = fs::read_to_string(path.join("src/a.sv")).unwrap();
+    assert!(protected.contains("`pragma protect begin"));
+    assert!(!protected.contains("sourceMappingURL"));
+    assert!(!path.join("src/a.sv.map").exists());
+
+    let unprotected = fs::read_to_string(path.join("src/b.sv")).unwrap();
+    assert!(unprotected.contains("//# sourceMappingURL=b.sv.map"));
+    assert!(path.join("src/b.sv.map").exists());
+}
+
+#[test]
+fn api_diff_required_version() {
+    let required =
+        |base: &str, change| required_version(&Version::parse(base).unwrap(), change).to_string();
+
+    assert_eq!(required("1.2.3", None), "1.2.3");
+    assert_eq!(required("1.2.3", Some(Change::Compatible)), "1.3.0");
+    assert_eq!(required("1.2.3", Some(Change::Breaking)), "2.0.0");
+    assert_eq!(required("0.2.3", None), "0.2.3");
+    assert_eq!(required("0.2.3", Some(Change::Compatible)), "0.2.4");
+    assert_eq!(required("0.2.3", Some(Change::Breaking)), "0.3.0");
+    assert_eq!(required("1.2.3-alpha", Some(Change::Compatible)), "1.3.0");
+
+    // Breaking change without version bump is insufficient
+    let base = Version::parse("1.2.3").unwrap();
+    assert!(base < required_version(&base, Some(Change::Breaking)));
+    assert!(base >= required_version(&base, None));
+}
+
+#[test]
+fn verify_accepted() {
+    let testcases = [
+        "module a; endmodule",
+        r#"module a (
+    input logic [7:0] i_a,
+    output logic o_b
+);
+    always_comb begin
+        case (i_a)
+            8'h00: o_b = '0;
+            default: o_b = '1;
+        endcase
+    end
+    always @(*) begin end
+endmodule
+"#,
+        r#"package p;
+    import "DPI-C" function int f(int a);
+    typedef class c;
+    // begin (
+    /* end ) */
+    localparam string S = "end )";
+endpackage
+"#,
+        r#"`ifdef A
+module a; endmodule
+`elsif B
+module b; endmodule
+`else
+module c; endmodule
+`endif
+"#,
+        r#"module a;
+    initial begin
+        fork
+            #1;
+        join_none
+        wait fork;
+    end
+    assert property (@(posedge clk) a |-> b);
+endmodule
+"#,
+    ];
+
+    for code in testcases {
+        assert_eq!(verify(code), vec![], "{code}");
+    }
+}
+
+#[test]
+fn verify_rejected() {
+    let testcases = [
+        (
+            "module a;\n",
+            vec![(1, "\"module\" is not closed by \"endmodule\"")],
+        ),
+        ("endmodule\n", vec![(1, "\"endmodule\" has no opening")]),
+        (
+            "module a;\n    assign a = (b;\nendmodule\n",
+            vec![(2, "\"(\" is not closed")],
+        ),
+        (
+            "module a;\n    assign a = b);\nendmodule\n",
+            vec![(2, "\")\" is unexpected in \"module\" at line 1")],
+        ),
+        (
+            "module a;\n    assign a = {b];\nendmodule\n",
+            vec![
+                (2, "\"]\" is unexpected in \"{\" at line 2"),
+                (2, "\"{\" is not closed"),
+            ],
+        ),
+        (
+            "module a;\n    initial begin\nendmodule\n",
+            vec![(
+                3,
+                "\"endmodule\" is unexpected, \"begin\" at line 2 should be closed by \"end\"",
+            )],
+        ),
+        (
+            "module a;\n    localparam string S = \"a;\nendmodule\n",
+            vec![(2, "string literal is not closed")],
+        ),
+        ("/* comment\n", vec![(1, "\"/*\" is not closed")]),
+        (
+            "`ifdef A\nmodule a; endmodule\n",
+            vec![(1, "\"`ifdef\" is not closed by \"`endif\"")],
+        ),
+    ];
+
+    for (code, expect) in testcases {
+        let ret: Vec<_> = verify(code)
+            .into_iter()
+            .map(|x| (x.line, x.message))
+            .collect();
+        let expect: Vec<_> = expect
+            .into_iter()
+            .map(|(line, message)| (line, message.to_string()))
+            .collect();
+        assert_eq!(ret, expect, "{code}");
+    }
+}
+
+#[test]
+fn bundle_file_name() {
+    let toml = |files: &str| {
+        format!(
+            r#"
+[project]
+name = "test"
+version = "0.1.0"
+
+[build]
+target = {{type = "source"}}
+
+[bundle]
+doc = false
+sdc = false
+files = [{files}]
+"#
+        )
+    };
+    let bundle = |path: &Path| {
+        let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+        Analyzer::new(&metadata).clear();
+        CmdBundle::new(OptBundle {}).exec(&mut metadata)
+    };
+    let sources = [
+        ("src/a.veryl", "module ModuleA {}\n"),
+        ("src/a.txt", "a\n"),
+        ("doc/a.txt", "a\n"),
+    ];
+
+    let tempdir = create_project(&toml(r#""src/a.txt""#), &sources);
+    assert!(bundle(tempdir.path()).unwrap());
+    assert!(tempdir.path().join("bundle/test-0.1.0/rtl/a.sv").exists());
+    assert!(tempdir.path().join("bundle/test-0.1.0/a.txt").exists());
+
+    // Files which have the same name conflict in the bundle
+    let tempdir = create_project(&toml(r#""src/a.txt", "doc/a.txt""#), &sources);
+    let err = bundle(tempdir.path()).unwrap_err();
+    assert!(err.to_string().contains("conflicts with another file"));
+
+    // Directory can't be bundled as a file
+    let tempdir = create_project(&toml(r#""src/..""#), &sources);
+    assert!(bundle(tempdir.path()).is_err());
+}
+
+const SOURCE_TOML: &str = r#"
+[project]
+name = "test"
+version = "0.1.0"
+
+[build]
+target = {type = "source"}
+"#;
+
+fn stats(path: &Path, files: Vec<PathBuf>) -> serde_json::Value {
+    let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+    let stats = CmdStats::new(OptStats {
+        files,
+        format: StatsFormat::Json,
+    });
+    serde_json::to_value(stats.stats(&mut metadata).unwrap()).unwrap()
+}
+
+#[test]
+fn stats_modules() {
+    let top = r#"module Top {
+    inst a: ModuleA;
+    inst b: ModuleB;
+}
+
+module ModuleA {
+    inst c: ModuleC (
+        i: 0,
+    );
+}
+
+module ModuleB {
+    inst c: ModuleC (
+        i: 1,
+    );
+}
+"#;
+    let c = r#"// comment
+module ModuleC (
+    i: input  logic,
+    o: output logic,
+) {
+    assign o = i;
+}
+
+interface InterfaceA {}
+
+package PackageA {}
+"#;
+    let tempdir = create_project(SOURCE_TOML, &[("src/top.veryl", top), ("src/c.veryl", c)]);
+    let path = tempdir.path();
+
+    let ret = stats(path, vec![]);
+    assert_eq!(ret["files"].as_array().unwrap().len(), 2);
+    assert_eq!(ret["modules"], 4);
+    assert_eq!(ret["interfaces"], 1);
+    assert_eq!(ret["packages"], 1);
+    assert_eq!(ret["lines"], 27);
+    assert_eq!(ret["code_lines"], 22);
+    assert_eq!(ret["average_ports"], 0.5);
+    assert_eq!(
+        ret["deepest_hierarchy"],
+        serde_json::json!(["Top", "ModuleA", "ModuleC"])
+    );
+}
+
+#[test]
+fn stats_path_filter() {
+    let a = "module ModuleA (\n    i: input logic,\n) {}\n";
+    let b = "module ModuleB (\n    i: input logic,\n    j: input logic,\n) {}\n";
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", a), ("src/b.veryl", b)]);
+    let path = tempdir.path();
+
+    let ret = stats(path, vec![path.join("src/b.veryl")]);
+    assert_eq!(ret["files"].as_array().unwrap().len(), 1);
+    assert_eq!(ret["files"][0]["path"], "src/b.veryl");
+    assert_eq!(ret["modules"], 1);
+    assert_eq!(ret["average_ports"], 2.0);
+}
+
+#[test]
+fn stats_hierarchy_depth() {
+    // Each level instantiates both modules of the next level,
+    // so the number of paths from the top grows exponentially
+    let depth = 24;
+    let mut code = String::new();
+    for i in 0..depth {
+        for x in ["A", "B"] {
+            code.push_str(&format!("module Module{i}{x} {{\n"));
+            if i + 1 < depth {
+                code.push_str(&format!("    inst a: Module{}A;\n", i + 1));
+                code.push_str(&format!("    inst b: Module{}B;\n", i + 1));
+            }
+            code.push_str("}\n\n");
+        }
+    }
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", &code)]);
+
+    let ret = stats(tempdir.path(), vec![]);
+    let hierarchy = ret["deepest_hierarchy"].as_array().unwrap();
+    assert_eq!(hierarchy.len(), depth);
+    assert_eq!(hierarchy[0], "Module0A");
+    assert_eq!(hierarchy[depth - 1], format!("Module{}A", depth - 1));
+}
+
+#[test]
+fn api_diff_interface() {
+    let base = r#"pub interface InterfaceA {
+    var a: logic;
+    var b: logic;
+    var c: logic;
+
+    function FuncA (x: input logic) -> logic {
+        return x;
+    }
+
+    modport mp_a {
+        a: input,
+        b: input,
+    }
+
+    modport mp_b {
+        a: output,
+    }
+}
+"#;
+    let head = r#"pub interface InterfaceA {
+    var a: logic;
+    var b: logic<2>;
+    var d: logic;
+
+    function FuncA (x: input logic<2>) -> logic {
+        return x[0];
+    }
+
+    modport mp_a {
+        a: output,
+        d: input,
+    }
+
+    modport mp_c {
+        a: input,
+    }
+}
+"#;
+
+    let apis = |code: &str| {
+        let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", code)]);
+        let mut metadata = Metadata::load(tempdir.path().join("Veryl.toml")).unwrap();
+        analyze(&mut metadata).unwrap();
+        collect_apis(&metadata)
+    };
+    let changes = diff_apis(&apis(base), &apis(head));
+    let changes: Vec<_> = changes.iter().map(|(x, y)| (*x, y.as_str())).collect();
+
+    use Change::*;
+    assert_eq!(
+        changes,
+        vec![
+            (
+                Breaking,
+                "function InterfaceA.FuncA is changed ((x: input logic) -> logic => (x: input logic<2>) -> logic)"
+            ),
+            (
+                Breaking,
+                "var InterfaceA.b is changed (logic => logic<2>)"
+            ),
+            (Breaking, "var InterfaceA.c is removed"),
+            (
+                Breaking,
+                "modport member InterfaceA.mp_a.a is changed (input => output)"
+            ),
+            (Breaking, "modport member InterfaceA.mp_a.b is removed"),
+            (Breaking, "modport InterfaceA.mp_b is removed"),
+            (Breaking, "modport member InterfaceA.mp_b.a is removed"),
+            (Compatible, "var InterfaceA.d is added"),
+            (Compatible, "modport member InterfaceA.mp_a.d is added"),
+            (Compatible, "modport InterfaceA.mp_c is added"),
+            (Compatible, "modport member InterfaceA.mp_c.a is added"),
+        ]
+    );
+}
+
+#[test]
+fn diff_split_units() {
+    let text = r#"module prj_ModuleA;
+    initial begin
+        $display("module");
+    end
+endmodule
+// comment between units
+  interface prj_InterfaceA;
+    modport mp (input a);
+  endinterface : prj_InterfaceA
+package prj_PackageA;
+endpackage
+"#;
+
+    let mut units = BTreeMap::new();
+    split_units(text, &mut units);
+
+    assert_eq!(
+        units.keys().collect::<Vec<_>>(),
+        vec!["prj_InterfaceA", "prj_ModuleA", "prj_PackageA"]
+    );
+    assert_eq!(
+        units["prj_ModuleA"],
+        "module prj_ModuleA;\n    initial begin\n        $display(\"module\");\n    end\nendmodule\n"
+    );
+    assert_eq!(
+        units["prj_InterfaceA"],
+        "  interface prj_InterfaceA;\n    modport mp (input a);\n  endinterface : prj_InterfaceA\n"
+    );
+    assert_eq!(units["prj_PackageA"], "package prj_PackageA;\nendpackage\n");
+
+    // Units of other files are merged
+    split_units("module prj_ModuleB;\nendmodule\n", &mut units);
+    assert_eq!(units.len(), 4);
+}
+
+#[test]
+fn diff_checkout_cleanup() {
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", "module ModuleA {}\n")]);
+    let path = tempdir.path();
+    let git = |args: &[&str]| crate::cmd_diff::git(path, args).unwrap();
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&[
+        "-c",
+        "user.name=veryl",
+        "-c",
+        "user.email=veryl@example.com",
+        "commit",
+        "-q",
+        "-m",
+        "base",
+    ]);
+    let worktrees = || git(&["worktree", "list"]).lines().count();
+
+    let metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+
+    let ret = checkout(&metadata, "HEAD", |x| Ok(x.project.name.clone()));
+    assert_eq!(ret.unwrap(), "test");
+    assert_eq!(worktrees(), 1);
+
+    let ret: miette::Result<()> = checkout(&metadata, "HEAD", |_| miette::bail!("failure"));
+    assert!(ret.is_err());
+    assert_eq!(worktrees(), 1);
+
+    // Ctrl-C during analysis of the base revision
+    let ret = checkout(&metadata, "HEAD", |x| {
+        metadata.cancellation.cancel();
+        analyze(x)
+    });
+    assert!(ret.is_err());
+    assert_eq!(worktrees(), 1);
+}
+
+fn export_symbols(path: &Path) -> miette::Result<serde_json::Value> {
+    let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+    let export = CmdExportSymbols::new(OptExportSymbols {
+        files: vec![],
+        output: None,
+    });
+    Ok(serde_json::to_value(export.export(&mut metadata)?).unwrap())
+}
+
+fn object_keys(x: &serde_json::Value) -> Vec<&str> {
+    let mut keys: Vec<_> = x.as_object().unwrap().keys().map(|x| x.as_str()).collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn export_symbols_schema() {
+    let code = r#"module ModuleA (
+    i: input  logic,
+    o: output logic,
+) {
+    assign o = i;
+}
+"#;
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", code)]);
+    let ret = export_symbols(tempdir.path()).unwrap();
+
+    assert_eq!(
+        object_keys(&ret),
+        vec!["assignments", "format_version", "project", "symbols"]
+    );
+    assert_eq!(ret["format_version"], 1);
+    assert_eq!(ret["project"], "test");
+
+    let symbols = ret["symbols"].as_array().unwrap();
+    let port = symbols
+        .iter()
+        .find(|x| x["name"] == "o" && x["kind"] == "port")
+        .unwrap();
+    assert_eq!(
+        object_keys(port),
+        vec![
+            "detail",
+            "doc_comment",
+            "id",
+            "kind",
+            "location",
+            "name",
+            "namespace",
+            "public",
+            "references",
+            "type"
+        ]
+    );
+    assert_eq!(
+        object_keys(&port["location"]),
+        vec!["column", "file", "length", "line", "text"]
+    );
+    assert_eq!(port["location"]["line"], 3);
+    assert_eq!(port["location"]["column"], 5);
+    assert_eq!(port["location"]["text"], "o");
+
+    // Assignments of the standard library are included too
+    let assign = ret["assignments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|x| x["symbols"] == serde_json::json!([port["id"]]))
+        .unwrap();
+    assert_eq!(
+        object_keys(assign),
+        vec!["partial", "path", "position", "symbols"]
+    );
+    assert_eq!(assign["path"], "o");
+    assert_eq!(assign["partial"], false);
+}
+
+#[test]
+fn export_symbols_analyze_error() {
+    let code = "module ModuleA {\n    assign a = 1;\n}\n";
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", code)]);
+    assert!(export_symbols(tempdir.path()).is_err());
+}
+
+fn create_template() -> TempDir {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path();
+    fs::create_dir_all(path.join("src")).unwrap();
+    fs::create_dir_all(path.join(".git")).unwrap();
+    fs::write(path.join(".git/HEAD"), "").unwrap();
+    fs::write(
+        path.join("Veryl.toml"),
+        "[project]\nname = \"{{project_name}}\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        path.join("src/{{project_name}}.veryl"),
+        "// {{author}}\nmodule {{project_name}} {}\n",
+    )
+    .unwrap();
+    fs::write(path.join("data.bin"), [0xff, 0xfe, 0x00]).unwrap();
+    tempdir
+}
+
+#[test]
+fn new_from_template() {
+    let template = create_template();
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("sample");
+
+    let opt = OptNew {
+        path: path.clone(),
+        template: Some(template.path().to_string_lossy().to_string()),
+        license: None,
+    };
+    assert!(CmdNew::new(opt).exec().unwrap());
+
+    let toml = fs::read_to_string(path.join("Veryl.toml")).unwrap();
+    assert!(toml.contains("name = \"sample\""));
+    let code = fs::read_to_string(path.join("src/sample.veryl")).unwrap();
+    assert!(code.contains("module sample {}"));
+    assert!(!code.contains("{{author}}"));
+    assert_eq!(fs::read(path.join("data.bin")).unwrap(), [0xff, 0xfe, 0x00]);
+    assert!(!path.join(".git").exists());
+}
+
+#[test]
+fn new_from_template_license() {
+    let template = create_template();
+    fs::write(template.path().join("LICENSE"), "{{license}}\n").unwrap();
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("sample");
+
+    let opt = OptNew {
+        path: path.clone(),
+        template: Some(template.path().to_string_lossy().to_string()),
+        license: None,
+    };
+    let err = CmdNew::new(opt).exec().unwrap_err();
+    assert!(err.to_string().contains("--license"));
+    assert!(!path.exists());
+
+    let opt = OptNew {
+        path: path.clone(),
+        template: Some(template.path().to_string_lossy().to_string()),
+        license: Some("MIT".to_string()),
+    };
+    assert!(CmdNew::new(opt).exec().unwrap());
+    assert_eq!(fs::read_to_string(path.join("LICENSE")).unwrap(), "MIT\n");
+}
+
+#[test]
+fn wavedrom_escape_script() {
+    let content = r#"Timing
+
+```wavejson
+{signal: [{name: "clk", wave: "p..", node: "</script><b>"}]}
+```
+"#;
+    let mut chapter = mdbook::book::Chapter::new("a", content.to_string(), "a.md", vec![]);
+    let ret = Wavedrom::add_wavedrom(&mut chapter).unwrap();
+
+    // Quotes are kept as is, and only the closing tag sequence is escaped
+    assert!(ret.contains(
+        r#"<script type="WaveDrom">{signal: [{name: "clk", wave: "p..", node: "<\/script><b>"}]}"#
+    ));
+    assert_eq!(ret.matches("</script>").count(), 1);
+    assert!(!ret.contains("&quot;"));
+    assert!(!ret.contains("```wavejson"));
+}
+
+#[test]
+fn completions_subcommands() {
+    let mut buf = Vec::new();
+    generate(CompletionShell::Fish, &mut buf);
+    let text = String::from_utf8(buf).unwrap();
+
+    assert!(text.contains("complete -c veryl"));
+    assert!(text.contains("-a \"export-symbols\""));
+    assert!(text.contains("-l output"));
+}
+
+#[test]
+fn man_pages() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path();
+    let opt = OptMan {
+        output: Some(path.to_path_buf()),
+    };
+    assert!(CmdMan::new(opt).exec().unwrap());
+
+    let page = fs::read_to_string(path.join("veryl.1")).unwrap();
+    assert!(page.starts_with(".TH VERYL 1"));
+    assert!(page.contains(".TP\n\\fBveryl\\-build\\fR(1)\n"));
+
+    // Global options are propagated to subcommands
+    let page = fs::read_to_string(path.join("veryl-build.1")).unwrap();
+    assert!(page.starts_with(".TH VERYL\\-BUILD 1"));
+    assert!(page.contains("\\fB\\-\\-quiet\\fR\nNo output printed to stdout\n"));
+
+    // Nested subcommands and hidden help
+    assert!(path.join("veryl-self-update.1").exists());
+    assert!(!path.join("veryl-help.1").exists());
+}
+
+fn report(path: &Path, format: ReportFormat, previous: Option<PathBuf>) -> String {
+    let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+    Analyzer::new(&metadata).clear();
+    let output = path.join("report");
+    let opt = OptReport {
+        files: vec![],
+        format,
+        output: Some(output.clone()),
+        previous,
+    };
+    assert!(CmdReport::new(opt).exec(&mut metadata).unwrap());
+    fs::read_to_string(output).unwrap()
+}
+
+#[test]
+fn report_trend() {
+    let a = "module ModuleA {\n    let a: logic = 1;\n}\n";
+    let b = "module ModuleB {  }\n";
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", a), ("src/b.veryl", b)]);
+    let path = tempdir.path();
+
+    let text = report(path, ReportFormat::Json, None);
+    let ret: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(ret["project"], "test");
+    assert_eq!(ret["errors"], 0);
+    assert_eq!(ret["warnings"], 1);
+    assert_eq!(ret["unformatted"], 1);
+    assert_eq!(ret["checks"], serde_json::json!({"unused_variable": 1}));
+    assert_eq!(ret["diagnostics"][0]["path"], "src/a.veryl");
+    assert!(ret.get("trend").is_none());
+
+    let files = ret["files"].as_array().unwrap();
+    let file = |x: &str| files.iter().find(|y| y["path"] == x).unwrap();
+    assert_eq!(file("src/a.veryl")["warnings"], 1);
+    assert_eq!(file("src/a.veryl")["formatted"], true);
+    assert_eq!(file("src/b.veryl")["formatted"], false);
+
+    // Fix the warning and compare with the previous report
+    let previous = path.join("previous.json");
+    fs::write(&previous, text).unwrap();
+    fs::write(path.join("src/a.veryl"), "module ModuleA {}\n").unwrap();
+
+    let text = report(path, ReportFormat::Json, Some(previous.clone()));
+    let ret: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(ret["warnings"], 0);
+    assert_eq!(
+        ret["trend"],
+        serde_json::json!({
+            "errors": 0,
+            "warnings": -1,
+            "advices": 0,
+            "unformatted": 0,
+            "checks": {"unused_variable": -1},
+        })
+    );
+
+    let text = report(path, ReportFormat::Html, Some(previous));
+    assert!(text.contains("<title>test - Veryl Report</title>"));
+    assert!(text.contains("<tr><td>Warnings</td><td>0</td><td>-1</td></tr>"));
+    assert!(text.contains("<tr><td>unused_variable</td><td>0</td><td>-1</td></tr>"));
+}
+
+const TESTGEN_CODE: &str = r#"module ModuleA (
+    clk: input  clock   ,
+    rst: input  reset   ,
+    a  : input  logic<4>,
+    b  : input  logic<4>,
+    o  : output logic<4>,
+) {
+    assign o = a + b;
+}
+"#;
+
+fn testgen(
+    path: &Path,
+    constraints: Option<&str>,
+    connectivity: bool,
+    lang: TestgenLang,
+) -> miette::Result<String> {
+    let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+    let constraints = constraints.map(|x| {
+        let file = path.join("constraints.txt");
+        fs::write(&file, x).unwrap();
+        file
+    });
+    let output = path.join("tb.out");
+    let opt = OptTestgen {
+        module: "ModuleA".to_string(),
+        constraints,
+        seed: 7,
+        cycles: 20,
+        lang,
+        connectivity,
+        output: Some(output.clone()),
+    };
+    CmdTestgen::new(opt).exec(&mut metadata)?;
+    Ok(fs::read_to_string(output).unwrap())
+}
+
+#[test]
+fn testgen_constrained_stimulus() {
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", TESTGEN_CODE)]);
+    let path = tempdir.path();
+
+    let text = testgen(path, Some("a in 3..5\nb > a\n"), false, TestgenLang::Sv).unwrap();
+    assert!(text.starts_with("// Generated by veryl testgen (seed = 7)\nmodule tb_test_ModuleA;\n"));
+    assert!(text.contains("    test_ModuleA u_dut (.*);\n"));
+    assert!(text.contains("    always #5 clk = ~clk;\n"));
+
+    let value = |line: &str, name: &str| {
+        let x = line.trim().strip_prefix(&format!("{name} = 'h"))?;
+        u64::from_str_radix(x.strip_suffix(';')?, 16).ok()
+    };
+    let lines: Vec<_> = text.lines().collect();
+    let mut cycles = 0;
+    for pair in lines.windows(2) {
+        if let (Some(a), Some(b)) = (value(pair[0], "a"), value(pair[1], "b")) {
+            assert!((3..5).contains(&a), "{a}");
+            assert!(b > a && b < 16, "{a} {b}");
+            cycles += 1;
+        }
+    }
+    assert_eq!(cycles, 20);
+
+    // The same seed generates the same stimulus
+    let python = testgen(path, Some("a in 3..5\nb > a\n"), false, TestgenLang::Python).unwrap();
+    assert!(python.starts_with("# Generated by veryl testgen (seed = 7)\n"));
+    let again = testgen(path, Some("a in 3..5\nb > a\n"), false, TestgenLang::Sv).unwrap();
+    assert_eq!(text, again);
+
+    // Constraint of unknown port
+    let err = testgen(path, Some("c in 0..2\n"), false, TestgenLang::Sv).unwrap_err();
+    assert!(err.to_string().contains("\"c\" is not an input port"));
+}
+
+#[test]
+fn testgen_connectivity() {
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", TESTGEN_CODE)]);
+    let path = tempdir.path();
+
+    let text = testgen(path, None, true, TestgenLang::Sv).unwrap();
+    assert!(text.starts_with("// Generated by veryl testgen --connectivity\n"));
+    assert!(text.contains("module tb_test_ModuleA_connectivity;\n"));
+    assert!(text.contains("    logic [3:0] prev_o;\n"));
+    // Reset is active low by default
+    assert!(text.contains(
+        "        rst = 1'b0;\n        repeat (4) @(posedge clk);\n        rst = 1'b1;\n"
+    ));
+    assert!(
+        text.contains("        if ($isunknown(o)) $error(\"output o is unknown after reset\");\n")
+    );
+
+    // Each data input is toggled, and clock and reset are not
+    for x in ["a", "b"] {
+        assert!(text.contains(&format!(
+            "        // {x}\n        prev_o = o;\n        {x} = '1;\n"
+        )));
+        assert!(text.contains(&format!(
+            "        if (o === prev_o) $error(\"input {x} has no path to outputs\");\n"
+        )));
+    }
+    assert!(!text.contains("        // clk\n"));
+    assert!(!text.contains("        // rst\n"));
+
+    let text = testgen(path, None, true, TestgenLang::Python).unwrap();
+    assert!(text.contains("INPUTS = [\"a\", \"b\"]\nOUTPUTS = [\"o\"]\n"));
+    assert!(text.contains("async def test_ModuleA_connectivity(dut):\n"));
+}
+
+const DOC_CODE: &str = r#"/// Package A
+pub package PackageA {
+    type word = logic<8>;
+}
+
+/// Module A
+pub module ModuleA #(
+    param W: u32 = 8,
+) (
+    i: input  PackageA::word,
+    o: output logic<W>,
+) {
+    assign o = i;
+}
+"#;
+
+fn doc(path: &Path, include_deps: bool, since: Option<&str>) {
+    let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+    Analyzer::new(&metadata).clear();
+    let opt = OptDoc {
+        files: vec![],
+        include_deps,
+        since: since.map(|x| x.to_string()),
+    };
+    assert!(CmdDoc::new(opt).exec(&mut metadata).unwrap());
+}
+
+#[test]
+fn doc_search_index() {
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", DOC_CODE)]);
+    let path = tempdir.path();
+    doc(path, false, None);
+
+    let index = fs::read_to_string(path.join("doc/search_index.json")).unwrap();
+    let index: serde_json::Value = serde_json::from_str(&index).unwrap();
+    let item = |x: &str| {
+        index
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|y| y["path"] == x)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(
+        item("ModuleA"),
+        serde_json::json!({
+            "name": "ModuleA",
+            "kind": "module",
+            "path": "ModuleA",
+            "url": "ModuleA.html",
+            "source": "source.src.a.veryl.html#L7",
+            "description": "Module A",
+        })
+    );
+    assert_eq!(item("ModuleA::W")["url"], "ModuleA.html#param-W");
+    assert_eq!(item("ModuleA::i")["kind"], "port");
+    assert_eq!(item("PackageA::word")["url"], "PackageA.html#type-word");
+
+    // Port type links to the page defining it
+    let page = fs::read_to_string(path.join("doc/ModuleA.html")).unwrap();
+    assert!(page.contains("<tr id=\"port-i\">"));
+    assert!(page.contains("<a href=\"PackageA.html#type-word\">PackageA::word</a>"));
+
+    let page = fs::read_to_string(path.join("doc/items.html")).unwrap();
+    assert!(page.contains("<a href=\"ModuleA.html#port-i\">ModuleA::i</a>"));
+}
+
+/// Commits all files in `path`, and returns the revision
+fn commit_all(path: &Path, message: &str) -> String {
+    let git = |args: &[&str]| crate::cmd_diff::git(path, args).unwrap();
+    if !path.join(".git").exists() {
+        git(&["init", "-q"]);
+    }
+    git(&["add", "."]);
+    git(&[
+        "-c",
+        "user.name=veryl",
+        "-c",
+        "user.email=veryl@example.com",
+        "commit",
+        "-q",
+        "-m",
+        message,
+    ]);
+    git(&["rev-parse", "HEAD"]).trim().to_string()
+}
+
+#[test]
+fn doc_include_deps() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let root = tempdir.path();
+
+    // Published dependency project
+    let dep = root.join("dep");
+    fs::create_dir_all(dep.join("src")).unwrap();
+    fs::write(
+        dep.join("Veryl.toml"),
+        "[project]\nname = \"dep\"\nversion = \"0.1.0\"\n\n[build]\ntarget = {type = \"source\"}\n",
+    )
+    .unwrap();
+    fs::write(
+        dep.join("src/dep.veryl"),
+        "/// Dependency module\npub module DepModule {}\n\nmodule DepPrivate {}\n",
+    )
+    .unwrap();
+    let revision = commit_all(&dep, "Add dep");
+    fs::write(
+        dep.join("Veryl.pub"),
+        format!("[[releases]]\nversion = \"0.1.0\"\nrevision = \"{revision}\"\n"),
+    )
+    .unwrap();
+    commit_all(&dep, "Publish 0.1.0");
+
+    let url = format!("file://{}", dep.to_string_lossy().replace('\\', "/"));
+    let toml = format!("{SOURCE_TOML}\n[dependencies]\n\"{url}\" = \"0.1.0\"\n");
+    let prj = root.join("prj");
+    fs::create_dir_all(prj.join("src")).unwrap();
+    fs::write(prj.join("Veryl.toml"), toml).unwrap();
+    fs::write(prj.join("src/a.veryl"), DOC_CODE).unwrap();
+
+    doc(&prj, false, None);
+    assert!(!prj.join("doc/dep.dep.html").exists());
+
+    doc(&prj, true, None);
+    let page = fs::read_to_string(prj.join("doc/dep.dep.html")).unwrap();
+    assert!(page.contains("0.1.0"));
+    assert!(page.contains("dep.dep.DepModule.html"));
+    assert!(!page.contains("DepPrivate"));
+    let page = fs::read_to_string(prj.join("doc/dep.dep.DepModule.html")).unwrap();
+    assert!(page.contains("Dependency module"));
+
+    let index = fs::read_to_string(prj.join("doc/search_index.json")).unwrap();
+    assert!(index.contains("\"url\":\"dep.dep.DepModule.html\""));
+    assert!(prj.join("doc/source.dep.src.dep.veryl.html").exists());
+}
+
+#[test]
+fn doc_since() {
+    let previous = r#"pub module ModuleA #(
+    param W: u32 = 8,
+) (
+    i: input  logic<W>,
+    o: output logic<W>,
+    x: input  logic   ,
+) {
+    assign o = i;
+}
+
+pub module ModuleOld {}
+"#;
+    let current = r#"pub module ModuleA #(
+    param W: u32 = 8,
+) (
+    i: input  logic<W>,
+    j: input  logic<W>,
+    o: output logic<W + 1>,
+) {
+    assign o = i + j;
+}
+
+pub module ModuleNew {}
+"#;
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", previous)]);
+    let path = tempdir.path();
+    let revision = commit_all(path, "0.1.0");
+    fs::write(
+        path.join("Veryl.pub"),
+        format!("[[releases]]\nversion = \"0.1.0\"\nrevision = \"{revision}\"\n"),
+    )
+    .unwrap();
+    fs::write(path.join("src/a.veryl"), current).unwrap();
+
+    doc(path, false, Some("0.1.0"));
+
+    let page = fs::read_to_string(path.join("doc/ModuleA.html")).unwrap();
+    let change = |x: &str| format!("<span class=\"hljs-comment\">{x}</span>");
+    assert!(!page.contains("New since 0.1.0"));
+    assert!(page.contains(&change("new")));
+    assert!(page.contains(&change("changed from output logic<W>")));
+    assert!(page.contains("Removed since 0.1.0"));
+    assert!(page.contains("<th class=\"table_list_item\">x</th>"));
+
+    let page = fs::read_to_string(path.join("doc/ModuleNew.html")).unwrap();
+    assert!(page.contains("New since 0.1.0"));
+
+    let page = fs::read_to_string(path.join("doc/index.html")).unwrap();
+    assert!(page.contains("<th class=\"table_list_item\">ModuleOld</th>"));
+    assert!(!path.join("doc/ModuleOld.html").exists());
+}
+
+#[test]
+fn doc_source_view() {
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", DOC_CODE)]);
+    let path = tempdir.path();
+    doc(path, false, None);
+
+    let page = fs::read_to_string(path.join("doc/source.src.a.veryl.html")).unwrap();
+    assert!(page.contains(
+        "<span id=\"L7\"><a class=\"source_line\" href=\"#L7\">   7</a>  <span class=\"hljs-keyword\">pub</span> <span class=\"hljs-keyword\">module</span> ModuleA #(</span>"
+    ));
+
+    // Items and their members link to the declaration
+    let page = fs::read_to_string(path.join("doc/ModuleA.html")).unwrap();
+    assert!(page.contains("<a href=\"source.src.a.veryl.html#L8\">W</a>"));
+    let page = fs::read_to_string(path.join("doc/sources.html")).unwrap();
+    assert!(page.contains("source.src.a.veryl.html"));
+}
+
+#[test]
+fn doc_render_source() {
+    let text = "/* a\n<b> */ var x: logic = \"</span>\"; // c\n";
+    assert_eq!(
+        render_source(text),
+        concat!(
+            "<pre class=\"source\"><code class=\"hljs nohighlight\">",
+            "<span id=\"L1\"><a class=\"source_line\" href=\"#L1\">   1</a>  ",
+            "<span class=\"hljs-comment\">/* a</span></span>\n",
+            "<span id=\"L2\"><a class=\"source_line\" href=\"#L2\">   2</a>  ",
+            "<span class=\"hljs-comment\">&lt;b&gt; */</span> ",
+            "<span class=\"hljs-keyword\">var</span> x: <span class=\"hljs-type\">logic</span> = ",
+            "<span class=\"hljs-string\">&quot;&lt;/span&gt;&quot;</span>; ",
+            "<span class=\"hljs-comment\">// c</span></span>\n",
+            "</code></pre>\n",
+        )
+    );
+}
+
+#[test]
+fn stats_openmetrics() {
+    let a = "module ModuleA (\n    i: input logic,\n) {\n    let a: logic = i;\n}\n";
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", a)]);
+    let path = tempdir.path();
+
+    let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+    let cmd = CmdStats::new(OptStats {
+        files: vec![],
+        format: StatsFormat::OpenMetrics,
+    });
+    let stats = cmd.stats(&mut metadata).unwrap();
+    let text = openmetrics("test", &stats);
+    let lines: Vec<_> = text.lines().collect();
+
+    assert!(text.starts_with("# TYPE veryl_lines gauge\n# HELP veryl_lines Number of lines\n"));
+    assert!(lines.contains(&"veryl_lines{project=\"test\",file=\"src/a.veryl\"} 5"));
+    assert!(lines.contains(&"veryl_modules{project=\"test\",file=\"src/a.veryl\"} 1"));
+    assert!(lines.contains(&"# UNIT veryl_analyze_time_seconds seconds"));
+    assert!(lines.contains(
+        &"veryl_diagnostics{project=\"test\",file=\"src/a.veryl\",severity=\"warning\"} 1"
+    ));
+    assert!(lines.contains(
+        &"veryl_diagnostics{project=\"test\",file=\"src/a.veryl\",severity=\"error\"} 0"
+    ));
+    assert!(lines.contains(&"veryl_average_ports{project=\"test\"} 1"));
+    assert!(lines.contains(&"veryl_hierarchy_depth{project=\"test\"} 1"));
+    assert_eq!(lines.last(), Some(&"# EOF"));
+
+    // Each metric family has TYPE and HELP before its samples
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let name = line.split('{').next().unwrap();
+        let position = |x: &str| {
+            let header = format!("# {x} {name} ");
+            lines.iter().position(|y| y.starts_with(&header)).unwrap()
+        };
+        assert!(position("TYPE") < i, "{line}");
+        assert!(position("HELP") < i, "{line}");
+    }
+}
+
+#[test]
+fn equiv_changed_modules() {
+    let base = "module ModuleA (\n    i: input logic,\n    o: output logic,\n) {\n    assign o = i;\n}\n\nmodule ModuleB {}\n";
+    let head = "module ModuleA (\n    i: input logic,\n    o: output logic,\n) {\n    assign o = ~~i;\n}\n\nmodule ModuleB {}\n";
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", base)]);
+    let path = tempdir.path();
+    commit_all(path, "base");
+    fs::write(path.join("src/a.veryl"), head).unwrap();
+
+    let equiv = |modules: &[&str], tool| {
+        let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+        let opt = OptEquiv {
+            base: "HEAD".to_string(),
+            modules: modules.iter().map(|x| x.to_string()).collect(),
+            tool,
+            output: PathBuf::from("target/equiv"),
+            run: false,
+        };
+        CmdEquiv::new(opt).exec(&mut metadata)
+    };
+    let output = path.join("target/equiv");
+
+    // Only changed modules are checked by default
+    assert!(equiv(&[], EquivTool::Yosys).unwrap());
+    let script = fs::read_to_string(output.join("test_ModuleA.ys")).unwrap();
+    assert!(script
+        .starts_with("# Equivalence check of test_ModuleA\n\nread_verilog -sv gold/test_a.sv\n"));
+    assert!(script.contains("equiv_make gold gate equiv\n"));
+    assert!(!output.join("test_ModuleB.ys").exists());
+    let gold = fs::read_to_string(output.join("gold/test_a.sv")).unwrap();
+    let gate = fs::read_to_string(output.join("gate/test_a.sv")).unwrap();
+    assert!(gold.contains("always_comb o = i;"));
+    assert!(gate.contains("always_comb o = ~~i;"));
+
+    // Specified modules are checked even if unchanged
+    assert!(equiv(&["ModuleB"], EquivTool::Eqy).unwrap());
+    let script = fs::read_to_string(output.join("test_ModuleB.eqy")).unwrap();
+    assert!(script.starts_with("[gold]\n"));
+    assert!(script.contains(&format!(
+        "read_verilog -sv {}\n",
+        output.join("gate/test_a.sv").to_string_lossy()
+    )));
+
+    let err = equiv(&["ModuleC"], EquivTool::Yosys).unwrap_err();
+    assert!(err.to_string().contains("module \"ModuleC\" is not found"));
+}
+
+#[test]
+fn probes_hierarchy() {
+    let code = r#"module Top (
+    i: input  logic,
+    o: output logic,
+) {
+    var state: logic;
+    assign state = i;
+
+    inst u_a: ModuleA (
+        i: state,
+        o,
+    );
+}
+
+module ModuleA (
+    i: input  logic,
+    o: output logic,
+) {
+    var state: logic;
+    assign state = i;
+    assign o     = state;
+}
+"#;
+    let tempdir = create_project(SOURCE_TOML, &[("src/top.veryl", code)]);
+    let path = tempdir.path();
+
+    let probes = |patterns: &[&str], format| {
+        let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+        Analyzer::new(&metadata).clear();
+        let output = path.join("probes.txt");
+        let opt = OptProbes {
+            top: "Top".to_string(),
+            patterns: patterns.iter().map(|x| x.to_string()).collect(),
+            format,
+            output: Some(output.clone()),
+        };
+        CmdProbes::new(opt).exec(&mut metadata)?;
+        Ok::<_, miette::Report>(fs::read_to_string(output).unwrap())
+    };
+
+    assert_eq!(
+        probes(&[], ProbesFormat::List).unwrap(),
+        "test_Top.i\ntest_Top.o\ntest_Top.state\ntest_Top.u_a.i\ntest_Top.u_a.o\ntest_Top.u_a.state\n"
+    );
+    assert_eq!(
+        probes(&["*.state"], ProbesFormat::Xdc).unwrap(),
+        "set_property MARK_DEBUG true [get_nets -hierarchical {state}]\nset_property MARK_DEBUG true [get_nets -hierarchical {u_a/state}]\n"
+    );
+    assert_eq!(
+        probes(&["*.u_a.*"], ProbesFormat::Force).unwrap(),
+        "force test_Top.u_a.i = '0;\nforce test_Top.u_a.o = '0;\nforce test_Top.u_a.state = '0;\n"
+    );
+
+    let err = probes(&["*.missing"], ProbesFormat::List).unwrap_err();
+    assert!(err.to_string().contains("no signal matches"));
+}
+
+#[test]
+fn grep_symbols() {
+    let code = r#"module ModuleA (
+    i_data: input  logic,
+    o_data: output logic,
+) {
+    var r_data: logic;
+    assign r_data = i_data;
+    assign o_data = r_data;
+}
+"#;
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", code)]);
+    let path = tempdir.path();
+
+    let grep = |pattern: &str, kinds: Vec<GrepKind>, direction, scope| {
+        let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+        Analyzer::new(&metadata).clear();
+        let opt = OptGrep {
+            pattern: pattern.to_string(),
+            files: vec![],
+            kinds,
+            direction,
+            scope,
+        };
+        let prefix = format!("{}:", path.join("src/a.veryl").to_string_lossy());
+        CmdGrep::new(opt)
+            .search(&mut metadata)
+            .unwrap()
+            .into_iter()
+            .map(|x| x.strip_prefix(&prefix).unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        grep("_data$", vec![], None, GrepScope::All),
+        vec![
+            "2:5: declaration port test::ModuleA::i_data",
+            "3:5: declaration port test::ModuleA::o_data",
+            "5:9: declaration variable test::ModuleA::r_data",
+            "6:12: reference variable test::ModuleA::r_data",
+            "6:21: reference port test::ModuleA::i_data",
+            "7:12: reference port test::ModuleA::o_data",
+            "7:21: reference variable test::ModuleA::r_data",
+        ]
+    );
+    assert_eq!(
+        grep(
+            "_data$",
+            vec![GrepKind::Port],
+            Some(GrepDirection::Output),
+            GrepScope::All
+        ),
+        vec![
+            "3:5: declaration port test::ModuleA::o_data",
+            "7:12: reference port test::ModuleA::o_data",
+        ]
+    );
+    assert_eq!(
+        grep("^r_", vec![], None, GrepScope::References),
+        vec![
+            "6:12: reference variable test::ModuleA::r_data",
+            "7:21: reference variable test::ModuleA::r_data",
+        ]
+    );
+    assert_eq!(
+        grep(
+            "Module",
+            vec![GrepKind::Module],
+            None,
+            GrepScope::Declarations
+        ),
+        vec!["1:8: declaration module test::ModuleA"]
+    );
+    assert!(grep("missing", vec![], None, GrepScope::All).is_empty());
+}
+
+#[test]
+fn repl_session() {
+    let code = r#"package PackageA {
+    const WIDTH: u32 = 8;
+}
+
+module ModuleA #(
+    param N: u32 = PackageA::WIDTH * 2,
+) (
+    i: input logic<N>,
+) {}
+"#;
+    let tempdir = create_project(SOURCE_TOML, &[("src/a.veryl", code)]);
+    let path = tempdir.path();
+    let mut metadata = Metadata::load(path.join("Veryl.toml")).unwrap();
+
+    let input = [
+        "PackageA::WIDTH + 1",
+        ":type PackageA::WIDTH",
+        ":scope ModuleA",
+        "N",
+        ":type i",
+        ":resolve i",
+        ":scope i",
+        ":scope",
+        "N",
+        ":foo",
+        ":quit",
+        "1",
+    ];
+    let mut output = Vec::new();
+    let repl = CmdRepl::new(OptRepl { scope: None });
+    let ret = repl.run(&mut metadata, &mut input.join("\n").as_bytes(), &mut output);
+    assert!(ret.unwrap());
+
+    let output = String::from_utf8(output).unwrap();
+    let output = output.replace(&path.to_string_lossy().to_string(), "<prj>");
+    assert_eq!(
+        output,
+        concat!(
+            "test> 9 (width: 32)\n",
+            "test> u32 (width: 32)\n",
+            "test> test::ModuleA> 16 (width: 32)\n",
+            "test::ModuleA> logic<N> (width: 16)\n",
+            "test::ModuleA> port test::ModuleA::i (<prj>/src/a.veryl:8:5)\n",
+            "    type: logic<N>\n",
+            "test::ModuleA> port `i` can't be a scope\n",
+            // `N` is not visible in the project root
+            "test::ModuleA> test> unknown\n",
+            "test> unknown command `:foo` (see `:help`)\n",
+            // Lines after `:quit` are ignored
+            "test> ",
+        )
+    );
+}
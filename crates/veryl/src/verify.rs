@@ -0,0 +1,341 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("line {line}: {message}")]
+pub struct VerifyError {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    Word,
+    Directive,
+    Text,
+    Symbol,
+}
+
+struct Lexeme<'a> {
+    kind: Kind,
+    text: &'a str,
+    line: usize,
+}
+
+/// Pairs of keywords opening and closing a block
+const BLOCKS: &[(&str, &[&str])] = &[
+    ("begin", &["end"]),
+    ("module", &["endmodule"]),
+    ("macromodule", &["endmodule"]),
+    ("interface", &["endinterface"]),
+    ("package", &["endpackage"]),
+    ("program", &["endprogram"]),
+    ("class", &["endclass"]),
+    ("function", &["endfunction"]),
+    ("task", &["endtask"]),
+    ("case", &["endcase"]),
+    ("casex", &["endcase"]),
+    ("casez", &["endcase"]),
+    ("randcase", &["endcase"]),
+    ("generate", &["endgenerate"]),
+    ("fork", &["join", "join_any", "join_none"]),
+    ("clocking", &["endclocking"]),
+    ("covergroup", &["endgroup"]),
+    ("property", &["endproperty"]),
+    ("sequence", &["endsequence"]),
+    ("checker", &["endchecker"]),
+    ("specify", &["endspecify"]),
+    ("primitive", &["endprimitive"]),
+    ("table", &["endtable"]),
+    ("config", &["endconfig"]),
+];
+
+const DIRECTIVES: &[(&str, &[&str])] = &[
+    ("`ifdef", &["`endif"]),
+    ("`ifndef", &["`endif"]),
+    ("`celldefine", &["`endcelldefine"]),
+    ("`protect", &["`endprotect"]),
+];
+
+fn closers(text: &str) -> Option<&'static [&'static str]> {
+    BLOCKS
+        .iter()
+        .chain(DIRECTIVES)
+        .find(|(open, _)| *open == text)
+        .map(|(_, close)| *close)
+}
+
+fn is_closer(text: &str) -> bool {
+    BLOCKS
+        .iter()
+        .chain(DIRECTIVES)
+        .any(|(_, close)| close.contains(&text))
+}
+
+fn lex(text: &str) -> Result<Vec<Lexeme<'_>>, VerifyError> {
+    let bytes = text.as_bytes();
+    let mut ret = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    let is_word = |x: u8| x.is_ascii_alphanumeric() || x == b'_' || x == b'$';
+
+    while i < bytes.len() {
+        let start = i;
+        let start_line = line;
+        let c = bytes[i];
+
+        if c == b'\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_ascii_whitespace() {
+            i += 1;
+        } else if text[i..].starts_with("//") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+        } else if text[i..].starts_with("/*") || text[i..].starts_with("(*") {
+            let end = if c == b'/' { "*/" } else { "*)" };
+            // `(*)` is a wildcard in sensitivity list like `@(*)`
+            if text[i..].starts_with("(*)") {
+                ret.push(Lexeme {
+                    kind: Kind::Symbol,
+                    text: "(",
+                    line,
+                });
+                ret.push(Lexeme {
+                    kind: Kind::Symbol,
+                    text: ")",
+                    line,
+                });
+                i += 3;
+                continue;
+            }
+            match text[i + 2..].find(end) {
+                Some(x) => {
+                    line += text[i..i + 2 + x].matches('\n').count();
+                    i += x + 4;
+                }
+                None => {
+                    return Err(VerifyError {
+                        line,
+                        message: format!("\"{}\" is not closed", &text[i..i + 2]),
+                    })
+                }
+            }
+        } else if c == b'"' {
+            i += 1;
+            loop {
+                match bytes.get(i) {
+                    Some(b'\\') => i += 2,
+                    Some(b'"') => break,
+                    Some(b'\n') | None => {
+                        return Err(VerifyError {
+                            line: start_line,
+                            message: "string literal is not closed".to_string(),
+                        })
+                    }
+                    Some(_) => i += 1,
+                }
+            }
+            i += 1;
+            ret.push(Lexeme {
+                kind: Kind::Text,
+                text: &text[start..i],
+                line: start_line,
+            });
+        } else if c == b'`' {
+            i += 1;
+            while i < bytes.len() && is_word(bytes[i]) {
+                i += 1;
+            }
+            ret.push(Lexeme {
+                kind: Kind::Directive,
+                text: &text[start..i],
+                line,
+            });
+        } else if c == b'\\' {
+            // escaped identifier is terminated by white space
+            while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            ret.push(Lexeme {
+                kind: Kind::Word,
+                text: &text[start..i],
+                line,
+            });
+        } else if c == b'\'' {
+            // based literal like `'h0f`, or cast and assignment pattern like `'{`
+            i += 1;
+            while i < bytes.len() && (is_word(bytes[i]) || bytes[i] == b'?') {
+                i += 1;
+            }
+            ret.push(Lexeme {
+                kind: Kind::Text,
+                text: &text[start..i],
+                line,
+            });
+        } else if is_word(c) {
+            while i < bytes.len() && is_word(bytes[i]) {
+                i += 1;
+            }
+            // `32'h0` is a single literal
+            if c.is_ascii_digit() && bytes.get(i) == Some(&b'\'') {
+                i += 1;
+                while i < bytes.len() && (is_word(bytes[i]) || bytes[i] == b'?') {
+                    i += 1;
+                }
+            }
+            let kind = if c.is_ascii_digit() {
+                Kind::Text
+            } else {
+                Kind::Word
+            };
+            ret.push(Lexeme {
+                kind,
+                text: &text[start..i],
+                line,
+            });
+        } else {
+            let len = text[i..].chars().next().map(|x| x.len_utf8()).unwrap_or(1);
+            i += len;
+            ret.push(Lexeme {
+                kind: Kind::Symbol,
+                text: &text[start..i],
+                line,
+            });
+        }
+    }
+
+    Ok(ret)
+}
+
+/// Whether the block keyword at `i` doesn't open a block like prototype declaration
+fn is_prototype(lexemes: &[Lexeme], i: usize) -> bool {
+    let prev = |n: usize| {
+        i.checked_sub(n)
+            .map(|x| lexemes[x].text)
+            .unwrap_or_default()
+    };
+    let next = lexemes.get(i + 1).map(|x| x.text).unwrap_or_default();
+    match lexemes[i].text {
+        "function" | "task" => {
+            // `extern function`, `pure virtual function` and `import "DPI-C" function`
+            prev(1) == "extern"
+                || prev(2) == "extern"
+                || prev(2) == "pure"
+                || prev(2) == "import"
+                || prev(3) == "import"
+        }
+        // `typedef class C;`
+        "class" => prev(1) == "typedef",
+        // `wait fork` and `disable fork`
+        "fork" => matches!(prev(1), "wait" | "disable"),
+        // generic interface port, `virtual interface` in declaration,
+        // and `interface class` closed by `endclass`
+        "interface" => matches!(prev(1), "(" | "," | "virtual" | "typedef") || next == "class",
+        // concurrent assertion like `assert property`, and formal argument type
+        "property" | "sequence" => matches!(
+            prev(1),
+            "(" | "," | "assert" | "assume" | "cover" | "restrict" | "expect"
+        ),
+        _ => false,
+    }
+}
+
+/// Check that emitted SystemVerilog is structurally well-formed.
+/// This checks lexical structure and nesting of brackets, blocks and directives only.
+/// Grammar of statements and expressions, and semantics are not validated.
+pub fn verify(text: &str) -> Vec<VerifyError> {
+    let lexemes = match lex(text) {
+        Ok(x) => x,
+        Err(x) => return vec![x],
+    };
+
+    let mut ret = Vec::new();
+    let mut stack: Vec<(&str, &[&str], usize)> = Vec::new();
+
+    for (i, lexeme) in lexemes.iter().enumerate() {
+        let text = lexeme.text;
+        match lexeme.kind {
+            Kind::Symbol => match text {
+                "(" | "[" | "{" => {
+                    let close: &[&str] = match text {
+                        "(" => &[")"],
+                        "[" => &["]"],
+                        _ => &["}"],
+                    };
+                    stack.push((text, close, lexeme.line));
+                }
+                ")" | "]" | "}" => match stack.last() {
+                    Some((_, close, _)) if close.contains(&text) => {
+                        stack.pop();
+                    }
+                    Some((open, _, line)) => ret.push(VerifyError {
+                        line: lexeme.line,
+                        message: format!("\"{text}\" is unexpected in \"{open}\" at line {line}"),
+                    }),
+                    None => ret.push(VerifyError {
+                        line: lexeme.line,
+                        message: format!("\"{text}\" has no opening"),
+                    }),
+                },
+                _ => (),
+            },
+            Kind::Word | Kind::Directive => {
+                if let Some(close) = closers(text) {
+                    if !is_prototype(&lexemes, i) {
+                        stack.push((text, close, lexeme.line));
+                    }
+                } else if is_closer(text)
+                    || (lexeme.kind == Kind::Directive && matches!(text, "`else" | "`elsif"))
+                {
+                    let is_else = matches!(text, "`else" | "`elsif");
+                    // brackets can't be across blocks
+                    while let Some((open, _, line)) = stack.last() {
+                        if !matches!(*open, "(" | "[" | "{") {
+                            break;
+                        }
+                        ret.push(VerifyError {
+                            line: *line,
+                            message: format!("\"{open}\" is not closed"),
+                        });
+                        stack.pop();
+                    }
+                    match stack.last() {
+                        Some((_, close, _)) if is_else && close.contains(&"`endif") => (),
+                        Some((_, close, _)) if close.contains(&text) => {
+                            stack.pop();
+                        }
+                        Some((open, close, line)) => {
+                            ret.push(VerifyError {
+                                line: lexeme.line,
+                                message: format!(
+                                    "\"{text}\" is unexpected, \"{open}\" at line {line} should be closed by \"{}\"",
+                                    close[0]
+                                ),
+                            });
+                            // recover if the closer matches an outer block
+                            if let Some(x) = stack.iter().rposition(|(_, x, _)| x.contains(&text)) {
+                                stack.truncate(x);
+                            }
+                        }
+                        None => ret.push(VerifyError {
+                            line: lexeme.line,
+                            message: format!("\"{text}\" has no opening"),
+                        }),
+                    }
+                }
+            }
+            Kind::Text => (),
+        }
+    }
+
+    for (open, close, line) in stack {
+        ret.push(VerifyError {
+            line,
+            message: format!("\"{open}\" is not closed by \"{}\"", close[0]),
+        });
+    }
+
+    ret
+}
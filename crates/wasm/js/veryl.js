@@ -0,0 +1,44 @@
+// JavaScript API of `veryl_wasm.wasm` built by
+//   cargo build -p veryl-wasm --features wasm --target wasm32-unknown-unknown --release
+//
+// const veryl = await Veryl.load(fetch("veryl_wasm.wasm"));
+// const { err, code } = veryl.emit(source);
+
+export class Veryl {
+  static async load(source) {
+    const { instance } = await WebAssembly.instantiateStreaming(source, {});
+    return new Veryl(instance.exports);
+  }
+
+  constructor(exports) {
+    this.exports = exports;
+  }
+
+  format(src) {
+    return this.call(this.exports.format, src);
+  }
+
+  check(src) {
+    return this.call(this.exports.check, src);
+  }
+
+  emit(src) {
+    return this.call(this.exports.emit, src);
+  }
+
+  call(func, src) {
+    const input = new TextEncoder().encode(src);
+    const ptr = this.exports.alloc(input.length);
+    new Uint8Array(this.exports.memory.buffer, ptr, input.length).set(input);
+
+    const ret = func(ptr, input.length);
+    const view = new DataView(this.exports.memory.buffer, ret);
+    const err = view.getUint8(0) !== 0;
+    const len = view.getUint32(1, true);
+    const code = new TextDecoder().decode(
+      new Uint8Array(this.exports.memory.buffer, ret + 5, len)
+    );
+    this.exports.dealloc(ret, 5 + len);
+    return { err, code };
+  }
+}
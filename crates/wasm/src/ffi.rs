@@ -0,0 +1,59 @@
+//! Functions exported to JavaScript.
+//!
+//! Strings are passed through the linear memory as UTF-8 bytes.
+//! Input buffer is allocated by `alloc` and taken by the exported function.
+//! Output buffer is `[err: u8, len: u32 (little endian), code: [u8; len]]`
+//! and must be released by `dealloc` with `5 + len`.
+
+use crate::Output;
+
+#[no_mangle]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    Box::into_raw(vec![0u8; len].into_boxed_slice()) as *mut u8
+}
+
+/// # Safety
+///
+/// `ptr` must be allocated by `alloc` or returned by the exported function with `len`.
+#[no_mangle]
+pub unsafe extern "C" fn dealloc(ptr: *mut u8, len: usize) {
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
+}
+
+unsafe fn call(ptr: *mut u8, len: usize, func: fn(&str) -> Output) -> *mut u8 {
+    let input = Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len));
+    let output = match std::str::from_utf8(&input) {
+        Ok(x) => func(x),
+        Err(x) => Output::err(x.to_string()),
+    };
+
+    let mut ret = Vec::with_capacity(5 + output.code.len());
+    ret.push(output.err as u8);
+    ret.extend_from_slice(&(output.code.len() as u32).to_le_bytes());
+    ret.extend_from_slice(output.code.as_bytes());
+    Box::into_raw(ret.into_boxed_slice()) as *mut u8
+}
+
+/// # Safety
+///
+/// `ptr` must be allocated by `alloc` with `len`.
+#[export_name = "format"]
+pub unsafe extern "C" fn format(ptr: *mut u8, len: usize) -> *mut u8 {
+    call(ptr, len, crate::format)
+}
+
+/// # Safety
+///
+/// `ptr` must be allocated by `alloc` with `len`.
+#[export_name = "check"]
+pub unsafe extern "C" fn check(ptr: *mut u8, len: usize) -> *mut u8 {
+    call(ptr, len, crate::check)
+}
+
+/// # Safety
+///
+/// `ptr` must be allocated by `alloc` with `len`.
+#[export_name = "emit"]
+pub unsafe extern "C" fn emit(ptr: *mut u8, len: usize) -> *mut u8 {
+    call(ptr, len, crate::emit)
+}
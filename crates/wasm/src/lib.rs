@@ -0,0 +1,122 @@
+//! Single-file API of Veryl for browser playground and in-browser editors.
+//!
+//! With `wasm` feature, `format`, `check` and `emit` are exported to JavaScript.
+//! `js/veryl.js` wraps them so that they can be called with JavaScript strings.
+
+#[cfg(feature = "wasm")]
+mod ffi;
+#[cfg(test)]
+mod tests;
+
+use miette::{GraphicalReportHandler, GraphicalTheme, Severity};
+use std::path::PathBuf;
+use veryl_analyzer::Analyzer;
+use veryl_emitter::Emitter;
+use veryl_formatter::Formatter;
+use veryl_metadata::{Metadata, SourceMapTarget};
+use veryl_parser::Parser;
+
+/// Name of the project which the source belongs to
+const PROJECT_NAME: &str = "project";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Output {
+    /// Whether `code` is error messages
+    pub err: bool,
+    pub code: String,
+}
+
+impl Output {
+    fn ok(code: String) -> Self {
+        Self { err: false, code }
+    }
+
+    fn err(code: String) -> Self {
+        Self { err: true, code }
+    }
+}
+
+fn render_err(err: miette::ErrReport) -> String {
+    let mut out = String::new();
+    GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+        .with_width(80)
+        .render_report(&mut out, err.as_ref())
+        .unwrap();
+    out
+}
+
+fn metadata() -> Metadata {
+    let mut metadata: Metadata = Metadata::create_default_toml(PROJECT_NAME)
+        .unwrap()
+        .parse()
+        .unwrap();
+    metadata.build.sourcemap_target = SourceMapTarget::None;
+    metadata
+}
+
+/// Analyzes `source` and returns the rendered diagnostics and whether it contains errors
+fn analyze(metadata: &Metadata, source: &str, parser: &Parser) -> (String, bool) {
+    let analyzer = Analyzer::new(metadata);
+    analyzer.clear();
+
+    let mut errors = vec![];
+    errors.append(&mut analyzer.analyze_pass1(PROJECT_NAME, source, "", &parser.veryl));
+    Analyzer::analyze_post_pass1();
+    errors.append(&mut analyzer.analyze_pass2(PROJECT_NAME, source, "", &parser.veryl));
+    errors.append(&mut analyzer.analyze_pass3(PROJECT_NAME, source, "", &parser.veryl));
+
+    let mut ret = String::new();
+    let mut has_error = false;
+    for error in errors {
+        has_error |= error.severity_with(&metadata.lint) == Severity::Error;
+        ret.push_str(&render_err(error.into()));
+    }
+    (ret, has_error)
+}
+
+/// Formats `source`
+pub fn format(source: &str) -> Output {
+    let metadata = metadata();
+    match Parser::parse(source, &"") {
+        Ok(parser) => {
+            let mut formatter = Formatter::new(&metadata);
+            formatter.format(&parser.veryl);
+            Output::ok(formatter.as_str().to_string())
+        }
+        Err(e) => Output::err(render_err(e.into())),
+    }
+}
+
+/// Checks `source` and returns the messages of errors and warnings
+pub fn check(source: &str) -> Output {
+    let metadata = metadata();
+    match Parser::parse(source, &"") {
+        Ok(parser) => {
+            let (code, err) = analyze(&metadata, source, &parser);
+            Output { err, code }
+        }
+        Err(e) => Output::err(render_err(e.into())),
+    }
+}
+
+/// Emits SystemVerilog of `source`
+pub fn emit(source: &str) -> Output {
+    let metadata = metadata();
+    match Parser::parse(source, &"") {
+        Ok(parser) => {
+            let (code, err) = analyze(&metadata, source, &parser);
+            if err {
+                return Output::err(code);
+            }
+            let mut emitter = Emitter::new(
+                &metadata,
+                &PathBuf::from("playground.veryl"),
+                &PathBuf::from("playground.sv"),
+                &PathBuf::from("playground.sv.map"),
+            );
+            emitter.emit(PROJECT_NAME, &parser.veryl);
+            Output::ok(emitter.as_str().to_string())
+        }
+        Err(e) => Output::err(render_err(e.into())),
+    }
+}
@@ -0,0 +1,32 @@
+use crate::*;
+
+#[test]
+fn format_source() {
+    let ret = format("module ModuleA{var a:logic;}");
+    assert!(!ret.err);
+    assert_eq!(ret.code, "module ModuleA {\n    var a: logic;\n}\n");
+
+    let ret = format("module ModuleA {");
+    assert!(ret.err);
+}
+
+#[test]
+fn check_source() {
+    let ret = check("module ModuleA {\n    assign a = 1;\n}\n");
+    assert!(ret.err);
+    assert!(ret.code.contains("undefined_identifier"));
+
+    let ret = check("module ModuleA {\n    let a: logic = 1;\n}\n");
+    assert!(!ret.err);
+    assert!(ret.code.contains("unused_variable"));
+}
+
+#[test]
+fn emit_source() {
+    let ret = emit("module ModuleA {\n    let a: logic = 1;\n}\n");
+    assert!(!ret.err);
+    assert_eq!(
+        ret.code,
+        "module project_ModuleA;\n    logic a;\n    always_comb a = 1;\nendmodule\n"
+    );
+}
@@ -18,7 +18,8 @@ module veryl_testcase_Module29 (
     end
 
     veryl_testcase_Module29 u0 (
-
+        .clk (),
+        .rst_n ()
 
     );
 endmodule
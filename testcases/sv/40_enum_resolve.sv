@@ -15,5 +15,8 @@ module veryl_testcase_Module40;
     ) ;
         return e == EnumA_member_a;
     endfunction
+
+    logic _b;
+    always_comb _b = is_a(a);
 endmodule
 //# sourceMappingURL=../map/testcases/sv/40_enum_resolve.sv.map